@@ -24,6 +24,12 @@ pub struct GameInfo {
 
     /// Last modification time of the prefix directory
     modified: SystemTime,
+
+    /// Size of the installed game files from the manifest, in bytes
+    size_on_disk: u64,
+
+    /// Directory below `steamapps/common` the game is installed in
+    install_dir: Option<String>,
 }
 
 impl GameInfo {
@@ -56,9 +62,18 @@ impl GameInfo {
             has_manifest,
             last_played,
             modified,
+            size_on_disk: 0,
+            install_dir: None,
         })
     }
 
+    /// Sets the install size and directory read from the manifest.
+    pub fn with_install(mut self, size_on_disk: u64, install_dir: Option<String>) -> Self {
+        self.size_on_disk = size_on_disk;
+        self.install_dir = install_dir;
+        self
+    }
+
     /// Gets the AppID of the game.
     pub fn app_id(&self) -> u32 {
         self.app_id
@@ -89,6 +104,16 @@ impl GameInfo {
         self.modified
     }
 
+    /// Gets the size of the installed game files in bytes, 0 if unknown.
+    pub fn size_on_disk(&self) -> u64 {
+        self.size_on_disk
+    }
+
+    /// Gets the install directory name below `steamapps/common`.
+    pub fn install_dir(&self) -> Option<&str> {
+        self.install_dir.as_deref()
+    }
+
     /// Checks if the Proton prefix exists.
     #[cfg_attr(test, allow(dead_code))]
     pub fn prefix_exists(&self) -> bool {
@@ -171,6 +196,11 @@ mod tests {
         assert_eq!(game.name(), "Test Game");
         assert_eq!(game.prefix_path(), &prefix_path);
         assert!(game.has_manifest());
+        assert_eq!(game.size_on_disk(), 0);
+
+        let game = game.with_install(42, Some("TestGame".to_string()));
+        assert_eq!(game.size_on_disk(), 42);
+        assert_eq!(game.install_dir(), Some("TestGame"));
 
         // Test invalid AppID
         assert!(GameInfo::new(0, "Test Game".to_string(), prefix_path.clone(), true, 0).is_err());
@@ -5,9 +5,10 @@
 
 use crate::core::models::{GameInfo, SteamLibrary};
 use crate::error::{Error, Result};
-use crate::utils::{library, user_config};
+use crate::utils::{binary_vdf, library, steam_paths, user_config};
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -25,15 +26,56 @@ struct ManifestCache {
 // Global caches with mutex protection
 static LIBRARY_CACHE: Lazy<Mutex<Option<LibraryCache>>> = Lazy::new(|| Mutex::new(None));
 static MANIFEST_CACHE: Lazy<Mutex<Option<ManifestCache>>> = Lazy::new(|| Mutex::new(None));
+static APP_NAMES: Lazy<Mutex<Option<HashMap<u32, String>>>> = Lazy::new(|| Mutex::new(None));
 
-#[cfg(test)]
+/// Drops the cached libraries, games and Steam config files so the next
+/// call reads them from disk again.
 pub fn clear_caches() {
     *LIBRARY_CACHE.lock().unwrap() = None;
     *MANIFEST_CACHE.lock().unwrap() = None;
+    *APP_NAMES.lock().unwrap() = None;
     library::clear_manifest_cache();
     user_config::clear_localconfig_cache();
 }
 
+/// Names Steam knows for apps without an appmanifest: apps in
+/// `appcache/appinfo.vdf` and non-Steam shortcuts.
+fn load_app_names() -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for base in steam_paths::steam_base_dirs() {
+        let path = base.join("appcache/appinfo.vdf");
+        if !path.is_file() {
+            continue;
+        }
+        match binary_vdf::appinfo_names(&path) {
+            Ok(found) => names.extend(found),
+            Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+        }
+    }
+    for shortcut in binary_vdf::all_shortcuts() {
+        if !shortcut.name.is_empty() {
+            names.insert(shortcut.appid, shortcut.name);
+        }
+    }
+    names
+}
+
+/// Name of `appid` from Steam's app cache or the user's non-Steam
+/// shortcuts, for prefixes whose appmanifest is missing.
+pub fn app_name(appid: u32) -> Option<String> {
+    let mut cache = APP_NAMES.lock().unwrap();
+    cache.get_or_insert_with(load_app_names).get(&appid).cloned()
+}
+
+/// Steam's `libraryfolders.vdf`, which lists the libraries and the games
+/// installed in each.
+pub fn libraryfolders_path() -> Option<PathBuf> {
+    steam_paths::config_dirs()
+        .into_iter()
+        .map(|dir| dir.join("libraryfolders.vdf"))
+        .find(|path| path.exists())
+}
+
 /// Gets a list of Steam library folders with caching.
 ///
 /// # Returns
@@ -55,17 +97,8 @@ pub fn get_steam_libraries() -> Result<Vec<SteamLibrary>> {
     }
 
     // Cache invalid or empty, fetch fresh data
-    let mut vdf_path = None;
-    for dir in crate::utils::steam_paths::config_dirs() {
-        let candidate = dir.join("libraryfolders.vdf");
-        if candidate.exists() {
-            vdf_path = Some(candidate);
-            break;
-        }
-    }
-
-    let vdf_path =
-        vdf_path.ok_or_else(|| Error::SteamConfigNotFound(PathBuf::from("libraryfolders.vdf")))?;
+    let vdf_path = libraryfolders_path()
+        .ok_or_else(|| Error::SteamConfigNotFound(PathBuf::from("libraryfolders.vdf")))?;
 
     let vdf_path_str = vdf_path
         .to_str()
@@ -114,6 +147,19 @@ pub fn find_proton_prefix(appid: u32, libraries: &[SteamLibrary]) -> Option<Path
     None
 }
 
+/// Returns the compatdata directory of a game, or where Steam would create
+/// it: next to the game's appmanifest.
+pub fn compatdata_dir(appid: u32) -> Result<PathBuf> {
+    let libraries = get_steam_libraries()?;
+    if let Some(prefix) = find_proton_prefix(appid, &libraries) {
+        return Ok(prefix);
+    }
+    find_manifest(appid)
+        .and_then(|m| m.parent().map(|steamapps| steamapps.join("compatdata")))
+        .map(|dir| dir.join(appid.to_string()))
+        .ok_or_else(|| Error::InvalidManifest(format!("appmanifest for {} not found", appid)))
+}
+
 /// Finds the Steam userdata directory for a specific AppID.
 ///
 /// This uses the active Steam user's `localconfig.vdf` location to
@@ -131,6 +177,37 @@ pub fn find_userdata_dir(appid: u32) -> Option<PathBuf> {
     None
 }
 
+/// Finds the `appmanifest_<appid>.acf` file in any Steam library.
+pub fn find_manifest(appid: u32) -> Option<PathBuf> {
+    get_steam_libraries()
+        .ok()?
+        .into_iter()
+        .map(|lib| {
+            lib.steamapps_path()
+                .join(format!("appmanifest_{}.acf", appid))
+        })
+        .find(|manifest| manifest.exists())
+}
+
+/// Finds the install directory of a game from its appmanifest.
+///
+/// Returns `Some(PathBuf)` pointing into `steamapps/common` if a manifest
+/// with an `installdir` entry exists in any library.
+pub fn find_install_dir(appid: u32) -> Option<PathBuf> {
+    let libraries = get_steam_libraries().ok()?;
+    for lib in libraries {
+        let manifest = lib
+            .steamapps_path()
+            .join(format!("appmanifest_{}.acf", appid));
+        if manifest.exists() {
+            if let Some((_, installdir)) = library::parse_appmanifest_installdir(&manifest) {
+                return Some(lib.steamapps_path().join("common").join(installdir));
+            }
+        }
+    }
+    None
+}
+
 /// Searches for games by name.
 ///
 /// # Arguments
@@ -159,10 +236,9 @@ pub fn search_games(name: &str) -> Result<Vec<GameInfo>> {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("acf") {
-                    if let Some((appid, game_name, last_played)) = library::parse_appmanifest(&path)
-                    {
-                        if game_name.to_lowercase().contains(&name.to_lowercase()) {
-                            matching_games.push((appid, game_name, last_played));
+                    if let Some(manifest) = library::parse_appmanifest(&path) {
+                        if manifest.name.to_lowercase().contains(&name.to_lowercase()) {
+                            matching_games.push(manifest);
                         }
                     }
                 }
@@ -171,9 +247,9 @@ pub fn search_games(name: &str) -> Result<Vec<GameInfo>> {
     }
 
     // Then find prefixes for all matching games
-    for (appid, game_name, last_played) in matching_games {
-        if let Some(prefix_path) = find_proton_prefix(appid, &libraries) {
-            if let Ok(game_info) = GameInfo::new(appid, game_name, prefix_path, true, last_played) {
+    for manifest in matching_games {
+        if let Some(prefix_path) = find_proton_prefix(manifest.appid, &libraries) {
+            if let Ok(game_info) = game_from_manifest(manifest, prefix_path) {
                 results.push(game_info);
             }
         }
@@ -182,6 +258,18 @@ pub fn search_games(name: &str) -> Result<Vec<GameInfo>> {
     Ok(results)
 }
 
+/// Builds the GameInfo of an installed game from its manifest.
+fn game_from_manifest(manifest: library::AppManifest, prefix_path: PathBuf) -> Result<GameInfo> {
+    Ok(GameInfo::new(
+        manifest.appid,
+        manifest.name,
+        prefix_path,
+        true,
+        manifest.last_played,
+    )?
+    .with_install(manifest.size_on_disk, manifest.installdir))
+}
+
 /// Loads games from a single library
 fn load_games_from_library(library: &SteamLibrary) -> Result<Vec<GameInfo>> {
     let mut games = Vec::new();
@@ -193,12 +281,10 @@ fn load_games_from_library(library: &SteamLibrary) -> Result<Vec<GameInfo>> {
             let path = entry.path();
             if let Some(fname) = path.file_name().and_then(|n| n.to_str()) {
                 if fname.starts_with("appmanifest_") && fname.ends_with(".acf") {
-                    if let Some((appid, game_name, last_played)) = library::parse_appmanifest(&path)
-                    {
-                        let prefix_path = library.compatdata_path().join(appid.to_string());
-                        if let Ok(game_info) =
-                            GameInfo::new(appid, game_name, prefix_path, true, last_played)
-                        {
+                    if let Some(manifest) = library::parse_appmanifest(&path) {
+                        let prefix_path =
+                            library.compatdata_path().join(manifest.appid.to_string());
+                        if let Ok(game_info) = game_from_manifest(manifest, prefix_path) {
                             games.push(game_info);
                         }
                     }
@@ -217,7 +303,7 @@ fn load_games_from_library(library: &SteamLibrary) -> Result<Vec<GameInfo>> {
                     let prefix_path = c.path();
                     if let Ok(game_info) = GameInfo::new(
                         appid,
-                        format!("App {}", appid),
+                        app_name(appid).unwrap_or_else(|| format!("App {}", appid)),
                         prefix_path,
                         false,
                         0, // No manifest means no last played time
@@ -270,20 +356,14 @@ pub fn refresh_game_info(app_id: u32) -> Result<GameInfo> {
     let libraries = get_steam_libraries()?;
 
     let mut prefix_path = None;
-    let mut name = None;
-    let mut last_played = 0;
-    let mut has_manifest = false;
+    let mut found = None;
 
     for lib in &libraries {
         let manifest = lib
             .steamapps_path()
             .join(format!("appmanifest_{}.acf", app_id));
         if manifest.exists() {
-            if let Some((_, game_name, lp)) = library::parse_appmanifest(&manifest) {
-                name = Some(game_name);
-                last_played = lp;
-                has_manifest = true;
-            }
+            found = library::parse_appmanifest(&manifest);
             prefix_path = Some(lib.compatdata_path().join(app_id.to_string()));
             break;
         }
@@ -294,9 +374,12 @@ pub fn refresh_game_info(app_id: u32) -> Result<GameInfo> {
     }
 
     let prefix = prefix_path.ok_or(Error::InvalidAppId(app_id.to_string()))?;
-    let game_name = name.unwrap_or_else(|| format!("App {}", app_id));
+    if let Some(manifest) = found {
+        return game_from_manifest(manifest, prefix);
+    }
+    let game_name = app_name(app_id).unwrap_or_else(|| format!("App {}", app_id));
 
-    GameInfo::new(app_id, game_name, prefix, has_manifest, last_played)
+    GameInfo::new(app_id, game_name, prefix, false, 0)
 }
 
 #[cfg(test)]
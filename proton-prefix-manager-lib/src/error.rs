@@ -1,6 +1,6 @@
 use std::fmt;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 pub enum Error {
@@ -13,6 +13,7 @@ pub enum Error {
     LibraryNotFound(PathBuf),
     FileSystemError(String),
     PermissionDenied(PathBuf),
+    Network(String),
 }
 
 impl fmt::Display for Error {
@@ -38,6 +39,40 @@ impl fmt::Display for Error {
             Error::PermissionDenied(path) => {
                 write!(f, "Permission denied accessing: {}", path.display())
             }
+            Error::Network(msg) => write!(f, "Network error: {}", msg),
+        }
+    }
+}
+
+impl Error {
+    /// Stable identifier of the error kind for machine-readable output.
+    /// Codes are never renamed; new variants get new codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "io",
+            Error::Parse(_) => "parse",
+            Error::SteamNotFound => "steam_not_found",
+            Error::SteamConfigNotFound(_) => "steam_config_not_found",
+            Error::InvalidAppId(_) => "invalid_app_id",
+            Error::InvalidManifest(_) => "invalid_manifest",
+            Error::LibraryNotFound(_) => "library_not_found",
+            Error::FileSystemError(_) => "file_system",
+            Error::PermissionDenied(_) => "permission_denied",
+            Error::Network(_) => "network",
+        }
+    }
+
+    /// Path the error is about, if it names one.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::SteamConfigNotFound(path)
+            | Error::LibraryNotFound(path)
+            | Error::PermissionDenied(path)
+                if !path.as_os_str().is_empty() =>
+            {
+                Some(path)
+            }
+            _ => None,
         }
     }
 }
@@ -61,3 +96,20 @@ impl From<io::Error> for Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_paths() {
+        let err = Error::SteamConfigNotFound(PathBuf::from("/home/deck/.steam"));
+        assert_eq!(err.code(), "steam_config_not_found");
+        assert_eq!(err.path(), Some(Path::new("/home/deck/.steam")));
+
+        let err = Error::from(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(err.code(), "permission_denied");
+        assert_eq!(err.path(), None);
+        assert_eq!(Error::Network("offline".into()).path(), None);
+    }
+}
@@ -0,0 +1,32 @@
+//! Steam and Proton prefix logic shared by the `proton-prefix-manager` CLI
+//! and GUI.
+//!
+//! The crate has no UI dependencies and can be used by scripts or other
+//! front ends:
+//!
+//! - [`core::steam`] finds Steam libraries, installed games and their
+//!   Proton prefixes.
+//! - [`core::models`] holds [`GameInfo`](core::models::GameInfo) and
+//!   [`SteamLibrary`](core::models::SteamLibrary).
+//! - [`utils::backup`] creates, lists and restores prefix backups.
+//! - [`utils::user_config`] reads and writes per-game compatibility tools
+//!   and launch options in Steam's `localconfig.vdf`.
+//! - [`utils::prefix_info`] inspects a prefix and validates that Wine can
+//!   start in it.
+//!
+//! ```no_run
+//! use proton_prefix_manager_lib::core::steam;
+//!
+//! let libraries = steam::get_steam_libraries()?;
+//! for game in steam::load_games_from_libraries(&libraries)? {
+//!     println!("{} {}", game.app_id(), game.prefix_path().display());
+//! }
+//! # Ok::<(), proton_prefix_manager_lib::error::Error>(())
+//! ```
+
+pub mod core;
+pub mod error;
+pub mod utils;
+
+#[cfg(test)]
+mod test_helpers;
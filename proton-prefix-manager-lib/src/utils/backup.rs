@@ -0,0 +1,1237 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::os::unix::fs as unix_fs;
+
+use chrono::Local;
+use dirs_next;
+use rayon::prelude::*;
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::core::models::SteamLibrary;
+use crate::error::{Error, Result};
+use crate::utils::backup_format::{self, BackupType, Descriptor};
+use crate::utils::backup_manifest::{self, FileEntry};
+use crate::utils::prefix_info::{self, REGISTRY_FILES};
+use crate::utils::{overrides, reflink};
+
+/// Marker file identifying a backup that only holds save and config data.
+const SAVES_ONLY_MARKER: &str = ".saves-only";
+
+/// Directory inside a full backup holding the game's shader cache.
+const SHADER_CACHE_DIR: &str = ".shadercache";
+
+/// Files describing a backup that are not part of the prefix.
+const METADATA_FILES: [&str; 2] =
+    [backup_manifest::MANIFEST_FILE, backup_format::DESCRIPTOR_FILE];
+
+/// Most files copied at once. Prefixes hold tens of thousands of small
+/// files, so copying several at a time hides the per-file overhead, while
+/// more threads than this mostly compete for the same disk.
+const MAX_COPY_THREADS: usize = 8;
+
+/// Called with the bytes copied so far and the total bytes to copy.
+pub type Progress<'a> = &'a (dyn Fn(u64, u64) + Sync);
+
+/// A file to copy with its destination and size.
+type FileCopy = (PathBuf, PathBuf, u64);
+
+/// Create the directories and symlinks of `src` below `dst` and collect
+/// the files left to copy.
+fn prepare_copy(src: &Path, dst: &Path, files: &mut Vec<FileCopy>) -> Result<()> {
+    if !dst.exists() {
+        fs::create_dir_all(dst)?;
+    }
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let dest_path = dst.join(entry.file_name());
+        if file_type.is_dir() {
+            prepare_copy(&entry.path(), &dest_path, files)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            #[cfg(unix)]
+            unix_fs::symlink(&target, &dest_path)?;
+            #[cfg(not(unix))]
+            fs::copy(target, dest_path)?;
+        } else {
+            files.push((entry.path(), dest_path, entry.metadata()?.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Give the copy `to` the modification time of `from`, as `cp -p` does, so
+/// that a delta restore can tell unchanged files from their size and time.
+fn keep_modified(from: &Path, to: &Path) -> std::io::Result<()> {
+    let modified = fs::metadata(from)?.modified()?;
+    // Setting the times only takes owning the file, not write access.
+    fs::File::open(to)?.set_modified(modified)
+}
+
+/// Copy a directory tree, recreating symlinks instead of following them.
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+    copy_dir_with_progress(src, dst, &|_, _| {})
+}
+
+/// Copy a directory tree like [`copy_dir_recursive`], copying files in
+/// parallel and reporting the bytes copied after each file. Files are
+/// cloned instead when both trees are on the same copy-on-write
+/// filesystem. Copies keep the modification time of the original.
+pub fn copy_dir_with_progress(src: &Path, dst: &Path, progress: Progress) -> Result<()> {
+    let mut files = Vec::new();
+    prepare_copy(src, dst, &mut files)?;
+    let total = files.iter().map(|(_, _, size)| size).sum();
+    let copied = AtomicU64::new(0);
+    let clone = AtomicBool::new(reflink::supported(src, dst));
+    let copy = |(from, to, size): &FileCopy| -> Result<()> {
+        unlink(to)?;
+        let cloned = clone.load(Ordering::Relaxed)
+            && match reflink::clone_file(from, to) {
+                Ok(()) => true,
+                Err(e) => {
+                    if clone.swap(false, Ordering::Relaxed) {
+                        log::info!("Copying instead of cloning into {}: {}", dst.display(), e);
+                    }
+                    false
+                }
+            };
+        if !cloned {
+            fs::copy(from, to)?;
+        }
+        keep_modified(from, to)?;
+        progress(copied.fetch_add(*size, Ordering::Relaxed) + size, total);
+        Ok(())
+    };
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_COPY_THREADS);
+    match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(|| files.par_iter().try_for_each(copy)),
+        Err(e) => {
+            log::warn!("Copying files one at a time: {}", e);
+            files.iter().try_for_each(copy)
+        }
+    }
+}
+
+/// Remove the file `path` if there is one. Files are replaced by removing
+/// them first rather than writing to them, so that hard links to them, as
+/// in a snapshot, keep the old contents.
+fn unlink(path: &Path) -> std::io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Recreate the tree `src` at `dst` without copying file data where
+/// possible: files are cloned on copy-on-write filesystems, hard-linked
+/// elsewhere and only copied when neither works, e.g. across filesystems.
+/// Files for which `copy` returns true are copied rather than hard-linked,
+/// as a hard link shares every write made to the file in place.
+pub fn link_dir(src: &Path, dst: &Path, copy: &dyn Fn(&Path) -> bool) -> Result<()> {
+    let mut files = Vec::new();
+    prepare_copy(src, dst, &mut files)?;
+    let clone = reflink::supported(src, dst);
+    let mut link = true;
+    for (from, to, _) in &files {
+        if clone && reflink::clone_file(from, to).is_ok() {
+            keep_modified(from, to)?;
+            continue;
+        }
+        if link && !copy(from) {
+            match fs::hard_link(from, to) {
+                Ok(()) => continue,
+                Err(e) => {
+                    log::info!("Copying instead of linking into {}: {}", dst.display(), e);
+                    link = false;
+                }
+            }
+        }
+        fs::copy(from, to)?;
+        keep_modified(from, to)?;
+    }
+    Ok(())
+}
+
+/// Total size in bytes of the files below `path`.
+pub fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let md = entry.metadata()?;
+        if md.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else {
+            size += md.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Directory holding all backups, `~/.local/share/proton-prefix-manager/backups`
+/// unless overridden.
+pub fn backup_root() -> PathBuf {
+    if let Some(root) = overrides::get().backup_root {
+        return root;
+    }
+    dirs_next::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+        .join("backups")
+}
+
+/// Whether `path` is named like the timestamped directories backups are
+/// stored in.
+fn is_backup_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| chrono::NaiveDateTime::parse_from_str(n, "%Y%m%d%H%M%S").is_ok())
+}
+
+/// Move a directory tree, copying it when `src` and `dst` are on different
+/// filesystems. Directories that exist on both sides are merged, except for
+/// backups themselves; those and files that already exist in `dst` are left
+/// in `src` and returned.
+fn move_tree(src: &Path, dst: &Path) -> Result<Vec<PathBuf>> {
+    if !dst.exists() {
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::rename(src, dst).is_ok() {
+            return Ok(Vec::new());
+        }
+        if src.is_dir() {
+            copy_dir_recursive(src, dst)?;
+            fs::remove_dir_all(src)?;
+        } else {
+            fs::copy(src, dst)?;
+            fs::remove_file(src)?;
+        }
+        return Ok(Vec::new());
+    }
+    if !(src.is_dir() && dst.is_dir()) || is_backup_dir(src) {
+        return Ok(vec![src.to_path_buf()]);
+    }
+    let mut skipped = Vec::new();
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        skipped.extend(move_tree(&entry.path(), &dst.join(entry.file_name()))?);
+    }
+    if skipped.is_empty() {
+        fs::remove_dir(src)?;
+    }
+    Ok(skipped)
+}
+
+/// Move every backup from the backup root `from` to `to`, merging with
+/// backups already stored there. Returns the backups that could not be
+/// moved because `to` already has one with the same name.
+pub fn move_backups(from: &Path, to: &Path) -> Result<Vec<PathBuf>> {
+    if from == to || !from.exists() {
+        return Ok(Vec::new());
+    }
+    if to.starts_with(from) || from.starts_with(to) {
+        return Err(Error::FileSystemError(format!(
+            "{} and {} must not contain each other",
+            from.display(),
+            to.display()
+        )));
+    }
+    move_tree(from, to)
+}
+
+/// Shader cache belonging to a prefix: `steamapps/shadercache/<appid>` next
+/// to `steamapps/compatdata/<appid>`.
+fn shader_cache_of(prefix_path: &Path) -> Option<PathBuf> {
+    let appid = prefix_path.file_name()?;
+    let steamapps = prefix_path.parent()?.parent()?;
+    Some(steamapps.join("shadercache").join(appid))
+}
+
+/// Back up a prefix to the default backup location, optionally together
+/// with the game's shader cache. A format descriptor and a checksum
+/// manifest of the backup are written into it.
+pub fn create_backup(prefix_path: &Path, appid: u32, include_shader_cache: bool) -> Result<PathBuf> {
+    create_backup_with_progress(prefix_path, appid, include_shader_cache, &|_, _| {})
+}
+
+/// [`create_backup`], reporting the progress of copying the prefix.
+pub fn create_backup_with_progress(
+    prefix_path: &Path,
+    appid: u32,
+    include_shader_cache: bool,
+    progress: Progress,
+) -> Result<PathBuf> {
+    let dest = copy_prefix(prefix_path, &backup_root().join(appid.to_string()), progress)?;
+    let mut descriptor = Descriptor::new(BackupType::Plain, prefix_path);
+    descriptor.appid = Some(appid);
+    if include_shader_cache {
+        match shader_cache_of(prefix_path).filter(|c| c.is_dir()) {
+            Some(cache) => {
+                copy_dir_recursive(&cache, &dest.join(SHADER_CACHE_DIR))?;
+                descriptor.shader_cache = true;
+            }
+            None => log::info!("No shader cache to back up for {}", appid),
+        }
+    }
+    backup_format::write(&dest, &descriptor)?;
+    backup_manifest::write(&dest)?;
+    Ok(dest)
+}
+
+/// Back up a prefix into a timestamped directory below `root`, with a
+/// format descriptor and a checksum manifest.
+pub fn create_backup_in(prefix_path: &Path, root: &Path) -> Result<PathBuf> {
+    let dest = copy_prefix(prefix_path, root, &|_, _| {})?;
+    backup_format::write(&dest, &Descriptor::new(BackupType::Plain, prefix_path))?;
+    backup_manifest::write(&dest)?;
+    Ok(dest)
+}
+
+fn copy_prefix(prefix_path: &Path, root: &Path, progress: Progress) -> Result<PathBuf> {
+    if !prefix_path.exists() {
+        return Err(Error::FileSystemError(format!(
+            "Prefix not found: {}",
+            prefix_path.display()
+        )));
+    }
+
+    fs::create_dir_all(root)?;
+    let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
+    let dest = root.join(timestamp);
+    copy_dir_with_progress(prefix_path, &dest, progress)?;
+    Ok(dest)
+}
+
+/// Back up only the given save/config directories and files of a prefix.
+///
+/// Each location is stored under its path relative to the prefix so it can
+/// be merged back by `restore_prefix` without touching the rest of the prefix.
+/// Locations outside the prefix are skipped.
+pub fn create_saves_backup(prefix_path: &Path, appid: u32, locations: &[PathBuf]) -> Result<PathBuf> {
+    let relative: Vec<&Path> = locations
+        .iter()
+        .filter(|p| p.exists())
+        .filter_map(|p| p.strip_prefix(prefix_path).ok())
+        .collect();
+    if relative.is_empty() {
+        return Err(Error::FileSystemError(format!(
+            "No save locations found inside {}",
+            prefix_path.display()
+        )));
+    }
+
+    let root = backup_root().join(appid.to_string());
+    fs::create_dir_all(&root)?;
+    let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
+    let dest = root.join(timestamp);
+    fs::create_dir_all(&dest)?;
+    for rel in relative {
+        let (src, dst) = (prefix_path.join(rel), dest.join(rel));
+        if src.is_dir() {
+            copy_dir_recursive(&src, &dst)?;
+        } else {
+            // PCGamingWiki lists some config locations as single files.
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&src, &dst)?;
+            keep_modified(&src, &dst)?;
+        }
+    }
+    fs::write(dest.join(SAVES_ONLY_MARKER), "")?;
+    let mut descriptor = Descriptor::new(BackupType::Saves, prefix_path);
+    descriptor.appid = Some(appid);
+    backup_format::write(&dest, &descriptor)?;
+    backup_manifest::write(&dest)?;
+    Ok(dest)
+}
+
+/// What a delta restore changed in the prefix.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeltaSummary {
+    /// Files and symlinks copied from the backup because they differed or
+    /// were missing.
+    pub replaced: usize,
+    /// Files and directories not in the backup that were deleted.
+    pub removed: usize,
+    /// Files left alone because they already match the backup.
+    pub unchanged: usize,
+}
+
+impl std::fmt::Display for DeltaSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} replaced, {} removed, {} unchanged",
+            self.replaced, self.removed, self.unchanged
+        )
+    }
+}
+
+/// A file of the backup to compare with the prefix: source, destination,
+/// and its path relative to the backup as used in the manifest.
+type FileSync = (PathBuf, PathBuf, String);
+
+fn remove_any(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Make the directories and symlinks below `dst` match `src`, delete what
+/// the backup does not have and collect the files left to compare.
+fn prepare_sync(
+    src: &Path,
+    dst: &Path,
+    relative: &str,
+    files: &mut Vec<FileSync>,
+    summary: &mut DeltaSummary,
+) -> Result<()> {
+    if fs::symlink_metadata(dst).is_ok_and(|m| !m.is_dir()) {
+        fs::remove_file(dst)?;
+    }
+    fs::create_dir_all(dst)?;
+    let skipped = |name: &str| relative.is_empty() && is_backup_metadata(name);
+    for entry in fs::read_dir(dst)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if fs::symlink_metadata(src.join(&name)).is_err() || skipped(&name.to_string_lossy()) {
+            remove_any(&entry.path())?;
+            summary.removed += 1;
+        }
+    }
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if skipped(&name) {
+            continue;
+        }
+        let rel = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let file_type = entry.file_type()?;
+        let dest_path = dst.join(entry.file_name());
+        if file_type.is_dir() {
+            prepare_sync(&entry.path(), &dest_path, &rel, files, summary)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            if fs::read_link(&dest_path).is_ok_and(|t| t == target) {
+                summary.unchanged += 1;
+                continue;
+            }
+            if fs::symlink_metadata(&dest_path).is_ok() {
+                remove_any(&dest_path)?;
+            }
+            #[cfg(unix)]
+            unix_fs::symlink(&target, &dest_path)?;
+            #[cfg(not(unix))]
+            fs::copy(target, &dest_path)?;
+            summary.replaced += 1;
+        } else {
+            if fs::symlink_metadata(&dest_path).is_ok_and(|m| !m.is_file()) {
+                remove_any(&dest_path)?;
+            }
+            files.push((entry.path(), dest_path, rel));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `live` still holds the contents of the backed up file `saved`,
+/// whose manifest entry is `entry`. Files of another size differ. Like
+/// rsync's quick check, a file with exactly the size and modification time
+/// recorded in the manifest is taken as unchanged; the rest are compared by
+/// checksum.
+fn same_file(saved: &Path, live: &Path, entry: Option<&FileEntry>) -> std::io::Result<bool> {
+    let (Ok(saved_md), Ok(live_md)) = (fs::metadata(saved), fs::metadata(live)) else {
+        return Ok(false);
+    };
+    if saved_md.len() != live_md.len() {
+        return Ok(false);
+    }
+    if let Some(entry) = entry {
+        if entry.modified.is_some()
+            && entry.size == live_md.len()
+            && entry.modified == backup_manifest::modified(&live_md)
+        {
+            return Ok(true);
+        }
+    }
+    let expected = match entry {
+        Some(entry) => entry.hash.clone(),
+        None => backup_manifest::hash_file(saved)?,
+    };
+    Ok(backup_manifest::hash_file(live)? == expected)
+}
+
+/// Restore a full backup by only rewriting the files of the prefix that
+/// differ from it and deleting those it does not have, which is much faster
+/// than [`restore_prefix`] when most of a large prefix is unchanged. The
+/// checksums of the backup's manifest are used when it has one. Save-only
+/// backups are merged as by [`restore_prefix`].
+pub fn restore_prefix_delta(backup_path: &Path, prefix_path: &Path) -> Result<DeltaSummary> {
+    if !backup_path.exists() {
+        return Err(Error::FileSystemError(format!(
+            "Backup not found: {}",
+            backup_path.display()
+        )));
+    }
+    backup_format::migrate(backup_path)?;
+    if is_saves_only(backup_path) {
+        restore_prefix(backup_path, prefix_path)?;
+        return Ok(DeltaSummary::default());
+    }
+
+    let mut summary = DeltaSummary::default();
+    let mut files = Vec::new();
+    prepare_sync(backup_path, prefix_path, "", &mut files, &mut summary)?;
+    let manifest = backup_manifest::read(backup_path).unwrap_or_default();
+    let replaced = files
+        .par_iter()
+        .map(|(from, to, rel)| -> Result<bool> {
+            if same_file(from, to, manifest.get(rel))? {
+                return Ok(false);
+            }
+            unlink(to)?;
+            fs::copy(from, to)?;
+            keep_modified(from, to)?;
+            Ok(true)
+        })
+        .collect::<Result<Vec<bool>>>()?;
+    let count = replaced.iter().filter(|r| **r).count();
+    summary.replaced += count;
+    summary.unchanged += replaced.len() - count;
+
+    let backup_cache = backup_path.join(SHADER_CACHE_DIR);
+    if let Some(cache) = shader_cache_of(prefix_path).filter(|_| backup_cache.is_dir()) {
+        if cache.exists() {
+            fs::remove_dir_all(&cache)?;
+        }
+        copy_dir_recursive(&backup_cache, &cache)?;
+    }
+    Ok(summary)
+}
+
+/// Match `path` against a glob `pattern`, both `/`-separated: `*` matches
+/// within one path component, `**` across components (`**/` also matches
+/// none) and `?` matches one character.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(p: &[u8], s: &[u8]) -> bool {
+        match p.split_first() {
+            None => s.is_empty(),
+            Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+                let rest = &rest[1..];
+                if let Some(after) = rest.strip_prefix(b"/") {
+                    if matches(after, s) {
+                        return true;
+                    }
+                }
+                (0..=s.len()).any(|i| matches(rest, &s[i..]))
+            }
+            Some((b'*', rest)) => {
+                let end = s.iter().position(|c| *c == b'/').unwrap_or(s.len());
+                (0..=end).any(|i| matches(rest, &s[i..]))
+            }
+            Some((b'?', rest)) => s.first().is_some_and(|c| *c != b'/') && matches(rest, &s[1..]),
+            Some((c, rest)) => s.first() == Some(c) && matches(rest, &s[1..]),
+        }
+    }
+    matches(
+        pattern.trim_matches('/').as_bytes(),
+        path.trim_matches('/').as_bytes(),
+    )
+}
+
+/// Whether the backup path `relative` or one of its parents matches one of
+/// `patterns`. Paths inside the Wine prefix also match without their
+/// leading `pfx/`, so `drive_c/users/steamuser` selects the user directory.
+fn path_selected(relative: &str, patterns: &[String]) -> bool {
+    let mut candidates = vec![relative];
+    if let Some(inner) = relative.strip_prefix("pfx/") {
+        candidates.push(inner);
+    }
+    candidates.into_iter().any(|path| {
+        let mut ancestor = path;
+        loop {
+            if patterns.iter().any(|p| glob_match(p, ancestor)) {
+                return true;
+            }
+            match ancestor.rfind('/') {
+                Some(i) => ancestor = &ancestor[..i],
+                None => return false,
+            }
+        }
+    })
+}
+
+/// Collect the files and symlinks below `src` selected by `patterns`,
+/// leaving out the backup's metadata and shader cache.
+fn collect_selected(
+    src: &Path,
+    relative: &str,
+    patterns: &[String],
+    selected: &mut Vec<String>,
+) -> Result<()> {
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if relative.is_empty() && is_backup_metadata(&name) {
+            continue;
+        }
+        let rel = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        if entry.file_type()?.is_dir() {
+            collect_selected(&entry.path(), &rel, patterns, selected)?;
+        } else if path_selected(&rel, patterns) {
+            selected.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` at the top of a backup is bookkeeping of this tool
+/// rather than part of the prefix.
+pub fn is_backup_metadata(name: &str) -> bool {
+    name == SAVES_ONLY_MARKER || name == SHADER_CACHE_DIR || METADATA_FILES.contains(&name)
+}
+
+/// Restore only the paths of a backup matching one of the glob `patterns`
+/// (see [`glob_match`]), relative to the backup like `pfx/drive_c/users`.
+/// Matching files replace those in the prefix and everything else is left
+/// alone. Returns the number of files restored.
+pub fn restore_paths(backup_path: &Path, prefix_path: &Path, patterns: &[String]) -> Result<usize> {
+    if !backup_path.exists() {
+        return Err(Error::FileSystemError(format!(
+            "Backup not found: {}",
+            backup_path.display()
+        )));
+    }
+    backup_format::migrate(backup_path)?;
+
+    let mut selected = Vec::new();
+    collect_selected(backup_path, "", patterns, &mut selected)?;
+    if selected.is_empty() {
+        return Err(Error::FileSystemError(format!(
+            "No files in {} match {}",
+            backup_path.display(),
+            patterns.join(", ")
+        )));
+    }
+    selected.par_iter().try_for_each(|rel| -> Result<()> {
+        let from = backup_path.join(rel);
+        let to = prefix_path.join(rel);
+        if let Some(parent) = to.parent() {
+            if fs::symlink_metadata(parent).is_ok_and(|m| !m.is_dir()) {
+                fs::remove_file(parent)?;
+            }
+            fs::create_dir_all(parent)?;
+        }
+        if fs::symlink_metadata(&to).is_ok() {
+            remove_any(&to)?;
+        }
+        if fs::symlink_metadata(&from)?.file_type().is_symlink() {
+            let target = fs::read_link(&from)?;
+            #[cfg(unix)]
+            unix_fs::symlink(&target, &to)?;
+            #[cfg(not(unix))]
+            fs::copy(&from, &to)?;
+        } else {
+            fs::copy(&from, &to)?;
+        }
+        Ok(())
+    })?;
+    Ok(selected.len())
+}
+
+/// Check whether a backup includes the game's shader cache.
+pub fn has_shader_cache(backup_path: &Path) -> bool {
+    backup_path.join(SHADER_CACHE_DIR).is_dir()
+}
+
+/// Check whether a backup only contains save/config data.
+pub fn is_saves_only(backup_path: &Path) -> bool {
+    backup_path.join(SAVES_ONLY_MARKER).exists()
+}
+
+/// Directory holding the files of the prefix at `prefix_path`: the target
+/// of the symlink a relocated prefix leaves in compatdata, or else the
+/// prefix itself. Replacing the symlink instead would leave the relocated
+/// data behind.
+fn prefix_dir(prefix_path: &Path) -> PathBuf {
+    match prefix_info::relocated_target(prefix_path) {
+        Some(target) => match prefix_path.parent() {
+            Some(parent) => parent.join(target),
+            None => target,
+        },
+        None => prefix_path.to_path_buf(),
+    }
+}
+
+/// Restore a Proton prefix from a backup directory.
+///
+/// Save-only backups are merged into the existing prefix; full backups
+/// replace it entirely. A shader cache included in the backup replaces the
+/// game's current shader cache. Backups of an older format are migrated
+/// first; those of a newer format are refused. A relocated prefix is
+/// restored where it lives, keeping the symlink to it.
+pub fn restore_prefix(backup_path: &Path, prefix_path: &Path) -> Result<PathBuf> {
+    restore_prefix_with_progress(backup_path, prefix_path, &|_, _| {})
+}
+
+/// Restore a prefix like [`restore_prefix`], reporting the bytes copied
+/// of a full backup.
+pub fn restore_prefix_with_progress(
+    backup_path: &Path,
+    prefix_path: &Path,
+    progress: Progress,
+) -> Result<PathBuf> {
+    if !backup_path.exists() {
+        return Err(Error::FileSystemError(format!(
+            "Backup not found: {}",
+            backup_path.display()
+        )));
+    }
+    backup_format::migrate(backup_path)?;
+
+    if is_saves_only(backup_path) {
+        for entry in fs::read_dir(backup_path)? {
+            let entry = entry?;
+            if entry.file_name() == SAVES_ONLY_MARKER
+                || METADATA_FILES.iter().any(|f| entry.file_name() == *f)
+            {
+                continue;
+            }
+            copy_dir_recursive(&entry.path(), &prefix_path.join(entry.file_name()))?;
+        }
+        return Ok(prefix_path.to_path_buf());
+    }
+
+    let dir = prefix_dir(prefix_path);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    copy_dir_with_progress(backup_path, &dir, progress)?;
+    for file in METADATA_FILES {
+        let path = prefix_path.join(file);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    let restored_cache = prefix_path.join(SHADER_CACHE_DIR);
+    if restored_cache.exists() {
+        if let Some(cache) = shader_cache_of(prefix_path) {
+            if cache.exists() {
+                fs::remove_dir_all(&cache)?;
+            }
+            if let Some(parent) = cache.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&restored_cache, &cache)?;
+        } else {
+            fs::remove_dir_all(&restored_cache)?;
+        }
+    }
+    Ok(prefix_path.to_path_buf())
+}
+
+pub fn list_backups(appid: u32) -> Vec<PathBuf> {
+    list_backups_in(&backup_root().join(appid.to_string()))
+}
+
+/// List the backups stored directly below `root`, oldest first.
+pub fn list_backups_in(root: &Path) -> Vec<PathBuf> {
+    if let Ok(entries) = fs::read_dir(root) {
+        let mut list: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        list.sort();
+        list
+    } else {
+        Vec::new()
+    }
+}
+
+/// List backups for all applications.
+pub fn list_all_backups() -> BTreeMap<u32, Vec<PathBuf>> {
+    let mut map = BTreeMap::new();
+    let root = backup_root();
+    if let Ok(app_dirs) = fs::read_dir(root) {
+        for app_dir in app_dirs.flatten() {
+            let path = app_dir.path();
+            if path.is_dir() {
+                if let Some(appid_str) = app_dir.file_name().to_str() {
+                    if let Ok(appid) = appid_str.parse::<u32>() {
+                        let backups = list_backups(appid);
+                        if !backups.is_empty() {
+                            map.insert(appid, backups);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    map
+}
+
+/// Format a backup directory name (usually a timestamp) into a human readable string.
+pub fn format_backup_name(path: &Path) -> String {
+    let label = if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(name, "%Y%m%d%H%M%S") {
+            dt.format("%Y-%m-%d %H:%M:%S").to_string()
+        } else {
+            name.to_string()
+        }
+    } else {
+        path.display().to_string()
+    };
+    if is_saves_only(path) {
+        format!("{} (saves only)", label)
+    } else if has_shader_cache(path) {
+        format!("{} (with shader cache)", label)
+    } else {
+        label
+    }
+}
+
+pub fn delete_backup(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_dir_all(path)?;
+    }
+    Ok(())
+}
+
+/// Delete a whole prefix. Proton creates a new one on the next launch, in
+/// the same place when the prefix was relocated.
+pub fn reset_prefix(prefix_path: &Path) -> Result<()> {
+    reset_prefix_steps(prefix_path, &[])
+}
+
+/// A part of a prefix a reset can delete on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResetStep {
+    /// The contents of the user profiles in `drive_c/users`: documents,
+    /// `AppData` and with them most saves and settings.
+    UserData,
+    /// `system.reg`, `user.reg` and `userdef.reg`. Proton copies its
+    /// default registry and `drive_c` files back in on the next launch.
+    Registry,
+    /// Programs installed into `Program Files` and `ProgramData`, such as
+    /// launchers and tools, leaving Wine's and Steam's own files.
+    Software,
+}
+
+impl ResetStep {
+    pub const ALL: [ResetStep; 3] = [ResetStep::UserData, ResetStep::Registry, ResetStep::Software];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResetStep::UserData => "User data",
+            ResetStep::Registry => "Registry",
+            ResetStep::Software => "Installed software",
+        }
+    }
+}
+
+/// Directories in which programs are installed, relative to `drive_c`.
+const SOFTWARE_DIRS: [&str; 3] = ["Program Files", "Program Files (x86)", "ProgramData"];
+
+/// Entries of [`SOFTWARE_DIRS`] that belong to Wine or Proton.
+const BUILTIN_SOFTWARE: [&str; 6] = [
+    "Common Files",
+    "Internet Explorer",
+    "Windows Media Player",
+    "Windows NT",
+    "Microsoft",
+    "Steam",
+];
+
+/// Delete every entry of `dir` for which `keep` is false.
+fn clear_dir(dir: &Path, keep: impl Fn(&str) -> bool) -> Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        if !keep(&entry.file_name().to_string_lossy()) {
+            remove_any(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete the parts of a prefix given by `steps`, or the whole prefix when
+/// `steps` is empty.
+pub fn reset_prefix_steps(prefix_path: &Path, steps: &[ResetStep]) -> Result<()> {
+    if steps.is_empty() {
+        let dir = prefix_dir(prefix_path);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        // Keep the relocated directory for the symlink to point at.
+        if dir != prefix_path {
+            fs::create_dir_all(&dir)?;
+        }
+        return Ok(());
+    }
+    let pfx = prefix_path.join("pfx");
+    let drive_c = pfx.join("drive_c");
+    for step in steps {
+        match step {
+            ResetStep::UserData => {
+                for user in fs::read_dir(drive_c.join("users")).into_iter().flatten() {
+                    let user = user?.path();
+                    if user.is_dir() {
+                        clear_dir(&user, |_| false)?;
+                    }
+                }
+            }
+            ResetStep::Registry => {
+                for file in REGISTRY_FILES {
+                    let path = pfx.join(file);
+                    if path.exists() {
+                        fs::remove_file(path)?;
+                    }
+                }
+            }
+            ResetStep::Software => {
+                for dir in SOFTWARE_DIRS {
+                    clear_dir(&drive_c.join(dir), |name| BUILTIN_SOFTWARE.contains(&name))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Shader caches that Direct3D and the GPU drivers keep inside a prefix,
+/// relative to the game's compatdata directory.
+pub const PREFIX_SHADER_CACHES: [&str; 6] = [
+    "pfx/drive_c/users/steamuser/AppData/Local/D3DSCache",
+    "pfx/drive_c/users/steamuser/AppData/Local/NVIDIA/DXCache",
+    "pfx/drive_c/users/steamuser/AppData/Local/NVIDIA/GLCache",
+    "pfx/drive_c/users/steamuser/AppData/Local/AMD/DxCache",
+    "pfx/drive_c/users/steamuser/AppData/Local/AMD/DxcCache",
+    "pfx/drive_c/users/steamuser/AppData/Local/AMD/VkCache",
+];
+
+/// Delete every shader cache of a game: Steam's `shadercache/<appid>` and
+/// the [`PREFIX_SHADER_CACHES`] in its prefix, in all libraries.
+pub fn clear_shader_cache(appid: u32, libraries: &[SteamLibrary]) -> Result<()> {
+    for lib in libraries {
+        let cache = lib
+            .steamapps_path()
+            .join("shadercache")
+            .join(appid.to_string());
+        if cache.exists() {
+            fs::remove_dir_all(cache)?;
+        }
+        let compatdata = lib
+            .steamapps_path()
+            .join("compatdata")
+            .join(appid.to_string());
+        for dir in PREFIX_SHADER_CACHES {
+            let cache = compatdata.join(dir);
+            if cache.is_dir() {
+                fs::remove_dir_all(cache)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("prefix");
+        fs::create_dir_all(prefix.join("sub")).unwrap();
+        let mut f = fs::File::create(prefix.join("sub/file.txt")).unwrap();
+        writeln!(f, "test").unwrap();
+
+        let backup = create_backup(&prefix, 42, false).unwrap();
+        assert!(backup.join("sub/file.txt").exists());
+        assert!(backup_manifest::read(&backup).unwrap().contains_key("sub/file.txt"));
+        let descriptor = backup_format::read(&backup).unwrap().unwrap();
+        assert_eq!(descriptor.kind, BackupType::Plain);
+        assert_eq!(descriptor.appid, Some(42));
+        assert_eq!(descriptor.source_prefix.as_deref(), Some(prefix.as_path()));
+
+        fs::remove_dir_all(&prefix).unwrap();
+        restore_prefix(&backup, &prefix).unwrap();
+        assert!(prefix.join("sub/file.txt").exists());
+        assert!(!prefix.join(backup_manifest::MANIFEST_FILE).exists());
+        assert!(!prefix.join(backup_format::DESCRIPTOR_FILE).exists());
+    }
+
+    #[test]
+    fn test_restore_and_reset_relocated_prefix() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("games/620");
+        fs::create_dir_all(target.join("pfx")).unwrap();
+        fs::write(target.join("pfx/old.txt"), "old").unwrap();
+        let compatdata = dir.path().join("compatdata");
+        fs::create_dir_all(&compatdata).unwrap();
+        let prefix = compatdata.join("620");
+        unix_fs::symlink(&target, &prefix).unwrap();
+        let backup = dir.path().join("backup");
+        fs::create_dir_all(backup.join("pfx")).unwrap();
+        fs::write(backup.join("pfx/new.txt"), "new").unwrap();
+
+        restore_prefix(&backup, &prefix).unwrap();
+        assert!(fs::symlink_metadata(&prefix).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(target.join("pfx/new.txt")).unwrap(), "new");
+        assert!(!target.join("pfx/old.txt").exists());
+
+        reset_prefix(&prefix).unwrap();
+        assert!(fs::symlink_metadata(&prefix).unwrap().file_type().is_symlink());
+        assert!(target.is_dir());
+        assert_eq!(fs::read_dir(&target).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_copy_dir_with_progress() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        for i in 0..200 {
+            let sub = src.join(format!("dir{}", i % 7));
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(sub.join(format!("file{}.txt", i)), "x".repeat(i)).unwrap();
+        }
+        #[cfg(unix)]
+        unix_fs::symlink("dir0", src.join("link")).unwrap();
+
+        let dst = dir.path().join("dst");
+        let last = std::sync::Mutex::new((0, 0));
+        copy_dir_with_progress(&src, &dst, &|copied, total| {
+            let mut last = last.lock().unwrap();
+            last.0 = last.0.max(copied);
+            last.1 = total;
+        })
+        .unwrap();
+        assert_eq!(*last.lock().unwrap(), (19900, 19900));
+        assert_eq!(dir_size(&dst).unwrap(), dir_size(&src).unwrap());
+        assert_eq!(fs::read_to_string(dst.join("dir3/file10.txt")).unwrap(), "x".repeat(10));
+        #[cfg(unix)]
+        assert_eq!(fs::read_link(dst.join("link")).unwrap(), Path::new("dir0"));
+    }
+
+    #[test]
+    fn test_delta_restore() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("prefix");
+        fs::create_dir_all(prefix.join("pfx/drive_c/saves")).unwrap();
+        fs::write(prefix.join("version"), "8.0-3\n").unwrap();
+        fs::write(prefix.join("pfx/drive_c/saves/slot1.sav"), "level 3").unwrap();
+        fs::write(prefix.join("pfx/drive_c/saves/slot2.sav"), "level 9").unwrap();
+        let backup = create_backup_in(&prefix, &dir.path().join("backups")).unwrap();
+
+        // Same size, newer contents: only the checksum notices.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(prefix.join("pfx/drive_c/saves/slot1.sav"), "level 4").unwrap();
+        fs::remove_file(prefix.join("version")).unwrap();
+        fs::create_dir_all(prefix.join("pfx/drive_c/new")).unwrap();
+        fs::write(prefix.join("pfx/drive_c/new/log.txt"), "").unwrap();
+
+        let summary = restore_prefix_delta(&backup, &prefix).unwrap();
+        assert_eq!(
+            summary,
+            DeltaSummary {
+                replaced: 2,
+                removed: 1,
+                unchanged: 1
+            }
+        );
+        assert_eq!(fs::read_to_string(prefix.join("pfx/drive_c/saves/slot1.sav")).unwrap(), "level 3");
+        assert!(prefix.join("version").exists());
+        assert!(!prefix.join("pfx/drive_c/new").exists());
+        assert!(!prefix.join(backup_format::DESCRIPTOR_FILE).exists());
+        assert_eq!(restore_prefix_delta(&backup, &prefix).unwrap().replaced, 0);
+    }
+
+    #[test]
+    fn test_delta_restore_older_file_of_same_size() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("prefix");
+        let save = prefix.join("pfx/drive_c/saves/slot1.sav");
+        fs::create_dir_all(save.parent().unwrap()).unwrap();
+        fs::write(&save, "level 3").unwrap();
+        let backup = create_backup_in(&prefix, &dir.path().join("backups")).unwrap();
+
+        // An older save of the same size, as Steam Cloud or `cp -p` bring
+        // back, differs although it was not modified since the backup.
+        fs::remove_file(&save).unwrap();
+        fs::write(&save, "level 1").unwrap();
+        let backed_up = fs::metadata(backup.join("pfx/drive_c/saves/slot1.sav")).unwrap();
+        let older = backed_up.modified().unwrap() - std::time::Duration::from_secs(3600);
+        fs::File::open(&save).unwrap().set_modified(older).unwrap();
+
+        let summary = restore_prefix_delta(&backup, &prefix).unwrap();
+        assert_eq!(summary.replaced, 1);
+        assert_eq!(fs::read_to_string(&save).unwrap(), "level 3");
+        assert_eq!(fs::metadata(&save).unwrap().modified().unwrap(), backed_up.modified().unwrap());
+    }
+
+    #[test]
+    fn test_restore_selected_paths() {
+        assert!(glob_match("drive_c/users/*", "drive_c/users/steamuser"));
+        assert!(!glob_match("drive_c/users/*", "drive_c/users/steamuser/Documents"));
+        assert!(glob_match("**/*.sav", "pfx/drive_c/saves/slot1.sav"));
+        assert!(glob_match("pfx/**/slot?.sav", "pfx/slot1.sav"));
+
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("prefix");
+        fs::create_dir_all(prefix.join("pfx/drive_c/users/steamuser")).unwrap();
+        fs::write(prefix.join("pfx/drive_c/users/steamuser/save.dat"), "level 3").unwrap();
+        fs::write(prefix.join("pfx/user.reg"), "old").unwrap();
+        let backup = create_backup_in(&prefix, &dir.path().join("backups")).unwrap();
+
+        fs::write(prefix.join("pfx/drive_c/users/steamuser/save.dat"), "level 9").unwrap();
+        fs::write(prefix.join("pfx/drive_c/users/steamuser/new.dat"), "").unwrap();
+        fs::write(prefix.join("pfx/user.reg"), "new").unwrap();
+
+        let patterns = vec!["drive_c/users/steamuser".to_string()];
+        assert_eq!(restore_paths(&backup, &prefix, &patterns).unwrap(), 1);
+        assert_eq!(
+            fs::read_to_string(prefix.join("pfx/drive_c/users/steamuser/save.dat")).unwrap(),
+            "level 3"
+        );
+        assert!(prefix.join("pfx/drive_c/users/steamuser/new.dat").exists());
+        assert_eq!(fs::read_to_string(prefix.join("pfx/user.reg")).unwrap(), "new");
+        assert!(restore_paths(&backup, &prefix, &["drive_c/windows".to_string()]).is_err());
+        assert!(restore_paths(&backup, &prefix, &[backup_format::DESCRIPTOR_FILE.to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_reset_steps() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("620");
+        let drive_c = prefix.join("pfx/drive_c");
+        fs::create_dir_all(drive_c.join("users/steamuser/Documents")).unwrap();
+        fs::write(drive_c.join("users/steamuser/Documents/save.dat"), "level 3").unwrap();
+        fs::create_dir_all(drive_c.join("Program Files (x86)/Steam")).unwrap();
+        fs::create_dir_all(drive_c.join("Program Files (x86)/Launcher")).unwrap();
+        fs::create_dir_all(drive_c.join("ProgramData/Microsoft")).unwrap();
+        fs::write(prefix.join("pfx/user.reg"), "WINE REGISTRY").unwrap();
+        fs::write(prefix.join("pfx/system.reg"), "WINE REGISTRY").unwrap();
+
+        reset_prefix_steps(&prefix, &[ResetStep::Software]).unwrap();
+        assert!(drive_c.join("Program Files (x86)/Steam").exists());
+        assert!(drive_c.join("ProgramData/Microsoft").exists());
+        assert!(!drive_c.join("Program Files (x86)/Launcher").exists());
+        assert!(drive_c.join("users/steamuser/Documents/save.dat").exists());
+
+        reset_prefix_steps(&prefix, &[ResetStep::UserData, ResetStep::Registry]).unwrap();
+        assert!(drive_c.join("users/steamuser").is_dir());
+        assert!(!drive_c.join("users/steamuser/Documents").exists());
+        assert!(!prefix.join("pfx/user.reg").exists());
+        assert!(!prefix.join("pfx/system.reg").exists());
+        assert!(drive_c.join("Program Files (x86)/Steam").exists());
+
+        reset_prefix(&prefix).unwrap();
+        assert!(!prefix.exists());
+    }
+
+    #[test]
+    fn test_backup_with_shader_cache() {
+        let dir = tempdir().unwrap();
+        let steamapps = dir.path().join("steamapps");
+        let prefix = steamapps.join("compatdata/44");
+        let cache = steamapps.join("shadercache/44");
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        fs::create_dir_all(cache.join("DXVK_state_cache")).unwrap();
+        fs::write(cache.join("DXVK_state_cache/Game.dxvk-cache"), "state").unwrap();
+
+        let backup = create_backup(&prefix, 44, true).unwrap();
+        assert!(has_shader_cache(&backup));
+        assert!(format_backup_name(&backup).ends_with("(with shader cache)"));
+
+        fs::remove_dir_all(&cache).unwrap();
+        restore_prefix(&backup, &prefix).unwrap();
+        assert!(cache.join("DXVK_state_cache/Game.dxvk-cache").exists());
+        assert!(!prefix.join(SHADER_CACHE_DIR).exists());
+
+        fs::remove_dir_all(&backup).unwrap();
+    }
+
+    #[test]
+    fn test_saves_only_backup_merges_on_restore() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("prefix");
+        let saves = prefix.join("pfx/drive_c/users/steamuser/Documents/Game");
+        fs::create_dir_all(&saves).unwrap();
+        fs::write(saves.join("slot1.sav"), "progress").unwrap();
+        fs::write(prefix.join("version"), "8.0").unwrap();
+
+        let backup = create_saves_backup(&prefix, 43, std::slice::from_ref(&saves)).unwrap();
+        assert!(is_saves_only(&backup));
+        assert!(!backup.join("version").exists());
+        assert!(format_backup_name(&backup).ends_with("(saves only)"));
+
+        fs::write(saves.join("slot1.sav"), "corrupt").unwrap();
+        restore_prefix(&backup, &prefix).unwrap();
+        assert_eq!(fs::read_to_string(saves.join("slot1.sav")).unwrap(), "progress");
+        assert!(prefix.join("version").exists());
+        assert!(!prefix.join(SAVES_ONLY_MARKER).exists());
+
+        fs::remove_dir_all(&backup).unwrap();
+    }
+
+    #[test]
+    fn test_saves_only_backup_of_file_location() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("prefix");
+        let game = prefix.join("pfx/drive_c/users/steamuser/AppData/Local/Game");
+        fs::create_dir_all(&game).unwrap();
+        let config = game.join("config.ini");
+        fs::write(&config, "fov=90").unwrap();
+        fs::write(game.join("shaders.cache"), "cache").unwrap();
+
+        let backup = create_saves_backup(&prefix, 45, std::slice::from_ref(&config)).unwrap();
+        assert!(backup.join("pfx/drive_c/users/steamuser/AppData/Local/Game/config.ini").is_file());
+        assert!(!backup.join("pfx/drive_c/users/steamuser/AppData/Local/Game/shaders.cache").exists());
+
+        fs::write(&config, "fov=60").unwrap();
+        restore_prefix(&backup, &prefix).unwrap();
+        assert_eq!(fs::read_to_string(&config).unwrap(), "fov=90");
+        assert!(game.join("shaders.cache").exists());
+
+        fs::remove_dir_all(&backup).unwrap();
+    }
+
+    #[test]
+    fn test_move_backups_merges() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old");
+        let new = dir.path().join("new");
+        fs::create_dir_all(old.join("620/20240101000000")).unwrap();
+        fs::write(old.join("620/20240101000000/user.reg"), "old").unwrap();
+        fs::create_dir_all(old.join("620/20240202000000")).unwrap();
+        fs::create_dir_all(old.join("archived")).unwrap();
+        fs::write(old.join("archived/440.tar.gz"), "").unwrap();
+        fs::create_dir_all(new.join("620/20240101000000")).unwrap();
+
+        let skipped = move_backups(&old, &new).unwrap();
+        assert_eq!(skipped, vec![old.join("620/20240101000000")]);
+        assert!(new.join("620/20240202000000").is_dir());
+        assert!(new.join("archived/440.tar.gz").is_file());
+        assert!(!old.join("archived").exists());
+        assert!(!new.join("620/20240101000000/user.reg").exists());
+
+        assert!(move_backups(&new, &new.join("nested")).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Format descriptor of backups.
+//!
+//! Every backup holds a [`DESCRIPTOR_FILE`] saying which version of the
+//! backup layout it uses, what kind of backup it is, where it was taken
+//! from and by which version of this crate. [`migrate`] brings older
+//! backups, including those made before the descriptor existed, up to
+//! [`FORMAT_VERSION`], and refuses backups of a newer format instead of
+//! misreading them. A future layout change bumps the version and adds a
+//! step to [`upgrade`].
+
+use crate::error::{Error, Result};
+use crate::utils::backup;
+use crate::utils::prefix_info::prefix_appid;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File inside a backup describing its format.
+pub const DESCRIPTOR_FILE: &str = "backup.json";
+
+/// Version of the backup layout written by this crate.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupType {
+    /// A full copy of the prefix directory.
+    Plain,
+    /// Only the save and config directories, merged back on restore.
+    Saves,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Descriptor {
+    pub format_version: u32,
+    #[serde(rename = "type")]
+    pub kind: BackupType,
+    /// AppID of the game, if the prefix belonged to one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub appid: Option<u32>,
+    /// Prefix the backup was taken from. Unknown for migrated backups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_prefix: Option<PathBuf>,
+    /// Version of the crate that made the backup, `None` if it predates
+    /// the descriptor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    /// Whether the game's shader cache is included.
+    #[serde(default)]
+    pub shader_cache: bool,
+}
+
+impl Descriptor {
+    /// Descriptor of a backup of `source_prefix` made now.
+    pub fn new(kind: BackupType, source_prefix: &Path) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            kind,
+            appid: prefix_appid(source_prefix),
+            source_prefix: Some(source_prefix.to_path_buf()),
+            tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            shader_cache: false,
+        }
+    }
+}
+
+/// Store `descriptor` in `backup`.
+pub fn write(backup: &Path, descriptor: &Descriptor) -> Result<()> {
+    let json = serde_json::to_string_pretty(descriptor)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(backup.join(DESCRIPTOR_FILE), json)?;
+    Ok(())
+}
+
+/// The descriptor of `backup`, `None` if it predates descriptors.
+pub fn read(backup: &Path) -> Result<Option<Descriptor>> {
+    let path = backup.join(DESCRIPTOR_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path)?;
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+}
+
+/// Descriptor of a backup made before descriptors, derived from its layout.
+fn legacy(backup: &Path) -> Descriptor {
+    let kind = if backup::is_saves_only(backup) {
+        BackupType::Saves
+    } else {
+        BackupType::Plain
+    };
+    Descriptor {
+        format_version: 0,
+        kind,
+        appid: backup.parent().and_then(prefix_appid),
+        source_prefix: None,
+        tool_version: None,
+        shader_cache: backup::has_shader_cache(backup),
+    }
+}
+
+/// Bring `descriptor` one format version forward, changing `backup` as
+/// that version requires.
+fn upgrade(_backup: &Path, descriptor: &mut Descriptor) -> Result<()> {
+    match descriptor.format_version {
+        // Version 1 only added the descriptor itself.
+        0 => descriptor.format_version = 1,
+        v => {
+            return Err(Error::Parse(format!("no migration from backup format {}", v)));
+        }
+    }
+    Ok(())
+}
+
+/// Read the descriptor of `backup`, migrating the backup to the current
+/// format first if needed. Fails for backups of a newer format.
+pub fn migrate(backup: &Path) -> Result<Descriptor> {
+    let mut descriptor = match read(backup)? {
+        Some(descriptor) => descriptor,
+        None => legacy(backup),
+    };
+    if descriptor.format_version > FORMAT_VERSION {
+        return Err(Error::FileSystemError(format!(
+            "{} uses backup format {}, this version only knows up to {}",
+            backup.display(),
+            descriptor.format_version,
+            FORMAT_VERSION
+        )));
+    }
+    if descriptor.format_version == FORMAT_VERSION && backup.join(DESCRIPTOR_FILE).exists() {
+        return Ok(descriptor);
+    }
+    let from = descriptor.format_version;
+    while descriptor.format_version < FORMAT_VERSION {
+        upgrade(backup, &mut descriptor)?;
+    }
+    // A read-only backup can still be restored; it is migrated again on
+    // the next read.
+    match write(backup, &descriptor) {
+        Ok(()) => log::info!(
+            "Migrated {} from backup format {} to {}",
+            backup.display(),
+            from,
+            FORMAT_VERSION
+        ),
+        Err(e) => log::warn!("Failed to migrate {}: {}", backup.display(), e),
+    }
+    Ok(descriptor)
+}
+
+/// Migrate every backup below the backup root. Returns the backups that
+/// could not be migrated with the reason.
+pub fn migrate_all() -> Vec<(PathBuf, Error)> {
+    backup::list_all_backups()
+        .into_values()
+        .flatten()
+        .filter_map(|b| migrate(&b).err().map(|e| (b, e)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrate_legacy_backup() {
+        let dir = tempdir().unwrap();
+        let backup = dir.path().join("620/20240101000000");
+        fs::create_dir_all(backup.join(".shadercache")).unwrap();
+        fs::write(backup.join("version"), "8.0-3\n").unwrap();
+
+        assert_eq!(read(&backup).unwrap(), None);
+        let descriptor = migrate(&backup).unwrap();
+        assert_eq!(descriptor.format_version, FORMAT_VERSION);
+        assert_eq!(descriptor.kind, BackupType::Plain);
+        assert_eq!(descriptor.appid, Some(620));
+        assert!(descriptor.shader_cache);
+        assert_eq!(descriptor.tool_version, None);
+        assert_eq!(read(&backup).unwrap(), Some(descriptor));
+
+        let mut newer = Descriptor::new(BackupType::Saves, &dir.path().join("compatdata/620"));
+        assert_eq!(newer.appid, Some(620));
+        newer.format_version = FORMAT_VERSION + 1;
+        write(&backup, &newer).unwrap();
+        assert!(migrate(&backup).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! Checksum manifests of backups.
+//!
+//! [`write`] stores the relative path, size, modification time and BLAKE3
+//! hash of every file of a backup in [`MANIFEST_FILE`] inside it, one
+//! `<hash>  <size>@<mtime>  <path>` line per file with the time in
+//! nanoseconds since the epoch. Manifests written before the time was
+//! recorded have a plain `<size>`. [`verify`] compares a backup with its
+//! manifest, either by size only or by hashing every file again, and
+//! [`fingerprint`] lets identical backups be found without reading them.
+
+use crate::error::Result;
+use crate::utils::backup_format::DESCRIPTOR_FILE;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// File inside a backup holding its manifest.
+pub const MANIFEST_FILE: &str = ".manifest";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileEntry {
+    pub size: u64,
+    /// Modification time in nanoseconds since the epoch, `None` in older
+    /// manifests.
+    pub modified: Option<u64>,
+    /// BLAKE3 hash of the contents as hex.
+    pub hash: String,
+}
+
+/// Files of a backup by their path relative to it, using `/` separators.
+pub type Manifest = BTreeMap<String, FileEntry>;
+
+/// Result of comparing a backup with its manifest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Verification {
+    /// Files listed in the manifest that no longer exist.
+    pub missing: Vec<String>,
+    /// Files whose size or contents differ from the manifest.
+    pub changed: Vec<String>,
+    /// Files not listed in the manifest.
+    pub added: Vec<String>,
+}
+
+impl Verification {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.changed.is_empty() && self.added.is_empty()
+    }
+
+    /// Short description of the problems, e.g. `2 changed, 1 missing`.
+    pub fn summary(&self) -> String {
+        if self.is_ok() {
+            return "intact".to_string();
+        }
+        [
+            (self.changed.len(), "changed"),
+            (self.missing.len(), "missing"),
+            (self.added.len(), "added"),
+        ]
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, what)| format!("{} {}", n, what))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// BLAKE3 hash of the contents of `path` as hex.
+pub(crate) fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(fs::File::open(path)?)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Modification time of a file in nanoseconds since the epoch, as recorded
+/// in manifests.
+pub(crate) fn modified(metadata: &fs::Metadata) -> Option<u64> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    since_epoch.as_nanos().try_into().ok()
+}
+
+/// Regular files below `dir` with their relative paths. Symlinks are
+/// skipped, they are recreated rather than copied, and so are the manifest
+/// and the format descriptor, which may be rewritten by a migration.
+fn files(root: &Path, dir: &Path, out: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            files(root, &path, out)?;
+        } else if file_type.is_file() {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let relative = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if relative != MANIFEST_FILE && relative != DESCRIPTOR_FILE {
+                out.push((relative, path));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hash every file of `backup` and store the manifest in it.
+pub fn write(backup: &Path) -> Result<PathBuf> {
+    let mut list = Vec::new();
+    files(backup, backup, &mut list)?;
+    let mut contents = String::new();
+    for (relative, path) in list {
+        let metadata = fs::metadata(&path)?;
+        let size = match modified(&metadata) {
+            Some(time) => format!("{}@{}", metadata.len(), time),
+            None => metadata.len().to_string(),
+        };
+        contents.push_str(&format!("{}  {}  {}\n", hash_file(&path)?, size, relative));
+    }
+    let manifest = backup.join(MANIFEST_FILE);
+    fs::write(&manifest, contents)?;
+    Ok(manifest)
+}
+
+/// The manifest of `backup`, `None` for backups made without one.
+pub fn read(backup: &Path) -> Option<Manifest> {
+    let contents = fs::read_to_string(backup.join(MANIFEST_FILE)).ok()?;
+    let mut manifest = Manifest::new();
+    for line in contents.lines() {
+        let mut parts = line.splitn(3, "  ");
+        let (Some(hash), Some(size), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let (size, modified) = match size.split_once('@') {
+            Some((size, time)) => (size, time.parse().ok()),
+            None => (size, None),
+        };
+        let Ok(size) = size.parse() else {
+            continue;
+        };
+        manifest.insert(
+            path.to_string(),
+            FileEntry {
+                size,
+                modified,
+                hash: hash.to_string(),
+            },
+        );
+    }
+    Some(manifest)
+}
+
+/// Compare `backup` with its manifest. With `full` every file is hashed
+/// again; otherwise only sizes are compared, which is fast but misses
+/// changes that keep the size. `None` if the backup has no manifest.
+pub fn verify(backup: &Path, full: bool) -> Result<Option<Verification>> {
+    let Some(mut manifest) = read(backup) else {
+        return Ok(None);
+    };
+    let mut list = Vec::new();
+    files(backup, backup, &mut list)?;
+    let mut result = Verification::default();
+    for (relative, path) in list {
+        let Some(expected) = manifest.remove(&relative) else {
+            result.added.push(relative);
+            continue;
+        };
+        let size = fs::metadata(&path)?.len();
+        if size != expected.size || (full && hash_file(&path)? != expected.hash) {
+            result.changed.push(relative);
+        }
+    }
+    result.missing = manifest.into_keys().collect();
+    Ok(Some(result))
+}
+
+/// Hash of the paths, sizes and hashes of the manifest. Backups with the
+/// same fingerprint hold the same files, so all but one of them can be
+/// deleted. Modification times are left out, so copies of files that were
+/// only touched still count as the same.
+pub fn fingerprint(backup: &Path) -> Option<String> {
+    let mut hasher = blake3::Hasher::new();
+    for (path, entry) in read(backup)? {
+        hasher.update(format!("{}  {}  {}\n", entry.hash, entry.size, path).as_bytes());
+    }
+    Some(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_and_verify() {
+        let dir = tempdir().unwrap();
+        let backup = dir.path().join("20240101000000");
+        fs::create_dir_all(backup.join("pfx/drive_c")).unwrap();
+        fs::write(backup.join("version"), "8.0-3\n").unwrap();
+        fs::write(backup.join("pfx/drive_c/save.dat"), "level 3").unwrap();
+
+        assert_eq!(verify(&backup, true).unwrap(), None);
+        write(&backup).unwrap();
+        let manifest = read(&backup).unwrap();
+        assert_eq!(manifest.len(), 2);
+        assert_eq!(manifest["pfx/drive_c/save.dat"].size, 7);
+        assert_eq!(
+            manifest["pfx/drive_c/save.dat"].modified,
+            modified(&fs::metadata(backup.join("pfx/drive_c/save.dat")).unwrap())
+        );
+        assert!(verify(&backup, true).unwrap().unwrap().is_ok());
+
+        let copy = dir.path().join("20240102000000");
+        crate::utils::backup::copy_dir_recursive(&backup, &copy).unwrap();
+        assert_eq!(fingerprint(&backup), fingerprint(&copy));
+        // Manifests without modification times are still read.
+        let old = fs::read_to_string(backup.join(MANIFEST_FILE)).unwrap();
+        let old: String = old
+            .lines()
+            .map(|line| {
+                let (hash, rest) = line.split_once("  ").unwrap();
+                let (size, path) = rest.split_once("  ").unwrap();
+                format!("{}  {}  {}\n", hash, size.split('@').next().unwrap(), path)
+            })
+            .collect();
+        fs::write(copy.join(MANIFEST_FILE), old).unwrap();
+        assert_eq!(read(&copy).unwrap()["version"].modified, None);
+        assert_eq!(fingerprint(&backup), fingerprint(&copy));
+
+        // Same size, different contents: only a full check notices.
+        fs::write(backup.join("pfx/drive_c/save.dat"), "level 4").unwrap();
+        fs::remove_file(backup.join("version")).unwrap();
+        fs::write(backup.join("extra.txt"), "").unwrap();
+        let quick = verify(&backup, false).unwrap().unwrap();
+        assert!(quick.changed.is_empty());
+        let full = verify(&backup, true).unwrap().unwrap();
+        assert_eq!(full.changed, ["pfx/drive_c/save.dat"]);
+        assert_eq!(full.missing, ["version"]);
+        assert_eq!(full.added, ["extra.txt"]);
+        assert_eq!(full.summary(), "1 changed, 1 missing, 1 added");
+    }
+}
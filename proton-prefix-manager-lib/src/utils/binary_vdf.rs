@@ -0,0 +1,491 @@
+//! Reader and writer for Steam's binary KeyValues format.
+//!
+//! Steam stores `appcache/appinfo.vdf`, `appcache/packageinfo.vdf` and
+//! `userdata/<id>/config/shortcuts.vdf` in this format instead of the text
+//! VDF used by appmanifests. Only shortcuts are safe to write: the appinfo
+//! and packageinfo caches carry checksums and belong to Steam.
+
+use crate::error::{Error, Result};
+use crate::utils::steam_paths;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const TYPE_MAP: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT32: u8 = 0x02;
+const TYPE_FLOAT32: u8 = 0x03;
+const TYPE_POINTER: u8 = 0x04;
+const TYPE_COLOR: u8 = 0x06;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+const TYPE_INT64: u8 = 0x0A;
+const TYPE_ALT_END: u8 = 0x0B;
+
+const APPINFO_V27: u32 = 0x0756_4427;
+const APPINFO_V28: u32 = 0x0756_4428;
+const APPINFO_V29: u32 = 0x0756_4429;
+const PACKAGEINFO_V27: u32 = 0x0656_5527;
+const PACKAGEINFO_V28: u32 = 0x0656_5528;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Map(Map),
+    Str(String),
+    Int(i32),
+    Float(f32),
+    UInt64(u64),
+    Int64(i64),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n as i64),
+            Value::Int64(n) => Some(*n),
+            Value::UInt64(n) => i64::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&Map> {
+        match self {
+            Value::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+/// Keys and values of a binary VDF object in file order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Map(pub Vec<(String, Value)>);
+
+impl Map {
+    /// Value of `key`. Steam isn't consistent about the case of keys, e.g.
+    /// `AppName` and `appname` in shortcuts, so the lookup ignores case.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Value below nested objects, e.g. `["appinfo", "common", "name"]`.
+    pub fn get_path(&self, path: &[&str]) -> Option<&Value> {
+        let (last, parents) = path.split_last()?;
+        let mut map = self;
+        for key in parents {
+            map = map.get(key)?.as_map()?;
+        }
+        map.get(last)
+    }
+
+    /// Set `key`, replacing an existing value in place.
+    pub fn insert(&mut self, key: &str, value: Value) {
+        match self.0.iter_mut().find(|(k, _)| k.eq_ignore_ascii_case(key)) {
+            Some((_, v)) => *v = value,
+            None => self.0.push((key.to_string(), value)),
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Key names of appinfo v29, which stores keys as indexes.
+    strings: Option<Vec<String>>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            strings: None,
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+        let end = end.ok_or_else(|| {
+            Error::Parse(format!("binary VDF ends early at byte {}", self.pos))
+        })?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn cstr(&mut self) -> Result<String> {
+        let rest = &self.data[self.pos..];
+        let len = rest.iter().position(|b| *b == 0).ok_or_else(|| {
+            Error::Parse(format!("unterminated string at byte {}", self.pos))
+        })?;
+        let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Ok(s)
+    }
+
+    fn key(&mut self) -> Result<String> {
+        if self.strings.is_none() {
+            return self.cstr();
+        }
+        let index = self.u32()? as usize;
+        let strings = self.strings.as_ref().unwrap();
+        strings.get(index).cloned().ok_or_else(|| {
+            Error::Parse(format!("key index {} outside the string table", index))
+        })
+    }
+
+    fn map(&mut self) -> Result<Map> {
+        let mut map = Map::default();
+        loop {
+            let kind = self.u8()?;
+            if kind == TYPE_END || kind == TYPE_ALT_END {
+                return Ok(map);
+            }
+            let key = self.key()?;
+            let value = match kind {
+                TYPE_MAP => Value::Map(self.map()?),
+                TYPE_STRING => Value::Str(self.cstr()?),
+                TYPE_INT32 | TYPE_POINTER | TYPE_COLOR => Value::Int(self.u32()? as i32),
+                TYPE_FLOAT32 => Value::Float(f32::from_bits(self.u32()?)),
+                TYPE_UINT64 => Value::UInt64(self.u64()?),
+                TYPE_INT64 => Value::Int64(self.u64()? as i64),
+                other => {
+                    return Err(Error::Parse(format!(
+                        "unknown binary VDF type {:#04x} at byte {}",
+                        other,
+                        self.pos - 1
+                    )))
+                }
+            };
+            map.0.push((key, value));
+        }
+    }
+}
+
+/// Parse a binary KeyValues document such as `shortcuts.vdf`.
+pub fn parse(data: &[u8]) -> Result<Map> {
+    Reader::new(data).map()
+}
+
+fn write_map(out: &mut Vec<u8>, map: &Map) {
+    for (key, value) in &map.0 {
+        let kind = match value {
+            Value::Map(_) => TYPE_MAP,
+            Value::Str(_) => TYPE_STRING,
+            Value::Int(_) => TYPE_INT32,
+            Value::Float(_) => TYPE_FLOAT32,
+            Value::UInt64(_) => TYPE_UINT64,
+            Value::Int64(_) => TYPE_INT64,
+        };
+        out.push(kind);
+        out.extend_from_slice(key.as_bytes());
+        out.push(0);
+        match value {
+            Value::Map(m) => write_map(out, m),
+            Value::Str(s) => {
+                out.extend_from_slice(s.as_bytes());
+                out.push(0);
+            }
+            Value::Int(n) => out.extend_from_slice(&n.to_le_bytes()),
+            Value::Float(f) => out.extend_from_slice(&f.to_le_bytes()),
+            Value::UInt64(n) => out.extend_from_slice(&n.to_le_bytes()),
+            Value::Int64(n) => out.extend_from_slice(&n.to_le_bytes()),
+        }
+    }
+    out.push(TYPE_END);
+}
+
+/// Serialize a document in the format read by [`parse`].
+pub fn to_bytes(map: &Map) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_map(&mut out, map);
+    out
+}
+
+/// Parse `appcache/appinfo.vdf` into the data of every app, keyed by AppID.
+/// The name of an app is at `["appinfo", "common", "name"]`.
+pub fn parse_appinfo(data: &[u8]) -> Result<BTreeMap<u32, Map>> {
+    let mut reader = Reader::new(data);
+    let magic = reader.u32()?;
+    let _universe = reader.u32()?;
+    if ![APPINFO_V27, APPINFO_V28, APPINFO_V29].contains(&magic) {
+        return Err(Error::Parse(format!("unsupported appinfo.vdf version {:#x}", magic)));
+    }
+    let mut end = data.len();
+    if magic == APPINFO_V29 {
+        let offset = reader.u64()? as usize;
+        let mut table = Reader::new(data);
+        table.pos = offset;
+        let count = table.u32()?;
+        let strings = (0..count).map(|_| table.cstr()).collect::<Result<Vec<_>>>()?;
+        reader.strings = Some(strings);
+        end = offset;
+    }
+
+    let mut apps = BTreeMap::new();
+    while reader.pos < end {
+        let appid = reader.u32()?;
+        if appid == 0 {
+            break;
+        }
+        let size = reader.u32()? as usize;
+        let next = reader.pos + size;
+        // info state, last update, PICS token, SHA-1 and change number
+        reader.bytes(4 + 4 + 8 + 20 + 4)?;
+        if magic != APPINFO_V27 {
+            // SHA-1 of the binary data
+            reader.bytes(20)?;
+        }
+        apps.insert(appid, reader.map()?);
+        reader.pos = next;
+    }
+    Ok(apps)
+}
+
+/// Parse `appcache/packageinfo.vdf` into the data of every package, keyed
+/// by package ID.
+pub fn parse_packageinfo(data: &[u8]) -> Result<BTreeMap<u32, Map>> {
+    let mut reader = Reader::new(data);
+    let magic = reader.u32()?;
+    let _universe = reader.u32()?;
+    if magic != PACKAGEINFO_V27 && magic != PACKAGEINFO_V28 {
+        return Err(Error::Parse(format!(
+            "unsupported packageinfo.vdf version {:#x}",
+            magic
+        )));
+    }
+    let mut packages = BTreeMap::new();
+    loop {
+        let id = reader.u32()?;
+        if id == u32::MAX {
+            break;
+        }
+        // SHA-1 and change number
+        reader.bytes(20 + 4)?;
+        if magic == PACKAGEINFO_V28 {
+            // PICS token
+            reader.bytes(8)?;
+        }
+        packages.insert(id, reader.map()?);
+    }
+    Ok(packages)
+}
+
+/// A non-Steam game added to the library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shortcut {
+    /// AppID Steam assigned to the shortcut; its prefix is
+    /// `compatdata/<appid>`.
+    pub appid: u32,
+    pub name: String,
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+}
+
+/// Parse `userdata/<id>/config/shortcuts.vdf`.
+pub fn parse_shortcuts(data: &[u8]) -> Result<Vec<Shortcut>> {
+    let root = parse(data)?;
+    let Some(shortcuts) = root.get("shortcuts").and_then(Value::as_map) else {
+        return Ok(Vec::new());
+    };
+    let text = |map: &Map, key: &str| {
+        map.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    Ok(shortcuts
+        .0
+        .iter()
+        .filter_map(|(_, entry)| entry.as_map())
+        .filter_map(|entry| {
+            // Stored as a signed 32 bit number.
+            let appid = entry.get("appid")?.as_int()? as u32;
+            Some(Shortcut {
+                appid,
+                name: text(entry, "AppName"),
+                exe: text(entry, "Exe"),
+                start_dir: text(entry, "StartDir"),
+                launch_options: text(entry, "LaunchOptions"),
+            })
+        })
+        .collect())
+}
+
+/// Names of the apps in an `appinfo.vdf` file.
+pub fn appinfo_names(path: &Path) -> Result<BTreeMap<u32, String>> {
+    let apps = parse_appinfo(&std::fs::read(path)?)?;
+    Ok(apps
+        .into_iter()
+        .filter_map(|(appid, data)| {
+            let name = data.get_path(&["appinfo", "common", "name"])?.as_str()?;
+            Some((appid, name.to_string()))
+        })
+        .collect())
+}
+
+/// `shortcuts.vdf` files of every Steam user.
+pub fn shortcuts_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for userdata in steam_paths::userdata_dirs() {
+        if let Ok(users) = std::fs::read_dir(userdata) {
+            for user in users.flatten() {
+                let path = user.path().join("config/shortcuts.vdf");
+                if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Non-Steam games of every Steam user. Unreadable files are skipped.
+pub fn all_shortcuts() -> Vec<Shortcut> {
+    let mut shortcuts = Vec::new();
+    for path in shortcuts_files() {
+        match std::fs::read(&path).map_err(Error::from).and_then(|data| parse_shortcuts(&data)) {
+            Ok(mut found) => shortcuts.append(&mut found),
+            Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+        }
+    }
+    shortcuts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcuts_file() -> Map {
+        let entry = Map(vec![
+            ("appid".into(), Value::Int(-1_234_567_890)),
+            ("AppName".into(), Value::Str("Battle.net".into())),
+            ("Exe".into(), Value::Str("\"/games/bnet/Launcher.exe\"".into())),
+            ("StartDir".into(), Value::Str("\"/games/bnet/\"".into())),
+            ("LaunchOptions".into(), Value::Str(String::new())),
+            ("LastPlayTime".into(), Value::Int(0)),
+            ("tags".into(), Value::Map(Map::default())),
+        ]);
+        Map(vec![(
+            "shortcuts".into(),
+            Value::Map(Map(vec![("0".into(), Value::Map(entry))])),
+        )])
+    }
+
+    #[test]
+    fn test_shortcuts_roundtrip() {
+        let bytes = to_bytes(&shortcuts_file());
+        assert!(bytes.ends_with(&[TYPE_END, TYPE_END, TYPE_END]));
+        assert_eq!(parse(&bytes).unwrap(), shortcuts_file());
+
+        let shortcuts = parse_shortcuts(&bytes).unwrap();
+        assert_eq!(shortcuts.len(), 1);
+        assert_eq!(shortcuts[0].appid, 3_060_399_406);
+        assert_eq!(shortcuts[0].name, "Battle.net");
+
+        assert!(parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    fn appinfo_entry(out: &mut Vec<u8>, appid: u32, data: &[u8], v28: bool) {
+        let header = 4 + 4 + 8 + 20 + 4 + if v28 { 20 } else { 0 };
+        out.extend_from_slice(&appid.to_le_bytes());
+        out.extend_from_slice(&((header + data.len()) as u32).to_le_bytes());
+        out.extend(std::iter::repeat_n(0, header));
+        out.extend_from_slice(data);
+    }
+
+    #[test]
+    fn test_parse_appinfo() {
+        let common = Map(vec![("name".into(), Value::Str("Portal 2".into()))]);
+        let info = Map(vec![
+            ("appid".into(), Value::Int(620)),
+            ("common".into(), Value::Map(common)),
+        ]);
+        let data = to_bytes(&Map(vec![("appinfo".into(), Value::Map(info))]));
+
+        let mut file = Vec::new();
+        file.extend_from_slice(&APPINFO_V28.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        appinfo_entry(&mut file, 620, &data, true);
+        file.extend_from_slice(&0u32.to_le_bytes());
+        let apps = parse_appinfo(&file).unwrap();
+        assert_eq!(
+            apps[&620].get_path(&["appinfo", "common", "name"]),
+            Some(&Value::Str("Portal 2".into()))
+        );
+
+        // v29 stores keys as indexes into a string table at the end.
+        let strings = ["appinfo", "appid", "common", "name"];
+        let mut data = vec![TYPE_MAP];
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(TYPE_INT32);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&620i32.to_le_bytes());
+        data.push(TYPE_MAP);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(TYPE_STRING);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"Portal 2\0");
+        data.extend_from_slice(&[TYPE_END, TYPE_END, TYPE_END]);
+
+        let mut entries = Vec::new();
+        appinfo_entry(&mut entries, 620, &data, true);
+        entries.extend_from_slice(&0u32.to_le_bytes());
+        let mut file = Vec::new();
+        file.extend_from_slice(&APPINFO_V29.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        file.extend_from_slice(&((16 + entries.len()) as u64).to_le_bytes());
+        file.extend_from_slice(&entries);
+        file.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+        for s in strings {
+            file.extend_from_slice(s.as_bytes());
+            file.push(0);
+        }
+        let apps = parse_appinfo(&file).unwrap();
+        assert_eq!(
+            apps[&620].get_path(&["appinfo", "common", "name"]).and_then(Value::as_str),
+            Some("Portal 2")
+        );
+    }
+
+    #[test]
+    fn test_parse_packageinfo() {
+        let data = to_bytes(&Map(vec![(
+            "0".into(),
+            Value::Map(Map(vec![("packageid".into(), Value::Int(0))])),
+        )]));
+        let mut file = Vec::new();
+        file.extend_from_slice(&PACKAGEINFO_V28.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        file.extend_from_slice(&0u32.to_le_bytes());
+        file.extend(std::iter::repeat_n(0, 20 + 4 + 8));
+        file.extend_from_slice(&data);
+        file.extend_from_slice(&u32::MAX.to_le_bytes());
+        let packages = parse_packageinfo(&file).unwrap();
+        assert_eq!(
+            packages[&0].get_path(&["0", "packageid"]),
+            Some(&Value::Int(0))
+        );
+    }
+}
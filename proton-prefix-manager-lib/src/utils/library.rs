@@ -103,19 +103,98 @@ pub fn parse_libraryfolders_vdf(vdf_path: &str) -> Option<Vec<PathBuf>> {
     Some(library_paths)
 }
 
-pub fn parse_appmanifest(path: &Path) -> Option<(u32, String, u64)> {
+/// Move the `apps` entry of `appid` in a libraryfolders.vdf from the
+/// library at `from` to the one at `to`, keeping its size, so Steam looks
+/// for the game in its new library. `None` if the file cannot be parsed or
+/// lists no library at `to`.
+pub fn move_libraryfolders_app(contents: &str, appid: u32, from: &Path, to: &Path) -> Option<String> {
+    let mut vdf = Vdf::parse(contents).ok()?;
+    let folders = if vdf.key == "libraryfolders" {
+        vdf.value.get_mut_obj()?
+    } else {
+        vdf.value
+            .get_mut_obj()?
+            .get_mut("libraryfolders")?
+            .first_mut()?
+            .get_mut_obj()?
+    };
+    let is_library = |folder: &keyvalues_parser::Obj, path: &Path| {
+        folder
+            .get("path")
+            .and_then(|v| v.first())
+            .and_then(Value::get_str)
+            .is_some_and(|p| Path::new(p) == path)
+    };
+    let key = appid.to_string();
+    let mut entry = None;
+    for folder in folders.values_mut().filter_map(|v| v.first_mut()?.get_mut_obj()) {
+        if !is_library(folder, from) {
+            continue;
+        }
+        if let Some(apps) = folder.get_mut("apps").and_then(|v| v.first_mut()?.get_mut_obj()) {
+            entry = apps.remove(key.as_str());
+        }
+    }
+    let target = folders
+        .values_mut()
+        .filter_map(|v| v.first_mut()?.get_mut_obj())
+        .find(|folder| is_library(folder, to))?;
+    let apps = target
+        .entry("apps".into())
+        .or_insert_with(|| vec![Value::Obj(Default::default())])
+        .first_mut()?
+        .get_mut_obj()?;
+    apps.insert(key.into(), entry.unwrap_or_else(|| vec![Value::Str("0".into())]));
+    Some(format!("{}", vdf))
+}
+
+/// The fields of an appmanifest file used by the game list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppManifest {
+    pub appid: u32,
+    pub name: String,
+    /// Last time the game was played (Unix timestamp), 0 if never
+    pub last_played: u64,
+    /// Bytes the installed game files take, 0 if Steam did not record it
+    pub size_on_disk: u64,
+    /// Directory below `steamapps/common` holding the game files
+    pub installdir: Option<String>,
+    /// Steam's install state bits, [`STATE_FULLY_INSTALLED`] when nothing
+    /// is pending
+    pub state_flags: u32,
+}
+
+/// `StateFlags` of an app that is installed and has no update pending.
+pub const STATE_FULLY_INSTALLED: u32 = 4;
+
+impl AppManifest {
+    /// Whether Steam is installing or updating the app, or has an update
+    /// queued.
+    pub fn is_updating(&self) -> bool {
+        self.state_flags & !STATE_FULLY_INSTALLED != 0
+    }
+}
+
+pub fn parse_appmanifest(path: &Path) -> Option<AppManifest> {
     let contents = read_manifest_cached(path)?;
     let vdf = Vdf::parse(&contents).ok()?;
     let app_state = vdf.value.get_obj()?;
     let appid = app_state.get("appid")?.first()?.get_str()?.parse().ok()?;
     let name = app_state.get("name")?.first()?.get_str()?.to_string();
-    let last_played = app_state
-        .get("LastPlayed")
-        .and_then(|v| v.first())
-        .and_then(|v| v.get_str())
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(0);
-    Some((appid, name, last_played))
+    let field = |key: &str| {
+        app_state
+            .get(key)
+            .and_then(|v| v.first())
+            .and_then(|v| v.get_str())
+    };
+    Some(AppManifest {
+        appid,
+        name,
+        last_played: field("LastPlayed").and_then(|s| s.parse().ok()).unwrap_or(0),
+        size_on_disk: field("SizeOnDisk").and_then(|s| s.parse().ok()).unwrap_or(0),
+        installdir: field("installdir").map(str::to_string),
+        state_flags: field("StateFlags").and_then(|s| s.parse().ok()).unwrap_or(0),
+    })
 }
 
 /// Parse only the AppID and installdir from an appmanifest file.
@@ -147,6 +226,9 @@ mod tests {
         {
             "appid"     "123456"
             "name"      "Test Game"
+            "installdir"    "TestGame"
+            "SizeOnDisk"    "1073741824"
+            "StateFlags"    "1026"
             "other"     "value"
         }
         "#;
@@ -157,9 +239,13 @@ mod tests {
         let result = parse_appmanifest(&file_path);
         assert!(result.is_some());
 
-        let (appid, name, _) = result.unwrap();
-        assert_eq!(appid, 123456);
-        assert_eq!(name, "Test Game");
+        let manifest = result.unwrap();
+        assert_eq!(manifest.appid, 123456);
+        assert_eq!(manifest.name, "Test Game");
+        assert_eq!(manifest.last_played, 0);
+        assert_eq!(manifest.size_on_disk, 1 << 30);
+        assert_eq!(manifest.installdir.as_deref(), Some("TestGame"));
+        assert!(manifest.is_updating());
     }
 
     #[test]
@@ -202,6 +288,43 @@ mod tests {
         assert!(libs.contains(&lib2));
     }
 
+    #[test]
+    fn test_move_libraryfolders_app() {
+        let contents = r#""libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/deck/.steam/steam"
+		"apps"
+		{
+			"228980"		"182738418"
+			"4040"		"1024"
+		}
+	}
+	"1"
+	{
+		"path"		"/run/media/sdcard"
+	}
+}"#;
+        let home = Path::new("/home/deck/.steam/steam");
+        let updated =
+            move_libraryfolders_app(contents, 4040, home, Path::new("/run/media/sdcard")).unwrap();
+        let vdf = Vdf::parse(&updated).unwrap();
+        let folders = vdf.value.get_obj().unwrap();
+        let apps = |library: &str| {
+            folders[library][0].get_obj().unwrap()["apps"][0]
+                .get_obj()
+                .unwrap()
+                .keys()
+                .map(|k| k.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(apps("0"), vec!["228980"]);
+        assert_eq!(apps("1"), vec!["4040"]);
+        assert!(updated.contains("\"1024\""));
+        assert!(move_libraryfolders_app(contents, 4040, home, Path::new("/mnt/other")).is_none());
+    }
+
     #[test]
     fn test_parse_installdir() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,15 @@
+//! Steam file formats, backups and prefix inspection.
+
+pub mod backup;
+pub mod backup_format;
+pub mod backup_manifest;
+pub mod binary_vdf;
+pub mod library;
+pub mod overrides;
+pub mod prefix_info;
+pub mod prefix_repair;
+pub mod reflink;
+pub mod sandbox;
+pub mod steam_paths;
+pub mod user_config;
+pub mod vdf_history;
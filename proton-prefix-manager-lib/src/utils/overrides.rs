@@ -0,0 +1,34 @@
+//! Locations a front end can override, usually from its config file.
+//!
+//! Without overrides the backup root is the XDG data directory and Steam is
+//! searched in its usual places below the home directory.
+
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overrides {
+    /// Directory holding all backups.
+    pub backup_root: Option<PathBuf>,
+    /// Steam installation to use instead of searching for one.
+    pub steam_root: Option<PathBuf>,
+}
+
+static OVERRIDES: Lazy<RwLock<Overrides>> = Lazy::new(|| RwLock::new(Overrides::default()));
+
+/// Replace the current overrides. Cached Steam data is dropped when the
+/// Steam root changes.
+pub fn set(overrides: Overrides) {
+    let mut current = OVERRIDES.write().unwrap();
+    let steam_changed = current.steam_root != overrides.steam_root;
+    *current = overrides;
+    drop(current);
+    if steam_changed {
+        crate::core::steam::clear_caches();
+    }
+}
+
+pub fn get() -> Overrides {
+    OVERRIDES.read().unwrap().clone()
+}
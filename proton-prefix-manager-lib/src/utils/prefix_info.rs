@@ -0,0 +1,339 @@
+//! Facts about a Proton prefix gathered from the files inside it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Default)]
+pub struct PrefixInfo {
+    pub version: Option<String>,
+    pub has_dxvk: bool,
+    pub has_vkd3d: bool,
+}
+
+fn detect_proton_version(prefix_path: &Path) -> Option<String> {
+    log::debug!("Detecting Proton version for prefix: {:?}", prefix_path);
+
+    // First check the 'version' file in the prefix
+    let version_file = prefix_path.join("version");
+    log::debug!("Checking version file: {:?}", version_file);
+    if version_file.exists() {
+        if let Ok(contents) = fs::read_to_string(&version_file) {
+            let version = contents.trim().to_string();
+            log::debug!("Found version in prefix: {}", version);
+            return Some(version);
+        }
+    }
+
+    // Check for 'version' in the parent directory (compatdata)
+    if let Some(parent) = prefix_path.parent() {
+        let version_file = parent.join("version");
+        log::debug!("Checking parent version file: {:?}", version_file);
+        if version_file.exists() {
+            if let Ok(contents) = fs::read_to_string(&version_file) {
+                let version = contents.trim().to_string();
+                log::debug!("Found version in parent: {}", version);
+                return Some(version);
+            }
+        }
+    }
+
+    // Check for version in the prefix's parent directory name (e.g., Proton 8.0)
+    if let Some(parent) = prefix_path.parent() {
+        if let Some(parent_name) = parent.file_name() {
+            if let Some(parent_str) = parent_name.to_str() {
+                if parent_str.to_lowercase().contains("proton") {
+                    log::debug!("Found version in parent directory name: {}", parent_str);
+                    return Some(parent_str.to_string());
+                }
+            }
+        }
+    }
+
+    // Check for toolmanifest.vdf in the prefix
+    let toolmanifest = prefix_path.join("toolmanifest.vdf");
+    log::debug!("Checking toolmanifest: {:?}", toolmanifest);
+    if toolmanifest.exists() {
+        if let Ok(contents) = fs::read_to_string(&toolmanifest) {
+            for line in contents.lines() {
+                let line = line.trim();
+                if line.starts_with("\"name\"") {
+                    if let Some(name) = line.split('"').nth(3) {
+                        if name.contains("Proton") {
+                            log::debug!("Found version in toolmanifest: {}", name);
+                            return Some(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Check for proton_version in the prefix
+    let proton_version = prefix_path.join("proton_version");
+    log::debug!("Checking proton_version file: {:?}", proton_version);
+    if proton_version.exists() {
+        if let Ok(contents) = fs::read_to_string(&proton_version) {
+            let version = contents.trim().to_string();
+            log::debug!("Found version in proton_version: {}", version);
+            return Some(version);
+        }
+    }
+
+    // Check for the dist.info file which some Proton versions use
+    let dist_info = prefix_path.join("dist.info");
+    log::debug!("Checking dist.info file: {:?}", dist_info);
+    if dist_info.exists() {
+        if let Ok(contents) = fs::read_to_string(&dist_info) {
+            if let Some(version_line) = contents.lines().find(|l| l.contains("DIST_VERSION=")) {
+                if let Some(version) = version_line.split('=').nth(1) {
+                    let version = format!("Proton {}", version.trim());
+                    log::debug!("Found version in dist.info: {}", version);
+                    return Some(version);
+                }
+            }
+        }
+    }
+
+    log::debug!("No Proton version found for prefix: {:?}", prefix_path);
+    None
+}
+
+fn has_dxvk(prefix_path: &Path) -> bool {
+    // Check for DXVK DLLs in the prefix
+    let dll_path = prefix_path.join("pfx/drive_c/windows/system32");
+    if dll_path.exists() {
+        let dlls = ["d3d11.dll", "d3d10.dll", "d3d9.dll"];
+        dlls.iter().any(|dll| dll_path.join(dll).exists())
+    } else {
+        false
+    }
+}
+
+fn has_vkd3d(prefix_path: &Path) -> bool {
+    let dll_path = prefix_path.join("pfx/drive_c/windows/system32");
+    dll_path.join("d3d12.dll").exists()
+}
+
+pub fn collect_prefix_info(prefix_path: &Path) -> PrefixInfo {
+    PrefixInfo {
+        version: detect_proton_version(prefix_path),
+        has_dxvk: has_dxvk(prefix_path),
+        has_vkd3d: has_vkd3d(prefix_path),
+    }
+}
+
+/// Where a relocated prefix lives, if `prefix` is a symlink.
+pub fn relocated_target(prefix: &Path) -> Option<PathBuf> {
+    fs::symlink_metadata(prefix)
+        .ok()
+        .filter(|m| m.file_type().is_symlink())
+        .and_then(|_| fs::read_link(prefix).ok())
+}
+
+/// AppID of a Steam prefix, from its directory name. Other per-game
+/// directories named after the AppID, like those of backups, work too.
+pub fn prefix_appid(prefix: &Path) -> Option<u32> {
+    prefix.file_name()?.to_str()?.parse().ok()
+}
+
+/// Drive letters every Wine prefix maps in `dosdevices`, with the target
+/// Wine gives them.
+pub const DOS_DRIVES: [(&str, &str); 2] = [("c:", "../drive_c"), ("z:", "/")];
+
+/// Registry hives of a Wine prefix.
+pub const REGISTRY_FILES: [&str; 3] = ["system.reg", "user.reg", "userdef.reg"];
+
+/// Whether `path` is a Wine registry file rather than missing, empty or
+/// something else.
+pub fn is_registry_file(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| contents.starts_with("WINE REGISTRY"))
+}
+
+/// Direct3D 11 and 12 DLLs, provided by DXVK and vkd3d-proton in Proton
+/// prefixes, without which most games do not start.
+pub const D3D_DLLS: [&str; 2] = ["d3d11.dll", "d3d12.dll"];
+
+/// System directories of a Wine prefix: `system32` always and `syswow64`
+/// in 64-bit prefixes.
+pub fn system_dirs(wine_prefix: &Path) -> Vec<&'static str> {
+    let mut dirs = vec!["drive_c/windows/system32"];
+    if wine_prefix.join("drive_c/windows/syswow64").is_dir() {
+        dirs.push("drive_c/windows/syswow64");
+    }
+    dirs
+}
+
+/// Symlinks below a Wine prefix whose target does not exist, relative to
+/// the prefix. `dosdevices` is left out: its drive links are checked on
+/// their own and may point at removable media.
+pub fn broken_links(wine_prefix: &Path) -> Vec<PathBuf> {
+    fn walk(root: &Path, dir: &Path, found: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_symlink() {
+                if fs::metadata(&path).is_err() {
+                    found.push(path.strip_prefix(root).unwrap_or(&path).to_path_buf());
+                }
+            } else if file_type.is_dir() && path != root.join("dosdevices") {
+                walk(root, &path, found);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    walk(wine_prefix, wine_prefix, &mut found);
+    found.sort();
+    found
+}
+
+/// How much a problem found in a Wine prefix matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Wine starts, but some games may misbehave.
+    Warning,
+    /// Wine cannot use the prefix as it is.
+    Error,
+}
+
+/// A problem found by [`check_wine_prefix`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Check that a Wine prefix (the directory holding `drive_c`) has the files
+/// Wine needs to start. Returns each problem found with its severity.
+pub fn check_wine_prefix(wine_prefix: &Path) -> Vec<Problem> {
+    use Severity::{Error, Warning};
+
+    let mut problems = Vec::new();
+    if !wine_prefix.is_dir() {
+        problems.push(Problem::new(Error, format!("{} does not exist", wine_prefix.display())));
+        return problems;
+    }
+    for dir in ["drive_c", "drive_c/windows/system32", "dosdevices"] {
+        if !wine_prefix.join(dir).is_dir() {
+            problems.push(Problem::new(Error, format!("missing directory {}", dir)));
+        }
+    }
+    let dosdevices = wine_prefix.join("dosdevices");
+    if dosdevices.is_dir() {
+        for (drive, _) in DOS_DRIVES {
+            let link = dosdevices.join(drive);
+            if fs::symlink_metadata(&link).is_err() {
+                problems.push(Problem::new(Error, format!("missing drive link dosdevices/{}", drive)));
+            } else if fs::metadata(&link).is_err() {
+                problems.push(Problem::new(
+                    Error,
+                    format!("drive link dosdevices/{} points nowhere", drive),
+                ));
+            }
+        }
+    }
+    if wine_prefix.join("drive_c/windows/system32").is_dir() {
+        for dir in system_dirs(wine_prefix) {
+            for dll in D3D_DLLS {
+                if !wine_prefix.join(dir).join(dll).exists() {
+                    problems.push(Problem::new(Warning, format!("missing DLL {}/{}", dir, dll)));
+                }
+            }
+        }
+    }
+    for reg in REGISTRY_FILES {
+        let path = wine_prefix.join(reg);
+        if !path.exists() {
+            problems.push(Problem::new(Error, format!("missing registry file {}", reg)));
+        } else if !is_registry_file(&path) {
+            problems.push(Problem::new(Error, format!("{} is not a Wine registry file", reg)));
+        }
+    }
+    match broken_links(wine_prefix).len() {
+        0 => {}
+        1 => problems.push(Problem::new(Warning, "1 broken symlink")),
+        n => problems.push(Problem::new(Warning, format!("{} broken symlinks", n))),
+    }
+    problems
+}
+
+/// Like [`check_wine_prefix`], with only the description of each problem.
+pub fn validate_wine_prefix(wine_prefix: &Path) -> Vec<String> {
+    check_wine_prefix(wine_prefix)
+        .iter()
+        .map(Problem::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_wine_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path();
+        let system32 = prefix.join("drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        for dll in D3D_DLLS {
+            fs::write(system32.join(dll), "dll").unwrap();
+        }
+        fs::create_dir_all(prefix.join("dosdevices")).unwrap();
+        std::os::unix::fs::symlink("../drive_c", prefix.join("dosdevices/c:")).unwrap();
+        std::os::unix::fs::symlink("/", prefix.join("dosdevices/z:")).unwrap();
+        for reg in ["system.reg", "user.reg", "userdef.reg"] {
+            fs::write(prefix.join(reg), "WINE REGISTRY Version 2\n").unwrap();
+        }
+        assert!(validate_wine_prefix(prefix).is_empty());
+
+        fs::remove_file(prefix.join("dosdevices/z:")).unwrap();
+        std::os::unix::fs::symlink("/nonexistent", prefix.join("dosdevices/z:")).unwrap();
+        assert_eq!(
+            validate_wine_prefix(prefix),
+            vec!["drive link dosdevices/z: points nowhere".to_string()]
+        );
+
+        fs::remove_file(prefix.join("dosdevices/z:")).unwrap();
+        std::os::unix::fs::symlink("/", prefix.join("dosdevices/z:")).unwrap();
+        fs::remove_file(system32.join("d3d11.dll")).unwrap();
+        std::os::unix::fs::symlink("/nonexistent/d3d11.dll", system32.join("d3d11.dll")).unwrap();
+        assert_eq!(broken_links(prefix), vec![PathBuf::from("drive_c/windows/system32/d3d11.dll")]);
+        assert_eq!(
+            validate_wine_prefix(prefix),
+            vec![
+                "missing DLL drive_c/windows/system32/d3d11.dll".to_string(),
+                "1 broken symlink".to_string(),
+            ]
+        );
+        assert!(check_wine_prefix(prefix).iter().all(|p| p.severity == Severity::Warning));
+        fs::remove_file(system32.join("d3d11.dll")).unwrap();
+        fs::write(system32.join("d3d11.dll"), "dll").unwrap();
+        fs::create_dir_all(prefix.join("drive_c/windows/syswow64")).unwrap();
+        assert_eq!(validate_wine_prefix(prefix).len(), 2);
+        fs::remove_dir(prefix.join("drive_c/windows/syswow64")).unwrap();
+        assert!(check_wine_prefix(prefix).is_empty());
+
+        fs::write(prefix.join("user.reg"), "").unwrap();
+        fs::remove_dir_all(prefix.join("dosdevices")).unwrap();
+        let problems = check_wine_prefix(prefix);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().any(|p| p.message.contains("user.reg")));
+        assert!(problems.iter().all(|p| p.severity == Severity::Error));
+    }
+}
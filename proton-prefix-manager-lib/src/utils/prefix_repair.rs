@@ -0,0 +1,433 @@
+//! Repairs of Wine prefixes.
+//!
+//! Each step checks one part of a Wine prefix (the directory holding
+//! `drive_c`), fixes what is broken and returns a description of every
+//! change, so callers can report exactly what was done. Running
+//! [`prefix_info::validate_wine_prefix`](crate::utils::prefix_info::validate_wine_prefix)
+//! afterwards shows whether anything is left.
+
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+
+use crate::error::Result;
+use crate::utils::backup;
+use crate::utils::prefix_info::{self, DOS_DRIVES, REGISTRY_FILES};
+
+/// Recreate the `c:` and `z:` links in `dosdevices` when they are missing
+/// or point nowhere. Without them Wine cannot resolve paths on the drives,
+/// which games often report as a full disk or a missing file.
+pub fn fix_dosdevices(wine_prefix: &Path) -> Result<Vec<String>> {
+    let dosdevices = wine_prefix.join("dosdevices");
+    fs::create_dir_all(&dosdevices)?;
+    let mut fixes = Vec::new();
+    for (drive, target) in DOS_DRIVES {
+        let link = dosdevices.join(drive);
+        let change = match fs::symlink_metadata(&link) {
+            Err(_) => "created",
+            Ok(meta) if meta.file_type().is_symlink() && fs::metadata(&link).is_err() => {
+                fs::remove_file(&link)?;
+                "recreated"
+            }
+            Ok(_) => continue,
+        };
+        std::os::unix::fs::symlink(target, &link)?;
+        fixes.push(format!("{} dosdevices/{} -> {}", change, drive, target));
+    }
+    Ok(fixes)
+}
+
+/// Registry hives of the prefix that are missing, empty or not a Wine
+/// registry file.
+pub fn damaged_hives(wine_prefix: &Path) -> Vec<&'static str> {
+    REGISTRY_FILES
+        .into_iter()
+        .filter(|reg| !prefix_info::is_registry_file(&wine_prefix.join(reg)))
+        .collect()
+}
+
+/// When a file was last written and how long before `now`, in words.
+fn describe_age(modified: SystemTime, now: SystemTime) -> String {
+    let days = now
+        .duration_since(modified)
+        .map(|age| age.as_secs() / 86400)
+        .unwrap_or(0);
+    let ago = match days {
+        0 => "today".to_string(),
+        1 => "1 day ago".to_string(),
+        n => format!("{} days ago", n),
+    };
+    let modified: DateTime<Local> = modified.into();
+    format!("{}, {}", modified.format("%Y-%m-%d %H:%M"), ago)
+}
+
+/// Replace every damaged registry hive with the first good copy in
+/// `copies`, backups or snapshots of the Steam prefix (the directory
+/// holding `pfx`) ordered newest first. Copies whose hive is damaged too
+/// are skipped, and hives without a good copy are left alone. Each fix
+/// names the copy used and when its hive was last written, as an old copy
+/// undoes every setting changed since.
+pub fn fix_registry(wine_prefix: &Path, copies: &[PathBuf]) -> Result<Vec<String>> {
+    let now = SystemTime::now();
+    let mut fixes = Vec::new();
+    for reg in damaged_hives(wine_prefix) {
+        let Some(source) = copies
+            .iter()
+            .map(|copy| copy.join("pfx").join(reg))
+            .find(|source| prefix_info::is_registry_file(source))
+        else {
+            log::warn!("No good copy of {} to restore", reg);
+            continue;
+        };
+        let target = wine_prefix.join(reg);
+        // The file may be hard-linked into a snapshot, so replace it
+        // instead of writing into it.
+        if fs::symlink_metadata(&target).is_ok() {
+            fs::remove_file(&target)?;
+        }
+        fs::copy(&source, &target)?;
+        let age = fs::metadata(&source)
+            .and_then(|meta| meta.modified())
+            .map(|modified| format!(" (last written {})", describe_age(modified, now)))
+            .unwrap_or_default();
+        fixes.push(format!("restored {} from {}{}", reg, source.display(), age));
+    }
+    Ok(fixes)
+}
+
+/// DLLs Proton installs from DXVK and vkd3d-proton, by the name of their
+/// directory in Proton's Wine build. The first one is the DLL whose
+/// absence makes the whole set get reinstated.
+const D3D_SETS: [(&str, &[&str]); 2] = [
+    ("dxvk", &["d3d11.dll", "d3d10core.dll", "d3d9.dll", "dxgi.dll"]),
+    ("vkd3d-proton", &["d3d12.dll", "d3d12core.dll"]),
+];
+
+/// Directories of `dist` that may hold the DLLs of `set` for the given
+/// system directory. Proton 9 and later keep them in per-architecture
+/// subdirectories of `lib/wine/<set>`, older versions in `lib64/wine/<set>`
+/// for 64-bit and `lib/wine/<set>` for 32-bit.
+fn d3d_sources(dist: &Path, set: &str, bits64: bool) -> [PathBuf; 2] {
+    if bits64 {
+        [
+            dist.join("lib/wine").join(set).join("x86_64-windows"),
+            dist.join("lib64/wine").join(set),
+        ]
+    } else {
+        [
+            dist.join("lib/wine").join(set).join("i386-windows"),
+            dist.join("lib/wine").join(set),
+        ]
+    }
+}
+
+/// Where [`fix_d3d_dlls`] should keep the files it replaces in the prefix
+/// of `appid`: a new timestamped directory below the backup root.
+pub fn dll_backup_dir(appid: u32) -> PathBuf {
+    backup::backup_root()
+        .join("replaced-dlls")
+        .join(appid.to_string())
+        .join(Local::now().format("%Y%m%d%H%M%S").to_string())
+}
+
+/// Move `path` below `backup_dir`, keeping its path relative to the prefix.
+/// Symlinks are saved as symlinks.
+fn save_replaced(path: &Path, relative: &Path, backup_dir: &Path) -> Result<PathBuf> {
+    let saved = backup_dir.join(relative);
+    if let Some(parent) = saved.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::read_link(path) {
+        Ok(target) => std::os::unix::fs::symlink(target, &saved)?,
+        Err(_) => {
+            fs::copy(path, &saved)?;
+        }
+    }
+    fs::remove_file(path)?;
+    Ok(saved)
+}
+
+/// Reinstate the DXVK or vkd3d-proton DLLs of a system directory missing
+/// its `d3d11.dll` or `d3d12.dll` by copying them from `dist`, the Wine
+/// directory of the game's Proton. The files they replace are moved into
+/// `backup_dir` first. Returns a description of each DLL installed.
+pub fn fix_d3d_dlls(wine_prefix: &Path, dist: &Path, backup_dir: &Path) -> Result<Vec<String>> {
+    let dirs = prefix_info::system_dirs(wine_prefix);
+    let mut fixes = Vec::new();
+    for dir in &dirs {
+        // In a 64-bit prefix system32 holds the 64-bit DLLs.
+        let bits64 = dirs.len() == 2 && dir.ends_with("system32");
+        for (set, dlls) in D3D_SETS {
+            let target_dir = wine_prefix.join(dir);
+            if target_dir.join(dlls[0]).exists() {
+                continue;
+            }
+            let Some(source_dir) = d3d_sources(dist, set, bits64)
+                .into_iter()
+                .find(|d| d.join(dlls[0]).is_file())
+            else {
+                log::warn!("No {} DLLs for {} in {}", set, dir, dist.display());
+                continue;
+            };
+            for dll in dlls.iter() {
+                let source = source_dir.join(dll);
+                if !source.is_file() {
+                    continue;
+                }
+                let relative = Path::new(dir).join(dll);
+                let target = target_dir.join(dll);
+                let mut fix = format!("installed {} from {}", relative.display(), source.display());
+                if fs::symlink_metadata(&target).is_ok() {
+                    let saved = save_replaced(&target, &relative, backup_dir)?;
+                    fix.push_str(&format!(" (replaced file saved to {})", saved.display()));
+                }
+                fs::copy(&source, &target)?;
+                fixes.push(fix);
+            }
+        }
+    }
+    Ok(fixes)
+}
+
+/// What to do about a symlink whose target is gone. `link` is relative to
+/// the Wine prefix.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkFix {
+    /// Point the link at the same file in the current Proton build.
+    Repoint {
+        link: PathBuf,
+        old: PathBuf,
+        new: PathBuf,
+    },
+    /// Delete the link, as there is nothing to point it at.
+    Remove { link: PathBuf, old: PathBuf },
+}
+
+impl fmt::Display for LinkFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkFix::Repoint { link, old, new } => write!(
+                f,
+                "re-point {}: {} -> {}",
+                link.display(),
+                old.display(),
+                new.display()
+            ),
+            LinkFix::Remove { link, old } => {
+                write!(f, "remove {} (pointed to {})", link.display(), old.display())
+            }
+        }
+    }
+}
+
+/// Where `target` would be in `dist`, when it points into the `files` or
+/// `dist` directory of some other Proton build.
+fn dist_equivalent(target: &Path, dist: &Path) -> Option<PathBuf> {
+    let components: Vec<Component> = target.components().collect();
+    let start = components
+        .iter()
+        .rposition(|c| matches!(c.as_os_str().to_str(), Some("files" | "dist")))?;
+    let rest: PathBuf = components[start + 1..].iter().collect();
+    if rest.as_os_str().is_empty() {
+        return None;
+    }
+    let candidate = dist.join(rest);
+    candidate.exists().then_some(candidate)
+}
+
+/// Work out a fix for every broken symlink in the prefix without changing
+/// anything. Links into the dist files of a Proton build that is gone are
+/// re-pointed to the same file in `dist`, the Wine directory of the Proton
+/// the game uses now, when it has one; all others are removed.
+pub fn plan_broken_links(wine_prefix: &Path, dist: Option<&Path>) -> Vec<LinkFix> {
+    prefix_info::broken_links(wine_prefix)
+        .into_iter()
+        .filter_map(|link| {
+            let old = fs::read_link(wine_prefix.join(&link)).ok()?;
+            Some(match dist.and_then(|dist| dist_equivalent(&old, dist)) {
+                Some(new) => LinkFix::Repoint { link, old, new },
+                None => LinkFix::Remove { link, old },
+            })
+        })
+        .collect()
+}
+
+/// Apply fixes from [`plan_broken_links`]. Links that have changed since
+/// the plan was made are left alone. Returns a description of each change.
+pub fn fix_broken_links(wine_prefix: &Path, fixes: &[LinkFix]) -> Result<Vec<String>> {
+    let mut done = Vec::new();
+    for fix in fixes {
+        let (link, old) = match fix {
+            LinkFix::Repoint { link, old, .. } | LinkFix::Remove { link, old } => (link, old),
+        };
+        let path = wine_prefix.join(link);
+        if fs::read_link(&path).ok().as_ref() != Some(old) || path.exists() {
+            continue;
+        }
+        fs::remove_file(&path)?;
+        if let LinkFix::Repoint { new, .. } = fix {
+            std::os::unix::fs::symlink(new, &path)?;
+        }
+        done.push(fix.to_string());
+    }
+    Ok(done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::prefix_info::{validate_wine_prefix, D3D_DLLS};
+    use std::time::Duration;
+
+    #[test]
+    fn test_fix_dosdevices() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path();
+        fs::create_dir_all(prefix.join("drive_c/windows/system32")).unwrap();
+        for dll in D3D_DLLS {
+            fs::write(prefix.join("drive_c/windows/system32").join(dll), "dll").unwrap();
+        }
+        for reg in ["system.reg", "user.reg", "userdef.reg"] {
+            fs::write(prefix.join(reg), "WINE REGISTRY Version 2\n").unwrap();
+        }
+        fs::create_dir_all(prefix.join("dosdevices")).unwrap();
+        std::os::unix::fs::symlink("../old_drive_c", prefix.join("dosdevices/c:")).unwrap();
+        assert_eq!(validate_wine_prefix(prefix).len(), 2);
+
+        let fixes = fix_dosdevices(prefix).unwrap();
+        assert_eq!(
+            fixes,
+            vec![
+                "recreated dosdevices/c: -> ../drive_c".to_string(),
+                "created dosdevices/z: -> /".to_string(),
+            ]
+        );
+        assert!(validate_wine_prefix(prefix).is_empty());
+        assert!(fix_dosdevices(prefix).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fix_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("620/pfx");
+        fs::create_dir_all(&prefix).unwrap();
+        for reg in REGISTRY_FILES {
+            fs::write(prefix.join(reg), "WINE REGISTRY Version 2\n;; live\n").unwrap();
+        }
+        // The snapshot taken right before the repair has the damage too.
+        let snapshot = dir.path().join("snapshots/620/pre-repair-20240102000000");
+        let backup = dir.path().join("backups/620/20240101000000");
+        for copy in [&snapshot, &backup] {
+            fs::create_dir_all(copy.join("pfx")).unwrap();
+        }
+        fs::write(snapshot.join("pfx/system.reg"), "").unwrap();
+        let saved = backup.join("pfx/system.reg");
+        fs::write(&saved, "WINE REGISTRY Version 2\n;; backup\n").unwrap();
+        let written = SystemTime::now() - Duration::from_secs(3 * 86400 + 60);
+        fs::File::options()
+            .write(true)
+            .open(&saved)
+            .unwrap()
+            .set_modified(written)
+            .unwrap();
+        fs::write(prefix.join("system.reg"), "").unwrap();
+        fs::write(prefix.join("user.reg"), "garbage").unwrap();
+        assert_eq!(damaged_hives(&prefix), vec!["system.reg", "user.reg"]);
+        let copies = vec![snapshot, backup];
+
+        let fixes = fix_registry(&prefix, &copies).unwrap();
+        assert_eq!(fixes.len(), 1);
+        assert!(fixes[0].starts_with(&format!("restored system.reg from {}", saved.display())));
+        assert!(fixes[0].ends_with(", 3 days ago)"));
+        assert_eq!(
+            fs::read_to_string(prefix.join("system.reg")).unwrap(),
+            "WINE REGISTRY Version 2\n;; backup\n"
+        );
+        // Without a good copy the hive is left for wineboot to replace.
+        assert_eq!(fs::read_to_string(prefix.join("user.reg")).unwrap(), "garbage");
+        assert!(validate_wine_prefix(&prefix).contains(&"user.reg is not a Wine registry file".to_string()));
+        assert!(fix_registry(&prefix, &copies).unwrap().is_empty());
+        assert_eq!(damaged_hives(&prefix), vec!["user.reg"]);
+    }
+
+    #[test]
+    fn test_fix_d3d_dlls() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("pfx");
+        let system32 = prefix.join("drive_c/windows/system32");
+        let syswow64 = prefix.join("drive_c/windows/syswow64");
+        fs::create_dir_all(&system32).unwrap();
+        fs::create_dir_all(&syswow64).unwrap();
+        fs::write(system32.join("dxgi.dll"), "wine dxgi").unwrap();
+        fs::write(system32.join("d3d12.dll"), "vkd3d").unwrap();
+        fs::write(syswow64.join("d3d11.dll"), "dxvk32").unwrap();
+        fs::write(syswow64.join("d3d12.dll"), "vkd3d32").unwrap();
+
+        let dist = dir.path().join("Proton 9.0/files");
+        let dxvk64 = dist.join("lib/wine/dxvk/x86_64-windows");
+        fs::create_dir_all(&dxvk64).unwrap();
+        for dll in ["d3d11.dll", "dxgi.dll"] {
+            fs::write(dxvk64.join(dll), format!("dxvk {}", dll)).unwrap();
+        }
+        let backup_dir = dir.path().join("replaced");
+
+        let fixes = fix_d3d_dlls(&prefix, &dist, &backup_dir).unwrap();
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fs::read_to_string(system32.join("d3d11.dll")).unwrap(), "dxvk d3d11.dll");
+        assert_eq!(fs::read_to_string(system32.join("dxgi.dll")).unwrap(), "dxvk dxgi.dll");
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("drive_c/windows/system32/dxgi.dll")).unwrap(),
+            "wine dxgi"
+        );
+        assert_eq!(fs::read_to_string(syswow64.join("d3d11.dll")).unwrap(), "dxvk32");
+        assert!(!prefix_info::validate_wine_prefix(&prefix)
+            .iter()
+            .any(|p| p.starts_with("missing DLL")));
+        assert!(fix_d3d_dlls(&prefix, &dist, &backup_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fix_broken_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("pfx");
+        let system32 = prefix.join("drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        let dist = dir.path().join("Proton 9.0/files");
+        let wine_lib = dist.join("lib/wine/x86_64-windows");
+        fs::create_dir_all(&wine_lib).unwrap();
+        fs::write(wine_lib.join("d3d11.dll"), "dll").unwrap();
+        let old_lib = dir.path().join("Proton 5.0/dist/lib/wine/x86_64-windows");
+        std::os::unix::fs::symlink(old_lib.join("d3d11.dll"), system32.join("d3d11.dll")).unwrap();
+        std::os::unix::fs::symlink(old_lib.join("gone.dll"), system32.join("gone.dll")).unwrap();
+        std::os::unix::fs::symlink(wine_lib.join("d3d11.dll"), system32.join("ok.dll")).unwrap();
+
+        let plan = plan_broken_links(&prefix, Some(&dist));
+        assert_eq!(
+            plan,
+            vec![
+                LinkFix::Repoint {
+                    link: PathBuf::from("drive_c/windows/system32/d3d11.dll"),
+                    old: old_lib.join("d3d11.dll"),
+                    new: wine_lib.join("d3d11.dll"),
+                },
+                LinkFix::Remove {
+                    link: PathBuf::from("drive_c/windows/system32/gone.dll"),
+                    old: old_lib.join("gone.dll"),
+                },
+            ]
+        );
+        // Planning is a dry run.
+        assert_eq!(prefix_info::broken_links(&prefix).len(), 2);
+
+        let done = fix_broken_links(&prefix, &plan).unwrap();
+        assert_eq!(done.len(), 2);
+        assert!(done[0].starts_with("re-point drive_c/windows/system32/d3d11.dll"));
+        assert_eq!(fs::read_to_string(system32.join("d3d11.dll")).unwrap(), "dll");
+        assert!(fs::symlink_metadata(system32.join("gone.dll")).is_err());
+        assert!(system32.join("ok.dll").exists());
+        assert!(plan_broken_links(&prefix, Some(&dist)).is_empty());
+    }
+}
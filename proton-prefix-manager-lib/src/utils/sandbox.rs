@@ -0,0 +1,95 @@
+//! Running inside a Flatpak sandbox.
+//!
+//! A sandboxed build sees the user's home directory, given the permission,
+//! but none of the host's programs: Wine, Proton, protontricks and the
+//! terminal all have to be started on the host through
+//! `flatpak-spawn --host`. Environment variables and the working directory
+//! do not reach the host on their own, so [`host_command`] passes them as
+//! options of `flatpak-spawn`.
+
+use once_cell::sync::Lazy;
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+static FLATPAK: Lazy<bool> = Lazy::new(|| Path::new("/.flatpak-info").exists());
+
+/// Whether this process runs inside a Flatpak sandbox.
+pub fn in_flatpak() -> bool {
+    *FLATPAK
+}
+
+fn through_flatpak_spawn(cmd: &Command) -> Command {
+    let mut host = Command::new("flatpak-spawn");
+    host.arg("--host");
+    for (key, value) in cmd.get_envs() {
+        let mut arg = OsString::from(if value.is_some() { "--env=" } else { "--unset-env=" });
+        arg.push(key);
+        if let Some(value) = value {
+            arg.push("=");
+            arg.push(value);
+        }
+        host.arg(arg);
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        let mut arg = OsString::from("--directory=");
+        arg.push(dir);
+        host.arg(arg);
+    }
+    host.arg(cmd.get_program()).args(cmd.get_args());
+    host
+}
+
+/// `cmd` the way it has to be run to reach the host: unchanged outside a
+/// sandbox and through `flatpak-spawn --host` inside one. Arguments added
+/// afterwards still reach the program, environment variables do not.
+pub fn host_command(cmd: Command) -> Command {
+    if in_flatpak() {
+        through_flatpak_spawn(&cmd)
+    } else {
+        cmd
+    }
+}
+
+/// Whether `program` can be run on the host from inside the sandbox.
+pub fn host_program_available(program: &str) -> bool {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", "command -v \"$1\"", "sh", program]);
+    through_flatpak_spawn(&cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_through_flatpak_spawn() {
+        let mut cmd = Command::new("winecfg");
+        cmd.arg("/v")
+            .env("WINEPREFIX", "/home/deck/pfx")
+            .env_remove("WINEDEBUG")
+            .current_dir("/tmp");
+        let host = through_flatpak_spawn(&cmd);
+        assert_eq!(host.get_program(), "flatpak-spawn");
+        let mut args: Vec<&std::ffi::OsStr> = host.get_args().collect();
+        // The order of the environment is unspecified.
+        args[1..3].sort();
+        assert_eq!(
+            args,
+            [
+                "--host",
+                "--env=WINEPREFIX=/home/deck/pfx",
+                "--unset-env=WINEDEBUG",
+                "--directory=/tmp",
+                "winecfg",
+                "/v"
+            ]
+        );
+        assert_eq!(host.get_envs().count(), 0);
+    }
+}
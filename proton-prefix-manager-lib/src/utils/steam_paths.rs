@@ -3,11 +3,17 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::utils::overrides;
+
 /// Return possible base directories for Steam installations.
 ///
 /// Checks common locations under the user's home directory and
-/// returns any that exist, deduplicated using canonical paths.
+/// returns any that exist, deduplicated using canonical paths. A Steam
+/// root override replaces the search.
 pub fn steam_base_dirs() -> Vec<PathBuf> {
+    if let Some(root) = overrides::get().steam_root {
+        return vec![root];
+    }
     let mut dirs = Vec::new();
     let mut seen = HashSet::new();
 
@@ -18,6 +24,9 @@ pub fn steam_base_dirs() -> Vec<PathBuf> {
             home.join(".steam/root"),
             home.join(".steam/debian-installation"),
             home.join(".steam"),
+            // Steam installed as a Flatpak.
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+            home.join(".var/app/com.valvesoftware.Steam/.steam/steam"),
         ];
 
         for cand in candidates.iter() {
@@ -51,6 +60,9 @@ pub fn userdata_dirs() -> Vec<PathBuf> {
 
 /// Generate config directory paths for all detected Steam bases.
 pub fn config_dirs() -> Vec<PathBuf> {
+    if let Some(root) = overrides::get().steam_root {
+        return vec![root.join("config")];
+    }
     let mut dirs = Vec::new();
     let mut seen = HashSet::new();
 
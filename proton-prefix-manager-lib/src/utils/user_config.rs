@@ -1,6 +1,7 @@
-use crate::utils::steam_paths;
-use keyvalues_parser::{Value, Vdf};
+use crate::utils::{steam_paths, vdf_history};
+use keyvalues_parser::{Obj, Value, Vdf};
 use once_cell::sync::Lazy;
+use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::io;
@@ -122,7 +123,7 @@ fn most_recent_user_id() -> Option<String> {
     None
 }
 
-fn find_localconfig_files() -> Vec<PathBuf> {
+pub fn find_localconfig_files() -> Vec<PathBuf> {
     let mut files = Vec::new();
     let recent = most_recent_user_id();
     for dir in steam_paths::userdata_dirs() {
@@ -195,10 +196,8 @@ pub fn expected_localconfig_path() -> Option<PathBuf> {
     default_localconfig_path()
 }
 
-#[cfg(test)]
-#[allow(dead_code)]
-fn parse_compat_tool(contents: &str, app_id: u32) -> Option<String> {
-    let vdf = Vdf::parse(contents).ok()?;
+/// The `CompatToolOverrides` section of a parsed localconfig.vdf.
+fn compat_tool_overrides<'a>(vdf: &'a Vdf<'a>) -> Option<&'a Obj<'a>> {
     let mut root = vdf.value.get_obj()?;
 
     if let Some(obj) = root
@@ -222,8 +221,12 @@ fn parse_compat_tool(contents: &str, app_id: u32) -> Option<String> {
         .get("CompatToolOverrides")?
         .first()?
         .get_obj()?;
+    Some(overrides)
+}
 
-    overrides
+fn parse_compat_tool(contents: &str, app_id: u32) -> Option<String> {
+    let vdf = Vdf::parse(contents).ok()?;
+    compat_tool_overrides(&vdf)?
         .get(app_id.to_string().as_str())?
         .first()?
         .get_obj()?
@@ -233,9 +236,12 @@ fn parse_compat_tool(contents: &str, app_id: u32) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-#[cfg(test)]
-#[allow(dead_code)]
+/// Compatibility tool of `app_id`, from config.vdf's `CompatToolMapping`
+/// or else the user's `CompatToolOverrides`.
 pub fn get_compat_tool(app_id: u32) -> Option<String> {
+    if let Some(tool) = get_compat_tool_mapping(app_id) {
+        return Some(tool);
+    }
     for cfg in find_localconfig_files() {
         match read_localconfig_cached(&cfg) {
             Some(contents) => {
@@ -319,7 +325,7 @@ pub fn set_compat_tool(app_id: u32, value: &str) -> io::Result<()> {
         match read_localconfig_cached(&cfg) {
             Some(contents) => {
                 if let Some(updated) = update_compat_tool(&contents, app_id, Some(value)) {
-                    match fs::write(&cfg, &updated) {
+                    match vdf_history::write(&cfg, &updated) {
                         Ok(_) => {
                             update_localconfig_cache(&cfg, &updated);
                             return Ok(());
@@ -334,7 +340,7 @@ pub fn set_compat_tool(app_id: u32, value: &str) -> io::Result<()> {
     if let Some(cfg) = default_localconfig_path() {
         fs::create_dir_all(cfg.parent().unwrap())?;
         if let Some(updated) = update_compat_tool("", app_id, Some(value)) {
-            fs::write(&cfg, &updated)?;
+            vdf_history::write(&cfg, &updated)?;
             update_localconfig_cache(&cfg, &updated);
             return Ok(());
         }
@@ -359,7 +365,7 @@ pub fn clear_compat_tool(app_id: u32) -> io::Result<()> {
         match read_localconfig_cached(&cfg) {
             Some(contents) => {
                 if let Some(updated) = update_compat_tool(&contents, app_id, None) {
-                    match fs::write(&cfg, &updated) {
+                    match vdf_history::write(&cfg, &updated) {
                         Ok(_) => {
                             update_localconfig_cache(&cfg, &updated);
                             return Ok(());
@@ -375,7 +381,7 @@ pub fn clear_compat_tool(app_id: u32) -> io::Result<()> {
         if cfg.exists() {
             if let Some(contents) = read_localconfig_cached(&cfg) {
                 if let Some(updated) = update_compat_tool(&contents, app_id, None) {
-                    fs::write(&cfg, &updated)?;
+                    vdf_history::write(&cfg, &updated)?;
                     update_localconfig_cache(&cfg, &updated);
                     return Ok(());
                 }
@@ -395,59 +401,212 @@ pub fn clear_compat_tool(app_id: u32) -> io::Result<()> {
     }
 }
 
-fn parse_launch_options(contents: &str, app_id: u32) -> Option<String> {
+/// Steam's `config/config.vdf`, which holds the `CompatToolMapping` Steam
+/// itself reads to pick the Proton version of a game.
+pub fn config_vdf_path() -> Option<PathBuf> {
+    let dirs = steam_paths::config_dirs();
+    dirs.iter()
+        .map(|dir| dir.join("config.vdf"))
+        .find(|path| path.is_file())
+        .or_else(|| dirs.first().map(|dir| dir.join("config.vdf")))
+}
+
+/// Key of `obj` equal to `key` ignoring case. Steam writes both `Valve` and
+/// `valve` in config.vdf.
+fn find_key<'a>(obj: &Obj<'a>, key: &str) -> Option<Cow<'a, str>> {
+    obj.keys().find(|k| k.eq_ignore_ascii_case(key)).cloned()
+}
+
+const COMPAT_TOOL_MAPPING_PATH: [&str; 4] = ["Software", "Valve", "Steam", "CompatToolMapping"];
+
+fn parse_compat_tool_mapping(contents: &str, key: &str) -> Option<String> {
     let vdf = Vdf::parse(contents).ok()?;
-    let mut root = vdf.value.get_obj()?;
+    let mut obj = vdf.value.get_obj()?;
+    for name in COMPAT_TOOL_MAPPING_PATH.iter().chain(&[key]) {
+        obj = obj.get(find_key(obj, name)?.as_ref())?.first()?.get_obj()?;
+    }
+    obj.get("name")?
+        .first()?
+        .get_str()
+        .filter(|name| !name.is_empty())
+        .map(|s| s.to_string())
+}
+
+/// Names of the tools in every entry of `tools`, a `CompatToolMapping` or
+/// `CompatToolOverrides` section.
+fn tool_names(tools: &Obj) -> Vec<String> {
+    tools
+        .values()
+        .filter_map(|entry| entry.first()?.get_obj()?.get("name")?.first()?.get_str())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Every tool in the `CompatToolMapping` of a config.vdf, the default tool
+/// included. `None` if the file cannot be parsed.
+fn parse_mapped_tools(contents: &str) -> Option<Vec<String>> {
+    let vdf = Vdf::parse(contents).ok()?;
+    let mut obj = vdf.value.get_obj()?;
+    for name in COMPAT_TOOL_MAPPING_PATH {
+        let Some(key) = find_key(obj, name) else {
+            return Some(Vec::new());
+        };
+        obj = obj.get(key.as_ref())?.first()?.get_obj()?;
+    }
+    Some(tool_names(obj))
+}
+
+/// Set or remove the `CompatToolMapping` entry `key` of a config.vdf. An
+/// unparsable file yields `None` rather than being replaced, since it
+/// holds all of Steam's client settings.
+fn update_compat_tool_mapping(contents: &str, key: &str, value: Option<&str>) -> Option<String> {
+    let mut vdf = if contents.trim().is_empty() {
+        Vdf::new("InstallConfigStore".into(), Value::Obj(Default::default()))
+    } else {
+        Vdf::parse(contents).ok()?
+    };
+    let mut obj = vdf.value.get_mut_obj()?;
+    for name in COMPAT_TOOL_MAPPING_PATH {
+        let name = find_key(obj, name).unwrap_or_else(|| name.into());
+        obj = obj
+            .entry(name)
+            .or_insert_with(|| vec![Value::Obj(Default::default())])
+            .first_mut()
+            .and_then(Value::get_mut_obj)?;
+    }
+
+    match value {
+        Some(tool) => {
+            let mut entry = Obj::new();
+            entry.insert("name".into(), vec![Value::Str(tool.to_string().into())]);
+            entry.insert("config".into(), vec![Value::Str("".into())]);
+            entry.insert("priority".into(), vec![Value::Str("250".into())]);
+            obj.insert(key.to_string().into(), vec![Value::Obj(entry)]);
+        }
+        None => {
+            obj.remove(key);
+        }
+    }
+
+    Some(format!("{}", vdf))
+}
+
+fn compat_tool_mapping(key: &str) -> Option<String> {
+    let contents = fs::read_to_string(config_vdf_path()?).ok()?;
+    parse_compat_tool_mapping(&contents, key)
+}
+
+fn write_compat_tool_mapping(key: &str, value: Option<&str>) -> io::Result<()> {
+    let path = config_vdf_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Steam config directory not found"))?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let updated = update_compat_tool_mapping(&contents, key, value).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("failed to parse {}", path.display()),
+        )
+    })?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    vdf_history::write(&path, updated)
+}
+
+/// Compatibility tool Steam uses for `app_id` according to config.vdf.
+pub fn get_compat_tool_mapping(app_id: u32) -> Option<String> {
+    compat_tool_mapping(&app_id.to_string())
+}
+
+/// Make Steam run `app_id` with `value` by writing its `CompatToolMapping`
+/// entry in config.vdf.
+pub fn set_compat_tool_mapping(app_id: u32, value: &str) -> io::Result<()> {
+    write_compat_tool_mapping(&app_id.to_string(), Some(value))
+}
+
+/// Remove the `CompatToolMapping` entry of `app_id`, so Steam falls back to
+/// the default compatibility tool.
+pub fn clear_compat_tool_mapping(app_id: u32) -> io::Result<()> {
+    write_compat_tool_mapping(&app_id.to_string(), None)
+}
+
+/// Compatibility tool Steam uses for every game without its own mapping,
+/// set under Steam Play in Steam's settings. Stored as AppID `0`.
+pub fn get_default_compat_tool() -> Option<String> {
+    compat_tool_mapping("0")
+}
+
+/// Every compatibility tool some game is set to use: the `CompatToolMapping`
+/// of config.vdf, the default tool included, and the `CompatToolOverrides`
+/// of each localconfig.vdf. `None` when config.vdf cannot be read, since
+/// then it is unknown which tools are in use.
+pub fn used_compat_tools() -> Option<Vec<String>> {
+    let contents = fs::read_to_string(config_vdf_path()?).ok()?;
+    let mut tools = parse_mapped_tools(&contents)?;
+    for cfg in find_localconfig_files() {
+        let Some(contents) = read_localconfig_cached(&cfg) else {
+            continue;
+        };
+        let vdf = Vdf::parse(&contents).ok();
+        if let Some(overrides) = vdf.as_ref().and_then(compat_tool_overrides) {
+            tools.extend(tool_names(overrides));
+        }
+    }
+    tools.sort();
+    tools.dedup();
+    Some(tools)
+}
+
+/// Change the default compatibility tool of all games.
+pub fn set_default_compat_tool(value: &str) -> io::Result<()> {
+    write_compat_tool_mapping("0", Some(value))
+}
+
+/// Section of localconfig.vdf, below `UserLocalConfigStore`, with the
+/// launch options of each game.
+const LAUNCH_OPTIONS_PATH: [&str; 4] = ["Software", "Valve", "Steam", "apps"];
+
+/// Section of localconfig.vdf with the overlay and Steam Input settings of
+/// each game.
+const APP_SETTINGS_PATH: [&str; 1] = ["apps"];
+
+/// `key` of `app_id` in the per-game section at `path` of a localconfig.vdf.
+fn parse_app_value(contents: &str, path: &[&str], app_id: u32, key: &str) -> Option<String> {
+    let vdf = Vdf::parse(contents).ok()?;
+    let mut obj = vdf.value.get_obj()?;
 
     // Handle both single and nested `UserLocalConfigStore` keys
-    if let Some(obj) = root
+    if let Some(inner) = obj
         .get("UserLocalConfigStore")
         .and_then(|v| v.first())
         .and_then(Value::get_obj)
     {
-        root = obj;
+        obj = inner;
     }
 
-    let apps = root
-        .get("Software")?
-        .first()?
-        .get_obj()?
-        .get("Valve")?
-        .first()?
-        .get_obj()?
-        .get("Steam")?
-        .first()?
-        .get_obj()?
-        .get("apps")?
-        .first()?
-        .get_obj()?;
-    apps.get(app_id.to_string().as_str())?
+    for name in path {
+        obj = obj.get(*name)?.first()?.get_obj()?;
+    }
+    obj.get(app_id.to_string().as_str())?
         .first()?
         .get_obj()?
-        .get("LaunchOptions")?
+        .get(key)?
         .first()?
         .get_str()
         .map(|s| s.to_string())
 }
 
-pub fn get_launch_options(app_id: u32) -> Option<String> {
-    for cfg in find_localconfig_files() {
-        match read_localconfig_cached(&cfg) {
-            Some(contents) => {
-                log::debug!("read localconfig {:?} successfully", cfg);
-                if let Some(val) = parse_launch_options(&contents, app_id) {
-                    return Some(val);
-                }
-            }
-            None => {
-                log::debug!("failed to read {:?}", cfg);
-            }
-        }
-    }
-    None
-}
-
-fn update_launch_options(contents: &str, app_id: u32, value: &str) -> Option<String> {
+/// Set `key` of `app_id` in the per-game section at `path` of a
+/// localconfig.vdf, creating the sections that are missing.
+fn update_app_value(
+    contents: &str,
+    path: &[&str],
+    app_id: u32,
+    key: &str,
+    value: &str,
+) -> Option<String> {
     // Parse the existing VDF or create a new one if parsing fails
     let mut vdf = Vdf::parse(contents).unwrap_or_else(|_| {
         Vdf::new(
@@ -472,47 +631,62 @@ fn update_launch_options(contents: &str, app_id: u32, value: &str) -> Option<Str
         }
     };
 
-    // Walk or create the nested hierarchy down to the apps object
-
-    for key in ["Software", "Valve", "Steam", "apps"] {
+    // Walk or create the nested hierarchy down to the section of the game
+    let app_key = app_id.to_string();
+    for name in path.iter().copied().chain([app_key.as_str()]) {
         obj = obj
-            .entry(key.into())
+            .entry(name.to_string().into())
             .or_insert_with(|| vec![Value::Obj(Default::default())])
             .first_mut()
             .and_then(Value::get_mut_obj)
             .unwrap();
     }
 
-    let entry = obj
-        .entry(app_id.to_string().into())
-        .or_insert_with(|| vec![Value::Obj(Default::default())]);
-    let app_obj = entry.first_mut().and_then(Value::get_mut_obj).unwrap();
-
-    match app_obj.get_mut("LaunchOptions") {
+    match obj.get_mut(key) {
         Some(vals) if !vals.is_empty() => {
             if let Some(s) = vals.first_mut().and_then(Value::get_mut_str) {
                 *s.to_mut() = value.to_string();
             }
         }
         _ => {
-            app_obj.insert("LaunchOptions".into(), vec![Value::Str(value.into())]);
+            obj.insert(key.to_string().into(), vec![Value::Str(value.to_string().into())]);
         }
     }
 
     Some(format!("{}", vdf))
 }
 
-pub fn set_launch_options(app_id: u32, value: &str) -> io::Result<()> {
+/// `key` of `app_id` from the first localconfig.vdf that has it.
+fn get_app_value(path: &[&str], app_id: u32, key: &str) -> Option<String> {
+    for cfg in find_localconfig_files() {
+        match read_localconfig_cached(&cfg) {
+            Some(contents) => {
+                log::debug!("read localconfig {:?} successfully", cfg);
+                if let Some(val) = parse_app_value(&contents, path, app_id, key) {
+                    return Some(val);
+                }
+            }
+            None => {
+                log::debug!("failed to read {:?}", cfg);
+            }
+        }
+    }
+    None
+}
+
+/// Set `key` of `app_id` in the user's localconfig.vdf, creating the file
+/// if there is none yet.
+fn set_app_value(path: &[&str], app_id: u32, key: &str, value: &str) -> io::Result<()> {
     let mut found = false;
     for cfg in find_localconfig_files() {
         found = true;
         match read_localconfig_cached(&cfg) {
             Some(contents) => {
                 log::debug!("read localconfig {:?} successfully", cfg);
-                if let Some(updated) = update_launch_options(&contents, app_id, value) {
-                    match fs::write(&cfg, &updated) {
+                if let Some(updated) = update_app_value(&contents, path, app_id, key, value) {
+                    match vdf_history::write(&cfg, &updated) {
                         Ok(_) => {
-                            log::debug!("wrote launch options to {:?}", cfg);
+                            log::debug!("wrote {} to {:?}", key, cfg);
                             update_localconfig_cache(&cfg, &updated);
                             return Ok(());
                         }
@@ -530,10 +704,10 @@ pub fn set_launch_options(app_id: u32, value: &str) -> io::Result<()> {
     }
     if let Some(cfg) = default_localconfig_path() {
         fs::create_dir_all(cfg.parent().unwrap())?;
-        if let Some(updated) = update_launch_options("", app_id, value) {
-            fs::write(&cfg, &updated)?;
+        if let Some(updated) = update_app_value("", path, app_id, key, value) {
+            vdf_history::write(&cfg, &updated)?;
             update_localconfig_cache(&cfg, &updated);
-            log::debug!("created {:?} with launch options", cfg);
+            log::debug!("created {:?} with {}", cfg, key);
             return Ok(());
         }
     }
@@ -550,6 +724,79 @@ pub fn set_launch_options(app_id: u32, value: &str) -> io::Result<()> {
     }
 }
 
+pub fn get_launch_options(app_id: u32) -> Option<String> {
+    get_app_value(&LAUNCH_OPTIONS_PATH, app_id, "LaunchOptions")
+}
+
+pub fn set_launch_options(app_id: u32, value: &str) -> io::Result<()> {
+    set_app_value(&LAUNCH_OPTIONS_PATH, app_id, "LaunchOptions", value)
+}
+
+/// Whether the Steam overlay is enabled in `app_id`, or None when the game
+/// follows the global setting.
+pub fn get_overlay_enabled(app_id: u32) -> Option<bool> {
+    get_app_value(&APP_SETTINGS_PATH, app_id, "OverlayAppEnable").map(|v| v != "0")
+}
+
+/// Enable or disable the Steam overlay in `app_id`.
+pub fn set_overlay_enabled(app_id: u32, enabled: bool) -> io::Result<()> {
+    let value = if enabled { "1" } else { "0" };
+    set_app_value(&APP_SETTINGS_PATH, app_id, "OverlayAppEnable", value)
+}
+
+/// Whether Steam Input translates the controller for a game, stored as
+/// `UseSteamControllerConfig` in localconfig.vdf.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SteamInput {
+    /// Steam decides from its global settings and the game's defaults.
+    #[default]
+    Default,
+    /// The game reads the controller directly.
+    ForceOff,
+    /// Steam Input is on even for controllers it leaves alone otherwise.
+    ForceOn,
+}
+
+impl SteamInput {
+    pub const ALL: [SteamInput; 3] = [SteamInput::Default, SteamInput::ForceOff, SteamInput::ForceOn];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SteamInput::Default => "Default",
+            SteamInput::ForceOff => "Forced off",
+            SteamInput::ForceOn => "Forced on",
+        }
+    }
+
+    fn from_value(value: &str) -> Self {
+        match value {
+            "0" => SteamInput::ForceOff,
+            "2" => SteamInput::ForceOn,
+            _ => SteamInput::Default,
+        }
+    }
+
+    fn value(&self) -> &'static str {
+        match self {
+            SteamInput::ForceOff => "0",
+            SteamInput::Default => "1",
+            SteamInput::ForceOn => "2",
+        }
+    }
+}
+
+/// The Steam Input preference of `app_id`.
+pub fn get_steam_input(app_id: u32) -> SteamInput {
+    get_app_value(&APP_SETTINGS_PATH, app_id, "UseSteamControllerConfig")
+        .map(|v| SteamInput::from_value(&v))
+        .unwrap_or_default()
+}
+
+/// Change the Steam Input preference of `app_id`.
+pub fn set_steam_input(app_id: u32, value: SteamInput) -> io::Result<()> {
+    set_app_value(&APP_SETTINGS_PATH, app_id, "UseSteamControllerConfig", value.value())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -684,9 +931,10 @@ mod tests {
     fn test_update_launch_options_creates_section() {
         let _guard = TEST_MUTEX.lock().unwrap();
         let contents = "";
-        let updated = update_launch_options(contents, 123, "-novid").unwrap();
+        let updated =
+            update_app_value(contents, &LAUNCH_OPTIONS_PATH, 123, "LaunchOptions", "-novid").unwrap();
         assert_eq!(
-            parse_launch_options(&updated, 123),
+            parse_app_value(&updated, &LAUNCH_OPTIONS_PATH, 123, "LaunchOptions"),
             Some("-novid".to_string())
         );
     }
@@ -711,6 +959,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_overlay_and_steam_input() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let (home, _prefix, _login) = crate::test_helpers::setup_steam_env(123456, true);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+        let cfg_path = home
+            .path()
+            .join(".steam/steam/userdata/111111111/config/localconfig.vdf");
+        fs::create_dir_all(cfg_path.parent().unwrap()).unwrap();
+        fs::write(
+            &cfg_path,
+            r#""UserLocalConfigStore"
+{
+    "apps"
+    {
+        "123456"
+        {
+            "OverlayAppEnable"      "1"
+            "UseSteamControllerConfig"      "2"
+        }
+    }
+}
+"#,
+        )
+        .unwrap();
+        clear_localconfig_cache();
+
+        assert_eq!(get_overlay_enabled(123456), Some(true));
+        assert_eq!(get_overlay_enabled(654321), None);
+        set_overlay_enabled(123456, false).unwrap();
+        set_overlay_enabled(654321, false).unwrap();
+        assert_eq!(get_overlay_enabled(123456), Some(false));
+        assert_eq!(get_overlay_enabled(654321), Some(false));
+        // The launch options have a section of their own.
+        assert_eq!(get_launch_options(123456), None);
+
+        assert_eq!(get_steam_input(123456), SteamInput::ForceOn);
+        assert_eq!(get_steam_input(654321), SteamInput::Default);
+        set_steam_input(123456, SteamInput::ForceOff).unwrap();
+        assert_eq!(get_steam_input(123456), SteamInput::ForceOff);
+        set_steam_input(123456, SteamInput::Default).unwrap();
+        assert_eq!(get_steam_input(123456), SteamInput::Default);
+        assert_eq!(get_overlay_enabled(123456), Some(false));
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
     #[test]
     fn test_update_compat_tool_creates_section() {
         let _guard = TEST_MUTEX.lock().unwrap();
@@ -741,4 +1039,39 @@ mod tests {
             std::env::set_var("HOME", h);
         }
     }
+
+    #[test]
+    fn test_compat_tool_mapping() {
+        let contents = r#""InstallConfigStore"
+{
+	"Software"
+	{
+		"valve"
+		{
+			"Steam"
+			{
+				"AutoUpdateWindowEnabled"		"0"
+			}
+		}
+	}
+}"#;
+        let updated = update_compat_tool_mapping(contents, "620", Some("proton_8")).unwrap();
+        assert_eq!(
+            parse_compat_tool_mapping(&updated, "620"),
+            Some("proton_8".to_string())
+        );
+        assert!(updated.contains("AutoUpdateWindowEnabled"));
+        assert!(!updated.contains("\"Valve\""));
+
+        let cleared = update_compat_tool_mapping(&updated, "620", None).unwrap();
+        assert_eq!(parse_compat_tool_mapping(&cleared, "620"), None);
+        assert!(update_compat_tool_mapping("\"InstallConfigStore\" {", "620", None).is_none());
+
+        assert_eq!(parse_mapped_tools(contents), Some(Vec::new()));
+        let updated = update_compat_tool_mapping(&updated, "0", Some("GE-Proton9-7")).unwrap();
+        let mut tools = parse_mapped_tools(&updated).unwrap();
+        tools.sort();
+        assert_eq!(tools, vec!["GE-Proton9-7".to_string(), "proton_8".to_string()]);
+        assert_eq!(parse_mapped_tools("\"InstallConfigStore\" {"), None);
+    }
 }
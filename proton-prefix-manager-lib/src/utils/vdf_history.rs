@@ -0,0 +1,147 @@
+//! Rolling history of Steam config files written by this crate.
+//!
+//! Every write to `localconfig.vdf` or an appmanifest goes through
+//! [`write`], which first copies the current file into
+//! `<backup root>/vdf-history/<file>/`. [`rollback`] puts the newest copy
+//! back, so a bad write never corrupts Steam's config for good.
+
+use crate::core::steam;
+use crate::utils::{backup, library, user_config};
+use chrono::Local;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Snapshots kept per file; older ones are deleted.
+const KEEP: usize = 10;
+
+fn history_root() -> PathBuf {
+    backup::backup_root().join("vdf-history")
+}
+
+/// Directory below `root` holding the snapshots of `path`, named after its
+/// full path.
+fn history_dir(root: &Path, path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .to_string_lossy()
+        .trim_start_matches('/')
+        .replace('/', "_");
+    root.join(name)
+}
+
+/// Snapshots of `path`, oldest first.
+pub fn snapshots(path: &Path) -> Vec<PathBuf> {
+    snapshots_in(&history_root(), path)
+}
+
+fn snapshots_in(root: &Path, path: &Path) -> Vec<PathBuf> {
+    backup::list_backups_in(&history_dir(root, path))
+}
+
+/// Copy the current contents of `path` into its history. Does nothing when
+/// the file doesn't exist yet.
+pub fn snapshot(path: &Path) -> io::Result<Option<PathBuf>> {
+    snapshot_in(&history_root(), path)
+}
+
+fn snapshot_in(root: &Path, path: &Path) -> io::Result<Option<PathBuf>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let dir = history_dir(root, path);
+    fs::create_dir_all(&dir)?;
+    let mut dest = dir.join(Local::now().format("%Y%m%d%H%M%S%6f").to_string());
+    // Two writes within the same microsecond keep both snapshots in order.
+    while dest.exists() {
+        let mut name = dest.file_name().unwrap().to_os_string();
+        name.push("-");
+        dest = dir.join(name);
+    }
+    fs::copy(path, &dest)?;
+    let all = snapshots_in(root, path);
+    for old in &all[..all.len().saturating_sub(KEEP)] {
+        fs::remove_file(old)?;
+    }
+    Ok(Some(dest))
+}
+
+/// Snapshot `path` and write `contents` to it.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    snapshot(path)?;
+    fs::write(path, contents)
+}
+
+/// Restore `path` from its newest snapshot and drop that snapshot, so
+/// repeated rollbacks walk further back. Returns the snapshot used.
+pub fn rollback(path: &Path) -> io::Result<PathBuf> {
+    rollback_in(&history_root(), path)
+}
+
+fn rollback_in(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let latest = snapshots_in(root, path).pop().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no earlier version of {}", path.display()),
+        )
+    })?;
+    fs::copy(&latest, path)?;
+    fs::remove_file(&latest)?;
+    library::clear_manifest_cache();
+    user_config::clear_localconfig_cache();
+    Ok(latest)
+}
+
+/// Files written when the settings of `appid` are changed: its appmanifest,
+/// every `localconfig.vdf` and Steam's `config.vdf`.
+pub fn app_files(appid: u32) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = steam::find_manifest(appid).into_iter().collect();
+    files.extend(user_config::find_localconfig_files());
+    files.extend(user_config::config_vdf_path());
+    files
+}
+
+/// Roll back the last change to the settings of `appid`. Returns the files
+/// that were restored.
+pub fn rollback_app(appid: u32) -> io::Result<Vec<PathBuf>> {
+    let mut restored = Vec::new();
+    for file in app_files(appid) {
+        if !snapshots(&file).is_empty() {
+            rollback(&file)?;
+            restored.push(file);
+        }
+    }
+    if restored.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no earlier configuration of {}", appid),
+        ));
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("history");
+        let file = dir.path().join("appmanifest_620.acf");
+
+        assert_eq!(snapshot_in(&root, &file).unwrap(), None);
+        fs::write(&file, "v1").unwrap();
+        for v in 2..=13 {
+            snapshot_in(&root, &file).unwrap();
+            fs::write(&file, format!("v{}", v)).unwrap();
+        }
+        assert_eq!(snapshots_in(&root, &file).len(), KEEP);
+
+        rollback_in(&root, &file).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v12");
+        rollback_in(&root, &file).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v11");
+        assert_eq!(snapshots_in(&root, &file).len(), KEEP - 2);
+    }
+}
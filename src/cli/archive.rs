@@ -0,0 +1,79 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::steam;
+use crate::utils::{app_config, archive};
+
+fn archive_one(appid: u32, prefix: &std::path::Path) {
+    match archive::archive_prefix(appid, prefix) {
+        Ok(path) => println!("✅ Prefix for {} archived to {}", appid, path.display()),
+        Err(e) => eprintln!("❌ Failed to archive prefix for {}: {}", appid, e),
+    }
+}
+
+pub fn execute(appid: Option<u32>, inactive: Option<u32>, dry_run: bool) {
+    log::debug!(
+        "archive command: appid={:?} inactive={:?} dry_run={}",
+        appid,
+        inactive,
+        dry_run
+    );
+    let libraries = match steam::get_steam_libraries() {
+        Ok(libraries) => libraries,
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+            return;
+        }
+    };
+
+    if let Some(appid) = appid {
+        match steam::find_proton_prefix(appid, &libraries) {
+            Some(prefix) if !dry_run => archive_one(appid, &prefix),
+            Some(prefix) => println!("Would archive {}", prefix.display()),
+            None => println!("❌ Proton prefix not found for AppID: {}", appid),
+        }
+        return;
+    }
+
+    let config = app_config::load();
+    let months = inactive.unwrap_or(config.archive.inactive_months);
+    let mut games = match steam::load_games_from_libraries(&libraries) {
+        Ok(games) => games,
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+            return;
+        }
+    };
+    games.retain(|g| !config.general.is_excluded(g.app_id()));
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let candidates = archive::inactive_games(&games, months, now);
+    if candidates.is_empty() {
+        println!("✅ No prefixes of games unplayed for {} months", months);
+        return;
+    }
+    for game in candidates {
+        if dry_run {
+            println!("📦 {} ({}): {}", game.name(), game.app_id(), game.prefix_path().display());
+        } else {
+            println!("📦 Archiving {} ({})", game.name(), game.app_id());
+            archive_one(game.app_id(), game.prefix_path());
+        }
+    }
+}
+
+pub fn unarchive(appid: u32) {
+    log::debug!("unarchive command: appid={}", appid);
+    let prefix = match steam::compatdata_dir(appid) {
+        Ok(prefix) => prefix,
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+            return;
+        }
+    };
+    match archive::unarchive_prefix(appid, &prefix) {
+        Ok(path) => println!("✅ Prefix restored to {}", path.display()),
+        Err(e) => eprintln!("❌ Failed to unarchive prefix: {}", e),
+    }
+}
@@ -1,15 +1,69 @@
 use crate::core::steam;
 use crate::utils::backup as backup_utils;
+use crate::utils::hooks;
+use crate::utils::output;
+use crate::utils::save_locations;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-pub fn execute(appid: u32) {
-    log::debug!("backup command: appid={}", appid);
+/// Show the copy progress on one line, updated once per percent.
+pub(super) fn show_progress(last: &AtomicU64, copied: u64, total: u64) {
+    let percent = (copied * 100).checked_div(total).unwrap_or(100);
+    if last.swap(percent, Ordering::Relaxed) != percent {
+        print!(
+            "\r⏳ {}% ({} of {})",
+            percent,
+            output::format_size(copied),
+            output::format_size(total)
+        );
+        let _ = std::io::stdout().flush();
+    }
+}
+
+pub fn execute(appid: u32, saves_only: bool, shader_cache: bool, remote: bool) {
+    log::debug!(
+        "backup command: appid={} saves_only={} shader_cache={} remote={}",
+        appid,
+        saves_only,
+        shader_cache,
+        remote
+    );
     println!("📦 Backing up Proton prefix for AppID: {}", appid);
 
     match steam::get_steam_libraries() {
         Ok(libraries) => {
             if let Some(prefix_path) = steam::find_proton_prefix(appid, &libraries) {
-                match backup_utils::create_backup(&prefix_path, appid) {
-                    Ok(path) => println!("✅ Backup created at {}", path.display()),
+                let result = hooks::backup(&prefix_path, || {
+                    if saves_only {
+                        save_locations::detect_prefix_locations(appid, &prefix_path).and_then(
+                            |locations| {
+                                for location in &locations {
+                                    println!("💾 Including {}", location.display());
+                                }
+                                backup_utils::create_saves_backup(&prefix_path, appid, &locations)
+                            },
+                        )
+                    } else {
+                        let last = AtomicU64::new(u64::MAX);
+                        let result = backup_utils::create_backup_with_progress(
+                            &prefix_path,
+                            appid,
+                            shader_cache,
+                            &|copied, total| show_progress(&last, copied, total),
+                        );
+                        if last.load(Ordering::Relaxed) != u64::MAX {
+                            println!();
+                        }
+                        result
+                    }
+                });
+                match result {
+                    Ok(path) => {
+                        println!("✅ Backup created at {}", path.display());
+                        if remote {
+                            super::remote::push(appid, Some(&path));
+                        }
+                    }
                     Err(e) => eprintln!("❌ Failed to back up prefix: {}", e),
                 }
             } else {
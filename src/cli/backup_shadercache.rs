@@ -0,0 +1,17 @@
+use crate::core::steam;
+use crate::utils::shader_cache;
+
+pub fn execute(appid: u32) {
+    log::debug!("backup-shadercache command: appid={}", appid);
+    println!("📦 Backing up shader cache for AppID: {}", appid);
+
+    match steam::get_steam_libraries() {
+        Ok(libraries) => match shader_cache::create_backup(appid, &libraries) {
+            Ok(path) => println!("✅ Backup created at {}", path.display()),
+            Err(e) => eprintln!("❌ Failed to back up shader cache: {}", e),
+        },
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+        }
+    }
+}
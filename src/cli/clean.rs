@@ -0,0 +1,110 @@
+use crate::utils::app_config;
+use crate::utils::output;
+use crate::utils::runtime_cleaner::{self, RuntimeItem};
+use std::path::{Path, PathBuf};
+
+fn print_item(item: &RuntimeItem) {
+    let size = item.size.map(output::format_size).unwrap_or_default();
+    let unverified = if item.verified { "" } else { " [unverified]" };
+    println!(
+        "   {:>10}  {}  {}{}",
+        size,
+        item.path.display(),
+        item.reason,
+        unverified
+    );
+}
+
+pub fn execute(delete: bool, export: Option<&Path>) {
+    log::debug!("clean command: delete={} export={:?}", delete, export);
+
+    let results = runtime_cleaner::scan();
+    if let Some(file) = export {
+        match runtime_cleaner::export_report(&results, file) {
+            Ok(()) => println!("📝 Report written to {}", file.display()),
+            Err(e) => {
+                // Nothing is deleted without the report that was asked for.
+                eprintln!("❌ Failed to write {}: {}", file.display(), e);
+                return;
+            }
+        }
+    }
+    let mut found = false;
+    for (title, items) in results.groups() {
+        if items.is_empty() {
+            continue;
+        }
+        found = true;
+        let sizes: Vec<u64> = items.iter().filter_map(|i| i.size).collect();
+        if sizes.is_empty() {
+            println!("🗑️ {}", title);
+        } else {
+            println!("🗑️ {} ({})", title, output::format_size(sizes.iter().sum()));
+        }
+        for item in items.iter() {
+            print_item(item);
+        }
+    }
+    if results.ignored > 0 {
+        println!("🙈 {} ignored item(s) not shown", results.ignored);
+    }
+    if !found {
+        println!("✅ Nothing to clean up");
+        return;
+    }
+
+    if delete {
+        // Like the GUI, only what is selected by default: unverified items
+        // have to be deleted by hand.
+        let mut deleted = 0;
+        let mut freed = 0;
+        for (_, items) in results.groups() {
+            for item in items.iter().filter(|i| i.selected) {
+                match runtime_cleaner::delete_item(item) {
+                    Ok(()) => {
+                        deleted += 1;
+                        freed += item.size.unwrap_or(0);
+                    }
+                    Err(e) => eprintln!("❌ Failed to delete {}: {}", item.path.display(), e),
+                }
+            }
+        }
+        println!("✅ Deleted {} item(s), {} freed", deleted, output::format_size(freed));
+    }
+}
+
+/// Add paths to and remove paths from the ignore list of the config.
+pub fn set_ignored(ignore: &[PathBuf], unignore: &[PathBuf]) {
+    log::debug!("clean command: ignore={:?} unignore={:?}", ignore, unignore);
+
+    let mut config = app_config::load();
+    let mut changed = Vec::new();
+    for (paths, ignored) in [(ignore, true), (unignore, false)] {
+        for path in paths {
+            // Scans report absolute paths, so relative ones would never match.
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            if config.cleaner.set_ignored(&path, ignored) {
+                changed.push((path, ignored));
+            } else if ignored {
+                println!("🙈 {} is already ignored", path.display());
+            } else {
+                println!("🙈 {} is not ignored", path.display());
+            }
+        }
+    }
+    if changed.is_empty() {
+        return;
+    }
+    match app_config::save(&config) {
+        Ok(_) => {
+            for (path, ignored) in changed {
+                if ignored {
+                    println!("✅ Ignoring {} in future scans", path.display());
+                } else {
+                    println!("✅ No longer ignoring {}", path.display());
+                }
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
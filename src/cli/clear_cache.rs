@@ -1,13 +1,79 @@
 use crate::core::steam;
 use crate::utils::backup as backup_utils;
+use crate::utils::output;
+use crate::utils::shader_cache::{self, CacheKind};
 
-pub fn execute(appid: u32) {
-    log::debug!("clear-cache command: appid={}", appid);
+pub fn execute(
+    appid: u32,
+    dxvk: bool,
+    fozpipelines: bool,
+    mesa: bool,
+    prefix_caches: bool,
+    driver_caches: bool,
+) {
+    log::debug!(
+        "clear-cache command: appid={}, dxvk={}, fozpipelines={}, mesa={}, prefix_caches={}, driver_caches={}",
+        appid,
+        dxvk,
+        fozpipelines,
+        mesa,
+        prefix_caches,
+        driver_caches
+    );
+    let libs = match steam::get_steam_libraries() {
+        Ok(libs) => libs,
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return;
+        }
+    };
+    let mut kinds: Vec<CacheKind> = [
+        (dxvk, CacheKind::Dxvk),
+        (fozpipelines, CacheKind::Fossilize),
+        (mesa, CacheKind::Mesa),
+        (prefix_caches, CacheKind::Prefix),
+    ]
+    .into_iter()
+    .filter_map(|(selected, kind)| selected.then_some(kind))
+    .collect();
+
+    if kinds.is_empty() {
+        if let Err(e) = backup_utils::clear_shader_cache(appid, &libs) {
+            eprintln!("Failed to clear shader cache: {}", e);
+            return;
+        }
+        println!("Shader cache cleared");
+        if !driver_caches {
+            return;
+        }
+    }
+    // The driver caches are shared by all games, so only on request.
+    if driver_caches {
+        kinds.push(CacheKind::Driver);
+    }
+    match shader_cache::clear(appid, &libs, &kinds) {
+        Ok(freed) => {
+            let names: Vec<&str> = kinds.iter().map(|k| k.label()).collect();
+            println!("Cleared {} ({} freed)", names.join(", "), output::format_size(freed));
+        }
+        Err(e) => eprintln!("Failed to clear shader cache: {}", e),
+    }
+}
+
+pub fn sizes(appid: u32) {
+    log::debug!("clear-cache command: appid={}, sizes", appid);
     match steam::get_steam_libraries() {
-        Ok(libs) => match backup_utils::clear_shader_cache(appid, &libs) {
-            Ok(_) => println!("Shader cache cleared"),
-            Err(e) => eprintln!("Failed to clear shader cache: {}", e),
-        },
+        Ok(libs) => {
+            let sizes = shader_cache::sizes(appid, &libs);
+            if sizes.is_empty() {
+                println!("No shader cache found for {}", appid);
+                return;
+            }
+            for (kind, size) in &sizes {
+                println!("{:<22} {}", kind.label(), output::format_size(*size));
+            }
+            println!("{:<22} {}", "Total", output::format_size(sizes.values().sum()));
+        }
         Err(e) => eprintln!("❌ Error: {}", e),
     }
 }
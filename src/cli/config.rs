@@ -1,7 +1,10 @@
 use crate::core::steam;
 use crate::utils::manifest as manifest_utils;
+use crate::utils::{app_config, config_undo, history, launch_options, profile};
 use crate::utils::user_config;
+use crate::utils::vdf_history;
 use std::fs;
+use std::path::Path;
 
 pub fn execute(
     appid: u32,
@@ -9,20 +12,52 @@ pub fn execute(
     proton: Option<String>,
     cloud: Option<bool>,
     auto_update: Option<String>,
+    overlay: Option<bool>,
+    apply_profile: Option<String>,
 ) {
     log::debug!(
-        "config command: appid={} launch={:?} proton={:?} cloud={:?} auto_update={:?}",
+        "config command: appid={} launch={:?} proton={:?} cloud={:?} auto_update={:?} overlay={:?} apply_profile={:?}",
         appid,
         launch,
         proton,
         cloud,
-        auto_update
+        auto_update,
+        overlay,
+        apply_profile
     );
-    if launch.is_none() && proton.is_none() && cloud.is_none() && auto_update.is_none() {
+    if launch.is_none()
+        && proton.is_none()
+        && cloud.is_none()
+        && auto_update.is_none()
+        && overlay.is_none()
+        && apply_profile.is_none()
+    {
         println!("No configuration changes specified.");
         return;
     }
 
+    if let Some(name) = apply_profile {
+        let Some(p) = app_config::load().profiles.remove(&name) else {
+            eprintln!("❌ Profile '{}' not found in {}", name, app_config::config_path().display());
+            return;
+        };
+        match profile::import_profile(appid, &p) {
+            Ok(_) => println!("✅ Applied profile '{}' to {}", name, appid),
+            Err(e) => {
+                eprintln!("❌ Error: {}", e);
+                return;
+            }
+        }
+        if launch.is_none()
+            && proton.is_none()
+            && cloud.is_none()
+            && auto_update.is_none()
+            && overlay.is_none()
+        {
+            return;
+        }
+    }
+
     match steam::get_steam_libraries() {
         Ok(libraries) => {
             for lib in libraries {
@@ -32,26 +67,42 @@ pub fn execute(
                 if manifest.exists() {
                     match fs::read_to_string(&manifest) {
                         Ok(mut contents) => {
+                            let mut changes = Vec::new();
                             if let Some(v) = launch {
+                                // Keep the variables managed with `env` in place.
+                                let settings = app_config::load().game(appid);
+                                let v = launch_options::materialize(appid, &v, &settings, &settings);
                                 contents = manifest_utils::update_or_insert(&contents, "LaunchOptions", &v);
+                                changes.push(format!("launch options: {}", v));
                                 if let Err(e) = user_config::set_launch_options(appid, &v) {
                                     eprintln!("Failed to update launch options: {}", e);
                                 }
                             }
                             if let Some(v) = proton {
                                 contents = manifest_utils::update_or_insert(&contents, "CompatToolOverride", &v);
-                                if let Err(e) = user_config::set_compat_tool(appid, &v) {
+                                changes.push(format!("Proton: {}", v));
+                                if let Err(e) = user_config::set_compat_tool_mapping(appid, &v) {
                                     eprintln!("Failed to update compatibility tool: {}", e);
                                 }
                             }
                             if let Some(v) = cloud {
                                 let val = if v { "1" } else { "0" };
                                 contents = manifest_utils::update_or_insert(&contents, "AllowCloudSaves", val);
+                                changes.push(format!("Steam Cloud: {}", if v { "on" } else { "off" }));
                             }
                             if let Some(v) = auto_update {
                                 contents = manifest_utils::update_or_insert(&contents, "AutoUpdateBehavior", &v);
+                                changes.push(format!("auto update: {}", v));
+                            }
+                            if let Some(v) = overlay {
+                                changes.push(format!("Steam overlay: {}", if v { "on" } else { "off" }));
+                                if let Err(e) = user_config::set_overlay_enabled(appid, v) {
+                                    eprintln!("Failed to update the Steam overlay setting: {}", e);
+                                }
                             }
-                            if let Err(e) = fs::write(&manifest, contents) {
+                            let result = vdf_history::write(&manifest, contents);
+                            history::record_result(appid, "config", changes.join(", "), &result);
+                            if let Err(e) = result {
                                 eprintln!("Failed to write manifest: {}", e);
                             } else {
                                 println!("Updated {}", manifest.display());
@@ -69,3 +120,55 @@ pub fn execute(
         Err(e) => eprintln!("❌ Error: {}", e),
     }
 }
+
+/// Write the game's configuration profile to a TOML file.
+pub fn export(appid: u32, file: &Path) {
+    log::debug!("config export command: appid={} file={}", appid, file.display());
+    match profile::export_profile(appid).and_then(|p| profile::save_profile(&p, file)) {
+        Ok(_) => println!("✅ Exported configuration for {} to {}", appid, file.display()),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Apply a configuration profile from a TOML file to the game.
+pub fn import(appid: u32, file: &Path) {
+    log::debug!("config import command: appid={} file={}", appid, file.display());
+    match profile::load_profile(file).and_then(|p| profile::import_profile(appid, &p)) {
+        Ok(_) => println!("✅ Imported {} into configuration for {}", file.display(), appid),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Undo the last configuration change of a game, as recorded in its history.
+pub fn undo(appid: u32) {
+    log::debug!("config undo command: appid={}", appid);
+    match config_undo::undo(appid) {
+        Ok(change) => println!(
+            "↩️ Undid {} from {} ({})",
+            change.operation,
+            change.time(),
+            change.details
+        ),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Undo the last write to a game's Steam config files, or to one file.
+pub fn rollback(target: &str) {
+    log::debug!("config rollback command: target={}", target);
+    let result = match target.parse::<u32>() {
+        Ok(appid) => vdf_history::rollback_app(appid),
+        Err(_) => vdf_history::rollback(Path::new(target)).map(|_| vec![target.into()]),
+    };
+    if let Ok(appid) = target.parse::<u32>() {
+        history::record_result(appid, "rollback", "Steam config files", &result);
+    }
+    match result {
+        Ok(files) => {
+            for file in files {
+                println!("↩️ Restored the previous version of {}", file.display());
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
@@ -0,0 +1,20 @@
+use crate::utils::{proton_runtime, user_config};
+
+pub fn get() {
+    log::debug!("default-proton get command");
+    match user_config::get_default_compat_tool() {
+        Some(tool) => println!("🍷 Default compatibility tool: {}", tool),
+        None => println!("🍷 No default compatibility tool set; Steam picks its own Proton version"),
+    }
+}
+
+pub fn set(version: &str) {
+    log::debug!("default-proton set command: version={}", version);
+    if proton_runtime::find_proton(version).is_none() {
+        println!("⚠️ {} is not installed; Steam will ignore it until it is", version);
+    }
+    match user_config::set_default_compat_tool(version) {
+        Ok(_) => println!("✅ Default compatibility tool set to {}", version),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::utils::diagnostics::{self, Status};
+use crate::utils::library_health::{self, LibraryHealth};
+use crate::utils::json_model::{self, Versioned};
+use crate::utils::output::{self, OutputFormat};
+
+fn space(bytes: Option<u64>) -> String {
+    bytes.map(output::format_size).unwrap_or_else(|| "?".to_string())
+}
+
+fn print_table(report: &[LibraryHealth]) {
+    println!(
+        "{:<40} {:>10} {:>10} {:>8} {:>18} {:>11} {:>11}",
+        "LIBRARY", "FREE", "TOTAL", "PREFIXES", "ORPHANED", "MANIFESTS", "PERMISSIONS"
+    );
+    for lib in report {
+        println!(
+            "{:<40} {:>10} {:>10} {:>8} {:>18} {:>11} {:>11}",
+            lib.path.display(),
+            space(lib.free_space),
+            space(lib.total_space),
+            lib.prefixes,
+            format!(
+                "{} ({})",
+                lib.orphaned.len(),
+                output::format_size(lib.orphaned_size)
+            ),
+            lib.unreadable_manifests.len(),
+            lib.permission_problems.len()
+        );
+    }
+    for lib in report.iter().filter(|l| !l.is_healthy()) {
+        println!();
+        println!("⚠️ {}", lib.path.display());
+        for path in &lib.orphaned {
+            println!("   🗑️ Orphaned: {}", path.display());
+        }
+        for path in &lib.unreadable_manifests {
+            println!("   ❓ Unreadable manifest: {}", path.display());
+        }
+        for problem in &lib.permission_problems {
+            println!("   🔒 {}", problem);
+        }
+    }
+}
+
+pub fn library(json: bool) {
+    log::debug!("doctor command: library json={}", json);
+
+    match library_health::check_all() {
+        Ok(report) if json => {
+            let output = json_model::LibraryReport {
+                libraries: report.iter().map(json_model::Library::from).collect(),
+            };
+            println!("{}", Versioned::new(output).to_json());
+        }
+        Ok(report) => {
+            print_table(&report);
+            if report.iter().all(|l| l.is_healthy()) {
+                println!("✅ All libraries are healthy");
+            }
+        }
+        Err(err) => {
+            let format = if json { OutputFormat::Json } else { OutputFormat::Normal };
+            output::print_error(&err, &format);
+        }
+    }
+}
+
+pub fn environment(json: bool) {
+    log::debug!("doctor command: environment json={}", json);
+
+    let checks = diagnostics::run_checks();
+    if json {
+        let output = json_model::EnvironmentReport {
+            checks: checks.iter().map(json_model::Check::from).collect(),
+        };
+        println!("{}", Versioned::new(output).to_json());
+        return;
+    }
+    for check in &checks {
+        println!("{} {:<14} {}", check.status.icon(), check.name, check.message);
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    if failed + warned == 0 {
+        println!("✅ Everything looks good");
+    } else {
+        println!("{} failed, {} warning(s)", failed, warned);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::utils::{app_config, launch_options};
+
+pub fn execute(appid: u32, set: &[String], unset: &[String]) {
+    log::debug!("env command: appid={} set={:?} unset={:?}", appid, set, unset);
+
+    let mut settings = app_config::load().game(appid);
+    if !set.is_empty() || !unset.is_empty() {
+        for key in unset {
+            settings.env.remove(key);
+        }
+        for entry in set {
+            let Some((key, value)) = entry.split_once('=') else {
+                eprintln!("❌ Error: expected KEY=VALUE, got {}", entry);
+                return;
+            };
+            if !launch_options::valid_env_key(key) {
+                eprintln!("❌ Error: {} is not a valid variable name", key);
+                return;
+            }
+            settings.env.insert(key.to_string(), value.to_string());
+        }
+        match launch_options::apply(appid, &settings) {
+            Ok(launch) => println!("✅ Launch options for {}: {}", appid, launch),
+            Err(e) => {
+                eprintln!("❌ Error: {}", e);
+                return;
+            }
+        }
+    }
+
+    if settings.env.is_empty() {
+        println!("📄 No environment variables stored for {}", appid);
+        return;
+    }
+    println!("📄 Environment variables for {}:", appid);
+    for (key, value) in &settings.env {
+        println!("   {}", launch_options::format_env(key, value));
+    }
+}
@@ -0,0 +1,43 @@
+use super::ExportTarget;
+use crate::utils::prefix_export;
+use std::path::PathBuf;
+
+pub fn execute(appid: u32, target: ExportTarget, dest: Option<PathBuf>, link: bool) {
+    log::debug!(
+        "export command: appid={} target={:?} dest={:?} link={}",
+        appid,
+        target,
+        dest,
+        link
+    );
+
+    let result = match target {
+        ExportTarget::Lutris => {
+            let root = dest.unwrap_or_else(prefix_export::default_lutris_root);
+            println!("📦 Exporting prefix for AppID {} to Lutris in {}", appid, root.display());
+            prefix_export::export_lutris(appid, &root, link)
+        }
+        ExportTarget::Bottles => {
+            let root = dest.unwrap_or_else(prefix_export::default_bottles_root);
+            println!("📦 Exporting prefix for AppID {} to Bottles in {}", appid, root.display());
+            prefix_export::export_bottles(appid, &root, link)
+        }
+    };
+
+    match result {
+        Ok(export) => {
+            println!("✅ Prefix {} at {}", if link { "linked" } else { "copied" }, export.prefix.display());
+            println!("📝 Metadata written to {}", export.metadata.display());
+            match target {
+                ExportTarget::Lutris => println!(
+                    "   Install it with: lutris -i {}",
+                    export.metadata.display()
+                ),
+                ExportTarget::Bottles => {
+                    println!("   Restart Bottles to see the new bottle")
+                }
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use crate::utils::backup as backup_utils;
+use crate::utils::heroic::{self, HeroicGame};
+
+fn find_game(app_name: &str) -> Option<HeroicGame> {
+    let game = heroic::find(app_name);
+    if game.is_none() {
+        println!("❌ No Heroic prefix found for: {}", app_name);
+    }
+    game
+}
+
+pub fn list() {
+    log::debug!("heroic list command");
+    let games = heroic::discover();
+    if games.is_empty() {
+        println!("❌ No Heroic games with a Wine prefix found");
+        return;
+    }
+    for game in games {
+        println!("🎮 {} [{}] ({})", game.title, game.app_name, game.store.label());
+        println!("   📁 {}", game.prefix.display());
+    }
+}
+
+pub fn backup(app_name: &str) {
+    log::debug!("heroic backup command: app_name={}", app_name);
+    let Some(game) = find_game(app_name) else {
+        return;
+    };
+    println!("📦 Backing up Wine prefix for {}", game.title);
+    match backup_utils::create_backup_in(&game.prefix, &game.backup_dir()) {
+        Ok(path) => println!("✅ Backup created at {}", path.display()),
+        Err(e) => eprintln!("❌ Failed to back up prefix: {}", e),
+    }
+}
+
+pub fn restore(app_name: &str, backup_path: &Path) {
+    log::debug!(
+        "heroic restore command: app_name={} backup_path={}",
+        app_name,
+        backup_path.display()
+    );
+    let Some(game) = find_game(app_name) else {
+        return;
+    };
+    println!("♻️ Restoring Wine prefix for {}", game.title);
+    match backup_utils::restore_prefix(backup_path, &game.prefix) {
+        Ok(path) => println!("✅ Prefix restored to {}", path.display()),
+        Err(e) => eprintln!("❌ Failed to restore prefix: {}", e),
+    }
+}
+
+pub fn validate(app_name: &str, strict: bool) -> i32 {
+    log::debug!("heroic validate command: app_name={} strict={}", app_name, strict);
+    let Some(game) = find_game(app_name) else {
+        return super::validate::EXIT_NOT_FOUND;
+    };
+    super::validate::report(&game.wine_prefix(), strict)
+}
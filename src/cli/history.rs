@@ -0,0 +1,23 @@
+use crate::utils::history;
+
+/// Print the operations run on a game, oldest first.
+pub fn execute(appid: u32) {
+    log::debug!("history command: appid={}", appid);
+    let entries = history::load(appid);
+    if entries.is_empty() {
+        println!("📜 No history recorded for {}", appid);
+        return;
+    }
+    for entry in entries {
+        match &entry.error {
+            Some(error) => println!(
+                "❌ {} {} {}: {}",
+                entry.time(),
+                entry.operation,
+                entry.details,
+                error
+            ),
+            None => println!("✅ {} {} {}", entry.time(), entry.operation, entry.details),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::path::Path;
+
+use crate::utils::prefix_import;
+
+pub fn execute(appid: u32, source: &Path) {
+    log::debug!(
+        "import-prefix command: appid={} source={}",
+        appid,
+        source.display()
+    );
+    println!("📥 Importing {} as the Proton prefix for AppID: {}", source.display(), appid);
+
+    match prefix_import::import_prefix(appid, source) {
+        Ok(result) => {
+            if let Some(backup) = result.backup {
+                println!("📦 Previous prefix backed up to {}", backup.display());
+            }
+            println!("✅ Prefix installed at {}", result.prefix.display());
+        }
+        Err(e) => eprintln!("❌ Failed to import prefix: {}", e),
+    }
+}
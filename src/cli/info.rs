@@ -0,0 +1,41 @@
+use crate::utils::json_model::{self, Versioned};
+use crate::utils::{steam_paths, system_info};
+
+pub fn system(json: bool) {
+    log::debug!("info command: system json={}", json);
+
+    let os = system_info::os_name();
+    let kernel = system_info::kernel_version();
+    let steam_root = steam_paths::steam_base_dirs().into_iter().next();
+    let gpus = system_info::vulkan_devices();
+
+    if json {
+        let output = json_model::SystemInfo {
+            os,
+            kernel,
+            steam_root,
+            gpus: gpus.map(|gpus| gpus.iter().map(json_model::Gpu::from).collect()),
+        };
+        println!("{}", Versioned::new(output).to_json());
+        return;
+    }
+
+    let unknown = || "unknown".to_string();
+    println!("🐧 OS: {}", os.unwrap_or_else(unknown));
+    println!("⚙️ Kernel: {}", kernel.unwrap_or_else(unknown));
+    match steam_root {
+        Some(root) => println!("📁 Steam: {}", root.display()),
+        None => println!("📁 Steam: not found"),
+    }
+    match gpus {
+        Some(gpus) if !gpus.is_empty() => {
+            for gpu in gpus {
+                println!("🎮 GPU: {} ({})", gpu.name, gpu.device_type);
+                println!("   Driver: {} {}", gpu.driver_name, gpu.driver_info);
+                println!("   Vulkan: {}", gpu.api_version);
+            }
+        }
+        Some(_) => println!("❌ No Vulkan device found"),
+        None => println!("❌ vulkaninfo is not installed or failed; Vulkan drivers could not be checked"),
+    }
+}
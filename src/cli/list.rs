@@ -0,0 +1,157 @@
+use crate::core::models::GameInfo;
+use crate::core::steam;
+use crate::utils::app_config;
+use crate::utils::json_model::{self, Versioned};
+use crate::utils::library_overview::{self, LibraryOverview};
+use crate::utils::output::{self, OutputFormat};
+
+#[cfg(test)]
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(not(test))]
+fn emit_list(games: Vec<GameInfo>, format: &OutputFormat) {
+    output::print_search_results(games, format);
+}
+
+#[cfg(test)]
+pub static LIST_RESULTS: Lazy<Mutex<Vec<Vec<GameInfo>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[cfg(test)]
+fn emit_list(games: Vec<GameInfo>, _format: &OutputFormat) {
+    LIST_RESULTS.lock().unwrap().push(games);
+}
+
+/// List the installed games, only those tagged `tag` when given.
+pub fn execute(tag: Option<&str>, format: &OutputFormat) {
+    log::debug!("list command: tag={:?} format={:?}", tag, format);
+    let games = steam::get_steam_libraries().and_then(|libs| steam::load_games_from_libraries(&libs));
+    let mut games = match games {
+        Ok(games) => games,
+        Err(err) => {
+            output::print_error(&err, format);
+            return;
+        }
+    };
+    let config = app_config::load();
+    games.retain(|g| !config.general.is_excluded(g.app_id()));
+    if let Some(tag) = tag {
+        let tagged = config.tagged(tag.trim());
+        games.retain(|g| tagged.contains(&g.app_id()));
+    }
+    games.sort_by_key(|g| g.name().to_lowercase());
+    emit_list(games, format);
+}
+
+fn space(bytes: Option<u64>) -> String {
+    bytes.map(output::format_size).unwrap_or_else(|| "?".to_string())
+}
+
+fn print_libraries(libraries: &[LibraryOverview], format: &OutputFormat) {
+    match format {
+        OutputFormat::Normal => {
+            println!(
+                "{:<40} {:>10} {:>10} {:>6} {:>11}",
+                "LIBRARY", "FREE", "TOTAL", "GAMES", "COMPATDATA"
+            );
+            for lib in libraries {
+                println!(
+                    "{:<40} {:>10} {:>10} {:>6} {:>11}",
+                    lib.path.display(),
+                    space(lib.free_space),
+                    space(lib.total_space),
+                    lib.games,
+                    output::format_size(lib.compatdata_size)
+                );
+            }
+            for lib in libraries.iter().filter(|l| l.low_space) {
+                println!("⚠️ Low on free space: {}", lib.path.display());
+            }
+        }
+        OutputFormat::Plain => {
+            for lib in libraries {
+                println!("path={}", lib.path.display());
+                println!("free_space={}", lib.free_space.map(|s| s.to_string()).unwrap_or_default());
+                println!("total_space={}", lib.total_space.map(|s| s.to_string()).unwrap_or_default());
+                println!("games={}", lib.games);
+                println!("compatdata_size={}", lib.compatdata_size);
+                println!("low_space={}", lib.low_space);
+            }
+        }
+        OutputFormat::Json => {
+            let output = json_model::LibrariesOutput {
+                libraries: libraries.iter().map(json_model::LibrarySummary::from).collect(),
+            };
+            println!("{}", Versioned::new(output).to_json());
+        }
+        OutputFormat::Delimited(delimiter) => {
+            for lib in libraries {
+                let fields = [
+                    lib.path.display().to_string(),
+                    lib.free_space.map(|s| s.to_string()).unwrap_or_default(),
+                    lib.total_space.map(|s| s.to_string()).unwrap_or_default(),
+                    lib.games.to_string(),
+                    lib.compatdata_size.to_string(),
+                    lib.low_space.to_string(),
+                ];
+                println!("{}", fields.join(delimiter));
+            }
+        }
+    }
+}
+
+/// List the Steam libraries with their space, games and prefix sizes.
+pub fn libraries(format: &OutputFormat) {
+    log::debug!("list command: libraries format={:?}", format);
+    match library_overview::all() {
+        Ok(libraries) => print_libraries(&libraries, format),
+        Err(err) => output::print_error(&err, format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use std::fs;
+
+    #[test]
+    fn test_list_by_tag() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, _prefix, _) = setup_steam_env(7001, false);
+        let steamapps = home.path().join("library/steamapps");
+        for (appid, name) in [(7001, "Tagged Game"), (7002, "Other Game")] {
+            fs::create_dir_all(steamapps.join(format!("compatdata/{}", appid))).unwrap();
+            fs::write(
+                steamapps.join(format!("appmanifest_{}.acf", appid)),
+                format!("\"AppState\" {{\n    \"appid\" \"{}\"\n    \"name\" \"{}\"\n}}", appid, name),
+            )
+            .unwrap();
+        }
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+        let mut config = app_config::load();
+        config.add_tag(7001, "modded");
+        app_config::save(&config).unwrap();
+
+        LIST_RESULTS.lock().unwrap().clear();
+        execute(None, &OutputFormat::Plain);
+        execute(Some("modded"), &OutputFormat::Plain);
+
+        let results = LIST_RESULTS.lock().unwrap();
+        let ids = |games: &[GameInfo]| games.iter().map(|g| g.app_id()).collect::<Vec<_>>();
+        assert_eq!(ids(&results[0]), [7002, 7001]);
+        assert_eq!(ids(&results[1]), [7001]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+    }
+}
@@ -1,17 +1,54 @@
 use crate::core::steam;
 use crate::utils::backup as backup_utils;
+use crate::utils::backup_format;
+use crate::utils::output;
+use std::path::PathBuf;
 
-pub fn execute(appid: u32) {
-    log::debug!("list-backups command: appid={}", appid);
+/// Print the backups of a game, or of every game when `appid` is `None`.
+/// With `sizes` each backup is shown with its size, followed by per-game
+/// and overall totals.
+pub fn execute(appid: Option<u32>, sizes: bool) {
+    log::debug!("list-backups command: appid={:?} sizes={}", appid, sizes);
     match steam::get_steam_libraries() {
         Ok(_libs) => {
-            let backups = backup_utils::list_backups(appid);
-            if backups.is_empty() {
+            for (backup, e) in backup_format::migrate_all() {
+                eprintln!("⚠️ {}: {}", backup.display(), e);
+            }
+            let games: Vec<(u32, Vec<PathBuf>)> = match appid {
+                Some(appid) => vec![(appid, backup_utils::list_backups(appid))],
+                None => backup_utils::list_all_backups().into_iter().collect(),
+            };
+            if games.iter().all(|(_, backups)| backups.is_empty()) {
                 println!("No backups found");
-            } else {
+                return;
+            }
+            let mut total = 0;
+            for (id, backups) in games {
+                if appid.is_none() {
+                    let name = steam::app_name(id).unwrap_or_else(|| format!("App {}", id));
+                    println!("🎮 {} ({})", name, id);
+                }
+                let mut subtotal = 0;
                 for b in backups {
-                    println!("{}", b.display());
+                    if sizes {
+                        let size = backup_utils::dir_size(&b).unwrap_or(0);
+                        subtotal += size;
+                        println!("{}  {}", output::format_size(size), b.display());
+                    } else {
+                        println!("{}", b.display());
+                    }
                 }
+                if sizes && appid.is_none() {
+                    println!("   Subtotal: {}", output::format_size(subtotal));
+                }
+                total += subtotal;
+            }
+            if sizes {
+                println!(
+                    "💾 Total: {} in {}",
+                    output::format_size(total),
+                    backup_utils::backup_root().display()
+                );
             }
         }
         Err(err) => eprintln!("❌ Error: {}", err),
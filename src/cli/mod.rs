@@ -1,20 +1,54 @@
-use clap::{Parser, Subcommand};
+use crate::utils::logging::{self, LogFormat};
+use crate::utils::proton_runtime::WineTool;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use log::LevelFilter;
 use std::path::PathBuf;
 
+pub mod archive;
 pub mod backup;
+pub mod backup_shadercache;
+pub mod clean;
 pub mod clear_cache;
 pub mod config;
 pub mod config_paths;
+pub mod default_proton;
 pub mod delete_backup;
+pub mod doctor;
+pub mod env;
+pub mod export;
+pub mod heroic;
+pub mod history;
+pub mod import_prefix;
+pub mod info;
+pub mod list;
 pub mod list_backups;
+pub mod move_backups;
+pub mod move_game;
 pub mod open;
+pub mod pin;
 pub mod prefix;
+pub mod proton_ge;
+pub mod proton_settings;
 pub mod protontricks;
+pub mod relocate;
+pub mod remote;
+pub mod repair;
 pub mod reset;
 pub mod restore;
+pub mod restore_saves;
+pub mod restore_shadercache;
+#[cfg(feature = "s3")]
+pub mod s3;
+pub mod saves;
 pub mod search;
+pub mod serve;
+pub mod tag;
+pub mod tool;
 pub mod userdata;
+pub mod validate;
+pub mod wasted_space;
 pub mod winecfg;
+pub mod winetricks;
 
 /// Proton Prefix Manager CLI
 ///
@@ -25,9 +59,41 @@ pub mod winecfg;
 #[command(name = "proton-prefix-manager")]
 #[command(about = "Find and manage Proton prefixes easily", long_about = None)]
 pub struct Cli {
-    /// Enable debug logging
-    #[arg(long, short, global = true)]
-    pub debug: bool,
+    /// Log more details: -v for debug, -vv for trace, -vvv for trace including libraries
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        alias = "debug",
+        short_alias = 'd'
+    )]
+    pub verbose: u8,
+
+    /// Set the log level of one module, e.g. --log user_config=trace
+    #[arg(long, global = true, value_name = "MODULE=LEVEL", value_parser = logging::parse_module_level)]
+    pub log: Vec<(String, LevelFilter)>,
+
+    /// Log as human readable text or as one JSON object per line
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Print the JSON schema of every --json output and exit
+    #[arg(long)]
+    pub schema: bool,
+
+    /// Serve list, backup, restore and validate requests on the session D-Bus
+    #[arg(long, conflicts_with = "schema")]
+    pub service: bool,
+
+    /// Store and look for backups in this directory for this run
+    #[arg(long, global = true, value_name = "DIR")]
+    pub backup_root: Option<PathBuf>,
+
+    /// Use the Steam installation in this directory for this run instead
+    /// of the configured or detected one; also read from STEAM_ROOT
+    #[arg(long, global = true, value_name = "DIR")]
+    pub steam_root: Option<PathBuf>,
 
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -53,6 +119,29 @@ pub enum Commands {
         delimiter: Option<String>,
     },
 
+    /// List installed games, optionally only those with a tag, or the Steam libraries (supports --json, --plain, --delimiter output options)
+    List {
+        /// Only list games with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// List the Steam libraries with their free space, games and prefix sizes instead
+        #[arg(long, conflicts_with = "tag")]
+        libraries: bool,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Output in plain format (no formatting or emojis)
+        #[arg(long)]
+        plain: bool,
+
+        /// Specify custom delimiter for output
+        #[arg(long)]
+        delimiter: Option<String>,
+    },
+
     /// Find the Proton prefix for an installed game (supports --json, --plain, --delimiter output options)
     Prefix {
         /// The Steam App ID of the game
@@ -69,6 +158,10 @@ pub enum Commands {
         /// Specify custom delimiter for output
         #[arg(long)]
         delimiter: Option<String>,
+
+        /// Initialize the prefix with wineboot before the game's first launch
+        #[arg(long, conflicts_with_all = ["json", "plain", "delimiter"])]
+        create: bool,
     },
 
     /// Open the Proton prefix in the file manager
@@ -87,6 +180,18 @@ pub enum Commands {
     Backup {
         /// The Steam App ID of the game
         appid: u32,
+
+        /// Only back up save and config directories detected via PCGamingWiki
+        #[arg(long)]
+        saves_only: bool,
+
+        /// Include the game's shader cache in the backup
+        #[arg(long, conflicts_with = "saves_only")]
+        shader_cache: bool,
+
+        /// Also copy the backup to the remote target of the config file
+        #[arg(long)]
+        remote: bool,
     },
 
     /// Restore the Proton prefix from a backup directory
@@ -96,10 +201,75 @@ pub enum Commands {
 
         /// Path to the backup directory
         path: PathBuf,
+
+        /// Only rewrite files that differ from the backup instead of
+        /// replacing the whole prefix
+        #[arg(long)]
+        delta: bool,
+
+        /// Only restore backup paths matching this glob, e.g.
+        /// `drive_c/users/steamuser` (repeatable)
+        #[arg(long, value_name = "GLOB", conflicts_with = "delta")]
+        only: Vec<String>,
     },
 
-    /// List backups for the given App ID
+    /// Check that a game's Proton prefix is a complete Wine prefix. Exits
+    /// with 0 when it is healthy, 1 when it is not found, 2 when it only
+    /// has warnings and `--strict` is given and 3 when it has errors
+    Validate {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Exit with an error on warnings too, such as missing DXVK DLLs
+        /// or broken symlinks
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Fix broken parts of a game's Proton prefix, such as missing drive
+    /// links in dosdevices, damaged registry hives or missing
+    /// DXVK/vkd3d-proton DLLs
+    Repair {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Also fix symlinks whose target is gone: re-point links into an
+        /// old Proton build to the current one, remove the rest
+        #[arg(long)]
+        broken_links: bool,
+
+        /// Only list the broken links and what would be done about each,
+        /// without changing anything
+        #[arg(long, requires = "broken_links")]
+        dry_run: bool,
+    },
+
+    /// Install an external Wine prefix as the game's Proton prefix
+    ImportPrefix {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Path to the Wine prefix (containing drive_c) or Proton prefix (containing pfx)
+        path: PathBuf,
+    },
+
+    /// List backups for the given App ID, or for every game with --all
     ListBackups {
+        /// The Steam App ID of the game
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        appid: Option<u32>,
+
+        /// List the backups of every game
+        #[arg(long)]
+        all: bool,
+
+        /// Show the size of each backup and the totals
+        #[arg(long)]
+        sizes: bool,
+    },
+
+    /// Show the backups, restores, resets and config changes of a game
+    History {
         /// The Steam App ID of the game
         appid: u32,
     },
@@ -110,16 +280,223 @@ pub enum Commands {
         backup: PathBuf,
     },
 
+    /// Move all backups to a new directory and keep storing them there
+    MoveBackups {
+        /// The new backup directory
+        destination: PathBuf,
+    },
+
+    /// Compress prefixes into the backup store and delete the live copy
+    Archive {
+        /// The Steam App ID of the game
+        #[arg(required_unless_present = "inactive")]
+        appid: Option<u32>,
+
+        /// Archive every game not played for this many months
+        #[arg(long, value_name = "MONTHS", conflicts_with = "appid")]
+        inactive: Option<u32>,
+
+        /// Only list what would be archived
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Restore an archived prefix
+    Unarchive {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Pin a game to the top of the GUI's game list
+    Pin {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Remove a game from the pinned games
+    Unpin {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Copy backups to and from the remote target of the config file
+    Remote {
+        #[command(subcommand)]
+        action: RemoteAction,
+    },
+
+    /// Upload backups to and download them from S3-compatible storage
+    #[cfg(feature = "s3")]
+    S3 {
+        #[command(subcommand)]
+        action: S3Action,
+    },
+
+    /// Add, remove or list user-defined tags of games
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+
+    /// Move the prefix to another directory and leave a symlink in compatdata
+    Relocate {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Directory to move the prefix into, e.g. on another drive
+        #[arg(required_unless_present = "back")]
+        target: Option<PathBuf>,
+
+        /// Move a relocated prefix back into compatdata
+        #[arg(long, conflicts_with = "target")]
+        back: bool,
+    },
+
+    /// Move a game's install folder, prefix, shader cache and Workshop
+    /// content to another Steam library
+    MoveGame {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Root of the Steam library to move the game to
+        library: PathBuf,
+    },
+
     /// Delete the existing prefix
     Reset {
         /// The Steam App ID of the game
         appid: u32,
+
+        /// Back up the detected save locations first and restore them into
+        /// the new prefix once Proton has created it on the next launch
+        #[arg(long, conflicts_with_all = ["user_data", "registry", "software"])]
+        keep_saves: bool,
+
+        /// Only delete the contents of the user profiles in drive_c/users
+        #[arg(long)]
+        user_data: bool,
+
+        /// Only delete the registry files, which Proton recreates
+        #[arg(long)]
+        registry: bool,
+
+        /// Only delete programs installed into Program Files and ProgramData
+        #[arg(long)]
+        software: bool,
+    },
+
+    /// Put the saves kept by `reset --keep-saves` back into the prefixes
+    /// Proton has created since, once their games are closed
+    RestoreSaves,
+
+    /// Back up the shader cache for the given App ID
+    BackupShadercache {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Restore the shader cache from a backup directory
+    RestoreShadercache {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Path to the backup directory (defaults to the latest backup)
+        path: Option<PathBuf>,
+    },
+
+    /// List data left behind by uninstalled games and unfinished downloads
+    /// in all Steam libraries, like the GUI's runtime cleaner
+    Clean {
+        /// Delete the listed items, except those marked unverified
+        #[arg(long)]
+        delete: bool,
+        /// Write everything found to FILE before deleting anything: CSV if
+        /// it ends in .csv, JSON otherwise
+        #[arg(long, value_name = "FILE")]
+        export: Option<PathBuf>,
+        /// Leave this path and everything below it out of future scans;
+        /// may be repeated
+        #[arg(long, value_name = "PATH", conflicts_with = "delete")]
+        ignore: Vec<PathBuf>,
+        /// Take this path off the ignore list; may be repeated
+        #[arg(long, value_name = "PATH", conflicts_with = "delete")]
+        unignore: Vec<PathBuf>,
+    },
+
+    /// Report crash dumps, installer caches and temporary files inside prefixes
+    WastedSpace {
+        /// Only check the prefix of this App ID
+        appid: Option<u32>,
+
+        /// Delete the reported files
+        #[arg(long)]
+        clean: bool,
+    },
+
+    /// Serve a local HTTP API for listing games, prefix details and backups
+    Serve {
+        /// Address to listen on. The API has no authentication, so only
+        /// expose it to networks you trust
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+
+        /// Port to listen on
+        #[arg(long, default_value_t = 8787)]
+        port: u16,
+    },
+
+    /// Check Steam, its libraries, Vulkan drivers, helper tools and backup space
+    Doctor {
+        /// Report free space, prefixes, orphaned data, unreadable manifests and permission problems per library
+        #[arg(long)]
+        library: bool,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show details about this machine for bug reports
+    Info {
+        /// Operating system, kernel, Steam installation, GPUs and Vulkan drivers
+        #[arg(long, required = true)]
+        system: bool,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
     },
 
     /// Clear the shader cache for the given App ID
     ClearCache {
         /// The Steam App ID of the game
         appid: u32,
+
+        /// Only clear the DXVK state cache
+        #[arg(long)]
+        dxvk: bool,
+
+        /// Only clear Fossilize pipeline caches
+        #[arg(long)]
+        fozpipelines: bool,
+
+        /// Only clear Mesa shader caches
+        #[arg(long)]
+        mesa: bool,
+
+        /// Only clear the Direct3D, NVIDIA and AMD shader caches inside the
+        /// prefix
+        #[arg(long)]
+        prefix_caches: bool,
+
+        /// Also clear the Mesa and NVIDIA caches in ~/.cache, which are
+        /// shared by all games and rebuilt by each of them
+        #[arg(long)]
+        driver_caches: bool,
+
+        /// Show the size of each cache instead of clearing it
+        #[arg(long, conflicts_with_all = ["dxvk", "fozpipelines", "mesa", "prefix_caches", "driver_caches"])]
+        sizes: bool,
     },
 
     /// Run protontricks for the given App ID
@@ -132,17 +509,42 @@ pub enum Commands {
         args: Vec<String>,
     },
 
+    /// Run winetricks in the prefix with the game's Proton wine, without protontricks
+    Winetricks {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Verbs to install (opens the winetricks GUI when omitted)
+        #[arg(trailing_var_arg = true)]
+        verbs: Vec<String>,
+    },
+
     /// Launch winecfg for the given App ID
     Winecfg {
         /// The Steam App ID of the game
         appid: u32,
     },
 
-    /// Edit game configuration in the manifest
-    Config {
+    /// Run one of Wine's builtin tools in the prefix with the game's Proton wine
+    Tool {
         /// The Steam App ID of the game
         appid: u32,
 
+        /// The tool to run
+        #[arg(value_enum)]
+        tool: WineTool,
+    },
+
+    /// Edit game configuration in the manifest, or export/import it as a profile
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+
+        /// The Steam App ID of the game
+        #[arg(required = true)]
+        appid: Option<u32>,
+
         /// Set custom launch options
         #[arg(long)]
         launch: Option<String>,
@@ -158,8 +560,257 @@ pub enum Commands {
         /// Auto update behavior
         #[arg(long)]
         auto_update: Option<String>,
+
+        /// Enable or disable the Steam overlay in the game
+        #[arg(long, value_name = "on|off", value_parser = clap::builder::BoolishValueParser::new())]
+        overlay: Option<bool>,
+
+        /// Apply a named profile from the tool configuration before other changes
+        #[arg(long)]
+        apply_profile: Option<String>,
+    },
+
+    /// Manage environment variables kept by this tool and written into the launch options
+    Env {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Set a variable, e.g. --set PROTON_LOG=1
+        #[arg(long, value_name = "KEY=VALUE")]
+        set: Vec<String>,
+
+        /// Remove a variable
+        #[arg(long, value_name = "KEY")]
+        unset: Vec<String>,
     },
 
     /// Show paths to discovered localconfig.vdf files
     ConfigPaths,
+
+    /// Look up save and config locations on PCGamingWiki and map them into the prefix
+    Saves {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Ignore the local cache and query PCGamingWiki again
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Export the prefix for use with another Wine launcher
+    Export {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Launcher to export to
+        #[arg(long, value_enum)]
+        target: ExportTarget,
+
+        /// Directory to export into (defaults to the launcher's usual location)
+        #[arg(long)]
+        dest: Option<PathBuf>,
+
+        /// Symlink the prefix instead of copying it
+        #[arg(long)]
+        link: bool,
+    },
+
+    /// Check for a newer GE-Proton release than the installed ones
+    ProtonGe {
+        /// Download and install the latest release if it is newer
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Show or edit the user_settings.py options of a Proton version
+    ProtonSettings {
+        /// Proton directory name or Steam tool name (lists versions when omitted)
+        version: Option<String>,
+
+        /// Set an option, e.g. --set PROTON_NO_ESYNC=1
+        #[arg(long, value_name = "KEY=VALUE")]
+        set: Vec<String>,
+
+        /// Remove an option
+        #[arg(long, value_name = "KEY")]
+        unset: Vec<String>,
+    },
+
+    /// Show or change the compatibility tool Steam uses for games without their own
+    DefaultProton {
+        #[command(subcommand)]
+        action: DefaultProtonAction,
+    },
+
+    /// Manage Wine prefixes of Epic and GOG games installed with Heroic
+    Heroic {
+        #[command(subcommand)]
+        action: HeroicAction,
+    },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportTarget {
+    /// Lutris installer script using the wine runner
+    Lutris,
+    /// Bottles bottle with a generated bottle.yml
+    Bottles,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Save launch options, compatibility tool, cloud/auto-update flags, DLL overrides and dxvk.conf to a TOML file
+    Export {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Destination TOML file
+        file: PathBuf,
+    },
+
+    /// Apply a profile created with `config export` to a game
+    Import {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Profile TOML file
+        file: PathBuf,
+    },
+
+    /// Restore the launch options, compatibility tool and Steam flags a game had before its last configuration change
+    Undo {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Undo the last change to a game's appmanifest and localconfig.vdf, or to a single file
+    Rollback {
+        /// The Steam App ID of the game, or the path of a config file
+        target: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DefaultProtonAction {
+    /// Print the default compatibility tool
+    Get,
+
+    /// Make a Proton version the default for all games
+    Set {
+        /// Steam tool name, e.g. proton_9 or GE-Proton9-20
+        version: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HeroicAction {
+    /// List Heroic games that have a Wine prefix
+    List,
+
+    /// Back up the prefix of a Heroic game
+    Backup {
+        /// Heroic app name of the game (see `heroic list`)
+        app_name: String,
+    },
+
+    /// Restore the prefix of a Heroic game from a backup directory
+    Restore {
+        /// Heroic app name of the game (see `heroic list`)
+        app_name: String,
+
+        /// Path to the backup directory
+        path: PathBuf,
+    },
+
+    /// Check that the prefix of a Heroic game is a complete Wine prefix
+    Validate {
+        /// Heroic app name of the game (see `heroic list`)
+        app_name: String,
+
+        /// Exit with an error on warnings too
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RemoteAction {
+    /// Copy a backup to the remote target
+    Push {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Backup directory to copy, the newest backup of the game if omitted
+        backup: Option<PathBuf>,
+    },
+
+    /// List the backups of a game on the remote target
+    List {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Copy a backup from the remote target into the local backup directory
+    Pull {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Name of the backup as shown by `remote list`
+        name: String,
+    },
+}
+
+#[cfg(feature = "s3")]
+#[derive(Subcommand)]
+pub enum S3Action {
+    /// Compress a backup and upload it to the bucket
+    Push {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Backup directory to upload, the newest backup of the game if omitted
+        backup: Option<PathBuf>,
+    },
+
+    /// List the backups of a game in the bucket
+    List {
+        /// The Steam App ID of the game
+        appid: u32,
+    },
+
+    /// Download a backup from the bucket into the local backup directory
+    Pull {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Name of the backup as shown by `s3 list`
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TagAction {
+    /// Tag a game
+    Add {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Tag to add, e.g. modded
+        tag: String,
+    },
+
+    /// Remove a tag from a game
+    Remove {
+        /// The Steam App ID of the game
+        appid: u32,
+
+        /// Tag to remove
+        tag: String,
+    },
+
+    /// List the tags of a game, or every tag in use
+    List {
+        /// The Steam App ID of the game
+        appid: Option<u32>,
+    },
 }
@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use crate::utils::app_config;
+use crate::utils::backup as backup_utils;
+use crate::utils::logging;
+
+/// Move every backup to `destination` and store it as the backup root.
+pub fn execute(destination: &Path) {
+    log::debug!("move-backups command: destination={}", destination.display());
+    let destination = match std::path::absolute(destination) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return;
+        }
+    };
+    let current = backup_utils::backup_root();
+    println!(
+        "📦 Moving backups from {} to {}",
+        current.display(),
+        destination.display()
+    );
+    match logging::operation("move_backups", None, &current, || {
+        backup_utils::move_backups(&current, &destination)
+    }) {
+        Ok(skipped) => {
+            for path in &skipped {
+                println!("⚠️ Kept {}: the new location already has it", path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return;
+        }
+    }
+
+    let mut config = app_config::load();
+    config.general.backup_root = Some(destination.clone());
+    match app_config::save(&config) {
+        Ok(_) => println!("✅ Backups are now stored in {}", destination.display()),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
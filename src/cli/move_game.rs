@@ -0,0 +1,26 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::utils::game_move;
+
+pub fn execute(appid: u32, library: &Path) {
+    log::debug!("move-game command: appid={} library={}", appid, library.display());
+
+    println!("🚚 Moving AppID {} to {}", appid, library.display());
+    let last = AtomicU64::new(u64::MAX);
+    let result = game_move::move_game(appid, library, &|moved, total| {
+        super::backup::show_progress(&last, moved, total)
+    });
+    if last.load(Ordering::Relaxed) != u64::MAX {
+        println!();
+    }
+    match result {
+        Ok(moved) => println!(
+            "✅ Moved AppID {} from {} to {}",
+            appid,
+            moved.from.display(),
+            moved.to.display()
+        ),
+        Err(e) => eprintln!("❌ Failed to move game: {}", e),
+    }
+}
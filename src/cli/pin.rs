@@ -0,0 +1,58 @@
+use crate::utils::app_config;
+
+fn set_favorite(appid: u32, favorite: bool) {
+    let mut config = app_config::load();
+    if !config.general.set_favorite(appid, favorite) {
+        if favorite {
+            println!("⭐ {} is already pinned", appid);
+        } else {
+            println!("⭐ {} is not pinned", appid);
+        }
+        return;
+    }
+    match app_config::save(&config) {
+        Ok(_) if favorite => println!("✅ Pinned {} to the top of the game list", appid),
+        Ok(_) => println!("✅ Unpinned {}", appid),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+pub fn execute(appid: u32) {
+    log::debug!("pin command: appid={}", appid);
+    set_favorite(appid, true);
+}
+
+pub fn unpin(appid: u32) {
+    log::debug!("unpin command: appid={}", appid);
+    set_favorite(appid, false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+
+        execute(620);
+        execute(400);
+        execute(620);
+        assert_eq!(app_config::load().general.favorites, [400, 620]);
+        unpin(400);
+        assert_eq!(app_config::load().general.favorites, [620]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+    }
+}
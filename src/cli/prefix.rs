@@ -1,7 +1,6 @@
 use crate::core::steam;
-#[cfg(not(test))]
-use crate::utils::output;
-use crate::utils::output::OutputFormat;
+use crate::utils::proton_runtime;
+use crate::utils::output::{self, OutputFormat};
 
 #[cfg(test)]
 use once_cell::sync::Lazy;
@@ -34,8 +33,21 @@ pub fn execute(appid: u32, format: &OutputFormat) {
             emit_prefix_result(appid, prefix, format);
         }
         Err(err) => {
-            eprintln!("❌ Error: {}", err);
+            output::print_error(&err, format);
+        }
+    }
+}
+
+pub fn create(appid: u32) {
+    log::debug!("prefix create command: appid={}", appid);
+    println!("🍷 Creating Proton prefix for AppID: {}", appid);
+
+    match proton_runtime::create_prefix(appid) {
+        Ok((prefix, proton)) => {
+            println!("⚙️ Initialized with {}", proton.display());
+            println!("✅ Prefix created at {}", prefix.display());
         }
+        Err(e) => eprintln!("❌ Failed to create prefix: {}", e),
     }
 }
 
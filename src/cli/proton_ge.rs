@@ -0,0 +1,39 @@
+use crate::utils::proton_ge;
+
+pub fn execute(install: bool) {
+    log::debug!("proton-ge command: install={}", install);
+
+    let installed = proton_ge::installed_versions();
+    match installed.last() {
+        Some(newest) => println!("📦 Newest installed GE-Proton: {}", newest),
+        None => println!("📦 No GE-Proton builds found in compatibilitytools.d"),
+    }
+
+    let latest = match proton_ge::latest_release(true) {
+        Ok(release) => release,
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return;
+        }
+    };
+    println!("🔍 Latest release: {}", latest.tag);
+
+    let newer = installed
+        .last()
+        .and_then(|v| proton_ge::parse_version(v))
+        .is_none_or(|v| proton_ge::parse_version(&latest.tag) > Some(v));
+    if !newer {
+        println!("✅ GE-Proton is up to date");
+        return;
+    }
+    if !install {
+        println!("⬆️  Run with --install to download {}", latest.tag);
+        return;
+    }
+
+    println!("⬇️  Installing {}...", latest.tag);
+    match proton_ge::install(&latest) {
+        Ok(path) => println!("✅ Installed to {}", path.display()),
+        Err(e) => eprintln!("❌ Failed to install {}: {}", latest.tag, e),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::utils::{proton_runtime, proton_settings};
+
+fn list_versions() {
+    let protons = proton_runtime::installed_protons();
+    if protons.is_empty() {
+        println!("❌ No Proton installations found");
+        return;
+    }
+    for dir in protons {
+        let count = proton_settings::load(&dir).len();
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        println!("🍷 {} ({} option(s))", name, count);
+        println!("   📁 {}", dir.display());
+    }
+}
+
+pub fn execute(version: Option<&str>, set: &[String], unset: &[String]) {
+    log::debug!(
+        "proton-settings command: version={:?} set={:?} unset={:?}",
+        version,
+        set,
+        unset
+    );
+
+    let Some(version) = version else {
+        list_versions();
+        return;
+    };
+    let Some(dir) = proton_runtime::find_proton(version) else {
+        eprintln!("❌ Error: Proton version {} not found", version);
+        return;
+    };
+
+    let mut settings = proton_settings::load(&dir);
+    if !set.is_empty() || !unset.is_empty() {
+        for key in unset {
+            settings.remove(key);
+        }
+        for entry in set {
+            let Some((key, value)) = entry.split_once('=') else {
+                eprintln!("❌ Error: expected KEY=VALUE, got {}", entry);
+                return;
+            };
+            if proton_settings::describe(key).is_none() {
+                println!("⚠️ {} is not a known Proton option", key);
+            }
+            settings.insert(key.to_string(), value.to_string());
+        }
+        match proton_settings::save(&dir, &settings) {
+            Ok(_) => println!("✅ Saved {}", proton_settings::settings_path(&dir).display()),
+            Err(e) => {
+                eprintln!("❌ Error: {}", e);
+                return;
+            }
+        }
+    }
+
+    if settings.is_empty() {
+        println!("📄 No options set for {}", dir.display());
+        return;
+    }
+    println!("📄 Options for {}:", dir.display());
+    for (key, value) in &settings {
+        match proton_settings::describe(key) {
+            Some(desc) => println!("   {}={}  ({})", key, value, desc),
+            None => println!("   {}={}", key, value),
+        }
+    }
+}
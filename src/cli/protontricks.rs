@@ -1,5 +1,6 @@
 use crate::core::steam;
-use crate::utils::dependencies::command_available;
+use crate::utils::dependencies::{command_available, missing_message};
+use crate::utils::proton_runtime;
 
 #[cfg(test)]
 use once_cell::sync::Lazy;
@@ -12,7 +13,8 @@ fn run_protontricks(appid: Option<u32>, args: &[String]) -> std::io::Result<()>
     if let Some(id) = appid {
         cmd.arg(id.to_string());
     }
-    let status = cmd.args(args).status()?;
+    cmd.args(args);
+    let status = crate::utils::sandbox::host_command(cmd).status()?;
     if status.success() {
         Ok(())
     } else {
@@ -41,13 +43,22 @@ pub fn execute(appid: u32, args: &[String]) {
     println!("🔧 Running protontricks for AppID: {}", appid);
 
     if !command_available("protontricks") {
-        eprintln!("❌ 'protontricks' is not installed or not found in PATH. Please install it to use this feature.");
+        if command_available("winetricks") {
+            println!("⚠️ protontricks not found; running winetricks with the game's Proton wine instead");
+            let verbs: Vec<String> = args.iter().filter(|a| *a != "--gui").cloned().collect();
+            super::winetricks::execute(appid, &verbs);
+        } else {
+            eprintln!("❌ {}", missing_message("protontricks"));
+        }
         return;
     }
 
     match steam::get_steam_libraries() {
         Ok(libraries) => {
             if steam::find_proton_prefix(appid, &libraries).is_some() {
+                if let Some(warning) = proton_runtime::container_warning(appid) {
+                    println!("⚠️ {}", warning);
+                }
                 if args.is_empty() {
                     if let Err(e) = run_protontricks(Some(appid), &["--gui".to_string()]) {
                         eprintln!("❌ Failed to run protontricks: {}", e);
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use crate::core::steam;
+use crate::utils::prefix_relocate;
+
+pub fn execute(appid: u32, target: Option<&Path>, back: bool) {
+    log::debug!(
+        "relocate command: appid={} target={:?} back={}",
+        appid,
+        target,
+        back
+    );
+
+    let prefix = match steam::get_steam_libraries() {
+        Ok(libraries) => match steam::find_proton_prefix(appid, &libraries) {
+            Some(prefix) => prefix,
+            None => {
+                println!("❌ Proton prefix not found for AppID: {}", appid);
+                return;
+            }
+        },
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+            return;
+        }
+    };
+
+    if back {
+        println!("🚚 Moving prefix for AppID {} back to {}", appid, prefix.display());
+        match prefix_relocate::bring_back(&prefix) {
+            Ok(from) => println!("✅ Prefix moved back from {}", from.display()),
+            Err(e) => eprintln!("❌ Failed to move prefix back: {}", e),
+        }
+    } else if let Some(target) = target {
+        println!("🚚 Moving prefix for AppID {} to {}", appid, target.display());
+        match prefix_relocate::relocate(&prefix, target) {
+            Ok(dest) => println!("✅ Prefix moved to {} and linked from {}", dest.display(), prefix.display()),
+            Err(e) => eprintln!("❌ Failed to move prefix: {}", e),
+        }
+    }
+}
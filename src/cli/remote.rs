@@ -0,0 +1,48 @@
+use crate::utils::backup as backup_utils;
+use crate::utils::remote_backup;
+use std::path::Path;
+
+/// Copy `backup`, or the newest backup of `appid`, to the remote target.
+pub fn push(appid: u32, backup: Option<&Path>) {
+    log::debug!("remote push command: appid={} backup={:?}", appid, backup);
+    let Some(backup) = backup
+        .map(Path::to_path_buf)
+        .or_else(|| backup_utils::list_backups(appid).pop())
+    else {
+        eprintln!("❌ Error: no backups of {} to copy", appid);
+        return;
+    };
+    println!("📤 Copying {} to the remote target", backup.display());
+    match remote_backup::push(&backup, appid) {
+        Ok(remote) => println!("✅ Backup copied to {}", remote),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Print the remote backups of `appid`, oldest first.
+pub fn list(appid: u32) {
+    log::debug!("remote list command: appid={}", appid);
+    match remote_backup::list(appid) {
+        Ok(names) if names.is_empty() => println!("No remote backups found"),
+        Ok(names) => {
+            for name in names {
+                let date = backup_utils::format_backup_name(Path::new(&name));
+                println!("☁️ {} ({})", name, date);
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Copy the remote backup `name` of `appid` into the local backup root.
+pub fn pull(appid: u32, name: &str) {
+    log::debug!("remote pull command: appid={} name={}", appid, name);
+    println!("📥 Copying remote backup {} of {}", name, appid);
+    match remote_backup::pull(appid, name) {
+        Ok(path) => {
+            println!("✅ Backup copied to {}", path.display());
+            println!("♻️ Restore it with: proton-prefix-manager restore {} {}", appid, path.display());
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
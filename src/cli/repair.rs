@@ -0,0 +1,73 @@
+use crate::core::steam;
+use crate::utils::{hooks, prefix_info, prefix_repair, proton_runtime, snapshot};
+
+pub fn execute(appid: u32, broken_links: bool, dry_run: bool) {
+    log::debug!(
+        "repair command: appid={} broken_links={} dry_run={}",
+        appid,
+        broken_links,
+        dry_run
+    );
+    let libraries = match steam::get_steam_libraries() {
+        Ok(libraries) => libraries,
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return;
+        }
+    };
+    let Some(prefix) = steam::find_proton_prefix(appid, &libraries) else {
+        println!("❌ Proton prefix not found for AppID: {}", appid);
+        return;
+    };
+
+    let wine_prefix = prefix.join("pfx");
+    let dist = proton_runtime::proton_for(appid).and_then(|proton| proton_runtime::wine_dir(&proton));
+    let link_fixes = if broken_links {
+        prefix_repair::plan_broken_links(&wine_prefix, dist.as_deref())
+    } else {
+        Vec::new()
+    };
+    if dry_run {
+        if link_fixes.is_empty() {
+            println!("✅ No broken symlinks in {}", wine_prefix.display());
+        } else {
+            println!("🔍 Would change {} broken symlink(s):", link_fixes.len());
+            for fix in &link_fixes {
+                println!("   - {}", fix);
+            }
+        }
+        return;
+    }
+    println!("🔧 Repairing Proton prefix for AppID: {}", appid);
+
+    match hooks::repair(&prefix, || {
+        let mut fixes = prefix_repair::fix_dosdevices(&wine_prefix)?;
+        fixes.extend(prefix_repair::fix_registry(&wine_prefix, &snapshot::copies(&prefix))?);
+        fixes.extend(proton_runtime::regenerate_registry(appid, &prefix)?);
+        if let Some(dist) = &dist {
+            let backup_dir = prefix_repair::dll_backup_dir(appid);
+            fixes.extend(prefix_repair::fix_d3d_dlls(&wine_prefix, dist, &backup_dir)?);
+        }
+        fixes.extend(prefix_repair::fix_broken_links(&wine_prefix, &link_fixes)?);
+        Ok(fixes)
+    }) {
+        Ok(fixes) if fixes.is_empty() => println!("✅ Nothing to repair"),
+        Ok(fixes) => {
+            for fix in fixes {
+                println!("   - {}", fix);
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Failed to repair prefix: {}", e);
+            return;
+        }
+    }
+
+    let problems = prefix_info::validate_wine_prefix(&wine_prefix);
+    if !problems.is_empty() {
+        println!("⚠️ Problems left in {}:", wine_prefix.display());
+        for problem in problems {
+            println!("   - {}", problem);
+        }
+    }
+}
@@ -1,15 +1,35 @@
 use crate::core::steam;
-use crate::utils::backup as backup_utils;
+use crate::utils::backup::{self as backup_utils, ResetStep};
+use crate::utils::{hooks, keep_saves};
 
-pub fn execute(appid: u32) {
-    log::debug!("reset command: appid={}", appid);
+pub fn execute(appid: u32, keep_saves: bool, steps: &[ResetStep]) {
+    log::debug!(
+        "reset command: appid={} keep_saves={} steps={:?}",
+        appid,
+        keep_saves,
+        steps
+    );
     println!("\u{26a0}\u{fe0f} It's prudent to create a backup of your important data or configuration files before performing any critical actions. This ensures you can restore your system to a known good state if something unexpected happens.");
     match steam::get_steam_libraries() {
         Ok(libraries) => {
             if let Some(prefix) = steam::find_proton_prefix(appid, &libraries) {
-                match backup_utils::reset_prefix(&prefix) {
-                    Ok(_) => println!("Prefix deleted"),
-                    Err(e) => eprintln!("Failed to delete prefix: {}", e),
+                if keep_saves {
+                    match keep_saves::reset(appid, &prefix) {
+                        Ok(saves) => {
+                            println!("💾 Saves backed up to {}", saves.display());
+                            println!("Prefix deleted, run `restore-saves` or open the GUI after launching the game again to put the saves back");
+                        }
+                        Err(e) => eprintln!("Failed to reset prefix: {}", e),
+                    }
+                    return;
+                }
+                match hooks::reset(&prefix, || backup_utils::reset_prefix_steps(&prefix, steps)) {
+                    Ok(_) if steps.is_empty() => println!("Prefix deleted"),
+                    Ok(_) => {
+                        let parts: Vec<&str> = steps.iter().map(|s| s.label()).collect();
+                        println!("Reset {} of the prefix", parts.join(", "));
+                    }
+                    Err(e) => eprintln!("Failed to reset prefix: {}", e),
                 }
             } else {
                 println!("Prefix not found for {}", appid);
@@ -2,12 +2,15 @@ use std::path::PathBuf;
 
 use crate::core::steam;
 use crate::utils::backup as backup_utils;
+use crate::utils::hooks;
 
-pub fn execute(appid: u32, backup_path: PathBuf) {
+pub fn execute(appid: u32, backup_path: PathBuf, delta: bool, only: &[String]) {
     log::debug!(
-        "restore command: appid={} backup_path={}",
+        "restore command: appid={} backup_path={} delta={} only={:?}",
         appid,
-        backup_path.display()
+        backup_path.display(),
+        delta,
+        only
     );
     println!("\u{26a0}\u{fe0f} It's prudent to create a backup of your important data or configuration files before performing any critical actions. This ensures you can restore your system to a known good state if something unexpected happens.");
     println!("♻️ Restoring Proton prefix for AppID: {}", appid);
@@ -15,8 +18,28 @@ pub fn execute(appid: u32, backup_path: PathBuf) {
     match steam::get_steam_libraries() {
         Ok(libraries) => {
             if let Some(prefix_path) = steam::find_proton_prefix(appid, &libraries) {
-                match backup_utils::restore_prefix(&backup_path, &prefix_path) {
-                    Ok(path) => println!("✅ Prefix restored to {}", path.display()),
+                let mut summary = None;
+                let mut restored = None;
+                match hooks::restore(&prefix_path, &backup_path, || {
+                    if !only.is_empty() {
+                        restored = Some(backup_utils::restore_paths(&backup_path, &prefix_path, only)?);
+                        Ok(prefix_path.clone())
+                    } else if delta {
+                        summary = Some(backup_utils::restore_prefix_delta(&backup_path, &prefix_path)?);
+                        Ok(prefix_path.clone())
+                    } else {
+                        backup_utils::restore_prefix(&backup_path, &prefix_path)
+                    }
+                }) {
+                    Ok(path) => {
+                        println!("✅ Prefix restored to {}", path.display());
+                        if let Some(summary) = summary {
+                            println!("📄 Files: {}", summary);
+                        }
+                        if let Some(count) = restored {
+                            println!("📄 Restored {} file(s) matching {}", count, only.join(", "));
+                        }
+                    }
                     Err(e) => eprintln!("❌ Failed to restore prefix: {}", e),
                 }
             } else {
@@ -0,0 +1,18 @@
+use crate::utils::keep_saves;
+
+pub fn execute() {
+    log::debug!("restore-saves command");
+    let done = keep_saves::restore_pending();
+    if done.is_empty() {
+        println!("💾 No kept saves to restore: the game has to be launched, and closed, after its prefix was reset");
+    }
+    for (pending, result) in done {
+        match result {
+            Ok(_) => println!(
+                "💾 Restored the saves of {} kept when its prefix was reset",
+                pending.appid
+            ),
+            Err(e) => eprintln!("❌ Failed to restore the kept saves of {}: {}", pending.appid, e),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use crate::core::steam;
+use crate::utils::shader_cache;
+
+pub fn execute(appid: u32, backup_path: Option<PathBuf>) {
+    log::debug!(
+        "restore-shadercache command: appid={} backup_path={:?}",
+        appid,
+        backup_path
+    );
+    let Some(backup_path) = backup_path.or_else(|| shader_cache::list_backups(appid).pop()) else {
+        println!("❌ No shader cache backups found for AppID: {}", appid);
+        return;
+    };
+    println!("♻️ Restoring shader cache for AppID: {}", appid);
+
+    match steam::get_steam_libraries() {
+        Ok(libraries) => match shader_cache::restore_backup(appid, &libraries, &backup_path) {
+            Ok(path) => println!("✅ Shader cache restored to {}", path.display()),
+            Err(e) => eprintln!("❌ Failed to restore shader cache: {}", e),
+        },
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+        }
+    }
+}
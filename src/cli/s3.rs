@@ -0,0 +1,48 @@
+use crate::utils::backup as backup_utils;
+use crate::utils::s3_backup;
+use std::path::Path;
+
+/// Upload `backup`, or the newest backup of `appid`, to the bucket.
+pub fn push(appid: u32, backup: Option<&Path>) {
+    log::debug!("s3 push command: appid={} backup={:?}", appid, backup);
+    let Some(backup) = backup
+        .map(Path::to_path_buf)
+        .or_else(|| backup_utils::list_backups(appid).pop())
+    else {
+        eprintln!("❌ Error: no backups of {} to upload", appid);
+        return;
+    };
+    println!("📤 Uploading {}", backup.display());
+    match s3_backup::push(&backup, appid) {
+        Ok(url) => println!("✅ Backup uploaded to {}", url),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Print the backups of `appid` in the bucket, oldest first.
+pub fn list(appid: u32) {
+    log::debug!("s3 list command: appid={}", appid);
+    match s3_backup::list(appid) {
+        Ok(names) if names.is_empty() => println!("No backups found in the bucket"),
+        Ok(names) => {
+            for name in names {
+                let date = backup_utils::format_backup_name(Path::new(&name));
+                println!("☁️ {} ({})", name, date);
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Download the backup `name` of `appid` into the local backup root.
+pub fn pull(appid: u32, name: &str) {
+    log::debug!("s3 pull command: appid={} name={}", appid, name);
+    println!("📥 Downloading backup {} of {}", name, appid);
+    match s3_backup::pull(appid, name) {
+        Ok(path) => {
+            println!("✅ Backup unpacked to {}", path.display());
+            println!("♻️ Restore it with: proton-prefix-manager restore {} {}", appid, path.display());
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
@@ -0,0 +1,109 @@
+use crate::core::steam;
+use crate::utils::save_locations::{self, SaveLocation};
+
+#[cfg(test)]
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(not(test))]
+fn emit_locations(appid: u32, locations: Vec<SaveLocation>) {
+    if locations.is_empty() {
+        println!("❌ No save locations listed for AppID: {}", appid);
+        return;
+    }
+    for loc in locations {
+        println!("{}: {}", loc.kind.label(), loc.windows_path);
+        match loc.path {
+            Some(path) if path.is_file() => println!("   📄 {}", path.display()),
+            Some(path) if path.exists() => println!("   📁 {}", path.display()),
+            Some(path) => println!("   ❓ {} (not created yet)", path.display()),
+            None => println!("   ❓ Not mapped into the prefix"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub static EMITTED_LOCATIONS: Lazy<Mutex<Vec<Vec<SaveLocation>>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+#[cfg(test)]
+fn emit_locations(_appid: u32, locations: Vec<SaveLocation>) {
+    EMITTED_LOCATIONS.lock().unwrap().push(locations);
+}
+
+pub fn execute(appid: u32, refresh: bool) {
+    log::debug!("saves command: appid={} refresh={}", appid, refresh);
+    println!("💾 Looking up save locations for AppID: {}", appid);
+
+    let libraries = match steam::get_steam_libraries() {
+        Ok(libs) => libs,
+        Err(err) => {
+            eprintln!("❌ Error: {}", err);
+            return;
+        }
+    };
+    let Some(prefix) = steam::find_proton_prefix(appid, &libraries) else {
+        println!("❌ Proton prefix not found for AppID: {}", appid);
+        return;
+    };
+
+    match save_locations::lookup(appid, refresh) {
+        Ok(paths) => {
+            let install_dir = steam::find_install_dir(appid);
+            emit_locations(
+                appid,
+                save_locations::resolve(&paths, &prefix, install_dir.as_deref()),
+            );
+        }
+        Err(e) => eprintln!("❌ Failed to look up save locations: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use crate::utils::http::HTTP_RESPONSES;
+
+    #[test]
+    fn test_execute_maps_into_prefix() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 3030;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        {
+            let mut responses = HTTP_RESPONSES.lock().unwrap();
+            responses.clear();
+            responses.push((
+                "https://www.pcgamingwiki.com/w/api.php?action=cargoquery".to_string(),
+                r#"{"cargoquery":[{"title":{"Page":"Example"}}]}"#.to_string(),
+            ));
+            responses.push((
+                "https://www.pcgamingwiki.com/w/api.php?action=parse".to_string(),
+                serde_json::json!({"parse": {"wikitext": {"*":
+                    "{{Game data/saves|Windows|{{p|localappdata}}\\Example\\Saved\\}}"}}})
+                .to_string(),
+            ));
+        }
+
+        EMITTED_LOCATIONS.lock().unwrap().clear();
+        execute(appid, false);
+
+        let emitted = EMITTED_LOCATIONS.lock().unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(emitted[0].len(), 1);
+        assert_eq!(
+            emitted[0][0].path.as_ref().unwrap(),
+            &prefix.join("pfx/drive_c/users/steamuser/AppData/Local/Example/Saved")
+        );
+
+        HTTP_RESPONSES.lock().unwrap().clear();
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
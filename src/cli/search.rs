@@ -1,7 +1,6 @@
 use crate::core::steam;
-#[cfg(not(test))]
-use crate::utils::output;
-use crate::utils::output::OutputFormat;
+use crate::utils::app_config;
+use crate::utils::output::{self, OutputFormat};
 use crate::core::models::GameInfo;
 
 #[cfg(test)]
@@ -30,11 +29,13 @@ pub fn execute(name: &str, format: &OutputFormat) {
     }
 
     match steam::search_games(name) {
-        Ok(results) => {
+        Ok(mut results) => {
+            let general = app_config::load().general;
+            results.retain(|g| !general.is_excluded(g.app_id()));
             emit_search_results(results, format);
         }
         Err(err) => {
-            eprintln!("❌ Error: {}", err);
+            output::print_error(&err, format);
         }
     }
 }
@@ -0,0 +1,13 @@
+use crate::server;
+
+pub fn execute(bind: &str, port: u16) {
+    log::debug!("serve command: bind={} port={}", bind, port);
+    if bind != "127.0.0.1" && bind != "localhost" && bind != "::1" {
+        println!("⚠️ The API has no authentication and is reachable from other machines on {}", bind);
+    }
+    println!("🌐 Serving API on http://{}:{}/api", bind, port);
+
+    if let Err(e) = server::run(bind, port) {
+        eprintln!("❌ Error: {}", e);
+    }
+}
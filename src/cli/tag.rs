@@ -0,0 +1,92 @@
+use crate::utils::app_config;
+
+pub fn add(appid: u32, tag: &str) {
+    log::debug!("tag add command: appid={} tag={}", appid, tag);
+    let mut config = app_config::load();
+    if tag.trim().is_empty() {
+        eprintln!("❌ Error: the tag is empty");
+        return;
+    }
+    if !config.add_tag(appid, tag) {
+        println!("🏷️ {} is already tagged '{}'", appid, tag.trim());
+        return;
+    }
+    match app_config::save(&config) {
+        Ok(_) => println!("✅ Tagged {} '{}'", appid, tag.trim()),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+pub fn remove(appid: u32, tag: &str) {
+    log::debug!("tag remove command: appid={} tag={}", appid, tag);
+    let mut config = app_config::load();
+    if !config.remove_tag(appid, tag) {
+        println!("🏷️ {} is not tagged '{}'", appid, tag.trim());
+        return;
+    }
+    match app_config::save(&config) {
+        Ok(_) => println!("✅ Removed tag '{}' from {}", tag.trim(), appid),
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+/// Print the tags of a game, or every tag with the number of games using
+/// it.
+pub fn list(appid: Option<u32>) {
+    log::debug!("tag list command: appid={:?}", appid);
+    let config = app_config::load();
+    match appid {
+        Some(appid) => {
+            let tags = config.tags_of(appid);
+            if tags.is_empty() {
+                println!("🏷️ {} has no tags", appid);
+            }
+            for tag in tags {
+                println!("🏷️ {}", tag);
+            }
+        }
+        None => {
+            let tags = config.all_tags();
+            if tags.is_empty() {
+                println!("🏷️ No games are tagged");
+            }
+            for tag in tags {
+                println!("🏷️ {} ({} games)", tag, config.tagged(&tag).len());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_add_and_remove() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+
+        add(620, "modded");
+        add(620, "needs GE");
+        add(400, "modded");
+        add(400, " ");
+        let config = app_config::load();
+        assert_eq!(config.tagged("modded"), [400, 620]);
+        assert_eq!(config.tags_of(620).len(), 2);
+
+        remove(620, "modded");
+        assert_eq!(app_config::load().tagged("modded"), [400]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::utils::proton_runtime::{self, WineTool};
+use std::process::Command;
+
+#[cfg(test)]
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(not(test))]
+fn run_tool(mut cmd: Command) -> std::io::Result<()> {
+    let status = cmd.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "wine exited with status {}",
+            status
+        )))
+    }
+}
+
+#[cfg(test)]
+pub static TOOL_CALLS: Lazy<Mutex<Vec<Vec<String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[cfg(test)]
+fn run_tool(cmd: Command) -> std::io::Result<()> {
+    TOOL_CALLS
+        .lock()
+        .unwrap()
+        .push(cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect());
+    Ok(())
+}
+
+/// Run one of Wine's builtin tools in the prefix of `appid` with the Wine
+/// of the game's Proton build.
+pub fn execute(appid: u32, tool: WineTool) {
+    log::debug!("tool command: appid={} tool={:?}", appid, tool);
+    println!("🍷 Launching {} for AppID: {}", tool.program(), appid);
+
+    match proton_runtime::wine_tool(appid, tool.program()) {
+        Ok(cmd) => {
+            if let Some(warning) = proton_runtime::container_warning(appid) {
+                println!("⚠️ {}", warning);
+            }
+            if let Err(e) = run_tool(cmd) {
+                eprintln!("❌ Failed to launch {}: {}", tool.program(), e);
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use std::fs;
+
+    #[test]
+    fn test_execute_runs_tool() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 7654;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        TOOL_CALLS.lock().unwrap().clear();
+        // No Proton build yet: nothing is started.
+        execute(appid, WineTool::Regedit);
+        assert!(TOOL_CALLS.lock().unwrap().is_empty());
+
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        let proton = home.path().join("library/steamapps/common/Proton 9.0");
+        fs::create_dir_all(proton.join("files/bin")).unwrap();
+        fs::write(proton.join("proton"), "").unwrap();
+        fs::write(proton.join("files/bin/wine"), "").unwrap();
+        execute(appid, WineTool::Regedit);
+        execute(appid, WineTool::Uninstaller);
+
+        let calls = TOOL_CALLS.lock().unwrap();
+        assert_eq!(*calls, vec![vec!["regedit".to_string()], vec!["uninstaller".to_string()]]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use crate::core::steam;
+use crate::utils::prefix_info::{self, Problem, Severity};
+
+/// Exit code when the prefix has no problems, or only warnings without
+/// `--strict`.
+pub const EXIT_HEALTHY: i32 = 0;
+/// Exit code when the prefix could not be found.
+pub const EXIT_NOT_FOUND: i32 = 1;
+/// Exit code when the prefix only has warnings and `--strict` is given.
+pub const EXIT_WARNINGS: i32 = 2;
+/// Exit code when the prefix has errors.
+pub const EXIT_ERRORS: i32 = 3;
+
+/// Exit code for the problems found in a prefix.
+pub fn exit_code(problems: &[Problem], strict: bool) -> i32 {
+    match problems.iter().map(|p| p.severity).max() {
+        Some(Severity::Error) => EXIT_ERRORS,
+        Some(Severity::Warning) if strict => EXIT_WARNINGS,
+        _ => EXIT_HEALTHY,
+    }
+}
+
+/// Print the problems of the Wine prefix at `wine_prefix` and return the
+/// exit code for them.
+pub fn report(wine_prefix: &Path, strict: bool) -> i32 {
+    let problems = prefix_info::check_wine_prefix(wine_prefix);
+    if problems.is_empty() {
+        println!("✅ {} looks like a complete Wine prefix", wine_prefix.display());
+        return EXIT_HEALTHY;
+    }
+    println!("⚠️ Problems found in {}:", wine_prefix.display());
+    for problem in &problems {
+        let icon = match problem.severity {
+            Severity::Error => "❌",
+            Severity::Warning => "⚠️",
+        };
+        println!("   {} {}", icon, problem);
+    }
+    exit_code(&problems, strict)
+}
+
+pub fn execute(appid: u32, strict: bool) -> i32 {
+    log::debug!("validate command: appid={} strict={}", appid, strict);
+    let libraries = match steam::get_steam_libraries() {
+        Ok(libraries) => libraries,
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return EXIT_NOT_FOUND;
+        }
+    };
+    let Some(prefix) = steam::find_proton_prefix(appid, &libraries) else {
+        println!("❌ Proton prefix not found for AppID: {}", appid);
+        return EXIT_NOT_FOUND;
+    };
+    report(&prefix.join("pfx"), strict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        let warning = Problem {
+            severity: Severity::Warning,
+            message: "1 broken symlink".to_string(),
+        };
+        let error = Problem {
+            severity: Severity::Error,
+            message: "missing registry file user.reg".to_string(),
+        };
+        assert_eq!(exit_code(&[], true), EXIT_HEALTHY);
+        assert_eq!(exit_code(std::slice::from_ref(&warning), false), EXIT_HEALTHY);
+        assert_eq!(exit_code(std::slice::from_ref(&warning), true), EXIT_WARNINGS);
+        assert_eq!(exit_code(&[warning.clone(), error.clone()], false), EXIT_ERRORS);
+        assert_eq!(exit_code(&[error, warning], true), EXIT_ERRORS);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::core::steam;
+use crate::utils::output;
+use crate::utils::prefix_junk::{self, PrefixJunk};
+
+fn print_report(junk: &PrefixJunk) {
+    println!(
+        "🗑️ {} ({}): {} reclaimable",
+        junk.app_id,
+        junk.prefix.display(),
+        output::format_size(junk.total())
+    );
+    for item in &junk.items {
+        println!(
+            "   {:<28} {:>10}  {}",
+            item.kind.to_string(),
+            output::format_size(item.size),
+            item.path.display()
+        );
+    }
+}
+
+pub fn execute(appid: Option<u32>, clean: bool) {
+    log::debug!("wasted-space command: appid={:?} clean={}", appid, clean);
+
+    let report = match appid {
+        Some(appid) => match steam::get_steam_libraries() {
+            Ok(libraries) => match steam::find_proton_prefix(appid, &libraries) {
+                Some(prefix) => vec![prefix_junk::scan_game(appid, &prefix)],
+                None => {
+                    println!("❌ Proton prefix not found for AppID: {}", appid);
+                    return;
+                }
+            },
+            Err(err) => {
+                eprintln!("❌ Error: {}", err);
+                return;
+            }
+        },
+        None => match prefix_junk::scan_all() {
+            Ok(report) => report,
+            Err(err) => {
+                eprintln!("❌ Error: {}", err);
+                return;
+            }
+        },
+    };
+
+    let report: Vec<PrefixJunk> = report.into_iter().filter(|j| !j.items.is_empty()).collect();
+    if report.is_empty() {
+        println!("✅ No reclaimable space found");
+        return;
+    }
+    for junk in &report {
+        print_report(junk);
+    }
+    let total: u64 = report.iter().map(|j| j.total()).sum();
+    println!("Total: {}", output::format_size(total));
+
+    if clean {
+        let mut freed = 0;
+        for junk in &report {
+            match prefix_junk::clean(&junk.items) {
+                Ok(bytes) => freed += bytes,
+                Err(e) => eprintln!("❌ Failed to clean prefix for {}: {}", junk.app_id, e),
+            }
+        }
+        println!("✅ Freed {}", output::format_size(freed));
+    }
+}
@@ -1,60 +1,92 @@
 use crate::core::steam;
-use crate::utils::dependencies::command_available;
+use crate::utils::dependencies::{command_available, missing_message};
+use crate::utils::{proton_runtime, sandbox};
+use std::process::Command;
 
 #[cfg(test)]
 use once_cell::sync::Lazy;
 #[cfg(test)]
+use std::path::PathBuf;
+#[cfg(test)]
 use std::sync::Mutex;
 
 #[cfg(not(test))]
-fn run_winecfg(prefix_path: &std::path::Path) -> std::io::Result<()> {
-    let status = std::process::Command::new("winecfg")
-        .env("WINEPREFIX", prefix_path)
-        .status()?;
+fn run_winecfg(mut cmd: Command) -> std::io::Result<()> {
+    let status = cmd.status()?;
     if status.success() {
         Ok(())
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("winecfg exited with status {}", status),
-        ))
+        Err(std::io::Error::other(format!(
+            "winecfg exited with status {}",
+            status
+        )))
     }
 }
 
+/// Program, arguments and `WINEPREFIX` of a winecfg launch.
+#[cfg(test)]
+type WinecfgCall = (PathBuf, Vec<String>, Option<PathBuf>);
+
 #[cfg(test)]
-pub static WINECFG_CALLS: Lazy<Mutex<Vec<std::path::PathBuf>>> = Lazy::new(|| Mutex::new(Vec::new()));
+pub static WINECFG_CALLS: Lazy<Mutex<Vec<WinecfgCall>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
 #[cfg(test)]
-fn run_winecfg(prefix_path: &std::path::Path) -> std::io::Result<()> {
-    WINECFG_CALLS
-        .lock()
-        .unwrap()
-        .push(prefix_path.to_path_buf());
+fn run_winecfg(cmd: Command) -> std::io::Result<()> {
+    let prefix = cmd
+        .get_envs()
+        .find(|(k, _)| *k == "WINEPREFIX")
+        .and_then(|(_, v)| v.map(PathBuf::from));
+    WINECFG_CALLS.lock().unwrap().push((
+        PathBuf::from(cmd.get_program()),
+        cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect(),
+        prefix,
+    ));
     Ok(())
 }
 
+/// Launch winecfg in the prefix of `appid` with the Wine of the game's own
+/// Proton build. The system winecfg is only used when that Wine can't be
+/// found, since a different Wine version may upgrade or break the prefix.
 pub fn execute(appid: u32) {
     log::debug!("winecfg command: appid={}", appid);
     println!("🍷 Launching winecfg for AppID: {}", appid);
 
-    if !command_available("winecfg") {
-        eprintln!("❌ 'winecfg' is not installed or not found in PATH. Please install it to use this feature.");
-        return;
-    }
-
-    match steam::get_steam_libraries() {
-        Ok(libraries) => {
-            if let Some(prefix_path) = steam::find_proton_prefix(appid, &libraries) {
-                if let Err(e) = run_winecfg(&prefix_path) {
-                    eprintln!("❌ Failed to launch winecfg: {}", e);
-                }
-            } else {
+    let prefix_path = match steam::get_steam_libraries() {
+        Ok(libraries) => match steam::find_proton_prefix(appid, &libraries) {
+            Some(prefix_path) => prefix_path,
+            None => {
                 println!("❌ Proton prefix not found for AppID: {}", appid);
+                return;
             }
-        }
+        },
         Err(err) => {
             eprintln!("❌ Error: {}", err);
+            return;
         }
+    };
+
+    let cmd = match proton_runtime::wine_tool(appid, "winecfg") {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            if !command_available("winecfg") {
+                eprintln!("❌ Error: {}", e);
+                eprintln!("❌ {}", missing_message("winecfg"));
+                return;
+            }
+            println!(
+                "⚠️ {}; falling back to the system winecfg, whose Wine version may not match the prefix",
+                e
+            );
+            let mut cmd = Command::new("winecfg");
+            cmd.env("WINEPREFIX", prefix_path.join("pfx"));
+            sandbox::host_command(cmd)
+        }
+    };
+    if let Some(warning) = proton_runtime::container_warning(appid) {
+        println!("⚠️ {}", warning);
+    }
+    if let Err(e) = run_winecfg(cmd) {
+        eprintln!("❌ Failed to launch winecfg: {}", e);
     }
 }
 
@@ -76,9 +108,21 @@ mod tests {
         WINECFG_CALLS.lock().unwrap().clear();
         execute(appid);
 
+        let proton = home.path().join("library/steamapps/common/Proton 9.0");
+        fs::create_dir_all(proton.join("files/bin")).unwrap();
+        fs::write(proton.join("proton"), "").unwrap();
+        fs::write(proton.join("files/bin/wine"), "").unwrap();
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        execute(appid);
+
         let calls = WINECFG_CALLS.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        assert_eq!(calls[0], prefix);
+        assert_eq!(calls.len(), 2);
+        // Without a Proton build the system winecfg is the fallback.
+        assert_eq!(calls[0], ("winecfg".into(), vec![], Some(prefix.join("pfx"))));
+        assert_eq!(
+            calls[1],
+            (proton.join("files/bin/wine"), vec!["winecfg".to_string()], Some(prefix.join("pfx")))
+        );
 
         if let Some(h) = old_home { std::env::set_var("HOME", h); }
     }
@@ -0,0 +1,94 @@
+use crate::utils::dependencies::{command_available, missing_message};
+use crate::utils::proton_runtime;
+
+#[cfg(test)]
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(not(test))]
+fn run_winetricks(mut cmd: std::process::Command) -> std::io::Result<()> {
+    let status = cmd.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "winetricks exited with status {}",
+            status
+        )))
+    }
+}
+
+#[cfg(test)]
+pub static WINETRICKS_CALLS: Lazy<Mutex<Vec<Vec<String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[cfg(test)]
+fn run_winetricks(cmd: std::process::Command) -> std::io::Result<()> {
+    WINETRICKS_CALLS
+        .lock()
+        .unwrap()
+        .push(cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect());
+    Ok(())
+}
+
+/// Run winetricks in the prefix of `appid` with the Wine of the game's
+/// Proton build. Without verbs the winetricks GUI opens.
+pub fn execute(appid: u32, verbs: &[String]) {
+    log::debug!("winetricks command: appid={} verbs={:?}", appid, verbs);
+    println!("🔧 Running winetricks for AppID: {}", appid);
+
+    if !command_available("winetricks") {
+        eprintln!("❌ {}", missing_message("winetricks"));
+        return;
+    }
+
+    match proton_runtime::wine_command(appid, "winetricks") {
+        Ok(mut cmd) => {
+            if let Some(warning) = proton_runtime::container_warning(appid) {
+                println!("⚠️ {}", warning);
+            }
+            if verbs.is_empty() {
+                cmd.arg("--gui");
+            } else {
+                cmd.args(verbs);
+            }
+            if let Err(e) = run_winetricks(cmd) {
+                eprintln!("❌ Failed to run winetricks: {}", e);
+            }
+        }
+        Err(e) => eprintln!("❌ Error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use std::fs;
+
+    #[test]
+    fn test_execute_runs_winetricks() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 6543;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        let proton = home.path().join("library/steamapps/common/Proton 8.0");
+        fs::create_dir_all(proton.join("files/bin")).unwrap();
+        fs::write(proton.join("proton"), "").unwrap();
+        fs::write(proton.join("files/bin/wine"), "").unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        WINETRICKS_CALLS.lock().unwrap().clear();
+        execute(appid, &["vcrun2019".to_string()]);
+        execute(appid, &[]);
+
+        let calls = WINETRICKS_CALLS.lock().unwrap();
+        assert_eq!(*calls, vec![vec!["vcrun2019".to_string()], vec!["--gui".to_string()]]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
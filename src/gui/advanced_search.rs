@@ -1,11 +1,19 @@
 use super::sort::{sort_games, GameSortKey};
 use crate::core::models::GameInfo;
 use crate::core::steam;
-use crate::utils::{manifest as manifest_utils, user_config};
+use crate::utils::disk_size::{self, SizeRange};
+use crate::utils::file_search::{self, SearchEvent};
+use crate::utils::prefix_info::collect_prefix_info;
+use crate::utils::app_config::{self, SearchPreset};
+use crate::utils::{manifest as manifest_utils, notes, output, user_config};
 use eframe::egui;
 use eframe::egui::Modal;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use tinyfiledialogs as tfd;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TriState {
@@ -29,6 +37,22 @@ impl TriState {
         }
     }
 
+    fn to_option(self) -> Option<bool> {
+        match self {
+            TriState::Any => None,
+            TriState::Has => Some(true),
+            TriState::Missing => Some(false),
+        }
+    }
+
+    fn from_option(value: Option<bool>) -> Self {
+        match value {
+            None => TriState::Any,
+            Some(true) => TriState::Has,
+            Some(false) => TriState::Missing,
+        }
+    }
+
     fn label(&self) -> &'static str {
         match self {
             TriState::Any => "Any",
@@ -60,38 +84,82 @@ fn tri_state_combo(ui: &mut egui::Ui, label: &str, state: &mut TriState) -> bool
         });
     changed
 }
-#[derive(Clone)]
 pub struct AdvancedSearchState {
     pub query: String,
+    /// Text to look for in the notes of each game.
+    pub notes_query: String,
     pub has_manifest: TriState,
     pub has_prefix: TriState,
     pub auto_update: TriState,
     pub cloud_sync: TriState,
     pub custom_launch: TriState,
     pub custom_proton: TriState,
+    pub favorite: TriState,
+    /// Tag the games must have.
+    pub tag: Option<String>,
+    /// AppIDs pinned in the game list, kept current by the app.
+    pub favorites: BTreeSet<u32>,
+    /// Prefix size range such as `> 5 GB`.
+    pub prefix_size: String,
+    /// Install size range from the manifest, in the same format.
+    pub install_size: String,
+    /// Proton versions detected in the prefixes to match; empty matches all.
+    pub detected_versions: BTreeSet<String>,
+    /// Part of a file name to look for under `drive_c`.
+    pub file_query: String,
+    pub file_depth: usize,
     pub sort_key: GameSortKey,
     pub descending: bool,
     #[allow(dead_code)]
     last_update: f64,
     pub results: Vec<GameInfo>,
     config_cache: HashMap<u32, ConfigFlags>,
+    /// Proton version detected in each prefix.
+    version_cache: HashMap<u32, Option<String>>,
+    /// Prefixes left out of the results until their size is known.
+    sizes_pending: usize,
+    file_rx: Option<Receiver<SearchEvent>>,
+    /// Prefixes searched for files so far, and in total.
+    file_progress: (usize, usize),
+    /// Files found per game by the last file search, which limits the
+    /// results to those games; `None` before a file search.
+    file_matches: Option<BTreeMap<u32, Vec<PathBuf>>>,
+    /// Saved presets, read from the config file when the dialog first opens.
+    presets: Option<BTreeMap<String, SearchPreset>>,
+    preset_name: String,
 }
 
 impl Default for AdvancedSearchState {
     fn default() -> Self {
         Self {
             query: String::new(),
+            notes_query: String::new(),
             has_manifest: TriState::Any,
             has_prefix: TriState::Any,
             auto_update: TriState::Any,
             cloud_sync: TriState::Any,
             custom_launch: TriState::Any,
             custom_proton: TriState::Any,
+            favorite: TriState::Any,
+            tag: None,
+            favorites: BTreeSet::new(),
+            prefix_size: String::new(),
+            install_size: String::new(),
+            detected_versions: BTreeSet::new(),
+            file_query: String::new(),
+            file_depth: file_search::DEFAULT_DEPTH,
             sort_key: GameSortKey::default(),
             descending: true,
             last_update: 0.0,
             results: Vec::new(),
             config_cache: HashMap::new(),
+            version_cache: HashMap::new(),
+            sizes_pending: 0,
+            file_rx: None,
+            file_progress: (0, 0),
+            file_matches: None,
+            presets: None,
+            preset_name: String::new(),
         }
     }
 }
@@ -133,8 +201,138 @@ impl AdvancedSearchState {
         None
     }
 
+    fn detected_version(&mut self, game: &GameInfo) -> Option<String> {
+        self.version_cache
+            .entry(game.app_id())
+            .or_insert_with(|| collect_prefix_info(game.prefix_path()).version)
+            .clone()
+    }
+
+    /// Every Proton version detected in the prefixes of `games`.
+    fn versions_found(&mut self, games: &[GameInfo]) -> BTreeSet<String> {
+        games
+            .iter()
+            .filter_map(|g| self.detected_version(g))
+            .collect()
+    }
+
+    fn to_preset(&self) -> SearchPreset {
+        SearchPreset {
+            query: self.query.clone(),
+            notes: self.notes_query.clone(),
+            has_manifest: self.has_manifest.to_option(),
+            has_prefix: self.has_prefix.to_option(),
+            auto_update: self.auto_update.to_option(),
+            cloud_sync: self.cloud_sync.to_option(),
+            custom_launch: self.custom_launch.to_option(),
+            custom_proton: self.custom_proton.to_option(),
+            favorite: self.favorite.to_option(),
+            tag: self.tag.clone(),
+            prefix_size: self.prefix_size.clone(),
+            install_size: self.install_size.clone(),
+            detected_versions: self.detected_versions.iter().cloned().collect(),
+            sort_by: Some(self.sort_key.label().to_string()),
+            descending: self.descending,
+        }
+    }
+
+    fn apply_preset(&mut self, preset: &SearchPreset) {
+        self.query = preset.query.clone();
+        self.notes_query = preset.notes.clone();
+        self.has_manifest = TriState::from_option(preset.has_manifest);
+        self.has_prefix = TriState::from_option(preset.has_prefix);
+        self.auto_update = TriState::from_option(preset.auto_update);
+        self.cloud_sync = TriState::from_option(preset.cloud_sync);
+        self.custom_launch = TriState::from_option(preset.custom_launch);
+        self.custom_proton = TriState::from_option(preset.custom_proton);
+        self.favorite = TriState::from_option(preset.favorite);
+        self.tag = preset.tag.clone();
+        self.prefix_size = preset.prefix_size.clone();
+        self.install_size = preset.install_size.clone();
+        self.detected_versions = preset.detected_versions.iter().cloned().collect();
+        self.sort_key = GameSortKey::ALL
+            .into_iter()
+            .find(|k| preset.sort_by.as_deref() == Some(k.label()))
+            .unwrap_or_default();
+        self.descending = preset.descending;
+    }
+
+    /// Save the current filters as preset `name`, replacing one of the
+    /// same name.
+    fn save_preset(&mut self, name: &str) -> crate::error::Result<()> {
+        let mut config = app_config::load();
+        config.search_presets.insert(name.to_string(), self.to_preset());
+        app_config::save(&config)?;
+        self.presets = Some(config.search_presets);
+        Ok(())
+    }
+
+    fn delete_preset(&mut self, name: &str) -> crate::error::Result<()> {
+        let mut config = app_config::load();
+        config.search_presets.remove(name);
+        app_config::save(&config)?;
+        self.presets = Some(config.search_presets);
+        Ok(())
+    }
+
+    /// Search the prefixes of all games for `file_query` in the background.
+    fn start_file_search(&mut self, games: &[GameInfo]) {
+        let prefixes: Vec<(u32, PathBuf)> = games
+            .iter()
+            .filter(|g| g.prefix_path().exists())
+            .map(|g| (g.app_id(), g.prefix_path().to_path_buf()))
+            .collect();
+        self.file_progress = (0, prefixes.len());
+        self.file_matches = Some(BTreeMap::new());
+        let (tx, rx) = mpsc::channel();
+        let query = self.file_query.trim().to_string();
+        let depth = self.file_depth;
+        thread::spawn(move || file_search::search(prefixes, query, depth, tx));
+        self.file_rx = Some(rx);
+    }
+
+    /// Collect what the file search found since the last frame. Returns
+    /// whether there were new matches.
+    fn receive_file_matches(&mut self) -> bool {
+        let Some(rx) = &self.file_rx else {
+            return false;
+        };
+        let mut found = false;
+        loop {
+            match rx.try_recv() {
+                Ok(SearchEvent::Found { app_id, path }) => {
+                    if let Some(matches) = &mut self.file_matches {
+                        matches.entry(app_id).or_default().push(path);
+                    }
+                    found = true;
+                }
+                Ok(SearchEvent::Progress { done, total }) => self.file_progress = (done, total),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.file_rx = None;
+                    found = true;
+                    break;
+                }
+            }
+        }
+        found
+    }
+
     pub fn perform_search(&mut self, games: &[GameInfo]) {
         let q = self.query.to_lowercase();
+        let size_range = SizeRange::parse(&self.prefix_size);
+        let install_range = SizeRange::parse(&self.install_size);
+        let notes_query = self.notes_query.trim().to_lowercase();
+        let tagged: Option<BTreeSet<u32>> = self
+            .tag
+            .as_ref()
+            .map(|tag| app_config::load().tagged(tag).into_iter().collect());
+        let notes = if notes_query.is_empty() {
+            HashMap::new()
+        } else {
+            notes::all()
+        };
+        self.sizes_pending = 0;
         let require_flags = self.sort_key == GameSortKey::ProtonVersion
             || self.auto_update != TriState::Any
             || self.cloud_sync != TriState::Any
@@ -153,6 +351,28 @@ impl AdvancedSearchState {
                         .contains(&q))
                     && self.has_manifest.matches(g.has_manifest())
                     && self.has_prefix.matches(g.prefix_path().exists())
+                    && self.favorite.matches(self.favorites.contains(&g.app_id()))
+                    && tagged.as_ref().is_none_or(|t| t.contains(&g.app_id()))
+                    && (notes_query.is_empty()
+                        || notes
+                            .get(&g.app_id())
+                            .is_some_and(|n| n.to_lowercase().contains(&notes_query)))
+                    && self
+                        .file_matches
+                        .as_ref()
+                        .is_none_or(|m| m.contains_key(&g.app_id()))
+                    && (self.detected_versions.is_empty()
+                        || self
+                            .detected_version(g)
+                            .is_some_and(|v| self.detected_versions.contains(&v)))
+                    && install_range.is_none_or(|range| range.contains(g.size_on_disk()))
+                    && size_range.is_none_or(|range| match disk_size::size(g.prefix_path()) {
+                        Some(size) => range.contains(size),
+                        None => {
+                            self.sizes_pending += 1;
+                            false
+                        }
+                    })
                     && {
                         if !require_flags {
                             true
@@ -189,6 +409,19 @@ impl AdvancedSearchState {
             }
             GameSortKey::Name => sort_games(&mut self.results, GameSortKey::Name, descending),
             GameSortKey::AppId => sort_games(&mut self.results, GameSortKey::AppId, descending),
+            GameSortKey::InstallSize => {
+                sort_games(&mut self.results, GameSortKey::InstallSize, descending)
+            }
+            GameSortKey::Size => {
+                // Keep searching until every result is measured so the
+                // order settles.
+                self.sizes_pending += self
+                    .results
+                    .iter()
+                    .filter(|g| disk_size::size(g.prefix_path()).is_none())
+                    .count();
+                sort_games(&mut self.results, GameSortKey::Size, descending)
+            }
             GameSortKey::ProtonVersion => {
                 self.results.sort_by(|a, b| {
                     let pa = self
@@ -225,6 +458,18 @@ pub fn advanced_search_dialog(
     let mut should_close = false;
     let mut close_window = false;
 
+    // Add prefixes to the results as the disk-size service measures them.
+    if state.sizes_pending > 0 {
+        state.perform_search(games);
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+    }
+    if state.file_rx.is_some() {
+        if state.receive_file_matches() {
+            state.perform_search(games);
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+
     let response = Modal::new(egui::Id::new("advanced_search"))
         .frame(egui::Frame::window(&ctx.style()))
         .show(ctx, |ui| {
@@ -240,19 +485,101 @@ pub fn advanced_search_dialog(
             ui.columns(2, |columns| {
                 columns[0].vertical(|ui| {
                     egui::ScrollArea::vertical().show(ui, |ui| {
+                        let show_sizes = !state.prefix_size.trim().is_empty()
+                            || state.sort_key == GameSortKey::Size;
+                        let show_install = !state.install_size.trim().is_empty()
+                            || state.sort_key == GameSortKey::InstallSize;
                         for game in &state.results {
+                            let mut label = match show_sizes.then(|| disk_size::size(game.prefix_path())).flatten() {
+                                Some(size) => format!(
+                                    "{} ({}) - {}",
+                                    game.name(),
+                                    game.app_id(),
+                                    output::format_size(size)
+                                ),
+                                None => format!("{} ({})", game.name(), game.app_id()),
+                            };
+                            if show_install && game.size_on_disk() > 0 {
+                                label.push_str(&format!(
+                                    " - installed {}",
+                                    output::format_size(game.size_on_disk())
+                                ));
+                            }
                             if ui
-                                .button(format!("{} ({})", game.name(), game.app_id()))
+                                .button(label)
                                 .clicked()
                             {
                                 *selected = Some(game.clone());
                                 close_window = true;
                             }
+                            let files = state.file_matches.as_ref().and_then(|m| m.get(&game.app_id()));
+                            for path in files.into_iter().flatten() {
+                                let shown = path.strip_prefix(game.prefix_path().join("pfx")).unwrap_or(path);
+                                if ui
+                                    .small_button(shown.display().to_string())
+                                    .on_hover_text("Open the containing folder")
+                                    .clicked()
+                                {
+                                    if let Some(parent) = path.parent() {
+                                        let _ = open::that(parent);
+                                    }
+                                }
+                            }
                         }
                     });
                 });
 
                 columns[1].vertical(|ui| {
+                    let presets = state
+                        .presets
+                        .get_or_insert_with(|| app_config::load().search_presets)
+                        .clone();
+                    let mut preset_error = None;
+                    ui.horizontal(|ui| {
+                        let mut apply = None;
+                        egui::ComboBox::from_id_salt("search_presets")
+                            .selected_text("Presets")
+                            .show_ui(ui, |ui| {
+                                if presets.is_empty() {
+                                    ui.label("No saved presets");
+                                }
+                                for (name, preset) in &presets {
+                                    if ui.selectable_label(state.preset_name == *name, name).clicked() {
+                                        apply = Some((name.clone(), preset.clone()));
+                                    }
+                                }
+                            });
+                        if let Some((name, preset)) = apply {
+                            state.apply_preset(&preset);
+                            state.preset_name = name;
+                            state.perform_search(games);
+                        }
+                        ui.add(
+                            egui::TextEdit::singleline(&mut state.preset_name)
+                                .hint_text("Preset name")
+                                .desired_width(120.0),
+                        );
+                        let name = state.preset_name.trim().to_string();
+                        if ui
+                            .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                            .clicked()
+                        {
+                            preset_error = state.save_preset(&name).err();
+                        }
+                        if ui
+                            .add_enabled(presets.contains_key(&name), egui::Button::new("Delete"))
+                            .clicked()
+                        {
+                            preset_error = state.delete_preset(&name).err();
+                        }
+                    });
+                    if let Some(e) = preset_error {
+                        tfd::message_box_ok(
+                            "Search preset",
+                            &format!("Failed to save the presets: {}", e),
+                            tfd::MessageBoxIcon::Error,
+                        );
+                    }
                     ui.horizontal(|ui| {
                         ui.label("Search:");
                         let resp = ui.text_edit_singleline(&mut state.query);
@@ -260,6 +587,16 @@ pub fn advanced_search_dialog(
                             state.perform_search(games);
                         }
                     });
+                    ui.horizontal(|ui| {
+                        ui.label("Notes:");
+                        let resp = ui.add(
+                            egui::TextEdit::singleline(&mut state.notes_query)
+                                .hint_text("Text in the game's notes"),
+                        );
+                        if resp.changed() {
+                            state.perform_search(games);
+                        }
+                    });
                     ui.separator();
                     let mut changed = false;
                     changed |= tri_state_combo(ui, "Has manifest", &mut state.has_manifest);
@@ -270,6 +607,116 @@ pub fn advanced_search_dialog(
                         tri_state_combo(ui, "Custom launch options", &mut state.custom_launch);
                     changed |=
                         tri_state_combo(ui, "Custom Proton version", &mut state.custom_proton);
+                    changed |= tri_state_combo(ui, "Pinned", &mut state.favorite);
+                    ui.horizontal(|ui| {
+                        ui.label("Tag:");
+                        egui::ComboBox::from_id_salt("adv_tag")
+                            .selected_text(state.tag.as_deref().unwrap_or("Any"))
+                            .show_ui(ui, |ui| {
+                                changed |= ui.selectable_value(&mut state.tag, None, "Any").changed();
+                                for tag in app_config::load().all_tags() {
+                                    let label = tag.clone();
+                                    changed |= ui
+                                        .selectable_value(&mut state.tag, Some(tag), label)
+                                        .changed();
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Prefix size:");
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut state.prefix_size)
+                                    .hint_text("> 5 GB")
+                                    .desired_width(100.0),
+                            )
+                            .changed();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Install size:");
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut state.install_size)
+                                    .hint_text("> 50 GB")
+                                    .desired_width(100.0),
+                            )
+                            .changed();
+                    });
+                    if [&state.prefix_size, &state.install_size]
+                        .iter()
+                        .any(|s| !s.trim().is_empty() && SizeRange::parse(s).is_none())
+                    {
+                        ui.colored_label(egui::Color32::RED, "Use > 5 GB, < 500 MB or 1-10 GB");
+                    }
+                    let versions = state.versions_found(games);
+                    egui::CollapsingHeader::new(if state.detected_versions.is_empty() {
+                        "Detected Proton version: Any".to_string()
+                    } else {
+                        format!("Detected Proton version: {} selected", state.detected_versions.len())
+                    })
+                    .id_salt("detected_versions")
+                    .show(ui, |ui| {
+                        if versions.is_empty() {
+                            ui.label("No Proton version detected in any prefix");
+                        }
+                        for version in versions {
+                            let mut checked = state.detected_versions.contains(&version);
+                            if ui.checkbox(&mut checked, &version).changed() {
+                                if checked {
+                                    state.detected_versions.insert(version);
+                                } else {
+                                    state.detected_versions.remove(&version);
+                                }
+                                changed = true;
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.label("File name in prefix (under drive_c):");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut state.file_query)
+                                .hint_text("d3dx9_43.dll")
+                                .desired_width(140.0),
+                        );
+                        ui.add(egui::DragValue::new(&mut state.file_depth).range(1..=20).prefix("Depth: "));
+                    });
+                    ui.horizontal(|ui| {
+                        if state.file_rx.is_some() {
+                            if ui.button("Cancel").clicked() {
+                                state.file_rx = None;
+                            }
+                        } else if ui
+                            .add_enabled(
+                                !state.file_query.trim().is_empty(),
+                                egui::Button::new("Search Files"),
+                            )
+                            .clicked()
+                        {
+                            state.start_file_search(games);
+                            changed = true;
+                        }
+                        if state.file_matches.is_some()
+                            && state.file_rx.is_none()
+                            && ui.button("Clear Files").clicked()
+                        {
+                            state.file_matches = None;
+                            changed = true;
+                        }
+                    });
+                    if state.file_rx.is_some() {
+                        let (done, total) = state.file_progress;
+                        ui.add(
+                            egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                                .text(format!("Searching prefixes {}/{}", done, total)),
+                        );
+                    }
+                    if state.sizes_pending > 0 {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("Measuring {} prefix(es)...", state.sizes_pending));
+                        });
+                    }
                     ui.separator();
                     egui::ComboBox::from_label("Sort By")
                         .selected_text(match state.sort_key {
@@ -278,6 +725,8 @@ pub fn advanced_search_dialog(
                             GameSortKey::Name => "Name",
                             GameSortKey::AppId => "AppID",
                             GameSortKey::ProtonVersion => "Proton Version",
+                            GameSortKey::InstallSize => "Install Size",
+                            GameSortKey::Size => "Prefix Size",
                         })
                         .show_ui(ui, |ui| {
                             changed |= ui
@@ -307,10 +756,26 @@ pub fn advanced_search_dialog(
                                     "Proton Version",
                                 )
                                 .changed();
+                            changed |= ui
+                                .selectable_value(
+                                    &mut state.sort_key,
+                                    GameSortKey::InstallSize,
+                                    "Install Size",
+                                )
+                                .changed();
+                            changed |= ui
+                                .selectable_value(
+                                    &mut state.sort_key,
+                                    GameSortKey::Size,
+                                    "Prefix Size",
+                                )
+                                .changed();
                         });
                     changed |= ui.checkbox(&mut state.descending, "Descending").changed();
                     if ui.button("Clear Previous Search").clicked() {
+                        let favorites = std::mem::take(&mut state.favorites);
                         *state = AdvancedSearchState::default();
+                        state.favorites = favorites;
                         state.perform_search(games);
                     }
                     ui.separator();
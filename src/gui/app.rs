@@ -1,22 +1,43 @@
 use super::advanced_search::{advanced_search_dialog, AdvancedSearchState};
+use super::archive::ArchiveWindow;
 use super::backup_manager::BackupManagerWindow;
-use super::details::{Action, GameConfig, GameDetails, PrefixInfo};
-use super::game_list::GameList;
+use super::compat_tools::CompatToolsWindow;
+use super::details::{Action, DetailsCache, GameDetails, SaveLookup};
+use super::game_list::{GameGrouping, GameList};
+use super::heroic::{heroic_list, HeroicDetails};
+use super::hidden_games::HiddenGamesWindow;
+use super::libraries::LibrariesWindow;
+use super::library_health::LibraryHealthWindow;
+use super::profiles::ProfilesWindow;
+use super::protontricks::ProtontricksWindow;
 use super::runtime_cleaner::RuntimeCleanerWindow;
-use super::sort::{sort_games, GameSortKey};
+use super::wasted_space::WastedSpaceWindow;
+use super::settings::SettingsWindow;
+use super::sort::{sort_games, sort_games_by_version, GameSortKey};
 use crate::core::models::GameInfo;
 use crate::core::steam;
 use crate::utils::dependencies::scan_tools;
+use crate::utils::heroic::{self, HeroicGame};
+use crate::utils::health_monitor::{self, Regression};
+use crate::utils::{hooks, keep_saves, prefix_import, snapshot};
+use crate::utils::logging;
+use crate::utils::output;
+use crate::utils::proton_ge::{self, GeRelease};
+use crate::utils::{app_config, artwork, disk_size, prefix_info, prefix_repair, proton_runtime};
+use crate::utils::save_locations::{self, SaveLocation};
 use crate::utils::terminal;
+use crate::utils::vkbasalt;
 use eframe::egui;
 use eframe::egui::Modal;
 use eframe::egui::{FontDefinitions};
 use egui_phosphor::{self as phosphor, regular};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::collections::HashMap;
-use std::sync::mpsc::{self, Receiver};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Instant;
 use tinyfiledialogs as tfd;
 
 pub struct ProtonPrefixManagerApp {
@@ -36,23 +57,75 @@ pub struct ProtonPrefixManagerApp {
     // removed validation and repair features
     tool_status: BTreeMap<String, bool>,
     last_tool_scan: f64,
-    config_cache: HashMap<u32, GameConfig>,
-    prefix_cache: HashMap<u32, PrefixInfo>,
+    details_cache: DetailsCache,
+    save_tx: Sender<(u32, crate::error::Result<Vec<SaveLocation>>)>,
+    save_rx: Receiver<(u32, crate::error::Result<Vec<SaveLocation>>)>,
     show_backup_manager: bool,
     backup_manager: BackupManagerWindow,
     show_runtime_cleaner: bool,
     runtime_cleaner: RuntimeCleanerWindow,
+    show_wasted_space: bool,
+    wasted_space: WastedSpaceWindow,
+    show_library_health: bool,
+    library_health: LibraryHealthWindow,
+    show_libraries: bool,
+    libraries: LibrariesWindow,
+    show_archive: bool,
+    archive: ArchiveWindow,
+    show_settings: bool,
+    settings: SettingsWindow,
+    show_profiles: bool,
+    profiles: ProfilesWindow,
+    show_compat_tools: bool,
+    compat_tools: CompatToolsWindow,
+    show_protontricks: bool,
+    protontricks: ProtontricksWindow,
+    artwork: HashMap<u32, Option<PathBuf>>,
+    art_tx: Sender<(u32, PathBuf)>,
+    art_rx: Receiver<(u32, PathBuf)>,
+    ge_update: Option<GeRelease>,
+    ge_dismissed: Option<String>,
+    ge_rx: Option<Receiver<Option<GeRelease>>>,
+    last_ge_check: Option<f64>,
+    health_rx: Option<Receiver<Vec<Regression>>>,
+    /// When the health config is read again and, if enabled, the prefixes
+    /// of recently played games are checked.
+    next_health_check: Option<f64>,
+    /// Prefix regressions found by the background check, shown until
+    /// dismissed.
+    health_alerts: Vec<Regression>,
+    heroic_games: Vec<HeroicGame>,
+    selected_heroic: Option<HeroicGame>,
+    heroic_problems: Option<Vec<String>>,
+    heroic_restore_open: bool,
     show_advanced_search: bool,
     adv_state: AdvancedSearchState,
     sort_key: GameSortKey,
     descending: bool,
+    grouping: GameGrouping,
+    /// AppIDs pinned to the top of the list.
+    favorites: BTreeSet<u32>,
+    /// AppIDs hidden from the list, the `exclude` setting.
+    hidden: BTreeSet<u32>,
+    show_hidden: bool,
+    /// Only list games with this tag.
+    tag_filter: Option<String>,
+    show_hidden_games: bool,
+    hidden_games: HiddenGamesWindow,
+    /// Prefix sizes measured when the list was last sorted by size.
+    sizes_sorted: u64,
     show_task_dialog: bool,
     task_message: String,
     task_rx: Option<Receiver<crate::error::Result<String>>>,
+    /// Bytes copied and total bytes of the running task, if it reports them.
+    task_progress: Arc<Mutex<Option<(u64, u64)>>>,
+    load_started: Instant,
 }
 
 impl Default for ProtonPrefixManagerApp {
     fn default() -> Self {
+        let (save_tx, save_rx) = mpsc::channel();
+        let (art_tx, art_rx) = mpsc::channel();
         Self {
             loading: true,
             search_query: String::new(),
@@ -68,76 +141,294 @@ impl Default for ProtonPrefixManagerApp {
             restore_dialog_open: false,
             delete_dialog_open: false,
             tool_status: {
-                let mut map = scan_tools(&["protontricks", "winecfg"]);
+                let mut map = scan_tools(&["protontricks", "winetricks", "winecfg", "mangohud", "gamescope"]);
                 map.insert("terminal".to_string(), terminal::terminal_available());
+                map.insert("vkbasalt".to_string(), vkbasalt::installed());
                 map
             },
             last_tool_scan: 0.0,
-            config_cache: HashMap::new(),
-            prefix_cache: HashMap::new(),
+            details_cache: DetailsCache::default(),
+            save_tx,
+            save_rx,
             show_backup_manager: false,
             backup_manager: BackupManagerWindow::new(),
             show_runtime_cleaner: false,
             runtime_cleaner: RuntimeCleanerWindow::new(),
+            show_wasted_space: false,
+            wasted_space: WastedSpaceWindow::new(),
+            show_library_health: false,
+            library_health: LibraryHealthWindow::new(),
+            show_libraries: false,
+            libraries: LibrariesWindow::new(),
+            show_archive: false,
+            archive: ArchiveWindow::new(),
+            show_settings: false,
+            settings: SettingsWindow::new(),
+            show_profiles: false,
+            profiles: ProfilesWindow::new(),
+            show_compat_tools: false,
+            compat_tools: CompatToolsWindow::new(),
+            show_protontricks: false,
+            protontricks: ProtontricksWindow::new(),
+            artwork: HashMap::new(),
+            art_tx,
+            art_rx,
+            ge_update: None,
+            ge_dismissed: None,
+            ge_rx: None,
+            last_ge_check: None,
+            health_rx: None,
+            next_health_check: None,
+            health_alerts: Vec::new(),
+            heroic_games: Vec::new(),
+            selected_heroic: None,
+            heroic_problems: None,
+            heroic_restore_open: false,
             show_advanced_search: false,
             adv_state: AdvancedSearchState::default(),
             sort_key: GameSortKey::LastPlayed,
             descending: true,
+            grouping: GameGrouping::None,
+            favorites: BTreeSet::new(),
+            hidden: BTreeSet::new(),
+            show_hidden: false,
+            tag_filter: None,
+            show_hidden_games: false,
+            hidden_games: HiddenGamesWindow::new(),
+            sizes_sorted: 0,
             show_task_dialog: false,
             task_message: String::new(),
             task_rx: None,
+            task_progress: Arc::new(Mutex::new(None)),
+            load_started: Instant::now(),
         }
     }
 }
 
 impl ProtonPrefixManagerApp {
     pub fn new() -> Self {
-        let app = Self::default();
-        let games = Arc::clone(&app.installed_games);
-
-        thread::spawn(move || match steam::get_steam_libraries() {
-            Ok(libraries) => match steam::load_games_from_libraries(&libraries) {
-                Ok(local_list) => {
-                    let mut locked = games.lock().unwrap();
-                    *locked = local_list;
+        let mut app = Self::default();
+        app.load_heroic_games();
+        app.load_games();
+        app
+    }
+
+    /// Load the installed Steam games in the background, leaving out the
+    /// AppIDs excluded in the settings.
+    fn load_games(&mut self) {
+        self.loading = true;
+        self.load_started = Instant::now();
+        let games = Arc::clone(&self.installed_games);
+        let general = app_config::load().general;
+        self.favorites = general.favorites.iter().copied().collect();
+        self.adv_state.favorites = self.favorites.clone();
+        self.hidden = general.exclude.iter().copied().collect();
+        let show_hidden = self.show_hidden;
+
+        thread::spawn(move || {
+            // Pick up prefixes Proton rebuilt since they were reset.
+            for (pending, result) in keep_saves::restore_pending() {
+                if let Err(e) = result {
+                    log::error!("Failed to restore the kept saves of {}: {}", pending.appid, e);
                 }
+            }
+            match steam::get_steam_libraries() {
+                Ok(libraries) => match steam::load_games_from_libraries(&libraries) {
+                    Ok(mut local_list) => {
+                        if !show_hidden {
+                            local_list.retain(|g| !general.is_excluded(g.app_id()));
+                        }
+                        let mut locked = games.lock().unwrap();
+                        *locked = local_list;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load games: {}", e);
+                    }
+                },
                 Err(e) => {
-                    log::error!("Failed to load games: {}", e);
+                    log::error!("Failed to get Steam libraries: {}", e);
                 }
-            },
-            Err(e) => {
-                log::error!("Failed to get Steam libraries: {}", e);
             }
         });
-
-        app
     }
 
     fn clear_selection_data(&mut self, app_id: Option<u32>) {
         if let Some(id) = app_id {
-            self.config_cache.remove(&id);
-            self.prefix_cache.remove(&id);
+            self.details_cache.remove(id);
         }
         if app_id.is_none() {
-            self.config_cache.clear();
-            self.prefix_cache.clear();
+            self.details_cache = DetailsCache::default();
         }
         crate::utils::library::clear_manifest_cache();
         crate::utils::user_config::clear_localconfig_cache();
     }
 
+    /// Scan for Heroic prefixes if the section is enabled in the settings.
+    fn load_heroic_games(&mut self) {
+        self.heroic_games = if app_config::load().heroic.enabled {
+            heroic::discover()
+        } else {
+            Vec::new()
+        };
+        if let Some(selected) = &self.selected_heroic {
+            if !self.heroic_games.iter().any(|g| g.app_name == selected.app_name) {
+                self.selected_heroic = None;
+            }
+        }
+    }
+
+    /// Download SteamGridDB art for games without local artwork, if enabled.
+    fn start_artwork_fetch(&self) {
+        let config = app_config::load();
+        let Some(key) = config.artwork.steamgriddb_key().map(str::to_string) else {
+            return;
+        };
+        let ids: Vec<u32> = match self.installed_games.lock() {
+            Ok(games) => games.iter().map(|g| g.app_id()).collect(),
+            Err(_) => return,
+        };
+        let tx = self.art_tx.clone();
+        thread::spawn(move || {
+            for id in ids.into_iter().filter(|id| artwork::needs_fetch(*id)) {
+                match artwork::fetch_steamgriddb(id, &key) {
+                    Ok(Some(path)) => {
+                        if tx.send((id, path)).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        // Most failures (bad key, no network) affect every
+                        // request, so stop instead of retrying each game.
+                        log::warn!("SteamGridDB artwork download failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Look for a newer GE-Proton release in the background.
+    fn start_ge_check(&mut self, force: bool) {
+        if self.ge_rx.is_some() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let update = proton_ge::available_update(force).unwrap_or_else(|e| {
+                log::warn!("GE-Proton update check failed: {}", e);
+                None
+            });
+            let _ = tx.send(update);
+        });
+        self.ge_rx = Some(rx);
+    }
+
+    /// Check the prefixes of recently played games in the background.
+    fn start_health_check(&mut self, config: app_config::HealthConfig) {
+        let games = self.installed_games.lock().map(|g| g.clone()).unwrap_or_default();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let regressions = health_monitor::check(&games, &config).unwrap_or_else(|e| {
+                log::warn!("Prefix health check failed: {}", e);
+                Vec::new()
+            });
+            let _ = tx.send(regressions);
+        });
+        self.health_rx = Some(rx);
+    }
+
+    /// Toast in the bottom right corner listing the prefixes that got worse.
+    fn show_health_alerts(&mut self, ctx: &egui::Context) {
+        if self.health_alerts.is_empty() {
+            return;
+        }
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("health_alerts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -40.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(360.0);
+                    for (i, alert) in self.health_alerts.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!("{} {} ({})", regular::WARNING, alert.name, alert.appid),
+                            );
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button(regular::X).on_hover_text("Dismiss").clicked() {
+                                    dismissed = Some(i);
+                                }
+                            });
+                        });
+                        for problem in &alert.problems {
+                            ui.label(format!("• {}", problem));
+                        }
+                    }
+                });
+            });
+        if let Some(i) = dismissed {
+            self.health_alerts.remove(i);
+        }
+    }
+
     fn sort_filtered_games(&mut self) {
-        sort_games(&mut self.filtered_games, self.sort_key, self.descending);
+        if self.sort_key == GameSortKey::ProtonVersion {
+            sort_games_by_version(
+                &mut self.filtered_games,
+                &mut self.details_cache.prefix_info,
+                self.descending,
+            );
+        } else {
+            sort_games(&mut self.filtered_games, self.sort_key, self.descending);
+        }
+        // Pinned games stay on top in the chosen order.
+        self.filtered_games.sort_by_key(|g| !self.favorites.contains(&g.app_id()));
+    }
+
+    /// Write the pinned games to the config file.
+    fn save_favorites(&mut self) {
+        let mut config = app_config::load();
+        config.general.favorites = self.favorites.iter().copied().collect();
+        if let Err(e) = app_config::save(&config) {
+            log::error!("Failed to save pinned games: {}", e);
+        }
+        self.adv_state.favorites = self.favorites.clone();
+    }
+
+    /// Write the hidden games to the config file and reload the list
+    /// without them.
+    fn save_hidden(&mut self) {
+        let mut config = app_config::load();
+        config.general.exclude = self.hidden.iter().copied().collect();
+        if let Err(e) = app_config::save(&config) {
+            log::error!("Failed to save hidden games: {}", e);
+        }
+        self.reload_games();
+    }
+
+    /// Load the game list again after a setting that filters it changed.
+    fn reload_games(&mut self) {
+        if let Ok(mut games) = self.installed_games.lock() {
+            games.clear();
+        }
+        self.load_games();
     }
 
     fn search_games(&mut self) {
         let query = self.search_query.to_lowercase();
+        let tagged: Option<BTreeSet<u32>> = self
+            .tag_filter
+            .as_ref()
+            .map(|tag| app_config::load().tagged(tag).into_iter().collect());
         if let Ok(locked) = self.installed_games.lock() {
             self.filtered_games = locked
                 .iter()
                 .filter(|game| {
-                    game.name().to_lowercase().contains(&query)
-                        || game.app_id().to_string().contains(&query)
+                    (game.name().to_lowercase().contains(&query)
+                        || game.app_id().to_string().contains(&query))
+                        && tagged.as_ref().is_none_or(|t| t.contains(&game.app_id()))
                 })
                 .cloned()
                 .collect();
@@ -216,6 +507,7 @@ impl ProtonPrefixManagerApp {
     {
         self.show_task_dialog = true;
         self.task_message = msg.to_string();
+        *self.task_progress.lock().unwrap() = None;
         let (tx, rx) = mpsc::channel();
         thread::spawn(move || {
             let res = task();
@@ -227,28 +519,273 @@ impl ProtonPrefixManagerApp {
     fn handle_action(&mut self, action: Action) {
         use Action::*;
         match action {
-            Backup { app_id, prefix } => {
+            Backup {
+                app_id,
+                prefix,
+                saves_only,
+                shader_cache,
+            } => {
+                let progress = self.task_progress.clone();
                 self.start_task("Creating backup...", move || {
-                    crate::utils::backup::create_backup(&prefix, app_id)
+                    let res = hooks::backup(&prefix, || {
+                        if saves_only {
+                            save_locations::detect_prefix_locations(app_id, &prefix).and_then(
+                                |locations| {
+                                    crate::utils::backup::create_saves_backup(
+                                        &prefix, app_id, &locations,
+                                    )
+                                },
+                            )
+                        } else {
+                            crate::utils::backup::create_backup_with_progress(
+                                &prefix,
+                                app_id,
+                                shader_cache,
+                                &|copied, total| {
+                                    *progress.lock().unwrap() = Some((copied, total));
+                                },
+                            )
+                        }
+                    });
+                    res.map(|p| format!("Backup created at {}", p.display()))
+                });
+            }
+            BackupInto { prefix, root } => {
+                self.start_task("Creating backup...", move || {
+                    hooks::backup(&prefix, || crate::utils::backup::create_backup_in(&prefix, &root))
                         .map(|p| format!("Backup created at {}", p.display()))
                 });
             }
-            Restore { backup, prefix } => {
+            Restore { backup, prefix, delta } => {
                 self.start_task("Restoring backup...", move || {
-                    crate::utils::backup::restore_prefix(&backup, &prefix)
-                        .map(|_| "Prefix restored".to_string())
+                    let mut summary = None;
+                    hooks::restore(&prefix, &backup, || {
+                        if delta {
+                            let changes = crate::utils::backup::restore_prefix_delta(&backup, &prefix)?;
+                            summary = Some(changes);
+                            Ok(prefix.clone())
+                        } else {
+                            crate::utils::backup::restore_prefix(&backup, &prefix)
+                        }
+                    })
+                    .map(|_| match summary {
+                        Some(summary) => format!("Prefix restored ({})", summary),
+                        None => "Prefix restored".to_string(),
+                    })
+                });
+            }
+            RestoreAs { backup, app_id } => {
+                self.details_cache.remove(app_id);
+                let progress = self.task_progress.clone();
+                self.start_task("Restoring backup...", move || {
+                    let result = prefix_import::restore_as(&backup, app_id, &|copied, total| {
+                        *progress.lock().unwrap() = Some((copied, total));
+                    })?;
+                    Ok(match result.backup {
+                        Some(backup) => format!(
+                            "Prefix restored to {}. The previous prefix was backed up to {}",
+                            result.prefix.display(),
+                            backup.display()
+                        ),
+                        None => format!("Prefix restored to {}", result.prefix.display()),
+                    })
+                });
+            }
+            RestorePaths { backup, prefix, paths } => {
+                self.start_task("Restoring selected files...", move || {
+                    let mut count = 0;
+                    hooks::restore(&prefix, &backup, || {
+                        count = crate::utils::backup::restore_paths(&backup, &prefix, &paths)?;
+                        Ok(prefix.clone())
+                    })
+                    .map(|_| format!("Restored {} file(s)", count))
                 });
             }
             DeleteBackup { backup } => {
                 self.start_task("Deleting backup...", move || {
-                    crate::utils::backup::delete_backup(&backup)
+                    logging::operation("delete_backup", None, &backup, || {
+                        crate::utils::backup::delete_backup(&backup)
+                    })
                         .map(|_| "Backup removed".to_string())
                 });
             }
-            Reset { prefix } => {
+            OpenProtontricks { app_id, name } => {
+                self.protontricks.open_for(app_id, &name);
+                self.show_protontricks = true;
+            }
+            Reset {
+                app_id,
+                prefix,
+                keep_saves,
+                steps,
+            } => {
                 self.start_task("Deleting prefix...", move || {
-                    crate::utils::backup::reset_prefix(&prefix)
-                        .map(|_| "Prefix deleted".to_string())
+                    if keep_saves {
+                        return keep_saves::reset(app_id, &prefix).map(|_| {
+                            "Prefix deleted. The saves are put back after the game was launched again.".to_string()
+                        });
+                    }
+                    hooks::reset(&prefix, || crate::utils::backup::reset_prefix_steps(&prefix, &steps))
+                        .map(|_| {
+                            if steps.is_empty() {
+                                "Prefix deleted".to_string()
+                            } else {
+                                let parts: Vec<&str> = steps.iter().map(|s| s.label()).collect();
+                                format!("Reset {} of the prefix", parts.join(", "))
+                            }
+                        })
+                });
+            }
+            RepairPrefix { app_id, prefix } => {
+                self.start_task("Repairing prefix...", move || {
+                    let wine_prefix = prefix.join("pfx");
+                    let dist = proton_runtime::proton_for(app_id).and_then(|proton| proton_runtime::wine_dir(&proton));
+                    let fixes = hooks::repair(&prefix, || {
+                        let mut fixes = prefix_repair::fix_dosdevices(&wine_prefix)?;
+                        fixes.extend(prefix_repair::fix_registry(&wine_prefix, &snapshot::copies(&prefix))?);
+                        fixes.extend(proton_runtime::regenerate_registry(app_id, &prefix)?);
+                        if let Some(dist) = &dist {
+                            let backup_dir = prefix_repair::dll_backup_dir(app_id);
+                            fixes.extend(prefix_repair::fix_d3d_dlls(&wine_prefix, dist, &backup_dir)?);
+                        }
+                        Ok(fixes)
+                    })?;
+                    let mut message = if fixes.is_empty() {
+                        "Nothing to repair".to_string()
+                    } else {
+                        format!("Repaired:\n{}", fixes.join("\n"))
+                    };
+                    let problems = prefix_info::validate_wine_prefix(&wine_prefix);
+                    if !problems.is_empty() {
+                        message.push_str(&format!("\n\nProblems left:\n{}", problems.join("\n")));
+                    }
+                    Ok(message)
+                });
+            }
+            FixBrokenLinks { prefix, fixes } => {
+                self.start_task("Fixing broken links...", move || {
+                    let wine_prefix = prefix.join("pfx");
+                    let done = hooks::repair(&prefix, || {
+                        prefix_repair::fix_broken_links(&wine_prefix, &fixes)
+                    })?;
+                    Ok(if done.is_empty() {
+                        "No links changed".to_string()
+                    } else {
+                        format!("Fixed {} link(s):\n{}", done.len(), done.join("\n"))
+                    })
+                });
+            }
+            RelocatePrefix {
+                app_id,
+                prefix,
+                target,
+            } => {
+                self.details_cache.remove(app_id);
+                self.start_task("Moving prefix...", move || {
+                    crate::utils::prefix_relocate::relocate(&prefix, &target)
+                        .map(|dest| format!("Prefix moved to {}", dest.display()))
+                });
+            }
+            BringBackPrefix { app_id, prefix } => {
+                self.details_cache.remove(app_id);
+                self.start_task("Moving prefix back...", move || {
+                    crate::utils::prefix_relocate::bring_back(&prefix)
+                        .map(|from| format!("Prefix moved back from {}", from.display()))
+                });
+            }
+            ArchivePrefixes { targets } => {
+                for (id, _) in &targets {
+                    self.details_cache.remove(*id);
+                }
+                self.start_task("Archiving prefixes...", move || {
+                    let failures: Vec<String> = targets
+                        .iter()
+                        .filter_map(|(id, prefix)| {
+                            crate::utils::archive::archive_prefix(*id, prefix)
+                                .err()
+                                .map(|e| format!("{}: {}", id, e))
+                        })
+                        .collect();
+                    if failures.is_empty() {
+                        Ok(format!("Archived {} prefix(es)", targets.len()))
+                    } else {
+                        Err(crate::error::Error::FileSystemError(format!(
+                            "Failed to archive:\n{}",
+                            failures.join("\n")
+                        )))
+                    }
+                });
+            }
+            UnarchivePrefix { app_id, prefix } => {
+                self.details_cache.remove(app_id);
+                self.start_task("Unarchiving prefix...", move || {
+                    crate::utils::archive::unarchive_prefix(app_id, &prefix)
+                        .map(|p| format!("Prefix restored to {}", p.display()))
+                });
+            }
+            ImportPrefix { app_id, source } => {
+                self.details_cache.remove(app_id);
+                self.start_task("Importing prefix...", move || {
+                    crate::utils::prefix_import::import_prefix(app_id, &source).map(|res| {
+                        match res.backup {
+                            Some(backup) => format!(
+                                "Prefix imported to {}. The previous prefix was backed up to {}",
+                                res.prefix.display(),
+                                backup.display()
+                            ),
+                            None => format!("Prefix imported to {}", res.prefix.display()),
+                        }
+                    })
+                });
+            }
+            LookupSaves {
+                app_id,
+                prefix,
+                refresh,
+            } => {
+                let tx = self.save_tx.clone();
+                thread::spawn(move || {
+                    let res = save_locations::lookup(app_id, refresh).map(|paths| {
+                        let install_dir = steam::find_install_dir(app_id);
+                        save_locations::resolve(&paths, &prefix, install_dir.as_deref())
+                    });
+                    let _ = tx.send((app_id, res));
+                });
+            }
+            SetWindowsVersion { app_id, version } => {
+                let label = crate::utils::windows_version::label(&version);
+                self.start_task("Changing Windows version...", move || {
+                    let result = crate::utils::windows_version::set_version(app_id, &version);
+                    crate::utils::history::record_result(app_id, "windows_version", &label, &result);
+                    result.map(|_| format!("Windows version set to {}", label))
+                });
+            }
+            ApplyProfile {
+                name,
+                profile,
+                app_ids,
+            } => {
+                for id in &app_ids {
+                    self.details_cache.remove(*id);
+                }
+                self.start_task("Applying profile...", move || {
+                    let failures: Vec<String> = app_ids
+                        .iter()
+                        .filter_map(|id| {
+                            crate::utils::profile::import_profile(*id, &profile)
+                                .err()
+                                .map(|e| format!("{}: {}", id, e))
+                        })
+                        .collect();
+                    if failures.is_empty() {
+                        Ok(format!("Applied \"{}\" to {} game(s)", name, app_ids.len()))
+                    } else {
+                        Err(crate::error::Error::FileSystemError(format!(
+                            "Failed to apply \"{}\" to:\n{}",
+                            name,
+                            failures.join("\n")
+                        )))
+                    }
                 });
             }
         }
@@ -274,7 +811,7 @@ impl eframe::App for ProtonPrefixManagerApp {
                     self.filtered_games = games.clone();
                     self.status_message =
                         Some(format!("Loaded {} games", self.filtered_games.len()));
-                } else if games.is_empty() && self.loading && ctx.input(|i| i.time) > 3.0 {
+                } else if games.is_empty() && self.loading && self.load_started.elapsed().as_secs() >= 3 {
                     // If after 3 seconds we still have no games, assume there was an error
                     self.loading = false;
                     self.error_message = Some(
@@ -284,6 +821,7 @@ impl eframe::App for ProtonPrefixManagerApp {
             }
             if !self.loading {
                 self.sort_filtered_games();
+                self.start_artwork_fetch();
             }
         }
 
@@ -301,6 +839,69 @@ impl eframe::App for ProtonPrefixManagerApp {
                 });
         }
 
+        // Check for GE-Proton updates on startup and hourly afterwards;
+        // the release lookup itself is cached for a day.
+        if self
+            .last_ge_check
+            .is_none_or(|last| current_time - last > 3600.0)
+        {
+            self.last_ge_check = Some(current_time);
+            if app_config::load().proton_ge.check_updates {
+                self.start_ge_check(false);
+            }
+        }
+        if let Some(rx) = &self.ge_rx {
+            if let Ok(update) = rx.try_recv() {
+                self.ge_rx = None;
+                self.ge_update = update.filter(|r| self.ge_dismissed.as_ref() != Some(&r.tag));
+            }
+        }
+
+        // Check the prefixes of recently played games when enabled. The
+        // config is read again every interval, or every minute while the
+        // check is off, so changes in the settings are picked up.
+        if !self.loading
+            && self.health_rx.is_none()
+            && self.next_health_check.is_none_or(|next| current_time >= next)
+        {
+            let config = app_config::load().health;
+            let wait = if config.enabled {
+                60.0 * f64::from(config.interval_minutes.max(1))
+            } else {
+                60.0
+            };
+            self.next_health_check = Some(current_time + wait);
+            if config.enabled {
+                self.start_health_check(config);
+            }
+        }
+        if let Some(rx) = &self.health_rx {
+            if let Ok(regressions) = rx.try_recv() {
+                self.health_rx = None;
+                for regression in regressions {
+                    self.health_alerts.retain(|a| a.appid != regression.appid);
+                    self.health_alerts.push(regression);
+                }
+            } else {
+                ctx.request_repaint_after(std::time::Duration::from_millis(500));
+            }
+        }
+        if let Some(next) = self.next_health_check {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64((next - current_time).max(1.0)));
+        }
+
+        while let Ok((app_id, path)) = self.art_rx.try_recv() {
+            self.artwork.insert(app_id, Some(path));
+        }
+
+        while let Ok((app_id, res)) = self.save_rx.try_recv() {
+            let state = match res {
+                Ok(locations) => SaveLookup::Loaded(locations),
+                Err(e) => SaveLookup::Failed(e.to_string()),
+            };
+            self.details_cache.saves.insert(app_id, state);
+        }
+
         if self.search_changed {
             self.search_games();
             self.last_status_update = ctx.input(|i| i.time);
@@ -313,6 +914,27 @@ impl eframe::App for ProtonPrefixManagerApp {
                     if ui.button(if self.dark_mode { regular::SUN } else { regular::MOON }).clicked() {
                         self.toggle_theme(ctx);
                     }
+                    if ui
+                        .button(regular::GEAR)
+                        .on_hover_text("Settings")
+                        .clicked()
+                    {
+                        self.show_settings = true;
+                    }
+                    if ui
+                        .button(format!("{} Profiles", regular::SLIDERS))
+                        .on_hover_text("Define configuration profiles and apply them to games.")
+                        .clicked()
+                    {
+                        self.show_profiles = true;
+                    }
+                    if ui
+                        .button(format!("{} Proton Versions", regular::STACK))
+                        .on_hover_text("Edit the user_settings.py options of installed Proton versions.")
+                        .clicked()
+                    {
+                        self.show_compat_tools = true;
+                    }
                     if ui
                         .button(format!("{} Advanced Search", regular::MAGNIFYING_GLASS))
                         .on_hover_text("Advanced Search")
@@ -337,6 +959,34 @@ impl eframe::App for ProtonPrefixManagerApp {
                     {
                         self.show_runtime_cleaner = true;
                     }
+                    if ui
+                        .button(format!("{} Wasted Space", regular::TRASH))
+                        .on_hover_text("Find crash dumps, installer caches and temporary files inside prefixes.")
+                        .clicked()
+                    {
+                        self.show_wasted_space = true;
+                    }
+                    if ui
+                        .button(format!("{} Archive Inactive", regular::ARCHIVE))
+                        .on_hover_text("Compress prefixes of games that have not been played for months.")
+                        .clicked()
+                    {
+                        self.show_archive = true;
+                    }
+                    if ui
+                        .button(format!("{} Library Statistics", regular::CHART_BAR))
+                        .on_hover_text("Free space, prefixes and problems of every Steam library.")
+                        .clicked()
+                    {
+                        self.show_library_health = true;
+                    }
+                    if ui
+                        .button(format!("{} Libraries", regular::HARD_DRIVES))
+                        .on_hover_text("Disk space, games and prefix sizes of every Steam library.")
+                        .clicked()
+                    {
+                        self.show_libraries = true;
+                    }
                     if let Some(game) = self.selected_game.as_ref() {
                         let details = GameDetails::new(Some(game));
                         if let Some(action) = details.prefix_tools_menu(
@@ -387,6 +1037,8 @@ impl eframe::App for ProtonPrefixManagerApp {
             });
         });
 
+        self.show_health_alerts(ctx);
+
         // Status bar at the bottom
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -399,6 +1051,42 @@ impl eframe::App for ProtonPrefixManagerApp {
                         "GitHub",
                         "https://github.com/D1G1T4L3CH0/proton-prefix-manager",
                     );
+                    ui.separator();
+                    if self.ge_rx.is_some() {
+                        ui.spinner();
+                    } else if ui
+                        .small_button(regular::ARROWS_CLOCKWISE)
+                        .on_hover_text("Check for GE-Proton updates")
+                        .clicked()
+                    {
+                        self.ge_dismissed = None;
+                        self.start_ge_check(true);
+                    }
+                    if let Some(release) = self.ge_update.clone() {
+                        let tag = release.tag.clone();
+                        if ui.small_button(regular::X).on_hover_text("Dismiss").clicked() {
+                            self.ge_dismissed = Some(tag.clone());
+                            self.ge_update = None;
+                        }
+                        if ui.small_button("Update").clicked() {
+                            self.ge_update = None;
+                            let msg = format!("Installing {}...", tag);
+                            self.start_task(&msg, move || {
+                                proton_ge::install(&release).map(|path| {
+                                    format!(
+                                        "{} installed to {}. Restart Steam to use it.",
+                                        release.tag,
+                                        path.display()
+                                    )
+                                })
+                            });
+                        }
+                        ui.label(format!(
+                            "{} {} is available",
+                            regular::ARROW_CIRCLE_UP,
+                            tag
+                        ));
+                    }
                 });
             });
         });
@@ -422,13 +1110,70 @@ impl eframe::App for ProtonPrefixManagerApp {
             egui::SidePanel::left("game_list_panel")
                 .resizable(true)
                 .show(ctx, |ui| {
-                    let changed = GameList::new(&self.filtered_games).show(
+                    if !self.heroic_games.is_empty() {
+                        egui::TopBottomPanel::bottom("heroic_panel")
+                            .resizable(true)
+                            .show_inside(ui, |ui| {
+                                if heroic_list(ui, &self.heroic_games, &mut self.selected_heroic) {
+                                    self.selected_game = None;
+                                    self.heroic_problems = None;
+                                }
+                            });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} Tag:", regular::TAG));
+                        egui::ComboBox::from_id_salt("tag_filter")
+                            .selected_text(self.tag_filter.as_deref().unwrap_or("All games"))
+                            .show_ui(ui, |ui| {
+                                let mut changed = ui
+                                    .selectable_value(&mut self.tag_filter, None, "All games")
+                                    .changed();
+                                for tag in app_config::load().all_tags() {
+                                    let label = tag.clone();
+                                    changed |= ui
+                                        .selectable_value(&mut self.tag_filter, Some(tag), label)
+                                        .changed();
+                                }
+                                if changed {
+                                    self.search_changed = true;
+                                }
+                            });
+                    });
+                    let favorites = self.favorites.len();
+                    let hidden = self.hidden.len();
+                    let show_hidden = self.show_hidden;
+                    let changed = GameList::new(
+                        &self.filtered_games,
+                        &mut self.details_cache.prefix_info,
+                        &mut self.favorites,
+                        &mut self.hidden,
+                        &mut self.show_hidden,
+                        &mut self.show_hidden_games,
+                    )
+                    .show(
                         ui,
                         &mut self.selected_game,
+                        &mut self.artwork,
                         &mut self.sort_key,
                         &mut self.descending,
+                        &mut self.grouping,
                     );
-                    if changed {
+                    // A star click pins or unpins exactly one game.
+                    if self.favorites.len() != favorites {
+                        self.save_favorites();
+                    }
+                    if self.hidden.len() != hidden {
+                        self.save_hidden();
+                    } else if self.show_hidden != show_hidden {
+                        self.reload_games();
+                    }
+                    // Prefix sizes arrive in the background; sort again as
+                    // they do.
+                    let sizes = disk_size::generation();
+                    if changed
+                        || (self.sort_key == GameSortKey::Size && sizes != self.sizes_sorted)
+                    {
+                        self.sizes_sorted = sizes;
                         self.sort_filtered_games();
                     }
                 });
@@ -438,13 +1183,14 @@ impl eframe::App for ProtonPrefixManagerApp {
                 self.clear_selection_data(self.last_selected_app_id);
                 self.last_selected_app_id = current_id;
                 if let Some(id) = current_id {
+                    self.selected_heroic = None;
                     if let Ok(updated) = steam::refresh_game_info(id) {
                         self.selected_game = Some(updated);
                     }
-                    self.config_cache.remove(&id);
-                    self.prefix_cache.insert(
+                    self.details_cache.configs.remove(&id);
+                    self.details_cache.prefix_info.insert(
                         id,
-                        super::details::collect_prefix_info(
+                        crate::utils::prefix_info::collect_prefix_info(
                             self.selected_game.as_ref().unwrap().prefix_path(),
                         ),
                     );
@@ -458,13 +1204,20 @@ impl eframe::App for ProtonPrefixManagerApp {
                     .auto_shrink([false; 2])
                     .id_salt("details_panel")
                     .show(ui, |ui| {
-                        let action = GameDetails::new(self.selected_game.as_ref()).show(
-                            ui,
-                            &mut self.restore_dialog_open,
-                            &mut self.delete_dialog_open,
-                            &mut self.config_cache,
-                            &mut self.prefix_cache,
-                        );
+                        let action = match &self.selected_heroic {
+                            Some(game) => HeroicDetails::new(game).show(
+                                ui,
+                                &mut self.heroic_restore_open,
+                                &mut self.heroic_problems,
+                            ),
+                            None => GameDetails::new(self.selected_game.as_ref()).show(
+                                ui,
+                                &mut self.restore_dialog_open,
+                                &mut self.delete_dialog_open,
+                                &mut self.details_cache,
+                                &self.tool_status,
+                            ),
+                        };
                         if let Some(act) = action {
                             self.handle_action(act);
                         }
@@ -472,16 +1225,81 @@ impl eframe::App for ProtonPrefixManagerApp {
             });
         });
 
-        if let Ok(games) = self.installed_games.lock() {
-            self.backup_manager
-                .show(ctx, &mut self.show_backup_manager, Some(&games));
-        } else {
-            self.backup_manager
-                .show(ctx, &mut self.show_backup_manager, None);
+        let action = match self.installed_games.lock() {
+            Ok(games) => self
+                .backup_manager
+                .show(ctx, &mut self.show_backup_manager, Some(&games)),
+            Err(_) => self.backup_manager.show(ctx, &mut self.show_backup_manager, None),
+        };
+        if let Some(action) = action {
+            self.handle_action(action);
         }
 
         self.runtime_cleaner
             .show(ctx, &mut self.show_runtime_cleaner);
+        if let Ok(games) = self.installed_games.lock() {
+            self.wasted_space
+                .show(ctx, &mut self.show_wasted_space, Some(&games));
+        } else {
+            self.wasted_space
+                .show(ctx, &mut self.show_wasted_space, None);
+        }
+        self.library_health
+            .show(ctx, &mut self.show_library_health);
+        self.libraries.show(ctx, &mut self.show_libraries);
+        let hidden = self.hidden.len();
+        self.hidden_games
+            .show(ctx, &mut self.show_hidden_games, &mut self.hidden);
+        if self.hidden.len() != hidden {
+            self.save_hidden();
+        }
+        self.compat_tools.show(ctx, &mut self.show_compat_tools);
+        self.protontricks.show(ctx, &mut self.show_protontricks);
+        if self.settings.show(ctx, &mut self.show_settings) {
+            // The Steam root or the excluded games may have changed.
+            self.selected_game = None;
+            self.reload_games();
+            self.load_heroic_games();
+            if let Some((from, to)) = self.settings.take_pending_move() {
+                self.start_task("Moving backups...", move || {
+                    let skipped = logging::operation("move_backups", None, &from, || {
+                        crate::utils::backup::move_backups(&from, &to)
+                    })?;
+                    Ok(if skipped.is_empty() {
+                        format!("Backups moved to {}", to.display())
+                    } else {
+                        format!(
+                            "Backups moved to {}; {} already existed there and were left in {}",
+                            to.display(),
+                            skipped.len(),
+                            from.display()
+                        )
+                    })
+                });
+            }
+        }
+        let games = if self.show_archive {
+            self.installed_games
+                .lock()
+                .map(|g| g.clone())
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        if let Some(action) = self.archive.show(ctx, &mut self.show_archive, &games) {
+            self.handle_action(action);
+        }
+        let games = if self.show_profiles {
+            self.installed_games
+                .lock()
+                .map(|g| g.clone())
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        if let Some(action) = self.profiles.show(ctx, &mut self.show_profiles, &games) {
+            self.handle_action(action);
+        }
 
         if let Ok(games) = self.installed_games.lock() {
             if self.show_advanced_search {
@@ -500,6 +1318,18 @@ impl eframe::App for ProtonPrefixManagerApp {
                 if let Ok(res) = rx.try_recv() {
                     self.show_task_dialog = false;
                     self.task_rx = None;
+                    // Tasks may have added or removed backups.
+                    self.backup_manager.refresh();
+                    self.status_message = Some(match &res {
+                        Ok(msg) => msg.lines().next().unwrap_or_default().to_string(),
+                        Err(e) => format!("Task failed: {}", e),
+                    });
+                    self.last_status_update = ctx.input(|i| i.time);
+                    // Details read while the task ran may be out of date.
+                    if let Some(game) = &self.selected_game {
+                        self.details_cache.remove(game.app_id());
+                        disk_size::invalidate(game.prefix_path());
+                    }
                     match res {
                         Ok(msg) => {
                             tfd::message_box_ok("Task", &msg, tfd::MessageBoxIcon::Info);
@@ -524,6 +1354,14 @@ impl eframe::App for ProtonPrefixManagerApp {
                     ui.vertical_centered(|ui| {
                         ui.spinner();
                         ui.label(&self.task_message);
+                        if let Some((copied, total)) = *self.task_progress.lock().unwrap() {
+                            let fraction = if total == 0 { 1.0 } else { copied as f32 / total as f32 };
+                            ui.add(egui::ProgressBar::new(fraction).text(format!(
+                                "{} of {}",
+                                output::format_size(copied),
+                                output::format_size(total)
+                            )));
+                        }
                     });
                 });
         }
@@ -531,9 +1369,11 @@ impl eframe::App for ProtonPrefixManagerApp {
         // Periodically rescan for external tools so disabled buttons can update
         let now = ctx.input(|i| i.time);
         if now - self.last_tool_scan > 5.0 {
-            self.tool_status = scan_tools(&["protontricks", "winecfg"]);
+            self.tool_status = scan_tools(&["protontricks", "winetricks", "winecfg", "mangohud", "gamescope"]);
             self.tool_status
                 .insert("terminal".to_string(), terminal::terminal_available());
+            self.tool_status
+                .insert("vkbasalt".to_string(), vkbasalt::installed());
             self.last_tool_scan = now;
         }
     }
@@ -0,0 +1,128 @@
+use super::details::Action;
+use crate::core::models::GameInfo;
+use crate::utils::app_config::{self, AppConfig};
+use crate::utils::archive;
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::collections::BTreeSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tinyfiledialogs as tfd;
+
+/// Lists prefixes of games that have not been played for a while and
+/// archives the checked ones.
+pub struct ArchiveWindow {
+    config: AppConfig,
+    loaded: bool,
+    unchecked: BTreeSet<u32>,
+}
+
+impl ArchiveWindow {
+    pub fn new() -> Self {
+        Self {
+            config: AppConfig::default(),
+            loaded: false,
+            unchecked: BTreeSet::new(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, games: &[GameInfo]) -> Option<Action> {
+        if !*open {
+            self.loaded = false;
+            return None;
+        }
+
+        if !self.loaded {
+            self.config = app_config::load();
+            self.unchecked.clear();
+            self.loaded = true;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut action = None;
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("archive_inactive"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Archive Inactive Prefixes");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Archived prefixes are compressed into the backup directory and can be restored with Unarchive.",
+                    )
+                    .small(),
+                );
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Not played for");
+                    let months = ui.add(
+                        egui::DragValue::new(&mut self.config.archive.inactive_months).range(1..=120),
+                    );
+                    ui.label("months");
+                    if months.drag_stopped() || months.lost_focus() {
+                        let mut config = app_config::load();
+                        config.archive = self.config.archive.clone();
+                        if let Err(e) = app_config::save(&config) {
+                            log::error!("Failed to save archive settings: {}", e);
+                        }
+                    }
+                });
+
+                let candidates = archive::inactive_games(games, self.config.archive.inactive_months, now);
+                if candidates.is_empty() {
+                    ui.label("No prefixes of inactive games found.");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for game in &candidates {
+                        let mut checked = !self.unchecked.contains(&game.app_id());
+                        let played = chrono::DateTime::<chrono::Local>::from(
+                            UNIX_EPOCH + std::time::Duration::from_secs(game.last_played()),
+                        );
+                        let label = format!("{} (last played {})", game.name(), played.format("%Y-%m-%d"));
+                        if ui.checkbox(&mut checked, label).changed() {
+                            if checked {
+                                self.unchecked.remove(&game.app_id());
+                            } else {
+                                self.unchecked.insert(game.app_id());
+                            }
+                        }
+                    }
+                });
+
+                let targets: Vec<(u32, std::path::PathBuf)> = candidates
+                    .iter()
+                    .filter(|g| !self.unchecked.contains(&g.app_id()))
+                    .map(|g| (g.app_id(), g.prefix_path().to_path_buf()))
+                    .collect();
+                if ui
+                    .add_enabled(
+                        !targets.is_empty(),
+                        egui::Button::new(format!("{} Archive {} Prefix(es)", regular::ARCHIVE, targets.len())),
+                    )
+                    .clicked()
+                    && super::confirm(
+                        "Confirm Archive",
+                        "The selected prefixes will be compressed and the live copies deleted. Continue?",
+                        tfd::MessageBoxIcon::Question,
+                    ) {
+                    action = Some(Action::ArchivePrefixes { targets });
+                    should_close = true;
+                }
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+        action
+    }
+}
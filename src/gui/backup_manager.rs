@@ -1,11 +1,24 @@
+use super::details::Action;
+use super::file_tree::{self, FileTree};
 use crate::core::{models::GameInfo, steam};
 use crate::utils::backup as backup_utils;
+use crate::utils::backup_format;
+use crate::utils::backup_manifest::{self, Verification};
+use crate::utils::app_config::{self, RemoteConfig};
+use crate::utils::hooks;
+use crate::utils::logging;
+use crate::utils::output;
+use crate::utils::remote_backup;
 use eframe::egui;
 use eframe::egui::Modal;
+use egui_phosphor::regular;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 use std::sync::mpsc::{self, Receiver};
 use std::thread;
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tinyfiledialogs as tfd;
 
 pub struct BackupEntry {
@@ -14,59 +27,130 @@ pub struct BackupEntry {
     pub path: PathBuf,
     pub size: u64,
     pub created: String,
+    /// When the backup directory was written.
+    pub modified: SystemTime,
+    /// Sizes compared with the checksum manifest, or after Verify the
+    /// checksums too. `None` for backups made without a manifest.
+    pub integrity: Option<Verification>,
+    /// Whether the checksums were compared, not only the sizes.
+    pub verified: bool,
+    /// Date of an older backup of the same game with identical files.
+    pub duplicate_of: Option<String>,
     pub selected: bool,
 }
 
+impl BackupEntry {
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.game_name.to_lowercase().contains(&filter)
+            || self.app_id.to_string().contains(&filter)
+            || self.created.to_lowercase().contains(&filter)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BackupSortKey {
+    Game,
+    Age,
+    Size,
+}
+
+impl BackupSortKey {
+    fn compare(&self, a: &BackupEntry, b: &BackupEntry) -> Ordering {
+        match self {
+            BackupSortKey::Game => a
+                .game_name
+                .to_lowercase()
+                .cmp(&b.game_name.to_lowercase())
+                .then(a.app_id.cmp(&b.app_id)),
+            // Youngest first when ascending.
+            BackupSortKey::Age => b.modified.cmp(&a.modified),
+            BackupSortKey::Size => a.size.cmp(&b.size),
+        }
+    }
+}
+
+/// How long ago `time` was, e.g. `3 days`.
+fn format_age(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min", secs / 60),
+        3600..=86399 => format!("{} h", secs / 3600),
+        _ => format!("{} days", secs / 86400),
+    }
+}
+
+/// Backup picked for "Restore as…" and the App ID typed for it.
+struct RestoreAs {
+    backup: PathBuf,
+    label: String,
+    app_id: String,
+}
+
+/// Backups read in the background and the size of the whole backup root,
+/// which also holds config snapshots and archived prefixes.
+type Loaded = (Vec<BackupEntry>, u64);
+
 pub struct BackupManagerWindow {
     entries: Vec<BackupEntry>,
+    /// Space used by everything below `backup_root()`.
+    root_size: u64,
     confirm_delete_all: bool,
     needs_refresh: bool,
     loading: bool,
-    rx: Option<Receiver<Vec<BackupEntry>>>,
+    rx: Option<Receiver<Loaded>>,
+    sort_key: BackupSortKey,
+    descending: bool,
+    /// Text the game name, AppID or date must contain.
+    filter: String,
+    /// Show the backups in a section per game with its total size.
+    group_by_game: bool,
+    restore_as: Option<RestoreAs>,
+    /// Remote targets, read with the backups.
+    remote: RemoteConfig,
+    /// Backup being uploaded and the result of the upload.
+    upload: Option<(PathBuf, Receiver<crate::error::Result<String>>)>,
+    /// Backup whose files are being browsed.
+    browse: Option<FileTree>,
+    /// Long-running work for the app to do, such as a restore.
+    action: Option<Action>,
 }
 
 impl BackupManagerWindow {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            root_size: 0,
             confirm_delete_all: false,
             needs_refresh: true,
             loading: false,
             rx: None,
+            sort_key: BackupSortKey::Game,
+            descending: false,
+            filter: String::new(),
+            group_by_game: false,
+            restore_as: None,
+            remote: RemoteConfig::default(),
+            upload: None,
+            browse: None,
+            action: None,
         }
     }
 
-    fn dir_size(path: &Path) -> std::io::Result<u64> {
-        let mut size = 0;
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let md = entry.metadata()?;
-            if md.is_dir() {
-                size += Self::dir_size(&entry.path())?;
-            } else {
-                size += md.len();
-            }
-        }
-        Ok(size)
-    }
-
-    fn format_size(size: u64) -> String {
-        const KB: f64 = 1024.0;
-        const MB: f64 = KB * 1024.0;
-        const GB: f64 = MB * 1024.0;
-        let f = size as f64;
-        if f >= GB {
-            format!("{:.1} GB", f / GB)
-        } else if f >= MB {
-            format!("{:.1} MB", f / MB)
-        } else if f >= KB {
-            format!("{:.1} KB", f / KB)
-        } else {
-            format!("{} B", size)
-        }
+    /// Read the backups again, e.g. after a task added one.
+    pub fn refresh(&mut self) {
+        self.needs_refresh = true;
     }
 
     fn collect_entries(games: Option<Vec<GameInfo>>) -> Vec<BackupEntry> {
+        for (backup, e) in backup_format::migrate_all() {
+            log::warn!("Backup {} not migrated: {}", backup.display(), e);
+        }
         let all = backup_utils::list_all_backups();
         let mut entries = Vec::new();
         for (appid, backups) in all {
@@ -76,36 +160,105 @@ impl BackupManagerWindow {
                 .map(|g| g.name().to_string())
                 .unwrap_or_else(|| format!("App {}", appid));
             for b in backups {
-                let size = Self::dir_size(&b).unwrap_or(0);
+                let size = backup_utils::dir_size(&b).unwrap_or(0);
                 let created = backup_utils::format_backup_name(&b);
+                let modified = fs::metadata(&b)
+                    .and_then(|m| m.modified())
+                    .unwrap_or(UNIX_EPOCH);
+                let integrity = backup_manifest::verify(&b, false).ok().flatten();
                 entries.push(BackupEntry {
                     app_id: appid,
                     game_name: game_name.clone(),
                     path: b,
                     size,
                     created,
+                    modified,
+                    integrity,
+                    verified: false,
+                    duplicate_of: None,
                     selected: false,
                 });
             }
         }
+        Self::find_duplicates(&mut entries);
         entries
     }
 
+    /// Mark backups whose manifest matches an older backup of the same game.
+    fn find_duplicates(entries: &mut [BackupEntry]) {
+        let mut seen: HashMap<(u32, String), String> = HashMap::new();
+        // Backups of a game are listed oldest first.
+        for entry in entries.iter_mut() {
+            let intact = entry.integrity.as_ref().is_some_and(Verification::is_ok);
+            let Some(fingerprint) = backup_manifest::fingerprint(&entry.path).filter(|_| intact) else {
+                continue;
+            };
+            match seen.get(&(entry.app_id, fingerprint.clone())) {
+                Some(original) => entry.duplicate_of = Some(original.clone()),
+                None => {
+                    seen.insert((entry.app_id, fingerprint), entry.created.clone());
+                }
+            }
+        }
+    }
+
     fn start_refresh(&mut self, games: Option<&[GameInfo]>) {
         self.entries.clear();
+        self.remote = app_config::load().remote;
         self.loading = true;
         let rx_slot = {
             let games_owned = games.map(|g| g.to_vec());
             let (tx, rx) = mpsc::channel();
             thread::spawn(move || {
                 let entries = Self::collect_entries(games_owned);
-                let _ = tx.send(entries);
+                let root_size = backup_utils::dir_size(&backup_utils::backup_root()).unwrap_or(0);
+                let _ = tx.send((entries, root_size));
             });
             rx
         };
         self.rx = Some(rx_slot);
     }
 
+    fn start_upload(&mut self, app_id: u32, backup: PathBuf) {
+        if self.upload.is_some() {
+            tfd::message_box_ok(
+                "Upload",
+                "Wait for the current upload to finish",
+                tfd::MessageBoxIcon::Info,
+            );
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let path = backup.clone();
+        thread::spawn(move || {
+            let _ = tx.send(remote_backup::push(&path, app_id));
+        });
+        self.upload = Some((backup, rx));
+    }
+
+    /// Report a finished upload.
+    fn poll_upload(&mut self) {
+        let Some((_, rx)) = &self.upload else {
+            return;
+        };
+        let Ok(result) = rx.try_recv() else {
+            return;
+        };
+        self.upload = None;
+        match result {
+            Ok(remote) => tfd::message_box_ok(
+                "Upload",
+                &format!("Backup copied to {}", remote),
+                tfd::MessageBoxIcon::Info,
+            ),
+            Err(e) => tfd::message_box_ok(
+                "Upload failed",
+                &format!("{}", e),
+                tfd::MessageBoxIcon::Error,
+            ),
+        }
+    }
+
     fn prefix_for(app_id: u32, games: Option<&[GameInfo]>) -> Option<PathBuf> {
         if let Some(g) = games.and_then(|g| g.iter().find(|x| x.app_id() == app_id)) {
             return Some(g.prefix_path().to_path_buf());
@@ -124,43 +277,348 @@ impl BackupManagerWindow {
             .map(|e| e.path.clone())
             .collect();
         for p in paths {
-            let _ = backup_utils::delete_backup(&p);
+            let _ = logging::operation("delete_backup", None, &p, || {
+                backup_utils::delete_backup(&p)
+            });
         }
         self.needs_refresh = true;
     }
 
     fn delete_all(&mut self) {
         for e in &self.entries {
-            let _ = backup_utils::delete_backup(&e.path);
+            let _ = logging::operation("delete_backup", None, &e.path, || {
+                backup_utils::delete_backup(&e.path)
+            });
         }
         self.needs_refresh = true;
     }
 
+    /// Ask for the App ID to restore the picked backup into. The restore
+    /// itself is left to the app, which runs it in the background.
+    fn restore_as_window(&mut self, ctx: &egui::Context, games: Option<&[GameInfo]>) {
+        let Some(state) = &mut self.restore_as else {
+            return;
+        };
+        let mut close = false;
+        let mut restore = None;
+        let response = Modal::new(egui::Id::new("restore_as"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.heading("Restore As");
+                ui.label(format!("Backup: {}", state.label));
+                ui.label("The prefix of the target game is backed up and replaced.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("App ID:");
+                    ui.add(egui::TextEdit::singleline(&mut state.app_id).desired_width(100.0));
+                    if let Some(games) = games {
+                        let selected = state
+                            .app_id
+                            .trim()
+                            .parse::<u32>()
+                            .ok()
+                            .and_then(|id| games.iter().find(|g| g.app_id() == id))
+                            .map(|g| g.name().to_string())
+                            .unwrap_or_else(|| "Pick a game".to_string());
+                        egui::ComboBox::from_id_salt("restore_as_game")
+                            .selected_text(selected)
+                            .width(220.0)
+                            .show_ui(ui, |ui| {
+                                for g in games {
+                                    if ui.selectable_label(false, g.name()).clicked() {
+                                        state.app_id = g.app_id().to_string();
+                                    }
+                                }
+                            });
+                    }
+                });
+                let app_id = state.app_id.trim().parse::<u32>().ok();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(app_id.is_some(), egui::Button::new("Restore")).clicked() {
+                        restore = app_id;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if let Some(app_id) = restore {
+            self.action = Some(Action::RestoreAs {
+                backup: state.backup.clone(),
+                app_id,
+            });
+            close = true;
+        }
+        if close || response.should_close() {
+            self.restore_as = None;
+        }
+    }
+
+    /// Space used by the backup root and by the backups of each game, the
+    /// largest first.
+    fn usage_overview(&self, ui: &mut egui::Ui) {
+        let mut per_game: BTreeMap<u32, (&str, u64, usize)> = BTreeMap::new();
+        for e in &self.entries {
+            let game = per_game.entry(e.app_id).or_insert((&e.game_name, 0, 0));
+            game.1 += e.size;
+            game.2 += 1;
+        }
+        let mut per_game: Vec<_> = per_game.into_iter().collect();
+        per_game.sort_by_key(|(_, (_, size, _))| std::cmp::Reverse(*size));
+        let largest = per_game.first().map(|g| g.1 .1).unwrap_or(0).max(1);
+
+        egui::CollapsingHeader::new(format!(
+            "{} Disk usage: {} in {}",
+            regular::HARD_DRIVES,
+            output::format_size(self.root_size),
+            backup_utils::backup_root().display()
+        ))
+        .id_salt("backup_usage")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("backup_usage_scroll")
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("backup_usage_grid")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            for (app_id, (name, size, count)) in per_game {
+                                ui.label(format!("{} ({})", name, app_id))
+                                    .on_hover_text(format!("{} backup(s)", count));
+                                ui.add(
+                                    egui::ProgressBar::new(size as f32 / largest as f32)
+                                        .desired_width(240.0)
+                                        .text(output::format_size(size)),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+    }
+
+    /// Indices of the entries matching the filter, in the chosen order.
+    fn visible(&self) -> Vec<usize> {
+        let mut visible: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].matches(&self.filter))
+            .collect();
+        visible.sort_by(|&a, &b| {
+            let ord = self.sort_key.compare(&self.entries[a], &self.entries[b]);
+            if self.descending {
+                ord.reverse()
+            } else {
+                ord
+            }
+        });
+        visible
+    }
+
+    /// A column heading that sorts by `key` when clicked, and toggles the
+    /// order when it already does.
+    fn sort_heading(&mut self, ui: &mut egui::Ui, label: &str, key: BackupSortKey) {
+        let text = if self.sort_key != key {
+            label.to_string()
+        } else if self.descending {
+            format!("{} {}", label, regular::ARROW_DOWN)
+        } else {
+            format!("{} {}", label, regular::ARROW_UP)
+        };
+        if ui
+            .selectable_label(self.sort_key == key, egui::RichText::new(text).heading())
+            .on_hover_text("Sort by this column")
+            .clicked()
+        {
+            if self.sort_key == key {
+                self.descending = !self.descending;
+            } else {
+                self.sort_key = key;
+                self.descending = false;
+            }
+        }
+    }
+
+    fn header_row(&mut self, ui: &mut egui::Ui) {
+        self.sort_heading(ui, "Game Name", BackupSortKey::Game);
+        ui.heading("App ID");
+        ui.heading("Backup");
+        self.sort_heading(ui, "Age", BackupSortKey::Age);
+        self.sort_heading(ui, "Size", BackupSortKey::Size);
+        ui.heading("Integrity");
+        ui.heading("Actions");
+        ui.end_row();
+    }
+
+    fn integrity_label(ui: &mut egui::Ui, entry: &BackupEntry) {
+        ui.horizontal(|ui| {
+            match &entry.integrity {
+                None => {
+                    ui.weak("no manifest")
+                        .on_hover_text("Made before backups got a checksum manifest");
+                }
+                Some(v) if v.is_ok() => {
+                    let text = if entry.verified { "verified" } else { "sizes match" };
+                    ui.label(format!("{} {}", regular::CHECK_CIRCLE, text)).on_hover_text(
+                        if entry.verified {
+                            "Every file matches its checksum"
+                        } else {
+                            "File sizes match the manifest; Verify compares the checksums"
+                        },
+                    );
+                }
+                Some(v) => {
+                    let files: Vec<&String> =
+                        v.changed.iter().chain(&v.missing).chain(&v.added).take(10).collect();
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("{} {}", regular::WARNING, v.summary()),
+                    )
+                    .on_hover_text(
+                        files.iter().map(|f| f.as_str()).collect::<Vec<_>>().join("\n"),
+                    );
+                }
+            }
+            if let Some(original) = &entry.duplicate_of {
+                ui.label(regular::COPY)
+                    .on_hover_text(format!("Same files as the backup from {}", original));
+            }
+        });
+    }
+
+    /// One backup with its actions. Returns whether the list needs to be
+    /// read again.
+    fn entry_row(
+        ui: &mut egui::Ui,
+        entry: &mut BackupEntry,
+        games: Option<&[GameInfo]>,
+        restore_as: &mut Option<RestoreAs>,
+        remote: &RemoteConfig,
+        upload: &mut Option<(u32, PathBuf)>,
+        browse: &mut Option<FileTree>,
+    ) -> bool {
+        let mut refresh = false;
+        ui.label(&entry.game_name);
+        ui.label(entry.app_id.to_string());
+        ui.label(&entry.created);
+        ui.label(format_age(entry.modified));
+        ui.label(output::format_size(entry.size));
+        Self::integrity_label(ui, entry);
+        ui.horizontal(|ui| {
+            if ui.button("Restore").clicked() {
+                if let Some(prefix) = Self::prefix_for(entry.app_id, games) {
+                    match hooks::restore(&prefix, &entry.path, || backup_utils::restore_prefix(&entry.path, &prefix)) {
+                        Ok(_) => tfd::message_box_ok("Restore", "Prefix restored", tfd::MessageBoxIcon::Info),
+                        Err(e) => tfd::message_box_ok("Restore failed", &format!("{}", e), tfd::MessageBoxIcon::Error),
+                    };
+                } else {
+                    tfd::message_box_ok("Restore failed", "Prefix path not found", tfd::MessageBoxIcon::Error);
+                }
+            }
+            if ui
+                .button("Browse")
+                .on_hover_text("Look through the files of this backup")
+                .clicked()
+            {
+                *browse = Some(FileTree::new(&entry.path, false));
+            }
+            if ui
+                .add_enabled(entry.integrity.is_some(), egui::Button::new("Verify"))
+                .on_hover_text("Compare every file with its checksum")
+                .clicked()
+            {
+                match backup_manifest::verify(&entry.path, true) {
+                    Ok(integrity) => {
+                        entry.verified = true;
+                        entry.integrity = integrity;
+                    }
+                    Err(e) => tfd::message_box_ok(
+                        "Verify failed",
+                        &format!("{}", e),
+                        tfd::MessageBoxIcon::Error,
+                    ),
+                }
+            }
+            if ui
+                .add_enabled(
+                    !backup_utils::is_saves_only(&entry.path),
+                    egui::Button::new("Restore as…"),
+                )
+                .on_hover_text("Restore into the prefix of another App ID")
+                .on_disabled_hover_text("Backups of saves only belong to their game")
+                .clicked()
+            {
+                *restore_as = Some(RestoreAs {
+                    backup: entry.path.clone(),
+                    label: format!("{} ({}), {}", entry.game_name, entry.app_id, entry.created),
+                    app_id: String::new(),
+                });
+            }
+            let target = remote.target_for(entry.app_id);
+            if ui
+                .add_enabled(target.is_some(), egui::Button::new("Upload"))
+                .on_hover_text(format!("Copy to {}", target.unwrap_or_default()))
+                .on_disabled_hover_text("Set a remote target in the settings")
+                .clicked()
+            {
+                *upload = Some((entry.app_id, entry.path.clone()));
+            }
+            if ui.button("Delete").clicked() {
+                match logging::operation("delete_backup", None, &entry.path, || {
+                    backup_utils::delete_backup(&entry.path)
+                }) {
+                    Ok(_) => tfd::message_box_ok(
+                        "Delete",
+                        "Backup removed",
+                        tfd::MessageBoxIcon::Info,
+                    ),
+                    Err(e) => tfd::message_box_ok(
+                        "Delete failed",
+                        &format!("{}", e),
+                        tfd::MessageBoxIcon::Error,
+                    ),
+                };
+                refresh = true;
+            }
+        });
+        ui.checkbox(&mut entry.selected, "");
+        ui.end_row();
+        refresh
+    }
+
     fn has_selection(&self) -> bool {
         self.entries.iter().any(|e| e.selected)
     }
 
-    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, games: Option<&[GameInfo]>) {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        games: Option<&[GameInfo]>,
+    ) -> Option<Action> {
         if !*open {
             self.entries.clear();
             self.rx = None;
             self.loading = false;
             self.needs_refresh = true;
-            return;
+            return None;
         }
         if self.needs_refresh && !self.loading {
             self.start_refresh(games);
         }
 
         if let Some(rx) = &self.rx {
-            if let Ok(entries) = rx.try_recv() {
+            if let Ok((entries, root_size)) = rx.try_recv() {
                 self.entries = entries;
+                self.root_size = root_size;
                 self.loading = false;
                 self.needs_refresh = false;
                 self.rx = None;
             }
         }
 
+        self.poll_upload();
+
         let mut should_close = false;
         let response = Modal::new(egui::Id::new("backup_manager"))
             .frame(egui::Frame::window(&ctx.style()))
@@ -176,13 +634,11 @@ impl BackupManagerWindow {
                 ui.horizontal(|ui| {
                     let delete_enabled = self.has_selection();
                     if ui.add_enabled(delete_enabled, egui::Button::new("Delete Selected")).clicked() {
-                        if tfd::message_box_yes_no(
+                        if super::confirm(
                             "Confirm",
                             "Delete selected backups?",
                             tfd::MessageBoxIcon::Warning,
-                            tfd::YesNo::No,
-                        ) == tfd::YesNo::Yes
-                        {
+                        ) {
                             self.delete_selected();
                         }
                     }
@@ -190,77 +646,139 @@ impl BackupManagerWindow {
                         self.confirm_delete_all = true;
                     }
                 });
+                if !self.loading && !self.entries.is_empty() {
+                    self.usage_overview(ui);
+                }
+                ui.horizontal(|ui| {
+                    ui.label(regular::MAGNIFYING_GLASS);
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter)
+                            .hint_text("Filter by game, AppID or date"),
+                    );
+                    ui.checkbox(&mut self.group_by_game, "Group by game");
+                    let total: u64 = self
+                        .entries
+                        .iter()
+                        .filter(|e| e.matches(&self.filter))
+                        .map(|e| e.size)
+                        .sum();
+                    ui.label(format!("Total: {}", output::format_size(total)));
+                });
 
+                if let Some((backup, _)) = &self.upload {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!(
+                            "Uploading {}...",
+                            backup_utils::format_backup_name(backup)
+                        ));
+                    });
+                }
                 if self.loading {
                     ui.centered_and_justified(|ui| {
                         ui.spinner();
                         ui.label("Loading backups...");
                     });
                 } else {
-                    egui::Grid::new("backups_grid")
-                        .striped(true)
-                        .show(ui, |ui| {
-                            ui.heading("Game Name");
-                            ui.heading("App ID");
-                            ui.heading("Backup");
-                            ui.heading("Size");
-                            ui.heading("Actions");
-                            ui.end_row();
-
-                            for entry in &mut self.entries {
-                                ui.label(&entry.game_name);
-                                ui.label(entry.app_id.to_string());
-                                ui.label(&entry.created);
-                                ui.label(Self::format_size(entry.size));
-                                ui.horizontal(|ui| {
-                                    if ui.button("Restore").clicked() {
-                                        if let Some(prefix) = Self::prefix_for(entry.app_id, games) {
-                                            match backup_utils::restore_prefix(&entry.path, &prefix) {
-                                                Ok(_) => tfd::message_box_ok("Restore", "Prefix restored", tfd::MessageBoxIcon::Info),
-                                                Err(e) => tfd::message_box_ok("Restore failed", &format!("{}", e), tfd::MessageBoxIcon::Error),
-                                            };
-                                        } else {
-                                            tfd::message_box_ok("Restore failed", "Prefix path not found", tfd::MessageBoxIcon::Error);
-                                        }
-                                    }
-                                    if ui.button("Delete").clicked() {
-                                        match backup_utils::delete_backup(&entry.path) {
-                                            Ok(_) => tfd::message_box_ok(
-                                                "Delete",
-                                                "Backup removed",
-                                                tfd::MessageBoxIcon::Info,
-                                            ),
-                                            Err(e) => tfd::message_box_ok(
-                                                "Delete failed",
-                                                &format!("{}", e),
-                                                tfd::MessageBoxIcon::Error,
-                                            ),
-                                        };
-                                        self.needs_refresh = true;
+                    let visible = self.visible();
+                    let mut refresh = false;
+                    let mut upload = None;
+                    if visible.is_empty() {
+                        ui.label("No backups match the filter");
+                    }
+                    egui::ScrollArea::vertical().max_height(480.0).show(ui, |ui| {
+                        if self.group_by_game {
+                            let mut groups: BTreeMap<(String, u32), Vec<usize>> = BTreeMap::new();
+                            for i in visible {
+                                let e = &self.entries[i];
+                                groups
+                                    .entry((e.game_name.to_lowercase(), e.app_id))
+                                    .or_default()
+                                    .push(i);
+                            }
+                            for ((_, app_id), indices) in groups {
+                                let total: u64 = indices.iter().map(|&i| self.entries[i].size).sum();
+                                let title = format!(
+                                    "{} ({}): {} backup(s), {}",
+                                    self.entries[indices[0]].game_name,
+                                    app_id,
+                                    indices.len(),
+                                    output::format_size(total)
+                                );
+                                egui::CollapsingHeader::new(title)
+                                    .id_salt(("backup_group", app_id))
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        egui::Grid::new(("backups_grid", app_id))
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                self.header_row(ui);
+                                                for i in indices {
+                                                    refresh |= Self::entry_row(
+                                                        ui,
+                                                        &mut self.entries[i],
+                                                        games,
+                                                        &mut self.restore_as,
+                                                        &self.remote,
+                                                        &mut upload,
+                                                        &mut self.browse,
+                                                    );
+                                                }
+                                            });
+                                    });
+                            }
+                        } else {
+                            egui::Grid::new("backups_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    self.header_row(ui);
+                                    for i in visible {
+                                        refresh |= Self::entry_row(
+                                            ui,
+                                            &mut self.entries[i],
+                                            games,
+                                            &mut self.restore_as,
+                                            &self.remote,
+                                            &mut upload,
+                                            &mut self.browse,
+                                        );
                                     }
                                 });
-                                ui.checkbox(&mut entry.selected, "");
-                                ui.end_row();
-                            }
-                        });
+                        }
+                    });
+                    if refresh {
+                        self.needs_refresh = true;
+                    }
+                    if let Some((app_id, backup)) = upload {
+                        self.start_upload(app_id, backup);
+                    }
                 }
 
                 if self.confirm_delete_all {
-                    if tfd::message_box_yes_no(
+                    if super::confirm(
                         "Confirm",
                         "Are you sure you want to delete all backups? This action cannot be undone.",
                         tfd::MessageBoxIcon::Warning,
-                        tfd::YesNo::No,
-                    ) == tfd::YesNo::Yes
-                    {
+                    ) {
                         self.delete_all();
                     }
                     self.confirm_delete_all = false;
                 }
             });
 
+        if self.restore_as.is_some() {
+            self.restore_as_window(ctx, games);
+        }
+
+        if let Some(tree) = &mut self.browse {
+            if !file_tree::browse_window(ctx, "Backup Contents", tree) {
+                self.browse = None;
+            }
+        }
+
         if response.should_close() || should_close {
             *open = false;
         }
+        self.action.take()
     }
 }
@@ -0,0 +1,193 @@
+use crate::utils::{proton_runtime, proton_settings};
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tinyfiledialogs as tfd;
+
+/// Lists installed Proton versions and edits their `user_settings.py`.
+pub struct CompatToolsWindow {
+    protons: Vec<PathBuf>,
+    loaded: bool,
+    selected: Option<PathBuf>,
+    settings: BTreeMap<String, String>,
+    new_key: String,
+    new_value: String,
+}
+
+impl CompatToolsWindow {
+    pub fn new() -> Self {
+        Self {
+            protons: Vec::new(),
+            loaded: false,
+            selected: None,
+            settings: BTreeMap::new(),
+            new_key: String::new(),
+            new_value: String::new(),
+        }
+    }
+
+    fn select(&mut self, dir: PathBuf) {
+        self.settings = proton_settings::load(&dir);
+        self.selected = Some(dir);
+        self.new_key.clear();
+        self.new_value.clear();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !*open {
+            self.loaded = false;
+            return;
+        }
+
+        if !self.loaded {
+            self.protons = proton_runtime::installed_protons();
+            self.loaded = true;
+            if let Some(dir) = self.selected.clone() {
+                self.select(dir);
+            }
+        }
+
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("compat_tools"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Proton Versions");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                if self.protons.is_empty() {
+                    ui.label("No Proton installations found.");
+                    return;
+                }
+
+                let mut clicked = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("compat_tools_list")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for dir in &self.protons {
+                            let name = dir.file_name().unwrap_or_default().to_string_lossy();
+                            let is_selected = self.selected.as_ref() == Some(dir);
+                            if ui
+                                .selectable_label(is_selected, name)
+                                .on_hover_text(dir.display().to_string())
+                                .clicked()
+                            {
+                                clicked = Some(dir.clone());
+                            }
+                        }
+                    });
+                if let Some(dir) = clicked {
+                    self.select(dir);
+                }
+
+                let Some(dir) = self.selected.clone() else {
+                    return;
+                };
+                ui.separator();
+                ui.strong("user_settings.py");
+                ui.label(
+                    egui::RichText::new("Options apply to every game run with this version.")
+                        .small(),
+                );
+                self.settings_editor(ui);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        match proton_settings::save(&dir, &self.settings) {
+                            Ok(_) => tfd::message_box_ok(
+                                "Saved",
+                                &format!("Saved {}", proton_settings::settings_path(&dir).display()),
+                                tfd::MessageBoxIcon::Info,
+                            ),
+                            Err(e) => tfd::message_box_ok(
+                                "Save failed",
+                                &format!("{}", e),
+                                tfd::MessageBoxIcon::Error,
+                            ),
+                        }
+                    }
+                    if ui.button("Revert").clicked() {
+                        self.select(dir.clone());
+                    }
+                });
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+    }
+
+    fn settings_editor(&mut self, ui: &mut egui::Ui) {
+        let mut remove = None;
+        egui::Grid::new("proton_settings_grid")
+            .num_columns(3)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                for (key, value) in self.settings.iter_mut() {
+                    let label = ui.monospace(key);
+                    if let Some(desc) = proton_settings::describe(key) {
+                        label.on_hover_text(desc);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(value).id_salt(("proton_setting", key.as_str())));
+                        if let Err(e) = proton_settings::validate(key, value) {
+                            ui.colored_label(egui::Color32::RED, regular::WARNING)
+                                .on_hover_text(e);
+                        }
+                    });
+                    if ui.small_button(regular::TRASH).on_hover_text("Remove").clicked() {
+                        remove = Some(key.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(key) = remove {
+            self.settings.remove(&key);
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("proton_setting_key")
+                .selected_text(if self.new_key.is_empty() {
+                    "Option"
+                } else {
+                    self.new_key.as_str()
+                })
+                .show_ui(ui, |ui| {
+                    for (name, _, desc) in proton_settings::KNOWN_OPTIONS {
+                        ui.selectable_value(&mut self.new_key, name.to_string(), *name)
+                            .on_hover_text(*desc);
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_key)
+                    .desired_width(160.0)
+                    .hint_text("or custom variable"),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_value)
+                    .desired_width(120.0)
+                    .hint_text("value"),
+            );
+            let key = self.new_key.trim().to_string();
+            let valid = proton_settings::validate(&key, &self.new_value);
+            let add = ui.add_enabled(valid.is_ok(), egui::Button::new(format!("{} Add", regular::PLUS)));
+            if let Err(e) = &valid {
+                if !key.is_empty() {
+                    add.on_disabled_hover_text(e);
+                }
+            } else if add.clicked() {
+                self.settings.insert(key, self.new_value.clone());
+                self.new_key.clear();
+                self.new_value.clear();
+            }
+        });
+    }
+}
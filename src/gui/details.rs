@@ -1,16 +1,37 @@
-use crate::cli::{protontricks, winecfg};
+use super::file_tree::{self, FileTree};
+use crate::cli::{protontricks, tool, winecfg};
 use crate::core::models::GameInfo;
 use crate::core::steam;
-use crate::utils::backup as backup_utils;
+use crate::utils::anticheat::{self, AntiCheat};
+use crate::utils::app_config::{self, GameSettings, Upscaler};
+use crate::utils::archive;
+use crate::utils::backup::{self as backup_utils, ResetStep};
+use crate::utils::cloud_sync::{self, CloudConflict};
+use crate::utils::config_undo;
+use crate::utils::dependencies;
+use crate::utils::history::{self, Entry as HistoryEntry};
+use crate::utils::keep_saves;
+use crate::utils::notes;
+use crate::utils::prefix_info::{collect_prefix_info, PrefixInfo};
+use crate::utils::prefix_relocate;
+use crate::utils::prefix_repair::{self, LinkFix};
+use crate::utils::prefix_software::{self, Program, Runtime};
+use crate::utils::proton_runtime::{self, ContainerRuntime, WineTool};
+use crate::utils::profile::GameProfile;
+use crate::utils::save_locations::{self, SaveLocation};
+use crate::utils::shader_cache::{self, CacheKind};
 use crate::utils::steam_paths;
+use crate::utils::system_info::{self, Gpu};
 use crate::utils::terminal;
-use crate::utils::user_config;
-use crate::utils::{library, manifest as manifest_utils};
+use crate::utils::user_config::{self, SteamInput};
+use crate::utils::vdf_history;
+use crate::utils::windows_version::{self, WindowsInfo};
+use crate::utils::{gamescope, launch_options, library, manifest as manifest_utils, output};
 use eframe::egui;
 use eframe::egui::Modal;
 use egui::menu;
 use egui_phosphor::regular;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::collections::HashMap;
 use std::fs;
 use std::io;
@@ -30,21 +51,104 @@ pub struct GameConfig {
     launch_options: String,
     auto_update: bool,
     cloud_sync: bool,
+    overlay_disabled: bool,
+    steam_input: SteamInput,
 }
 
+#[derive(Clone)]
+pub enum SaveLookup {
+    NotLoaded,
+    Loading,
+    Loaded(Vec<SaveLocation>),
+    Failed(String),
+}
+
+/// Per-game data shown in the details panel, cached between frames.
+#[derive(Default)]
+pub struct DetailsCache {
+    pub configs: HashMap<u32, GameConfig>,
+    pub prefix_info: HashMap<u32, PrefixInfo>,
+    /// Container runtime of each game and its install directory.
+    pub containers: HashMap<u32, Option<(ContainerRuntime, Option<PathBuf>)>>,
+    /// Windows version and architecture of each prefix.
+    pub windows: HashMap<u32, Option<WindowsInfo>>,
+    /// Visual C++, .NET and DirectX runtimes installed in each prefix.
+    pub runtimes: HashMap<u32, Vec<Runtime>>,
+    /// Programs registered in each prefix, read when Installed Software is
+    /// first opened.
+    pub programs: HashMap<u32, Vec<Program>>,
+    /// Anti-cheats each game uses and whether their runtime is installed.
+    pub anticheat: HashMap<u32, Vec<(AntiCheat, bool)>>,
+    /// The user's notes about each game.
+    pub notes: HashMap<u32, String>,
+    /// The user's tags of each game.
+    pub tags: HashMap<u32, BTreeSet<String>>,
+    /// Tag being typed for the selected game.
+    pub tag_draft: String,
+    pub saves: HashMap<u32, SaveLookup>,
+    pub cloud_conflicts: HashMap<u32, Vec<CloudConflict>>,
+    pub game_settings: HashMap<u32, GameSettings>,
+    /// Key and value of the environment variable being added.
+    pub env_draft: (String, String),
+    pub shader_cache: HashMap<u32, BTreeMap<CacheKind, u64>>,
+    /// Kinds of shader cache ticked for clearing.
+    pub shader_cache_clear: BTreeSet<CacheKind>,
+    /// Operations run on each game, read when History is first opened.
+    pub history: HashMap<u32, Vec<HistoryEntry>>,
+    /// Vulkan devices of this machine, detected when Troubleshooting is
+    /// first opened.
+    pub gpus: Option<Option<Vec<Gpu>>>,
+}
+
+impl DetailsCache {
+    /// Drop everything cached for a single game.
+    pub fn remove(&mut self, app_id: u32) {
+        self.configs.remove(&app_id);
+        self.prefix_info.remove(&app_id);
+        self.containers.remove(&app_id);
+        self.windows.remove(&app_id);
+        self.runtimes.remove(&app_id);
+        self.programs.remove(&app_id);
+        self.anticheat.remove(&app_id);
+        self.notes.remove(&app_id);
+        self.tags.remove(&app_id);
+        self.tag_draft.clear();
+        self.saves.remove(&app_id);
+        self.cloud_conflicts.remove(&app_id);
+        self.game_settings.remove(&app_id);
+        self.shader_cache.remove(&app_id);
+        self.history.remove(&app_id);
+    }
+}
+
+/// State of the reset dialog.
 #[derive(Clone, Default)]
-pub struct PrefixInfo {
-    pub version: Option<String>,
-    pub has_dxvk: bool,
-    pub has_vkd3d: bool,
+struct ResetOptions {
+    partial: bool,
+    keep_saves: bool,
+    steps: BTreeSet<ResetStep>,
 }
 
 #[derive(Debug)]
 pub enum Action {
-    Backup { app_id: u32, prefix: PathBuf },
-    Restore { backup: PathBuf, prefix: PathBuf },
+    Backup { app_id: u32, prefix: PathBuf, saves_only: bool, shader_cache: bool },
+    BackupInto { prefix: PathBuf, root: PathBuf },
+    Restore { backup: PathBuf, prefix: PathBuf, delta: bool },
+    RestoreAs { backup: PathBuf, app_id: u32 },
+    RestorePaths { backup: PathBuf, prefix: PathBuf, paths: Vec<String> },
     DeleteBackup { backup: PathBuf },
-    Reset { prefix: PathBuf },
+    Reset { app_id: u32, prefix: PathBuf, keep_saves: bool, steps: Vec<ResetStep> },
+    ImportPrefix { app_id: u32, source: PathBuf },
+    RelocatePrefix { app_id: u32, prefix: PathBuf, target: PathBuf },
+    RepairPrefix { app_id: u32, prefix: PathBuf },
+    FixBrokenLinks { prefix: PathBuf, fixes: Vec<LinkFix> },
+    BringBackPrefix { app_id: u32, prefix: PathBuf },
+    ArchivePrefixes { targets: Vec<(u32, PathBuf)> },
+    UnarchivePrefix { app_id: u32, prefix: PathBuf },
+    LookupSaves { app_id: u32, prefix: PathBuf, refresh: bool },
+    ApplyProfile { name: String, profile: GameProfile, app_ids: Vec<u32> },
+    OpenProtontricks { app_id: u32, name: String },
+    SetWindowsVersion { app_id: u32, version: String },
 }
 
 impl<'a> GameDetails<'a> {
@@ -123,13 +227,35 @@ impl<'a> GameDetails<'a> {
         let mut action = None;
         menu::menu_button(ui, &format!("{} Prefix Tools ▾", regular::WRENCH), |ui| {
             ui.menu_button("Prefix ▾", |ui| {
+                let saves_only_id = egui::Id::new("backup_saves_only");
+                let mut saves_only =
+                    ui.data_mut(|d| d.get_temp::<bool>(saves_only_id).unwrap_or(false));
+                let shader_cache_id = egui::Id::new("backup_shader_cache");
+                let mut shader_cache =
+                    ui.data_mut(|d| d.get_temp::<bool>(shader_cache_id).unwrap_or(false));
                 if ui.button("Backup").clicked() {
                     action = Some(Action::Backup {
                         app_id: game.app_id(),
                         prefix: game.prefix_path().to_path_buf(),
+                        saves_only,
+                        shader_cache: shader_cache && !saves_only,
                     });
                     ui.close_menu();
                 }
+                if ui
+                    .checkbox(&mut saves_only, "Saves only")
+                    .on_hover_text("Only back up save and config directories detected via PCGamingWiki")
+                    .changed()
+                {
+                    ui.data_mut(|d| d.insert_temp(saves_only_id, saves_only));
+                }
+                if ui
+                    .add_enabled(!saves_only, egui::Checkbox::new(&mut shader_cache, "Include shader cache"))
+                    .on_hover_text("Also back up steamapps/shadercache for this game")
+                    .changed()
+                {
+                    ui.data_mut(|d| d.insert_temp(shader_cache_id, shader_cache));
+                }
                 if ui.button("Restore").clicked() {
                     *restore_dialog_open = true;
                     ui.close_menu();
@@ -138,27 +264,131 @@ impl<'a> GameDetails<'a> {
                     *delete_dialog_open = true;
                     ui.close_menu();
                 }
-                if ui.button("Reset").clicked() {
-                    if tfd::message_box_yes_no(
-                        "Confirm Reset",
-                        "Resetting will delete the prefix. It's prudent to create a backup of your important data or configuration files before performing any critical actions. This ensures you can restore your system to a known good state if something unexpected happens. Continue?",
-                        tfd::MessageBoxIcon::Warning,
-                        tfd::YesNo::No,
-                    ) == tfd::YesNo::Yes
+                if ui.button("Import Prefix...").clicked() {
+                    ui.close_menu();
+                    if let Some(dir) =
+                        tfd::select_folder_dialog("Select Wine prefix to import", "")
                     {
-                        action = Some(Action::Reset { prefix: game.prefix_path().to_path_buf() });
+                        if super::confirm(
+                            "Confirm Import",
+                            "Importing replaces the current prefix. The current prefix is backed up first. Continue?",
+                            tfd::MessageBoxIcon::Question,
+                        ) {
+                            action = Some(Action::ImportPrefix {
+                                app_id: game.app_id(),
+                                source: PathBuf::from(dir),
+                            });
+                        }
                     }
+                }
+                if let Some(target) = prefix_relocate::relocated_target(game.prefix_path()) {
+                    if ui
+                        .button("Bring Back")
+                        .on_hover_text(format!("Move the prefix back from {}", target.display()))
+                        .clicked()
+                    {
+                        action = Some(Action::BringBackPrefix {
+                            app_id: game.app_id(),
+                            prefix: game.prefix_path().to_path_buf(),
+                        });
+                        ui.close_menu();
+                    }
+                } else if ui
+                    .button("Move to Another Drive...")
+                    .on_hover_text("Move the prefix and leave a symlink in compatdata")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    if let Some(dir) =
+                        tfd::select_folder_dialog("Select directory to move the prefix into", "")
+                    {
+                        action = Some(Action::RelocatePrefix {
+                            app_id: game.app_id(),
+                            prefix: game.prefix_path().to_path_buf(),
+                            target: PathBuf::from(dir),
+                        });
+                    }
+                }
+                if archive::is_archived(game.app_id()) {
+                    if ui.button("Unarchive").clicked() {
+                        action = Some(Action::UnarchivePrefix {
+                            app_id: game.app_id(),
+                            prefix: game.prefix_path().to_path_buf(),
+                        });
+                        ui.close_menu();
+                    }
+                } else if ui
+                    .add_enabled(game.prefix_path().is_dir(), egui::Button::new("Archive"))
+                    .on_hover_text("Compress the prefix into the backup directory and delete the live copy")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    if super::confirm(
+                        "Confirm Archive",
+                        "The prefix will be compressed and the live copy deleted. Continue?",
+                        tfd::MessageBoxIcon::Question,
+                    ) {
+                        action = Some(Action::ArchivePrefixes {
+                            targets: vec![(game.app_id(), game.prefix_path().to_path_buf())],
+                        });
+                    }
+                }
+                if ui.button("Reset…").clicked() {
+                    ui.data_mut(|d| d.insert_temp(egui::Id::new("reset_options"), ResetOptions::default()));
                     ui.close_menu();
                 }
             });
 
             ui.menu_button("Troubleshooting ▾", |ui| {
+                let container_warning = proton_runtime::container_warning(game.app_id());
+                if let Some(warning) = &container_warning {
+                    ui.colored_label(egui::Color32::YELLOW, format!("{} Container runtime", regular::WARNING))
+                        .on_hover_text(warning);
+                    ui.separator();
+                }
                 if ui
-                    .add_enabled(
-                        *tools.get("winecfg").unwrap_or(&false),
-                        egui::Button::new("Launch winecfg"),
-                    )
+                    .add_enabled(game.prefix_path().is_dir(), egui::Button::new("Repair Prefix"))
+                    .on_hover_text("Recreate missing or dangling drive links in dosdevices and reinstate missing DXVK/vkd3d-proton DLLs from the game's Proton")
+                    .clicked()
+                {
+                    action = Some(Action::RepairPrefix {
+                        app_id: game.app_id(),
+                        prefix: game.prefix_path().to_path_buf(),
+                    });
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(game.prefix_path().is_dir(), egui::Button::new("Fix Broken Links…"))
+                    .on_hover_text("Preview and fix symlinks whose target is gone, e.g. links into a removed Proton version")
                     .clicked()
+                {
+                    let dist = proton_runtime::proton_for(game.app_id())
+                        .and_then(|proton| proton_runtime::wine_dir(&proton));
+                    let fixes = prefix_repair::plan_broken_links(&game.prefix_path().join("pfx"), dist.as_deref());
+                    ui.data_mut(|d| d.insert_temp(egui::Id::new("broken_links"), fixes));
+                    ui.close_menu();
+                }
+                let proton_wine = proton_runtime::proton_for(game.app_id())
+                    .and_then(|proton| proton_runtime::wine_dir(&proton))
+                    .is_some();
+                let winecfg_button = ui.add_enabled(
+                    proton_wine || *tools.get("winecfg").unwrap_or(&false),
+                    egui::Button::new("Launch winecfg"),
+                );
+                let winecfg_button =
+                    winecfg_button.on_disabled_hover_text(dependencies::missing_message("winecfg"));
+                let winecfg_button = if proton_wine {
+                    winecfg_button
+                } else {
+                    winecfg_button.on_hover_text(
+                        "Proton's Wine was not found; the system winecfg may not match the prefix's Wine version",
+                    )
+                };
+                let winecfg_button = match &container_warning {
+                    Some(warning) => winecfg_button.on_hover_text(warning),
+                    None => winecfg_button,
+                };
+                if winecfg_button.clicked()
                 {
                     let appid = game.app_id();
                     *status_message = Some("Launching winecfg...".to_string());
@@ -168,21 +398,61 @@ impl<'a> GameDetails<'a> {
                     });
                     ui.close_menu();
                 }
-                if ui
-                    .add_enabled(
-                        *tools.get("protontricks").unwrap_or(&false),
-                        egui::Button::new("Launch protontricks"),
-                    )
-                    .clicked()
+                ui.add_enabled_ui(proton_wine, |ui| {
+                    ui.menu_button("Wine Tools ▾", |ui| {
+                        for wine_tool in WineTool::ALL {
+                            if ui.button(wine_tool.label()).clicked() {
+                                let appid = game.app_id();
+                                *status_message = Some(format!("Launching {}...", wine_tool.program()));
+                                *status_time = ui.input(|i| i.time);
+                                thread::spawn(move || {
+                                    tool::execute(appid, wine_tool);
+                                });
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                })
+                .response
+                .on_disabled_hover_text("No Wine build found in the game's Proton installation");
+                let has_protontricks = *tools.get("protontricks").unwrap_or(&false);
+                let has_winetricks = has_protontricks || *tools.get("winetricks").unwrap_or(&false);
+                let tricks = if has_protontricks { "protontricks" } else { "winetricks" };
+                let protontricks_button = ui.add_enabled(
+                    has_winetricks,
+                    egui::Button::new(format!("Launch {}", tricks)),
+                );
+                let protontricks_button = protontricks_button
+                    .on_disabled_hover_text(dependencies::missing_message("protontricks"));
+                let protontricks_button = match &container_warning {
+                    Some(warning) => protontricks_button.on_hover_text(warning),
+                    None => protontricks_button,
+                };
+                if protontricks_button.clicked()
                 {
                     let appid = game.app_id();
-                    *status_message = Some("Launching protontricks...".to_string());
+                    *status_message = Some(format!("Launching {}...", tricks));
                     *status_time = ui.input(|i| i.time);
                     thread::spawn(move || {
                         protontricks::execute(appid, &[]);
                     });
                     ui.close_menu();
                 }
+                if ui
+                    .add_enabled(
+                        has_winetricks,
+                        egui::Button::new("Winetricks Verbs..."),
+                    )
+                    .on_hover_text("List the installed verbs and install new ones")
+                    .on_disabled_hover_text(dependencies::missing_message("protontricks"))
+                    .clicked()
+                {
+                    action = Some(Action::OpenProtontricks {
+                        app_id: game.app_id(),
+                        name: game.name().to_string(),
+                    });
+                    ui.close_menu();
+                }
                 if ui.button("Clear Shader Cache").clicked() {
                     if let Ok(libs) = steam::get_steam_libraries() {
                         match backup_utils::clear_shader_cache(game.app_id(), &libs) {
@@ -204,6 +474,128 @@ impl<'a> GameDetails<'a> {
         })
         .response
         .on_hover_text("Tools for managing this game's Proton prefix");
+        if let Some(act) = self.reset_window(ui.ctx(), game) {
+            action = Some(act);
+        }
+        if let Some(act) = self.broken_links_window(ui.ctx(), game) {
+            action = Some(act);
+        }
+        action
+    }
+
+    /// Show what fixing the broken symlinks would change before doing it.
+    fn broken_links_window(&self, ctx: &egui::Context, game: &GameInfo) -> Option<Action> {
+        let id = egui::Id::new("broken_links");
+        let fixes = ctx.data_mut(|d| d.get_temp::<Vec<LinkFix>>(id))?;
+        let mut action = None;
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("broken_links_modal"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.heading("Broken Symlinks");
+                ui.separator();
+                if fixes.is_empty() {
+                    ui.label("No broken symlinks found.");
+                } else {
+                    ui.label(format!("{} link(s) will be changed:", fixes.len()));
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for fix in &fixes {
+                            ui.label(egui::RichText::new(fix.to_string()).small());
+                        }
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!fixes.is_empty(), egui::Button::new("Apply")).clicked() {
+                        action = Some(Action::FixBrokenLinks {
+                            prefix: game.prefix_path().to_path_buf(),
+                            fixes: fixes.clone(),
+                        });
+                        should_close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        should_close = true;
+                    }
+                });
+            });
+        if response.should_close() || should_close {
+            ctx.data_mut(|d| d.remove::<Vec<LinkFix>>(id));
+        }
+        action
+    }
+
+    /// Ask what to reset: the whole prefix, optionally keeping the saves,
+    /// or only some parts of it.
+    fn reset_window(&self, ctx: &egui::Context, game: &GameInfo) -> Option<Action> {
+        let id = egui::Id::new("reset_options");
+        let mut options = ctx.data_mut(|d| d.get_temp::<ResetOptions>(id))?;
+        let mut action = None;
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("reset_modal"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.heading("Reset Prefix");
+                ui.separator();
+                ui.radio_value(&mut options.partial, false, "Delete the whole prefix");
+                ui.add_enabled_ui(!options.partial, |ui| {
+                    ui.indent("reset_whole", |ui| {
+                        ui.checkbox(&mut options.keep_saves, "Keep saves").on_hover_text(
+                            "Back up the save directories detected via PCGamingWiki and put them back once Proton has rebuilt the prefix",
+                        );
+                    });
+                });
+                ui.radio_value(&mut options.partial, true, "Only delete");
+                ui.add_enabled_ui(options.partial, |ui| {
+                    ui.indent("reset_steps", |ui| {
+                        for step in ResetStep::ALL {
+                            let mut on = options.steps.contains(&step);
+                            let hint = match step {
+                                ResetStep::UserData => "Documents, AppData and other files in drive_c/users",
+                                ResetStep::Registry => "system.reg, user.reg and userdef.reg; Proton restores its defaults",
+                                ResetStep::Software => "Programs installed into Program Files and ProgramData",
+                            };
+                            if ui.checkbox(&mut on, step.label()).on_hover_text(hint).changed() {
+                                if on {
+                                    options.steps.insert(step);
+                                } else {
+                                    options.steps.remove(&step);
+                                }
+                            }
+                        }
+                    });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ready = !options.partial || !options.steps.is_empty();
+                    if ui.add_enabled(ready, egui::Button::new("Reset")).clicked()
+                        && super::confirm(
+                            "Confirm Reset",
+                            "Resetting will delete the selected data of the prefix. It's prudent to create a backup of your important data or configuration files before performing any critical actions. This ensures you can restore your system to a known good state if something unexpected happens. Continue?",
+                            tfd::MessageBoxIcon::Warning,
+                        )
+                    {
+                        action = Some(Action::Reset {
+                            app_id: game.app_id(),
+                            prefix: game.prefix_path().to_path_buf(),
+                            keep_saves: options.keep_saves && !options.partial,
+                            steps: if options.partial {
+                                options.steps.iter().copied().collect()
+                            } else {
+                                Vec::new()
+                            },
+                        });
+                        should_close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        should_close = true;
+                    }
+                });
+            });
+        if response.should_close() || should_close {
+            ctx.data_mut(|d| d.remove::<ResetOptions>(id));
+        } else {
+            ctx.data_mut(|d| d.insert_temp(id, options));
+        }
         action
     }
 
@@ -230,7 +622,8 @@ impl<'a> GameDetails<'a> {
                 let contents = library::read_manifest_cached(&manifest).ok_or_else(|| {
                     io::Error::new(io::ErrorKind::Other, "failed to read manifest")
                 })?;
-                let proton = manifest_utils::get_value(&contents, "CompatToolOverride");
+                let proton = user_config::get_compat_tool(app_id)
+                    .or_else(|| manifest_utils::get_value(&contents, "CompatToolOverride"));
                 let launch = user_config::get_launch_options(app_id)
                     .or_else(|| manifest_utils::get_value(&contents, "LaunchOptions"))
                     .unwrap_or_default();
@@ -245,6 +638,8 @@ impl<'a> GameDetails<'a> {
                     launch_options: launch,
                     cloud_sync: cloud,
                     auto_update: auto,
+                    overlay_disabled: user_config::get_overlay_enabled(app_id) == Some(false),
+                    steam_input: user_config::get_steam_input(app_id),
                 });
             }
         }
@@ -254,6 +649,298 @@ impl<'a> GameDetails<'a> {
         ))
     }
 
+    /// Sizes of the shader cache parts of a game with checkboxes to clear
+    /// them individually.
+    fn shader_cache_section(ui: &mut egui::Ui, app_id: u32, cache: &mut DetailsCache) {
+        let libs = steam::get_steam_libraries().unwrap_or_default();
+        let sizes = cache
+            .shader_cache
+            .entry(app_id)
+            .or_insert_with(|| shader_cache::sizes(app_id, &libs));
+        if sizes.is_empty() {
+            ui.label("No shader cache found for this game.");
+            return;
+        }
+        egui::Grid::new("shader_cache_grid")
+            .num_columns(2)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                for (kind, size) in sizes.iter() {
+                    let mut selected = cache.shader_cache_clear.contains(kind);
+                    let mut checkbox = ui.checkbox(&mut selected, kind.label());
+                    if *kind == CacheKind::Driver {
+                        checkbox = checkbox.on_hover_text(
+                            "Mesa and NVIDIA caches in ~/.cache hold the shaders of every game; all of them rebuild theirs after clearing",
+                        );
+                    }
+                    if checkbox.changed() {
+                        if selected {
+                            cache.shader_cache_clear.insert(*kind);
+                        } else {
+                            cache.shader_cache_clear.remove(kind);
+                        }
+                    }
+                    ui.monospace(output::format_size(*size));
+                    ui.end_row();
+                }
+            });
+
+        let kinds: Vec<CacheKind> = cache
+            .shader_cache_clear
+            .iter()
+            .filter(|k| sizes.contains_key(k))
+            .copied()
+            .collect();
+        let mut refresh = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !kinds.is_empty(),
+                    egui::Button::new(format!("{} Clear Selected", regular::TRASH)),
+                )
+                .clicked()
+            {
+                match shader_cache::clear(app_id, &libs, &kinds) {
+                    Ok(freed) => tfd::message_box_ok(
+                        "Shader Cache",
+                        &format!("Freed {}", output::format_size(freed)),
+                        tfd::MessageBoxIcon::Info,
+                    ),
+                    Err(e) => tfd::message_box_ok(
+                        "Shader Cache failed",
+                        &format!("{}", e),
+                        tfd::MessageBoxIcon::Error,
+                    ),
+                }
+                refresh = true;
+            }
+            if ui.button(format!("{} Refresh", regular::ARROW_CLOCKWISE)).clicked() {
+                refresh = true;
+            }
+        });
+        if refresh {
+            cache.shader_cache.remove(&app_id);
+        }
+    }
+
+    /// Edit the environment variables stored for a game.
+    fn env_editor(ui: &mut egui::Ui, settings: &mut GameSettings, draft: &mut (String, String)) {
+        let mut remove = None;
+        egui::Grid::new("env_grid")
+            .num_columns(3)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                for (key, value) in settings.env.iter_mut() {
+                    ui.monospace(key);
+                    ui.add(egui::TextEdit::singleline(value).id_salt(("env_value", key.as_str())));
+                    if ui.small_button(regular::TRASH).on_hover_text("Remove").clicked() {
+                        remove = Some(key.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(key) = remove {
+            settings.env.remove(&key);
+        }
+
+        ui.horizontal(|ui| {
+            let (key, value) = draft;
+            ui.add(
+                egui::TextEdit::singleline(key)
+                    .id_salt("env_new_key")
+                    .desired_width(140.0)
+                    .hint_text("NAME"),
+            );
+            ui.label("=");
+            ui.add(
+                egui::TextEdit::singleline(value)
+                    .id_salt("env_new_value")
+                    .desired_width(140.0)
+                    .hint_text("value"),
+            );
+            let name = key.trim().to_string();
+            if ui
+                .add_enabled(
+                    launch_options::valid_env_key(&name),
+                    egui::Button::new(format!("{} Add", regular::PLUS)),
+                )
+                .clicked()
+            {
+                settings.env.insert(name, std::mem::take(value));
+                key.clear();
+            }
+        });
+    }
+
+    /// Edit the MangoHud overlay settings of a game.
+    fn mangohud_editor(ui: &mut egui::Ui, settings: &mut GameSettings, installed: bool) {
+        let mut enabled = settings.mangohud.is_some();
+        ui.add_enabled_ui(installed || enabled, |ui| {
+            ui.checkbox(&mut enabled, "Show MangoHud overlay")
+                .on_disabled_hover_text(dependencies::missing_message("mangohud"));
+        });
+        if enabled != settings.mangohud.is_some() {
+            settings.mangohud = enabled.then(Default::default);
+        }
+        if enabled && !installed {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("{} {}", regular::WARNING, dependencies::missing_message("mangohud")),
+            );
+        }
+        let Some(hud) = &mut settings.mangohud else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("FPS limit:");
+            ui.add(egui::DragValue::new(&mut hud.fps_limit).range(0..=1000));
+            if hud.fps_limit == 0 {
+                ui.label("(unlimited)");
+            }
+        });
+        egui::Grid::new("mangohud_metrics")
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.checkbox(&mut hud.fps, "FPS");
+                ui.checkbox(&mut hud.frametime, "Frame time");
+                ui.checkbox(&mut hud.frame_timing, "Frame time graph");
+                ui.end_row();
+                ui.checkbox(&mut hud.cpu_stats, "CPU load");
+                ui.checkbox(&mut hud.cpu_temp, "CPU temperature");
+                ui.checkbox(&mut hud.ram, "RAM");
+                ui.end_row();
+                ui.checkbox(&mut hud.gpu_stats, "GPU load");
+                ui.checkbox(&mut hud.gpu_temp, "GPU temperature");
+                ui.checkbox(&mut hud.vram, "VRAM");
+                ui.end_row();
+            });
+    }
+
+    /// Edit the vkBasalt effects of a game.
+    fn vkbasalt_editor(ui: &mut egui::Ui, settings: &mut GameSettings, installed: bool) {
+        let mut enabled = settings.vkbasalt.is_some();
+        ui.add_enabled_ui(installed || enabled, |ui| {
+            ui.checkbox(&mut enabled, "Enable vkBasalt")
+                .on_disabled_hover_text(format!(
+                    "vkBasalt Vulkan layer not found. {}",
+                    dependencies::install_hint("vkbasalt")
+                ));
+        });
+        if enabled != settings.vkbasalt.is_some() {
+            settings.vkbasalt = enabled.then(Default::default);
+        }
+        if enabled && !installed {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "{} vkBasalt Vulkan layer not found. {}",
+                    regular::WARNING,
+                    dependencies::install_hint("vkbasalt")
+                ),
+            );
+        }
+        let Some(basalt) = &mut settings.vkbasalt else {
+            return;
+        };
+        egui::Grid::new("vkbasalt_grid")
+            .num_columns(2)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                ui.checkbox(&mut basalt.cas, "CAS sharpening");
+                ui.add_enabled(
+                    basalt.cas,
+                    egui::Slider::new(&mut basalt.cas_sharpness, 0.0..=1.0),
+                );
+                ui.end_row();
+                ui.checkbox(&mut basalt.dls, "DLS sharpening");
+                ui.add_enabled(
+                    basalt.dls,
+                    egui::Slider::new(&mut basalt.dls_sharpness, 0.0..=1.0),
+                );
+                ui.end_row();
+                ui.checkbox(&mut basalt.fxaa, "FXAA");
+                ui.checkbox(&mut basalt.smaa, "SMAA");
+                ui.end_row();
+                ui.label("Toggle key:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut basalt.toggle_key)
+                        .id_salt("vkbasalt_toggle_key")
+                        .desired_width(80.0),
+                );
+                ui.end_row();
+            });
+    }
+
+    /// Compose the gamescope options of a game.
+    fn gamescope_editor(ui: &mut egui::Ui, settings: &mut GameSettings, installed: bool) {
+        let mut enabled = settings.gamescope.is_some();
+        ui.add_enabled_ui(installed || enabled, |ui| {
+            ui.checkbox(&mut enabled, "Run in gamescope")
+                .on_disabled_hover_text(dependencies::missing_message("gamescope"));
+        });
+        if enabled != settings.gamescope.is_some() {
+            settings.gamescope = enabled.then(Default::default);
+        }
+        if enabled && !installed {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("{} {}", regular::WARNING, dependencies::missing_message("gamescope")),
+            );
+        }
+        let Some(scope) = &mut settings.gamescope else {
+            return;
+        };
+        egui::Grid::new("gamescope_grid")
+            .num_columns(2)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Game resolution:");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut scope.width).range(0..=7680));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(&mut scope.height).range(0..=4320));
+                });
+                ui.end_row();
+                ui.label("Output resolution:");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut scope.output_width).range(0..=7680));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(&mut scope.output_height).range(0..=4320));
+                });
+                ui.end_row();
+                ui.label("Upscaling:");
+                egui::ComboBox::from_id_salt("gamescope_upscaler")
+                    .selected_text(scope.upscaler.label())
+                    .show_ui(ui, |ui| {
+                        for upscaler in Upscaler::ALL {
+                            ui.selectable_value(&mut scope.upscaler, upscaler, upscaler.label());
+                        }
+                    });
+                ui.end_row();
+                ui.label("FPS limit:");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut scope.fps_limit).range(0..=1000));
+                    if scope.fps_limit == 0 {
+                        ui.label("(unlimited)");
+                    }
+                });
+                ui.end_row();
+            });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut scope.fullscreen, "Fullscreen");
+            ui.checkbox(&mut scope.hdr, "HDR");
+        });
+        ui.label(egui::RichText::new("0 leaves a size to gamescope.").small());
+        match gamescope::validate(scope) {
+            Ok(()) => {
+                ui.monospace(gamescope::wrapper(scope).join(" "));
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("{} {}", regular::WARNING, e));
+            }
+        }
+    }
+
     fn save_game_config(app_id: u32, cfg: &GameConfig) -> io::Result<()> {
         let libraries = steam::get_steam_libraries()
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
@@ -273,17 +960,38 @@ impl<'a> GameDetails<'a> {
                 user_config::set_launch_options(app_id, &cfg.launch_options)?;
                 if let Some(p) = &cfg.proton {
                     contents = manifest_utils::update_or_insert(&contents, "CompatToolOverride", p);
-                    user_config::set_compat_tool(app_id, p)?;
+                    user_config::set_compat_tool_mapping(app_id, p)?;
                 } else {
+                    user_config::clear_compat_tool_mapping(app_id)?;
                     let _ = user_config::clear_compat_tool(app_id);
                 }
+                // Games that follow the global overlay setting are left alone.
+                let overlay = user_config::get_overlay_enabled(app_id).unwrap_or(true);
+                if overlay == cfg.overlay_disabled {
+                    user_config::set_overlay_enabled(app_id, !cfg.overlay_disabled)?;
+                }
+                if user_config::get_steam_input(app_id) != cfg.steam_input {
+                    user_config::set_steam_input(app_id, cfg.steam_input)?;
+                }
                 let cloud_val = if cfg.cloud_sync { "1" } else { "0" };
                 contents =
                     manifest_utils::update_or_insert(&contents, "AllowCloudSaves", cloud_val);
                 let auto_val = if cfg.auto_update { "0" } else { "1" };
                 contents =
                     manifest_utils::update_or_insert(&contents, "AutoUpdateBehavior", auto_val);
-                fs::write(&manifest, contents.as_bytes())?;
+                let result = vdf_history::write(&manifest, contents.as_bytes());
+                let details = format!(
+                    "launch options: {}, Proton: {}, Steam Cloud: {}, auto update: {}, Steam overlay: {}, \
+                     Steam Input: {}",
+                    cfg.launch_options,
+                    cfg.proton.as_deref().unwrap_or("default"),
+                    if cfg.cloud_sync { "on" } else { "off" },
+                    if cfg.auto_update { "on" } else { "off" },
+                    if cfg.overlay_disabled { "off" } else { "on" },
+                    cfg.steam_input.label().to_lowercase(),
+                );
+                history::record_result(app_id, "config", details, &result);
+                result?;
                 library::update_manifest_cache(&manifest, &contents);
                 return Ok(());
             }
@@ -294,7 +1002,213 @@ impl<'a> GameDetails<'a> {
         ))
     }
 
-    fn list_proton_versions() -> Vec<String> {
+    /// The game's tags, each removable, and a field to add another.
+    fn tags_row(ui: &mut egui::Ui, app_id: u32, cache: &mut DetailsCache) {
+        let tags = cache
+            .tags
+            .entry(app_id)
+            .or_insert_with(|| app_config::load().tags_of(app_id));
+        let mut removed = None;
+        let mut added = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.strong("Tags:");
+            for tag in tags.iter() {
+                if ui
+                    .small_button(format!("{} {}", tag, regular::X))
+                    .on_hover_text("Remove tag")
+                    .clicked()
+                {
+                    removed = Some(tag.clone());
+                }
+            }
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut cache.tag_draft)
+                    .hint_text("Add tag")
+                    .desired_width(100.0),
+            );
+            let submitted =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            egui::ComboBox::from_id_salt("existing_tags")
+                .selected_text("")
+                .width(24.0)
+                .show_ui(ui, |ui| {
+                    // Read only while the list is open.
+                    let existing: Vec<String> = app_config::load()
+                        .all_tags()
+                        .into_iter()
+                        .filter(|t| !tags.contains(t))
+                        .collect();
+                    if existing.is_empty() {
+                        ui.label("No other tags yet");
+                    }
+                    for tag in existing {
+                        if ui.selectable_label(false, &tag).clicked() {
+                            added = Some(tag);
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Add a tag used by other games");
+            if submitted && !cache.tag_draft.trim().is_empty() {
+                added = Some(cache.tag_draft.trim().to_string());
+                cache.tag_draft.clear();
+            }
+        });
+        if removed.is_none() && added.is_none() {
+            return;
+        }
+        let mut config = app_config::load();
+        if let Some(tag) = &removed {
+            config.remove_tag(app_id, tag);
+        }
+        if let Some(tag) = &added {
+            config.add_tag(app_id, tag);
+        }
+        match app_config::save(&config) {
+            Ok(()) => *tags = config.tags_of(app_id),
+            Err(e) => log::error!("Failed to save tags for {}: {}", app_id, e),
+        }
+    }
+
+    /// Timeline of the operations run on the game, newest first.
+    fn history_section(ui: &mut egui::Ui, app_id: u32, cache: &mut DetailsCache) {
+        let entries = cache.history.entry(app_id).or_insert_with(|| history::load(app_id));
+        if entries.is_empty() {
+            ui.label("No backups, restores or config changes recorded yet");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("history")
+            .max_height(240.0)
+            .show(ui, |ui| {
+                egui::Grid::new("history_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in entries.iter().rev() {
+                            ui.monospace(entry.time());
+                            match &entry.error {
+                                Some(error) => {
+                                    ui.colored_label(
+                                        egui::Color32::RED,
+                                        format!("{} {}", regular::X_CIRCLE, entry.operation),
+                                    )
+                                    .on_hover_text(error);
+                                }
+                                None => {
+                                    ui.label(format!("{} {}", regular::CHECK_CIRCLE, entry.operation));
+                                }
+                            }
+                            ui.label(&entry.details);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    /// Programs installed into the prefix, such as launchers and mods.
+    fn installed_software_section(ui: &mut egui::Ui, game: &GameInfo, cache: &mut DetailsCache) {
+        let programs = cache.programs.entry(game.app_id()).or_insert_with(|| {
+            prefix_software::installed_programs(&game.prefix_path().join("pfx"))
+        });
+        if programs.is_empty() {
+            ui.label("No programs are registered in the prefix");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("installed_software")
+            .max_height(240.0)
+            .show(ui, |ui| {
+                egui::Grid::new("installed_software_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.strong("Name");
+                        ui.strong("Version");
+                        ui.strong("Location");
+                        ui.end_row();
+                        for program in programs.iter() {
+                            let name = ui.label(&program.name);
+                            if let Some(publisher) = &program.publisher {
+                                name.on_hover_text(publisher);
+                            }
+                            ui.monospace(program.version.as_deref().unwrap_or("-"));
+                            match &program.location {
+                                Some(location) => {
+                                    let path = save_locations::map_to_prefix(
+                                        location,
+                                        game.prefix_path(),
+                                        None,
+                                    )
+                                    .filter(|p| p.is_dir());
+                                    match path {
+                                        Some(path) => {
+                                            if ui
+                                                .link(location)
+                                                .on_hover_text(path.display().to_string())
+                                                .clicked()
+                                            {
+                                                let _ = open::that(&path);
+                                            }
+                                        }
+                                        None => {
+                                            ui.label(location);
+                                        }
+                                    }
+                                }
+                                None => {
+                                    ui.label("-");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    /// GPUs and Vulkan drivers, since many games that don't start have a
+    /// driver problem rather than a prefix problem.
+    fn troubleshooting_section(ui: &mut egui::Ui, cache: &mut DetailsCache) {
+        let gpus = cache.gpus.get_or_insert_with(system_info::vulkan_devices);
+        match gpus {
+            Some(gpus) if !gpus.is_empty() => {
+                egui::Grid::new("gpus")
+                    .num_columns(2)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        for gpu in gpus.iter() {
+                            ui.label("GPU:");
+                            ui.monospace(format!("{} ({})", gpu.name, gpu.device_type));
+                            ui.end_row();
+                            ui.label("Driver:");
+                            ui.monospace(format!("{} {}", gpu.driver_name, gpu.driver_info));
+                            ui.end_row();
+                            ui.label("Vulkan:");
+                            ui.monospace(&gpu.api_version);
+                            ui.end_row();
+                        }
+                    });
+                if gpus.iter().all(Gpu::is_software) {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("{} Only software rendering is available; install the Vulkan driver for your GPU", regular::WARNING),
+                    );
+                }
+            }
+            Some(_) => {
+                ui.colored_label(egui::Color32::RED, "No Vulkan device found");
+            }
+            None => {
+                ui.label("vulkaninfo is not installed or failed, so the Vulkan drivers could not be checked. Install vulkan-tools for details.");
+            }
+        }
+        if ui.button("Refresh").clicked() {
+            cache.gpus = None;
+        }
+    }
+
+    pub fn list_proton_versions() -> Vec<String> {
         use once_cell::sync::OnceCell;
         static CACHE: OnceCell<Vec<String>> = OnceCell::new();
         if let Some(v) = CACHE.get() {
@@ -359,25 +1273,76 @@ impl<'a> GameDetails<'a> {
                     });
                 });
                 ui.separator();
+                let tree_id = egui::Id::new("restore_tree");
+                if let Some(mut tree) = ui.data_mut(|d| d.get_temp::<FileTree>(tree_id)) {
+                    ui.label(format!(
+                        "Pick what to restore from {}",
+                        backup_utils::format_backup_name(tree.root())
+                    ));
+                    tree.show(ui);
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(!tree.selected.is_empty(), egui::Button::new("Restore Selected"))
+                            .clicked()
+                        {
+                            action = Some(Action::RestorePaths {
+                                backup: tree.root().to_path_buf(),
+                                prefix: game.prefix_path().to_path_buf(),
+                                paths: tree.selected.iter().cloned().collect(),
+                            });
+                            should_close = true;
+                        }
+                        if ui.button("Back").clicked() {
+                            ui.data_mut(|d| d.remove::<FileTree>(tree_id));
+                        } else {
+                            ui.data_mut(|d| d.insert_temp(tree_id, tree.clone()));
+                        }
+                    });
+                    return;
+                }
+                let delta_id = egui::Id::new("restore_delta");
+                let mut delta = ui.data_mut(|d| d.get_temp::<bool>(delta_id).unwrap_or(false));
+                if ui
+                    .checkbox(&mut delta, "Only replace changed files")
+                    .on_hover_text(
+                        "Compare the prefix with the backup and rewrite only the files that differ. \
+                         Much faster for large prefixes that mostly match the backup.",
+                    )
+                    .changed()
+                {
+                    ui.data_mut(|d| d.insert_temp(delta_id, delta));
+                }
                 let backups = backup_utils::list_backups(game.app_id());
                 if backups.is_empty() {
                     ui.label("No backups found");
                 } else {
                     for backup in backups {
                         let label = backup_utils::format_backup_name(&backup);
-                        if ui.button(label).clicked() {
-                            action = Some(Action::Restore {
-                                backup: backup.clone(),
-                                prefix: game.prefix_path().to_path_buf(),
-                            });
-                            should_close = true;
-                        }
+                        ui.horizontal(|ui| {
+                            if ui.button(label).clicked() {
+                                action = Some(Action::Restore {
+                                    backup: backup.clone(),
+                                    prefix: game.prefix_path().to_path_buf(),
+                                    delta,
+                                });
+                                should_close = true;
+                            }
+                            if ui
+                                .button("Select Files…")
+                                .on_hover_text("Restore only some files or folders of this backup")
+                                .clicked()
+                            {
+                                ui.data_mut(|d| d.insert_temp(tree_id, FileTree::new(&backup, true)));
+                            }
+                        });
                     }
                 }
             });
 
         if response.should_close() || should_close {
             *open = false;
+            ctx.data_mut(|d| d.remove::<FileTree>(egui::Id::new("restore_tree")));
         }
         action
     }
@@ -433,8 +1398,8 @@ impl<'a> GameDetails<'a> {
         ui: &mut egui::Ui,
         restore_dialog_open: &mut bool,
         delete_dialog_open: &mut bool,
-        configs: &mut HashMap<u32, GameConfig>,
-        info_cache: &mut HashMap<u32, PrefixInfo>,
+        cache: &mut DetailsCache,
+        tools: &BTreeMap<String, bool>,
     ) -> Option<Action> {
         let mut repair_request = None;
         if let Some(game) = self.game {
@@ -446,6 +1411,9 @@ impl<'a> GameDetails<'a> {
                 .show(ui, |ui| {
                     if self.prefix_available() {
                         self.show_path(ui, "Prefix Path:", game.prefix_path());
+                        if let Some(target) = prefix_relocate::relocated_target(game.prefix_path()) {
+                            self.show_path(ui, "Relocated To:", &target);
+                        }
 
                         let modified = game.modified();
                         if let Ok(time) = modified.duration_since(UNIX_EPOCH) {
@@ -466,8 +1434,33 @@ impl<'a> GameDetails<'a> {
                         if drive_c.exists() {
                             self.show_path(ui, "Drive C:", &drive_c);
                         }
+                    } else if archive::is_archived(game.app_id()) {
+                        ui.label(format!("{} The prefix of this game is archived.", regular::ARCHIVE));
+                        ui.horizontal(|ui| {
+                            if ui.button("Unarchive").clicked() {
+                                repair_request = Some(Action::UnarchivePrefix {
+                                    app_id: game.app_id(),
+                                    prefix: game.prefix_path().to_path_buf(),
+                                });
+                            }
+                            if ui
+                                .button("Browse")
+                                .on_hover_text("Look through the archived files without unpacking them")
+                                .clicked()
+                            {
+                                let tree = FileTree::new(&archive::archive_path(game.app_id()), false);
+                                ui.data_mut(|d| d.insert_temp(egui::Id::new("browse_archive"), tree));
+                            }
+                        });
                     } else {
                         ui.label("No prefix currently exists for this game.");
+                        if let Some(pending) = keep_saves::pending(game.app_id()) {
+                            ui.label(format!(
+                                "{} The saves kept at the last reset are put back once the game has been launched and closed again.",
+                                regular::FLOPPY_DISK
+                            ))
+                            .on_hover_text(pending.backup.display().to_string());
+                        }
                     }
 
                     // Tools moved to the top toolbar
@@ -477,7 +1470,8 @@ impl<'a> GameDetails<'a> {
             egui::CollapsingHeader::new(format!("{} Proton Information", regular::ROCKET))
                 .default_open(true)
                 .show(ui, |ui| {
-                    let info = info_cache
+                    let info = cache
+                        .prefix_info
                         .entry(game.app_id())
                         .or_insert_with(|| collect_prefix_info(game.prefix_path()));
                     if let Some(version) = &info.version {
@@ -489,6 +1483,65 @@ impl<'a> GameDetails<'a> {
                         ui.label("Proton version could not be detected");
                     }
 
+                    let container = cache.containers.entry(game.app_id()).or_insert_with(|| {
+                        proton_runtime::container_for(game.app_id())
+                            .map(|runtime| (runtime, proton_runtime::runtime_dir(runtime)))
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Runtime:");
+                        match container {
+                            Some((runtime, Some(dir))) => {
+                                ui.monospace(runtime.name())
+                                    .on_hover_text(format!("Runs in a pressure-vessel container from {}", dir.display()));
+                            }
+                            Some((runtime, None)) => {
+                                ui.monospace(runtime.name());
+                                ui.colored_label(egui::Color32::YELLOW, "(not installed yet)")
+                                    .on_hover_text("Steam downloads the runtime on the next launch of the game");
+                            }
+                            None => {
+                                ui.monospace("None (runs directly on the host)");
+                            }
+                        }
+                    });
+
+                    let windows = cache
+                        .windows
+                        .entry(game.app_id())
+                        .or_insert_with(|| windows_version::detect(&game.prefix_path().join("pfx")));
+                    if let Some(windows) = windows {
+                        ui.horizontal(|ui| {
+                            ui.label("Windows:");
+                            ui.monospace(format!("{} ({})", windows.label(), windows.arch()));
+                            let mut selected = windows.version.clone();
+                            egui::ComboBox::from_id_salt(("windows_version", game.app_id()))
+                                .selected_text("Change...")
+                                .show_ui(ui, |ui| {
+                                    for (id, name) in windows_version::VERSIONS {
+                                        ui.selectable_value(&mut selected, id.to_string(), name);
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Runs winecfg -v with the game's Proton Wine");
+                            if selected != windows.version
+                                && super::confirm(
+                                    "Change Windows version",
+                                    &format!(
+                                        "Set the Windows version of {} to {}? Close the game first.",
+                                        game.name(),
+                                        windows_version::label(&selected)
+                                    ),
+                                    tfd::MessageBoxIcon::Question,
+                                )
+                            {
+                                repair_request = Some(Action::SetWindowsVersion {
+                                    app_id: game.app_id(),
+                                    version: selected,
+                                });
+                            }
+                        });
+                    }
+
                     if info.has_dxvk {
                         ui.label(format!("{} DXVK is enabled", regular::CHECK));
                     }
@@ -519,23 +1572,150 @@ impl<'a> GameDetails<'a> {
                         });
                     }
 
-                    if let Some(install_dir) = find_install_dir(game.app_id()) {
+                    if game.size_on_disk() > 0 {
+                        ui.horizontal(|ui| {
+                            ui.label("Install size:");
+                            ui.monospace(output::format_size(game.size_on_disk()));
+                        });
+                    }
+
+                    if let Some(install_dir) = steam::find_install_dir(game.app_id()) {
                         self.show_path(ui, "Install Directory:", &install_dir);
                     }
 
                     if let Some(user_dir) = steam::find_userdata_dir(game.app_id()) {
                         self.show_path(ui, "Userdata Directory:", &user_dir);
                     }
+
+                    let anticheats = cache
+                        .anticheat
+                        .entry(game.app_id())
+                        .or_insert_with(|| anticheat::check(game.app_id(), game.prefix_path()));
+                    for (ac, installed) in anticheats.iter() {
+                        if *installed {
+                            ui.label(format!(
+                                "{} Uses {}; {} installed",
+                                regular::SHIELD_CHECK,
+                                ac.name(),
+                                ac.runtime_name()
+                            ));
+                        } else {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!(
+                                    "{} Uses {} but the {} is not installed",
+                                    regular::WARNING,
+                                    ac.name(),
+                                    ac.runtime_name()
+                                ),
+                            )
+                            .on_hover_text(ac.install_hint());
+                        }
+                    }
+
+                    if self.prefix_available() {
+                        let runtimes = cache.runtimes.entry(game.app_id()).or_insert_with(|| {
+                            prefix_software::installed_runtimes(&game.prefix_path().join("pfx"))
+                        });
+                        ui.add_space(4.0);
+                        ui.strong("Runtimes:");
+                        if runtimes.is_empty() {
+                            ui.label("No Visual C++, .NET or DirectX runtimes installed in the prefix");
+                        }
+                        for runtime in runtimes.iter() {
+                            let label = ui.label(format!(
+                                "{} {}: {}",
+                                regular::CHECK,
+                                runtime.kind.label(),
+                                runtime.name
+                            ));
+                            if let Some(version) = &runtime.version {
+                                label.on_hover_text(format!("Version {}", version));
+                            }
+                        }
+                    }
+
+                    let cloud_enabled = cache
+                        .configs
+                        .entry(game.app_id())
+                        .or_insert_with(|| Self::load_game_config(game.app_id()).unwrap_or_default())
+                        .cloud_sync;
+                    if cloud_enabled && self.prefix_available() {
+                        let conflicts = cache
+                            .cloud_conflicts
+                            .entry(game.app_id())
+                            .or_insert_with(|| {
+                                cloud_sync::detect_conflicts(game.app_id(), game.prefix_path())
+                            });
+                        if !conflicts.is_empty() {
+                            ui.add_space(4.0);
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!(
+                                    "{} {} file(s) differ from Steam Cloud",
+                                    regular::CLOUD_WARNING,
+                                    conflicts.len()
+                                ),
+                            )
+                            .on_hover_text(
+                                "Steam may overwrite these files or report a sync conflict on the next launch. Restoring a backup while they differ can be undone by Steam Cloud.",
+                            );
+                            for conflict in conflicts.iter() {
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(format!(
+                                            "{}: {}",
+                                            conflict.file,
+                                            conflict.divergence.describe()
+                                        ))
+                                        .small(),
+                                    ),
+                                )
+                                .on_hover_text(conflict.local_path.display().to_string());
+                            }
+                        }
+                    }
+
+                    ui.add_space(4.0);
+                    Self::tags_row(ui, game.app_id(), cache);
+
+                    ui.add_space(4.0);
+                    ui.strong("Notes:");
+                    let notes = cache
+                        .notes
+                        .entry(game.app_id())
+                        .or_insert_with(|| notes::load(game.app_id()));
+                    if ui
+                        .add(
+                            egui::TextEdit::multiline(notes)
+                                .hint_text("Tweaks, verbs or launch options that made it work")
+                                .desired_rows(3)
+                                .desired_width(f32::INFINITY),
+                        )
+                        .changed()
+                    {
+                        if let Err(e) = notes::save(game.app_id(), notes) {
+                            log::error!("Failed to save notes for {}: {}", game.app_id(), e);
+                        }
+                    }
                 });
 
             // Game Settings section
-            let cfg = configs
+            let cfg = cache
+                .configs
                 .entry(game.app_id())
                 .or_insert_with(|| Self::load_game_config(game.app_id()).unwrap_or_default());
+            let settings = cache
+                .game_settings
+                .entry(game.app_id())
+                .or_insert_with(|| app_config::load().game(game.app_id()));
             let has_custom = !cfg.launch_options.is_empty()
+                || *settings != GameSettings::default()
                 || cfg.proton.is_some()
                 || !cfg.auto_update
-                || !cfg.cloud_sync;
+                || !cfg.cloud_sync
+                || cfg.overlay_disabled
+                || cfg.steam_input != SteamInput::Default;
             let header_label = if has_custom {
                 format!("{} Game Settings *", regular::GEAR)
             } else {
@@ -567,6 +1747,31 @@ impl<'a> GameDetails<'a> {
                                 .hint_text("e.g. PROTON_LOG=1"),
                         );
                     });
+                    egui::CollapsingHeader::new("Environment Variables")
+                        .default_open(!settings.env.is_empty())
+                        .show(ui, |ui| {
+                            Self::env_editor(ui, settings, &mut cache.env_draft);
+                        })
+                        .header_response
+                        .on_hover_text("Kept by this tool and written in front of %command% on save");
+                    egui::CollapsingHeader::new("MangoHud")
+                        .default_open(settings.mangohud.is_some())
+                        .show(ui, |ui| {
+                            let installed = *tools.get("mangohud").unwrap_or(&false);
+                            Self::mangohud_editor(ui, settings, installed);
+                        });
+                    egui::CollapsingHeader::new("vkBasalt")
+                        .default_open(settings.vkbasalt.is_some())
+                        .show(ui, |ui| {
+                            let installed = *tools.get("vkbasalt").unwrap_or(&false);
+                            Self::vkbasalt_editor(ui, settings, installed);
+                        });
+                    egui::CollapsingHeader::new("Gamescope")
+                        .default_open(settings.gamescope.is_some())
+                        .show(ui, |ui| {
+                            let installed = *tools.get("gamescope").unwrap_or(&false);
+                            Self::gamescope_editor(ui, settings, installed);
+                        });
                     ui.horizontal(|ui| {
                         let lbl = ui.checkbox(&mut cfg.auto_update, "Enable auto-update");
                         lbl.on_hover_text("Toggle automatic updates for this game");
@@ -575,13 +1780,41 @@ impl<'a> GameDetails<'a> {
                         let lbl = ui.checkbox(&mut cfg.cloud_sync, "Enable Steam Cloud");
                         lbl.on_hover_text("Sync save data via Steam Cloud");
                     });
+                    ui.horizontal(|ui| {
+                        let lbl = ui.checkbox(&mut cfg.overlay_disabled, "Disable Steam Overlay");
+                        lbl.on_hover_text(
+                            "Turn off the in-game overlay, which causes crashes and stutter in some Proton games",
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Steam Input:");
+                        egui::ComboBox::from_id_salt("steam_input")
+                            .selected_text(cfg.steam_input.label())
+                            .show_ui(ui, |ui| {
+                                for input in SteamInput::ALL {
+                                    ui.selectable_value(&mut cfg.steam_input, input, input.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "Force Steam Input off when the game sees the controller twice \
+                                 or not at all",
+                            );
+                    });
                     if ui.button("Save").clicked() {
-                        match Self::save_game_config(game.app_id(), cfg) {
-                            Ok(_) => tfd::message_box_ok(
-                                "Config",
-                                "Settings saved",
-                                tfd::MessageBoxIcon::Info,
-                            ),
+                        let result = Self::save_game_config(game.app_id(), cfg)
+                            .map_err(crate::error::Error::from)
+                            .and_then(|_| launch_options::apply(game.app_id(), settings));
+                        cache.history.remove(&game.app_id());
+                        match result {
+                            Ok(launch) => {
+                                cfg.launch_options = launch;
+                                tfd::message_box_ok(
+                                    "Config",
+                                    "Settings saved",
+                                    tfd::MessageBoxIcon::Info,
+                                );
+                            }
                             Err(e) => tfd::message_box_ok(
                                 "Save failed",
                                 &format!("{}", e),
@@ -589,10 +1822,151 @@ impl<'a> GameDetails<'a> {
                             ),
                         };
                     }
+                    let last_change = cache
+                        .history
+                        .entry(game.app_id())
+                        .or_insert_with(|| history::load(game.app_id()));
+                    let last_change = config_undo::last_change(last_change).cloned();
+                    let undo = ui
+                        .add_enabled(last_change.is_some(), egui::Button::new("Undo Last Change"))
+                        .on_disabled_hover_text("No configuration change to undo");
+                    if let Some(change) = &last_change {
+                        let text = format!(
+                            "Undo {} from {}:\n{}",
+                            change.operation,
+                            change.time(),
+                            change.details
+                        );
+                        if undo.on_hover_text(&text).clicked()
+                            && super::confirm("Undo", &format!("{}?", text), tfd::MessageBoxIcon::Question)
+                        {
+                            let result = config_undo::undo(game.app_id());
+                            cache.history.remove(&game.app_id());
+                            match result {
+                                Ok(_) => {
+                                    *cfg = Self::load_game_config(game.app_id()).unwrap_or_default();
+                                    tfd::message_box_ok(
+                                        "Config",
+                                        "Previous settings restored",
+                                        tfd::MessageBoxIcon::Info,
+                                    );
+                                }
+                                Err(e) => tfd::message_box_ok(
+                                    "Undo failed",
+                                    &format!("{}", e),
+                                    tfd::MessageBoxIcon::Error,
+                                ),
+                            }
+                        }
+                    }
                 })
                 .header_response
                 .on_hover_text("Manage game specific options stored in appmanifest");
 
+            // Save Locations
+            egui::CollapsingHeader::new(format!("{} Save Locations", regular::FLOPPY_DISK))
+                .default_open(false)
+                .show(ui, |ui| {
+                    let state = cache.saves.entry(game.app_id()).or_insert_with(|| {
+                        match save_locations::cached_paths(game.app_id()) {
+                            Some(paths) => SaveLookup::Loaded(save_locations::resolve(
+                                &paths,
+                                game.prefix_path(),
+                                steam::find_install_dir(game.app_id()).as_deref(),
+                            )),
+                            None => SaveLookup::NotLoaded,
+                        }
+                    });
+                    let mut lookup = None;
+                    match state.clone() {
+                        SaveLookup::NotLoaded => {
+                            ui.label("Save locations have not been looked up yet.");
+                            if ui
+                                .button(format!("{} Look up on PCGamingWiki", regular::BOOKS))
+                                .on_hover_text("Query PCGamingWiki for this game's save and config paths")
+                                .clicked()
+                            {
+                                lookup = Some(false);
+                            }
+                        }
+                        SaveLookup::Loading => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Querying PCGamingWiki...");
+                            });
+                        }
+                        SaveLookup::Loaded(locations) => {
+                            if locations.is_empty() {
+                                ui.label("PCGamingWiki lists no Windows save locations.");
+                            }
+                            for loc in &locations {
+                                match &loc.path {
+                                    Some(path) if path.exists() => {
+                                        self.show_path(ui, &format!("{}:", loc.kind.label()), path);
+                                    }
+                                    _ => {
+                                        ui.horizontal(|ui| {
+                                            ui.strong(format!("{}:", loc.kind.label()));
+                                            ui.label("not present in prefix");
+                                        });
+                                        ui.add(egui::Label::new(
+                                            egui::RichText::new(&loc.windows_path).small().monospace(),
+                                        ));
+                                        ui.add_space(4.0);
+                                    }
+                                }
+                            }
+                            if ui.button(format!("{} Refresh", regular::ARROW_CLOCKWISE)).clicked() {
+                                lookup = Some(true);
+                            }
+                        }
+                        SaveLookup::Failed(err) => {
+                            ui.colored_label(egui::Color32::RED, err);
+                            if ui.button("Retry").clicked() {
+                                lookup = Some(true);
+                            }
+                        }
+                    }
+                    if let Some(refresh) = lookup {
+                        *state = SaveLookup::Loading;
+                        repair_request = Some(Action::LookupSaves {
+                            app_id: game.app_id(),
+                            prefix: game.prefix_path().to_path_buf(),
+                            refresh,
+                        });
+                    }
+                });
+
+            // Shader Cache
+            egui::CollapsingHeader::new(format!("{} Shader Cache", regular::CPU))
+                .default_open(false)
+                .show(ui, |ui| {
+                    Self::shader_cache_section(ui, game.app_id(), cache);
+                });
+
+            // Installed Software
+            if self.prefix_available() {
+                egui::CollapsingHeader::new(format!("{} Installed Software", regular::PACKAGE))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        Self::installed_software_section(ui, game, cache);
+                    });
+            }
+
+            // History
+            egui::CollapsingHeader::new(format!("{} History", regular::CLOCK_COUNTER_CLOCKWISE))
+                .default_open(false)
+                .show(ui, |ui| {
+                    Self::history_section(ui, game.app_id(), cache);
+                });
+
+            // Troubleshooting
+            egui::CollapsingHeader::new(format!("{} Troubleshooting", regular::FIRST_AID_KIT))
+                .default_open(false)
+                .show(ui, |ui| {
+                    Self::troubleshooting_section(ui, cache);
+                });
+
             ui.add_space(8.0);
 
             // External Links
@@ -624,6 +1998,15 @@ impl<'a> GameDetails<'a> {
                     repair_request = Some(act);
                 }
             }
+
+            let browse_id = egui::Id::new("browse_archive");
+            if let Some(mut tree) = ui.data_mut(|d| d.get_temp::<FileTree>(browse_id)) {
+                if file_tree::browse_window(ui.ctx(), "Archived Prefix", &mut tree) {
+                    ui.data_mut(|d| d.insert_temp(browse_id, tree));
+                } else {
+                    ui.data_mut(|d| d.remove::<FileTree>(browse_id));
+                }
+            }
         } else {
             ui.centered_and_justified(|ui| {
                 ui.label("Select a game to view details");
@@ -632,140 +2015,3 @@ impl<'a> GameDetails<'a> {
         repair_request
     }
 }
-
-fn detect_proton_version(prefix_path: &Path) -> Option<String> {
-    log::debug!("Detecting Proton version for prefix: {:?}", prefix_path);
-
-    // First check the 'version' file in the prefix
-    let version_file = prefix_path.join("version");
-    log::debug!("Checking version file: {:?}", version_file);
-    if version_file.exists() {
-        if let Ok(contents) = fs::read_to_string(&version_file) {
-            let version = contents.trim().to_string();
-            log::debug!("Found version in prefix: {}", version);
-            return Some(version);
-        }
-    }
-
-    // Check for 'version' in the parent directory (compatdata)
-    if let Some(parent) = prefix_path.parent() {
-        let version_file = parent.join("version");
-        log::debug!("Checking parent version file: {:?}", version_file);
-        if version_file.exists() {
-            if let Ok(contents) = fs::read_to_string(&version_file) {
-                let version = contents.trim().to_string();
-                log::debug!("Found version in parent: {}", version);
-                return Some(version);
-            }
-        }
-    }
-
-    // Check for version in the prefix's parent directory name (e.g., Proton 8.0)
-    if let Some(parent) = prefix_path.parent() {
-        if let Some(parent_name) = parent.file_name() {
-            if let Some(parent_str) = parent_name.to_str() {
-                if parent_str.to_lowercase().contains("proton") {
-                    log::debug!("Found version in parent directory name: {}", parent_str);
-                    return Some(parent_str.to_string());
-                }
-            }
-        }
-    }
-
-    // Check for toolmanifest.vdf in the prefix
-    let toolmanifest = prefix_path.join("toolmanifest.vdf");
-    log::debug!("Checking toolmanifest: {:?}", toolmanifest);
-    if toolmanifest.exists() {
-        if let Ok(contents) = fs::read_to_string(&toolmanifest) {
-            for line in contents.lines() {
-                let line = line.trim();
-                if line.starts_with("\"name\"") {
-                    if let Some(name) = line.split('"').nth(3) {
-                        if name.contains("Proton") {
-                            log::debug!("Found version in toolmanifest: {}", name);
-                            return Some(name.to_string());
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    // Check for proton_version in the prefix
-    let proton_version = prefix_path.join("proton_version");
-    log::debug!("Checking proton_version file: {:?}", proton_version);
-    if proton_version.exists() {
-        if let Ok(contents) = fs::read_to_string(&proton_version) {
-            let version = contents.trim().to_string();
-            log::debug!("Found version in proton_version: {}", version);
-            return Some(version);
-        }
-    }
-
-    // Check for the dist.info file which some Proton versions use
-    let dist_info = prefix_path.join("dist.info");
-    log::debug!("Checking dist.info file: {:?}", dist_info);
-    if dist_info.exists() {
-        if let Ok(contents) = fs::read_to_string(&dist_info) {
-            if let Some(version_line) = contents.lines().find(|l| l.contains("DIST_VERSION=")) {
-                if let Some(version) = version_line.split('=').nth(1) {
-                    let version = format!("Proton {}", version.trim());
-                    log::debug!("Found version in dist.info: {}", version);
-                    return Some(version);
-                }
-            }
-        }
-    }
-
-    log::debug!("No Proton version found for prefix: {:?}", prefix_path);
-    None
-}
-
-fn has_dxvk(prefix_path: &Path) -> bool {
-    // Check for DXVK DLLs in the prefix
-    let dll_path = prefix_path.join("pfx/drive_c/windows/system32");
-    if dll_path.exists() {
-        let dlls = ["d3d11.dll", "d3d10.dll", "d3d9.dll"];
-        dlls.iter().any(|dll| dll_path.join(dll).exists())
-    } else {
-        false
-    }
-}
-
-fn has_vkd3d(prefix_path: &Path) -> bool {
-    let dll_path = prefix_path.join("pfx/drive_c/windows/system32");
-    dll_path.join("d3d12.dll").exists()
-}
-
-pub fn collect_prefix_info(prefix_path: &Path) -> PrefixInfo {
-    PrefixInfo {
-        version: detect_proton_version(prefix_path),
-        has_dxvk: has_dxvk(prefix_path),
-        has_vkd3d: has_vkd3d(prefix_path),
-    }
-}
-
-fn find_install_dir(app_id: u32) -> Option<std::path::PathBuf> {
-    use crate::core::steam;
-
-    if let Ok(libraries) = steam::get_steam_libraries() {
-        for library in libraries {
-            let app_manifest = library
-                .join("steamapps")
-                .join(format!("appmanifest_{}.acf", app_id));
-            if app_manifest.exists() {
-                if let Ok(contents) = fs::read_to_string(&app_manifest) {
-                    // Look for the "installdir" field in the manifest
-                    if let Some(path) = contents
-                        .lines()
-                        .find(|line| line.contains("installdir"))
-                        .and_then(|line| line.split('"').nth(3))
-                    {
-                        return Some(library.join("steamapps/common").join(path));
-                    }
-                }
-            }
-        }
-    }
-    None
-}
@@ -0,0 +1,277 @@
+//! A collapsible view of the files of a backup or prefix archive.
+//!
+//! Directories are only read when they are expanded, so opening the tree of
+//! a large prefix is instant. Archives are listed once in the background
+//! without unpacking them. With checkboxes enabled the tree doubles as a
+//! picker of the paths to restore.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use eframe::egui;
+use egui_phosphor::regular;
+
+use crate::utils::archive;
+use crate::utils::backup as backup_utils;
+use crate::utils::output;
+
+/// A file or directory below the root of the tree.
+#[derive(Clone, Debug)]
+struct Node {
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Contents of the directories read so far, by path relative to the root.
+type Listing = BTreeMap<String, Vec<Node>>;
+
+/// Whether the contents of an archive are known yet.
+#[derive(Clone, Debug)]
+enum Loading {
+    Done,
+    Pending,
+    Failed(String),
+}
+
+#[derive(Clone)]
+pub struct FileTree {
+    root: PathBuf,
+    listing: Arc<Mutex<Listing>>,
+    loading: Arc<Mutex<Loading>>,
+    /// The root is an archive listed up front rather than a directory.
+    archive: bool,
+    checkable: bool,
+    /// Paths relative to the root picked by the user. A picked directory
+    /// stands for everything below it.
+    pub selected: BTreeSet<String>,
+}
+
+impl FileTree {
+    /// A tree of the backup directory or `.tar.gz` archive at `root`.
+    pub fn new(root: &Path, checkable: bool) -> Self {
+        let tree = Self {
+            root: root.to_path_buf(),
+            listing: Arc::new(Mutex::new(Listing::new())),
+            loading: Arc::new(Mutex::new(Loading::Done)),
+            archive: root.is_file(),
+            checkable,
+            selected: BTreeSet::new(),
+        };
+        if tree.archive {
+            *tree.loading.lock().unwrap() = Loading::Pending;
+            let archive = root.to_path_buf();
+            let listing = Arc::clone(&tree.listing);
+            let loading = Arc::clone(&tree.loading);
+            thread::spawn(move || {
+                let result = archive::list_archive(&archive);
+                *loading.lock().unwrap() = match result {
+                    Ok(entries) => {
+                        *listing.lock().unwrap() = Self::archive_listing(entries);
+                        Loading::Done
+                    }
+                    Err(e) => Loading::Failed(e.to_string()),
+                };
+            });
+        }
+        tree
+    }
+
+    /// Arrange the flat list of an archive into directories, adding those
+    /// the archive only has implicitly.
+    fn archive_listing(entries: Vec<archive::ArchiveEntry>) -> Listing {
+        let mut listing = Listing::new();
+        listing.insert(String::new(), Vec::new());
+        let mut known = HashSet::new();
+        for entry in entries {
+            let mut parent = String::new();
+            let mut components = entry.path.split('/').peekable();
+            while let Some(name) = components.next() {
+                let last = components.peek().is_none();
+                let path = if parent.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", parent, name)
+                };
+                if known.insert(path.clone()) {
+                    let is_dir = !last || entry.is_dir;
+                    listing.entry(parent.clone()).or_default().push(Node {
+                        name: name.to_string(),
+                        is_dir,
+                        size: if last { entry.size } else { 0 },
+                    });
+                    if is_dir {
+                        listing.entry(path.clone()).or_default();
+                    }
+                }
+                parent = path;
+            }
+        }
+        for nodes in listing.values_mut() {
+            Self::sort(nodes);
+        }
+        listing
+    }
+
+    fn sort(nodes: &mut [Node]) {
+        nodes.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directories first, each group sorted by name. The backup's own
+    /// bookkeeping files at the top are left out.
+    fn read(&self, relative: &str) -> Vec<Node> {
+        let mut nodes: Vec<Node> = fs::read_dir(self.root.join(relative))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if relative.is_empty() && backup_utils::is_backup_metadata(&name) {
+                    return None;
+                }
+                let metadata = entry.path().symlink_metadata().ok()?;
+                Some(Node {
+                    name,
+                    is_dir: metadata.is_dir(),
+                    size: metadata.len(),
+                })
+            })
+            .collect();
+        Self::sort(&mut nodes);
+        nodes
+    }
+
+    fn children(&self, relative: &str) -> Vec<Node> {
+        let mut listing = self.listing.lock().unwrap();
+        if self.archive {
+            return listing.get(relative).cloned().unwrap_or_default();
+        }
+        listing
+            .entry(relative.to_string())
+            .or_insert_with(|| self.read(relative))
+            .clone()
+    }
+
+    /// Whether `relative` or one of its parents is selected.
+    fn covered(&self, relative: &str) -> bool {
+        let mut path = relative;
+        loop {
+            if self.selected.contains(path) {
+                return true;
+            }
+            match path.rfind('/') {
+                Some(i) => path = &path[..i],
+                None => return false,
+            }
+        }
+    }
+
+    fn toggle(&mut self, relative: &str, on: bool) {
+        if on {
+            let below = format!("{}/", relative);
+            self.selected.retain(|p| !p.starts_with(&below));
+            self.selected.insert(relative.to_string());
+        } else {
+            self.selected.remove(relative);
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let loading = self.loading.lock().unwrap().clone();
+        match loading {
+            Loading::Pending => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Reading archive...");
+                });
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(200));
+                return;
+            }
+            Loading::Failed(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Cannot read {}: {}", self.root.display(), e));
+                return;
+            }
+            Loading::Done => {}
+        }
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                let nodes = self.children("");
+                if nodes.is_empty() {
+                    ui.label("Empty");
+                }
+                self.show_nodes(ui, "", &nodes);
+            });
+    }
+
+    fn show_nodes(&mut self, ui: &mut egui::Ui, parent: &str, nodes: &[Node]) {
+        for node in nodes {
+            let relative = if parent.is_empty() {
+                node.name.clone()
+            } else {
+                format!("{}/{}", parent, node.name)
+            };
+            if node.is_dir {
+                let id = ui.make_persistent_id(("file_tree", &self.root, &relative));
+                egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header(ui, |ui| {
+                        self.node_label(ui, &relative, &format!("{} {}", regular::FOLDER, node.name));
+                    })
+                    .body(|ui| {
+                        let nodes = self.children(&relative);
+                        self.show_nodes(ui, &relative, &nodes);
+                    });
+            } else {
+                ui.horizontal(|ui| {
+                    self.node_label(ui, &relative, &format!("{} {}", regular::FILE, node.name));
+                    ui.weak(output::format_size(node.size));
+                });
+            }
+        }
+    }
+
+    fn node_label(&mut self, ui: &mut egui::Ui, relative: &str, text: &str) {
+        if !self.checkable {
+            ui.label(text);
+            return;
+        }
+        let mut checked = self.covered(relative);
+        let by_parent = checked && !self.selected.contains(relative);
+        if ui
+            .add_enabled(!by_parent, egui::Checkbox::new(&mut checked, text))
+            .changed()
+        {
+            self.toggle(relative, checked);
+        }
+    }
+}
+
+/// Show `tree` read-only in a window of its own. Returns false once the
+/// window is closed.
+pub fn browse_window(ctx: &egui::Context, title: &str, tree: &mut FileTree) -> bool {
+    let mut open = true;
+    let response = egui::Modal::new(egui::Id::new("browse_files"))
+        .frame(egui::Frame::window(&ctx.style()))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(title);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("Close").clicked() {
+                        open = false;
+                    }
+                });
+            });
+            ui.label(egui::RichText::new(tree.root().display().to_string()).small());
+            ui.separator();
+            tree.show(ui);
+        });
+    open && !response.should_close()
+}
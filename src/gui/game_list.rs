@@ -1,25 +1,149 @@
-use super::sort::GameSortKey;
+use super::sort::{compare_versions, prefix_version, GameSortKey};
 use crate::core::models::GameInfo;
+use crate::utils::prefix_info::PrefixInfo;
+use crate::utils::{artwork, disk_size, output};
+use chrono::Datelike;
 use eframe::egui;
 use egui_phosphor::regular;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+const THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(24.0, 36.0);
+
+/// How the game list is split into collapsible sections.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameGrouping {
+    /// One flat list
+    #[default]
+    None,
+    /// A section per Steam library folder
+    Library,
+    /// A section per Proton version detected in the prefix
+    ProtonVersion,
+    /// Played this month, played before, never played
+    Recency,
+}
+
+impl GameGrouping {
+    pub const ALL: [GameGrouping; 4] = [
+        GameGrouping::None,
+        GameGrouping::Library,
+        GameGrouping::ProtonVersion,
+        GameGrouping::Recency,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameGrouping::None => "None",
+            GameGrouping::Library => "Steam Library",
+            GameGrouping::ProtonVersion => "Proton Version",
+            GameGrouping::Recency => "Last Played",
+        }
+    }
+}
+
+/// Sections of the recency grouping, in display order.
+const PLAYED_THIS_MONTH: &str = "Played this month";
+const PLAYED_BEFORE: &str = "Played before this month";
+const NEVER_PLAYED: &str = "Never played";
 
 /// Wrapper to display a game list with sorting controls
 
 pub struct GameList<'a> {
     games: &'a [GameInfo],
+    /// Prefix info of each game, for the Proton version group headers.
+    prefix_info: &'a mut HashMap<u32, PrefixInfo>,
+    /// AppIDs pinned to the top, toggled with the star of each row.
+    favorites: &'a mut BTreeSet<u32>,
+    /// AppIDs hidden from the list, toggled from each row's context menu.
+    hidden: &'a mut BTreeSet<u32>,
+    /// Whether hidden games are listed anyway.
+    show_hidden: &'a mut bool,
+    /// Set when the hidden games manager should open.
+    manage_hidden: &'a mut bool,
 }
 
 impl<'a> GameList<'a> {
-    pub fn new(games: &'a [GameInfo]) -> Self {
-        Self { games }
+    pub fn new(
+        games: &'a [GameInfo],
+        prefix_info: &'a mut HashMap<u32, PrefixInfo>,
+        favorites: &'a mut BTreeSet<u32>,
+        hidden: &'a mut BTreeSet<u32>,
+        show_hidden: &'a mut bool,
+        manage_hidden: &'a mut bool,
+    ) -> Self {
+        Self {
+            games,
+            prefix_info,
+            favorites,
+            hidden,
+            show_hidden,
+            manage_hidden,
+        }
+    }
+
+    /// Split the games into the sections of `grouping`, keeping their
+    /// order within each section.
+    fn groups(&mut self, grouping: GameGrouping) -> Vec<(String, Vec<&'a GameInfo>)> {
+        let mut groups: Vec<(String, Vec<&'a GameInfo>)> = Vec::new();
+        let mut versions: HashMap<String, Option<String>> = HashMap::new();
+        let month_start = chrono::Local::now()
+            .date_naive()
+            .with_day(1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .and_then(|d| d.and_local_timezone(chrono::Local).earliest())
+            .map_or(0, |d| d.timestamp().max(0) as u64);
+        for game in self.games {
+            let label = match grouping {
+                GameGrouping::None => String::new(),
+                GameGrouping::Library => game
+                    .prefix_path()
+                    .ancestors()
+                    .nth(3)
+                    .map_or_else(|| "Unknown library".to_string(), |p| p.display().to_string()),
+                GameGrouping::ProtonVersion => {
+                    let version = prefix_version(self.prefix_info, game).map(str::to_string);
+                    let label = version
+                        .clone()
+                        .unwrap_or_else(|| "Unknown Proton version".to_string());
+                    versions.insert(label.clone(), version);
+                    label
+                }
+                GameGrouping::Recency => match game.last_played() {
+                    0 => NEVER_PLAYED,
+                    t if t >= month_start => PLAYED_THIS_MONTH,
+                    _ => PLAYED_BEFORE,
+                }
+                .to_string(),
+            };
+            match groups.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, games)) => games.push(game),
+                None => groups.push((label, vec![game])),
+            }
+        }
+        match grouping {
+            GameGrouping::None => {}
+            GameGrouping::Library => groups.sort_by(|a, b| a.0.cmp(&b.0)),
+            GameGrouping::ProtonVersion => groups.sort_by(|a, b| {
+                compare_versions(versions[&a.0].as_deref(), versions[&b.0].as_deref())
+            }),
+            GameGrouping::Recency => groups.sort_by_key(|(label, _)| {
+                [PLAYED_THIS_MONTH, PLAYED_BEFORE, NEVER_PLAYED]
+                    .iter()
+                    .position(|l| l == label)
+            }),
+        }
+        groups
     }
 
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
         selected_game: &mut Option<GameInfo>,
+        art: &mut HashMap<u32, Option<PathBuf>>,
         sort_key: &mut GameSortKey,
         descending: &mut bool,
+        grouping: &mut GameGrouping,
     ) -> bool {
         let mut changed = false;
         ui.vertical(|ui| {
@@ -36,6 +160,8 @@ impl<'a> GameList<'a> {
                         ui.selectable_value(sort_key, GameSortKey::Name, "Name");
                         ui.selectable_value(sort_key, GameSortKey::AppId, "AppID");
                         ui.selectable_value(sort_key, GameSortKey::ProtonVersion, "Proton Version");
+                        ui.selectable_value(sort_key, GameSortKey::InstallSize, "Install Size");
+                        ui.selectable_value(sort_key, GameSortKey::Size, "Prefix Size");
                     });
                 if *sort_key != prev {
                     changed = true;
@@ -46,31 +172,194 @@ impl<'a> GameList<'a> {
                     *descending = !*descending;
                     changed = true;
                 }
+
+                ui.label("Group by:");
+                egui::ComboBox::from_id_salt("group_combo")
+                    .selected_text(grouping.label())
+                    .show_ui(ui, |ui| {
+                        for g in GameGrouping::ALL {
+                            ui.selectable_value(grouping, g, g.label());
+                        }
+                    });
             });
 
+            if !self.hidden.is_empty() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .checkbox(self.show_hidden, format!("Show {} hidden", self.hidden.len()))
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    if ui
+                        .small_button(format!("{} Manage", regular::EYE_SLASH))
+                        .on_hover_text("List and unhide hidden games")
+                        .clicked()
+                    {
+                        *self.manage_hidden = true;
+                    }
+                });
+            }
+
             if self.games.is_empty() {
                 ui.label("No games found");
                 return;
             }
 
+            let mut measuring = false;
+            let groups = self.groups(*grouping);
             egui::ScrollArea::vertical()
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
+                    if *grouping != GameGrouping::None {
+                        for (label, games) in &groups {
+                            egui::CollapsingHeader::new(format!("{} ({})", label, games.len()))
+                                .id_salt(("game_group", label))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    for game in games {
+                                        changed |= game_row(
+                                            ui,
+                                            game,
+                                            selected_game,
+                                            art,
+                                            self.favorites,
+                                            self.hidden,
+                                            &mut measuring,
+                                        );
+                                    }
+                                });
+                        }
+                        return;
+                    }
+                    let mut group: Option<Option<String>> = None;
                     for game in self.games {
-                        let is_selected = selected_game
-                            .as_ref()
-                            .map_or(false, |g| g.app_id() == game.app_id());
-
-                        let response = ui.selectable_label(is_selected, game.name());
-
-                        if response.clicked() {
-                            *selected_game = Some(game.clone());
+                        if *sort_key == GameSortKey::ProtonVersion {
+                            let version = prefix_version(self.prefix_info, game).map(str::to_string);
+                            if group.as_ref() != Some(&version) {
+                                ui.add_space(4.0);
+                                ui.strong(version.as_deref().unwrap_or("Unknown Proton version"));
+                                ui.separator();
+                                group = Some(version);
+                            }
                         }
-
-                        response.on_hover_text(format!("AppID: {}", game.app_id()));
+                        changed |= game_row(
+                            ui,
+                            game,
+                            selected_game,
+                            art,
+                            self.favorites,
+                            self.hidden,
+                            &mut measuring,
+                        );
                     }
                 });
+            if measuring {
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(250));
+            }
         });
         changed
     }
 }
+
+/// A row with a pin star and the game's artwork, name and prefix size,
+/// selecting the game when clicked and hiding it from its context menu.
+/// Returns whether the game was pinned, unpinned, hidden or unhidden.
+fn game_row(
+    ui: &mut egui::Ui,
+    game: &GameInfo,
+    selected_game: &mut Option<GameInfo>,
+    art: &mut HashMap<u32, Option<PathBuf>>,
+    favorites: &mut BTreeSet<u32>,
+    hidden: &mut BTreeSet<u32>,
+    measuring: &mut bool,
+) -> bool {
+    let mut toggled = false;
+    let is_selected = selected_game
+        .as_ref()
+        .map_or(false, |g| g.app_id() == game.app_id());
+
+    let thumbnail = art
+        .entry(game.app_id())
+        .or_insert_with(|| artwork::find_art(game.app_id()));
+    let response = ui
+        .horizontal(|ui| {
+            let pinned = favorites.contains(&game.app_id());
+            let star = egui::RichText::new(regular::STAR).color(if pinned {
+                egui::Color32::GOLD
+            } else {
+                ui.visuals().weak_text_color()
+            });
+            if ui
+                .add(egui::Button::new(star).frame(false))
+                .on_hover_text(if pinned { "Unpin" } else { "Pin to the top" })
+                .clicked()
+            {
+                if pinned {
+                    favorites.remove(&game.app_id());
+                } else {
+                    favorites.insert(game.app_id());
+                }
+                toggled = true;
+            }
+            match thumbnail {
+                Some(path) => {
+                    ui.add(
+                        egui::Image::new(format!("file://{}", path.display()))
+                            .fit_to_exact_size(THUMBNAIL_SIZE),
+                    );
+                }
+                None => {
+                    ui.allocate_space(THUMBNAIL_SIZE);
+                }
+            }
+            let is_hidden = hidden.contains(&game.app_id());
+            let response = if is_hidden {
+                ui.selectable_label(
+                    is_selected,
+                    egui::RichText::new(format!("{} {}", regular::EYE_SLASH, game.name())).weak(),
+                )
+            } else {
+                ui.selectable_label(is_selected, game.name())
+            };
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if !game.prefix_exists() {
+                    ui.weak("-");
+                } else if let Some(size) = disk_size::size(game.prefix_path()) {
+                    ui.weak(output::format_size(size));
+                } else {
+                    ui.spinner();
+                    *measuring = true;
+                }
+            });
+            response
+        })
+        .inner;
+
+    if response.clicked() {
+        *selected_game = Some(game.clone());
+    }
+    response.context_menu(|ui| {
+        if hidden.contains(&game.app_id()) {
+            if ui.button(format!("{} Unhide", regular::EYE)).clicked() {
+                hidden.remove(&game.app_id());
+                toggled = true;
+                ui.close_menu();
+            }
+        } else if ui.button(format!("{} Hide from list", regular::EYE_SLASH)).clicked() {
+            hidden.insert(game.app_id());
+            toggled = true;
+            ui.close_menu();
+        }
+    });
+
+    let mut hover = format!("AppID: {}", game.app_id());
+    if game.size_on_disk() > 0 {
+        hover.push_str(&format!(
+            "\nInstall size: {}",
+            output::format_size(game.size_on_disk())
+        ));
+    }
+    response.on_hover_text(hover);
+    toggled
+}
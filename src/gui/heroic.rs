@@ -0,0 +1,168 @@
+use super::details::Action;
+use crate::utils::backup as backup_utils;
+use crate::utils::heroic::HeroicGame;
+use crate::utils::prefix_info;
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::path::Path;
+
+/// List of Heroic games shown below the Steam game list.
+/// Returns true when a game was clicked.
+pub fn heroic_list(
+    ui: &mut egui::Ui,
+    games: &[HeroicGame],
+    selected: &mut Option<HeroicGame>,
+) -> bool {
+    let mut clicked = false;
+    ui.heading("Heroic Games");
+    if games.is_empty() {
+        ui.label("No Heroic prefixes found");
+        return clicked;
+    }
+    egui::ScrollArea::vertical()
+        .id_salt("heroic_list")
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            for game in games {
+                let is_selected = selected
+                    .as_ref()
+                    .is_some_and(|g| g.app_name == game.app_name);
+                let response = ui
+                    .selectable_label(is_selected, &game.title)
+                    .on_hover_text(format!("{}: {}", game.store.label(), game.app_name));
+                if response.clicked() {
+                    *selected = Some(game.clone());
+                    clicked = true;
+                }
+            }
+        });
+    clicked
+}
+
+/// Details panel for a Heroic game with backup, restore and validation.
+pub struct HeroicDetails<'a> {
+    game: &'a HeroicGame,
+}
+
+impl<'a> HeroicDetails<'a> {
+    pub fn new(game: &'a HeroicGame) -> Self {
+        Self { game }
+    }
+
+    fn show_path(ui: &mut egui::Ui, label: &str, path: &Path) {
+        ui.horizontal(|ui| {
+            ui.strong(label);
+            if ui.button(format!("{} Open", regular::FOLDER_OPEN)).clicked() {
+                let _ = open::that(path);
+            }
+            ui.monospace(path.display().to_string());
+        });
+    }
+
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        restore_dialog_open: &mut bool,
+        problems: &mut Option<Vec<String>>,
+    ) -> Option<Action> {
+        let game = self.game;
+        let mut action = None;
+
+        ui.heading(&game.title);
+        ui.label(format!("{} · {}", game.store.label(), game.app_name));
+        ui.separator();
+
+        egui::CollapsingHeader::new("Prefix Information")
+            .default_open(true)
+            .show(ui, |ui| {
+                Self::show_path(ui, "Prefix Path:", &game.prefix);
+                let drive_c = game.wine_prefix().join("drive_c");
+                if drive_c.exists() {
+                    Self::show_path(ui, "Drive C:", &drive_c);
+                }
+            });
+
+        egui::CollapsingHeader::new(format!("{} Prefix Tools", regular::WRENCH))
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Backup").clicked() {
+                        action = Some(Action::BackupInto {
+                            prefix: game.prefix.clone(),
+                            root: game.backup_dir(),
+                        });
+                    }
+                    if ui.button("Restore").clicked() {
+                        *restore_dialog_open = true;
+                    }
+                    if ui.button("Validate").clicked() {
+                        *problems = Some(prefix_info::validate_wine_prefix(&game.wine_prefix()));
+                    }
+                });
+
+                match problems {
+                    Some(list) if list.is_empty() => {
+                        ui.label(format!("{} Prefix looks complete", regular::CHECK));
+                    }
+                    Some(list) => {
+                        ui.label(
+                            egui::RichText::new(format!("{} Problems found:", regular::WARNING))
+                                .color(egui::Color32::YELLOW),
+                        );
+                        for problem in list.iter() {
+                            ui.label(format!("• {}", problem));
+                        }
+                    }
+                    None => {}
+                }
+            });
+
+        if let Some(act) = self.restore_window(ui.ctx(), restore_dialog_open) {
+            action = Some(act);
+        }
+        action
+    }
+
+    fn restore_window(&self, ctx: &egui::Context, open: &mut bool) -> Option<Action> {
+        let mut action = None;
+        if !*open {
+            return action;
+        }
+
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("heroic_restore_modal"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Select Backup to Restore");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.separator();
+                let backups = backup_utils::list_backups_in(&self.game.backup_dir());
+                if backups.is_empty() {
+                    ui.label("No backups found");
+                } else {
+                    for backup in backups {
+                        let label = backup_utils::format_backup_name(&backup);
+                        if ui.button(label).clicked() {
+                            action = Some(Action::Restore {
+                                backup: backup.clone(),
+                                prefix: self.game.prefix.clone(),
+                                delta: false,
+                            });
+                            should_close = true;
+                        }
+                    }
+                }
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+        action
+    }
+}
@@ -0,0 +1,76 @@
+use crate::core::steam;
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::collections::{BTreeSet, HashMap};
+
+/// Lists the games hidden from the game list and unhides them.
+pub struct HiddenGamesWindow {
+    /// Names of the hidden games, looked up once per AppID.
+    names: HashMap<u32, String>,
+}
+
+impl HiddenGamesWindow {
+    pub fn new() -> Self {
+        Self {
+            names: HashMap::new(),
+        }
+    }
+
+    /// Show the hidden games. Unhiding one removes it from `hidden`.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, hidden: &mut BTreeSet<u32>) {
+        if !*open {
+            return;
+        }
+
+        let mut should_close = false;
+        let mut unhide = Vec::new();
+        let response = Modal::new(egui::Id::new("hidden_games"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.set_width(420.0);
+                ui.horizontal(|ui| {
+                    ui.heading("Hidden Games");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                if hidden.is_empty() {
+                    ui.label("No games are hidden. Right-click a game in the list to hide it.");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    egui::Grid::new("hidden_games_grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for &appid in hidden.iter() {
+                                let name = self.names.entry(appid).or_insert_with(|| {
+                                    steam::app_name(appid)
+                                        .unwrap_or_else(|| format!("App {}", appid))
+                                });
+                                ui.label(format!("{} ({})", name, appid));
+                                if ui.button(format!("{} Unhide", regular::EYE)).clicked() {
+                                    unhide.push(appid);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                ui.separator();
+                if ui.button("Unhide All").clicked() {
+                    unhide.extend(hidden.iter().copied());
+                }
+            });
+
+        for appid in unhide {
+            hidden.remove(&appid);
+        }
+        if response.should_close() || should_close {
+            *open = false;
+        }
+    }
+}
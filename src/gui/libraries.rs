@@ -0,0 +1,146 @@
+use crate::utils::library_overview::{self, LibraryOverview};
+use crate::utils::output;
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Every Steam library with its disk space, games and prefix sizes.
+pub struct LibrariesWindow {
+    libraries: Vec<LibraryOverview>,
+    error: Option<String>,
+    loading: bool,
+    needs_refresh: bool,
+    rx: Option<Receiver<Result<Vec<LibraryOverview>, String>>>,
+}
+
+impl LibrariesWindow {
+    pub fn new() -> Self {
+        Self {
+            libraries: Vec::new(),
+            error: None,
+            loading: false,
+            needs_refresh: true,
+            rx: None,
+        }
+    }
+
+    fn start_scan(&mut self) {
+        self.loading = true;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(library_overview::all().map_err(|e| e.to_string()));
+        });
+        self.rx = Some(rx);
+    }
+
+    fn library_row(ui: &mut egui::Ui, lib: &LibraryOverview) {
+        let icon = if lib.low_space {
+            regular::WARNING
+        } else {
+            regular::HARD_DRIVES
+        };
+        ui.strong(format!("{} {}", icon, lib.path.display()));
+        if let (Some(free), Some(total)) = (lib.free_space, lib.total_space) {
+            let used = if total > 0 {
+                1.0 - free as f32 / total as f32
+            } else {
+                0.0
+            };
+            let mut bar = egui::ProgressBar::new(used).text(format!(
+                "{} free of {}",
+                output::format_size(free),
+                output::format_size(total)
+            ));
+            if lib.low_space {
+                bar = bar.fill(egui::Color32::from_rgb(200, 60, 60));
+            }
+            ui.add(bar);
+        } else {
+            ui.label("Free space unknown");
+        }
+        ui.label(format!(
+            "{} games, {} in compatdata",
+            lib.games,
+            output::format_size(lib.compatdata_size)
+        ));
+        if lib.low_space {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                "Low on free space: game updates and new prefixes may fail.",
+            );
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !*open {
+            self.rx = None;
+            self.loading = false;
+            self.needs_refresh = true;
+            return;
+        }
+
+        if self.needs_refresh && !self.loading {
+            self.start_scan();
+        }
+
+        if let Some(rx) = &self.rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(libraries) => {
+                        self.libraries = libraries;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                self.loading = false;
+                self.needs_refresh = false;
+                self.rx = None;
+            }
+        }
+
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("libraries"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Libraries");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                        if ui
+                            .add_enabled(!self.loading, egui::Button::new(format!("{} Refresh", regular::ARROW_CLOCKWISE)))
+                            .clicked()
+                        {
+                            self.needs_refresh = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                if self.loading {
+                    ui.centered_and_justified(|ui| {
+                        ui.spinner();
+                        ui.label("Measuring libraries...");
+                    });
+                    return;
+                }
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                    return;
+                }
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for lib in &self.libraries {
+                        Self::library_row(ui, lib);
+                        ui.separator();
+                    }
+                });
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+    }
+}
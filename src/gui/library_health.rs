@@ -0,0 +1,163 @@
+use crate::utils::library_health::{self, LibraryHealth};
+use crate::utils::output;
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Per-library statistics: free space, prefixes and detected problems.
+pub struct LibraryHealthWindow {
+    report: Vec<LibraryHealth>,
+    error: Option<String>,
+    loading: bool,
+    needs_refresh: bool,
+    rx: Option<Receiver<Result<Vec<LibraryHealth>, String>>>,
+}
+
+impl LibraryHealthWindow {
+    pub fn new() -> Self {
+        Self {
+            report: Vec::new(),
+            error: None,
+            loading: false,
+            needs_refresh: true,
+            rx: None,
+        }
+    }
+
+    fn start_scan(&mut self) {
+        self.loading = true;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(library_health::check_all().map_err(|e| e.to_string()));
+        });
+        self.rx = Some(rx);
+    }
+
+    fn space(bytes: Option<u64>) -> String {
+        bytes.map(output::format_size).unwrap_or_else(|| "?".to_string())
+    }
+
+    fn library_section(ui: &mut egui::Ui, lib: &LibraryHealth) {
+        let icon = if lib.is_healthy() {
+            regular::CHECK_CIRCLE
+        } else {
+            regular::WARNING
+        };
+        ui.strong(format!("{} {}", icon, lib.path.display()));
+        egui::Grid::new(("library_health", lib.path.display().to_string()))
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Free Space:");
+                ui.label(format!(
+                    "{} of {}",
+                    Self::space(lib.free_space),
+                    Self::space(lib.total_space)
+                ));
+                ui.end_row();
+                ui.label("Prefixes:");
+                ui.label(lib.prefixes.to_string());
+                ui.end_row();
+                ui.label("Orphaned Data:");
+                ui.label(format!(
+                    "{} ({})",
+                    lib.orphaned.len(),
+                    output::format_size(lib.orphaned_size)
+                ));
+                ui.end_row();
+                ui.label("Unreadable Manifests:");
+                ui.label(lib.unreadable_manifests.len().to_string());
+                ui.end_row();
+                ui.label("Permission Problems:");
+                ui.label(lib.permission_problems.len().to_string());
+                ui.end_row();
+            });
+        if !lib.is_healthy() {
+            egui::CollapsingHeader::new("Details")
+                .id_salt(("library_health_details", lib.path.display().to_string()))
+                .show(ui, |ui| {
+                    for path in &lib.orphaned {
+                        ui.label(format!("{} Orphaned: {}", regular::TRASH, path.display()));
+                    }
+                    for path in &lib.unreadable_manifests {
+                        ui.label(format!("{} Unreadable manifest: {}", regular::QUESTION, path.display()));
+                    }
+                    for problem in &lib.permission_problems {
+                        ui.label(format!("{} {}", regular::LOCK, problem));
+                    }
+                });
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !*open {
+            self.rx = None;
+            self.loading = false;
+            self.needs_refresh = true;
+            return;
+        }
+
+        if self.needs_refresh && !self.loading {
+            self.start_scan();
+        }
+
+        if let Some(rx) = &self.rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(report) => {
+                        self.report = report;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                self.loading = false;
+                self.needs_refresh = false;
+                self.rx = None;
+            }
+        }
+
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("library_health"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Library Statistics");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                        if ui
+                            .add_enabled(!self.loading, egui::Button::new(format!("{} Refresh", regular::ARROW_CLOCKWISE)))
+                            .clicked()
+                        {
+                            self.needs_refresh = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                if self.loading {
+                    ui.centered_and_justified(|ui| {
+                        ui.spinner();
+                        ui.label("Checking libraries...");
+                    });
+                    return;
+                }
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                    return;
+                }
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for lib in &self.report {
+                        Self::library_section(ui, lib);
+                        ui.separator();
+                    }
+                });
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+    }
+}
@@ -1,9 +1,28 @@
 mod advanced_search;
 mod app;
+mod archive;
 mod backup_manager;
+mod compat_tools;
 mod details;
+mod file_tree;
 mod game_list;
+mod heroic;
+mod hidden_games;
+mod libraries;
+mod library_health;
+mod profiles;
+mod protontricks;
 mod runtime_cleaner;
+mod settings;
 mod sort;
+mod wasted_space;
 
 pub use app::ProtonPrefixManagerApp;
+
+/// Ask a yes/no question before a destructive action, unless confirmations
+/// are turned off in the settings.
+fn confirm(title: &str, text: &str, icon: tinyfiledialogs::MessageBoxIcon) -> bool {
+    !crate::utils::app_config::load().general.confirm
+        || tinyfiledialogs::message_box_yes_no(title, text, icon, tinyfiledialogs::YesNo::No)
+            == tinyfiledialogs::YesNo::Yes
+}
@@ -0,0 +1,231 @@
+use super::details::{Action, GameDetails};
+use crate::core::models::GameInfo;
+use crate::utils::app_config::{self, AppConfig};
+use crate::utils::profile::GameProfile;
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::collections::BTreeSet;
+use tinyfiledialogs as tfd;
+
+pub struct ProfilesWindow {
+    config: AppConfig,
+    loaded: bool,
+    selected: Option<String>,
+    new_name: String,
+    targets: BTreeSet<u32>,
+}
+
+impl ProfilesWindow {
+    pub fn new() -> Self {
+        Self {
+            config: AppConfig::default(),
+            loaded: false,
+            selected: None,
+            new_name: String::new(),
+            targets: BTreeSet::new(),
+        }
+    }
+
+    /// Show the profile editor. Returns an action when a profile should be
+    /// applied to the checked games.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, games: &[GameInfo]) -> Option<Action> {
+        if !*open {
+            self.loaded = false;
+            return None;
+        }
+
+        if !self.loaded {
+            self.config = app_config::load();
+            self.loaded = true;
+        }
+
+        let mut action = None;
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("profiles"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Configuration Profiles");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_name)
+                            .hint_text("New profile name"),
+                    );
+                    let name = self.new_name.trim().to_string();
+                    if ui
+                        .add_enabled(
+                            !name.is_empty() && !self.config.profiles.contains_key(&name),
+                            egui::Button::new(format!("{} Add", regular::PLUS)),
+                        )
+                        .clicked()
+                    {
+                        self.config.profiles.insert(name.clone(), GameProfile::default());
+                        self.selected = Some(name);
+                        self.new_name.clear();
+                    }
+                });
+
+                ui.horizontal_wrapped(|ui| {
+                    for name in self.config.profiles.keys() {
+                        let is_selected = self.selected.as_ref() == Some(name);
+                        if ui.selectable_label(is_selected, name).clicked() {
+                            self.selected = Some(name.clone());
+                        }
+                    }
+                });
+                if self.config.profiles.is_empty() {
+                    ui.label("No profiles defined yet.");
+                }
+
+                let Some(name) = self.selected.clone() else {
+                    return;
+                };
+                let Some(profile) = self.config.profiles.get_mut(&name) else {
+                    self.selected = None;
+                    return;
+                };
+
+                ui.separator();
+                Self::profile_editor(ui, profile);
+                let profile = profile.clone();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Save Profiles").clicked() {
+                        if let Err(e) = app_config::save(&self.config) {
+                            tfd::message_box_ok(
+                                "Save failed",
+                                &format!("{}", e),
+                                tfd::MessageBoxIcon::Error,
+                            );
+                        }
+                    }
+                    if ui.button(format!("{} Delete Profile", regular::TRASH)).clicked() {
+                        self.config.profiles.remove(&name);
+                        self.selected = None;
+                    }
+                });
+
+                ui.separator();
+                ui.label(format!("Apply \"{}\" to:", name));
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for game in games {
+                            let mut checked = self.targets.contains(&game.app_id());
+                            if ui.checkbox(&mut checked, game.name()).changed() {
+                                if checked {
+                                    self.targets.insert(game.app_id());
+                                } else {
+                                    self.targets.remove(&game.app_id());
+                                }
+                            }
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    if ui.button("Select All").clicked() {
+                        self.targets = games.iter().map(|g| g.app_id()).collect();
+                    }
+                    if ui.button("Deselect All").clicked() {
+                        self.targets.clear();
+                    }
+                    let label = format!("Apply to {} game(s)", self.targets.len());
+                    if ui
+                        .add_enabled(!self.targets.is_empty(), egui::Button::new(label))
+                        .clicked()
+                        && super::confirm(
+                            "Confirm",
+                            &format!(
+                                "Apply profile \"{}\" to {} game(s)? Unsaved changes to the profile are applied as well.",
+                                name,
+                                self.targets.len()
+                            ),
+                            tfd::MessageBoxIcon::Question,
+                        )
+                    {
+                        action = Some(Action::ApplyProfile {
+                            name: name.clone(),
+                            profile,
+                            app_ids: self.targets.iter().copied().collect(),
+                        });
+                    }
+                });
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+        action
+    }
+
+    fn profile_editor(ui: &mut egui::Ui, profile: &mut GameProfile) {
+        egui::Grid::new("profile_editor")
+            .num_columns(2)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                let mut set = profile.launch_options.is_some();
+                ui.checkbox(&mut set, "Launch Options:");
+                if set != profile.launch_options.is_some() {
+                    profile.launch_options = set.then(String::new);
+                }
+                if let Some(opts) = &mut profile.launch_options {
+                    ui.add(
+                        egui::TextEdit::singleline(opts)
+                            .id_salt("profile_launch_options")
+                            .hint_text("e.g. PROTON_LOG=1 %command%"),
+                    );
+                }
+                ui.end_row();
+
+                let mut set = profile.compat_tool.is_some();
+                ui.checkbox(&mut set, "Proton Version:");
+                if set != profile.compat_tool.is_some() {
+                    profile.compat_tool = set.then(String::new);
+                }
+                if let Some(tool) = &mut profile.compat_tool {
+                    egui::ComboBox::from_id_salt("profile_proton")
+                        .selected_text(tool.clone())
+                        .show_ui(ui, |ui| {
+                            for v in GameDetails::list_proton_versions() {
+                                ui.selectable_value(tool, v.clone(), v);
+                            }
+                        });
+                }
+                ui.end_row();
+
+                let mut set = profile.cloud_sync.is_some();
+                ui.checkbox(&mut set, "Steam Cloud:");
+                if set != profile.cloud_sync.is_some() {
+                    profile.cloud_sync = set.then_some(true);
+                }
+                if let Some(cloud) = &mut profile.cloud_sync {
+                    ui.checkbox(cloud, "Enabled");
+                }
+                ui.end_row();
+
+                let mut set = profile.auto_update.is_some();
+                ui.checkbox(&mut set, "Auto-update:");
+                if set != profile.auto_update.is_some() {
+                    profile.auto_update = set.then(|| "0".to_string());
+                }
+                if let Some(auto) = &mut profile.auto_update {
+                    let mut enabled = auto.as_str() == "0";
+                    if ui.checkbox(&mut enabled, "Enabled").changed() {
+                        *auto = if enabled { "0" } else { "1" }.to_string();
+                    }
+                }
+                ui.end_row();
+            });
+        ui.label(
+            egui::RichText::new("Unchecked settings are left unchanged when the profile is applied.")
+                .small(),
+        );
+    }
+}
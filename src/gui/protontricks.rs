@@ -0,0 +1,247 @@
+use crate::utils::protontricks::{self, Verb};
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::collections::BTreeSet;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+enum Message {
+    Installed(Result<Vec<String>, String>),
+    Verbs(Result<Vec<Verb>, String>),
+    Output(String),
+    Finished(Result<(), String>),
+}
+
+/// Shows the winetricks verbs installed in a game's prefix and installs
+/// new ones with protontricks, or winetricks when protontricks is missing.
+pub struct ProtontricksWindow {
+    app_id: u32,
+    name: String,
+    installed: Option<Result<Vec<String>, String>>,
+    verbs: Option<Result<Vec<Verb>, String>>,
+    filter: String,
+    selected: BTreeSet<String>,
+    output: Vec<String>,
+    installing: bool,
+    rx: Option<Receiver<Message>>,
+}
+
+impl ProtontricksWindow {
+    pub fn new() -> Self {
+        Self {
+            app_id: 0,
+            name: String::new(),
+            installed: None,
+            verbs: None,
+            filter: String::new(),
+            selected: BTreeSet::new(),
+            output: Vec::new(),
+            installing: false,
+            rx: None,
+        }
+    }
+
+    /// Show the verbs of another game and start loading them.
+    pub fn open_for(&mut self, app_id: u32, name: &str) {
+        *self = Self::new();
+        self.app_id = app_id;
+        self.name = name.to_string();
+        let (tx, rx) = mpsc::channel();
+        let verbs_tx = tx.clone();
+        thread::spawn(move || {
+            let installed = protontricks::list_installed(app_id).map_err(|e| e.to_string());
+            let _ = tx.send(Message::Installed(installed));
+        });
+        thread::spawn(move || {
+            let verbs = protontricks::available_verbs(app_id).map_err(|e| e.to_string());
+            let _ = verbs_tx.send(Message::Verbs(verbs));
+        });
+        self.rx = Some(rx);
+    }
+
+    fn install(&mut self) {
+        let app_id = self.app_id;
+        let verbs: Vec<String> = self.selected.iter().cloned().collect();
+        self.output = vec![format!("$ winetricks -q {}", verbs.join(" "))];
+        self.installing = true;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (line_tx, line_rx) = mpsc::channel();
+            let forward = {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for line in line_rx {
+                        let _ = tx.send(Message::Output(line));
+                    }
+                })
+            };
+            let result = protontricks::install(app_id, &verbs, line_tx).map_err(|e| e.to_string());
+            let _ = forward.join();
+            let installed = protontricks::list_installed(app_id).map_err(|e| e.to_string());
+            let _ = tx.send(Message::Installed(installed));
+            let _ = tx.send(Message::Finished(result));
+        });
+        self.rx = Some(rx);
+    }
+
+    fn receive(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        while let Ok(message) = rx.try_recv() {
+            match message {
+                Message::Installed(result) => self.installed = Some(result),
+                Message::Verbs(result) => self.verbs = Some(result),
+                Message::Output(line) => self.output.push(line),
+                Message::Finished(result) => {
+                    self.installing = false;
+                    match result {
+                        Ok(()) => {
+                            self.output.push("✅ Done".to_string());
+                            self.selected.clear();
+                        }
+                        Err(e) => self.output.push(format!("❌ {}", e)),
+                    }
+                }
+            }
+        }
+        if self.installing || self.installed.is_none() || self.verbs.is_none() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !*open {
+            return;
+        }
+        self.receive(ctx);
+
+        let mut should_close = false;
+        let mut install = false;
+        let response = Modal::new(egui::Id::new("protontricks"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.set_width(600.0);
+                ui.horizontal(|ui| {
+                    ui.heading(format!("Protontricks: {}", self.name));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.add_enabled(!self.installing, egui::Button::new("Close")).clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                ui.strong("Installed");
+                match &self.installed {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Reading installed verbs...");
+                        });
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    Some(Ok(installed)) if installed.is_empty() => {
+                        ui.label("Nothing installed with winetricks yet.");
+                    }
+                    Some(Ok(installed)) => {
+                        ui.label(installed.join(", "));
+                    }
+                }
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    ui.strong("Available");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter)
+                            .hint_text(format!("{} Search verbs", regular::MAGNIFYING_GLASS)),
+                    );
+                });
+                let installed: BTreeSet<&str> = match &self.installed {
+                    Some(Ok(installed)) => installed.iter().map(String::as_str).collect(),
+                    _ => BTreeSet::new(),
+                };
+                match &self.verbs {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Loading verbs...");
+                        });
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    Some(Ok(verbs)) => {
+                        let filter = self.filter.to_lowercase();
+                        egui::ScrollArea::vertical()
+                            .id_salt("protontricks_verbs")
+                            .max_height(220.0)
+                            .show(ui, |ui| {
+                                for verb in verbs.iter().filter(|v| {
+                                    filter.is_empty()
+                                        || v.name.to_lowercase().contains(&filter)
+                                        || v.description.to_lowercase().contains(&filter)
+                                }) {
+                                    let is_installed = installed.contains(verb.name.as_str());
+                                    let mut checked = is_installed || self.selected.contains(&verb.name);
+                                    let label = format!("{} [{}]", verb.name, verb.category);
+                                    if ui
+                                        .add_enabled(!is_installed, egui::Checkbox::new(&mut checked, label))
+                                        .on_hover_text(&verb.description)
+                                        .changed()
+                                    {
+                                        if checked {
+                                            self.selected.insert(verb.name.clone());
+                                        } else {
+                                            self.selected.remove(&verb.name);
+                                        }
+                                    }
+                                }
+                            });
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    let label = if self.installing {
+                        "Installing...".to_string()
+                    } else {
+                        format!("{} Install {} verb(s)", regular::DOWNLOAD_SIMPLE, self.selected.len())
+                    };
+                    if ui
+                        .add_enabled(
+                            !self.installing && !self.selected.is_empty(),
+                            egui::Button::new(label),
+                        )
+                        .clicked()
+                    {
+                        install = true;
+                    }
+                    if self.installing {
+                        ui.spinner();
+                    }
+                });
+
+                if !self.output.is_empty() {
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .id_salt("protontricks_output")
+                        .max_height(160.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for line in &self.output {
+                                ui.monospace(line);
+                            }
+                        });
+                }
+            });
+
+        if install {
+            self.install();
+        }
+        if !self.installing && (response.should_close() || should_close) {
+            *open = false;
+        }
+    }
+}
@@ -1,7 +1,12 @@
-use crate::utils::runtime_cleaner::{delete_item, scan, RuntimeItem, ScanResults};
+use crate::utils::app_config;
+use crate::utils::output;
+use crate::utils::runtime_cleaner::{
+    delete_item, export_report, scan, RuntimeItem, ScanResults,
+};
 use eframe::egui::{self, Modal};
 use open;
 use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver};
 use std::thread;
 use tinyfiledialogs as tfd;
@@ -11,6 +16,8 @@ pub struct RuntimeCleanerWindow {
     loading: bool,
     rx: Option<Receiver<ScanResults>>,
     needs_refresh: bool,
+    /// The ignore list of the config, as of the last scan.
+    ignore: Vec<PathBuf>,
 }
 
 impl RuntimeCleanerWindow {
@@ -20,11 +27,13 @@ impl RuntimeCleanerWindow {
             loading: false,
             rx: None,
             needs_refresh: true,
+            ignore: Vec::new(),
         }
     }
 
     fn start_scan(&mut self) {
         self.loading = true;
+        self.ignore = app_config::load().cleaner.ignore;
         let (tx, rx) = mpsc::channel();
         thread::spawn(move || {
             let res = scan();
@@ -34,19 +43,14 @@ impl RuntimeCleanerWindow {
     }
 
     fn any_selected(&self) -> bool {
-        self.results.install_folders.iter().any(|i| i.selected)
-            || self.results.prefixes.iter().any(|i| i.selected)
-            || self.results.shader_caches.iter().any(|i| i.selected)
-            || self.results.tools.iter().any(|i| i.selected)
+        self.results
+            .groups()
+            .iter()
+            .any(|(_, items)| items.iter().any(|i| i.selected))
     }
 
     fn select_all(&mut self, val: bool) {
-        for list in [
-            &mut self.results.install_folders,
-            &mut self.results.prefixes,
-            &mut self.results.shader_caches,
-            &mut self.results.tools,
-        ] {
+        for (_, list) in self.results.groups_mut() {
             for item in list.iter_mut() {
                 item.selected = val;
             }
@@ -54,12 +58,7 @@ impl RuntimeCleanerWindow {
     }
 
     fn delete_selected(&mut self) {
-        for list in [
-            &mut self.results.install_folders,
-            &mut self.results.prefixes,
-            &mut self.results.shader_caches,
-            &mut self.results.tools,
-        ] {
+        for (_, list) in self.results.groups_mut() {
             let mut idx = 0;
             while idx < list.len() {
                 if list[idx].selected {
@@ -73,6 +72,51 @@ impl RuntimeCleanerWindow {
         }
     }
 
+    /// Add `path` to or remove it from the ignore list in the config.
+    fn set_ignored(&mut self, path: &Path, ignored: bool) {
+        let mut config = app_config::load();
+        if !config.cleaner.set_ignored(path, ignored) {
+            return;
+        }
+        if let Err(e) = app_config::save(&config) {
+            log::error!("Failed to save config: {}", e);
+            return;
+        }
+        self.ignore = config.cleaner.ignore;
+        if ignored {
+            for (_, list) in self.results.groups_mut() {
+                list.retain(|item| !item.path.starts_with(path));
+            }
+        } else {
+            // Whatever was hidden has to be found again.
+            self.needs_refresh = true;
+        }
+    }
+
+    /// Ask where to save a report of the scan results and write it.
+    fn export(&self) {
+        let Some(file) = tfd::save_file_dialog_with_filter(
+            "Export Report",
+            "runtime-cleaner.json",
+            &["*.json", "*.csv"],
+            "JSON or CSV report",
+        ) else {
+            return;
+        };
+        match export_report(&self.results, Path::new(&file)) {
+            Ok(()) => tfd::message_box_ok(
+                "Export Report",
+                &format!("Report written to {}", file),
+                tfd::MessageBoxIcon::Info,
+            ),
+            Err(e) => tfd::message_box_ok(
+                "Export failed",
+                &format!("{}", e),
+                tfd::MessageBoxIcon::Error,
+            ),
+        }
+    }
+
     pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
         if !*open {
             self.rx = None;
@@ -118,16 +162,21 @@ impl RuntimeCleanerWindow {
                         .add_enabled(self.any_selected(), egui::Button::new("Delete Selected"))
                         .clicked()
                     {
-                        if tfd::message_box_yes_no(
+                        if super::confirm(
                             "Confirm",
                             "Delete selected items?",
                             tfd::MessageBoxIcon::Warning,
-                            tfd::YesNo::No,
-                        ) == tfd::YesNo::Yes
-                        {
+                        ) {
                             self.delete_selected();
                         }
                     }
+                    if ui
+                        .add_enabled(!self.loading, egui::Button::new("Export Report"))
+                        .on_hover_text("Save everything found as JSON or CSV")
+                        .clicked()
+                    {
+                        self.export();
+                    }
                 });
 
                 ui.separator();
@@ -140,14 +189,37 @@ impl RuntimeCleanerWindow {
                     return;
                 }
 
-                Self::show_group(
-                    ui,
-                    "Orphaned Install Folders",
-                    &mut self.results.install_folders,
-                );
-                Self::show_group(ui, "Orphaned Proton Prefixes", &mut self.results.prefixes);
-                Self::show_group(ui, "Unused Shader Caches", &mut self.results.shader_caches);
-                Self::show_group(ui, "Broken Custom Proton Versions", &mut self.results.tools);
+                let mut ignore = None;
+                for (title, items) in self.results.groups_mut() {
+                    if let Some(path) = Self::show_group(ui, title, items) {
+                        ignore = Some(path);
+                    }
+                }
+                let mut unignore = None;
+                if !self.ignore.is_empty() {
+                    egui::CollapsingHeader::new(format!("Ignored ({})", self.ignore.len()))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            for path in &self.ignore {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .button(regular::EYE)
+                                        .on_hover_text("Stop Ignoring")
+                                        .clicked()
+                                    {
+                                        unignore = Some(path.clone());
+                                    }
+                                    ui.label(path.display().to_string());
+                                });
+                            }
+                        });
+                }
+                if let Some(path) = ignore {
+                    self.set_ignored(&path, true);
+                }
+                if let Some(path) = unignore {
+                    self.set_ignored(&path, false);
+                }
             });
 
         if response.should_close() || should_close {
@@ -155,8 +227,33 @@ impl RuntimeCleanerWindow {
         }
     }
 
-    fn show_group(ui: &mut egui::Ui, title: &str, items: &mut Vec<RuntimeItem>) {
-        egui::CollapsingHeader::new(title)
+    /// Why items of the group titled `title` may be marked unverified.
+    fn unverified_hint(title: &str) -> &'static str {
+        match title {
+            "Userdata of Uninstalled Games" => {
+                "Holds Steam Cloud files, which may be the only copy of the saves if cloud sync was off."
+            }
+            "Unused Custom Proton Versions" => {
+                "Steam's config.vdf could not be read, so games may still be set to use it."
+            }
+            _ => "Could not verify this is a valid Proton version.",
+        }
+    }
+
+    /// Show the items of a group. Returns the path the user chose to ignore.
+    fn show_group(
+        ui: &mut egui::Ui,
+        title: &str,
+        items: &mut [RuntimeItem],
+    ) -> Option<PathBuf> {
+        let mut ignore = None;
+        let sizes: Vec<u64> = items.iter().filter_map(|i| i.size).collect();
+        let header = if sizes.is_empty() {
+            title.to_string()
+        } else {
+            format!("{} ({})", title, output::format_size(sizes.iter().sum()))
+        };
+        egui::CollapsingHeader::new(header)
             .default_open(true)
             .show(ui, |ui| {
                 for item in items.iter_mut() {
@@ -169,6 +266,13 @@ impl RuntimeCleanerWindow {
                         {
                             let _ = open::that(&item.path);
                         }
+                        if ui
+                            .button(regular::EYE_SLASH)
+                            .on_hover_text("Ignore: leave out of future scans")
+                            .clicked()
+                        {
+                            ignore = Some(item.path.clone());
+                        }
                         if let Some(appid) = item.app_id {
                             if ui
                                 .button(regular::LINK)
@@ -184,12 +288,15 @@ impl RuntimeCleanerWindow {
                             item.path.display().to_string()
                         };
                         ui.label(lbl);
+                        if let Some(size) = item.size {
+                            ui.monospace(output::format_size(size));
+                        }
                         ui.label(egui::RichText::new(&item.reason).italics());
                         if !item.verified {
                             ui.label(
                                 egui::RichText::new("[unverified]").color(egui::Color32::YELLOW),
                             )
-                            .on_hover_text("Could not verify this is a valid Proton version.");
+                            .on_hover_text(Self::unverified_hint(title));
                         }
                     });
                 }
@@ -197,5 +304,6 @@ impl RuntimeCleanerWindow {
                     ui.label("None found");
                 }
             });
+        ignore
     }
 }
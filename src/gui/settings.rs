@@ -0,0 +1,321 @@
+use super::details::GameDetails;
+use crate::utils::app_config::{self, AppConfig, RemoteTransport};
+use crate::utils::{backup, user_config};
+use eframe::egui::{self, Modal};
+use std::path::{Path, PathBuf};
+use tinyfiledialogs as tfd;
+
+pub struct SettingsWindow {
+    config: AppConfig,
+    loaded: bool,
+    steam_root: String,
+    backup_root: String,
+    remote_target: String,
+    exclude: String,
+    default_tool: Option<String>,
+    pending_move: Option<(PathBuf, PathBuf)>,
+}
+
+impl SettingsWindow {
+    pub fn new() -> Self {
+        Self {
+            config: AppConfig::default(),
+            loaded: false,
+            steam_root: String::new(),
+            backup_root: String::new(),
+            remote_target: String::new(),
+            exclude: String::new(),
+            default_tool: None,
+            pending_move: None,
+        }
+    }
+
+    /// Show the settings dialog. Returns true when the settings were saved.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) -> bool {
+        if !*open {
+            self.loaded = false;
+            return false;
+        }
+
+        if !self.loaded {
+            self.config = app_config::load();
+            self.steam_root = self
+                .config
+                .general
+                .steam_root
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default();
+            self.backup_root = backup::backup_root().display().to_string();
+            self.remote_target = self.config.remote.target.clone().unwrap_or_default();
+            self.exclude = self
+                .config
+                .general
+                .exclude
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.default_tool = user_config::get_default_compat_tool();
+            self.loaded = true;
+        }
+
+        let mut saved = false;
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("settings"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.heading("Settings");
+                ui.separator();
+
+                egui::CollapsingHeader::new("General")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Steam Root:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.steam_root)
+                                    .hint_text("Detect automatically"),
+                            );
+                            if ui.button("Browse").clicked() {
+                                if let Some(dir) =
+                                    tfd::select_folder_dialog("Select Steam root", &self.steam_root)
+                                {
+                                    self.steam_root = dir;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Backup Location:");
+                            ui.text_edit_singleline(&mut self.backup_root);
+                            if ui.button("Browse").clicked() {
+                                if let Some(dir) = tfd::select_folder_dialog(
+                                    "Select backup location",
+                                    &self.backup_root,
+                                ) {
+                                    self.backup_root = dir;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Hidden AppIDs:");
+                            ui.text_edit_singleline(&mut self.exclude)
+                                .on_hover_text("Comma separated AppIDs left out of the game list");
+                        });
+                        ui.checkbox(
+                            &mut self.config.general.confirm,
+                            "Ask before deleting or overwriting data",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.checkbox(
+                                &mut self.config.snapshots.enabled,
+                                "Snapshot the prefix before restores and resets, kept for",
+                            )
+                            .on_hover_text(
+                                "Snapshots link to the prefix's files instead of copying them, so they take little time and space",
+                            );
+                            ui.add_enabled(
+                                self.config.snapshots.enabled,
+                                egui::DragValue::new(&mut self.config.snapshots.retention_days).range(1..=365),
+                            );
+                            ui.label("days");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(
+                                &mut self.config.health.enabled,
+                                "Check prefixes of games played in the last",
+                            )
+                            .on_hover_text(
+                                "Validates the prefixes in the background and shows a notice when one gets worse, e.g. a registry file truncated after a crash",
+                            );
+                            ui.add_enabled(
+                                self.config.health.enabled,
+                                egui::DragValue::new(&mut self.config.health.recent_days).range(1..=365),
+                            );
+                            ui.label("days every");
+                            ui.add_enabled(
+                                self.config.health.enabled,
+                                egui::DragValue::new(&mut self.config.health.interval_minutes).range(1..=1440),
+                            );
+                            ui.label("minutes");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Warn when a Steam library has less than");
+                            ui.add(egui::DragValue::new(&mut self.config.libraries.low_space_gb).range(0..=10000));
+                            ui.label("GiB free");
+                        });
+                    });
+
+                egui::CollapsingHeader::new("Remote Backups")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Target:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.remote_target)
+                                    .hint_text("user@host:/path"),
+                            )
+                            .on_hover_text(
+                                "Backups are uploaded over SSH using your SSH keys and config. Per-game targets can be set under [remote.games] in the config file",
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Transport:");
+                            let transport = &mut self.config.remote.transport;
+                            ui.radio_value(transport, RemoteTransport::Rsync, "rsync");
+                            ui.radio_value(transport, RemoteTransport::Sftp, "sftp");
+                        });
+                    });
+
+                egui::CollapsingHeader::new("Steam Settings")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Default Proton:");
+                            egui::ComboBox::from_id_salt("default_proton")
+                                .selected_text(
+                                    self.default_tool.as_deref().unwrap_or("Chosen by Steam"),
+                                )
+                                .show_ui(ui, |ui| {
+                                    for v in GameDetails::list_proton_versions() {
+                                        ui.selectable_value(
+                                            &mut self.default_tool,
+                                            Some(v.clone()),
+                                            v,
+                                        );
+                                    }
+                                });
+                        })
+                        .response
+                        .on_hover_text(
+                            "Used by every game without its own Proton version. Close Steam before saving, as it rewrites config.vdf on exit",
+                        );
+                    });
+
+                egui::CollapsingHeader::new("Artwork")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        let artwork = &mut self.config.artwork;
+                        ui.checkbox(
+                            &mut artwork.steamgriddb,
+                            "Download missing artwork from SteamGridDB",
+                        )
+                        .on_hover_text(
+                            "Games without Steam library art get a grid image from SteamGridDB",
+                        );
+                        ui.add_enabled_ui(artwork.steamgriddb, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("API Key:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut artwork.steamgriddb_api_key)
+                                        .password(true),
+                                );
+                            });
+                            ui.hyperlink_to(
+                                "Get an API key",
+                                "https://www.steamgriddb.com/profile/preferences/api",
+                            );
+                        });
+                    });
+
+                egui::CollapsingHeader::new("GE-Proton")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.checkbox(
+                            &mut self.config.proton_ge.check_updates,
+                            "Notify when a newer GE-Proton release is available",
+                        )
+                        .on_hover_text("Checks GitHub at most once a day");
+                    });
+
+                egui::CollapsingHeader::new("Other Launchers")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.checkbox(
+                            &mut self.config.heroic.enabled,
+                            "Show games installed with Heroic Games Launcher",
+                        )
+                        .on_hover_text("Lists Epic and GOG Wine prefixes in a separate section");
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        let remote_target = self.remote_target.trim();
+                        self.config.remote.target =
+                            (!remote_target.is_empty()).then(|| remote_target.to_string());
+                        let general = &mut self.config.general;
+                        let steam_root = self.steam_root.trim();
+                        general.steam_root =
+                            (!steam_root.is_empty()).then(|| PathBuf::from(steam_root));
+                        general.exclude = self
+                            .exclude
+                            .split(|c: char| c == ',' || c.is_whitespace())
+                            .filter_map(|id| id.parse().ok())
+                            .collect();
+                        let old_root = backup::backup_root();
+                        let backup_root = self.backup_root.trim();
+                        if backup_root.is_empty() {
+                            general.backup_root = None;
+                        } else if Path::new(backup_root) != old_root {
+                            general.backup_root = Some(PathBuf::from(backup_root));
+                        }
+                        if let Some(tool) = &self.default_tool {
+                            if user_config::get_default_compat_tool().as_ref() != Some(tool) {
+                                if let Err(e) = user_config::set_default_compat_tool(tool) {
+                                    tfd::message_box_ok(
+                                        "Save failed",
+                                        &format!("Failed to set the default Proton version: {}", e),
+                                        tfd::MessageBoxIcon::Error,
+                                    );
+                                }
+                            }
+                        }
+                        match app_config::save(&self.config) {
+                            Ok(_) => {
+                                self.config.general.apply();
+                                let new_root = backup::backup_root();
+                                if new_root != old_root
+                                    && old_root.exists()
+                                    && tfd::message_box_yes_no(
+                                        "Move Backups",
+                                        &format!(
+                                            "Move the existing backups from {} to {}?",
+                                            old_root.display(),
+                                            new_root.display()
+                                        ),
+                                        tfd::MessageBoxIcon::Question,
+                                        tfd::YesNo::Yes,
+                                    ) == tfd::YesNo::Yes
+                                {
+                                    self.pending_move = Some((old_root, new_root));
+                                }
+                                saved = true;
+                                should_close = true;
+                            }
+                            Err(e) => {
+                                tfd::message_box_ok(
+                                    "Save failed",
+                                    &format!("{}", e),
+                                    tfd::MessageBoxIcon::Error,
+                                );
+                            }
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        should_close = true;
+                    }
+                });
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+        saved
+    }
+
+    /// Backups to move after the backup location was changed, as (from, to).
+    pub fn take_pending_move(&mut self) -> Option<(PathBuf, PathBuf)> {
+        self.pending_move.take()
+    }
+}
@@ -1,5 +1,8 @@
 use crate::core::models::GameInfo;
+use crate::utils::disk_size;
+use crate::utils::prefix_info::{collect_prefix_info, PrefixInfo};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum GameSortKey {
@@ -11,8 +14,12 @@ pub enum GameSortKey {
     LastPlayed,
     /// Sort by Steam AppID
     AppId,
-    /// Sort by configured Proton version
+    /// Sort by the Proton version detected in the prefix
     ProtonVersion,
+    /// Sort by install size from the manifest
+    InstallSize,
+    /// Sort by measured prefix size
+    Size,
 }
 
 impl Default for GameSortKey {
@@ -22,6 +29,16 @@ impl Default for GameSortKey {
 }
 
 impl GameSortKey {
+    pub const ALL: [GameSortKey; 7] = [
+        GameSortKey::LastPlayed,
+        GameSortKey::LastUpdated,
+        GameSortKey::Name,
+        GameSortKey::AppId,
+        GameSortKey::ProtonVersion,
+        GameSortKey::InstallSize,
+        GameSortKey::Size,
+    ];
+
     pub fn label(&self) -> &'static str {
         match self {
             GameSortKey::Name => "Name",
@@ -29,6 +46,8 @@ impl GameSortKey {
             GameSortKey::LastPlayed => "Last Played",
             GameSortKey::AppId => "AppID",
             GameSortKey::ProtonVersion => "Proton Version",
+            GameSortKey::InstallSize => "Install Size",
+            GameSortKey::Size => "Prefix Size",
         }
     }
 }
@@ -39,7 +58,14 @@ pub fn compare_games(a: &GameInfo, b: &GameInfo, key: GameSortKey) -> Ordering {
         GameSortKey::LastUpdated => a.modified().cmp(&b.modified()),
         GameSortKey::LastPlayed => a.last_played().cmp(&b.last_played()),
         GameSortKey::AppId => a.app_id().cmp(&b.app_id()),
+        // Needs the prefix info; see `sort_games_by_version`.
         GameSortKey::ProtonVersion => Ordering::Equal,
+        GameSortKey::InstallSize => a.size_on_disk().cmp(&b.size_on_disk()),
+        // Prefixes not measured yet sort as the smallest and move once their
+        // size is known.
+        GameSortKey::Size => disk_size::cached(a.prefix_path())
+            .cmp(&disk_size::cached(b.prefix_path()))
+            .then_with(|| a.name().to_lowercase().cmp(&b.name().to_lowercase())),
     }
 }
 
@@ -49,3 +75,64 @@ pub fn sort_games(games: &mut [GameInfo], key: GameSortKey, descending: bool) {
         games.reverse();
     }
 }
+
+/// Proton version detected in the prefix of `game`, reading the prefix into
+/// `info` the first time it is needed.
+pub fn prefix_version<'a>(info: &'a mut HashMap<u32, PrefixInfo>, game: &GameInfo) -> Option<&'a str> {
+    info.entry(game.app_id())
+        .or_insert_with(|| collect_prefix_info(game.prefix_path()))
+        .version
+        .as_deref()
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum VersionPart {
+    Number(u64),
+    Text(String),
+}
+
+/// Split a version into runs of digits and other text so `Proton 10.0`
+/// sorts after `Proton 9.0`.
+fn version_key(version: &str) -> Vec<VersionPart> {
+    let mut parts = Vec::new();
+    let mut chars = version.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let digit = c.is_ascii_digit();
+        let mut run = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_digit() != digit {
+                break;
+            }
+            run.push(c);
+            chars.next();
+        }
+        parts.push(match run.parse() {
+            Ok(n) if digit => VersionPart::Number(n),
+            _ => VersionPart::Text(run.to_lowercase()),
+        });
+    }
+    parts
+}
+
+/// Order two detected Proton versions the way `sort_games_by_version` does.
+pub fn compare_versions(a: Option<&str>, b: Option<&str>) -> Ordering {
+    a.map(version_key).cmp(&b.map(version_key))
+}
+
+/// Sort by the Proton version detected in each prefix, with games whose
+/// version is unknown first, and by name within a version.
+pub fn sort_games_by_version(
+    games: &mut [GameInfo],
+    info: &mut HashMap<u32, PrefixInfo>,
+    descending: bool,
+) {
+    games.sort_by_cached_key(|g| {
+        (
+            prefix_version(info, g).map(version_key),
+            g.name().to_lowercase(),
+        )
+    });
+    if descending {
+        games.reverse();
+    }
+}
@@ -0,0 +1,206 @@
+use crate::core::models::GameInfo;
+use crate::utils::output;
+use crate::utils::prefix_junk::{self, JunkItem, PrefixJunk};
+use eframe::egui::{self, Modal};
+use egui_phosphor::regular;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use tinyfiledialogs as tfd;
+
+struct GameJunk {
+    name: String,
+    junk: PrefixJunk,
+    selected: Vec<bool>,
+}
+
+/// Reports reclaimable space inside every prefix and cleans it up.
+pub struct WastedSpaceWindow {
+    games: Vec<GameJunk>,
+    error: Option<String>,
+    loading: bool,
+    needs_refresh: bool,
+    rx: Option<Receiver<Result<Vec<PrefixJunk>, String>>>,
+}
+
+impl WastedSpaceWindow {
+    pub fn new() -> Self {
+        Self {
+            games: Vec::new(),
+            error: None,
+            loading: false,
+            needs_refresh: true,
+            rx: None,
+        }
+    }
+
+    fn start_scan(&mut self) {
+        self.loading = true;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(prefix_junk::scan_all().map_err(|e| e.to_string()));
+        });
+        self.rx = Some(rx);
+    }
+
+    fn selected_items(&self) -> Vec<JunkItem> {
+        self.games
+            .iter()
+            .flat_map(|g| {
+                g.junk
+                    .items
+                    .iter()
+                    .zip(&g.selected)
+                    .filter(|(_, selected)| **selected)
+                    .map(|(item, _)| item.clone())
+            })
+            .collect()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, games: Option<&[GameInfo]>) {
+        if !*open {
+            self.rx = None;
+            self.loading = false;
+            self.needs_refresh = true;
+            return;
+        }
+
+        if self.needs_refresh && !self.loading {
+            self.start_scan();
+        }
+
+        if let Some(rx) = &self.rx {
+            if let Ok(result) = rx.try_recv() {
+                let names: HashMap<u32, String> = games
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|g| (g.app_id(), g.name().to_string()))
+                    .collect();
+                match result {
+                    Ok(report) => {
+                        self.games = report
+                            .into_iter()
+                            .map(|junk| GameJunk {
+                                name: names
+                                    .get(&junk.app_id)
+                                    .cloned()
+                                    .unwrap_or_else(|| format!("App {}", junk.app_id)),
+                                selected: vec![true; junk.items.len()],
+                                junk,
+                            })
+                            .collect();
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                self.loading = false;
+                self.needs_refresh = false;
+                self.rx = None;
+            }
+        }
+
+        let mut should_close = false;
+        let response = Modal::new(egui::Id::new("wasted_space"))
+            .frame(egui::Frame::window(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Wasted Space");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Crash dumps, installer caches, temporary files and leftover redistributable installers. Known save locations are never included.",
+                    )
+                    .small(),
+                );
+
+                let selected = self.selected_items();
+                let selected_size: u64 = selected.iter().map(|i| i.size).sum();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !selected.is_empty() && !self.loading,
+                            egui::Button::new(format!(
+                                "{} Clean Selected ({})",
+                                regular::BROOM,
+                                output::format_size(selected_size)
+                            )),
+                        )
+                        .clicked()
+                        && super::confirm(
+                            "Confirm",
+                            "Delete the selected files?",
+                            tfd::MessageBoxIcon::Warning,
+                        ) {
+                        match prefix_junk::clean(&selected) {
+                            Ok(freed) => tfd::message_box_ok(
+                                "Wasted Space",
+                                &format!("Freed {}", output::format_size(freed)),
+                                tfd::MessageBoxIcon::Info,
+                            ),
+                            Err(e) => tfd::message_box_ok(
+                                "Cleanup failed",
+                                &format!("{}", e),
+                                tfd::MessageBoxIcon::Error,
+                            ),
+                        }
+                        self.needs_refresh = true;
+                    }
+                    if ui
+                        .add_enabled(!self.loading, egui::Button::new(format!("{} Rescan", regular::ARROW_CLOCKWISE)))
+                        .clicked()
+                    {
+                        self.needs_refresh = true;
+                    }
+                });
+                ui.separator();
+
+                if self.loading {
+                    ui.centered_and_justified(|ui| {
+                        ui.spinner();
+                        ui.label("Scanning...");
+                    });
+                    return;
+                }
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                    return;
+                }
+                if self.games.is_empty() {
+                    ui.label("No reclaimable space found.");
+                    return;
+                }
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for game in &mut self.games {
+                        egui::CollapsingHeader::new(format!(
+                            "{} — {}",
+                            game.name,
+                            output::format_size(game.junk.total())
+                        ))
+                        .id_salt(("wasted_space", game.junk.app_id))
+                        .show(ui, |ui| {
+                            for (item, selected) in game.junk.items.iter().zip(game.selected.iter_mut()) {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(selected, "");
+                                    ui.label(item.kind.to_string());
+                                    ui.monospace(output::format_size(item.size));
+                                    let rel = item.path.strip_prefix(&game.junk.prefix).unwrap_or(&item.path);
+                                    ui.label(egui::RichText::new(rel.display().to_string()).small())
+                                        .on_hover_text(item.path.display().to_string());
+                                });
+                            }
+                        });
+                    }
+                });
+            });
+
+        if response.should_close() || should_close {
+            *open = false;
+        }
+    }
+}
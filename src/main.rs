@@ -40,22 +40,53 @@ use clap::Parser;
 use eframe::{egui, NativeOptions};
 
 mod cli;
-mod core;
-mod error;
 mod gui;
+mod server;
+mod service;
 mod utils;
 
+use proton_prefix_manager_lib::{core, error};
+
 #[cfg(test)]
 mod test_helpers;
 
-use cli::{Cli, Commands};
+#[cfg(feature = "s3")]
+use cli::S3Action;
+use cli::{
+    Cli, Commands, ConfigAction, DefaultProtonAction, HeroicAction, RemoteAction, TagAction,
+};
 use gui::ProtonPrefixManagerApp;
+use utils::backup::ResetStep;
 use utils::logging;
 use utils::output::determine_format;
 
 fn main() {
     let cli = Cli::parse();
-    logging::init(cli.debug);
+    logging::init(cli.verbose, &cli.log, cli.log_format);
+    let mut general = utils::app_config::load().general;
+    if let Some(root) = &cli.backup_root {
+        general.backup_root = Some(root.clone());
+    }
+    if let Err(e) = general.override_steam_root(cli.steam_root.clone(), std::env::var_os("STEAM_ROOT")) {
+        eprintln!("❌ Error: {}", e);
+        std::process::exit(1);
+    }
+    general.apply();
+
+    if cli.schema {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&utils::json_model::schemas()).unwrap()
+        );
+        return;
+    }
+
+    if cli.service {
+        if let Err(e) = service::run() {
+            eprintln!("❌ Error: {}", e);
+        }
+        return;
+    }
 
     match &cli.command {
         Some(Commands::Search {
@@ -67,11 +98,33 @@ fn main() {
             let format = determine_format(*json, *plain, delimiter);
             cli::search::execute(name, &format);
         }
+        Some(Commands::List {
+            tag,
+            libraries,
+            json,
+            plain,
+            delimiter,
+        }) => {
+            let format = determine_format(*json, *plain, delimiter);
+            if *libraries {
+                cli::list::libraries(&format);
+            } else {
+                cli::list::execute(tag.as_deref(), &format);
+            }
+        }
+        Some(Commands::Prefix {
+            appid,
+            create: true,
+            ..
+        }) => {
+            cli::prefix::create(*appid);
+        }
         Some(Commands::Prefix {
             appid,
             json,
             plain,
             delimiter,
+            ..
         }) => {
             let format = determine_format(*json, *plain, delimiter);
             cli::prefix::execute(*appid, &format);
@@ -82,36 +135,237 @@ fn main() {
         Some(Commands::Userdata { appid }) => {
             cli::userdata::execute(*appid);
         }
-        Some(Commands::Backup { appid }) => {
-            cli::backup::execute(*appid);
+        Some(Commands::Backup {
+            appid,
+            saves_only,
+            shader_cache,
+            remote,
+        }) => {
+            cli::backup::execute(*appid, *saves_only, *shader_cache, *remote);
+        }
+        Some(Commands::Restore {
+            appid,
+            path,
+            delta,
+            only,
+        }) => {
+            cli::restore::execute(*appid, path.clone(), *delta, only);
+        }
+        Some(Commands::Validate { appid, strict }) => {
+            std::process::exit(cli::validate::execute(*appid, *strict));
+        }
+        Some(Commands::Repair {
+            appid,
+            broken_links,
+            dry_run,
+        }) => {
+            cli::repair::execute(*appid, *broken_links, *dry_run);
+        }
+        Some(Commands::ImportPrefix { appid, path }) => {
+            cli::import_prefix::execute(*appid, path);
         }
-        Some(Commands::Restore { appid, path }) => {
-            cli::restore::execute(*appid, path.clone());
+        Some(Commands::ListBackups { appid, sizes, .. }) => {
+            cli::list_backups::execute(*appid, *sizes);
         }
-        Some(Commands::ListBackups { appid }) => {
-            cli::list_backups::execute(*appid);
+        Some(Commands::History { appid }) => {
+            cli::history::execute(*appid);
         }
         Some(Commands::DeleteBackup { backup }) => {
             cli::delete_backup::execute(backup.clone());
         }
-        Some(Commands::Reset { appid }) => {
-            cli::reset::execute(*appid);
+        Some(Commands::MoveBackups { destination }) => {
+            cli::move_backups::execute(destination);
+        }
+        Some(Commands::Archive {
+            appid,
+            inactive,
+            dry_run,
+        }) => {
+            cli::archive::execute(*appid, *inactive, *dry_run);
+        }
+        Some(Commands::Unarchive { appid }) => {
+            cli::archive::unarchive(*appid);
+        }
+        Some(Commands::Pin { appid }) => {
+            cli::pin::execute(*appid);
+        }
+        Some(Commands::Unpin { appid }) => {
+            cli::pin::unpin(*appid);
+        }
+        Some(Commands::Remote {
+            action: RemoteAction::Push { appid, backup },
+        }) => {
+            cli::remote::push(*appid, backup.as_deref());
+        }
+        Some(Commands::Remote {
+            action: RemoteAction::List { appid },
+        }) => {
+            cli::remote::list(*appid);
+        }
+        Some(Commands::Remote {
+            action: RemoteAction::Pull { appid, name },
+        }) => {
+            cli::remote::pull(*appid, name);
+        }
+        #[cfg(feature = "s3")]
+        Some(Commands::S3 {
+            action: S3Action::Push { appid, backup },
+        }) => {
+            cli::s3::push(*appid, backup.as_deref());
+        }
+        #[cfg(feature = "s3")]
+        Some(Commands::S3 {
+            action: S3Action::List { appid },
+        }) => {
+            cli::s3::list(*appid);
+        }
+        #[cfg(feature = "s3")]
+        Some(Commands::S3 {
+            action: S3Action::Pull { appid, name },
+        }) => {
+            cli::s3::pull(*appid, name);
+        }
+        Some(Commands::Tag {
+            action: TagAction::Add { appid, tag },
+        }) => {
+            cli::tag::add(*appid, tag);
+        }
+        Some(Commands::Tag {
+            action: TagAction::Remove { appid, tag },
+        }) => {
+            cli::tag::remove(*appid, tag);
+        }
+        Some(Commands::Tag {
+            action: TagAction::List { appid },
+        }) => {
+            cli::tag::list(*appid);
+        }
+        Some(Commands::Relocate { appid, target, back }) => {
+            cli::relocate::execute(*appid, target.as_deref(), *back);
+        }
+        Some(Commands::MoveGame { appid, library }) => {
+            cli::move_game::execute(*appid, library);
+        }
+        Some(Commands::Reset {
+            appid,
+            keep_saves,
+            user_data,
+            registry,
+            software,
+        }) => {
+            let steps = [
+                (*user_data, ResetStep::UserData),
+                (*registry, ResetStep::Registry),
+                (*software, ResetStep::Software),
+            ]
+            .into_iter()
+            .filter_map(|(on, step)| on.then_some(step))
+            .collect::<Vec<_>>();
+            cli::reset::execute(*appid, *keep_saves, &steps);
+        }
+        Some(Commands::RestoreSaves) => {
+            cli::restore_saves::execute();
+        }
+        Some(Commands::BackupShadercache { appid }) => {
+            cli::backup_shadercache::execute(*appid);
+        }
+        Some(Commands::RestoreShadercache { appid, path }) => {
+            cli::restore_shadercache::execute(*appid, path.clone());
+        }
+        Some(Commands::Clean {
+            delete,
+            export,
+            ignore,
+            unignore,
+        }) => {
+            if ignore.is_empty() && unignore.is_empty() {
+                cli::clean::execute(*delete, export.as_deref());
+            } else {
+                cli::clean::set_ignored(ignore, unignore);
+            }
+        }
+        Some(Commands::WastedSpace { appid, clean }) => {
+            cli::wasted_space::execute(*appid, *clean);
+        }
+        Some(Commands::Serve { bind, port }) => {
+            cli::serve::execute(bind, *port);
+        }
+        Some(Commands::Doctor { library: true, json }) => {
+            cli::doctor::library(*json);
         }
-        Some(Commands::ClearCache { appid }) => {
-            cli::clear_cache::execute(*appid);
+        Some(Commands::Doctor { library: false, json }) => {
+            cli::doctor::environment(*json);
+        }
+        Some(Commands::Info { system: _, json }) => {
+            cli::info::system(*json);
+        }
+        Some(Commands::ClearCache {
+            appid,
+            dxvk,
+            fozpipelines,
+            mesa,
+            prefix_caches,
+            driver_caches,
+            sizes,
+        }) => {
+            if *sizes {
+                cli::clear_cache::sizes(*appid);
+            } else {
+                cli::clear_cache::execute(
+                    *appid,
+                    *dxvk,
+                    *fozpipelines,
+                    *mesa,
+                    *prefix_caches,
+                    *driver_caches,
+                );
+            }
         }
         Some(Commands::Protontricks { appid, args }) => {
             cli::protontricks::execute(*appid, args);
         }
+        Some(Commands::Winetricks { appid, verbs }) => {
+            cli::winetricks::execute(*appid, verbs);
+        }
         Some(Commands::Winecfg { appid }) => {
             cli::winecfg::execute(*appid);
         }
+        Some(Commands::Tool { appid, tool }) => {
+            cli::tool::execute(*appid, *tool);
+        }
         Some(Commands::Config {
-            appid,
+            action: Some(ConfigAction::Export { appid, file }),
+            ..
+        }) => {
+            cli::config::export(*appid, file);
+        }
+        Some(Commands::Config {
+            action: Some(ConfigAction::Import { appid, file }),
+            ..
+        }) => {
+            cli::config::import(*appid, file);
+        }
+        Some(Commands::Config {
+            action: Some(ConfigAction::Undo { appid }),
+            ..
+        }) => {
+            cli::config::undo(*appid);
+        }
+        Some(Commands::Config {
+            action: Some(ConfigAction::Rollback { target }),
+            ..
+        }) => {
+            cli::config::rollback(target);
+        }
+        Some(Commands::Config {
+            appid: Some(appid),
             launch,
             proton,
             cloud,
             auto_update,
+            overlay,
+            apply_profile,
+            ..
         }) => {
             cli::config::execute(
                 *appid,
@@ -119,11 +373,69 @@ fn main() {
                 proton.clone(),
                 *cloud,
                 auto_update.clone(),
+                *overlay,
+                apply_profile.clone(),
             );
         }
+        // clap requires an AppID unless a subcommand is given
+        Some(Commands::Config { appid: None, .. }) => {}
+        Some(Commands::Env { appid, set, unset }) => {
+            cli::env::execute(*appid, set, unset);
+        }
         Some(Commands::ConfigPaths) => {
             cli::config_paths::execute();
         }
+        Some(Commands::Saves { appid, refresh }) => {
+            cli::saves::execute(*appid, *refresh);
+        }
+        Some(Commands::Export {
+            appid,
+            target,
+            dest,
+            link,
+        }) => {
+            cli::export::execute(*appid, *target, dest.clone(), *link);
+        }
+        Some(Commands::DefaultProton {
+            action: DefaultProtonAction::Get,
+        }) => {
+            cli::default_proton::get();
+        }
+        Some(Commands::DefaultProton {
+            action: DefaultProtonAction::Set { version },
+        }) => {
+            cli::default_proton::set(version);
+        }
+        Some(Commands::Heroic {
+            action: HeroicAction::List,
+        }) => {
+            cli::heroic::list();
+        }
+        Some(Commands::Heroic {
+            action: HeroicAction::Backup { app_name },
+        }) => {
+            cli::heroic::backup(app_name);
+        }
+        Some(Commands::Heroic {
+            action: HeroicAction::Restore { app_name, path },
+        }) => {
+            cli::heroic::restore(app_name, path);
+        }
+        Some(Commands::Heroic {
+            action: HeroicAction::Validate { app_name, strict },
+        }) => {
+            std::process::exit(cli::heroic::validate(app_name, *strict));
+        }
+        Some(Commands::ProtonSettings {
+            version,
+            set,
+            unset,
+        }) => {
+            cli::proton_settings::execute(version.as_deref(), set, unset);
+        }
+        Some(Commands::ProtonGe { install }) => {
+            cli::proton_ge::execute(*install);
+        }
         None => {
             log::info!("Launching GUI...");
             let mut native_options = NativeOptions::default();
@@ -139,6 +451,7 @@ fn main() {
                 native_options,
                 Box::new(|cc| {
                     ProtonPrefixManagerApp::setup_fonts(&cc.egui_ctx);
+                    egui_extras::install_image_loaders(&cc.egui_ctx);
                     Ok(Box::new(ProtonPrefixManagerApp::new()))
                 }),
             )
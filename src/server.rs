@@ -0,0 +1,433 @@
+//! Local HTTP API started by `serve`.
+//!
+//! Every response is JSON following the models in `json_model`:
+//!
+//! - `GET /api/games` lists the installed games
+//! - `GET /api/games/<appid>` shows details of a game's prefix
+//! - `POST /api/games/<appid>/backup[?shader_cache=true]` starts a backup
+//! - `GET /api/tasks` and `GET /api/tasks/<id>` show background tasks
+//! - `GET /api/tasks/<id>/events` streams a task's progress as
+//!   server-sent events until it finishes
+//!
+//! Browsers let any page send requests to a local port, so requests whose
+//! `Origin` or `Host` is neither the local host nor the bind address are
+//! refused.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::{backup, hooks};
+use crate::utils::json_model::{
+    ErrorOutput, Game, GamesOutput, PrefixDetails, Task, TaskState, TasksOutput, Versioned,
+};
+use crate::utils::prefix_info;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// How often `/events` reports the state of a running task.
+const EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Finished tasks kept for `/api/tasks`; older ones are forgotten.
+const KEPT_FINISHED_TASKS: usize = 50;
+
+enum Reply {
+    Json(u16, String),
+    Events(u64),
+}
+
+fn json<T: Serialize>(status: u16, data: T) -> Reply {
+    Reply::Json(status, Versioned::new(data).to_json())
+}
+
+fn error(status: u16, message: impl Into<String>) -> Reply {
+    let code = match status {
+        400 => "invalid_request",
+        403 => "forbidden",
+        404 => "not_found",
+        _ => "internal",
+    };
+    json(
+        status,
+        ErrorOutput {
+            error: message.into(),
+            code: code.to_string(),
+            path: None,
+        },
+    )
+}
+
+fn failed(status: u16, e: &Error) -> Reply {
+    json(status, ErrorOutput::from(e))
+}
+
+/// The host of an `Origin` or `Host` header value or a bind address,
+/// without scheme, port or the brackets around an IPv6 address.
+fn host_name(value: &str) -> &str {
+    let rest = value.split_once("://").map_or(value, |(_, rest)| rest);
+    if rest.parse::<IpAddr>().is_ok() {
+        return rest;
+    }
+    match rest.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => rest.split(':').next().unwrap_or_default(),
+    }
+}
+
+/// Whether `host` names the local host or `bind`, the address the server
+/// listens on. Wildcard addresses like `0.0.0.0` are no name of the server,
+/// so with them only the local host is accepted.
+fn allowed_host(host: &str, bind: &str) -> bool {
+    let host = host_name(host);
+    if matches!(host.to_ascii_lowercase().as_str(), "localhost" | "127.0.0.1" | "::1") {
+        return true;
+    }
+    let bind = host_name(bind);
+    let wildcard = bind.parse::<IpAddr>().is_ok_and(|ip| ip.is_unspecified());
+    !host.is_empty() && !wildcard && host.eq_ignore_ascii_case(bind)
+}
+
+/// Whether a request's `Origin` header names the local host or the bind
+/// address. Requests from scripts and tools other than browsers have no
+/// `Origin` at all.
+fn allowed_origin(origin: &str, bind: &str) -> bool {
+    origin.contains("://") && allowed_host(origin, bind)
+}
+
+#[derive(Clone, Default)]
+struct Api {
+    /// The address given to `serve --bind`.
+    bind: String,
+    tasks: Arc<Mutex<Vec<Task>>>,
+    last_id: Arc<AtomicU64>,
+}
+
+impl Api {
+    fn task(&self, id: u64) -> Option<Task> {
+        self.tasks.lock().unwrap().iter().find(|t| t.id == id).cloned()
+    }
+
+    /// Add `task`, forgetting the oldest finished tasks beyond
+    /// [`KEPT_FINISHED_TASKS`].
+    fn add_task(&self, task: Task) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut finished = tasks.iter().filter(|t| t.state != TaskState::Running).count();
+        tasks.retain(|t| {
+            if t.state == TaskState::Running || finished < KEPT_FINISHED_TASKS {
+                return true;
+            }
+            finished -= 1;
+            false
+        });
+        tasks.push(task);
+    }
+
+    fn finish_task(&self, id: u64, result: Result<String>) {
+        if let Some(task) = self.tasks.lock().unwrap().iter_mut().find(|t| t.id == id) {
+            match result {
+                Ok(message) => {
+                    task.state = TaskState::Done;
+                    task.message = Some(message);
+                }
+                Err(e) => {
+                    task.state = TaskState::Failed;
+                    task.message = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    fn games(&self) -> Reply {
+        // The server outlives changes made by Steam.
+        steam::clear_caches();
+        match steam::get_steam_libraries().and_then(|libs| steam::load_games_from_libraries(&libs)) {
+            Ok(games) => json(
+                200,
+                GamesOutput {
+                    games: games.iter().map(Game::from).collect(),
+                },
+            ),
+            Err(e) => failed(500, &e),
+        }
+    }
+
+    fn game(&self, appid: u32) -> Reply {
+        let game = match steam::refresh_game_info(appid) {
+            Ok(game) => game,
+            Err(e) => return failed(404, &e),
+        };
+        let prefix = game.prefix_path();
+        let info = prefix_info::collect_prefix_info(prefix);
+        json(
+            200,
+            PrefixDetails {
+                appid,
+                name: game.name().to_string(),
+                prefix_path: game.prefix_exists().then(|| prefix.to_path_buf()),
+                proton_version: info.version,
+                dxvk: info.has_dxvk,
+                vkd3d: info.has_vkd3d,
+                problems: if game.prefix_exists() {
+                    prefix_info::validate_wine_prefix(&prefix.join("pfx"))
+                } else {
+                    Vec::new()
+                },
+            },
+        )
+    }
+
+    fn start_backup(&self, appid: u32, shader_cache: bool) -> Reply {
+        let prefix = match steam::get_steam_libraries()
+            .map(|libs| steam::find_proton_prefix(appid, &libs))
+        {
+            Ok(Some(prefix)) => prefix,
+            Ok(None) => return error(404, format!("Proton prefix not found for AppID: {}", appid)),
+            Err(e) => return failed(500, &e),
+        };
+        let task = Task {
+            id: self.last_id.fetch_add(1, Ordering::Relaxed) + 1,
+            description: format!("Back up prefix of {}", appid),
+            state: TaskState::Running,
+            message: None,
+        };
+        self.add_task(task.clone());
+        let api = self.clone();
+        let id = task.id;
+        thread::spawn(move || {
+            let result = hooks::backup(&prefix, || backup::create_backup(&prefix, appid, shader_cache))
+                .map(|path| path.display().to_string());
+            api.finish_task(id, result);
+        });
+        json(202, task)
+    }
+
+    fn route(&self, method: &Method, url: &str) -> Reply {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let appid = |s: &str| s.parse::<u32>().ok();
+        let task_id = |s: &str| s.parse::<u64>().ok();
+        match (method, segments.as_slice()) {
+            (Method::Get, ["api", "games"]) => self.games(),
+            (Method::Get, ["api", "games", id]) => match appid(id) {
+                Some(appid) => self.game(appid),
+                None => error(400, format!("Invalid AppID: {}", id)),
+            },
+            (Method::Post, ["api", "games", id, "backup"]) => match appid(id) {
+                Some(appid) => {
+                    let shader_cache = query
+                        .split('&')
+                        .any(|p| p == "shader_cache=true" || p == "shader_cache=1");
+                    self.start_backup(appid, shader_cache)
+                }
+                None => error(400, format!("Invalid AppID: {}", id)),
+            },
+            (Method::Get, ["api", "tasks"]) => json(
+                200,
+                TasksOutput {
+                    tasks: self.tasks.lock().unwrap().clone(),
+                },
+            ),
+            (Method::Get, ["api", "tasks", id]) => match task_id(id).and_then(|id| self.task(id)) {
+                Some(task) => json(200, task),
+                None => error(404, format!("No task {}", id)),
+            },
+            (Method::Get, ["api", "tasks", id, "events"]) => {
+                match task_id(id).filter(|id| self.task(*id).is_some()) {
+                    Some(id) => Reply::Events(id),
+                    None => error(404, format!("No task {}", id)),
+                }
+            }
+            _ => error(404, format!("No such endpoint: {} {}", method, path)),
+        }
+    }
+
+    /// Write the state of a task as server-sent events until it finishes.
+    fn stream_events(&self, out: &mut dyn Write, id: u64, interval: Duration) -> io::Result<()> {
+        loop {
+            let Some(task) = self.task(id) else {
+                return Ok(());
+            };
+            let event = match task.state {
+                TaskState::Running => "running",
+                TaskState::Done => "done",
+                TaskState::Failed => "failed",
+            };
+            let data = serde_json::to_string(&Versioned::new(&task)).unwrap();
+            write!(out, "event: {}\ndata: {}\n\n", event, data)?;
+            out.flush()?;
+            if task.state != TaskState::Running {
+                return Ok(());
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    fn respond(&self, request: Request) {
+        log::debug!("serve: {} {}", request.method(), request.url());
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv(name))
+                .map(|h| h.value.as_str().to_string())
+        };
+        // Checking the Host too stops pages whose name was rebound to a
+        // local address after loading, which then count as same-origin.
+        let refused = match (header("Host"), header("Origin")) {
+            (Some(host), _) if !allowed_host(&host, &self.bind) => {
+                Some(format!("Requests for {} are not allowed", host))
+            }
+            (_, Some(origin)) if !allowed_origin(&origin, &self.bind) => {
+                Some(format!("Requests from {} are not allowed", origin))
+            }
+            _ => None,
+        };
+        let reply = match refused {
+            Some(message) => {
+                log::warn!("serve: {}", message);
+                error(403, message)
+            }
+            None => self.route(request.method(), request.url()),
+        };
+        let result = match reply {
+            Reply::Json(status, body) => {
+                let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+                request.respond(Response::from_string(body).with_status_code(status).with_header(header))
+            }
+            Reply::Events(id) => {
+                // Written by hand so every event is flushed immediately.
+                let mut out = request.into_writer();
+                out.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+                )
+                .and_then(|_| self.stream_events(&mut out, id, EVENT_INTERVAL))
+            }
+        };
+        if let Err(e) = result {
+            log::debug!("serve: failed to send response: {}", e);
+        }
+    }
+}
+
+/// Serve the API on `bind:port` until the process is killed.
+pub fn run(bind: &str, port: u16) -> Result<()> {
+    let server = Server::http((bind, port)).map_err(|e| Error::Network(e.to_string()))?;
+    let api = Api {
+        bind: bind.to_string(),
+        ..Api::default()
+    };
+    for request in server.incoming_requests() {
+        let api = api.clone();
+        thread::spawn(move || api.respond(request));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use std::fs;
+
+    fn body(reply: Reply) -> (u16, serde_json::Value) {
+        match reply {
+            Reply::Json(status, body) => (status, serde_json::from_str(&body).unwrap()),
+            Reply::Events(_) => panic!("expected JSON"),
+        }
+    }
+
+    #[test]
+    fn test_api_routes() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, prefix, _) = setup_steam_env(6060, false);
+        fs::write(
+            home.path().join("library/steamapps/appmanifest_6060.acf"),
+            "\"AppState\" { \"appid\" \"6060\" \"name\" \"Served Game\" }",
+        )
+        .unwrap();
+        fs::write(prefix.join("version"), "9.0-1").unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let api = Api::default();
+        let (status, games) = body(api.route(&Method::Get, "/api/games"));
+        assert_eq!(status, 200);
+        assert_eq!(games["schema_version"], 1);
+        assert_eq!(games["games"][0]["name"], "Served Game");
+
+        let (status, game) = body(api.route(&Method::Get, "/api/games/6060"));
+        assert_eq!(status, 200);
+        assert_eq!(game["appid"], 6060);
+        assert!(!game["problems"].as_array().unwrap().is_empty());
+
+        assert_eq!(body(api.route(&Method::Get, "/api/games/abc")).0, 400);
+        assert_eq!(body(api.route(&Method::Get, "/api/nothing")).0, 404);
+        assert_eq!(body(api.route(&Method::Post, "/api/games/6061/backup")).0, 404);
+
+        let (status, task) = body(api.route(&Method::Post, "/api/games/6060/backup"));
+        assert_eq!(status, 202);
+        assert_eq!(task["state"], "running");
+        assert!(matches!(api.route(&Method::Get, "/api/tasks/1/events"), Reply::Events(1)));
+        let mut events = Vec::new();
+        api.stream_events(&mut events, 1, Duration::from_millis(10)).unwrap();
+        let events = String::from_utf8(events).unwrap();
+        assert!(events.contains("event: done\ndata: {"));
+        let (_, task) = body(api.route(&Method::Get, "/api/tasks/1"));
+        assert_eq!(task["state"], "done");
+        assert!(std::path::Path::new(task["message"].as_str().unwrap()).is_dir());
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_allowed_origin() {
+        assert!(allowed_origin("http://localhost:8080", "127.0.0.1"));
+        assert!(allowed_origin("http://127.0.0.1", "127.0.0.1"));
+        assert!(allowed_origin("http://[::1]:8080", "127.0.0.1"));
+        assert!(allowed_origin("http://deck.lan:8787", "deck.lan"));
+        assert!(allowed_origin("http://192.168.1.20:8787", "192.168.1.20"));
+        assert!(!allowed_origin("https://evil.example", "deck.lan"));
+        assert!(!allowed_origin("http://localhost.evil.example", "127.0.0.1"));
+        assert!(!allowed_origin("http://0.0.0.0:8787", "0.0.0.0"));
+        assert!(!allowed_origin("null", "127.0.0.1"));
+    }
+
+    #[test]
+    fn test_allowed_host() {
+        assert!(allowed_host("localhost:8787", "0.0.0.0"));
+        assert!(allowed_host("[::1]:8787", "::"));
+        assert!(allowed_host("deck.lan:8787", "deck.lan"));
+        assert!(allowed_host("[fe80::1]:8787", "fe80::1"));
+        // A rebound name reaches the server with a Host it does not know.
+        assert!(!allowed_host("evil.example:8787", "0.0.0.0"));
+        assert!(!allowed_host("evil.example:8787", "127.0.0.1"));
+        assert!(!allowed_host("0.0.0.0:8787", "0.0.0.0"));
+        assert!(!allowed_host("", ""));
+    }
+
+    #[test]
+    fn test_finished_tasks_are_pruned() {
+        let api = Api::default();
+        let task = |id, state| Task {
+            id,
+            description: String::new(),
+            state,
+            message: None,
+        };
+        api.add_task(task(1, TaskState::Running));
+        for id in 2..=KEPT_FINISHED_TASKS as u64 + 11 {
+            api.add_task(task(id, TaskState::Done));
+        }
+        let tasks = api.tasks.lock().unwrap();
+        assert_eq!(tasks.len(), KEPT_FINISHED_TASKS + 1);
+        assert_eq!(tasks[0].id, 1);
+        assert_eq!(tasks[1].id, 12);
+    }
+}
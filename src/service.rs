@@ -0,0 +1,187 @@
+//! Session D-Bus service exposing the core operations.
+//!
+//! Started with `--service`. Desktop extensions and applets can call the
+//! methods of [`INTERFACE`] on [`BUS_NAME`] instead of spawning the CLI for
+//! every action.
+//!
+//! With the `[health]` config section enabled, the service also checks the
+//! prefixes of recently played games every few minutes and emits a
+//! `PrefixRegressed(appid, name, problems)` signal for each one that got
+//! worse.
+
+use crate::core::steam;
+use crate::error::Error;
+use crate::utils::{app_config, backup, health_monitor, hooks, prefix_info};
+use std::path::PathBuf;
+use std::time::Duration;
+use zbus::fdo;
+use zbus::names::BusName;
+
+pub const BUS_NAME: &str = "io.github.D1G1T4L3CH0.ProtonPrefixManager";
+pub const INTERFACE: &str = "io.github.D1G1T4L3CH0.ProtonPrefixManager1";
+const OBJECT_PATH: &str = "/io/github/D1G1T4L3CH0/ProtonPrefixManager";
+
+fn failed(e: Error) -> fdo::Error {
+    fdo::Error::Failed(e.to_string())
+}
+
+fn prefix_of(appid: u32) -> fdo::Result<PathBuf> {
+    let libraries = steam::get_steam_libraries().map_err(failed)?;
+    steam::find_proton_prefix(appid, &libraries).ok_or_else(|| {
+        fdo::Error::FileNotFound(format!("Proton prefix not found for AppID: {}", appid))
+    })
+}
+
+struct Service;
+
+#[zbus::interface(name = "io.github.D1G1T4L3CH0.ProtonPrefixManager1")]
+impl Service {
+    /// Installed games as (App ID, name, prefix path). The path is empty
+    /// when the game has no prefix yet.
+    fn list_games(&self) -> fdo::Result<Vec<(u32, String, String)>> {
+        log::debug!("service: ListGames");
+        // The service outlives changes made by Steam.
+        steam::clear_caches();
+        let libraries = steam::get_steam_libraries().map_err(failed)?;
+        let games = steam::load_games_from_libraries(&libraries).map_err(failed)?;
+        Ok(games
+            .iter()
+            .map(|g| {
+                let prefix = if g.prefix_exists() {
+                    g.prefix_path().display().to_string()
+                } else {
+                    String::new()
+                };
+                (g.app_id(), g.name().to_string(), prefix)
+            })
+            .collect())
+    }
+
+    /// Back up the prefix of a game. Returns the backup path.
+    fn backup(&self, appid: u32, shader_cache: bool) -> fdo::Result<String> {
+        log::debug!(
+            "service: Backup appid={} shader_cache={}",
+            appid,
+            shader_cache
+        );
+        let prefix = prefix_of(appid)?;
+        hooks::backup(&prefix, || {
+            backup::create_backup(&prefix, appid, shader_cache)
+        })
+        .map(|p| p.display().to_string())
+        .map_err(failed)
+    }
+
+    /// Restore the prefix of a game from `backup_path`, or from its latest
+    /// backup when the path is empty. Returns the restored prefix path.
+    fn restore(&self, appid: u32, backup_path: &str) -> fdo::Result<String> {
+        log::debug!(
+            "service: Restore appid={} backup_path={}",
+            appid,
+            backup_path
+        );
+        let prefix = prefix_of(appid)?;
+        let backup_path = if backup_path.is_empty() {
+            backup::list_backups(appid).pop().ok_or_else(|| {
+                fdo::Error::FileNotFound(format!("No backups found for AppID: {}", appid))
+            })?
+        } else {
+            PathBuf::from(backup_path)
+        };
+        hooks::restore(&prefix, &backup_path, || {
+            backup::restore_prefix(&backup_path, &prefix)
+        })
+        .map(|p| p.display().to_string())
+        .map_err(failed)
+    }
+
+    /// Problems that keep Wine from starting in the prefix of a game. An
+    /// empty list means the prefix is valid.
+    fn validate(&self, appid: u32) -> fdo::Result<Vec<String>> {
+        log::debug!("service: Validate appid={}", appid);
+        let prefix = prefix_of(appid)?;
+        Ok(prefix_info::validate_wine_prefix(&prefix.join("pfx")))
+    }
+}
+
+/// Run the prefix health check if enabled and signal its regressions.
+/// Returns how long to wait before the next one.
+fn check_health(connection: &zbus::blocking::Connection) -> Duration {
+    let config = app_config::load().health;
+    if !config.enabled {
+        return Duration::from_secs(60);
+    }
+    match health_monitor::check_installed(&config) {
+        Ok(regressions) => {
+            for regression in regressions {
+                let body = (
+                    regression.appid,
+                    regression.name.as_str(),
+                    &regression.problems,
+                );
+                if let Err(e) = connection.emit_signal(
+                    None::<BusName<'_>>,
+                    OBJECT_PATH,
+                    INTERFACE,
+                    "PrefixRegressed",
+                    &body,
+                ) {
+                    log::warn!("Failed to signal regression of {}: {}", regression.appid, e);
+                }
+            }
+        }
+        Err(e) => log::warn!("Prefix health check failed: {}", e),
+    }
+    Duration::from_secs(60 * u64::from(config.interval_minutes.max(1)))
+}
+
+/// Claim [`BUS_NAME`] on the session bus and serve requests until the
+/// process is killed.
+pub fn run() -> zbus::Result<()> {
+    let connection = zbus::blocking::connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, Service)?
+        .build()?;
+    println!("🚌 Serving {} on the session bus", INTERFACE);
+    loop {
+        let wait = check_health(&connection);
+        std::thread::sleep(wait);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use std::fs;
+
+    #[test]
+    fn test_service_methods() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, prefix, _) = setup_steam_env(5050, false);
+        fs::write(
+            home.path().join("library/steamapps/appmanifest_5050.acf"),
+            "\"AppState\" { \"appid\" \"5050\" \"name\" \"Service Game\" }",
+        )
+        .unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let games = Service.list_games().unwrap();
+        assert_eq!(
+            games,
+            vec![(
+                5050,
+                "Service Game".to_string(),
+                prefix.display().to_string()
+            )]
+        );
+        assert!(!Service.validate(5050).unwrap().is_empty());
+        assert!(Service.validate(5051).is_err());
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
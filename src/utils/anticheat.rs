@@ -0,0 +1,149 @@
+//! Proton's EasyAntiCheat and BattlEye runtimes.
+//!
+//! Games shipping one of these anti-cheats only run under Proton when the
+//! matching runtime, a separate Steam tool, is installed.
+
+use crate::core::steam;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AntiCheat {
+    EasyAntiCheat,
+    BattlEye,
+}
+
+impl AntiCheat {
+    pub const ALL: [AntiCheat; 2] = [AntiCheat::EasyAntiCheat, AntiCheat::BattlEye];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AntiCheat::EasyAntiCheat => "EasyAntiCheat",
+            AntiCheat::BattlEye => "BattlEye",
+        }
+    }
+
+    /// AppID of the Steam tool providing the Proton runtime.
+    pub fn runtime_appid(&self) -> u32 {
+        match self {
+            AntiCheat::EasyAntiCheat => 1826330,
+            AntiCheat::BattlEye => 1161040,
+        }
+    }
+
+    pub fn runtime_name(&self) -> &'static str {
+        match self {
+            AntiCheat::EasyAntiCheat => "Proton EasyAntiCheat Runtime",
+            AntiCheat::BattlEye => "Proton BattlEye Runtime",
+        }
+    }
+
+    /// Whether a file or directory name in a game's install directory
+    /// belongs to this anti-cheat.
+    fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        match self {
+            AntiCheat::EasyAntiCheat => {
+                name.starts_with("easyanticheat") || name == "start_protected_game.exe"
+            }
+            AntiCheat::BattlEye => name == "battleye" || name.starts_with("beservice"),
+        }
+    }
+
+    /// Directories the anti-cheat's service installs into a prefix.
+    fn prefix_dirs(&self) -> &'static [&'static str] {
+        match self {
+            AntiCheat::EasyAntiCheat => &[
+                "drive_c/Program Files (x86)/EasyAntiCheat",
+                "drive_c/Program Files (x86)/EasyAntiCheat_EOS",
+            ],
+            AntiCheat::BattlEye => &["drive_c/Program Files (x86)/Common Files/BattlEye"],
+        }
+    }
+
+    /// Whether Steam has installed the Proton runtime.
+    pub fn runtime_installed(&self) -> bool {
+        steam::find_manifest(self.runtime_appid()).is_some()
+            || steam::get_steam_libraries().is_ok_and(|libs| {
+                libs.iter().any(|lib| {
+                    lib.steamapps_path()
+                        .join("common")
+                        .join(self.runtime_name())
+                        .is_dir()
+                })
+            })
+    }
+
+    pub fn install_hint(&self) -> String {
+        format!(
+            "Install \"{}\" from the Tools section of the Steam library, or open steam://install/{}",
+            self.runtime_name(),
+            self.runtime_appid()
+        )
+    }
+}
+
+/// Anti-cheats found in a game's install directory (up to a few levels
+/// deep) or in its Wine prefix (the `pfx` directory).
+pub fn detect(install_dir: Option<&Path>, pfx: &Path) -> Vec<AntiCheat> {
+    let names: Vec<String> = install_dir
+        .map(|dir| {
+            WalkDir::new(dir)
+                .min_depth(1)
+                .max_depth(3)
+                .into_iter()
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    AntiCheat::ALL
+        .into_iter()
+        .filter(|ac| {
+            names.iter().any(|n| ac.matches(n))
+                || ac.prefix_dirs().iter().any(|d| fs::metadata(pfx.join(d)).is_ok_and(|m| m.is_dir()))
+        })
+        .collect()
+}
+
+/// Anti-cheats a game uses, each with whether its runtime is installed.
+pub fn check(appid: u32, prefix: &Path) -> Vec<(AntiCheat, bool)> {
+    let install_dir = steam::find_install_dir(appid);
+    detect(install_dir.as_deref(), &prefix.join("pfx"))
+        .into_iter()
+        .map(|ac| (ac, ac.runtime_installed()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_check() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let appid = 7272;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let install = home.path().join("game");
+        fs::create_dir_all(install.join("Binaries/EasyAntiCheat")).unwrap();
+        let pfx = prefix.join("pfx");
+        assert_eq!(detect(Some(&install), &pfx), [AntiCheat::EasyAntiCheat]);
+        fs::create_dir_all(pfx.join("drive_c/Program Files (x86)/Common Files/BattlEye")).unwrap();
+        assert_eq!(detect(None, &pfx), [AntiCheat::BattlEye]);
+
+        assert!(!AntiCheat::BattlEye.runtime_installed());
+        fs::create_dir_all(home.path().join("library/steamapps/common/Proton BattlEye Runtime")).unwrap();
+        assert!(AntiCheat::BattlEye.runtime_installed());
+        assert_eq!(check(appid, &prefix), [(AntiCheat::BattlEye, true)]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -0,0 +1,771 @@
+//! Settings for proton-prefix-manager itself.
+//!
+//! Stored as TOML in `~/.config/proton-prefix-manager/config.toml`. Missing
+//! files or fields fall back to their defaults.
+
+use crate::error::{Error, Result};
+use crate::utils::overrides::{self, Overrides};
+use crate::utils::profile::GameProfile;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub general: GeneralConfig,
+    pub artwork: ArtworkConfig,
+    pub proton_ge: ProtonGeConfig,
+    pub heroic: HeroicConfig,
+    pub archive: ArchiveConfig,
+    pub snapshots: SnapshotConfig,
+    pub health: HealthConfig,
+    pub cleaner: CleanerConfig,
+    pub libraries: LibrariesConfig,
+    pub hooks: HooksConfig,
+    pub remote: RemoteConfig,
+    pub s3: S3Config,
+    /// Named configuration profiles that can be applied to any game.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, GameProfile>,
+    /// Settings managed for individual games, keyed by AppID.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub games: BTreeMap<String, GameSettings>,
+    /// Named filter sets of the GUI's advanced search.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub search_presets: BTreeMap<String, SearchPreset>,
+    /// User-defined tags of each game, keyed by AppID.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Filters of the advanced search saved under a name. Each flag is `true`
+/// to require it, `false` to require its absence and unset for either.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchPreset {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Text the game's notes must contain.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_manifest: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_prefix: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_update: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud_sync: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_launch: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_proton: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favorite: Option<bool>,
+    /// Prefix size range such as `> 5 GB`.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub prefix_size: String,
+    /// Install size range from the manifest, in the same format.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub install_size: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub detected_versions: Vec<String>,
+    /// Sort order by its label, such as `Last Played`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<String>,
+    pub descending: bool,
+}
+
+/// Settings the tool keeps for a game and writes into its launch options.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    /// Environment variables placed in front of `%command%`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// MangoHud overlay, enabled when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mangohud: Option<MangoHudSettings>,
+    /// Gamescope session wrapping the game, enabled when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gamescope: Option<GamescopeSettings>,
+    /// vkBasalt post-processing, enabled when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vkbasalt: Option<VkBasaltSettings>,
+}
+
+/// Options written to a generated MangoHud config file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MangoHudSettings {
+    /// Frame rate cap, 0 for none.
+    pub fps_limit: u32,
+    pub fps: bool,
+    pub frametime: bool,
+    pub frame_timing: bool,
+    pub cpu_stats: bool,
+    pub cpu_temp: bool,
+    pub gpu_stats: bool,
+    pub gpu_temp: bool,
+    pub ram: bool,
+    pub vram: bool,
+}
+
+impl Default for MangoHudSettings {
+    fn default() -> Self {
+        Self {
+            fps_limit: 0,
+            fps: true,
+            frametime: true,
+            frame_timing: true,
+            cpu_stats: true,
+            cpu_temp: false,
+            gpu_stats: true,
+            gpu_temp: false,
+            ram: false,
+            vram: false,
+        }
+    }
+}
+
+/// Effects written to a game's vkBasalt config file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VkBasaltSettings {
+    /// Contrast Adaptive Sharpening.
+    pub cas: bool,
+    pub cas_sharpness: f32,
+    /// Denoised Luma Sharpening.
+    pub dls: bool,
+    pub dls_sharpness: f32,
+    pub fxaa: bool,
+    pub smaa: bool,
+    /// Key that turns the effects on and off in game.
+    pub toggle_key: String,
+}
+
+impl Default for VkBasaltSettings {
+    fn default() -> Self {
+        Self {
+            cas: true,
+            cas_sharpness: 0.4,
+            dls: false,
+            dls_sharpness: 0.5,
+            fxaa: false,
+            smaa: false,
+            toggle_key: "Home".to_string(),
+        }
+    }
+}
+
+/// Options passed to gamescope. Zero sizes leave the choice to gamescope.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamescopeSettings {
+    /// Resolution the game renders at.
+    pub width: u32,
+    pub height: u32,
+    /// Resolution of the gamescope window.
+    pub output_width: u32,
+    pub output_height: u32,
+    pub upscaler: Upscaler,
+    /// Frame rate cap, 0 for none.
+    pub fps_limit: u32,
+    pub fullscreen: bool,
+    pub hdr: bool,
+}
+
+impl Default for GamescopeSettings {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            output_width: 0,
+            output_height: 0,
+            upscaler: Upscaler::default(),
+            fps_limit: 0,
+            fullscreen: true,
+            hdr: false,
+        }
+    }
+}
+
+/// Upscaling filter used by gamescope when the game renders below the
+/// output resolution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Upscaler {
+    #[default]
+    Linear,
+    Nearest,
+    Fsr,
+    Nis,
+    Pixel,
+}
+
+impl Upscaler {
+    pub const ALL: [Upscaler; 5] = [
+        Upscaler::Linear,
+        Upscaler::Nearest,
+        Upscaler::Fsr,
+        Upscaler::Nis,
+        Upscaler::Pixel,
+    ];
+
+    /// Value of gamescope's `--filter` option.
+    pub fn arg(self) -> &'static str {
+        match self {
+            Upscaler::Linear => "linear",
+            Upscaler::Nearest => "nearest",
+            Upscaler::Fsr => "fsr",
+            Upscaler::Nis => "nis",
+            Upscaler::Pixel => "pixel",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Upscaler::Linear => "Linear",
+            Upscaler::Nearest => "Nearest neighbor",
+            Upscaler::Fsr => "AMD FSR",
+            Upscaler::Nis => "NVIDIA Image Scaling",
+            Upscaler::Pixel => "Pixel art",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneralConfig {
+    /// Where backups are stored instead of the XDG data directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_root: Option<PathBuf>,
+    /// Steam installation to use instead of searching the usual places.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steam_root: Option<PathBuf>,
+    /// Output format of `search` and `prefix` when no flag is given.
+    pub output_format: OutputFormatConfig,
+    /// AppIDs hidden from game lists and skipped when archiving.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<u32>,
+    /// AppIDs pinned to the top of the GUI's game list.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub favorites: Vec<u32>,
+    /// Ask before deleting or overwriting data in the GUI.
+    pub confirm: bool,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            backup_root: None,
+            steam_root: None,
+            output_format: OutputFormatConfig::default(),
+            exclude: Vec::new(),
+            favorites: Vec::new(),
+            confirm: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormatConfig {
+    #[default]
+    Normal,
+    Plain,
+    Json,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtworkConfig {
+    /// Download grid images from SteamGridDB for games without local art.
+    pub steamgriddb: bool,
+    /// Personal SteamGridDB API key.
+    pub steamgriddb_api_key: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtonGeConfig {
+    /// Periodically check GitHub for newer GE-Proton releases.
+    pub check_updates: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeroicConfig {
+    /// Also list Epic and GOG games installed with Heroic Games Launcher.
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Months without playing after which a prefix is offered for archiving.
+    pub inactive_months: u32,
+}
+
+/// Safety snapshots taken before restores and resets. See
+/// `utils::snapshot`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    pub enabled: bool,
+    /// Days a snapshot is kept before it is deleted.
+    pub retention_days: u32,
+}
+
+/// Background checks of the prefixes of recently played games. See
+/// `utils::health_monitor`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub enabled: bool,
+    /// Minutes between two checks.
+    pub interval_minutes: u32,
+    /// Only games played within this many days are checked.
+    pub recent_days: u32,
+}
+
+/// Settings of the runtime cleaner. See `utils::runtime_cleaner`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanerConfig {
+    /// Paths left out of scans, together with everything below them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<PathBuf>,
+}
+
+/// Free space monitoring of the Steam libraries. See
+/// `utils::library_overview`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibrariesConfig {
+    /// Warn when a library has less than this many GiB free.
+    pub low_space_gb: u32,
+}
+
+/// Shell commands run before and after prefix operations. See
+/// `utils::hooks` for the environment they receive.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_backup: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_backup: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_restore: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_restore: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_reset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_reset: Option<String>,
+}
+
+/// Where backups are copied to on another machine.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    /// Destination of every game's backups, `user@host:/path`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Program used to copy backups to and from the host.
+    pub transport: RemoteTransport,
+    /// Destinations of individual games, keyed by AppID, used instead of
+    /// `target`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub games: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteTransport {
+    /// `rsync` over SSH, which resumes interrupted copies.
+    #[default]
+    Rsync,
+    /// `sftp`, for hosts without rsync or a shell.
+    Sftp,
+}
+
+/// Bucket of S3-compatible object storage holding compressed backups, used
+/// by builds with the `s3` feature.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    /// Endpoint URL, e.g. `https://s3.eu-central-1.amazonaws.com` or the
+    /// address of a MinIO server. Buckets are addressed path-style.
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    /// Key prefix of the backups in the bucket.
+    pub prefix: String,
+    /// Access key, `AWS_ACCESS_KEY_ID` when empty.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub access_key: String,
+    /// Secret key, `AWS_SECRET_ACCESS_KEY` when empty.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub secret_key: String,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            prefix: "proton-prefix-manager".to_string(),
+            access_key: String::new(),
+            secret_key: String::new(),
+        }
+    }
+}
+
+impl CleanerConfig {
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.iter().any(|p| path.starts_with(p))
+    }
+
+    /// Add `path` to or remove it from the ignore list, returning whether
+    /// anything changed.
+    pub fn set_ignored(&mut self, path: &Path, ignored: bool) -> bool {
+        if ignored == self.ignore.iter().any(|p| p == path) {
+            return false;
+        }
+        if ignored {
+            self.ignore.push(path.to_path_buf());
+            self.ignore.sort();
+        } else {
+            self.ignore.retain(|p| p != path);
+        }
+        true
+    }
+}
+
+impl RemoteConfig {
+    /// Destination of the backups of `appid`, if one is configured.
+    pub fn target_for(&self, appid: u32) -> Option<&str> {
+        self.games
+            .get(&appid.to_string())
+            .or(self.target.as_ref())
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+    }
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self { inactive_months: 6 }
+    }
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 7,
+        }
+    }
+}
+
+impl Default for LibrariesConfig {
+    fn default() -> Self {
+        Self { low_space_gb: 20 }
+    }
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 30,
+            recent_days: 14,
+        }
+    }
+}
+
+impl Default for ProtonGeConfig {
+    fn default() -> Self {
+        Self {
+            check_updates: true,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Managed settings of a game, or defaults if there are none.
+    pub fn game(&self, appid: u32) -> GameSettings {
+        self.games.get(&appid.to_string()).cloned().unwrap_or_default()
+    }
+
+    /// Tags of a game, empty if it has none.
+    pub fn tags_of(&self, appid: u32) -> BTreeSet<String> {
+        self.tags.get(&appid.to_string()).cloned().unwrap_or_default()
+    }
+
+    /// Tag a game, returning whether it was not tagged so before.
+    pub fn add_tag(&mut self, appid: u32, tag: &str) -> bool {
+        let tag = tag.trim();
+        !tag.is_empty()
+            && self
+                .tags
+                .entry(appid.to_string())
+                .or_default()
+                .insert(tag.to_string())
+    }
+
+    /// Remove a tag from a game, returning whether it had it.
+    pub fn remove_tag(&mut self, appid: u32, tag: &str) -> bool {
+        let key = appid.to_string();
+        let Some(tags) = self.tags.get_mut(&key) else {
+            return false;
+        };
+        let removed = tags.remove(tag.trim());
+        if tags.is_empty() {
+            self.tags.remove(&key);
+        }
+        removed
+    }
+
+    /// Every tag used by any game.
+    pub fn all_tags(&self) -> BTreeSet<String> {
+        self.tags.values().flatten().cloned().collect()
+    }
+
+    /// AppIDs of the games tagged with `tag`.
+    pub fn tagged(&self, tag: &str) -> Vec<u32> {
+        self.tags
+            .iter()
+            .filter(|(_, tags)| tags.contains(tag))
+            .filter_map(|(appid, _)| appid.parse().ok())
+            .collect()
+    }
+
+    /// Store the managed settings of a game, dropping the entry when empty.
+    pub fn set_game(&mut self, appid: u32, settings: GameSettings) {
+        if settings == GameSettings::default() {
+            self.games.remove(&appid.to_string());
+        } else {
+            self.games.insert(appid.to_string(), settings);
+        }
+    }
+}
+
+impl GeneralConfig {
+    pub fn is_excluded(&self, appid: u32) -> bool {
+        self.exclude.contains(&appid)
+    }
+
+    pub fn is_favorite(&self, appid: u32) -> bool {
+        self.favorites.contains(&appid)
+    }
+
+    /// Pin or unpin a game, returning whether anything changed.
+    pub fn set_favorite(&mut self, appid: u32, favorite: bool) -> bool {
+        if favorite == self.is_favorite(appid) {
+            return false;
+        }
+        if favorite {
+            self.favorites.push(appid);
+            self.favorites.sort_unstable();
+        } else {
+            self.favorites.retain(|&id| id != appid);
+        }
+        true
+    }
+
+    /// Use the Steam root given for this run: `flag` wins over the
+    /// `STEAM_ROOT` value `env`, and both over the config file. The root
+    /// must be a directory, as falling back to the detected Steam would act
+    /// on another installation than the one asked for.
+    pub fn override_steam_root(&mut self, flag: Option<PathBuf>, env: Option<OsString>) -> Result<()> {
+        let root = flag.or_else(|| env.filter(|root| !root.is_empty()).map(PathBuf::from));
+        if let Some(root) = root {
+            if !root.is_dir() {
+                return Err(Error::FileSystemError(format!(
+                    "Steam root {} is not a directory",
+                    root.display()
+                )));
+            }
+            self.steam_root = Some(root);
+        }
+        Ok(())
+    }
+
+    /// Hand the configured locations to the Steam and backup code.
+    pub fn apply(&self) {
+        overrides::set(Overrides {
+            backup_root: self.backup_root.clone(),
+            steam_root: self.steam_root.clone(),
+        });
+    }
+}
+
+impl ArtworkConfig {
+    /// Return the API key if SteamGridDB downloads are enabled and configured.
+    pub fn steamgriddb_key(&self) -> Option<&str> {
+        let key = self.steamgriddb_api_key.trim();
+        (self.steamgriddb && !key.is_empty()).then_some(key)
+    }
+}
+
+/// Location of the configuration file.
+pub fn config_path() -> PathBuf {
+    dirs_next::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+        .join("config.toml")
+}
+
+/// Load the configuration, falling back to defaults on any error.
+pub fn load() -> AppConfig {
+    let path = config_path();
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return AppConfig::default();
+    };
+    match toml::from_str(&contents) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            log::warn!("Ignoring invalid config {}: {}", path.display(), e);
+            AppConfig::default()
+        }
+    }
+}
+
+/// Write the configuration to disk.
+pub fn save(config: &AppConfig) -> Result<()> {
+    let path = config_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = toml::to_string_pretty(config).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let cfg: AppConfig = toml::from_str("[artwork]\nsteamgriddb = true\n").unwrap();
+        assert!(cfg.artwork.steamgriddb);
+        assert_eq!(cfg.artwork.steamgriddb_key(), None);
+
+        let cfg: AppConfig =
+            toml::from_str("[artwork]\nsteamgriddb = true\nsteamgriddb_api_key = \" abc \"\n")
+                .unwrap();
+        assert_eq!(cfg.artwork.steamgriddb_key(), Some("abc"));
+    }
+
+    #[test]
+    fn test_general_section() {
+        let cfg: AppConfig = toml::from_str("").unwrap();
+        assert!(cfg.general.confirm);
+        assert_eq!(cfg.general.output_format, OutputFormatConfig::Normal);
+
+        let cfg: AppConfig = toml::from_str(
+            "[general]\nsteam_root = \"/opt/steam\"\noutput_format = \"json\"\nexclude = [620, 228980]\nconfirm = false\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.general.steam_root, Some(PathBuf::from("/opt/steam")));
+        assert_eq!(cfg.general.output_format, OutputFormatConfig::Json);
+        assert!(cfg.general.is_excluded(228980));
+        assert!(!cfg.general.is_excluded(440));
+        assert!(!cfg.general.confirm);
+    }
+
+    #[test]
+    fn test_override_steam_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let flag = dir.path().join("flag");
+        let env = dir.path().join("env");
+        fs::create_dir_all(&flag).unwrap();
+        fs::create_dir_all(&env).unwrap();
+        let configured = Some(PathBuf::from("/opt/steam"));
+        let general = || GeneralConfig {
+            steam_root: configured.clone(),
+            ..GeneralConfig::default()
+        };
+
+        let mut cfg = general();
+        cfg.override_steam_root(Some(flag.clone()), Some(env.clone().into())).unwrap();
+        assert_eq!(cfg.steam_root, Some(flag));
+        let mut cfg = general();
+        cfg.override_steam_root(None, Some(env.clone().into())).unwrap();
+        assert_eq!(cfg.steam_root, Some(env.clone()));
+        let mut cfg = general();
+        cfg.override_steam_root(None, Some(OsString::new())).unwrap();
+        assert_eq!(cfg.steam_root, configured);
+
+        // A missing root is an error, not a reason to search elsewhere.
+        let mut cfg = general();
+        let missing = dir.path().join("missing");
+        assert!(cfg.override_steam_root(Some(missing), Some(env.into())).is_err());
+        assert_eq!(cfg.steam_root, configured);
+    }
+
+    #[test]
+    fn test_profiles_roundtrip() {
+        let mut cfg = AppConfig::default();
+        cfg.profiles.insert(
+            "Streaming".to_string(),
+            GameProfile {
+                launch_options: Some("gamemoderun %command%".to_string()),
+                compat_tool: Some("GE-Proton9-20".to_string()),
+                ..Default::default()
+            },
+        );
+        let contents = toml::to_string_pretty(&cfg).unwrap();
+        assert!(contents.contains("[profiles.Streaming]"));
+        assert_eq!(toml::from_str::<AppConfig>(&contents).unwrap(), cfg);
+    }
+
+    #[test]
+    fn test_search_presets_roundtrip() {
+        let mut cfg = AppConfig::default();
+        cfg.search_presets.insert(
+            "Unplayed with custom Proton".to_string(),
+            SearchPreset {
+                custom_proton: Some(true),
+                prefix_size: "> 5 GB".to_string(),
+                sort_by: Some("Last Played".to_string()),
+                ..Default::default()
+            },
+        );
+        let contents = toml::to_string_pretty(&cfg).unwrap();
+        assert!(contents.contains("[search_presets.\"Unplayed with custom Proton\"]"));
+        assert!(!contents.contains("has_manifest"));
+        assert_eq!(toml::from_str::<AppConfig>(&contents).unwrap(), cfg);
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut cfg = AppConfig::default();
+        assert!(cfg.add_tag(620, "modded"));
+        assert!(cfg.add_tag(620, " needs GE "));
+        assert!(!cfg.add_tag(620, "modded"));
+        assert!(!cfg.add_tag(620, " "));
+        assert!(cfg.add_tag(400, "modded"));
+        assert_eq!(cfg.tagged("modded"), [400, 620]);
+        assert_eq!(cfg.all_tags().len(), 2);
+
+        let contents = toml::to_string_pretty(&cfg).unwrap();
+        assert!(contents.contains("620 = [\n    \"modded\",\n    \"needs GE\",\n]"));
+        assert_eq!(toml::from_str::<AppConfig>(&contents).unwrap(), cfg);
+
+        assert!(cfg.remove_tag(400, "modded"));
+        assert!(!cfg.remove_tag(400, "modded"));
+        assert!(cfg.tags_of(400).is_empty());
+        assert!(!cfg.tags.contains_key("400"));
+    }
+}
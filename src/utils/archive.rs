@@ -0,0 +1,225 @@
+//! Archiving prefixes of games that have not been played in a while.
+//!
+//! An archived prefix is stored as a compressed tarball in the backup store
+//! and the live copy is deleted. Unarchiving unpacks it to its old place.
+
+use crate::core::models::GameInfo;
+use crate::error::{Error, Result};
+use crate::utils::{backup, logging, prefix_relocate};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Seconds in the 30-day months used for the inactivity threshold.
+const MONTH_SECS: u64 = 30 * 24 * 60 * 60;
+
+fn archive_root() -> PathBuf {
+    backup::backup_root().join("archived")
+}
+
+/// Location of the archive of a game's prefix.
+pub fn archive_path(appid: u32) -> PathBuf {
+    archive_root().join(format!("{}.tar.gz", appid))
+}
+
+pub fn is_archived(appid: u32) -> bool {
+    archive_path(appid).is_file()
+}
+
+/// Games with a prefix that were last played more than `months` months
+/// before `now`. Games that were never played and relocated prefixes are
+/// left alone.
+pub fn inactive_games(games: &[GameInfo], months: u32, now: u64) -> Vec<&GameInfo> {
+    let cutoff = now.saturating_sub(u64::from(months) * MONTH_SECS);
+    games
+        .iter()
+        .filter(|g| g.last_played() > 0 && g.last_played() < cutoff)
+        .filter(|g| g.prefix_path().is_dir())
+        .filter(|g| prefix_relocate::relocated_target(g.prefix_path()).is_none())
+        .collect()
+}
+
+/// Write `prefix` as a gzip-compressed tarball to `dest`.
+pub(crate) fn write_archive(prefix: &Path, dest: &Path) -> Result<()> {
+    let encoder = GzEncoder::new(File::create(dest)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    builder.append_dir_all(".", prefix)?;
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// A file, directory or symlink stored in an archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path relative to the archived directory, without a leading `./`.
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// List the contents of an archive without unpacking it.
+pub fn list_archive(archive: &Path) -> Result<Vec<ArchiveEntry>> {
+    let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut entries = Vec::new();
+    for entry in tar.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        if path.is_empty() || path == "." {
+            continue;
+        }
+        entries.push(ArchiveEntry {
+            path: path.to_string(),
+            is_dir: entry.header().entry_type().is_dir(),
+            size: entry.header().size()?,
+        });
+    }
+    Ok(entries)
+}
+
+/// Number of entries in an archive, reading it completely.
+fn count_entries(archive: &Path) -> Result<usize> {
+    let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut count = 0;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        std::io::copy(&mut entry, &mut std::io::sink())?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Compress a prefix into the backup store and delete the live copy.
+///
+/// Returns the path of the archive.
+pub fn archive_prefix(appid: u32, prefix: &Path) -> Result<PathBuf> {
+    logging::operation("archive", Some(appid), prefix, || {
+        if !prefix.is_dir() || prefix_relocate::relocated_target(prefix).is_some() {
+            return Err(Error::FileSystemError(format!(
+                "No prefix to archive at {}",
+                prefix.display()
+            )));
+        }
+        let dest = archive_path(appid);
+        if dest.exists() {
+            return Err(Error::FileSystemError(format!(
+                "{} is already archived",
+                appid
+            )));
+        }
+        fs::create_dir_all(archive_root())?;
+        let partial = dest.with_extension("partial");
+        let verified = write_archive(prefix, &partial).and_then(|_| {
+            let expected = WalkDir::new(prefix).into_iter().count();
+            if count_entries(&partial)? == expected {
+                Ok(())
+            } else {
+                Err(Error::FileSystemError(format!(
+                    "Archive of {} is incomplete",
+                    prefix.display()
+                )))
+            }
+        });
+        if let Err(e) = verified {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, &dest)?;
+        fs::remove_dir_all(prefix)?;
+        Ok(dest)
+    })
+}
+
+/// Unpack the tarball `archive` into `dest`, keeping file permissions.
+pub(crate) fn unpack_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    tar.set_preserve_permissions(true);
+    tar.unpack(dest)?;
+    Ok(())
+}
+
+/// Unpack an archived prefix to `prefix` and delete the archive.
+pub fn unarchive_prefix(appid: u32, prefix: &Path) -> Result<PathBuf> {
+    logging::operation("unarchive", Some(appid), prefix, || {
+        let archive = archive_path(appid);
+        if !archive.is_file() {
+            return Err(Error::FileSystemError(format!(
+                "No archived prefix for {}",
+                appid
+            )));
+        }
+        let occupied = fs::read_dir(prefix)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+        if occupied {
+            return Err(Error::FileSystemError(format!(
+                "{} already contains a prefix",
+                prefix.display()
+            )));
+        }
+        fs::create_dir_all(prefix)?;
+        if let Err(e) = unpack_archive(&archive, prefix) {
+            let _ = fs::remove_dir_all(prefix);
+            return Err(e);
+        }
+        fs::remove_file(&archive)?;
+        Ok(prefix.to_path_buf())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_archive_and_unarchive() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let prefix = home.path().join("compatdata/7070");
+        fs::create_dir_all(prefix.join("pfx/drive_c/users")).unwrap();
+        fs::write(prefix.join("pfx/system.reg"), "WINE REGISTRY").unwrap();
+        fs::create_dir_all(prefix.join("pfx/dosdevices")).unwrap();
+        std::os::unix::fs::symlink("../drive_c", prefix.join("pfx/dosdevices/c:")).unwrap();
+
+        let now = 1_700_000_000;
+        let games = vec![
+            GameInfo::new(7070, "Old".into(), prefix.clone(), true, now - 7 * MONTH_SECS).unwrap(),
+            GameInfo::new(7071, "Recent".into(), prefix.clone(), true, now - MONTH_SECS).unwrap(),
+            GameInfo::new(7072, "Never".into(), prefix.clone(), true, 0).unwrap(),
+        ];
+        let inactive: Vec<u32> = inactive_games(&games, 6, now).iter().map(|g| g.app_id()).collect();
+        assert_eq!(inactive, vec![7070]);
+
+        let archive = archive_prefix(7070, &prefix).unwrap();
+        assert!(archive.is_file());
+        assert!(!prefix.exists());
+        assert!(is_archived(7070));
+        let listing = list_archive(&archive).unwrap();
+        assert!(listing.contains(&ArchiveEntry {
+            path: "pfx/system.reg".into(),
+            is_dir: false,
+            size: 13
+        }));
+        assert!(listing.iter().any(|e| e.path == "pfx/drive_c/users" && e.is_dir));
+
+        unarchive_prefix(7070, &prefix).unwrap();
+        assert!(!is_archived(7070));
+        assert_eq!(fs::read_to_string(prefix.join("pfx/system.reg")).unwrap(), "WINE REGISTRY");
+        assert_eq!(
+            fs::read_link(prefix.join("pfx/dosdevices/c:")).unwrap(),
+            PathBuf::from("../drive_c")
+        );
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
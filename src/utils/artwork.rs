@@ -0,0 +1,168 @@
+//! Game artwork for the game list.
+//!
+//! Steam keeps capsule images in `appcache/librarycache`. Games without local
+//! art (for example prefixes left behind by uninstalled games) can optionally
+//! fall back to grid images downloaded from SteamGridDB, which are cached on
+//! disk so each game is only requested once.
+
+use crate::error::{Error, Result};
+use crate::utils::{http, steam_paths};
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+const STEAMGRIDDB_API: &str = "https://www.steamgriddb.com/api/v2";
+const IMAGE_EXTENSIONS: [&str; 3] = ["jpg", "png", "webp"];
+
+#[derive(Deserialize)]
+struct GridResponse {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    data: Vec<GridImage>,
+}
+
+#[derive(Deserialize)]
+struct GridImage {
+    url: String,
+}
+
+fn cache_dir() -> PathBuf {
+    http::cache_root().join("steamgriddb")
+}
+
+/// Marker recording that SteamGridDB has no grid for a game.
+fn missing_marker(appid: u32) -> PathBuf {
+    cache_dir().join(format!("{}.none", appid))
+}
+
+/// Find the portrait capsule Steam downloaded for a game.
+pub fn local_art(appid: u32) -> Option<PathBuf> {
+    for base in steam_paths::steam_base_dirs() {
+        let cache = base.join("appcache/librarycache");
+        let flat = cache.join(format!("{}_library_600x900.jpg", appid));
+        if flat.is_file() {
+            return Some(flat);
+        }
+        // Newer clients store art in a per-app directory, sometimes nested
+        // one level deeper under a content hash.
+        let app_dir = cache.join(appid.to_string());
+        let direct = app_dir.join("library_600x900.jpg");
+        if direct.is_file() {
+            return Some(direct);
+        }
+        if let Ok(entries) = fs::read_dir(&app_dir) {
+            for entry in entries.flatten() {
+                let nested = entry.path().join("library_600x900.jpg");
+                if nested.is_file() {
+                    return Some(nested);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Return a previously downloaded SteamGridDB image.
+pub fn cached_art(appid: u32) -> Option<PathBuf> {
+    IMAGE_EXTENSIONS
+        .iter()
+        .map(|ext| cache_dir().join(format!("{}.{}", appid, ext)))
+        .find(|p| p.is_file())
+}
+
+/// Local Steam art if present, otherwise a cached SteamGridDB image.
+pub fn find_art(appid: u32) -> Option<PathBuf> {
+    local_art(appid).or_else(|| cached_art(appid))
+}
+
+/// Whether SteamGridDB should be queried for this game.
+pub fn needs_fetch(appid: u32) -> bool {
+    find_art(appid).is_none() && !missing_marker(appid).exists()
+}
+
+/// Download a portrait grid image from SteamGridDB.
+///
+/// Returns `Ok(None)` if SteamGridDB has no grid for the game. That result is
+/// remembered so the game is not queried again.
+pub fn fetch_steamgriddb(appid: u32, api_key: &str) -> Result<Option<PathBuf>> {
+    let auth = format!("Bearer {}", api_key);
+    let body = http::get_with_headers(
+        &format!("{}/grids/steam/{}", STEAMGRIDDB_API, appid),
+        &[("dimensions", "600x900")],
+        &[("Authorization", &auth)],
+    )?;
+    let resp: GridResponse = serde_json::from_str(&body)
+        .map_err(|e| Error::Parse(format!("SteamGridDB response: {}", e)))?;
+    if !resp.success {
+        return Err(Error::Network(format!(
+            "SteamGridDB rejected the request for AppID {}",
+            appid
+        )));
+    }
+
+    let Some(image) = resp.data.first() else {
+        fs::create_dir_all(cache_dir())?;
+        fs::write(missing_marker(appid), "")?;
+        return Ok(None);
+    };
+    let ext = IMAGE_EXTENSIONS
+        .iter()
+        .find(|ext| image.url.to_lowercase().ends_with(&format!(".{}", ext)))
+        .copied()
+        .unwrap_or("png");
+    let dest = cache_dir().join(format!("{}.{}", appid, ext));
+    http::download(&image.url, &dest)?;
+    Ok(Some(dest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+    use crate::utils::http::HTTP_RESPONSES;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_fetch_steamgriddb_caches_image() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_cache = std::env::var("XDG_CACHE_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CACHE_HOME");
+
+        {
+            let mut responses = HTTP_RESPONSES.lock().unwrap();
+            responses.clear();
+            responses.push((
+                format!("{}/grids/steam/4040", STEAMGRIDDB_API),
+                r#"{"success":true,"data":[{"url":"https://cdn.example/grid/abc.PNG"}]}"#
+                    .to_string(),
+            ));
+            responses.push((
+                format!("{}/grids/steam/5050", STEAMGRIDDB_API),
+                r#"{"success":true,"data":[]}"#.to_string(),
+            ));
+            responses.push(("https://cdn.example/grid/abc.PNG".to_string(), "img".to_string()));
+        }
+
+        assert!(needs_fetch(4040));
+        let path = fetch_steamgriddb(4040, "key").unwrap().unwrap();
+        assert_eq!(path.extension().unwrap(), "png");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "img");
+        assert_eq!(find_art(4040), Some(path));
+        assert!(!needs_fetch(4040));
+
+        assert_eq!(fetch_steamgriddb(5050, "key").unwrap(), None);
+        assert!(!needs_fetch(5050));
+
+        HTTP_RESPONSES.lock().unwrap().clear();
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_cache {
+            std::env::set_var("XDG_CACHE_HOME", c);
+        }
+    }
+}
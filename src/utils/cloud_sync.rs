@@ -0,0 +1,240 @@
+//! Steam Cloud conflict detection.
+//!
+//! Steam records the state of every synced file in
+//! `userdata/<account>/<appid>/remotecache.vdf`. Comparing those records with
+//! the files currently on disk reveals saves that Steam Cloud is likely to
+//! overwrite or flag as a conflict on the next launch.
+
+use crate::core::steam;
+use keyvalues_parser::{Value, Vdf};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Allowed difference between recorded and actual modification times.
+const TIME_TOLERANCE: u64 = 2;
+
+/// A file entry from `remotecache.vdf`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CloudEntry {
+    pub name: String,
+    pub root: u32,
+    pub size: u64,
+    pub localtime: u64,
+    pub remotetime: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Divergence {
+    /// The file no longer exists locally and will be downloaded again.
+    MissingLocally,
+    /// The cloud copy is newer than the last synced local copy.
+    CloudNewer,
+    /// The local file changed since the last sync.
+    LocalNewer,
+    /// The local file size differs from the synced size.
+    SizeMismatch,
+}
+
+impl Divergence {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Divergence::MissingLocally => "missing locally, Steam will download the cloud copy",
+            Divergence::CloudNewer => "cloud copy is newer and will replace the local file",
+            Divergence::LocalNewer => "changed locally since the last sync",
+            Divergence::SizeMismatch => "size differs from the synced copy",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CloudConflict {
+    pub file: String,
+    pub local_path: PathBuf,
+    pub divergence: Divergence,
+}
+
+fn get_u64(obj: &keyvalues_parser::Obj, key: &str) -> u64 {
+    obj.get(key)
+        .and_then(|v| v.first())
+        .and_then(Value::get_str)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Parse the file entries from `remotecache.vdf` contents.
+pub fn parse_remotecache(contents: &str) -> Vec<CloudEntry> {
+    let Ok(vdf) = Vdf::parse(contents) else {
+        return Vec::new();
+    };
+    let Some(obj) = vdf.value.get_obj() else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for (name, vals) in obj.iter() {
+        if let Some(file) = vals.first().and_then(Value::get_obj) {
+            let remotetime = match get_u64(file, "remotetime") {
+                0 => get_u64(file, "time"),
+                t => t,
+            };
+            entries.push(CloudEntry {
+                name: name.to_string(),
+                root: get_u64(file, "root") as u32,
+                size: get_u64(file, "size"),
+                localtime: get_u64(file, "localtime"),
+                remotetime,
+            });
+        }
+    }
+    entries
+}
+
+/// Map a Steam Cloud root (`ERemoteStorageFileRoot`) to a local directory.
+fn root_dir(root: u32, userdata_app_dir: &Path, prefix: &Path, install_dir: Option<&Path>) -> Option<PathBuf> {
+    let drive_c = prefix.join("pfx/drive_c");
+    let user = drive_c.join("users/steamuser");
+    match root {
+        0 => Some(userdata_app_dir.join("remote")),
+        1 => install_dir.map(Path::to_path_buf),
+        2 => Some(user.join("Documents")),
+        3 => Some(user.join("AppData/Local")),
+        4 => Some(user.join("AppData/Roaming")),
+        9 => Some(user.join("Saved Games")),
+        10 => Some(drive_c.join("ProgramData")),
+        12 => Some(user.join("AppData/LocalLow")),
+        _ => None,
+    }
+}
+
+fn compare(entry: &CloudEntry, local: &Path) -> Option<Divergence> {
+    let Ok(meta) = fs::metadata(local) else {
+        return Some(Divergence::MissingLocally);
+    };
+    if entry.remotetime > entry.localtime + TIME_TOLERANCE {
+        return Some(Divergence::CloudNewer);
+    }
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if mtime > entry.localtime + TIME_TOLERANCE {
+        return Some(Divergence::LocalNewer);
+    }
+    if meta.len() != entry.size {
+        return Some(Divergence::SizeMismatch);
+    }
+    None
+}
+
+/// Compare the entries of a `remotecache.vdf` file with the files on disk.
+pub fn find_conflicts(
+    userdata_app_dir: &Path,
+    prefix: &Path,
+    install_dir: Option<&Path>,
+) -> Vec<CloudConflict> {
+    let Ok(contents) = fs::read_to_string(userdata_app_dir.join("remotecache.vdf")) else {
+        return Vec::new();
+    };
+    parse_remotecache(&contents)
+        .into_iter()
+        .filter_map(|entry| {
+            let base = root_dir(entry.root, userdata_app_dir, prefix, install_dir)?;
+            let local_path = base.join(&entry.name);
+            let divergence = compare(&entry, &local_path)?;
+            Some(CloudConflict {
+                file: entry.name,
+                local_path,
+                divergence,
+            })
+        })
+        .collect()
+}
+
+/// Detect Steam Cloud divergences for the active user's copy of a game.
+pub fn detect_conflicts(appid: u32, prefix: &Path) -> Vec<CloudConflict> {
+    match steam::find_userdata_dir(appid) {
+        Some(userdata) => {
+            let install_dir = steam::find_install_dir(appid);
+            find_conflicts(&userdata, prefix, install_dir.as_deref())
+        }
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_remotecache() {
+        let contents = r#""620"
+        {
+            "ChangeNumber"  "5"
+            "save/slot1.sav"
+            {
+                "root"  "2"
+                "size"  "4"
+                "localtime"  "1700000000"
+                "time"  "1700000000"
+            }
+        }"#;
+        let entries = parse_remotecache(contents);
+        assert_eq!(
+            entries,
+            vec![CloudEntry {
+                name: "save/slot1.sav".to_string(),
+                root: 2,
+                size: 4,
+                localtime: 1700000000,
+                remotetime: 1700000000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let dir = tempdir().unwrap();
+        let userdata = dir.path().join("userdata/111/620");
+        let prefix = dir.path().join("compatdata/620");
+        let docs = prefix.join("pfx/drive_c/users/steamuser/Documents/Game");
+        fs::create_dir_all(&userdata).unwrap();
+        fs::create_dir_all(&docs).unwrap();
+
+        let synced_at = SystemTime::now() - Duration::from_secs(3600);
+        let synced_secs = synced_at.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        for name in ["in_sync.sav", "edited.sav"] {
+            fs::write(docs.join(name), "data").unwrap();
+        }
+        fs::File::options()
+            .write(true)
+            .open(docs.join("in_sync.sav"))
+            .unwrap()
+            .set_modified(synced_at)
+            .unwrap();
+
+        let entry = |name: &str| {
+            format!(
+                "\"Game/{}\" {{ \"root\" \"2\" \"size\" \"4\" \"localtime\" \"{}\" \"time\" \"{}\" }}",
+                name, synced_secs, synced_secs
+            )
+        };
+        let contents = format!(
+            "\"620\" {{ {} {} {} }}",
+            entry("in_sync.sav"),
+            entry("edited.sav"),
+            entry("gone.sav")
+        );
+        fs::write(userdata.join("remotecache.vdf"), contents).unwrap();
+
+        let conflicts = find_conflicts(&userdata, &prefix, None);
+        assert_eq!(conflicts.len(), 2);
+        let edited = conflicts.iter().find(|c| c.file == "Game/edited.sav").unwrap();
+        assert_eq!(edited.divergence, Divergence::LocalNewer);
+        let gone = conflicts.iter().find(|c| c.file == "Game/gone.sav").unwrap();
+        assert_eq!(gone.divergence, Divergence::MissingLocally);
+    }
+}
@@ -0,0 +1,202 @@
+//! Undo the last change to a game's Steam settings.
+//!
+//! The change to undo is taken from the game's [`history`]: the newest
+//! successful `config` or `apply_profile` entry that an `undo` or
+//! `rollback` has not already reverted. The settings before it are read
+//! from the appmanifest snapshot [`vdf_history`] took right before that
+//! write. Only this game's launch options, compatibility tool and Steam
+//! flags are restored, unlike `config rollback`, which puts back whole
+//! files shared with other games.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::history::{self, Entry};
+use crate::utils::{library, manifest as manifest_utils, user_config, vdf_history};
+use chrono::{Local, NaiveDateTime};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// History operations that change the settings in Steam's config files.
+const CHANGES: [&str; 2] = ["config", "apply_profile"];
+
+/// History operations that revert the last change.
+const REVERTS: [&str; 2] = ["undo", "rollback"];
+
+/// Steam flags of the appmanifest, restored when the snapshot has them.
+const KEYS: [&str; 2] = ["AllowCloudSaves", "AutoUpdateBehavior"];
+
+/// The change an undo would revert, given the game's history.
+pub fn last_change(entries: &[Entry]) -> Option<&Entry> {
+    let mut reverted = 0;
+    for entry in entries.iter().rev().filter(|e| e.error.is_none()) {
+        if REVERTS.contains(&entry.operation.as_str()) {
+            reverted += 1;
+        } else if CHANGES.contains(&entry.operation.as_str()) {
+            if reverted == 0 {
+                return Some(entry);
+            }
+            reverted -= 1;
+        }
+    }
+    None
+}
+
+/// Time a snapshot was taken, from its `%Y%m%d%H%M%S%6f` file name.
+fn snapshot_time(path: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    let time = NaiveDateTime::parse_from_str(name.get(..14)?, "%Y%m%d%H%M%S").ok()?;
+    let time = time.and_local_timezone(Local).earliest()?;
+    u64::try_from(time.timestamp()).ok()
+}
+
+/// Newest snapshot taken no later than `timestamp`, i.e. the one written
+/// right before the change recorded then.
+fn snapshot_before(snapshots: &[PathBuf], timestamp: u64) -> Option<&PathBuf> {
+    snapshots
+        .iter()
+        .rev()
+        .find(|s| snapshot_time(s).is_some_and(|t| t <= timestamp))
+}
+
+fn restore(appid: u32, change: &Entry) -> Result<()> {
+    let manifest = steam::find_manifest(appid).ok_or_else(|| {
+        Error::InvalidManifest(format!("appmanifest for {} not found", appid))
+    })?;
+    let snapshots = vdf_history::snapshots(&manifest);
+    let snapshot = snapshot_before(&snapshots, change.timestamp).ok_or_else(|| {
+        Error::FileSystemError(format!(
+            "no copy of {} from before {}",
+            manifest.display(),
+            change.time()
+        ))
+    })?;
+    let previous = fs::read_to_string(snapshot)?;
+    let mut contents = fs::read_to_string(&manifest)?;
+
+    let launch = manifest_utils::get_value(&previous, "LaunchOptions").unwrap_or_default();
+    contents = manifest_utils::update_or_insert(&contents, "LaunchOptions", &launch);
+    user_config::set_launch_options(appid, &launch)?;
+    match manifest_utils::get_value(&previous, "CompatToolOverride").filter(|v| !v.is_empty()) {
+        Some(tool) => {
+            contents = manifest_utils::update_or_insert(&contents, "CompatToolOverride", &tool);
+            user_config::set_compat_tool_mapping(appid, &tool)?;
+        }
+        None => {
+            user_config::clear_compat_tool_mapping(appid)?;
+            let _ = user_config::clear_compat_tool(appid);
+        }
+    }
+    for key in KEYS {
+        if let Some(value) = manifest_utils::get_value(&previous, key) {
+            contents = manifest_utils::update_or_insert(&contents, key, &value);
+        }
+    }
+    // Written without a new snapshot and the used one dropped, so the next
+    // undo goes one change further back.
+    fs::write(&manifest, &contents)?;
+    library::update_manifest_cache(&manifest, &contents);
+    fs::remove_file(snapshot)?;
+    Ok(())
+}
+
+/// Revert the last change to the settings of `appid`. Returns the history
+/// entry of the change that was undone.
+pub fn undo(appid: u32) -> Result<Entry> {
+    let entries = history::load(appid);
+    let change = last_change(&entries).cloned().ok_or_else(|| {
+        Error::FileSystemError(format!("no configuration change of {} to undo", appid))
+    })?;
+    let result = restore(appid, &change);
+    history::record_result(
+        appid,
+        "undo",
+        format!("{} from {}: {}", change.operation, change.time(), change.details),
+        &result,
+    );
+    result.map(|_| change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    fn entry(operation: &str, error: Option<&str>) -> Entry {
+        Entry {
+            timestamp: 0,
+            operation: operation.to_string(),
+            details: String::new(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_last_change() {
+        let mut entries = vec![
+            entry("config", None),
+            entry("backup", None),
+            entry("apply_profile", None),
+            entry("config", Some("manifest not found")),
+        ];
+        assert_eq!(last_change(&entries).unwrap().operation, "apply_profile");
+        entries.push(entry("undo", None));
+        assert_eq!(last_change(&entries).unwrap().operation, "config");
+        entries.push(entry("rollback", None));
+        assert_eq!(last_change(&entries), None);
+    }
+
+    #[test]
+    fn test_undo() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        library::clear_manifest_cache();
+        user_config::clear_localconfig_cache();
+        let appid = 7101;
+        let (home, _prefix, _) = setup_steam_env(appid, true);
+        let manifest = home
+            .path()
+            .join(format!("library/steamapps/appmanifest_{}.acf", appid));
+        fs::write(
+            &manifest,
+            format!(
+                "\"AppState\" {{\n    \"appid\" \"{}\"\n    \"name\" \"Undo Game\"\n    \"LaunchOptions\" \"%command%\"\n}}",
+                appid
+            ),
+        )
+        .unwrap();
+        fs::create_dir_all(home.path().join(".steam/steam/userdata/111111111/config")).unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+        std::env::remove_var("XDG_DATA_HOME");
+
+        let launch = Some("-dx11 %command%".into());
+        crate::cli::config::execute(appid, launch, None, None, None, None, None);
+        let launch = Some("-dx12 %command%".into());
+        crate::cli::config::execute(appid, launch, None, None, None, None, None);
+        let launch = || {
+            manifest_utils::get_value(&fs::read_to_string(&manifest).unwrap(), "LaunchOptions")
+        };
+        assert_eq!(launch().as_deref(), Some("-dx12 %command%"));
+
+        let undone = undo(appid).unwrap();
+        assert_eq!(undone.details, "launch options: -dx12 %command%");
+        assert_eq!(launch().as_deref(), Some("-dx11 %command%"));
+        assert_eq!(user_config::get_launch_options(appid).as_deref(), Some("-dx11 %command%"));
+        undo(appid).unwrap();
+        assert_eq!(launch().as_deref(), Some("%command%"));
+        assert!(undo(appid).is_err());
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
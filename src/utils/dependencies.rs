@@ -1,10 +1,21 @@
-use std::collections::BTreeMap;
+//! External tools this application runs and how to install missing ones.
 
+use crate::utils::system_info;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(not(test))]
+use crate::utils::sandbox;
 #[cfg(not(test))]
 use which::which;
 
+/// Whether `command` can be run. Inside a Flatpak sandbox this asks the
+/// host, where the tools are run.
 #[cfg(not(test))]
 pub fn command_available(command: &str) -> bool {
+    if sandbox::in_flatpak() {
+        return sandbox::host_program_available(command);
+    }
     which(command).is_ok()
 }
 
@@ -28,3 +39,139 @@ pub fn scan_tools(tools: &[&str]) -> BTreeMap<String, bool> {
         .map(|t| ((*t).to_string(), true))
         .collect()
 }
+
+/// Linux distribution families, which decide the install command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distro {
+    Arch,
+    Debian,
+    Fedora,
+    OpenSuse,
+    /// Read-only root filesystem, so only Flatpak applies.
+    SteamOs,
+    Unknown,
+}
+
+impl Distro {
+    fn from_os_release(release: &HashMap<String, String>) -> Self {
+        let ids: Vec<String> = release
+            .get("ID")
+            .into_iter()
+            .chain(release.get("ID_LIKE"))
+            .flat_map(|v| v.split_whitespace())
+            .map(str::to_lowercase)
+            .collect();
+        let has = |names: &[&str]| ids.iter().any(|id| names.contains(&id.as_str()));
+        if has(&["steamos"]) {
+            Distro::SteamOs
+        } else if has(&["arch"]) {
+            Distro::Arch
+        } else if has(&["debian", "ubuntu"]) {
+            Distro::Debian
+        } else if has(&["fedora", "rhel"]) {
+            Distro::Fedora
+        } else if has(&["suse", "opensuse"]) {
+            Distro::OpenSuse
+        } else {
+            Distro::Unknown
+        }
+    }
+
+    fn install_command(&self, package: &str) -> Option<String> {
+        match self {
+            Distro::Arch => Some(format!("sudo pacman -S {}", package)),
+            Distro::Debian => Some(format!("sudo apt install {}", package)),
+            Distro::Fedora => Some(format!("sudo dnf install {}", package)),
+            Distro::OpenSuse => Some(format!("sudo zypper install {}", package)),
+            Distro::SteamOs | Distro::Unknown => None,
+        }
+    }
+}
+
+static DISTRO: Lazy<Distro> = Lazy::new(|| Distro::from_os_release(&system_info::os_release()));
+
+/// Distribution family of this machine.
+pub fn distro() -> Distro {
+    *DISTRO
+}
+
+/// Package providing `tool`, and whether the distribution packages it.
+/// Protontricks is only in the AUR on Arch.
+fn package(tool: &str, distro: Distro) -> Option<&'static str> {
+    match (tool, distro) {
+        ("protontricks", Distro::Arch) => None,
+        ("protontricks", _) => Some("protontricks"),
+        ("winecfg", _) => Some("wine"),
+        ("winetricks", _) => Some("winetricks"),
+        ("mangohud", _) => Some("mangohud"),
+        ("gamescope", _) => Some("gamescope"),
+        ("vkbasalt", Distro::Fedora) => Some("vkBasalt"),
+        ("vkbasalt", _) => Some("vkbasalt"),
+        ("vulkaninfo", _) => Some("vulkan-tools"),
+        ("curl", _) => Some("curl"),
+        ("tar", _) => Some("tar"),
+        _ => None,
+    }
+}
+
+/// Flatpak of `tool`, for systems without a usable package manager.
+fn flatpak(tool: &str) -> Option<&'static str> {
+    match tool {
+        "protontricks" => Some("com.github.Matoking.protontricks"),
+        "mangohud" => Some("org.freedesktop.Platform.VulkanLayer.MangoHud"),
+        "gamescope" => Some("org.freedesktop.Platform.VulkanLayer.gamescope"),
+        _ => None,
+    }
+}
+
+fn install_hint_for(tool: &str, distro: Distro) -> String {
+    if let Some(command) = package(tool, distro).and_then(|p| distro.install_command(p)) {
+        return format!("Install it with: {}", command);
+    }
+    if let Some(id) = flatpak(tool) {
+        return format!("Install it with: flatpak install flathub {}", id);
+    }
+    format!("Install {} with your distribution's package manager.", tool)
+}
+
+/// How to install a missing tool on this machine.
+pub fn install_hint(tool: &str) -> String {
+    install_hint_for(tool, distro())
+}
+
+/// Error shown when `tool` is needed but missing, with an install hint.
+pub fn missing_message(tool: &str) -> String {
+    format!("'{}' is not installed or not found in PATH. {}", tool, install_hint(tool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(id: &str, id_like: Option<&str>) -> HashMap<String, String> {
+        let mut release = HashMap::from([("ID".to_string(), id.to_string())]);
+        if let Some(like) = id_like {
+            release.insert("ID_LIKE".to_string(), like.to_string());
+        }
+        release
+    }
+
+    #[test]
+    fn test_install_hints() {
+        assert_eq!(Distro::from_os_release(&release("linuxmint", Some("ubuntu debian"))), Distro::Debian);
+        assert_eq!(Distro::from_os_release(&release("steamos", Some("arch"))), Distro::SteamOs);
+        assert_eq!(Distro::from_os_release(&release("cachyos", Some("arch"))), Distro::Arch);
+        assert_eq!(Distro::from_os_release(&release("opensuse-tumbleweed", Some("opensuse suse"))), Distro::OpenSuse);
+        assert_eq!(Distro::from_os_release(&HashMap::new()), Distro::Unknown);
+
+        assert_eq!(install_hint_for("winecfg", Distro::Fedora), "Install it with: sudo dnf install wine");
+        assert_eq!(
+            install_hint_for("protontricks", Distro::Arch),
+            "Install it with: flatpak install flathub com.github.Matoking.protontricks"
+        );
+        assert_eq!(
+            install_hint_for("vulkaninfo", Distro::SteamOs),
+            "Install vulkaninfo with your distribution's package manager."
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Checks of the whole environment for `doctor`.
+//!
+//! Looks at everything outside of a single prefix that keeps games from
+//! running or this tool from working: Steam itself, its libraries, the
+//! Vulkan drivers, anti-cheat runtimes, the helper tools and the space left
+//! for backups.
+
+use crate::core::steam;
+use crate::utils::dependencies::{command_available, install_hint};
+use crate::utils::{anticheat, backup, output, sandbox, steam_paths, system_info};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Free space below which backups are likely to fail.
+const LOW_BACKUP_SPACE: u64 = 10 * 1024 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Status::Pass => "✅",
+            Status::Warn => "⚠️",
+            Status::Fail => "❌",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub message: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+fn check_steam() -> Check {
+    match steam_paths::steam_base_dirs().first() {
+        Some(dir) => Check::new("steam", Status::Pass, format!("Found at {}", dir.display())),
+        None if sandbox::in_flatpak() => Check::new(
+            "steam",
+            Status::Fail,
+            "No Steam installation found; the Flatpak sandbox may lack access to it, \
+             grant it with `flatpak override --user --filesystem=home`",
+        ),
+        None => Check::new("steam", Status::Fail, "No Steam installation found"),
+    }
+}
+
+fn check_libraries() -> Check {
+    let libraries = match steam::get_steam_libraries() {
+        Ok(libraries) => libraries,
+        Err(e) => return Check::new("libraries", Status::Fail, e.to_string()),
+    };
+    let unreadable: Vec<String> = libraries
+        .iter()
+        .filter(|lib| fs::read_dir(lib.steamapps_path()).is_err())
+        .map(|lib| lib.path().display().to_string())
+        .collect();
+    if unreadable.is_empty() {
+        Check::new(
+            "libraries",
+            Status::Pass,
+            format!("{} library folder(s) readable", libraries.len()),
+        )
+    } else {
+        Check::new(
+            "libraries",
+            Status::Fail,
+            format!("Cannot read {}", unreadable.join(", ")),
+        )
+    }
+}
+
+fn check_vulkan() -> Check {
+    if !command_available("vulkaninfo") {
+        return Check::new(
+            "vulkan",
+            Status::Warn,
+            format!("vulkaninfo not found in PATH. {}", install_hint("vulkaninfo")),
+        );
+    }
+    let Some(gpus) = system_info::vulkan_devices() else {
+        return Check::new("vulkan", Status::Fail, "vulkaninfo failed; no working Vulkan driver");
+    };
+    let names: Vec<&str> = gpus.iter().map(|gpu| gpu.name.as_str()).collect();
+    if gpus.is_empty() {
+        Check::new("vulkan", Status::Fail, "No Vulkan device found")
+    } else if gpus.iter().all(|gpu| gpu.is_software()) {
+        Check::new(
+            "vulkan",
+            Status::Warn,
+            format!("Only software rendering available: {}", names.join(", ")),
+        )
+    } else {
+        Check::new("vulkan", Status::Pass, names.join(", "))
+    }
+}
+
+/// 32-bit Vulkan loaders of the common distribution layouts.
+const VULKAN_32BIT: [&str; 2] = [
+    "/usr/lib32/libvulkan.so.1",
+    "/usr/lib/i386-linux-gnu/libvulkan.so.1",
+];
+
+/// Directories holding Vulkan driver manifests.
+const ICD_DIRS: [&str; 2] = ["/usr/share/vulkan/icd.d", "/etc/vulkan/icd.d"];
+
+/// Whether 32-bit Vulkan drivers are installed, which Steam and many older
+/// games need.
+fn has_32bit_drivers(loaders: &[&str], icd_dirs: &[&str]) -> bool {
+    loaders.iter().any(|p| Path::new(p).exists())
+        || icd_dirs.iter().any(|dir| {
+            fs::read_dir(dir).into_iter().flatten().flatten().any(|e| {
+                let name = e.file_name().to_string_lossy().to_lowercase();
+                name.contains("i686") || name.contains("i386")
+            })
+        })
+}
+
+fn check_32bit() -> Check {
+    if has_32bit_drivers(&VULKAN_32BIT, &ICD_DIRS) {
+        Check::new("32bit_drivers", Status::Pass, "32-bit Vulkan drivers installed")
+    } else {
+        Check::new(
+            "32bit_drivers",
+            Status::Warn,
+            "No 32-bit Vulkan drivers found; Steam and 32-bit games may not start",
+        )
+    }
+}
+
+/// Games using EasyAntiCheat or BattlEye whose Proton runtime is missing.
+fn check_anticheat() -> Check {
+    let games = steam::get_steam_libraries()
+        .and_then(|libs| steam::load_games_from_libraries(&libs))
+        .unwrap_or_default();
+    let mut missing = Vec::new();
+    let mut hints = Vec::new();
+    for game in &games {
+        for (ac, installed) in anticheat::check(game.app_id(), game.prefix_path()) {
+            if !installed {
+                missing.push(format!("{} ({})", game.name(), ac.name()));
+                let hint = ac.install_hint();
+                if !hints.contains(&hint) {
+                    hints.push(hint);
+                }
+            }
+        }
+    }
+    if missing.is_empty() {
+        Check::new("anticheat", Status::Pass, "No game is missing its anti-cheat runtime")
+    } else {
+        Check::new(
+            "anticheat",
+            Status::Warn,
+            format!("Runtime missing for {}. {}", missing.join(", "), hints.join(". ")),
+        )
+    }
+}
+
+fn check_tool(name: &'static str) -> Check {
+    if command_available(name) {
+        Check::new(name, Status::Pass, "Installed")
+    } else {
+        Check::new(name, Status::Warn, format!("Not found in PATH. {}", install_hint(name)))
+    }
+}
+
+fn check_backup_space() -> Check {
+    let root = backup::backup_root();
+    // The backup directory may not exist before the first backup.
+    let existing = root.ancestors().find(|p| p.exists()).unwrap_or(&root);
+    match fs2::available_space(existing) {
+        Ok(free) if free < LOW_BACKUP_SPACE => Check::new(
+            "backup_space",
+            Status::Warn,
+            format!("Only {} free at {}", output::format_size(free), root.display()),
+        ),
+        Ok(free) => Check::new(
+            "backup_space",
+            Status::Pass,
+            format!("{} free at {}", output::format_size(free), root.display()),
+        ),
+        Err(e) => Check::new(
+            "backup_space",
+            Status::Fail,
+            format!("Cannot check {}: {}", root.display(), e),
+        ),
+    }
+}
+
+/// Run every check in the order they are printed.
+pub fn run_checks() -> Vec<Check> {
+    vec![
+        check_steam(),
+        check_libraries(),
+        check_vulkan(),
+        check_32bit(),
+        check_anticheat(),
+        check_tool("protontricks"),
+        check_tool("winetricks"),
+        check_backup_space(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_run_checks() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, _prefix, _) = setup_steam_env(5050, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let checks = run_checks();
+        let names: Vec<&str> = checks.iter().map(|c| c.name).collect();
+        assert_eq!(
+            names,
+            [
+                "steam",
+                "libraries",
+                "vulkan",
+                "32bit_drivers",
+                "anticheat",
+                "protontricks",
+                "winetricks",
+                "backup_space"
+            ]
+        );
+        assert_eq!(checks[0].status, Status::Pass);
+        assert_eq!(checks[1].status, Status::Pass);
+        assert_eq!(checks[2].message, "AMD Radeon RX 6800 (RADV NAVI21)");
+
+        let icd = home.path().join("icd.d");
+        fs::create_dir_all(&icd).unwrap();
+        let icd_dir = icd.to_str().unwrap();
+        assert!(!has_32bit_drivers(&[], &[icd_dir]));
+        fs::write(icd.join("radeon_icd.i686.json"), "{}").unwrap();
+        assert!(has_32bit_drivers(&[], &[icd_dir]));
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Directory sizes computed on a background thread and shared by every
+//! view that shows or filters by them.
+//!
+//! Walking a prefix takes long enough that it must not happen on the GUI
+//! thread. [`size`] answers from the cache and queues directories it has
+//! not measured yet; callers ask again, usually on the next frame. A size
+//! is measured again once the modification time of its directory changes.
+
+use crate::utils::backup;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::SystemTime;
+
+struct Entry {
+    /// Modification time of the directory when it was queued.
+    modified: Option<SystemTime>,
+    /// Measured size, and `None` while the directory is still queued.
+    size: Option<u64>,
+}
+
+struct Service {
+    sizes: Mutex<HashMap<PathBuf, Entry>>,
+    queue: Mutex<Sender<PathBuf>>,
+    /// Number of sizes measured so far.
+    measured: AtomicU64,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+static SERVICE: Lazy<Service> = Lazy::new(|| {
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    thread::spawn(move || {
+        for path in rx {
+            let size = backup::dir_size(&path).unwrap_or(0);
+            if let Some(entry) = SERVICE.sizes.lock().unwrap().get_mut(&path) {
+                entry.size = Some(size);
+            }
+            SERVICE.measured.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    Service {
+        sizes: Mutex::new(HashMap::new()),
+        queue: Mutex::new(tx),
+        measured: AtomicU64::new(0),
+    }
+});
+
+/// Size of `path` in bytes if it has been measured since the directory was
+/// last modified, otherwise `None` after queueing it.
+pub fn size(path: &Path) -> Option<u64> {
+    let modified = modified(path);
+    let mut sizes = SERVICE.sizes.lock().unwrap();
+    match sizes.get(path) {
+        Some(entry) if entry.size.is_none() || entry.modified == modified => entry.size,
+        _ => {
+            sizes.insert(path.to_path_buf(), Entry { modified, size: None });
+            let _ = SERVICE.queue.lock().unwrap().send(path.to_path_buf());
+            None
+        }
+    }
+}
+
+/// Last measured size of `path` without checking whether it is current or
+/// queueing it, cheap enough for sort comparisons.
+pub fn cached(path: &Path) -> Option<u64> {
+    SERVICE.sizes.lock().unwrap().get(path).and_then(|entry| entry.size)
+}
+
+/// A counter that changes whenever a size has been measured, so views
+/// ordered by size know when to sort again.
+pub fn generation() -> u64 {
+    SERVICE.measured.load(Ordering::Relaxed)
+}
+
+/// Forget the size of `path`, after it changed.
+pub fn invalidate(path: &Path) {
+    let mut sizes = SERVICE.sizes.lock().unwrap();
+    if sizes.get(path).is_some_and(|entry| entry.size.is_some()) {
+        sizes.remove(path);
+    }
+}
+
+/// A range of sizes typed as `> 5 GB`, `< 500 MB`, `1-10 GB` or `2 GB`
+/// (at least 2 GB).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeRange {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+/// Parse a size such as `5`, `5GB` or `1.5 TB`; the unit defaults to
+/// `default_unit` bytes.
+fn parse_size(text: &str, default_unit: Option<u64>) -> Option<(u64, Option<u64>)> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let number: f64 = text[..split].trim().parse().ok()?;
+    let unit = match text[split..].trim().to_lowercase().as_str() {
+        "" => default_unit,
+        "b" => Some(1),
+        "k" | "kb" | "kib" => Some(1 << 10),
+        "m" | "mb" | "mib" => Some(1 << 20),
+        "g" | "gb" | "gib" => Some(1 << 30),
+        "t" | "tb" | "tib" => Some(1 << 40),
+        _ => return None,
+    };
+    let bytes = (number * unit.unwrap_or(1 << 30) as f64) as u64;
+    Some((bytes, unit))
+}
+
+impl SizeRange {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(rest) = text.strip_prefix(">=").or_else(|| text.strip_prefix('>')) {
+            return Some(Self { min: Some(parse_size(rest, None)?.0), max: None });
+        }
+        if let Some(rest) = text.strip_prefix("<=").or_else(|| text.strip_prefix('<')) {
+            return Some(Self { min: None, max: Some(parse_size(rest, None)?.0) });
+        }
+        if let Some((low, high)) = text.split_once('-') {
+            // "1-10 GB": the lower bound takes the unit of the upper one.
+            let (max, unit) = parse_size(high, None)?;
+            let (min, _) = parse_size(low, unit)?;
+            return Some(Self { min: Some(min), max: Some(max) });
+        }
+        Some(Self { min: Some(parse_size(text, None)?.0), max: None })
+    }
+
+    pub fn contains(&self, size: u64) -> bool {
+        self.min.is_none_or(|min| size >= min) && self.max.is_none_or(|max| size <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_size_range() {
+        const GB: u64 = 1 << 30;
+        assert_eq!(SizeRange::parse("> 5 GB"), Some(SizeRange { min: Some(5 * GB), max: None }));
+        assert_eq!(SizeRange::parse("<500mb"), Some(SizeRange { min: None, max: Some(500 << 20) }));
+        assert_eq!(
+            SizeRange::parse("1-10 GB"),
+            Some(SizeRange { min: Some(GB), max: Some(10 * GB) })
+        );
+        assert_eq!(
+            SizeRange::parse("512 MB - 1.5 GB"),
+            Some(SizeRange { min: Some(512 << 20), max: Some(GB + GB / 2) })
+        );
+        assert_eq!(SizeRange::parse("2"), Some(SizeRange { min: Some(2 * GB), max: None }));
+        assert_eq!(SizeRange::parse("big"), None);
+        assert!(SizeRange::parse(">5GB").unwrap().contains(6 * GB));
+        assert!(!SizeRange::parse(">5GB").unwrap().contains(GB));
+    }
+
+    #[test]
+    fn test_size() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), vec![0u8; 1000]).unwrap();
+        let start = Instant::now();
+        let size = loop {
+            if let Some(size) = size(dir.path()) {
+                break size;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(size, 1000);
+        invalidate(dir.path());
+        assert_eq!(super::size(dir.path()), None);
+    }
+}
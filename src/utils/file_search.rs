@@ -0,0 +1,76 @@
+//! Searching file names inside the `drive_c` of many prefixes.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use walkdir::WalkDir;
+
+/// Directory levels below `drive_c` searched by default.
+pub const DEFAULT_DEPTH: usize = 8;
+
+/// Matches kept per prefix, so a vague query stays readable.
+const MAX_MATCHES: usize = 50;
+
+pub enum SearchEvent {
+    /// A prefix was searched; `done` of `total` are finished.
+    Progress { done: usize, total: usize },
+    Found { app_id: u32, path: PathBuf },
+}
+
+/// Files and directories under `drive_c` whose name contains `query`,
+/// ignoring case, at most `max_depth` levels deep.
+pub fn find_in_prefix(prefix: &Path, query: &str, max_depth: usize) -> Vec<PathBuf> {
+    let query = query.to_lowercase();
+    WalkDir::new(prefix.join("pfx/drive_c"))
+        .min_depth(1)
+        .max_depth(max_depth)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().to_lowercase().contains(&query))
+        .take(MAX_MATCHES)
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Search the prefixes of `games` one after another, reporting matches and
+/// progress to `events`. Stops early once the receiver is dropped.
+pub fn search(games: Vec<(u32, PathBuf)>, query: String, max_depth: usize, events: Sender<SearchEvent>) {
+    let total = games.len();
+    for (done, (app_id, prefix)) in games.into_iter().enumerate() {
+        for path in find_in_prefix(&prefix, &query, max_depth) {
+            if events.send(SearchEvent::Found { app_id, path }).is_err() {
+                return;
+            }
+        }
+        let progress = SearchEvent::Progress { done: done + 1, total };
+        if events.send(progress).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("100");
+        let b = dir.path().join("200");
+        let system32 = a.join("pfx/drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        fs::write(system32.join("D3DX9_43.dll"), "").unwrap();
+        fs::create_dir_all(b.join("pfx/drive_c/users")).unwrap();
+
+        assert_eq!(find_in_prefix(&a, "d3dx9_43", DEFAULT_DEPTH), [system32.join("D3DX9_43.dll")]);
+        assert!(find_in_prefix(&a, "d3dx9_43", 2).is_empty());
+
+        let (tx, rx) = mpsc::channel();
+        search(vec![(100, a), (200, b)], "d3dx9".into(), DEFAULT_DEPTH, tx);
+        let events: Vec<SearchEvent> = rx.iter().collect();
+        assert!(matches!(&events[0], SearchEvent::Found { app_id: 100, .. }));
+        assert!(matches!(events.last(), Some(SearchEvent::Progress { done: 2, total: 2 })));
+    }
+}
@@ -0,0 +1,303 @@
+//! Moving a game to another Steam library, like Steam's own "Move install
+//! folder".
+//!
+//! The install directory moves together with the game's prefix, shader
+//! cache and Workshop content, since Steam looks for them in the library
+//! the game is installed in. The appmanifest and the game's entry in
+//! `libraryfolders.vdf` are moved last, so the game never shows up in a
+//! library that lacks its files, and a move that fails halfway is undone.
+//! Steam should not be running meanwhile.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::backup::{self, Progress};
+use crate::utils::library::{self, parse_appmanifest, parse_appmanifest_installdir};
+use crate::utils::{logging, output, prefix_relocate, vdf_history};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs as unix_fs;
+
+/// Libraries a game was moved between.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MovedGame {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Paths below `steamapps` that belong to the game, apart from its
+/// appmanifest.
+fn game_paths(appid: u32, installdir: &str) -> [PathBuf; 5] {
+    [
+        Path::new("common").join(installdir),
+        Path::new("compatdata").join(appid.to_string()),
+        Path::new("shadercache").join(appid.to_string()),
+        Path::new("workshop/content").join(appid.to_string()),
+        PathBuf::from(format!("workshop/appworkshop_{}.acf", appid)),
+    ]
+}
+
+fn size(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => backup::dir_size(path).unwrap_or(0),
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => 0,
+    }
+}
+
+/// Move `src` to `dst`, renaming it when both are on one filesystem and
+/// copying it otherwise. A relocated prefix moves as its symlink.
+fn move_path(src: &Path, dst: &Path, progress: Progress) -> Result<()> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+    let meta = fs::symlink_metadata(src)?;
+    if meta.file_type().is_symlink() {
+        #[cfg(unix)]
+        unix_fs::symlink(fs::read_link(src)?, dst)?;
+        fs::remove_file(src)?;
+    } else if meta.is_dir() {
+        if let Err(e) = prefix_relocate::copy_verified(src, dst, progress) {
+            let _ = fs::remove_dir_all(dst);
+            return Err(e);
+        }
+        fs::remove_dir_all(src)?;
+    } else {
+        fs::copy(src, dst)?;
+        fs::remove_file(src)?;
+    }
+    Ok(())
+}
+
+/// Move the paths of `moved`, pairs of where they were and are now, back
+/// in reverse order. Failures are logged, as there is no better place left
+/// for the files.
+fn move_back(moved: &[(&Path, &Path)]) {
+    for (src, dst) in moved.iter().rev() {
+        log::info!("Moving {} back to {}", dst.display(), src.display());
+        if let Err(e) = move_path(dst, src, &|_, _| {}) {
+            log::error!("Failed to move {} back to {}: {}", dst.display(), src.display(), e);
+        }
+    }
+}
+
+/// Move the `apps` entry of `appid` in libraryfolders.vdf from the library
+/// at `from` to the one at `to`.
+fn update_libraryfolders(appid: u32, from: &Path, to: &Path) -> Result<()> {
+    let path = steam::libraryfolders_path()
+        .ok_or_else(|| Error::SteamConfigNotFound(PathBuf::from("libraryfolders.vdf")))?;
+    let contents = fs::read_to_string(&path)?;
+    let updated = library::move_libraryfolders_app(&contents, appid, from, to)
+        .ok_or_else(|| Error::Parse(format!("failed to update {}", path.display())))?;
+    vdf_history::write(&path, updated)?;
+    Ok(())
+}
+
+/// Move the game `appid` to the Steam library at `target`, reporting the
+/// bytes moved so far. Nothing is moved if the target already has any of
+/// the game's files or too little free space.
+pub fn move_game(appid: u32, target: &Path, progress: Progress) -> Result<MovedGame> {
+    let libraries = steam::get_steam_libraries()?;
+    let target_canon =
+        fs::canonicalize(target).map_err(|_| Error::LibraryNotFound(target.to_path_buf()))?;
+    let to = libraries
+        .iter()
+        .find(|lib| fs::canonicalize(lib.path()).is_ok_and(|p| p == target_canon))
+        .ok_or_else(|| Error::LibraryNotFound(target.to_path_buf()))?;
+    let manifest_name = format!("appmanifest_{}.acf", appid);
+    let from = libraries
+        .iter()
+        .find(|lib| lib.steamapps_path().join(&manifest_name).is_file())
+        .ok_or_else(|| {
+            Error::InvalidManifest(format!("no appmanifest found for AppID {}", appid))
+        })?;
+    let manifest = from.steamapps_path().join(&manifest_name);
+    logging::operation("move_game", Some(appid), &manifest, || {
+        if fs::canonicalize(from.path())? == target_canon {
+            return Err(Error::FileSystemError(format!(
+                "AppID {} is already in {}",
+                appid,
+                target.display()
+            )));
+        }
+        if parse_appmanifest(&manifest).is_some_and(|m| m.is_updating()) {
+            return Err(Error::FileSystemError(format!(
+                "AppID {} has a download or update pending; let Steam finish it first",
+                appid
+            )));
+        }
+        let (_, installdir) = parse_appmanifest_installdir(&manifest)
+            .ok_or_else(|| Error::InvalidManifest(manifest.display().to_string()))?;
+
+        let mut moves = Vec::new();
+        for rel in game_paths(appid, &installdir) {
+            let src = from.steamapps_path().join(&rel);
+            if fs::symlink_metadata(&src).is_err() {
+                continue;
+            }
+            let dst = to.steamapps_path().join(&rel);
+            if fs::symlink_metadata(&dst).is_ok() {
+                return Err(Error::FileSystemError(format!("{} already exists", dst.display())));
+            }
+            moves.push((src, dst));
+        }
+        let new_manifest = to.steamapps_path().join(&manifest_name);
+        if new_manifest.exists() {
+            return Err(Error::FileSystemError(format!(
+                "{} already exists",
+                new_manifest.display()
+            )));
+        }
+        let sizes: Vec<u64> = moves.iter().map(|(src, _)| size(src)).collect();
+        let total: u64 = sizes.iter().sum();
+        if let Ok(free) = fs2::available_space(to.path()) {
+            if free < total {
+                return Err(Error::FileSystemError(format!(
+                    "{} has {} free, the game needs {}",
+                    to.path().display(),
+                    output::format_size(free),
+                    output::format_size(total)
+                )));
+            }
+        }
+
+        let mut done = 0;
+        let mut moved = Vec::new();
+        let mut result = Ok(());
+        for ((src, dst), size) in moves.iter().zip(sizes) {
+            log::info!("Moving {} to {}", src.display(), dst.display());
+            result = move_path(src, dst, &|copied, _| progress(done + copied, total));
+            if result.is_err() {
+                break;
+            }
+            moved.push((src.as_path(), dst.as_path()));
+            done += size;
+            progress(done, total);
+        }
+        let result = result
+            .and_then(|_| fs::copy(&manifest, &new_manifest).map(|_| ()).map_err(Error::from))
+            .and_then(|_| update_libraryfolders(appid, from.path(), to.path()));
+        if let Err(e) = result {
+            log::warn!("Moving AppID {} failed, moving its files back: {}", appid, e);
+            let _ = fs::remove_file(&new_manifest);
+            move_back(&moved);
+            return Err(e);
+        }
+        fs::remove_file(&manifest)?;
+        steam::clear_caches();
+        Ok(MovedGame {
+            from: from.path().clone(),
+            to: to.path().clone(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use tempfile::TempDir;
+
+    /// A Steam home with AppID 4040 installed in `library` and a second,
+    /// empty library at `other`. Returns the home, the game's prefix and
+    /// the second library.
+    fn two_libraries() -> (TempDir, PathBuf, PathBuf) {
+        let (home, prefix, _) = setup_steam_env(4040, false);
+        let steamapps = home.path().join("library/steamapps");
+        let other = home.path().join("other");
+        fs::create_dir_all(other.join("steamapps")).unwrap();
+        fs::write(
+            home.path().join(".steam/steam/config/libraryfolders.vdf"),
+            format!(
+                "\"libraryfolders\" {{ \"0\" {{ \"path\" \"{}\" \"apps\" {{ \"4040\" \"2048\" }} }} \"1\" {{ \"path\" \"{}\" }} }}",
+                home.path().join("library").display(),
+                other.display()
+            ),
+        )
+        .unwrap();
+        fs::write(
+            steamapps.join("appmanifest_4040.acf"),
+            "\"AppState\" { \"appid\" \"4040\" \"name\" \"Game\" \"installdir\" \"Game\" \"StateFlags\" \"4\" }",
+        )
+        .unwrap();
+        fs::create_dir_all(steamapps.join("common/Game/bin")).unwrap();
+        fs::write(steamapps.join("common/Game/bin/game.exe"), "MZ").unwrap();
+        fs::write(prefix.join("version"), "9.0-1\n").unwrap();
+        (home, prefix, other)
+    }
+
+    #[test]
+    fn test_move_game() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, prefix, other) = two_libraries();
+        let steamapps = home.path().join("library/steamapps");
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        assert!(move_game(4040, &home.path().join("library"), &|_, _| {}).is_err());
+        assert!(move_game(4040, &home.path().join("nowhere"), &|_, _| {}).is_err());
+        let moved = move_game(4040, &other, &|_, _| {}).unwrap();
+        assert_eq!(moved.to, other);
+        let moved_steamapps = other.join("steamapps");
+        assert_eq!(fs::read_to_string(moved_steamapps.join("common/Game/bin/game.exe")).unwrap(), "MZ");
+        assert!(moved_steamapps.join("compatdata/4040/version").is_file());
+        assert!(moved_steamapps.join("appmanifest_4040.acf").is_file());
+        assert!(!steamapps.join("appmanifest_4040.acf").exists());
+        assert!(!steamapps.join("common/Game").exists());
+        assert!(!prefix.exists());
+        assert_eq!(steam::find_manifest(4040), Some(moved_steamapps.join("appmanifest_4040.acf")));
+        let folders = fs::read_to_string(home.path().join(".steam/steam/config/libraryfolders.vdf")).unwrap();
+        let (first, second) = folders.split_once("\"1\"").unwrap();
+        assert!(!first.contains("\"4040\""));
+        assert!(second.contains("\"4040\""));
+        assert!(second.contains("\"2048\""));
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+
+    #[test]
+    fn test_failed_move_is_undone() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, prefix, other) = two_libraries();
+        let steamapps = home.path().join("library/steamapps");
+        fs::create_dir_all(steamapps.join("shadercache/4040")).unwrap();
+        // The shader cache cannot move, after the game files and prefix did.
+        fs::write(other.join("steamapps/shadercache"), "").unwrap();
+        let folders_vdf = home.path().join(".steam/steam/config/libraryfolders.vdf");
+        let folders = fs::read_to_string(&folders_vdf).unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        assert!(move_game(4040, &other, &|_, _| {}).is_err());
+        assert_eq!(fs::read_to_string(steamapps.join("common/Game/bin/game.exe")).unwrap(), "MZ");
+        assert!(prefix.join("version").is_file());
+        assert!(steamapps.join("shadercache/4040").is_dir());
+        assert!(steamapps.join("appmanifest_4040.acf").is_file());
+        assert!(!other.join("steamapps/common/Game").exists());
+        assert!(!other.join("steamapps/compatdata/4040").exists());
+        assert!(!other.join("steamapps/appmanifest_4040.acf").exists());
+        assert_eq!(fs::read_to_string(&folders_vdf).unwrap(), folders);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Composing the gamescope command line for a game.
+
+use crate::utils::app_config::GamescopeSettings;
+
+/// Check that the settings describe a usable gamescope session.
+pub fn validate(settings: &GamescopeSettings) -> std::result::Result<(), String> {
+    if (settings.width == 0) != (settings.height == 0) {
+        return Err("Game resolution needs both a width and a height".to_string());
+    }
+    if (settings.output_width == 0) != (settings.output_height == 0) {
+        return Err("Output resolution needs both a width and a height".to_string());
+    }
+    Ok(())
+}
+
+/// Wrapper tokens placed in front of `%command%`, from `gamescope` up to
+/// the `--` separating it from the game command.
+pub fn wrapper(settings: &GamescopeSettings) -> Vec<String> {
+    let mut args = vec!["gamescope".to_string()];
+    let mut push = |flag: &str, value: u32| {
+        if value > 0 {
+            args.push(flag.to_string());
+            args.push(value.to_string());
+        }
+    };
+    push("-w", settings.width);
+    push("-h", settings.height);
+    push("-W", settings.output_width);
+    push("-H", settings.output_height);
+    push("-r", settings.fps_limit);
+    args.push("--filter".to_string());
+    args.push(settings.upscaler.arg().to_string());
+    if settings.fullscreen {
+        args.push("-f".to_string());
+    }
+    if settings.hdr {
+        args.push("--hdr-enabled".to_string());
+    }
+    args.push("--".to_string());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::app_config::Upscaler;
+
+    #[test]
+    fn test_wrapper() {
+        let settings = GamescopeSettings {
+            width: 1280,
+            height: 720,
+            output_width: 2560,
+            output_height: 1440,
+            upscaler: Upscaler::Fsr,
+            fps_limit: 60,
+            hdr: true,
+            ..Default::default()
+        };
+        assert!(validate(&settings).is_ok());
+        assert_eq!(
+            wrapper(&settings).join(" "),
+            "gamescope -w 1280 -h 720 -W 2560 -H 1440 -r 60 --filter fsr -f --hdr-enabled --"
+        );
+        assert_eq!(
+            wrapper(&GamescopeSettings::default()).join(" "),
+            "gamescope --filter linear -f --"
+        );
+        let partial = GamescopeSettings {
+            width: 1280,
+            ..Default::default()
+        };
+        assert!(validate(&partial).is_err());
+    }
+}
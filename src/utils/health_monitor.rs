@@ -0,0 +1,161 @@
+//! Periodic health checks of the prefixes of recently played games.
+//!
+//! When the `[health]` config section enables it, the GUI and the D-Bus
+//! service run [`check`] in the background every few minutes. The problems
+//! found in each prefix are remembered in
+//! `~/.local/share/proton-prefix-manager/health.json`, so only problems
+//! that were not there on the previous check, such as a registry file
+//! truncated by a crash, are reported. The first check of a prefix only
+//! records its state.
+
+use crate::core::models::GameInfo;
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::app_config::HealthConfig;
+use crate::utils::prefix_info;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// New problems in the prefix of a game since its previous check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regression {
+    pub appid: u32,
+    pub name: String,
+    pub problems: Vec<String>,
+}
+
+/// Problems found on the last check, keyed by AppID.
+type State = BTreeMap<u32, Vec<String>>;
+
+fn state_path() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+        .join("health.json")
+}
+
+fn load_state() -> State {
+    fs::read_to_string(state_path())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &State) -> Result<()> {
+    let path = state_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Check the prefixes of the games played within the configured number of
+/// days of `now`, in seconds since the epoch, and return those with new
+/// problems.
+fn check_at(games: &[GameInfo], config: &HealthConfig, now: u64) -> Result<Vec<Regression>> {
+    let cutoff = now.saturating_sub(u64::from(config.recent_days) * 24 * 60 * 60);
+    let mut state = load_state();
+    let mut regressions = Vec::new();
+    for game in games {
+        if game.last_played() < cutoff || !game.prefix_path().is_dir() {
+            continue;
+        }
+        let problems = prefix_info::validate_wine_prefix(&game.prefix_path().join("pfx"));
+        if let Some(previous) = state.get(&game.app_id()) {
+            let new: Vec<String> = problems
+                .iter()
+                .filter(|p| !previous.contains(p))
+                .cloned()
+                .collect();
+            if !new.is_empty() {
+                log::warn!("Prefix of {} regressed: {}", game.name(), new.join(", "));
+                regressions.push(Regression {
+                    appid: game.app_id(),
+                    name: game.name().to_string(),
+                    problems: new,
+                });
+            }
+        }
+        state.insert(game.app_id(), problems);
+    }
+    save_state(&state)?;
+    Ok(regressions)
+}
+
+/// Check the prefixes of recently played `games`. See the module docs.
+pub fn check(games: &[GameInfo], config: &HealthConfig) -> Result<Vec<Regression>> {
+    check_at(games, config, now())
+}
+
+/// Like [`check`], with the games of all Steam libraries.
+pub fn check_installed(config: &HealthConfig) -> Result<Vec<Regression>> {
+    // Long running callers outlive changes made by Steam.
+    steam::clear_caches();
+    let libraries = steam::get_steam_libraries()?;
+    let games = steam::load_games_from_libraries(&libraries)?;
+    check(&games, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_reports_new_problems_once() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        let now = 1_700_000_000;
+        let prefix = home.path().join("compatdata/620");
+        let pfx = prefix.join("pfx");
+        fs::create_dir_all(pfx.join("drive_c/windows/system32")).unwrap();
+        for reg in ["system.reg", "user.reg", "userdef.reg"] {
+            fs::write(pfx.join(reg), "WINE REGISTRY Version 2\n").unwrap();
+        }
+        let played = GameInfo::new(620, "Portal 2".into(), prefix.clone(), true, now - 3600).unwrap();
+        let stale = GameInfo::new(400, "Portal".into(), prefix.clone(), true, now - 90 * 86400).unwrap();
+        let games = [played, stale];
+        let config = HealthConfig::default();
+
+        // The first check only records how the prefix looks.
+        assert!(check_at(&games, &config, now).unwrap().is_empty());
+        assert!(check_at(&games, &config, now).unwrap().is_empty());
+
+        fs::write(pfx.join("user.reg"), "").unwrap();
+        let regressions = check_at(&games, &config, now).unwrap();
+        assert_eq!(
+            regressions,
+            vec![Regression {
+                appid: 620,
+                name: "Portal 2".to_string(),
+                problems: vec!["user.reg is not a Wine registry file".to_string()],
+            }]
+        );
+        assert!(check_at(&games, &config, now).unwrap().is_empty());
+        assert!(!load_state().contains_key(&400));
+
+        match old_home {
+            Some(h) => std::env::set_var("HOME", h),
+            None => std::env::remove_var("HOME"),
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
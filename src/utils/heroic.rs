@@ -0,0 +1,221 @@
+//! Discovery of Wine prefixes created by the Heroic Games Launcher.
+//!
+//! Heroic installs Epic (via Legendary) and GOG games into ordinary Wine or
+//! Proton prefixes, so they can be backed up and restored like Steam ones.
+//! Games are identified by their store `appName` instead of a Steam AppID.
+
+use crate::utils::backup;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Store a Heroic game was installed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeroicStore {
+    Epic,
+    Gog,
+}
+
+impl HeroicStore {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeroicStore::Epic => "Epic",
+            HeroicStore::Gog => "GOG",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HeroicGame {
+    /// Store identifier used by Heroic for the game.
+    pub app_name: String,
+    pub title: String,
+    pub store: HeroicStore,
+    /// Prefix directory as configured in Heroic.
+    pub prefix: PathBuf,
+}
+
+impl HeroicGame {
+    /// Directory holding `drive_c`. Proton-based prefixes keep it in `pfx`.
+    pub fn wine_prefix(&self) -> PathBuf {
+        let pfx = self.prefix.join("pfx");
+        if pfx.is_dir() {
+            pfx
+        } else {
+            self.prefix.clone()
+        }
+    }
+
+    /// Directory backups of this game are stored in.
+    pub fn backup_dir(&self) -> PathBuf {
+        backup::backup_root().join("heroic").join(&self.app_name)
+    }
+}
+
+/// Heroic configuration directories for native and Flatpak installs.
+fn config_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(config) = dirs_next::config_dir() {
+        roots.push(config.join("heroic"));
+    }
+    if let Some(home) = dirs_next::home_dir() {
+        roots.push(home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic"));
+    }
+    roots.into_iter().filter(|p| p.is_dir()).collect()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn is_windows(platform: Option<&str>) -> bool {
+    platform.is_none_or(|p| p.eq_ignore_ascii_case("windows"))
+}
+
+/// Installed Windows games as `(app_name, title, store)`.
+fn installed_games(root: &Path) -> Vec<(String, String, HeroicStore)> {
+    let mut games = Vec::new();
+
+    let legendary = read_json(&root.join("legendaryConfig/legendary/installed.json"));
+    if let Some(Value::Object(map)) = legendary {
+        for (app_name, info) in map {
+            if !is_windows(info["platform"].as_str()) {
+                continue;
+            }
+            let title = info["title"].as_str().unwrap_or(&app_name).to_string();
+            games.push((app_name, title, HeroicStore::Epic));
+        }
+    }
+
+    let gog_titles: BTreeMap<String, String> = read_json(&root.join("store_cache/gog_library.json"))
+        .and_then(|v| v["games"].as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|g| Some((g["app_name"].as_str()?.to_string(), g["title"].as_str()?.to_string())))
+        .collect();
+    let gog = read_json(&root.join("gog_store/installed.json"));
+    for info in gog.as_ref().and_then(|v| v["installed"].as_array()).into_iter().flatten() {
+        let Some(app_name) = info["appName"].as_str() else {
+            continue;
+        };
+        if !is_windows(info["platform"].as_str()) {
+            continue;
+        }
+        let title = gog_titles
+            .get(app_name)
+            .cloned()
+            .or_else(|| {
+                info["install_path"]
+                    .as_str()
+                    .and_then(|p| Path::new(p).file_name())
+                    .map(|n| n.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| app_name.to_string());
+        games.push((app_name.to_string(), title, HeroicStore::Gog));
+    }
+
+    games
+}
+
+/// Prefix configured for a game, falling back to Heroic's default location.
+fn game_prefix(root: &Path, app_name: &str, title: &str) -> Option<PathBuf> {
+    let config = read_json(&root.join("GamesConfig").join(format!("{}.json", app_name)));
+    let configured = config
+        .as_ref()
+        .and_then(|c| c[app_name]["winePrefix"].as_str())
+        .map(PathBuf::from);
+    let prefix = configured.or_else(|| {
+        let home = dirs_next::home_dir()?;
+        Some(home.join("Games/Heroic/Prefixes/default").join(title))
+    })?;
+    prefix.is_dir().then_some(prefix)
+}
+
+/// Find installed Heroic games that have a Wine prefix on disk.
+pub fn discover() -> Vec<HeroicGame> {
+    let mut games: Vec<HeroicGame> = Vec::new();
+    for root in config_roots() {
+        for (app_name, title, store) in installed_games(&root) {
+            if games.iter().any(|g| g.app_name == app_name) {
+                continue;
+            }
+            if let Some(prefix) = game_prefix(&root, &app_name, &title) {
+                games.push(HeroicGame {
+                    app_name,
+                    title,
+                    store,
+                    prefix,
+                });
+            }
+        }
+    }
+    games.sort_by_key(|g| g.title.to_lowercase());
+    games
+}
+
+/// Look up a game by its store identifier.
+pub fn find(app_name: &str) -> Option<HeroicGame> {
+    discover().into_iter().find(|g| g.app_name == app_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_discover() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+
+        let root = home.path().join(".config/heroic");
+        fs::create_dir_all(root.join("legendaryConfig/legendary")).unwrap();
+        fs::create_dir_all(root.join("GamesConfig")).unwrap();
+        fs::create_dir_all(root.join("gog_store")).unwrap();
+        fs::write(
+            root.join("legendaryConfig/legendary/installed.json"),
+            r#"{"Fortnite": {"title": "Fortnite", "platform": "Windows"},
+                "Native": {"title": "Native Game", "platform": "Mac"}}"#,
+        )
+        .unwrap();
+        let custom = home.path().join("prefixes/fortnite");
+        fs::create_dir_all(custom.join("pfx/drive_c")).unwrap();
+        fs::write(
+            root.join("GamesConfig/Fortnite.json"),
+            format!(r#"{{"Fortnite": {{"winePrefix": "{}"}}}}"#, custom.display()),
+        )
+        .unwrap();
+        fs::write(
+            root.join("gog_store/installed.json"),
+            r#"{"installed": [{"appName": "1207658924", "platform": "windows",
+                "install_path": "/games/Unreal Tournament"}]}"#,
+        )
+        .unwrap();
+        let default = home
+            .path()
+            .join("Games/Heroic/Prefixes/default/Unreal Tournament");
+        fs::create_dir_all(default.join("drive_c")).unwrap();
+
+        let games = discover();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].title, "Fortnite");
+        assert_eq!(games[0].store, HeroicStore::Epic);
+        assert_eq!(games[0].wine_prefix(), custom.join("pfx"));
+        assert_eq!(games[1].app_name, "1207658924");
+        assert_eq!(games[1].store, HeroicStore::Gog);
+        assert_eq!(games[1].wine_prefix(), default);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+    }
+}
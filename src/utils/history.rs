@@ -0,0 +1,127 @@
+//! Timeline of the operations run on each game.
+//!
+//! Backups, restores, resets, config changes and the like are appended as
+//! one JSON object per line to
+//! `~/.local/share/proton-prefix-manager/history/<appid>.jsonl`, so the
+//! history survives prefix resets and can be read by other tools.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Operation name, e.g. `backup` or `config`.
+    pub operation: String,
+    /// Parameters of the operation, such as the path or the new settings.
+    pub details: String,
+    /// Why the operation failed, `None` if it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Entry {
+    /// Local date and time of the entry, e.g. `2024-05-01 18:30`.
+    pub fn time(&self) -> String {
+        let time = UNIX_EPOCH + Duration::from_secs(self.timestamp);
+        chrono::DateTime::<chrono::Local>::from(time)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+}
+
+fn history_dir() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+        .join("history")
+}
+
+fn history_path(appid: u32) -> PathBuf {
+    history_dir().join(format!("{}.jsonl", appid))
+}
+
+fn append(appid: u32, entry: &Entry) -> std::io::Result<()> {
+    fs::create_dir_all(history_dir())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path(appid))?;
+    writeln!(file, "{}", serde_json::to_string(entry).map_err(std::io::Error::other)?)
+}
+
+/// Add an operation to the history of `appid`. Failing to write the
+/// history is only logged, it never fails the operation itself.
+pub fn record(appid: u32, operation: &str, details: impl Into<String>, error: Option<String>) {
+    let entry = Entry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        operation: operation.to_string(),
+        details: details.into(),
+        error,
+    };
+    if let Err(e) = append(appid, &entry) {
+        log::warn!("Failed to record {} in the history of {}: {}", operation, appid, e);
+    }
+}
+
+/// Record the outcome of an operation that returned `result`.
+pub fn record_result<T, E: Display>(
+    appid: u32,
+    operation: &str,
+    details: impl Into<String>,
+    result: &Result<T, E>,
+) {
+    record(appid, operation, details, result.as_ref().err().map(|e| e.to_string()));
+}
+
+/// The history of `appid`, oldest first. Unreadable lines are skipped.
+pub fn load(appid: u32) -> Vec<Entry> {
+    fs::read_to_string(history_path(appid))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_record_and_load() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        assert!(load(620).is_empty());
+        record(620, "backup", "/prefixes/620", None);
+        record_result(620, "restore", "/prefixes/620", &Err::<(), _>("disk full"));
+        record(400, "config", "launch options: %command%", None);
+
+        let entries = load(620);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, "backup");
+        assert_eq!(entries[0].error, None);
+        assert_eq!(entries[1].error.as_deref(), Some("disk full"));
+        assert_eq!(load(400)[0].details, "launch options: %command%");
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! User scripts run before and after backup, restore and reset.
+//!
+//! Commands come from the `[hooks]` table of the config file and run
+//! through `sh -c` with these environment variables:
+//!
+//! - `PPM_HOOK`: the hook name, e.g. `post_backup`
+//! - `PPM_APP_ID`: the AppID, taken from the prefix directory name
+//! - `PPM_PREFIX`: the prefix path
+//! - `PPM_BACKUP`: the backup path, for restores and after backups
+//!
+//! A failing `pre_*` command cancels the operation. A failing `post_*`
+//! command is only logged, since the operation already happened. After the
+//! `pre_*` command of a restore or reset the prefix is snapshotted, see
+//! `utils::snapshot`. Repairs have no hooks but are snapshotted as well.
+
+use crate::error::{Error, Result};
+use crate::utils::app_config::{self, HooksConfig};
+use crate::utils::prefix_info::prefix_appid;
+use crate::utils::{logging, sandbox, snapshot};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn run(hook: &str, command: Option<&String>, prefix: &Path, backup: Option<&Path>) -> Result<()> {
+    let Some(command) = command.filter(|c| !c.trim().is_empty()) else {
+        return Ok(());
+    };
+    log::debug!("Running {} hook: {}", hook, command);
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .env("PPM_HOOK", hook)
+        .env("PPM_PREFIX", prefix)
+        .env(
+            "PPM_APP_ID",
+            prefix.file_name().unwrap_or_default().to_string_lossy().as_ref(),
+        );
+    if let Some(backup) = backup {
+        cmd.env("PPM_BACKUP", backup);
+    }
+    let status = sandbox::host_command(cmd).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::FileSystemError(format!(
+            "{} hook failed with {}",
+            hook, status
+        )))
+    }
+}
+
+fn run_post(hook: &str, command: Option<&String>, prefix: &Path, backup: Option<&Path>) {
+    if let Err(e) = run(hook, command, prefix, backup) {
+        log::warn!("{}", e);
+    }
+}
+
+fn backup_with(
+    hooks: &HooksConfig,
+    prefix: &Path,
+    action: impl FnOnce() -> Result<PathBuf>,
+) -> Result<PathBuf> {
+    run("pre_backup", hooks.pre_backup.as_ref(), prefix, None)?;
+    let backup = logging::operation("backup", prefix_appid(prefix), prefix, action)?;
+    run_post("post_backup", hooks.post_backup.as_ref(), prefix, Some(&backup));
+    Ok(backup)
+}
+
+/// Create a backup of `prefix` with `action`, running the backup hooks.
+pub fn backup(prefix: &Path, action: impl FnOnce() -> Result<PathBuf>) -> Result<PathBuf> {
+    backup_with(&app_config::load().hooks, prefix, action)
+}
+
+/// Restore `prefix` from `backup` with `action`, running the restore hooks.
+pub fn restore(
+    prefix: &Path,
+    backup: &Path,
+    action: impl FnOnce() -> Result<PathBuf>,
+) -> Result<PathBuf> {
+    let hooks = app_config::load().hooks;
+    run("pre_restore", hooks.pre_restore.as_ref(), prefix, Some(backup))?;
+    snapshot::take(prefix, "restore")?;
+    let restored = logging::operation("restore", prefix_appid(prefix), prefix, action)?;
+    run_post("post_restore", hooks.post_restore.as_ref(), prefix, Some(backup));
+    Ok(restored)
+}
+
+/// Delete `prefix` with `action`, running the reset hooks.
+pub fn reset(prefix: &Path, action: impl FnOnce() -> Result<()>) -> Result<()> {
+    let hooks = app_config::load().hooks;
+    run("pre_reset", hooks.pre_reset.as_ref(), prefix, None)?;
+    snapshot::take(prefix, "reset")?;
+    logging::operation("reset", prefix_appid(prefix), prefix, action)?;
+    run_post("post_reset", hooks.post_reset.as_ref(), prefix, None);
+    Ok(())
+}
+
+/// Repair `prefix` with `action`, snapshotting it first.
+pub fn repair<T>(prefix: &Path, action: impl FnOnce() -> Result<T>) -> Result<T> {
+    snapshot::take(prefix, "repair")?;
+    logging::operation("repair", prefix_appid(prefix), prefix, action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+    use std::fs;
+
+    #[test]
+    fn test_backup_hooks() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        // The backup is recorded in the history below HOME.
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", dir.path());
+        std::env::remove_var("XDG_DATA_HOME");
+        let prefix = dir.path().join("620");
+        let log = dir.path().join("hooks.log");
+        let hooks = HooksConfig {
+            pre_backup: Some(format!("echo \"$PPM_HOOK $PPM_APP_ID\" >> {}", log.display())),
+            post_backup: Some(format!("echo \"$PPM_HOOK $PPM_BACKUP\" >> {}", log.display())),
+            ..Default::default()
+        };
+        let backup = backup_with(&hooks, &prefix, || Ok(PathBuf::from("/backups/620/1"))).unwrap();
+        assert_eq!(backup, PathBuf::from("/backups/620/1"));
+        assert_eq!(
+            fs::read_to_string(&log).unwrap(),
+            "pre_backup 620\npost_backup /backups/620/1\n"
+        );
+
+        let failing = HooksConfig {
+            pre_backup: Some("exit 3".into()),
+            ..Default::default()
+        };
+        let mut ran = false;
+        assert!(backup_with(&failing, &prefix, || {
+            ran = true;
+            Ok(PathBuf::new())
+        })
+        .is_err());
+        assert!(!ran);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+
+    #[test]
+    fn test_repair_takes_snapshot() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", dir.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+        std::env::remove_var("XDG_DATA_HOME");
+        let prefix = dir.path().join("620");
+        fs::create_dir_all(prefix.join("pfx/dosdevices")).unwrap();
+        fs::write(prefix.join("pfx/user.reg"), "WINE REGISTRY").unwrap();
+
+        // Like the repairs themselves, replace the file rather than write
+        // into the one shared with the snapshot.
+        let fixes = repair(&prefix, || {
+            fs::remove_file(prefix.join("pfx/user.reg"))?;
+            fs::write(prefix.join("pfx/user.reg"), "repaired")?;
+            Ok(vec!["user.reg".to_string()])
+        })
+        .unwrap();
+        assert_eq!(fixes, vec!["user.reg".to_string()]);
+        let snapshots = snapshot::list("620");
+        assert_eq!(snapshots.len(), 1);
+        assert!(snapshots[0].file_name().unwrap().to_string_lossy().starts_with("pre-repair-"));
+        assert_eq!(
+            fs::read_to_string(snapshots[0].join("pfx/user.reg")).unwrap(),
+            "WINE REGISTRY"
+        );
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::sync::Mutex;
+
+/// Directory used to cache the results of online lookups.
+pub fn cache_root() -> PathBuf {
+    dirs_next::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+}
+
+#[cfg(not(test))]
+fn run_curl(url: &str, params: &[(&str, &str)], headers: &[(&str, &str)]) -> Result<Vec<u8>> {
+    use crate::utils::dependencies::{command_available, missing_message};
+
+    if !command_available("curl") {
+        return Err(Error::Network(missing_message("curl")));
+    }
+
+    let mut cmd = std::process::Command::new("curl");
+    cmd.args(["-fsSL", "--max-time", "20", "-A"])
+        .arg(concat!("proton-prefix-manager/", env!("CARGO_PKG_VERSION")));
+    for (name, value) in headers {
+        cmd.arg("-H").arg(format!("{}: {}", name, value));
+    }
+    if !params.is_empty() {
+        cmd.arg("-G");
+        for (key, value) in params {
+            cmd.arg("--data-urlencode").arg(format!("{}={}", key, value));
+        }
+    }
+    let output = cmd.arg(url).output()?;
+    if !output.status.success() {
+        return Err(Error::Network(format!(
+            "request to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+pub static HTTP_RESPONSES: Lazy<Mutex<Vec<(String, String)>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+#[cfg(test)]
+fn run_curl(url: &str, params: &[(&str, &str)], _headers: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+    let full = if query.is_empty() {
+        url.to_string()
+    } else {
+        format!("{}?{}", url, query)
+    };
+    HTTP_RESPONSES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(prefix, _)| full.starts_with(prefix.as_str()))
+        .map(|(_, body)| body.clone().into_bytes())
+        .ok_or_else(|| Error::Network(format!("no response for {}", full)))
+}
+
+/// Perform a GET request and return the response body.
+///
+/// `params` are URL-encoded and appended as the query string.
+pub fn get(url: &str, params: &[(&str, &str)]) -> Result<String> {
+    get_with_headers(url, params, &[])
+}
+
+/// Like [`get`], sending additional request headers.
+pub fn get_with_headers(
+    url: &str,
+    params: &[(&str, &str)],
+    headers: &[(&str, &str)],
+) -> Result<String> {
+    log::debug!("GET {} {:?}", url, params);
+    String::from_utf8(run_curl(url, params, headers)?)
+        .map_err(|_| Error::Network(format!("response from {} is not valid UTF-8", url)))
+}
+
+/// Download `url` to `dest`, replacing any existing file.
+pub fn download(url: &str, dest: &Path) -> Result<()> {
+    log::debug!("Downloading {} to {}", url, dest.display());
+    let body = run_curl(url, &[], &[])?;
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = dest.with_extension("part");
+    fs::write(&tmp, body)?;
+    fs::rename(&tmp, dest)?;
+    Ok(())
+}
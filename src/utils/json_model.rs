@@ -0,0 +1,393 @@
+//! Stable models for the CLI's `--json` output.
+//!
+//! Every JSON document printed by a command is one of the models below
+//! wrapped in [`Versioned`], which adds a `schema_version` field. Fields may
+//! be added without changing the version; renaming, retyping or removing a
+//! field bumps [`SCHEMA_VERSION`]. `--schema` prints the JSON schema of every
+//! output.
+
+use crate::core::models::GameInfo;
+use crate::error::Error;
+use crate::utils::diagnostics::{self, Status};
+use crate::utils::library_health::LibraryHealth;
+use crate::utils::library_overview::LibraryOverview;
+use crate::utils::runtime_cleaner::RuntimeItem;
+use crate::utils::system_info;
+use schemars::{schema_for, JsonSchema};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Version of the JSON output format.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A command's output together with the schema version it follows.
+#[derive(Serialize, JsonSchema)]
+pub struct Versioned<T> {
+    /// Version of the output format, see the README.
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T: Serialize> Versioned<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+/// A game returned by `search`.
+#[derive(Serialize, JsonSchema)]
+pub struct Game {
+    /// Steam App ID.
+    pub appid: u32,
+    /// Name from the appmanifest.
+    pub name: String,
+    /// Path of the Proton prefix, if one exists.
+    pub prefix_path: Option<PathBuf>,
+}
+
+impl From<&GameInfo> for Game {
+    fn from(game: &GameInfo) -> Self {
+        Self {
+            appid: game.app_id(),
+            name: game.name().to_string(),
+            prefix_path: game
+                .prefix_exists()
+                .then(|| game.prefix_path().to_path_buf()),
+        }
+    }
+}
+
+/// Output of `search --json`.
+#[derive(Serialize, JsonSchema)]
+pub struct SearchOutput {
+    /// Installed games whose name matches the query.
+    pub results: Vec<Game>,
+}
+
+/// Output of `prefix --json`.
+#[derive(Serialize, JsonSchema)]
+pub struct PrefixOutput {
+    /// Steam App ID.
+    pub appid: u32,
+    /// Path of the Proton prefix, if one exists.
+    pub prefix_path: Option<PathBuf>,
+}
+
+/// Health of one Steam library.
+#[derive(Serialize, JsonSchema)]
+pub struct Library {
+    /// Root of the library.
+    pub path: PathBuf,
+    /// Bytes available to the user on the library's filesystem.
+    pub free_space: Option<u64>,
+    /// Size of the library's filesystem in bytes.
+    pub total_space: Option<u64>,
+    /// Number of prefixes in `steamapps/compatdata`.
+    pub prefixes: usize,
+    /// Install folders, prefixes and shader caches without an appmanifest.
+    pub orphaned: Vec<PathBuf>,
+    /// Combined size of the orphaned data in bytes.
+    pub orphaned_size: u64,
+    /// Appmanifests that cannot be parsed.
+    pub unreadable_manifests: Vec<PathBuf>,
+    /// Human readable descriptions of permission problems.
+    pub permission_problems: Vec<String>,
+}
+
+impl From<&LibraryHealth> for Library {
+    fn from(health: &LibraryHealth) -> Self {
+        Self {
+            path: health.path.clone(),
+            free_space: health.free_space,
+            total_space: health.total_space,
+            prefixes: health.prefixes,
+            orphaned: health.orphaned.clone(),
+            orphaned_size: health.orphaned_size,
+            unreadable_manifests: health.unreadable_manifests.clone(),
+            permission_problems: health.permission_problems.clone(),
+        }
+    }
+}
+
+/// Output of `doctor --library --json`.
+#[derive(Serialize, JsonSchema)]
+pub struct LibraryReport {
+    /// Every Steam library.
+    pub libraries: Vec<Library>,
+}
+
+/// Space and games of one Steam library.
+#[derive(Serialize, JsonSchema)]
+pub struct LibrarySummary {
+    /// Root of the library.
+    pub path: PathBuf,
+    /// Bytes available to the user on the library's filesystem.
+    pub free_space: Option<u64>,
+    /// Size of the library's filesystem in bytes.
+    pub total_space: Option<u64>,
+    /// Number of games with a readable appmanifest.
+    pub games: usize,
+    /// Size of `steamapps/compatdata` in bytes.
+    pub compatdata_size: u64,
+    /// Whether less than the configured `low_space_gb` is free.
+    pub low_space: bool,
+}
+
+impl From<&LibraryOverview> for LibrarySummary {
+    fn from(lib: &LibraryOverview) -> Self {
+        Self {
+            path: lib.path.clone(),
+            free_space: lib.free_space,
+            total_space: lib.total_space,
+            games: lib.games,
+            compatdata_size: lib.compatdata_size,
+            low_space: lib.low_space,
+        }
+    }
+}
+
+/// Output of `list --libraries --json`.
+#[derive(Serialize, JsonSchema)]
+pub struct LibrariesOutput {
+    /// Every Steam library.
+    pub libraries: Vec<LibrarySummary>,
+}
+
+/// Something the runtime cleaner found.
+#[derive(Serialize, JsonSchema)]
+pub struct CleanerItem {
+    /// Kind of leftover, e.g. `Orphaned Proton Prefixes`.
+    pub group: String,
+    pub path: PathBuf,
+    /// Steam App ID the item belongs to, if known.
+    pub appid: Option<u32>,
+    /// Why the item is considered a leftover.
+    pub reason: String,
+    /// Size in bytes, for kinds where it is measured.
+    pub size: Option<u64>,
+    /// Whether the item is safe to delete; unverified items are only
+    /// deleted by hand.
+    pub verified: bool,
+}
+
+impl CleanerItem {
+    pub fn new(group: &str, item: &RuntimeItem) -> Self {
+        Self {
+            group: group.to_string(),
+            path: item.path.clone(),
+            appid: item.app_id,
+            reason: item.reason.clone(),
+            size: item.size,
+            verified: item.verified,
+        }
+    }
+}
+
+/// Report written by `clean --export` and the runtime cleaner window.
+#[derive(Serialize, JsonSchema)]
+pub struct CleanerReport {
+    /// Every item found, grouped by kind.
+    pub items: Vec<CleanerItem>,
+    /// Number of items left out because they are on the ignore list.
+    pub ignored: usize,
+}
+
+/// Result of one environment check.
+#[derive(Serialize, JsonSchema)]
+pub struct Check {
+    /// Stable name of the check, e.g. `vulkan` or `backup_space`.
+    pub name: String,
+    /// `pass`, `warn` or `fail`.
+    pub status: Status,
+    /// What was found.
+    pub message: String,
+}
+
+impl From<&diagnostics::Check> for Check {
+    fn from(check: &diagnostics::Check) -> Self {
+        Self {
+            name: check.name.to_string(),
+            status: check.status,
+            message: check.message.clone(),
+        }
+    }
+}
+
+/// Output of `doctor --json`.
+#[derive(Serialize, JsonSchema)]
+pub struct EnvironmentReport {
+    /// Every check in the order they ran.
+    pub checks: Vec<Check>,
+}
+
+/// A Vulkan device.
+#[derive(Serialize, JsonSchema)]
+pub struct Gpu {
+    /// Device name, e.g. `AMD Radeon RX 6800 (RADV NAVI21)`.
+    pub name: String,
+    /// `discrete gpu`, `integrated gpu`, `virtual gpu` or `cpu`.
+    pub device_type: String,
+    /// Highest Vulkan version the driver supports.
+    pub vulkan_version: String,
+    /// Driver name, e.g. `radv` or `NVIDIA`.
+    pub driver: String,
+    /// Driver version.
+    pub driver_version: String,
+}
+
+impl From<&system_info::Gpu> for Gpu {
+    fn from(gpu: &system_info::Gpu) -> Self {
+        Self {
+            name: gpu.name.clone(),
+            device_type: gpu.device_type.clone(),
+            vulkan_version: gpu.api_version.clone(),
+            driver: gpu.driver_name.clone(),
+            driver_version: gpu.driver_info.clone(),
+        }
+    }
+}
+
+/// Output of `info --system --json`.
+#[derive(Serialize, JsonSchema)]
+pub struct SystemInfo {
+    /// Name of the distribution from `/etc/os-release`.
+    pub os: Option<String>,
+    /// Version of the running kernel.
+    pub kernel: Option<String>,
+    /// Steam installation in use.
+    pub steam_root: Option<PathBuf>,
+    /// Vulkan devices, or `null` when `vulkaninfo` is missing or fails.
+    pub gpus: Option<Vec<Gpu>>,
+}
+
+/// Output of `GET /api/games`.
+#[derive(Serialize, JsonSchema)]
+pub struct GamesOutput {
+    /// Every installed game.
+    pub games: Vec<Game>,
+}
+
+/// Output of `GET /api/games/<appid>`.
+#[derive(Serialize, JsonSchema)]
+pub struct PrefixDetails {
+    /// Steam App ID.
+    pub appid: u32,
+    /// Name from the appmanifest.
+    pub name: String,
+    /// Path of the Proton prefix, if one exists.
+    pub prefix_path: Option<PathBuf>,
+    /// Proton version that last updated the prefix.
+    pub proton_version: Option<String>,
+    /// Whether DXVK is installed in the prefix.
+    pub dxvk: bool,
+    /// Whether VKD3D-Proton is installed in the prefix.
+    pub vkd3d: bool,
+    /// Problems that keep Wine from starting in the prefix.
+    pub problems: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskState {
+    Running,
+    Done,
+    Failed,
+}
+
+/// A background task started through the HTTP API.
+#[derive(Clone, Serialize, JsonSchema)]
+pub struct Task {
+    /// Identifier used in `/api/tasks/<id>`.
+    pub id: u64,
+    /// What the task does.
+    pub description: String,
+    /// `running`, `done` or `failed`.
+    pub state: TaskState,
+    /// Result of a finished task, e.g. the backup path or an error.
+    pub message: Option<String>,
+}
+
+/// Output of `GET /api/tasks`.
+#[derive(Serialize, JsonSchema)]
+pub struct TasksOutput {
+    /// Tasks started since the server was launched.
+    pub tasks: Vec<Task>,
+}
+
+/// A failed command with `--json`, and the body of every failed HTTP API
+/// request.
+#[derive(Serialize, JsonSchema)]
+pub struct ErrorOutput {
+    /// What went wrong.
+    pub error: String,
+    /// Stable identifier of the kind of error, e.g. `steam_not_found`.
+    pub code: String,
+    /// Path the error is about, if any.
+    pub path: Option<PathBuf>,
+}
+
+impl From<&Error> for ErrorOutput {
+    fn from(e: &Error) -> Self {
+        Self {
+            error: e.to_string(),
+            code: e.code().to_string(),
+            path: e.path().map(Path::to_path_buf),
+        }
+    }
+}
+
+/// JSON schemas of every command's output, keyed by command.
+pub fn schemas() -> serde_json::Value {
+    serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
+        "search": schema_for!(Versioned<SearchOutput>),
+        "prefix": schema_for!(Versioned<PrefixOutput>),
+        "doctor": schema_for!(Versioned<EnvironmentReport>),
+        "doctor --library": schema_for!(Versioned<LibraryReport>),
+        "list --libraries": schema_for!(Versioned<LibrariesOutput>),
+        "clean --export": schema_for!(Versioned<CleanerReport>),
+        "info --system": schema_for!(Versioned<SystemInfo>),
+        "serve GET /api/games": schema_for!(Versioned<GamesOutput>),
+        "serve GET /api/games/<appid>": schema_for!(Versioned<PrefixDetails>),
+        "serve GET /api/tasks": schema_for!(Versioned<TasksOutput>),
+        "serve GET /api/tasks/<id>": schema_for!(Versioned<Task>),
+        "errors": schema_for!(Versioned<ErrorOutput>),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_output() {
+        let output = Versioned::new(PrefixOutput {
+            appid: 620,
+            prefix_path: None,
+        });
+        let value: serde_json::Value = serde_json::from_str(&output.to_json()).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["appid"], 620);
+        assert!(value["prefix_path"].is_null());
+
+        let schemas = schemas();
+        for command in ["search", "prefix", "doctor", "doctor --library", "errors"] {
+            let properties = &schemas[command]["properties"];
+            assert!(properties["schema_version"].is_object(), "{}", command);
+        }
+        assert!(schemas["search"]["properties"]["results"].is_object());
+
+        let error = Versioned::new(ErrorOutput::from(&Error::SteamNotFound));
+        let value: serde_json::Value = serde_json::from_str(&error.to_json()).unwrap();
+        assert_eq!(value["code"], "steam_not_found");
+        assert!(value["path"].is_null());
+    }
+}
@@ -0,0 +1,205 @@
+//! Resetting a prefix while keeping its save games.
+//!
+//! The save and config locations found in the prefix are backed up before
+//! it is deleted. Proton only builds the new prefix on the game's next
+//! launch, so the saves cannot be put back right away: the reset is
+//! remembered in `~/.local/share/proton-prefix-manager/pending-saves/<appid>.json`
+//! and [`restore_pending`], run by `restore-saves` and whenever the GUI
+//! loads its game list, restores the saves once the new prefix exists and
+//! the game has been closed.
+
+use crate::error::{Error, Result};
+use crate::utils::{backup, hooks, save_locations};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+/// A saves backup waiting for its prefix to be rebuilt.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pending {
+    pub appid: u32,
+    pub prefix: PathBuf,
+    pub backup: PathBuf,
+}
+
+fn pending_dir() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+        .join("pending-saves")
+}
+
+fn pending_path(appid: u32) -> PathBuf {
+    pending_dir().join(format!("{}.json", appid))
+}
+
+/// The reset of `appid` whose saves are still to be restored, if any.
+pub fn pending(appid: u32) -> Option<Pending> {
+    let text = fs::read_to_string(pending_path(appid)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn all_pending() -> Vec<Pending> {
+    let mut list: Vec<Pending> = fs::read_dir(pending_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|text| serde_json::from_str(&text).ok())
+        .collect();
+    list.sort_by_key(|p| p.appid);
+    list
+}
+
+/// Whether Proton has set up the prefix again: it writes `version` once
+/// the Wine prefix inside is complete.
+fn rebuilt(prefix: &Path) -> bool {
+    prefix.join("version").is_file() && prefix.join("pfx/user.reg").is_file()
+}
+
+/// Back up the saves of `appid`, delete its prefix and remember to restore
+/// the saves into the new prefix. Returns the saves backup.
+pub fn reset(appid: u32, prefix: &Path) -> Result<PathBuf> {
+    let locations = save_locations::detect_prefix_locations(appid, prefix)?;
+    let saves = hooks::backup(prefix, || {
+        backup::create_saves_backup(prefix, appid, &locations)
+    })?;
+    hooks::reset(prefix, || backup::reset_prefix(prefix))?;
+    let pending = Pending {
+        appid,
+        prefix: prefix.to_path_buf(),
+        backup: saves.clone(),
+    };
+    fs::create_dir_all(pending_dir())?;
+    let json = serde_json::to_string_pretty(&pending).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(pending_path(appid), json)?;
+    Ok(saves)
+}
+
+/// Whether a process below `proc`, the procfs mount, runs in `prefix`.
+/// Proton sets `STEAM_COMPAT_DATA_PATH` and `WINEPREFIX` for the game and
+/// everything it starts, so their environment gives them away.
+fn in_use(proc: &Path, prefix: &Path) -> bool {
+    let wine_prefix = prefix.join("pfx");
+    fs::read_dir(proc)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().as_bytes().iter().all(u8::is_ascii_digit))
+        .filter_map(|e| fs::read(e.path().join("environ")).ok())
+        .any(|environ| {
+            environ.split(|&b| b == 0).any(|var| {
+                let Some(eq) = var.iter().position(|&b| b == b'=') else {
+                    return false;
+                };
+                let value = Path::new(std::ffi::OsStr::from_bytes(&var[eq + 1..]));
+                match &var[..eq] {
+                    b"STEAM_COMPAT_DATA_PATH" => value == prefix,
+                    b"WINEPREFIX" => value == wine_prefix,
+                    _ => false,
+                }
+            })
+        })
+}
+
+/// Restore the saves of the resets whose prefixes have been rebuilt since,
+/// unless the game is still running and could overwrite them. Returns the
+/// restores done, with their result. Failed and skipped restores are tried
+/// again next time.
+pub fn restore_pending() -> Vec<(Pending, Result<PathBuf>)> {
+    restore_ready(Path::new("/proc"))
+}
+
+fn restore_ready(proc: &Path) -> Vec<(Pending, Result<PathBuf>)> {
+    let mut done = Vec::new();
+    for pending in all_pending() {
+        if !rebuilt(&pending.prefix) {
+            continue;
+        }
+        if in_use(proc, &pending.prefix) {
+            log::info!("{} is running, restoring its kept saves later", pending.appid);
+            continue;
+        }
+        if !pending.backup.is_dir() {
+            log::warn!(
+                "Saves backup {} of {} is gone, not restoring it",
+                pending.backup.display(),
+                pending.appid
+            );
+            let _ = fs::remove_file(pending_path(pending.appid));
+            continue;
+        }
+        let result = hooks::restore(&pending.prefix, &pending.backup, || {
+            backup::restore_prefix(&pending.backup, &pending.prefix)
+        });
+        if result.is_ok() {
+            if let Err(e) = fs::remove_file(pending_path(pending.appid)) {
+                log::warn!("Failed to clear pending saves of {}: {}", pending.appid, e);
+            }
+        }
+        done.push((pending, result));
+    }
+    done
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_saves_come_back_after_rebuild() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        let prefix = home.path().join("compatdata/620");
+        let saves = prefix.join("pfx/drive_c/users/steamuser/Saved Games/Portal");
+        fs::create_dir_all(&saves).unwrap();
+        fs::write(saves.join("slot1.sav"), "level 3").unwrap();
+        fs::write(prefix.join("version"), "8.0-3\n").unwrap();
+        let backup = backup::create_saves_backup(&prefix, 620, std::slice::from_ref(&saves)).unwrap();
+        backup::reset_prefix(&prefix).unwrap();
+        let entry = Pending {
+            appid: 620,
+            prefix: prefix.clone(),
+            backup,
+        };
+        fs::create_dir_all(pending_dir()).unwrap();
+        fs::write(pending_path(620), serde_json::to_string(&entry).unwrap()).unwrap();
+        assert_eq!(pending(620), Some(entry));
+
+        // Nothing happens until Proton has built the new prefix.
+        assert!(restore_pending().is_empty());
+        fs::create_dir_all(prefix.join("pfx/drive_c")).unwrap();
+        fs::write(prefix.join("pfx/user.reg"), "WINE REGISTRY").unwrap();
+        fs::write(prefix.join("version"), "9.0-1\n").unwrap();
+
+        // Nor while the game runs.
+        let proc = home.path().join("proc");
+        fs::create_dir_all(proc.join("4242")).unwrap();
+        let environ = format!("HOME=/home/deck\0STEAM_COMPAT_DATA_PATH={}/\0", prefix.display());
+        fs::write(proc.join("4242/environ"), environ).unwrap();
+        assert!(restore_ready(&proc).is_empty());
+        fs::remove_dir_all(proc.join("4242")).unwrap();
+
+        let done = restore_ready(&proc);
+        assert_eq!(done.len(), 1);
+        assert!(done[0].1.is_ok());
+        assert_eq!(fs::read_to_string(saves.join("slot1.sav")).unwrap(), "level 3");
+        assert_eq!(fs::read_to_string(prefix.join("version")).unwrap(), "9.0-1\n");
+        assert_eq!(pending(620), None);
+
+        match old_home {
+            Some(h) => std::env::set_var("HOME", h),
+            None => std::env::remove_var("HOME"),
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Writing managed settings into Steam launch options.
+//!
+//! Settings stored in the tool's configuration are materialized into the
+//! launch options string whenever they are saved. Entries written for the
+//! previous settings are stripped first, so hand-written parts of the launch
+//! options survive and removed settings disappear again.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::app_config::{self, GameSettings};
+use crate::utils::{
+    gamescope, library, mangohud, manifest as manifest_utils, user_config, vdf_history, vkbasalt,
+};
+use std::fs;
+
+const COMMAND: &str = "%command%";
+
+/// Split launch options on whitespace outside of quotes. Quotes are kept so
+/// the tokens can be joined back unchanged.
+fn tokenize(launch: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in launch.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Whether `key` can be used as an environment variable name.
+pub fn valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Name of the variable assigned by a `KEY=value` token.
+fn env_key(token: &str) -> Option<&str> {
+    let (key, _) = token.split_once('=')?;
+    valid_env_key(key).then_some(key)
+}
+
+/// Format a `KEY=value` assignment, quoting the value if needed.
+pub fn format_env(key: &str, value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-_.,:/=@%".contains(c))
+    {
+        format!("{}={}", key, value)
+    } else {
+        format!("{}=\"{}\"", key, value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Environment variable pointing MangoHud at the generated config.
+const MANGOHUD_CONFIG_ENV: &str = "MANGOHUD_CONFIGFILE";
+
+/// Environment variables enabling vkBasalt and pointing it at the
+/// generated config.
+const VKBASALT_ENABLE_ENV: &str = "ENABLE_VKBASALT";
+const VKBASALT_CONFIG_ENV: &str = "VKBASALT_CONFIG_FILE";
+
+/// Tokens placed in front of `%command%` for `settings`: environment
+/// variables first, then wrapper commands with gamescope outermost.
+fn managed_tokens(appid: u32, settings: &GameSettings) -> Vec<String> {
+    let mut tokens: Vec<String> = settings.env.iter().map(|(k, v)| format_env(k, v)).collect();
+    if settings.vkbasalt.is_some() {
+        tokens.push(format_env(VKBASALT_ENABLE_ENV, "1"));
+        match vkbasalt::config_file(appid) {
+            Ok(config) => tokens.push(format_env(VKBASALT_CONFIG_ENV, &config.display().to_string())),
+            Err(e) => log::warn!("No vkBasalt config location for {}: {}", appid, e),
+        }
+    }
+    if settings.mangohud.is_some() {
+        let config = mangohud::config_file(appid).display().to_string();
+        tokens.push(format_env(MANGOHUD_CONFIG_ENV, &config));
+    }
+    if let Some(gamescope) = &settings.gamescope {
+        tokens.extend(gamescope::wrapper(gamescope));
+    }
+    if settings.mangohud.is_some() {
+        tokens.push("mangohud".to_string());
+    }
+    tokens
+}
+
+/// Drop the tokens written for `old` or `new` from the part of the launch
+/// options before `%command%`. A managed gamescope also replaces a
+/// hand-written `gamescope … --` invocation.
+fn strip_managed(prefix: &[String], old: &GameSettings, new: &GameSettings) -> Vec<String> {
+    let mangohud = old.mangohud.is_some() || new.mangohud.is_some();
+    let gamescope = old.gamescope.is_some() || new.gamescope.is_some();
+    let vkbasalt = old.vkbasalt.is_some() || new.vkbasalt.is_some();
+    let mut rest = Vec::new();
+    let mut in_gamescope = false;
+    for token in prefix {
+        if in_gamescope {
+            in_gamescope = token != "--";
+            continue;
+        }
+        let managed = match env_key(token) {
+            Some(k) => {
+                old.env.contains_key(k)
+                    || new.env.contains_key(k)
+                    || (mangohud && k == MANGOHUD_CONFIG_ENV)
+                    || (vkbasalt && (k == VKBASALT_ENABLE_ENV || k == VKBASALT_CONFIG_ENV))
+            }
+            None if gamescope && token == "gamescope" => {
+                in_gamescope = true;
+                true
+            }
+            None => mangohud && token == "mangohud",
+        };
+        if !managed {
+            rest.push(token.clone());
+        }
+    }
+    rest
+}
+
+/// Rewrite `launch` so it reflects `new` instead of `old`.
+pub fn materialize(appid: u32, launch: &str, old: &GameSettings, new: &GameSettings) -> String {
+    let tokens = tokenize(launch);
+    let managed = managed_tokens(appid, new);
+    let (prefix, args) = match tokens.iter().position(|t| t.contains(COMMAND)) {
+        Some(idx) => (&tokens[..idx], &tokens[idx + 1..]),
+        // Without %command% Steam passes everything to the game as arguments.
+        None => {
+            if managed.is_empty() {
+                return launch.trim().to_string();
+            }
+            (&tokens[..0], &tokens[..])
+        }
+    };
+
+    // Hand-written variables stay in front of the wrapper commands.
+    let rest = strip_managed(prefix, old, new);
+    let user_env = rest.iter().take_while(|t| env_key(t).is_some()).count();
+    let (new_env, wrappers): (Vec<String>, Vec<String>) =
+        managed.into_iter().partition(|t| env_key(t).is_some());
+    let mut out = new_env;
+    out.extend(rest[..user_env].iter().cloned());
+    out.extend(wrappers);
+    out.extend(rest[user_env..].iter().cloned());
+    if out.is_empty() && args.is_empty() {
+        return String::new();
+    }
+    out.push(COMMAND.to_string());
+    out.extend(args.iter().cloned());
+    out.join(" ")
+}
+
+/// Current launch options of a game.
+pub fn current(appid: u32) -> String {
+    user_config::get_launch_options(appid)
+        .or_else(|| {
+            let manifest = steam::find_manifest(appid)?;
+            let contents = library::read_manifest_cached(&manifest)?;
+            manifest_utils::get_value(&contents, "LaunchOptions")
+        })
+        .unwrap_or_default()
+}
+
+/// Write launch options to the appmanifest and Steam's localconfig.vdf.
+pub fn write(appid: u32, launch: &str) -> Result<()> {
+    let manifest = steam::find_manifest(appid).ok_or_else(|| {
+        Error::InvalidManifest(format!("appmanifest for {} not found", appid))
+    })?;
+    let contents = manifest_utils::update_or_insert(&fs::read_to_string(&manifest)?, "LaunchOptions", launch);
+    vdf_history::write(&manifest, &contents)?;
+    library::update_manifest_cache(&manifest, &contents);
+    user_config::set_launch_options(appid, launch)?;
+    Ok(())
+}
+
+/// Store managed settings for a game and materialize them into its launch
+/// options. Returns the new launch options.
+pub fn apply(appid: u32, settings: &GameSettings) -> Result<String> {
+    if let Some(gamescope) = &settings.gamescope {
+        gamescope::validate(gamescope).map_err(Error::Parse)?;
+    }
+    let mut config = app_config::load();
+    let old = config.game(appid);
+    let launch = materialize(appid, &current(appid), &old, settings);
+    mangohud::write_config(appid, settings.mangohud.as_ref())?;
+    vkbasalt::write_config(appid, settings.vkbasalt.as_ref())?;
+    write(appid, &launch)?;
+    config.set_game(appid, settings.clone());
+    app_config::save(&config)?;
+    Ok(launch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    fn env(pairs: &[(&str, &str)]) -> GameSettings {
+        GameSettings {
+            env: pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_materialize() {
+        let none = GameSettings::default();
+        let log = env(&[("PROTON_LOG", "1"), ("DXVK_HUD", "fps,devinfo")]);
+
+        assert_eq!(materialize(1, "", &none, &log), "DXVK_HUD=fps,devinfo PROTON_LOG=1 %command%");
+        assert_eq!(
+            materialize(1, "gamemoderun %command% -novid", &none, &log),
+            "DXVK_HUD=fps,devinfo PROTON_LOG=1 gamemoderun %command% -novid"
+        );
+        // Arguments without %command% are kept as game arguments.
+        assert_eq!(
+            materialize(1, "-novid", &none, &log),
+            "DXVK_HUD=fps,devinfo PROTON_LOG=1 %command% -novid"
+        );
+        assert_eq!(materialize(1, "-novid", &none, &none), "-novid");
+
+        // Stale values are replaced and removed keys are stripped.
+        let launch = "PROTON_LOG=0 DXVK_HUD=full FOO=\"a b\" %command%";
+        let new = env(&[("PROTON_LOG", "1")]);
+        assert_eq!(materialize(1, launch, &log, &new), "PROTON_LOG=1 FOO=\"a b\" %command%");
+        assert_eq!(materialize(1, "PROTON_LOG=1 %command%", &new, &none), "");
+
+        assert_eq!(format_env("WINEDLLOVERRIDES", "d3d9=n,b"), "WINEDLLOVERRIDES=d3d9=n,b");
+        assert_eq!(format_env("NAME", "two words"), "NAME=\"two words\"");
+    }
+
+    #[test]
+    fn test_materialize_mangohud() {
+        let none = GameSettings::default();
+        let hud = GameSettings {
+            mangohud: Some(Default::default()),
+            ..env(&[("PROTON_LOG", "1")])
+        };
+        let config = mangohud::config_file(620).display().to_string();
+
+        let launch = materialize(620, "FOO=1 gamemoderun %command%", &none, &hud);
+        assert_eq!(
+            launch,
+            format!(
+                "PROTON_LOG=1 {} FOO=1 mangohud gamemoderun %command%",
+                format_env(MANGOHUD_CONFIG_ENV, &config)
+            )
+        );
+        assert_eq!(materialize(620, &launch, &hud, &hud), launch);
+        assert_eq!(materialize(620, &launch, &hud, &none), "FOO=1 gamemoderun %command%");
+    }
+
+    #[test]
+    fn test_materialize_vkbasalt() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let (home, prefix, _) = setup_steam_env(620, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let none = GameSettings::default();
+        let basalt = GameSettings {
+            vkbasalt: Some(Default::default()),
+            ..Default::default()
+        };
+        let launch = materialize(620, "ENABLE_VKBASALT=0 %command%", &none, &basalt);
+        assert_eq!(
+            launch,
+            format!(
+                "ENABLE_VKBASALT=1 VKBASALT_CONFIG_FILE={} %command%",
+                prefix.join("vkBasalt.conf").display()
+            )
+        );
+        assert_eq!(materialize(620, &launch, &basalt, &none), "");
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_materialize_gamescope() {
+        let none = GameSettings::default();
+        let scope = GameSettings {
+            gamescope: Some(crate::utils::app_config::GamescopeSettings {
+                width: 1280,
+                height: 720,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let wrapped = "gamescope -w 1280 -h 720 --filter linear -f -- %command%";
+
+        // A hand-written gamescope invocation is replaced.
+        let launch = materialize(1, "gamescope -W 1920 -- %command% -novid", &none, &scope);
+        assert_eq!(launch, "gamescope -w 1280 -h 720 --filter linear -f -- %command% -novid");
+        assert_eq!(materialize(1, &launch, &scope, &scope), launch);
+        assert_eq!(materialize(1, &launch, &scope, &none), "%command% -novid");
+        assert_eq!(
+            materialize(1, "gamescope -W 1920 -- %command%", &none, &none),
+            "gamescope -W 1920 -- %command%"
+        );
+
+        // MangoHud runs inside the gamescope session.
+        let both = GameSettings {
+            mangohud: Some(Default::default()),
+            ..scope.clone()
+        };
+        let config = format_env(MANGOHUD_CONFIG_ENV, &mangohud::config_file(1).display().to_string());
+        assert_eq!(
+            materialize(1, wrapped, &scope, &both),
+            format!("{} gamescope -w 1280 -h 720 --filter linear -f -- mangohud %command%", config)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Health report for every Steam library.
+//!
+//! Collects free space, the number of prefixes, data left behind by
+//! uninstalled games, appmanifests Steam cannot read and prefixes owned by
+//! another user.
+
+use crate::core::models::SteamLibrary;
+use crate::core::steam;
+use crate::error::Result;
+use crate::utils::{backup, library, runtime_cleaner};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct LibraryHealth {
+    pub path: PathBuf,
+    pub free_space: Option<u64>,
+    pub total_space: Option<u64>,
+    pub prefixes: usize,
+    /// Install folders, prefixes and shader caches without an appmanifest.
+    pub orphaned: Vec<PathBuf>,
+    pub orphaned_size: u64,
+    pub unreadable_manifests: Vec<PathBuf>,
+    pub permission_problems: Vec<String>,
+}
+
+impl LibraryHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.orphaned.is_empty()
+            && self.unreadable_manifests.is_empty()
+            && self.permission_problems.is_empty()
+    }
+}
+
+fn unreadable_manifests(steamapps: &Path) -> Vec<PathBuf> {
+    let mut manifests: Vec<PathBuf> = fs::read_dir(steamapps)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("appmanifest_") && n.ends_with(".acf"))
+        })
+        .filter(|p| library::parse_appmanifest(p).is_none())
+        .collect();
+    manifests.sort();
+    manifests
+}
+
+/// Directories Steam cannot write to and prefixes that belong to a
+/// different user than the library, usually left by running Steam as root.
+fn permission_problems(lib: &SteamLibrary) -> Vec<String> {
+    let steamapps = lib.steamapps_path();
+    let mut problems = Vec::new();
+    for dir in [steamapps.clone(), lib.compatdata_path(), steamapps.join("common")] {
+        match fs::metadata(&dir) {
+            Ok(meta) if meta.permissions().readonly() => {
+                problems.push(format!("{} is read-only", dir.display()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                problems.push(format!("{} is not accessible", dir.display()))
+            }
+            _ => {}
+        }
+    }
+    let Ok(owner) = fs::metadata(&steamapps).map(|m| m.uid()) else {
+        return problems;
+    };
+    let Ok(entries) = fs::read_dir(lib.compatdata_path()) else {
+        return problems;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.uid() != owner => problems.push(format!(
+                "{} is owned by another user (uid {})",
+                path.display(),
+                meta.uid()
+            )),
+            Ok(_) => {
+                if let Err(e) = fs::read_dir(&path) {
+                    if e.kind() == std::io::ErrorKind::PermissionDenied {
+                        problems.push(format!("{} is not readable", path.display()));
+                    }
+                }
+            }
+            Err(e) => problems.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    problems
+}
+
+fn count_prefixes(compatdata: &Path) -> usize {
+    fs::read_dir(compatdata)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .count()
+}
+
+/// Health of a single library. `orphaned` is every orphaned path known for
+/// all libraries; only the ones inside this library are kept.
+fn check_library(lib: &SteamLibrary, orphaned: &[PathBuf]) -> LibraryHealth {
+    let orphaned: Vec<PathBuf> = orphaned
+        .iter()
+        .filter(|p| p.starts_with(lib.path()))
+        .cloned()
+        .collect();
+    let orphaned_size = orphaned
+        .iter()
+        .map(|p| backup::dir_size(p).unwrap_or(0))
+        .sum();
+    LibraryHealth {
+        path: lib.path().clone(),
+        free_space: fs2::available_space(lib.path()).ok(),
+        total_space: fs2::total_space(lib.path()).ok(),
+        prefixes: count_prefixes(&lib.compatdata_path()),
+        orphaned,
+        orphaned_size,
+        unreadable_manifests: unreadable_manifests(&lib.steamapps_path()),
+        permission_problems: permission_problems(lib),
+    }
+}
+
+/// Health of every Steam library.
+pub fn check_all() -> Result<Vec<LibraryHealth>> {
+    let libraries = steam::get_steam_libraries()?;
+    let scan = runtime_cleaner::scan();
+    let orphaned: Vec<PathBuf> = scan
+        .install_folders
+        .iter()
+        .chain(&scan.prefixes)
+        .chain(&scan.shader_caches)
+        .chain(&scan.workshop)
+        .map(|item| item.path.clone())
+        .collect();
+    Ok(libraries
+        .iter()
+        .map(|lib| check_library(lib, &orphaned))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_check_all() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, prefix, _) = setup_steam_env(4040, false);
+        let steamapps = home.path().join("library/steamapps");
+        fs::write(
+            steamapps.join("appmanifest_4040.acf"),
+            "\"AppState\" { \"appid\" \"4040\" \"name\" \"Game\" \"installdir\" \"Game\" }",
+        )
+        .unwrap();
+        fs::write(steamapps.join("appmanifest_4041.acf"), "not a manifest").unwrap();
+        fs::create_dir_all(steamapps.join("common/Game")).unwrap();
+        fs::create_dir_all(steamapps.join("compatdata/4042/pfx")).unwrap();
+        fs::write(steamapps.join("compatdata/4042/pfx/user.reg"), "1234").unwrap();
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let report = check_all().unwrap();
+        assert_eq!(report.len(), 1);
+        let health = &report[0];
+        assert_eq!(health.prefixes, 2);
+        assert_eq!(health.orphaned, vec![steamapps.join("compatdata/4042")]);
+        assert_eq!(health.orphaned_size, 4);
+        assert_eq!(
+            health.unreadable_manifests,
+            vec![steamapps.join("appmanifest_4041.acf")]
+        );
+        assert!(health.permission_problems.is_empty());
+        assert!(health.free_space.is_some());
+        assert!(!health.is_healthy());
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Overview of every Steam library: disk space, installed games and the
+//! space taken by their prefixes.
+//!
+//! Cheaper than the health report of `utils::library_health`, which also
+//! scans for leftovers. A library is low on space when less than the
+//! `low_space_gb` of the `[libraries]` config section is free.
+
+use crate::core::models::SteamLibrary;
+use crate::core::steam;
+use crate::error::Result;
+use crate::utils::app_config::{self, LibrariesConfig};
+use crate::utils::{backup, library};
+use std::fs;
+use std::path::PathBuf;
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+#[derive(Clone, Debug)]
+pub struct LibraryOverview {
+    pub path: PathBuf,
+    pub free_space: Option<u64>,
+    pub total_space: Option<u64>,
+    /// Games with a readable appmanifest.
+    pub games: usize,
+    /// Combined size of `steamapps/compatdata`.
+    pub compatdata_size: u64,
+    /// Less free space than configured is left.
+    pub low_space: bool,
+}
+
+fn count_games(lib: &SteamLibrary) -> usize {
+    fs::read_dir(lib.steamapps_path())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("appmanifest_") && n.ends_with(".acf"))
+        })
+        .filter(|p| library::parse_appmanifest(p).is_some())
+        .count()
+}
+
+fn overview(lib: &SteamLibrary, config: &LibrariesConfig) -> LibraryOverview {
+    let free_space = fs2::available_space(lib.path()).ok();
+    LibraryOverview {
+        path: lib.path().clone(),
+        free_space,
+        total_space: fs2::total_space(lib.path()).ok(),
+        games: count_games(lib),
+        compatdata_size: backup::dir_size(&lib.compatdata_path()).unwrap_or(0),
+        low_space: free_space.is_some_and(|free| free < u64::from(config.low_space_gb) * GB),
+    }
+}
+
+/// Overview of every Steam library.
+pub fn all() -> Result<Vec<LibraryOverview>> {
+    let config = app_config::load().libraries;
+    Ok(steam::get_steam_libraries()?
+        .iter()
+        .map(|lib| overview(lib, &config))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_overview() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, prefix, _) = setup_steam_env(4040, false);
+        let steamapps = home.path().join("library/steamapps");
+        fs::write(
+            steamapps.join("appmanifest_4040.acf"),
+            "\"AppState\" { \"appid\" \"4040\" \"name\" \"Game\" \"installdir\" \"Game\" }",
+        )
+        .unwrap();
+        fs::write(steamapps.join("appmanifest_4041.acf"), "not a manifest").unwrap();
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        fs::write(prefix.join("pfx/user.reg"), "1234").unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let libraries = steam::get_steam_libraries().unwrap();
+        let lib = overview(&libraries[0], &LibrariesConfig::default());
+        assert_eq!(lib.path, home.path().join("library"));
+        assert_eq!(lib.games, 1);
+        assert_eq!(lib.compatdata_size, 4);
+        assert!(lib.total_space.is_some());
+        // No filesystem has this much free space.
+        let config = LibrariesConfig {
+            low_space_gb: u32::MAX,
+        };
+        assert!(overview(&libraries[0], &config).low_space);
+        let config = LibrariesConfig { low_space_gb: 0 };
+        assert!(!overview(&libraries[0], &config).low_space);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -1,7 +1,224 @@
-use env_logger::{Builder, Env};
+//! Log setup shared by the CLI and the GUI.
+//!
+//! `-v` raises the level of this application's own modules, `--log` sets
+//! the level of single modules, and `RUST_LOG` is applied last for anything
+//! else. With `--log-format json` every record is printed as one JSON
+//! object per line, and [`operation`] events carry their fields as keys.
 
-/// Initialize logging with optional debug output.
-pub fn init(debug: bool) {
-    let env = Env::default().default_filter_or(if debug { "debug" } else { "info" });
-    Builder::from_env(env).init();
+use crate::error::Result;
+use crate::utils::history;
+use clap::ValueEnum;
+use env_logger::Builder;
+use log::kv::{self, Key, Value, VisitSource, VisitValue};
+use log::LevelFilter;
+use serde_json::{Map, Value as Json};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Crates whose modules `--log` names are looked up in.
+const CRATES: [&str; 2] = ["proton_prefix_manager", "proton_prefix_manager_lib"];
+
+/// Parent modules searched for a short module name like `user_config`.
+const PARENTS: [&str; 5] = ["", "cli::", "core::", "gui::", "utils::"];
+
+/// Parse a `--log` value such as `user_config=trace`.
+pub fn parse_module_level(value: &str) -> std::result::Result<(String, LevelFilter), String> {
+    let (module, level) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODULE=LEVEL, got '{}'", value))?;
+    let level = LevelFilter::from_str(level)
+        .map_err(|_| format!("unknown log level '{}'", level))?;
+    Ok((module.to_string(), level))
+}
+
+/// Level of the application's modules and of everything else for `-v`
+/// repeated `verbosity` times.
+fn levels(verbosity: u8) -> (LevelFilter, LevelFilter) {
+    match verbosity {
+        0 => (LevelFilter::Info, LevelFilter::Info),
+        1 => (LevelFilter::Debug, LevelFilter::Info),
+        2 => (LevelFilter::Trace, LevelFilter::Info),
+        _ => (LevelFilter::Trace, LevelFilter::Trace),
+    }
+}
+
+/// Full module paths a `--log` module name refers to. Names containing
+/// `::` are used as given.
+fn module_paths(module: &str) -> Vec<String> {
+    if module.contains("::") {
+        return vec![module.to_string()];
+    }
+    CRATES
+        .iter()
+        .flat_map(|krate| {
+            PARENTS
+                .iter()
+                .map(move |parent| format!("{}::{}{}", krate, parent, module))
+        })
+        .collect()
+}
+
+/// Converts a log value into JSON, keeping numbers, booleans and nulls.
+struct JsonValue(Json);
+
+impl<'v> VisitValue<'v> for JsonValue {
+    fn visit_any(&mut self, value: Value) -> std::result::Result<(), kv::Error> {
+        self.0 = Json::from(value.to_string());
+        Ok(())
+    }
+
+    fn visit_null(&mut self) -> std::result::Result<(), kv::Error> {
+        self.0 = Json::Null;
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> std::result::Result<(), kv::Error> {
+        self.0 = Json::from(value);
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> std::result::Result<(), kv::Error> {
+        self.0 = Json::from(value);
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> std::result::Result<(), kv::Error> {
+        self.0 = Json::from(value);
+        Ok(())
+    }
+}
+
+/// Collects the key-value pairs of a record into a JSON object.
+struct JsonFields<'a>(&'a mut Map<String, Json>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> std::result::Result<(), kv::Error> {
+        let mut json = JsonValue(Json::Null);
+        value.visit(&mut json)?;
+        self.0.insert(key.to_string(), json.0);
+        Ok(())
+    }
+}
+
+fn json_record(record: &log::Record, timestamp: String) -> Json {
+    let mut object = Map::new();
+    object.insert("timestamp".into(), timestamp.into());
+    object.insert("level".into(), record.level().as_str().into());
+    object.insert("target".into(), record.target().into());
+    object.insert("message".into(), record.args().to_string().into());
+    let _ = record.key_values().visit(&mut JsonFields(&mut object));
+    Json::Object(object)
+}
+
+/// Run a significant action and log it as an event with the operation
+/// name, AppID, path, duration and result. Actions on a game are also
+/// added to its [`history`].
+pub fn operation<T>(
+    name: &str,
+    appid: Option<u32>,
+    path: &Path,
+    action: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let start = Instant::now();
+    let result = action();
+    let duration_ms = start.elapsed().as_millis() as u64;
+    match &result {
+        Ok(_) => log::info!(
+            operation = name, appid = appid, path:% = path.display(), duration_ms = duration_ms, result = "ok";
+            "{} of {} finished in {} ms", name, path.display(), duration_ms
+        ),
+        Err(e) => log::warn!(
+            operation = name, appid = appid, path:% = path.display(), duration_ms = duration_ms, result = "error", error:% = e;
+            "{} of {} failed after {} ms: {}", name, path.display(), duration_ms, e
+        ),
+    }
+    if let Some(appid) = appid {
+        history::record_result(appid, name, path.display().to_string(), &result);
+    }
+    result
+}
+
+/// Initialize logging for `-v` repeated `verbosity` times and the module
+/// levels given with `--log`.
+pub fn init(verbosity: u8, modules: &[(String, LevelFilter)], format: LogFormat) {
+    let (own, other) = levels(verbosity);
+    let mut builder = Builder::new();
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let timestamp = buf.timestamp_millis().to_string();
+            writeln!(buf, "{}", json_record(record, timestamp))
+        });
+    }
+    builder.filter_level(other);
+    for krate in CRATES {
+        builder.filter_module(krate, own);
+    }
+    for (module, level) in modules {
+        for path in module_paths(module) {
+            builder.filter_module(&path, *level);
+        }
+    }
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse_filters(&filters);
+    }
+    builder.init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_levels() {
+        assert_eq!(
+            parse_module_level("user_config=trace").unwrap(),
+            ("user_config".to_string(), LevelFilter::Trace)
+        );
+        assert!(parse_module_level("user_config").is_err());
+        assert!(parse_module_level("user_config=loud").is_err());
+
+        let paths = module_paths("user_config");
+        assert!(paths.contains(&"proton_prefix_manager_lib::utils::user_config".to_string()));
+        assert!(paths.contains(&"proton_prefix_manager::user_config".to_string()));
+        assert_eq!(module_paths("zbus::proxy"), vec!["zbus::proxy".to_string()]);
+
+        assert_eq!(levels(0), (LevelFilter::Info, LevelFilter::Info));
+        assert_eq!(levels(2), (LevelFilter::Trace, LevelFilter::Info));
+        assert_eq!(levels(5), (LevelFilter::Trace, LevelFilter::Trace));
+    }
+
+    #[test]
+    fn test_json_record() {
+        let path = Path::new("/prefixes/620").display();
+        let kvs: &[(&str, Value)] = &[
+            ("operation", Value::from("backup")),
+            ("appid", Value::from(620u32)),
+            ("path", Value::from_display(&path)),
+            ("error", Value::null()),
+        ];
+        let record = log::Record::builder()
+            .args(format_args!("backup finished"))
+            .level(log::Level::Info)
+            .target("proton_prefix_manager::utils::hooks")
+            .key_values(&kvs)
+            .build();
+        let json = json_record(&record, "2024-01-01T00:00:00.000Z".into());
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["message"], "backup finished");
+        assert_eq!(json["operation"], "backup");
+        assert_eq!(json["appid"], 620);
+        assert_eq!(json["path"], "/prefixes/620");
+        assert!(json["error"].is_null());
+    }
 }
@@ -0,0 +1,80 @@
+//! Generated per-game MangoHud configuration files.
+
+use crate::error::Result;
+use crate::utils::app_config::{self, MangoHudSettings};
+use std::fs;
+use std::path::PathBuf;
+
+/// Config file MangoHud is pointed at through `MANGOHUD_CONFIGFILE`.
+pub fn config_file(appid: u32) -> PathBuf {
+    app_config::config_path()
+        .with_file_name("mangohud")
+        .join(format!("{}.conf", appid))
+}
+
+/// Render a MangoHud config. Disabled metrics are written as `name=0` so
+/// MangoHud's defaults don't bring them back.
+pub fn render(settings: &MangoHudSettings) -> String {
+    let toggles = [
+        ("fps", settings.fps),
+        ("frametime", settings.frametime),
+        ("frame_timing", settings.frame_timing),
+        ("cpu_stats", settings.cpu_stats),
+        ("cpu_temp", settings.cpu_temp),
+        ("gpu_stats", settings.gpu_stats),
+        ("gpu_temp", settings.gpu_temp),
+        ("ram", settings.ram),
+        ("vram", settings.vram),
+    ];
+    let mut out = String::from("# Generated by proton-prefix-manager\n");
+    if settings.fps_limit > 0 {
+        out.push_str(&format!("fps_limit={}\n", settings.fps_limit));
+    }
+    for (name, enabled) in toggles {
+        if enabled {
+            out.push_str(&format!("{}\n", name));
+        } else {
+            out.push_str(&format!("{}=0\n", name));
+        }
+    }
+    out
+}
+
+/// Write or remove the config file of a game.
+pub fn write_config(appid: u32, settings: Option<&MangoHudSettings>) -> Result<()> {
+    let path = config_file(appid);
+    match settings {
+        Some(settings) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, render(settings))?;
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let settings = MangoHudSettings {
+            fps_limit: 60,
+            ram: true,
+            ..Default::default()
+        };
+        let conf = render(&settings);
+        assert!(conf.contains("fps_limit=60\n"));
+        assert!(conf.contains("\nfps\n"));
+        assert!(conf.contains("\nram\n"));
+        assert!(conf.contains("\ngpu_temp=0\n"));
+        assert!(!render(&MangoHudSettings::default()).contains("fps_limit"));
+    }
+}
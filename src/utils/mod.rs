@@ -1,11 +1,55 @@
 // Utility/helper functions
-pub mod backup;
+pub mod anticheat;
+pub mod app_config;
+pub mod archive;
+pub mod artwork;
+pub mod cloud_sync;
+pub mod config_undo;
 pub mod dependencies;
-pub mod library;
+pub mod diagnostics;
+pub mod disk_size;
+pub mod file_search;
+pub mod game_move;
+pub mod gamescope;
+pub mod health_monitor;
+pub mod heroic;
+pub mod history;
+pub mod hooks;
+pub mod http;
+pub mod json_model;
+pub mod keep_saves;
+pub mod launch_options;
+pub mod library_health;
+pub mod library_overview;
 pub mod logging;
+pub mod mangohud;
 pub mod manifest;
+pub mod notes;
 pub mod output;
+pub mod prefix_export;
+pub mod prefix_import;
+pub mod prefix_junk;
+pub mod prefix_relocate;
+pub mod prefix_software;
+pub mod profile;
+pub mod proton_ge;
+pub mod proton_runtime;
+pub mod proton_settings;
+pub mod protontricks;
+pub mod remote_backup;
 pub mod runtime_cleaner;
-pub mod steam_paths;
+#[cfg(feature = "s3")]
+pub mod s3_backup;
+pub mod save_locations;
+pub mod shader_cache;
+pub mod snapshot;
+pub mod system_info;
 pub mod terminal;
-pub mod user_config;
+pub mod vkbasalt;
+pub mod windows_version;
+pub mod wine_reg;
+
+pub use proton_prefix_manager_lib::utils::{
+    backup, backup_format, backup_manifest, library, overrides, prefix_info, prefix_repair,
+    sandbox, steam_paths, user_config, vdf_history,
+};
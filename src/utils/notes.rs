@@ -0,0 +1,90 @@
+//! Free-text notes about each game, such as the tweaks that made it run.
+//!
+//! Notes live in `~/.local/share/proton-prefix-manager/notes/<appid>.txt`,
+//! outside of Steam's files so they survive reinstalls and prefix resets.
+
+use crate::error::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+fn notes_dir() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("proton-prefix-manager")
+        .join("notes")
+}
+
+fn notes_path(appid: u32) -> PathBuf {
+    notes_dir().join(format!("{}.txt", appid))
+}
+
+/// The notes of a game, empty if there are none.
+pub fn load(appid: u32) -> String {
+    fs::read_to_string(notes_path(appid)).unwrap_or_default()
+}
+
+/// Store the notes of a game, deleting the file when they are blank.
+pub fn save(appid: u32, text: &str) -> Result<()> {
+    let path = notes_path(appid);
+    if text.trim().is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    fs::create_dir_all(notes_dir())?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Notes of every game that has some, keyed by AppID.
+pub fn all() -> HashMap<u32, String> {
+    fs::read_dir(notes_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let appid = path.file_stem()?.to_str()?.parse().ok()?;
+            (path.extension()? == "txt").then_some(())?;
+            Some((appid, fs::read_to_string(&path).ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_notes_roundtrip() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        assert_eq!(load(620), "");
+        save(620, "winetricks vcrun2019\nPROTON_USE_WINED3D=1").unwrap();
+        save(400, "runs out of the box").unwrap();
+        assert_eq!(load(620), "winetricks vcrun2019\nPROTON_USE_WINED3D=1");
+        assert_eq!(all().len(), 2);
+
+        save(400, "  ").unwrap();
+        assert!(!home
+            .path()
+            .join(".local/share/proton-prefix-manager/notes/400.txt")
+            .exists());
+        assert_eq!(all().keys().collect::<Vec<_>>(), [&620]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+}
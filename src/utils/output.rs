@@ -1,20 +1,10 @@
 use crate::core::models::GameInfo;
-use serde::Serialize;
+use crate::error::Error;
+use crate::utils::app_config::{self, OutputFormatConfig};
+use crate::utils::json_model::{self, Versioned};
+#[cfg(not(test))]
 use std::path::PathBuf;
 
-#[derive(Serialize)]
-pub struct SearchResult {
-    pub appid: u32,
-    pub name: String,
-    pub prefix_path: Option<String>,
-}
-
-#[derive(Serialize)]
-pub struct PrefixResult {
-    pub appid: u32,
-    pub prefix_path: Option<PathBuf>,
-}
-
 #[cfg_attr(test, allow(dead_code, unused))]
 #[derive(Debug)]
 pub enum OutputFormat {
@@ -24,6 +14,23 @@ pub enum OutputFormat {
     Delimited(String),
 }
 
+/// Format a byte count for display.
+pub fn format_size(size: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    let f = size as f64;
+    if f >= GB {
+        format!("{:.1} GB", f / GB)
+    } else if f >= MB {
+        format!("{:.1} MB", f / MB)
+    } else if f >= KB {
+        format!("{:.1} KB", f / KB)
+    } else {
+        format!("{} B", size)
+    }
+}
+
 #[cfg_attr(test, allow(dead_code))]
 pub fn print_search_results(results: Vec<GameInfo>, format: &OutputFormat) {
     match format {
@@ -53,19 +60,10 @@ pub fn print_search_results(results: Vec<GameInfo>, format: &OutputFormat) {
             }
         }
         OutputFormat::Json => {
-            let search_results: Vec<SearchResult> = results
-                .into_iter()
-                .map(|game| SearchResult {
-                    appid: game.app_id(),
-                    name: game.name().to_string(),
-                    prefix_path: if game.prefix_exists() {
-                        Some(game.prefix_path().display().to_string())
-                    } else {
-                        None
-                    },
-                })
-                .collect();
-            println!("{}", serde_json::to_string_pretty(&search_results).unwrap());
+            let output = json_model::SearchOutput {
+                results: results.iter().map(json_model::Game::from).collect(),
+            };
+            println!("{}", Versioned::new(output).to_json());
         }
         OutputFormat::Delimited(delimiter) => {
             for game in results {
@@ -99,11 +97,11 @@ pub fn print_prefix_result(appid: u32, prefix: Option<PathBuf>, format: &OutputF
             None => println!("prefix="),
         },
         OutputFormat::Json => {
-            let result = PrefixResult {
+            let output = json_model::PrefixOutput {
                 appid,
                 prefix_path: prefix,
             };
-            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            println!("{}", Versioned::new(output).to_json());
         }
         OutputFormat::Delimited(delimiter) => match prefix {
             Some(path) => println!("{}{}{}", appid, delimiter, path.display()),
@@ -112,6 +110,17 @@ pub fn print_prefix_result(appid: u32, prefix: Option<PathBuf>, format: &OutputF
     }
 }
 
+/// Print an error the way `format` expects: a JSON `ErrorOutput` on stdout
+/// for `--json`, a message on stderr otherwise.
+pub fn print_error(err: &Error, format: &OutputFormat) {
+    match format {
+        OutputFormat::Json => {
+            println!("{}", Versioned::new(json_model::ErrorOutput::from(err)).to_json())
+        }
+        _ => eprintln!("❌ Error: {}", err),
+    }
+}
+
 pub fn determine_format(json: bool, plain: bool, delimiter: &Option<String>) -> OutputFormat {
     if json {
         OutputFormat::Json
@@ -120,6 +129,10 @@ pub fn determine_format(json: bool, plain: bool, delimiter: &Option<String>) ->
     } else if let Some(d) = delimiter {
         OutputFormat::Delimited(d.clone())
     } else {
-        OutputFormat::Normal
+        match app_config::load().general.output_format {
+            OutputFormatConfig::Normal => OutputFormat::Normal,
+            OutputFormatConfig::Plain => OutputFormat::Plain,
+            OutputFormatConfig::Json => OutputFormat::Json,
+        }
     }
 }
@@ -0,0 +1,426 @@
+//! Export a Proton prefix for use with other Wine front-ends.
+//!
+//! The Wine prefix inside `compatdata/<appid>/pfx` is copied (or symlinked)
+//! next to the metadata the target launcher expects, so a game can be moved
+//! out of Steam without reinstalling its Windows dependencies.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::backup::copy_dir_recursive;
+use crate::utils::prefix_info::collect_prefix_info;
+use crate::utils::{profile, sandbox, steam_paths, user_config, windows_version};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Executables that are almost never the game itself.
+const EXE_BLACKLIST: [&str; 8] = [
+    "unins", "setup", "crash", "redist", "vc_redist", "dxsetup", "ue4prereq", "report",
+];
+
+/// Files written by an export.
+#[derive(Debug)]
+pub struct ExportResult {
+    /// The generated launcher metadata.
+    pub metadata: PathBuf,
+    /// Where the Wine prefix was placed.
+    pub prefix: PathBuf,
+}
+
+#[derive(Serialize)]
+struct LutrisInstaller {
+    name: String,
+    game_slug: String,
+    version: String,
+    slug: String,
+    runner: String,
+    script: LutrisScript,
+}
+
+#[derive(Serialize)]
+struct LutrisScript {
+    game: LutrisGame,
+    wine: LutrisWine,
+}
+
+#[derive(Serialize)]
+struct LutrisGame {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exe: Option<PathBuf>,
+    prefix: PathBuf,
+    arch: String,
+}
+
+#[derive(Serialize)]
+struct LutrisWine {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    dxvk: bool,
+    vkd3d: bool,
+    esync: bool,
+    fsync: bool,
+}
+
+#[derive(Serialize)]
+struct BottleConfig {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Arch")]
+    arch: String,
+    #[serde(rename = "Windows")]
+    windows: String,
+    #[serde(rename = "Runner")]
+    runner: String,
+    #[serde(rename = "Path")]
+    path: String,
+    #[serde(rename = "Custom_Path")]
+    custom_path: bool,
+    #[serde(rename = "Environment")]
+    environment: String,
+    #[serde(rename = "Creation_Date")]
+    creation_date: String,
+    #[serde(rename = "Update_Date")]
+    update_date: String,
+    #[serde(rename = "Versioning")]
+    versioning: bool,
+    #[serde(rename = "State")]
+    state: u32,
+    #[serde(rename = "Parameters")]
+    parameters: BottleParameters,
+    #[serde(rename = "DLL_Overrides")]
+    dll_overrides: BTreeMap<String, String>,
+    #[serde(rename = "Environment_Variables")]
+    environment_variables: BTreeMap<String, String>,
+    #[serde(rename = "Installed_Dependencies")]
+    installed_dependencies: Vec<String>,
+    #[serde(rename = "Programs")]
+    programs: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct BottleParameters {
+    dxvk: bool,
+    vkd3d: bool,
+}
+
+/// Turn a game name into a lowercase, dash separated identifier.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Guess the main executable of a game: the largest `.exe` near the top of
+/// the install directory that is not an installer or crash reporter.
+pub fn find_game_exe(install_dir: &Path) -> Option<PathBuf> {
+    WalkDir::new(install_dir)
+        .max_depth(3)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_lowercase();
+            name.ends_with(".exe") && !EXE_BLACKLIST.iter().any(|b| name.contains(b))
+        })
+        .max_by_key(|e| e.metadata().map(|m| m.len()).unwrap_or(0))
+        .map(|e| e.into_path())
+}
+
+/// The custom compatibility tool used by a game, if it is installed in
+/// `compatibilitytools.d` where other launchers can pick it up as a runner.
+fn custom_runner(appid: u32) -> Option<String> {
+    let tool = user_config::get_compat_tool(appid)?;
+    steam_paths::compatibilitytools_dirs()
+        .iter()
+        .any(|dir| dir.join(&tool).is_dir())
+        .then_some(tool)
+}
+
+/// Runner name Bottles uses for the system Wine installation.
+fn system_wine_runner() -> Option<String> {
+    let mut cmd = std::process::Command::new("wine");
+    cmd.arg("--version");
+    let output = sandbox::host_command(cmd).output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then(|| format!("sys-{}", version))
+}
+
+/// Copy or symlink the Wine prefix of a Proton prefix to `dest`.
+fn place_prefix(pfx: &Path, dest: &Path, link: bool) -> Result<()> {
+    if dest.exists() {
+        return Err(Error::FileSystemError(format!(
+            "{} already exists",
+            dest.display()
+        )));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if link {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(pfx, dest)?;
+        #[cfg(not(unix))]
+        copy_dir_recursive(pfx, dest)?;
+    } else {
+        copy_dir_recursive(pfx, dest)?;
+    }
+    Ok(())
+}
+
+/// Copy or symlink each top-level entry of the Wine prefix into `dest`.
+fn place_prefix_contents(pfx: &Path, dest: &Path, link: bool) -> Result<()> {
+    if dest.exists() {
+        return Err(Error::FileSystemError(format!(
+            "{} already exists",
+            dest.display()
+        )));
+    }
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(pfx)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if link {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(entry.path(), &target)?;
+            #[cfg(not(unix))]
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Locate the Wine prefix (`pfx`) of a game's Proton prefix.
+fn wine_prefix(appid: u32) -> Result<PathBuf> {
+    let libraries = steam::get_steam_libraries()?;
+    let prefix = steam::find_proton_prefix(appid, &libraries)
+        .ok_or_else(|| Error::FileSystemError(format!("Proton prefix for {} not found", appid)))?;
+    let pfx = prefix.join("pfx");
+    if !pfx.is_dir() {
+        return Err(Error::FileSystemError(format!(
+            "{} has not been initialized by Proton yet",
+            prefix.display()
+        )));
+    }
+    Ok(pfx)
+}
+
+/// Default directory Lutris installs games into.
+pub fn default_lutris_root() -> PathBuf {
+    dirs_next::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Games")
+}
+
+/// Export a game's prefix to `<dest_root>/<slug>/prefix` and write a Lutris
+/// installer script that points at it.
+pub fn export_lutris(appid: u32, dest_root: &Path, link: bool) -> Result<ExportResult> {
+    let pfx = wine_prefix(appid)?;
+    let game = steam::refresh_game_info(appid)?;
+    let slug = slugify(game.name());
+    let info = collect_prefix_info(game.prefix_path());
+    let game_dir = dest_root.join(&slug);
+    let prefix_dest = game_dir.join("prefix");
+    place_prefix(&pfx, &prefix_dest, link)?;
+
+    let installer = LutrisInstaller {
+        name: game.name().to_string(),
+        game_slug: slug.clone(),
+        version: "Steam Proton prefix".to_string(),
+        slug: format!("{}-steam-prefix", slug),
+        runner: "wine".to_string(),
+        script: LutrisScript {
+            game: LutrisGame {
+                exe: steam::find_install_dir(appid).and_then(|d| find_game_exe(&d)),
+                prefix: prefix_dest.clone(),
+                arch: "win64".to_string(),
+            },
+            wine: LutrisWine {
+                version: custom_runner(appid),
+                dxvk: info.has_dxvk,
+                vkd3d: info.has_vkd3d,
+                esync: true,
+                fsync: true,
+            },
+        },
+    };
+    let yaml = serde_yaml::to_string(&installer).map_err(|e| Error::Parse(e.to_string()))?;
+    let metadata = game_dir.join(format!("{}-lutris.yml", slug));
+    fs::write(&metadata, yaml)?;
+
+    Ok(ExportResult {
+        metadata,
+        prefix: prefix_dest,
+    })
+}
+
+/// Default directory holding Bottles bottles, preferring the Flatpak
+/// installation when it exists.
+pub fn default_bottles_root() -> PathBuf {
+    let home = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    let flatpak = home.join(".var/app/com.usebottles.bottles/data/bottles/bottles");
+    if flatpak.is_dir() {
+        return flatpak;
+    }
+    dirs_next::data_dir()
+        .unwrap_or_else(|| home.join(".local/share"))
+        .join("bottles/bottles")
+}
+
+/// Export a game's prefix as a bottle in `<dest_root>/<slug>` with a
+/// `bottle.yml` describing its runner, Windows version and DLL overrides.
+pub fn export_bottles(appid: u32, dest_root: &Path, link: bool) -> Result<ExportResult> {
+    let pfx = wine_prefix(appid)?;
+    let game = steam::refresh_game_info(appid)?;
+    let slug = slugify(game.name());
+    let info = collect_prefix_info(game.prefix_path());
+    let bottle_dir = dest_root.join(&slug);
+    place_prefix_contents(&pfx, &bottle_dir, link)?;
+
+    let system_reg = fs::read_to_string(pfx.join("system.reg")).unwrap_or_default();
+    let user_reg = fs::read_to_string(pfx.join("user.reg")).unwrap_or_default();
+    let now = chrono::Local::now()
+        .format("%Y-%m-%d %H:%M:%S%.6f")
+        .to_string();
+    let config = BottleConfig {
+        name: game.name().to_string(),
+        arch: if windows_version::is_64bit(&system_reg) { "win64" } else { "win32" }.to_string(),
+        windows: windows_version::detect_windows_version(&system_reg).to_string(),
+        runner: custom_runner(appid)
+            .or_else(system_wine_runner)
+            .unwrap_or_else(|| "sys-wine".to_string()),
+        path: slug,
+        custom_path: false,
+        environment: "Custom".to_string(),
+        creation_date: now.clone(),
+        update_date: now,
+        versioning: false,
+        state: 0,
+        parameters: BottleParameters {
+            dxvk: info.has_dxvk,
+            vkd3d: info.has_vkd3d,
+        },
+        dll_overrides: profile::parse_dll_overrides(&user_reg),
+        environment_variables: BTreeMap::new(),
+        installed_dependencies: Vec::new(),
+        programs: BTreeMap::new(),
+    };
+    let yaml = serde_yaml::to_string(&config).map_err(|e| Error::Parse(e.to_string()))?;
+    let metadata = bottle_dir.join("bottle.yml");
+    fs::write(&metadata, yaml)?;
+
+    Ok(ExportResult {
+        metadata,
+        prefix: bottle_dir,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Portal 2"), "portal-2");
+        assert_eq!(slugify("S.T.A.L.K.E.R.: Shadow of Chernobyl"), "s-t-a-l-k-e-r-shadow-of-chernobyl");
+        assert_eq!(slugify("  Hades II "), "hades-ii");
+    }
+
+    /// Create a game with a manifest, install directory and prefix.
+    fn setup_game(appid: u32) -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let steamapps = home.path().join("library/steamapps");
+        fs::write(
+            steamapps.join(format!("appmanifest_{}.acf", appid)),
+            format!(
+                "\"AppState\" {{ \"appid\" \"{}\" \"name\" \"Example Game\" \"installdir\" \"Example\" }}",
+                appid
+            ),
+        )
+        .unwrap();
+        let install = steamapps.join("common/Example");
+        fs::create_dir_all(install.join("bin")).unwrap();
+        fs::write(install.join("bin/Example.exe"), "game binary").unwrap();
+        fs::write(install.join("unins000.exe"), "a much larger uninstaller").unwrap();
+        let system32 = prefix.join("pfx/drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        fs::write(system32.join("d3d11.dll"), "").unwrap();
+        (home, prefix, install)
+    }
+
+    #[test]
+    fn test_export_lutris() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 7070;
+        let (home, _prefix, install) = setup_game(appid);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let dest = home.path().join("Games");
+        let result = export_lutris(appid, &dest, false).unwrap();
+        assert_eq!(result.prefix, dest.join("example-game/prefix"));
+        assert!(result.prefix.join("drive_c/windows/system32/d3d11.dll").exists());
+
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string(&result.metadata).unwrap()).unwrap();
+        assert_eq!(yaml["runner"].as_str(), Some("wine"));
+        assert_eq!(yaml["script"]["wine"]["dxvk"].as_bool(), Some(true));
+        assert_eq!(
+            yaml["script"]["game"]["exe"].as_str().map(PathBuf::from),
+            Some(install.join("bin/Example.exe"))
+        );
+
+        // Exporting again must not overwrite the existing copy.
+        assert!(export_lutris(appid, &dest, false).is_err());
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_export_bottles() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 7071;
+        let (home, prefix, _install) = setup_game(appid);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+        fs::write(
+            prefix.join("pfx/user.reg"),
+            "[Software\\\\Wine\\\\DllOverrides] 1700000000\n\"dinput8\"=\"native,builtin\"\n",
+        )
+        .unwrap();
+
+        let dest = home.path().join("bottles");
+        let result = export_bottles(appid, &dest, true).unwrap();
+        assert_eq!(result.prefix, dest.join("example-game"));
+        assert!(result.prefix.join("drive_c").is_symlink());
+        assert!(result.prefix.join("drive_c/windows/system32/d3d11.dll").exists());
+
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string(&result.metadata).unwrap()).unwrap();
+        assert_eq!(yaml["Name"].as_str(), Some("Example Game"));
+        assert_eq!(yaml["Path"].as_str(), Some("example-game"));
+        assert_eq!(yaml["Windows"].as_str(), Some("win10"));
+        assert_eq!(yaml["Parameters"]["dxvk"].as_bool(), Some(true));
+        assert_eq!(yaml["DLL_Overrides"]["dinput8"].as_str(), Some("native,builtin"));
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Installing an external Wine prefix, or the backup of another game's
+//! prefix, as a game's Proton prefix.
+//!
+//! Plain Wine prefixes keep `drive_c` at their root while Proton expects it
+//! inside `compatdata/<appid>/pfx` next to a `version` file, so imported
+//! prefixes are wrapped accordingly.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::backup::{self, Progress};
+use crate::utils::{hooks, logging, prefix_info};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix version written for imported Wine prefixes. Proton compares it with
+/// its own prefix version and upgrades the prefix on the next launch.
+const IMPORTED_PREFIX_VERSION: &str = "5.13-1";
+
+pub struct ImportResult {
+    /// The game's compatdata directory the prefix was installed to.
+    pub prefix: PathBuf,
+    /// Backup of the prefix that was replaced, if there was one.
+    pub backup: Option<PathBuf>,
+}
+
+/// Directory holding `drive_c` in `source`, which may use either layout.
+fn source_wine_prefix(source: &Path) -> PathBuf {
+    let pfx = source.join("pfx");
+    if pfx.join("drive_c").is_dir() {
+        pfx
+    } else {
+        source.to_path_buf()
+    }
+}
+
+/// Write the `version` file Proton needs if `prefix` has none.
+fn ensure_version(prefix: &Path) -> Result<()> {
+    let version = prefix.join("version");
+    if !version.exists() {
+        fs::write(&version, format!("{}\n", IMPORTED_PREFIX_VERSION))?;
+    }
+    Ok(())
+}
+
+/// Back up and clear `target` if it holds a prefix. Returns the backup.
+fn replace_existing(appid: u32, target: &Path, progress: Progress) -> Result<Option<PathBuf>> {
+    let has_contents = fs::read_dir(target)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if !has_contents {
+        return Ok(None);
+    }
+    let path = backup::create_backup_with_progress(target, appid, false, progress)?;
+    fs::remove_dir_all(target)?;
+    Ok(Some(path))
+}
+
+/// Restore a full backup, possibly of another game, as the prefix of
+/// `appid`, e.g. after a game's App ID changed or to clone a known-good
+/// prefix. The restore hooks run for the target prefix.
+///
+/// An existing non-empty prefix is backed up before it is replaced.
+/// `progress` is told about the bytes copied by the backup and then by the
+/// restore.
+pub fn restore_as(backup_path: &Path, appid: u32, progress: Progress) -> Result<ImportResult> {
+    if backup::is_saves_only(backup_path) {
+        return Err(Error::FileSystemError(format!(
+            "{} only holds saves and cannot be restored as another game's prefix",
+            backup_path.display()
+        )));
+    }
+    let target = steam::compatdata_dir(appid)?;
+    let mut replaced = None;
+    hooks::restore(&target, backup_path, || {
+        replaced = replace_existing(appid, &target, progress)?;
+        let prefix = backup::restore_prefix_with_progress(backup_path, &target, progress)?;
+        ensure_version(&prefix)?;
+        Ok(prefix)
+    })?;
+    Ok(ImportResult {
+        prefix: target,
+        backup: replaced,
+    })
+}
+
+/// Validate `source` and install it as the Proton prefix of `appid`.
+///
+/// An existing non-empty prefix is backed up before it is replaced.
+pub fn import_prefix(appid: u32, source: &Path) -> Result<ImportResult> {
+    logging::operation("import", Some(appid), source, || {
+        let wine_prefix = source_wine_prefix(source);
+        let problems = prefix_info::validate_wine_prefix(&wine_prefix);
+        if !wine_prefix.join("drive_c").is_dir() || !wine_prefix.join("system.reg").is_file() {
+            return Err(Error::FileSystemError(format!(
+                "{} is not a Wine prefix: {}",
+                source.display(),
+                problems.join(", ")
+            )));
+        }
+        for problem in &problems {
+            log::warn!("Importing {}: {}", source.display(), problem);
+        }
+
+        let target = steam::compatdata_dir(appid)?;
+        let backup = replace_existing(appid, &target, &|_, _| {})?;
+
+        if wine_prefix == source {
+            backup::copy_dir_recursive(source, &target.join("pfx"))?;
+        } else {
+            backup::copy_dir_recursive(source, &target)?;
+        }
+        ensure_version(&target)?;
+
+        Ok(ImportResult {
+            prefix: target,
+            backup,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    fn wine_prefix(path: &Path) {
+        fs::create_dir_all(path.join("drive_c/windows/system32")).unwrap();
+        fs::create_dir_all(path.join("dosdevices")).unwrap();
+        for reg in ["system.reg", "user.reg", "userdef.reg"] {
+            fs::write(path.join(reg), "WINE REGISTRY Version 2\n").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_import_prefix() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 8080;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+        fs::write(prefix.join("old"), "previous prefix").unwrap();
+
+        let source = home.path().join("external");
+        wine_prefix(&source);
+        assert!(import_prefix(appid, &home.path().join("missing")).is_err());
+
+        let result = import_prefix(appid, &source).unwrap();
+        assert_eq!(result.prefix, prefix);
+        assert!(prefix.join("pfx/drive_c/windows/system32").is_dir());
+        assert!(prefix.join("pfx/system.reg").exists());
+        assert!(!prefix.join("old").exists());
+        assert_eq!(
+            fs::read_to_string(prefix.join("version")).unwrap().trim(),
+            IMPORTED_PREFIX_VERSION
+        );
+        let backup = result.backup.unwrap();
+        assert!(backup.join("old").exists());
+
+        // Restoring the replaced prefix as another game's brings back its
+        // files and gives it a version file.
+        let other = home.path().join("library/steamapps/compatdata/8081");
+        fs::create_dir_all(&other).unwrap();
+        let restored = restore_as(&backup, 8081, &|_, _| {}).unwrap();
+        assert_eq!(restored.prefix, other);
+        assert!(restored.backup.is_none());
+        assert_eq!(fs::read_to_string(other.join("old")).unwrap(), "previous prefix");
+        assert!(other.join("version").exists());
+        fs::remove_dir_all(&backup).unwrap();
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
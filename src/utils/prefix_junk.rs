@@ -0,0 +1,239 @@
+//! Finding reclaimable space inside Proton prefixes.
+//!
+//! Only locations that Windows programs treat as disposable are reported:
+//! crash dumps, installer caches, temporary directories and redistributable
+//! installers left behind by game setups. Anything inside a known save
+//! location is skipped.
+
+use crate::core::steam;
+use crate::error::Result;
+use crate::utils::{backup, save_locations};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JunkKind {
+    CrashDump,
+    InstallerCache,
+    Temp,
+    Redistributable,
+}
+
+impl fmt::Display for JunkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            JunkKind::CrashDump => "Crash dumps",
+            JunkKind::InstallerCache => "Installer cache",
+            JunkKind::Temp => "Temporary files",
+            JunkKind::Redistributable => "Redistributable installers",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct JunkItem {
+    pub kind: JunkKind,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Reclaimable space found in one game's prefix.
+#[derive(Clone, Debug)]
+pub struct PrefixJunk {
+    pub app_id: u32,
+    pub prefix: PathBuf,
+    pub items: Vec<JunkItem>,
+}
+
+impl PrefixJunk {
+    pub fn total(&self) -> u64 {
+        self.items.iter().map(|i| i.size).sum()
+    }
+}
+
+/// Installer file names shipped next to games for first-run setup.
+const REDIST_PREFIXES: [&str; 6] = ["vcredist", "vc_redist", "dxsetup", "dotnetfx", "oalinst", "physx"];
+
+/// Directories whose contents are disposable, relative to `drive_c`.
+const TEMP_DIRS: [&str; 2] = ["users/steamuser/AppData/Local/Temp", "windows/temp"];
+const INSTALLER_CACHE_DIRS: [&str; 2] = [
+    "ProgramData/Package Cache",
+    "users/steamuser/AppData/Local/Package Cache",
+];
+const CRASH_DUMP_DIR: &str = "users/steamuser/AppData/Local/CrashDumps";
+
+fn size_of(path: &Path) -> u64 {
+    if path.is_dir() {
+        backup::dir_size(path).unwrap_or(0)
+    } else {
+        path.metadata().map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+fn is_redistributable(name: &str) -> bool {
+    let name = name.to_lowercase();
+    (name.ends_with(".exe") || name.ends_with(".msi"))
+        && REDIST_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+/// Known junk inside a prefix, skipping anything that overlaps `protected`.
+pub fn scan_prefix(prefix: &Path, protected: &[PathBuf]) -> Vec<JunkItem> {
+    let drive_c = prefix.join("pfx/drive_c");
+    let mut items = Vec::new();
+    let mut push = |kind: JunkKind, path: PathBuf| {
+        if protected
+            .iter()
+            .any(|p| p.starts_with(&path) || path.starts_with(p))
+        {
+            return;
+        }
+        let size = size_of(&path);
+        if size > 0 {
+            items.push(JunkItem { kind, path, size });
+        }
+    };
+
+    for dir in TEMP_DIRS {
+        if let Ok(entries) = fs::read_dir(drive_c.join(dir)) {
+            for entry in entries.flatten() {
+                push(JunkKind::Temp, entry.path());
+            }
+        }
+    }
+    for dir in INSTALLER_CACHE_DIRS {
+        let path = drive_c.join(dir);
+        if path.is_dir() {
+            push(JunkKind::InstallerCache, path);
+        }
+    }
+    let dumps = drive_c.join(CRASH_DUMP_DIR);
+    if dumps.is_dir() {
+        push(JunkKind::CrashDump, dumps);
+    }
+    // Stray dumps and redistributables outside the Windows directory and
+    // the locations reported above.
+    let covered: Vec<PathBuf> = TEMP_DIRS
+        .iter()
+        .chain(INSTALLER_CACHE_DIRS.iter())
+        .chain(std::iter::once(&CRASH_DUMP_DIR))
+        .map(|d| drive_c.join(d))
+        .collect();
+    let walker = WalkDir::new(&drive_c)
+        .into_iter()
+        .filter_entry(|e| e.depth() != 1 || e.file_name() != "windows");
+    for entry in walker.flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if covered.iter().any(|d| path.starts_with(d)) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if name.ends_with(".dmp") || name.ends_with(".mdmp") {
+            push(JunkKind::CrashDump, path.to_path_buf());
+        } else if is_redistributable(&name) {
+            push(JunkKind::Redistributable, path.to_path_buf());
+        }
+    }
+    items.sort_by(|a, b| a.kind.cmp(&b.kind).then(b.size.cmp(&a.size)));
+    items
+}
+
+/// Junk in the prefix of one game.
+pub fn scan_game(appid: u32, prefix: &Path) -> PrefixJunk {
+    PrefixJunk {
+        app_id: appid,
+        prefix: prefix.to_path_buf(),
+        items: scan_prefix(prefix, &save_locations::cached_locations(appid, prefix)),
+    }
+}
+
+/// Junk in every prefix of every library, largest first. Prefixes without
+/// junk are left out.
+pub fn scan_all() -> Result<Vec<PrefixJunk>> {
+    let mut report = Vec::new();
+    for lib in steam::get_steam_libraries()? {
+        let Ok(entries) = fs::read_dir(lib.compatdata_path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(appid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+                continue;
+            };
+            let junk = scan_game(appid, &entry.path());
+            if !junk.items.is_empty() {
+                report.push(junk);
+            }
+        }
+    }
+    report.sort_by_key(|j| std::cmp::Reverse(j.total()));
+    Ok(report)
+}
+
+/// Delete the given items. Returns the number of bytes freed.
+pub fn clean(items: &[JunkItem]) -> Result<u64> {
+    let mut freed = 0;
+    for item in items {
+        if !item.path.exists() {
+            continue;
+        }
+        let size = size_of(&item.path);
+        if item.path.is_dir() {
+            fs::remove_dir_all(&item.path)?;
+        } else {
+            fs::remove_file(&item.path)?;
+        }
+        freed += size;
+    }
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_and_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("620");
+        let drive_c = prefix.join("pfx/drive_c");
+        let local = drive_c.join("users/steamuser/AppData/Local");
+        for (path, bytes) in [
+            ("users/steamuser/AppData/Local/Temp/setup.log", 10),
+            ("users/steamuser/AppData/Local/CrashDumps/game.exe.123.dmp", 20),
+            ("ProgramData/Package Cache/{guid}/vc_redist.x64.exe", 30),
+            ("Program Files/Game/_CommonRedist/vcredist_x86.exe", 40),
+            ("users/steamuser/AppData/Local/Game/Saved/crash.dmp", 50),
+            ("users/steamuser/AppData/Local/Game/Saved/slot1.sav", 60),
+            ("windows/system32/vcredist_x64.exe", 70),
+        ] {
+            let file = drive_c.join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, vec![0u8; bytes]).unwrap();
+        }
+
+        let protected = vec![local.join("Game/Saved")];
+        let items = scan_prefix(&prefix, &protected);
+        let kinds: Vec<JunkKind> = items.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                JunkKind::CrashDump,
+                JunkKind::InstallerCache,
+                JunkKind::Temp,
+                JunkKind::Redistributable,
+            ]
+        );
+        assert_eq!(items.iter().map(|i| i.size).sum::<u64>(), 100);
+
+        assert_eq!(clean(&items).unwrap(), 100);
+        assert!(local.join("Temp").is_dir());
+        assert!(!local.join("Temp/setup.log").exists());
+        assert!(local.join("Game/Saved/crash.dmp").exists());
+        assert!(scan_prefix(&prefix, &protected).is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Moving a prefix to another drive and leaving a symlink in compatdata.
+//!
+//! Steam follows the symlink, so a relocated prefix keeps working while its
+//! data lives on a larger disk. Copies are verified before the original is
+//! removed.
+
+use crate::error::{Error, Result};
+use crate::utils::{backup, logging};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+pub use crate::utils::prefix_info::relocated_target;
+
+#[cfg(unix)]
+use std::os::unix::fs as unix_fs;
+
+/// What is compared when verifying a copy.
+#[derive(Debug, PartialEq, Eq)]
+enum Entry {
+    Dir,
+    File(u64),
+    Link(PathBuf),
+}
+
+/// Every entry below `root` by relative path.
+fn snapshot(root: &Path) -> Result<BTreeMap<PathBuf, Entry>> {
+    let mut entries = BTreeMap::new();
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry.map_err(|e| Error::FileSystemError(e.to_string()))?;
+        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path()).to_path_buf();
+        let kind = if entry.path_is_symlink() {
+            Entry::Link(fs::read_link(entry.path())?)
+        } else if entry.file_type().is_dir() {
+            Entry::Dir
+        } else {
+            Entry::File(entry.metadata().map_err(|e| Error::FileSystemError(e.to_string()))?.len())
+        };
+        entries.insert(rel, kind);
+    }
+    Ok(entries)
+}
+
+/// Copy `src` to `dst` and check that every file arrived with its size.
+/// A failed copy is removed again.
+pub(crate) fn copy_verified(src: &Path, dst: &Path, progress: backup::Progress) -> Result<()> {
+    let result = backup::copy_dir_with_progress(src, dst, progress).and_then(|_| {
+        if snapshot(src)? == snapshot(dst)? {
+            Ok(())
+        } else {
+            Err(Error::FileSystemError(format!(
+                "Copy of {} to {} does not match the original",
+                src.display(),
+                dst.display()
+            )))
+        }
+    });
+    if result.is_err() && dst.exists() {
+        let _ = fs::remove_dir_all(dst);
+    }
+    result
+}
+
+/// Move `prefix` into `target_dir` and replace it with a symlink.
+///
+/// Returns the new location of the prefix.
+pub fn relocate(prefix: &Path, target_dir: &Path) -> Result<PathBuf> {
+    logging::operation("relocate", None, prefix, || {
+        if relocated_target(prefix).is_some() {
+            return Err(Error::FileSystemError(format!(
+                "{} is already relocated",
+                prefix.display()
+            )));
+        }
+        if !prefix.is_dir() {
+            return Err(Error::FileSystemError(format!(
+                "Prefix not found: {}",
+                prefix.display()
+            )));
+        }
+        let name = prefix.file_name().ok_or_else(|| {
+            Error::FileSystemError(format!("Invalid prefix path: {}", prefix.display()))
+        })?;
+        fs::create_dir_all(target_dir)?;
+        // The symlink has to work from inside compatdata.
+        let dest = fs::canonicalize(target_dir)?.join(name);
+        if dest.exists() {
+            return Err(Error::FileSystemError(format!(
+                "{} already exists",
+                dest.display()
+            )));
+        }
+        copy_verified(prefix, &dest, &|_, _| {})?;
+        fs::remove_dir_all(prefix)?;
+        #[cfg(unix)]
+        unix_fs::symlink(&dest, prefix)?;
+        Ok(dest)
+    })
+}
+
+/// Move a relocated prefix back into compatdata and remove the symlink.
+///
+/// Returns the location the prefix was moved back from.
+pub fn bring_back(prefix: &Path) -> Result<PathBuf> {
+    logging::operation("bring_back", None, prefix, || {
+        let target = relocated_target(prefix).ok_or_else(|| {
+            Error::FileSystemError(format!("{} is not relocated", prefix.display()))
+        })?;
+        if !target.is_dir() {
+            return Err(Error::FileSystemError(format!(
+                "Relocated prefix not found: {}",
+                target.display()
+            )));
+        }
+        let name = prefix.file_name().unwrap_or_default().to_string_lossy();
+        let staging = prefix.with_file_name(format!(".{}.returning", name));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        copy_verified(&target, &staging, &|_, _| {})?;
+        fs::remove_file(prefix)?;
+        fs::rename(&staging, prefix)?;
+        fs::remove_dir_all(&target)?;
+        Ok(target)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relocate_and_bring_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("compatdata/620");
+        fs::create_dir_all(prefix.join("pfx/drive_c")).unwrap();
+        fs::write(prefix.join("pfx/system.reg"), "WINE REGISTRY").unwrap();
+        unix_fs::symlink("drive_c", prefix.join("pfx/c_link")).unwrap();
+        let other = fs::canonicalize(dir.path()).unwrap().join("other-disk");
+
+        let dest = relocate(&prefix, &other).unwrap();
+        assert_eq!(dest, other.join("620"));
+        assert_eq!(relocated_target(&prefix), Some(dest.clone()));
+        assert!(prefix.join("pfx/system.reg").exists());
+        assert!(relocate(&prefix, &other).is_err());
+
+        assert_eq!(bring_back(&prefix).unwrap(), dest);
+        assert!(relocated_target(&prefix).is_none());
+        assert!(prefix.join("pfx/system.reg").is_file());
+        assert!(fs::symlink_metadata(prefix.join("pfx/c_link")).unwrap().file_type().is_symlink());
+        assert!(!dest.exists());
+        assert!(bring_back(&prefix).is_err());
+    }
+}
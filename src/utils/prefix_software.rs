@@ -0,0 +1,287 @@
+//! Software installed inside a Wine prefix, read from its registry and
+//! `system32`.
+
+use crate::utils::wine_reg;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Keys listing installed programs, for 64- and 32-bit installers.
+const UNINSTALL_KEYS: [&str; 2] = [
+    r"Software\Microsoft\Windows\CurrentVersion\Uninstall",
+    r"Software\Wow6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
+];
+
+const NDP_KEY: &str = r"Software\Microsoft\NET Framework Setup\NDP";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RuntimeKind {
+    VcRedist,
+    DotNet,
+    DirectX,
+}
+
+impl RuntimeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuntimeKind::VcRedist => "Visual C++",
+            RuntimeKind::DotNet => ".NET",
+            RuntimeKind::DirectX => "DirectX",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Runtime {
+    pub kind: RuntimeKind,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// A program registered for uninstallation in the prefix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program {
+    pub name: String,
+    pub version: Option<String>,
+    pub publisher: Option<String>,
+    /// Windows path the program was installed to, such as
+    /// `C:\Program Files\Launcher`.
+    pub location: Option<String>,
+}
+
+/// Native DLLs that show a runtime was installed even when its installer
+/// left no uninstall entry: kind, name, file name prefix and the text an
+/// uninstall entry of the same runtime contains.
+const RUNTIME_DLLS: [(RuntimeKind, &str, &str, &str); 11] = [
+    (RuntimeKind::VcRedist, "Visual C++ 2015+ runtime", "msvcp140", "2015"),
+    (RuntimeKind::VcRedist, "Visual C++ 2013 runtime", "msvcr120", "2013"),
+    (RuntimeKind::VcRedist, "Visual C++ 2012 runtime", "msvcr110", "2012"),
+    (RuntimeKind::VcRedist, "Visual C++ 2010 runtime", "msvcr100", "2010"),
+    (RuntimeKind::VcRedist, "Visual C++ 2008 runtime", "msvcr90", "2008"),
+    (RuntimeKind::DirectX, "D3DX9", "d3dx9_", "DirectX"),
+    (RuntimeKind::DirectX, "D3DX10", "d3dx10_", "DirectX"),
+    (RuntimeKind::DirectX, "D3DX11", "d3dx11_", "DirectX"),
+    (RuntimeKind::DirectX, "D3DCompiler", "d3dcompiler_", "DirectX"),
+    (RuntimeKind::DirectX, "XAudio2", "xaudio2_", "DirectX"),
+    (RuntimeKind::DirectX, "XInput", "xinput1_", "DirectX"),
+];
+
+/// Values of every program registered for uninstallation.
+fn uninstall_entries(system_reg: &str) -> Vec<BTreeMap<String, String>> {
+    UNINSTALL_KEYS
+        .iter()
+        .flat_map(|key| wine_reg::read_subkeys(system_reg, key).into_values())
+        .collect()
+}
+
+fn runtime_kind(name: &str) -> Option<RuntimeKind> {
+    if name.contains("Visual C++") {
+        Some(RuntimeKind::VcRedist)
+    } else if name.contains(".NET") || name.contains("Wine Mono") {
+        Some(RuntimeKind::DotNet)
+    } else if name.contains("DirectX") {
+        Some(RuntimeKind::DirectX)
+    } else {
+        None
+    }
+}
+
+/// Runtimes registered in `system.reg`: uninstall entries of Visual C++,
+/// .NET and DirectX, and the .NET Framework versions set up.
+fn registry_runtimes(system_reg: &str) -> Vec<Runtime> {
+    let mut runtimes: Vec<Runtime> = uninstall_entries(system_reg)
+        .into_iter()
+        .filter_map(|mut values| {
+            let name = wine_reg::unescape(&values.remove("DisplayName")?);
+            Some(Runtime {
+                kind: runtime_kind(&name)?,
+                name,
+                version: values.remove("DisplayVersion"),
+            })
+        })
+        .collect();
+    // Versions up to 3.5 sit in their own key, 4.x in its Full and Client
+    // subkeys.
+    let v4 = wine_reg::read_subkeys(system_reg, &format!(r"{}\v4", NDP_KEY))
+        .into_values()
+        .map(|values| ("4".to_string(), values));
+    let older = wine_reg::read_subkeys(system_reg, NDP_KEY)
+        .into_iter()
+        .map(|(name, values)| (name.trim_start_matches('v').to_string(), values));
+    for (name, mut values) in older.chain(v4) {
+        let Some(version) = values.remove("Version") else {
+            continue;
+        };
+        if !runtimes.iter().any(|r| r.version.as_ref() == Some(&version)) {
+            runtimes.push(Runtime {
+                kind: RuntimeKind::DotNet,
+                name: format!(".NET Framework {}", name),
+                version: Some(version),
+            });
+        }
+    }
+    runtimes
+}
+
+/// Programs registered in `system.reg`, sorted by name.
+fn registry_programs(system_reg: &str) -> Vec<Program> {
+    let mut programs: Vec<Program> = uninstall_entries(system_reg)
+        .into_iter()
+        .filter_map(|values| {
+            let value = |name: &str| {
+                values
+                    .get(name)
+                    .map(|v| wine_reg::unescape(v))
+                    .filter(|v| !v.is_empty())
+            };
+            Some(Program {
+                name: value("DisplayName")?,
+                version: value("DisplayVersion"),
+                publisher: value("Publisher"),
+                location: value("InstallLocation"),
+            })
+        })
+        .collect();
+    programs.sort_by_key(|p| p.name.to_lowercase());
+    programs.dedup();
+    programs
+}
+
+/// Every program installed in a Wine prefix (the `pfx` directory) that
+/// registered an uninstall entry, including launchers and mods installed
+/// into the prefix.
+pub fn installed_programs(pfx: &Path) -> Vec<Program> {
+    let system_reg = fs::read_to_string(pfx.join("system.reg")).unwrap_or_default();
+    registry_programs(&system_reg)
+}
+
+/// Whether a DLL is a real Windows library rather than one of the stubs
+/// Wine puts in `system32` for its builtin DLLs.
+fn is_native_dll(path: &Path) -> bool {
+    let mut header = [0u8; 0x60];
+    let Ok(len) = fs::File::open(path).and_then(|mut f| f.read(&mut header)) else {
+        return false;
+    };
+    let header = &header[..len];
+    !header.windows(4).any(|w| w == b"Wine")
+}
+
+/// Runtimes of which native DLLs are present in the prefix, with the text
+/// of their uninstall entries.
+fn dll_runtimes(pfx: &Path) -> Vec<(Runtime, &'static str)> {
+    let files: Vec<_> = ["drive_c/windows/system32", "drive_c/windows/syswow64"]
+        .iter()
+        .flat_map(|dir| fs::read_dir(pfx.join(dir)).into_iter().flatten().flatten())
+        .map(|e| (e.file_name().to_string_lossy().to_lowercase(), e.path()))
+        .filter(|(name, _)| name.ends_with(".dll"))
+        .collect();
+    RUNTIME_DLLS
+        .iter()
+        .filter(|(_, _, prefix, _)| {
+            files
+                .iter()
+                .any(|(name, path)| name.starts_with(prefix) && is_native_dll(path))
+        })
+        .map(|(kind, name, _, marker)| {
+            let runtime = Runtime {
+                kind: *kind,
+                name: name.to_string(),
+                version: None,
+            };
+            (runtime, *marker)
+        })
+        .collect()
+}
+
+/// Visual C++ redistributables, .NET versions and DirectX components
+/// installed in a Wine prefix (the `pfx` directory), sorted by kind.
+pub fn installed_runtimes(pfx: &Path) -> Vec<Runtime> {
+    let system_reg = fs::read_to_string(pfx.join("system.reg")).unwrap_or_default();
+    let mut runtimes = registry_runtimes(&system_reg);
+    for (runtime, marker) in dll_runtimes(pfx) {
+        if !runtimes
+            .iter()
+            .any(|r| r.kind == runtime.kind && r.name.contains(marker))
+        {
+            runtimes.push(runtime);
+        }
+    }
+    runtimes.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+    runtimes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed_runtimes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pfx = dir.path();
+        fs::write(
+            pfx.join("system.reg"),
+            r#"WINE REGISTRY Version 2
+#arch=win64
+
+[Software\\Microsoft\\NET Framework Setup\\NDP\\v4\\Full] 1700000000
+"Version"="4.8.04084"
+
+[Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\{010792BA-551A-3AC0-A7EF-0FAB4156C382}] 1700000000
+#time=1da
+"DisplayName"="Microsoft Visual C++ 2015-2019 Redistributable (x64) - 14.29.30133"
+"DisplayVersion"="14.29.30133.0"
+
+[Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\EA app] 1700000000
+"DisplayName"="EA app"
+"DisplayVersion"="13.1.0.5568"
+"InstallLocation"="C:\\Program Files\\Electronic Arts\\EA Desktop\\"
+"Publisher"="Electronic Arts"
+
+[Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\Wine Mono Runtime] 1700000000
+"DisplayName"="Wine Mono Runtime"
+"DisplayVersion"="8.1.0"
+
+[Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\Wine Mono Runtime\\Nested] 1700000000
+"DisplayName"="Not a program"
+"#,
+        )
+        .unwrap();
+        let system32 = pfx.join("drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        fs::write(system32.join("msvcp140.dll"), b"MZ native").unwrap();
+        fs::write(system32.join("d3dx9_43.dll"), b"MZ native").unwrap();
+        let mut builtin = vec![0u8; 0x40];
+        builtin.extend_from_slice(b"Wine builtin DLL");
+        fs::write(system32.join("xinput1_3.dll"), builtin).unwrap();
+
+        let runtimes = installed_runtimes(pfx);
+        let names: Vec<&str> = runtimes.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "Microsoft Visual C++ 2015-2019 Redistributable (x64) - 14.29.30133",
+                ".NET Framework 4",
+                "Wine Mono Runtime",
+                "D3DX9",
+            ]
+        );
+        assert_eq!(runtimes[1].version.as_deref(), Some("4.8.04084"));
+
+        let programs = installed_programs(pfx);
+        let names: Vec<&str> = programs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "EA app",
+                "Microsoft Visual C++ 2015-2019 Redistributable (x64) - 14.29.30133",
+                "Wine Mono Runtime",
+            ]
+        );
+        assert_eq!(
+            programs[0].location.as_deref(),
+            Some(r"C:\Program Files\Electronic Arts\EA Desktop\")
+        );
+        assert_eq!(programs[0].publisher.as_deref(), Some("Electronic Arts"));
+    }
+}
@@ -0,0 +1,237 @@
+//! Per-game configuration profiles.
+//!
+//! A profile captures the settings that make up a known-good setup for a game
+//! (launch options, compatibility tool, Steam flags, Wine DLL overrides and
+//! `dxvk.conf`) so it can be written to a TOML file and applied elsewhere.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::{
+    history, library, manifest as manifest_utils, user_config, vdf_history, wine_reg,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Registry key holding DLL overrides in a prefix's `user.reg`.
+const DLL_OVERRIDES_KEY: &str = r"Software\Wine\DllOverrides";
+
+/// Settings are optional so a hand-written profile can change only a subset.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameProfile {
+    /// AppID the profile was exported from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_appid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launch_options: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compat_tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud_sync: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_update: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dll_overrides: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dxvk_conf: Option<String>,
+}
+
+/// Read the DLL overrides from the contents of a `user.reg` file.
+pub fn parse_dll_overrides(contents: &str) -> BTreeMap<String, String> {
+    wine_reg::read_values(contents, DLL_OVERRIDES_KEY)
+}
+
+/// Add or replace DLL overrides in the contents of a `user.reg` file.
+pub fn merge_dll_overrides(contents: &str, overrides: &BTreeMap<String, String>) -> String {
+    wine_reg::write_values(contents, DLL_OVERRIDES_KEY, overrides)
+}
+
+/// Capture the current configuration of a game.
+pub fn export_profile(appid: u32) -> Result<GameProfile> {
+    let manifest = steam::find_manifest(appid).ok_or_else(|| {
+        Error::InvalidManifest(format!("appmanifest for {} not found", appid))
+    })?;
+    let contents = library::read_manifest_cached(&manifest)
+        .ok_or_else(|| Error::InvalidManifest(format!("failed to read {}", manifest.display())))?;
+
+    let launch_options = user_config::get_launch_options(appid)
+        .or_else(|| manifest_utils::get_value(&contents, "LaunchOptions"))
+        .unwrap_or_default();
+    let compat_tool = user_config::get_compat_tool(appid)
+        .or_else(|| manifest_utils::get_value(&contents, "CompatToolOverride"));
+    let cloud_sync = manifest_utils::get_value(&contents, "AllowCloudSaves")
+        .is_none_or(|v| v == "1");
+    let auto_update = manifest_utils::get_value(&contents, "AutoUpdateBehavior")
+        .unwrap_or_else(|| "0".to_string());
+
+    let dll_overrides = steam::get_steam_libraries()
+        .ok()
+        .and_then(|libs| steam::find_proton_prefix(appid, &libs))
+        .and_then(|prefix| fs::read_to_string(prefix.join("pfx/user.reg")).ok())
+        .map(|reg| parse_dll_overrides(&reg))
+        .unwrap_or_default();
+    let dxvk_conf = steam::find_install_dir(appid)
+        .and_then(|dir| fs::read_to_string(dir.join("dxvk.conf")).ok());
+
+    Ok(GameProfile {
+        source_appid: Some(appid),
+        launch_options: Some(launch_options),
+        compat_tool,
+        cloud_sync: Some(cloud_sync),
+        auto_update: Some(auto_update),
+        dll_overrides,
+        dxvk_conf,
+    })
+}
+
+impl GameProfile {
+    /// The settings the profile changes, for the game's history.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(v) = &self.launch_options {
+            parts.push(format!("launch options: {}", v));
+        }
+        if let Some(v) = &self.compat_tool {
+            parts.push(format!("Proton: {}", v));
+        }
+        if let Some(v) = self.cloud_sync {
+            parts.push(format!("Steam Cloud: {}", if v { "on" } else { "off" }));
+        }
+        if let Some(v) = &self.auto_update {
+            parts.push(format!("auto update: {}", v));
+        }
+        if !self.dll_overrides.is_empty() {
+            parts.push(format!("{} DLL overrides", self.dll_overrides.len()));
+        }
+        if self.dxvk_conf.is_some() {
+            parts.push("dxvk.conf".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// Apply a profile to a game. Settings missing from the profile are left as is.
+pub fn import_profile(appid: u32, profile: &GameProfile) -> Result<()> {
+    let result = apply(appid, profile);
+    history::record_result(appid, "apply_profile", profile.summary(), &result);
+    result
+}
+
+fn apply(appid: u32, profile: &GameProfile) -> Result<()> {
+    let manifest = steam::find_manifest(appid).ok_or_else(|| {
+        Error::InvalidManifest(format!("appmanifest for {} not found", appid))
+    })?;
+    let mut contents = fs::read_to_string(&manifest)?;
+    if let Some(v) = &profile.launch_options {
+        contents = manifest_utils::update_or_insert(&contents, "LaunchOptions", v);
+        user_config::set_launch_options(appid, v)?;
+    }
+    if let Some(v) = &profile.compat_tool {
+        contents = manifest_utils::update_or_insert(&contents, "CompatToolOverride", v);
+        user_config::set_compat_tool_mapping(appid, v)?;
+    }
+    if let Some(v) = profile.cloud_sync {
+        let val = if v { "1" } else { "0" };
+        contents = manifest_utils::update_or_insert(&contents, "AllowCloudSaves", val);
+    }
+    if let Some(v) = &profile.auto_update {
+        contents = manifest_utils::update_or_insert(&contents, "AutoUpdateBehavior", v);
+    }
+    vdf_history::write(&manifest, &contents)?;
+    library::update_manifest_cache(&manifest, &contents);
+
+    if !profile.dll_overrides.is_empty() {
+        let prefix = steam::find_proton_prefix(appid, &steam::get_steam_libraries()?)
+            .ok_or_else(|| {
+                Error::FileSystemError(format!("Proton prefix for {} not found", appid))
+            })?;
+        write_dll_overrides(&prefix.join("pfx/user.reg"), &profile.dll_overrides)?;
+    }
+
+    if let Some(conf) = &profile.dxvk_conf {
+        let dir = steam::find_install_dir(appid).ok_or_else(|| {
+            Error::FileSystemError(format!("install directory for {} not found", appid))
+        })?;
+        fs::write(dir.join("dxvk.conf"), conf)?;
+    }
+    Ok(())
+}
+
+fn write_dll_overrides(user_reg: &Path, overrides: &BTreeMap<String, String>) -> Result<()> {
+    let contents = fs::read_to_string(user_reg).unwrap_or_default();
+    fs::write(user_reg, merge_dll_overrides(&contents, overrides))?;
+    Ok(())
+}
+
+/// Write a profile to a TOML file.
+pub fn save_profile(profile: &GameProfile, path: &Path) -> Result<()> {
+    let contents = toml::to_string_pretty(profile).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Read a profile from a TOML file.
+pub fn load_profile(path: &Path) -> Result<GameProfile> {
+    let contents = fs::read_to_string(path)?;
+    toml::from_str(&contents).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_REG: &str = "WINE REGISTRY Version 2\n\n\
+[Software\\\\Wine\\\\DllOverrides] 1700000000\n\
+#time=1da00000000000\n\
+\"d3dcompiler_47\"=\"native\"\n\
+\"xinput1_3\"=\"native,builtin\"\n\
+\n\
+[Software\\\\Wine\\\\Fonts] 1700000000\n\
+\"LogPixels\"=dword:00000060\n";
+
+    #[test]
+    fn test_parse_dll_overrides() {
+        let overrides = parse_dll_overrides(USER_REG);
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides["d3dcompiler_47"], "native");
+        assert_eq!(overrides["xinput1_3"], "native,builtin");
+    }
+
+    #[test]
+    fn test_merge_dll_overrides() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("xinput1_3".to_string(), "builtin".to_string());
+        overrides.insert("dinput8".to_string(), "native,builtin".to_string());
+
+        let merged = merge_dll_overrides(USER_REG, &overrides);
+        let parsed = parse_dll_overrides(&merged);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed["xinput1_3"], "builtin");
+        assert_eq!(parsed["dinput8"], "native,builtin");
+        assert!(merged.contains("\"LogPixels\"=dword:00000060"));
+
+        let fresh = merge_dll_overrides("WINE REGISTRY Version 2\n", &overrides);
+        assert_eq!(parse_dll_overrides(&fresh), overrides);
+    }
+
+    #[test]
+    fn test_profile_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profile.toml");
+        let mut profile = GameProfile {
+            source_appid: Some(620),
+            launch_options: Some("PROTON_LOG=1 %command%".to_string()),
+            compat_tool: Some("GE-Proton9-20".to_string()),
+            cloud_sync: Some(false),
+            auto_update: Some("1".to_string()),
+            dxvk_conf: Some("dxgi.maxFrameRate = 60\n".to_string()),
+            ..Default::default()
+        };
+        profile
+            .dll_overrides
+            .insert("dinput8".to_string(), "native,builtin".to_string());
+        save_profile(&profile, &path).unwrap();
+        assert_eq!(load_profile(&path).unwrap(), profile);
+    }
+}
@@ -0,0 +1,236 @@
+//! GE-Proton release tracking and installation.
+//!
+//! Installed GE-Proton builds live in `compatibilitytools.d` and are named
+//! after their release tag (`GE-Proton9-20`). The latest release is looked up
+//! through the GitHub API and cached so periodic checks stay cheap.
+
+use crate::error::{Error, Result};
+use crate::utils::{http, steam_paths};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases/latest";
+
+/// Minimum time between two online checks unless forced.
+const CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeRelease {
+    pub tag: String,
+    pub tarball_url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    checked_at: u64,
+    release: GeRelease,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+fn cache_file() -> PathBuf {
+    http::cache_root().join("proton-ge.json")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Parse a GE-Proton tag like `GE-Proton9-20` into `(9, 20)`.
+pub fn parse_version(name: &str) -> Option<(u32, u32)> {
+    let rest = name.strip_prefix("GE-Proton")?;
+    let (major, minor) = rest.split_once('-')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Names of the GE-Proton builds in all `compatibilitytools.d` directories.
+pub fn installed_versions() -> Vec<String> {
+    let mut versions = Vec::new();
+    for dir in steam_paths::compatibilitytools_dirs() {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for e in entries.flatten() {
+                if let Ok(name) = e.file_name().into_string() {
+                    if e.path().is_dir() && parse_version(&name).is_some() {
+                        versions.push(name);
+                    }
+                }
+            }
+        }
+    }
+    versions.sort_by_key(|v| parse_version(v));
+    versions.dedup();
+    versions
+}
+
+fn fetch_latest_release() -> Result<GeRelease> {
+    let body = http::get(LATEST_RELEASE_URL, &[])?;
+    let release: GithubRelease = serde_json::from_str(&body)
+        .map_err(|e| Error::Parse(format!("GitHub release: {}", e)))?;
+    let asset = release
+        .assets
+        .into_iter()
+        .find(|a| a.name.ends_with(".tar.gz"))
+        .ok_or_else(|| {
+            Error::Network(format!("release {} has no tarball", release.tag_name))
+        })?;
+    Ok(GeRelease {
+        tag: release.tag_name,
+        tarball_url: asset.browser_download_url,
+    })
+}
+
+/// Return the latest GE-Proton release, using the cached answer when it is
+/// recent enough and `force` is not set.
+pub fn latest_release(force: bool) -> Result<GeRelease> {
+    if !force {
+        if let Some(entry) = fs::read_to_string(cache_file())
+            .ok()
+            .and_then(|c| serde_json::from_str::<CacheEntry>(&c).ok())
+        {
+            if now_secs().saturating_sub(entry.checked_at) < CHECK_INTERVAL_SECS {
+                return Ok(entry.release);
+            }
+        }
+    }
+
+    let release = fetch_latest_release()?;
+    let entry = CacheEntry {
+        checked_at: now_secs(),
+        release: release.clone(),
+    };
+    let path = cache_file();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(&entry).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(path, json)?;
+    Ok(release)
+}
+
+/// Check whether a newer GE-Proton than the newest installed build exists.
+///
+/// Returns `Ok(None)` when GE-Proton is not installed at all.
+pub fn available_update(force: bool) -> Result<Option<GeRelease>> {
+    let Some(newest) = installed_versions().last().and_then(|v| parse_version(v)) else {
+        return Ok(None);
+    };
+    let latest = latest_release(force)?;
+    Ok(parse_version(&latest.tag)
+        .filter(|v| *v > newest)
+        .map(|_| latest))
+}
+
+/// Directory new GE-Proton builds are installed into.
+fn install_root() -> Result<PathBuf> {
+    if let Some(dir) = steam_paths::compatibilitytools_dirs().into_iter().next() {
+        return Ok(dir);
+    }
+    steam_paths::steam_base_dirs()
+        .into_iter()
+        .next()
+        .map(|base| base.join("compatibilitytools.d"))
+        .ok_or(Error::SteamNotFound)
+}
+
+/// Download and extract a GE-Proton release into `compatibilitytools.d`.
+pub fn install(release: &GeRelease) -> Result<PathBuf> {
+    use crate::utils::dependencies::{command_available, missing_message};
+
+    if !command_available("tar") {
+        return Err(Error::FileSystemError(missing_message("tar")));
+    }
+
+    let root = install_root()?;
+    fs::create_dir_all(&root)?;
+    let tarball = http::cache_root().join(format!("{}.tar.gz", release.tag));
+    http::download(&release.tarball_url, &tarball)?;
+
+    log::debug!("Extracting {} into {}", tarball.display(), root.display());
+    let status = std::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&tarball)
+        .arg("-C")
+        .arg(&root)
+        .status();
+    let _ = fs::remove_file(&tarball);
+    match status {
+        Ok(s) if s.success() => Ok(root.join(&release.tag)),
+        Ok(s) => Err(Error::FileSystemError(format!(
+            "failed to extract {}: tar exited with {}",
+            release.tag, s
+        ))),
+        Err(e) => Err(Error::Io(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+    use crate::utils::http::HTTP_RESPONSES;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("GE-Proton9-20"), Some((9, 20)));
+        assert_eq!(parse_version("GE-Proton10-3"), Some((10, 3)));
+        assert_eq!(parse_version("Proton-6.21-GE-2"), None);
+        assert!(parse_version("GE-Proton10-3") > parse_version("GE-Proton9-27"));
+    }
+
+    #[test]
+    fn test_available_update() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let (home, _, _) = setup_steam_env(6060, false);
+        let old_home = std::env::var("HOME").ok();
+        let old_cache = std::env::var("XDG_CACHE_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CACHE_HOME");
+
+        HTTP_RESPONSES.lock().unwrap().clear();
+        HTTP_RESPONSES.lock().unwrap().push((
+            LATEST_RELEASE_URL.to_string(),
+            r#"{"tag_name":"GE-Proton10-4","assets":[
+                {"name":"GE-Proton10-4.sha512sum","browser_download_url":"https://example/sum"},
+                {"name":"GE-Proton10-4.tar.gz","browser_download_url":"https://example/tar"}]}"#
+                .to_string(),
+        ));
+
+        // Nothing installed, so there is nothing to update.
+        assert_eq!(available_update(true).unwrap(), None);
+
+        let tools = home.path().join(".steam/steam/compatibilitytools.d");
+        fs::create_dir_all(tools.join("GE-Proton9-27")).unwrap();
+        let update = available_update(false).unwrap().unwrap();
+        assert_eq!(update.tag, "GE-Proton10-4");
+        assert_eq!(update.tarball_url, "https://example/tar");
+
+        // The cached answer is used without another request.
+        HTTP_RESPONSES.lock().unwrap().clear();
+        fs::create_dir_all(tools.join("GE-Proton10-4")).unwrap();
+        assert_eq!(available_update(false).unwrap(), None);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_cache {
+            std::env::set_var("XDG_CACHE_HOME", c);
+        }
+    }
+}
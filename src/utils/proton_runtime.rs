@@ -0,0 +1,475 @@
+//! Locating Proton installs and running them outside of Steam.
+
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::{
+    library, manifest as manifest_utils, prefix_repair, sandbox, steam_paths, user_config,
+};
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(test)]
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::sync::Mutex;
+
+/// Directories of all Proton installs: Steam's own builds in
+/// `steamapps/common` and custom ones in `compatibilitytools.d`.
+pub fn installed_protons() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(libraries) = steam::get_steam_libraries() {
+        for lib in libraries {
+            if let Ok(entries) = fs::read_dir(lib.steamapps_path().join("common")) {
+                dirs.extend(entries.flatten().map(|e| e.path()));
+            }
+        }
+    }
+    for dir in steam_paths::compatibilitytools_dirs() {
+        if let Ok(entries) = fs::read_dir(dir) {
+            dirs.extend(entries.flatten().map(|e| e.path()));
+        }
+    }
+    dirs.retain(|d| d.join("proton").is_file());
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Resolve a compatibility tool name to its install directory.
+///
+/// Accepts directory names as listed in the game settings as well as
+/// Steam's internal names such as `proton_9` or `proton_experimental`.
+pub fn find_proton(tool: &str) -> Option<PathBuf> {
+    let installed = installed_protons();
+    let dir_name = |d: &PathBuf| {
+        d.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    if let Some(dir) = installed.iter().find(|d| dir_name(d) == tool) {
+        return Some(dir.clone());
+    }
+    let wanted = match tool.strip_prefix("proton_")? {
+        "experimental" => "Proton - Experimental".to_string(),
+        "hotfix" => "Proton Hotfix".to_string(),
+        version => format!("Proton {}", version.replace('_', ".")),
+    };
+    installed
+        .into_iter()
+        .filter(|d| dir_name(d).starts_with(&wanted))
+        .max()
+}
+
+/// The Proton install used for a game: its compatibility tool override, or
+/// the newest Steam Proton build when none is set.
+pub fn proton_for(appid: u32) -> Option<PathBuf> {
+    let configured = user_config::get_compat_tool(appid).or_else(|| {
+        let manifest = steam::find_manifest(appid)?;
+        let contents = library::read_manifest_cached(&manifest)?;
+        manifest_utils::get_value(&contents, "CompatToolOverride")
+    });
+    if let Some(tool) = configured.filter(|t| !t.is_empty()) {
+        match find_proton(&tool) {
+            Some(dir) => return Some(dir),
+            None => log::warn!("Compatibility tool {} for {} is not installed", tool, appid),
+        }
+    }
+    installed_protons()
+        .into_iter()
+        .filter(|d| {
+            d.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("Proton ") && n[7..].starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .max()
+}
+
+/// Steam Linux Runtime container a Proton build runs in through
+/// pressure-vessel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Soldier,
+    Sniper,
+    Other(u32),
+}
+
+impl ContainerRuntime {
+    fn from_appid(appid: u32) -> Self {
+        match appid {
+            1391110 => ContainerRuntime::Soldier,
+            1628350 => ContainerRuntime::Sniper,
+            other => ContainerRuntime::Other(other),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ContainerRuntime::Soldier => "Steam Linux Runtime 2.0 (soldier)".to_string(),
+            ContainerRuntime::Sniper => "Steam Linux Runtime 3.0 (sniper)".to_string(),
+            ContainerRuntime::Other(appid) => format!("Steam Linux Runtime (AppID {})", appid),
+        }
+    }
+
+    /// Directory name of the runtime in `steamapps/common`.
+    fn dir_name(&self) -> Option<&'static str> {
+        match self {
+            ContainerRuntime::Soldier => Some("SteamLinuxRuntime_soldier"),
+            ContainerRuntime::Sniper => Some("SteamLinuxRuntime_sniper"),
+            ContainerRuntime::Other(_) => None,
+        }
+    }
+}
+
+/// The container runtime a Proton install asks for in its
+/// `toolmanifest.vdf`. Proton 5.0 and older run without one.
+pub fn required_runtime(proton: &Path) -> Option<ContainerRuntime> {
+    let contents = fs::read_to_string(proton.join("toolmanifest.vdf")).ok()?;
+    let appid = manifest_utils::get_value(&contents, "require_tool_appid")?;
+    appid.parse().ok().map(ContainerRuntime::from_appid)
+}
+
+/// Install directory of a container runtime, if Steam has downloaded it.
+pub fn runtime_dir(runtime: ContainerRuntime) -> Option<PathBuf> {
+    let name = runtime.dir_name()?;
+    steam::get_steam_libraries()
+        .ok()?
+        .into_iter()
+        .map(|lib| lib.steamapps_path().join("common").join(name))
+        .find(|dir| dir.join("pressure-vessel").is_dir())
+}
+
+/// The container runtime a game runs in, judging by its Proton build.
+pub fn container_for(appid: u32) -> Option<ContainerRuntime> {
+    required_runtime(&proton_for(appid)?)
+}
+
+/// Warning shown before tools that run Wine outside of Steam, like
+/// winecfg and protontricks, when the game itself runs in a container.
+pub fn container_warning(appid: u32) -> Option<String> {
+    let runtime = container_for(appid)?;
+    Some(format!(
+        "This game runs inside the {} container. Tools started from here use the system's libraries instead, so their results may differ from what the game sees.",
+        runtime.name()
+    ))
+}
+
+/// Directory of the Wine build bundled with a Proton install: `files` in
+/// current releases, `dist` in Proton 5.0 and older.
+pub fn wine_dir(proton: &Path) -> Option<PathBuf> {
+    ["files", "dist"]
+        .iter()
+        .map(|dir| proton.join(dir))
+        .find(|dir| dir.join("bin/wine").is_file())
+}
+
+/// The Wine prefix of `appid` and the `bin` directory of its Proton's Wine.
+fn wine_paths(appid: u32) -> Result<(PathBuf, PathBuf)> {
+    let compatdata = steam::compatdata_dir(appid)?;
+    let prefix = compatdata.join("pfx");
+    if !prefix.is_dir() {
+        return Err(Error::FileSystemError(format!(
+            "No Wine prefix at {}",
+            prefix.display()
+        )));
+    }
+    let proton = proton_for(appid).ok_or_else(|| {
+        Error::FileSystemError("No Proton installation found".to_string())
+    })?;
+    let wine = wine_dir(&proton).ok_or_else(|| {
+        Error::FileSystemError(format!("No Wine build found in {}", proton.display()))
+    })?;
+    Ok((prefix, wine.join("bin")))
+}
+
+fn wine_env(cmd: &mut Command, appid: u32, prefix: &Path, bin: &Path) {
+    let mut path = std::ffi::OsString::from(bin);
+    if let Some(system) = std::env::var_os("PATH") {
+        path.push(":");
+        path.push(system);
+    }
+    cmd.env("WINEPREFIX", prefix)
+        .env("WINE", bin.join("wine"))
+        .env("WINESERVER", bin.join("wineserver"))
+        .env("PATH", path)
+        .env("SteamAppId", appid.to_string())
+        .env("SteamGameId", appid.to_string());
+}
+
+/// Command running `program` against the prefix of `appid` with the Wine
+/// of the game's own Proton build, so tools like winetricks don't touch the
+/// prefix with a mismatched system Wine.
+pub fn wine_command(appid: u32, program: &str) -> Result<Command> {
+    let (prefix, bin) = wine_paths(appid)?;
+    let mut cmd = Command::new(program);
+    wine_env(&mut cmd, appid, &prefix, &bin);
+    Ok(sandbox::host_command(cmd))
+}
+
+/// Wine's builtin programs that help with troubleshooting a prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum WineTool {
+    /// Registry editor
+    Regedit,
+    /// File manager
+    Explorer,
+    /// Task manager
+    Taskmgr,
+    /// Control panel
+    Control,
+    /// Add/remove programs
+    Uninstaller,
+}
+
+impl WineTool {
+    pub const ALL: [WineTool; 5] = [
+        WineTool::Regedit,
+        WineTool::Explorer,
+        WineTool::Taskmgr,
+        WineTool::Control,
+        WineTool::Uninstaller,
+    ];
+
+    /// Name of the program inside the prefix.
+    pub fn program(&self) -> &'static str {
+        match self {
+            WineTool::Regedit => "regedit",
+            WineTool::Explorer => "explorer",
+            WineTool::Taskmgr => "taskmgr",
+            WineTool::Control => "control",
+            WineTool::Uninstaller => "uninstaller",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WineTool::Regedit => "Registry Editor",
+            WineTool::Explorer => "File Explorer",
+            WineTool::Taskmgr => "Task Manager",
+            WineTool::Control => "Control Panel",
+            WineTool::Uninstaller => "Uninstall Programs",
+        }
+    }
+}
+
+/// Command running one of Wine's builtin programs such as `winecfg` or
+/// `regedit` in the prefix of `appid` through the Proton build's `wine`.
+pub fn wine_tool(appid: u32, tool: &str) -> Result<Command> {
+    let (prefix, bin) = wine_paths(appid)?;
+    let mut cmd = Command::new(bin.join("wine"));
+    cmd.arg(tool);
+    wine_env(&mut cmd, appid, &prefix, &bin);
+    Ok(sandbox::host_command(cmd))
+}
+
+#[cfg(not(test))]
+fn run_proton(proton: &Path, compatdata: &Path, appid: u32, args: &[&str]) -> Result<()> {
+    let client = steam_paths::steam_base_dirs()
+        .into_iter()
+        .next()
+        .ok_or(Error::SteamNotFound)?;
+    let mut cmd = Command::new(proton.join("proton"));
+    cmd.arg("run")
+        .args(args)
+        .env("STEAM_COMPAT_DATA_PATH", compatdata)
+        .env("STEAM_COMPAT_CLIENT_INSTALL_PATH", client)
+        .env("SteamAppId", appid.to_string())
+        .env("SteamGameId", appid.to_string());
+    let status = sandbox::host_command(cmd).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::FileSystemError(format!(
+            "proton exited with status {}",
+            status
+        )))
+    }
+}
+
+#[cfg(test)]
+pub static PROTON_CALLS: Lazy<Mutex<Vec<Vec<String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[cfg(test)]
+fn run_proton(_proton: &Path, compatdata: &Path, _appid: u32, args: &[&str]) -> Result<()> {
+    let mut call = vec![compatdata.display().to_string()];
+    call.extend(args.iter().map(|a| a.to_string()));
+    PROTON_CALLS.lock().unwrap().push(call);
+    Ok(())
+}
+
+/// Initialize a game's prefix by running `wineboot` with its Proton build.
+///
+/// Returns the compatdata directory and the Proton install that was used.
+pub fn create_prefix(appid: u32) -> Result<(PathBuf, PathBuf)> {
+    let compatdata = steam::compatdata_dir(appid)?;
+    if compatdata.join("pfx/system.reg").exists() {
+        return Err(Error::FileSystemError(format!(
+            "A prefix already exists at {}",
+            compatdata.display()
+        )));
+    }
+    let proton = proton_for(appid).ok_or_else(|| {
+        Error::FileSystemError("No Proton installation found".to_string())
+    })?;
+    fs::create_dir_all(&compatdata)?;
+    run_proton(&proton, &compatdata, appid, &["wineboot", "-u"])?;
+    Ok((compatdata, proton))
+}
+
+/// Let Wine write new registry hives in place of the damaged ones in the
+/// prefix of `appid` by deleting them and running `wineboot -u` with the
+/// game's Proton build. Every setting stored in those hives is lost, so
+/// this is the last resort when no backup or snapshot has a good copy for
+/// [`prefix_repair::fix_registry`]. Returns a description of each hive
+/// regenerated.
+pub fn regenerate_registry(appid: u32, compatdata: &Path) -> Result<Vec<String>> {
+    let wine_prefix = compatdata.join("pfx");
+    let damaged = prefix_repair::damaged_hives(&wine_prefix);
+    if damaged.is_empty() {
+        return Ok(Vec::new());
+    }
+    let proton = proton_for(appid).ok_or_else(|| {
+        Error::FileSystemError("No Proton installation found to regenerate the registry".to_string())
+    })?;
+    for reg in &damaged {
+        let path = wine_prefix.join(reg);
+        if fs::symlink_metadata(&path).is_ok() {
+            fs::remove_file(&path)?;
+        }
+    }
+    run_proton(&proton, compatdata, appid, &["wineboot", "-u"])?;
+    Ok(damaged
+        .iter()
+        .map(|reg| format!("regenerated {} with wineboot; the settings it held are lost", reg))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_create_prefix() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 9090;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        fs::remove_dir_all(&prefix).unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        let steamapps = home.path().join("library/steamapps");
+        fs::write(
+            steamapps.join(format!("appmanifest_{}.acf", appid)),
+            format!("\"AppState\" {{ \"appid\" \"{}\" \"CompatToolOverride\" \"proton_9\" }}", appid),
+        )
+        .unwrap();
+        for dir in ["Proton 8.0", "Proton 9.0", "Proton - Experimental"] {
+            let path = steamapps.join("common").join(dir);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("proton"), "").unwrap();
+        }
+        assert_eq!(find_proton("proton_experimental"), Some(steamapps.join("common/Proton - Experimental")));
+
+        PROTON_CALLS.lock().unwrap().clear();
+        let (compatdata, proton) = create_prefix(appid).unwrap();
+        assert_eq!(compatdata, prefix);
+        assert_eq!(proton, steamapps.join("common/Proton 9.0"));
+        let calls = PROTON_CALLS.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0], vec![prefix.display().to_string(), "wineboot".into(), "-u".into()]);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_regenerate_registry() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 9091;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+        let proton = home.path().join("library/steamapps/common/Proton 9.0");
+        fs::create_dir_all(&proton).unwrap();
+        fs::write(proton.join("proton"), "").unwrap();
+        let wine_prefix = prefix.join("pfx");
+        fs::create_dir_all(&wine_prefix).unwrap();
+        for reg in ["system.reg", "userdef.reg"] {
+            fs::write(wine_prefix.join(reg), "WINE REGISTRY Version 2\n").unwrap();
+        }
+        fs::write(wine_prefix.join("user.reg"), "").unwrap();
+
+        PROTON_CALLS.lock().unwrap().clear();
+        let fixes = regenerate_registry(appid, &prefix).unwrap();
+        assert_eq!(fixes.len(), 1);
+        assert!(fixes[0].starts_with("regenerated user.reg with wineboot"));
+        // Wine only writes a new hive where there is none.
+        assert!(!wine_prefix.join("user.reg").exists());
+        assert!(wine_prefix.join("system.reg").exists());
+        assert_eq!(
+            *PROTON_CALLS.lock().unwrap(),
+            vec![vec![prefix.display().to_string(), "wineboot".into(), "-u".into()]]
+        );
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_wine_command() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        crate::core::steam::clear_caches();
+        let appid = 9191;
+        let (home, prefix, _) = setup_steam_env(appid, false);
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", home.path());
+
+        assert!(wine_command(appid, "winetricks").is_err());
+        fs::create_dir_all(prefix.join("pfx")).unwrap();
+        let proton = home.path().join("library/steamapps/common/Proton 9.0");
+        fs::create_dir_all(proton.join("files/bin")).unwrap();
+        fs::write(proton.join("proton"), "").unwrap();
+        fs::write(proton.join("files/bin/wine"), "").unwrap();
+
+        let cmd = wine_command(appid, "winetricks").unwrap();
+        assert_eq!(cmd.get_program(), "winetricks");
+        let env: std::collections::HashMap<_, _> = cmd
+            .get_envs()
+            .map(|(k, v)| (k.to_string_lossy().into_owned(), v.map(|v| v.to_owned())))
+            .collect();
+        assert_eq!(env["WINEPREFIX"], Some(prefix.join("pfx").into_os_string()));
+        assert_eq!(env["WINE"], Some(proton.join("files/bin/wine").into_os_string()));
+        let cmd = wine_tool(appid, "winecfg").unwrap();
+        assert_eq!(cmd.get_program(), proton.join("files/bin/wine"));
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["winecfg"]);
+        assert!(env["PATH"]
+            .as_ref()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(&*proton.join("files/bin").to_string_lossy()));
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_required_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(required_runtime(dir.path()), None);
+        fs::write(
+            dir.path().join("toolmanifest.vdf"),
+            "\"manifest\"\n{\n  \"version\" \"2\"\n  \"commandline\" \"/proton %verb%\"\n  \"require_tool_appid\" \"1628350\"\n  \"use_sessions\" \"1\"\n}\n",
+        )
+        .unwrap();
+        let runtime = required_runtime(dir.path()).unwrap();
+        assert_eq!(runtime, ContainerRuntime::Sniper);
+        assert_eq!(runtime.name(), "Steam Linux Runtime 3.0 (sniper)");
+    }
+}
@@ -0,0 +1,184 @@
+//! Reading and writing Proton's `user_settings.py`.
+//!
+//! Proton loads `user_settings.py` from its install directory and applies the
+//! `user_settings` dictionary as environment variables to every game run with
+//! that version. Only uncommented `"KEY": "value"` entries are understood.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionKind {
+    /// `0` or `1`.
+    Flag,
+    /// Free-form text such as a WINEDEBUG channel list.
+    Text,
+}
+
+/// Options documented in Proton's `user_settings.sample.py` and README.
+pub const KNOWN_OPTIONS: &[(&str, OptionKind, &str)] = &[
+    ("PROTON_LOG", OptionKind::Flag, "Write a log to $HOME/steam-$APPID.log"),
+    ("PROTON_LOG_DIR", OptionKind::Text, "Directory for Proton logs"),
+    ("PROTON_DUMP_DEBUG_COMMANDS", OptionKind::Flag, "Write debug scripts to /tmp/proton_$USER/"),
+    ("PROTON_USE_WINED3D", OptionKind::Flag, "Use OpenGL-based wined3d instead of DXVK"),
+    ("PROTON_NO_D3D11", OptionKind::Flag, "Disable Direct3D 11"),
+    ("PROTON_NO_D3D10", OptionKind::Flag, "Disable Direct3D 10"),
+    ("PROTON_NO_ESYNC", OptionKind::Flag, "Disable eventfd-based synchronization"),
+    ("PROTON_NO_FSYNC", OptionKind::Flag, "Disable futex-based synchronization"),
+    ("PROTON_FORCE_LARGE_ADDRESS_AWARE", OptionKind::Flag, "Give 32-bit games 4 GB of address space"),
+    ("PROTON_OLD_GL_STRING", OptionKind::Flag, "Limit the OpenGL extension string for old games"),
+    ("PROTON_ENABLE_NVAPI", OptionKind::Flag, "Enable NVIDIA's NVAPI library"),
+    ("PROTON_HIDE_NVIDIA_GPU", OptionKind::Flag, "Report NVIDIA GPUs as AMD"),
+    ("PROTON_ENABLE_HIDRAW", OptionKind::Flag, "Give games raw access to HID devices"),
+    ("PROTON_ENABLE_WAYLAND", OptionKind::Flag, "Use the Wayland driver"),
+    ("WINEDEBUG", OptionKind::Text, "Wine debug channels"),
+    ("DXVK_HUD", OptionKind::Text, "DXVK overlay elements"),
+    ("DXVK_LOG_LEVEL", OptionKind::Text, "DXVK log level"),
+    ("VKD3D_DEBUG", OptionKind::Text, "VKD3D-Proton log level"),
+];
+
+const HEADER: &str = "#!/usr/bin/env python3\n\n\
+# Settings here will take effect for all games run in this Proton version.\n\
+# Written by proton-prefix-manager.\n\n";
+
+/// Path of `user_settings.py` for a Proton install directory.
+pub fn settings_path(proton_dir: &Path) -> PathBuf {
+    proton_dir.join("user_settings.py")
+}
+
+fn option_kind(key: &str) -> Option<OptionKind> {
+    KNOWN_OPTIONS
+        .iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|(_, kind, _)| *kind)
+}
+
+/// Description of a known option.
+pub fn describe(key: &str) -> Option<&'static str> {
+    KNOWN_OPTIONS
+        .iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|(_, _, desc)| *desc)
+}
+
+/// Check an option before it is written. Unknown keys are accepted as long
+/// as they are valid environment variable names.
+pub fn validate(key: &str, value: &str) -> std::result::Result<(), String> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("{} is not a valid environment variable name", key));
+    }
+    if value.contains('"') || value.contains('\n') {
+        return Err(format!("{} contains quotes or line breaks", key));
+    }
+    match option_kind(key) {
+        Some(OptionKind::Flag) if value != "0" && value != "1" => {
+            Err(format!("{} must be 0 or 1", key))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parse the entries of the `user_settings` dictionary.
+pub fn parse(contents: &str) -> BTreeMap<String, String> {
+    contents
+        .lines()
+        .skip_while(|l| !l.trim_start().starts_with("user_settings"))
+        .skip(1)
+        .take_while(|l| !l.trim_start().starts_with('}'))
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.starts_with('#') {
+                return None;
+            }
+            let (key, value) = line.split_once(':')?;
+            let key = key.trim().strip_prefix('"')?.strip_suffix('"')?;
+            let value = value.trim().trim_end_matches(',').trim();
+            let value = value.strip_prefix('"')?.strip_suffix('"')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Render a complete `user_settings.py`.
+pub fn render(settings: &BTreeMap<String, String>) -> String {
+    let mut out = String::from(HEADER);
+    out.push_str("user_settings = {\n");
+    for (key, value) in settings {
+        out.push_str(&format!("    \"{}\": \"{}\",\n", key, value));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Load the settings of a Proton install. A missing file means no settings.
+pub fn load(proton_dir: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(settings_path(proton_dir))
+        .map(|c| parse(&c))
+        .unwrap_or_default()
+}
+
+/// Validate and write the settings of a Proton install. An empty set of
+/// settings removes the file.
+pub fn save(proton_dir: &Path, settings: &BTreeMap<String, String>) -> Result<()> {
+    for (key, value) in settings {
+        validate(key, value).map_err(Error::Parse)?;
+    }
+    let path = settings_path(proton_dir);
+    if settings.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    fs::write(path, render(settings))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"#!/usr/bin/env python3
+
+user_settings = {
+    #Logs are saved to $HOME/steam-$STEAM_APP_ID.log
+    "WINEDEBUG": "+timestamp,+pid,+tid",
+
+    #Enable DXVK's HUD
+#    "DXVK_HUD": "devinfo",
+
+    "PROTON_NO_ESYNC": "1",
+}
+"#;
+
+    #[test]
+    fn test_parse_and_render() {
+        let settings = parse(SAMPLE);
+        assert_eq!(settings.len(), 2);
+        assert_eq!(settings["WINEDEBUG"], "+timestamp,+pid,+tid");
+        assert_eq!(settings["PROTON_NO_ESYNC"], "1");
+        assert_eq!(parse(&render(&settings)), settings);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("PROTON_NO_ESYNC", "1").is_ok());
+        assert!(validate("PROTON_NO_ESYNC", "yes").is_err());
+        assert!(validate("WINEDEBUG", "-all").is_ok());
+        assert!(validate("MY_VAR", "x").is_ok());
+        assert!(validate("BAD KEY", "x").is_err());
+        assert!(validate("WINEDEBUG", "\"").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut settings = BTreeMap::new();
+        settings.insert("PROTON_LOG".to_string(), "2".to_string());
+        assert!(save(dir.path(), &settings).is_err());
+        settings.insert("PROTON_LOG".to_string(), "1".to_string());
+        save(dir.path(), &settings).unwrap();
+        assert_eq!(load(dir.path()), settings);
+        save(dir.path(), &BTreeMap::new()).unwrap();
+        assert!(!settings_path(dir.path()).exists());
+    }
+}
@@ -0,0 +1,158 @@
+//! Listing and installing winetricks verbs through protontricks, or plain
+//! winetricks with the game's Proton Wine when protontricks is missing.
+
+use crate::error::{Error, Result};
+use crate::utils::dependencies::{command_available, missing_message};
+use crate::utils::{proton_runtime, sandbox};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Verb categories offered for installation.
+const CATEGORIES: [&str; 3] = ["dlls", "fonts", "settings"];
+
+/// A winetricks verb such as `vcrun2019` or `corefonts`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verb {
+    pub name: String,
+    pub category: &'static str,
+    pub description: String,
+}
+
+/// Command running winetricks in the prefix of `appid`.
+fn winetricks(appid: u32) -> Result<Command> {
+    if command_available("protontricks") {
+        let mut cmd = Command::new("protontricks");
+        cmd.arg(appid.to_string());
+        Ok(sandbox::host_command(cmd))
+    } else if command_available("winetricks") {
+        proton_runtime::wine_command(appid, "winetricks")
+    } else {
+        Err(Error::FileSystemError(missing_message("protontricks")))
+    }
+}
+
+fn run(appid: u32, args: &[&str]) -> Result<String> {
+    let output = winetricks(appid)?.args(args).stdin(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(Error::FileSystemError(format!(
+            "winetricks exited with status {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `list-installed`: one verb per line between winetricks' notices.
+fn parse_installed(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.contains(char::is_whitespace)
+                && line
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '='))
+                && !line.starts_with('-')
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse `<category> list`: the verb followed by its description.
+fn parse_verbs(text: &str, category: &'static str) -> Vec<Verb> {
+    text.lines()
+        .filter_map(|line| {
+            let (name, description) = line.trim().split_once(char::is_whitespace)?;
+            let valid = name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '='));
+            (valid && !name.is_empty()).then(|| Verb {
+                name: name.to_string(),
+                category,
+                description: description.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Verbs already installed in the prefix of `appid`.
+pub fn list_installed(appid: u32) -> Result<Vec<String>> {
+    run(appid, &["list-installed"]).map(|text| parse_installed(&text))
+}
+
+/// Every verb protontricks can install, sorted by category.
+pub fn available_verbs(appid: u32) -> Result<Vec<Verb>> {
+    let mut verbs = Vec::new();
+    for category in CATEGORIES {
+        verbs.extend(parse_verbs(&run(appid, &[category, "list"])?, category));
+    }
+    Ok(verbs)
+}
+
+/// Install `verbs` into the prefix of `appid` without prompts, sending
+/// every line winetricks prints to `output` as it appears.
+pub fn install(appid: u32, verbs: &[String], output: Sender<String>) -> Result<()> {
+    let mut child = winetricks(appid)?
+        .arg("-q")
+        .args(verbs)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stderr = child.stderr.take().map(|stderr| {
+        let output = output.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+                let _ = output.send(line);
+            }
+        })
+    });
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+            let _ = output.send(line);
+        }
+    }
+    if let Some(handle) = stderr {
+        let _ = handle.join();
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::FileSystemError(format!(
+            "winetricks exited with status {}",
+            status
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let installed = "\
+------------------------------------------------------
+warning: You are using a 64-bit WINEPREFIX.
+------------------------------------------------------
+vcrun2019
+corefonts
+d3dcompiler_47
+";
+        assert_eq!(parse_installed(installed), ["vcrun2019", "corefonts", "d3dcompiler_47"]);
+
+        let dlls = "\
+d3dx9                    MS d3dx9_??.dll from DirectX 9 redistributable (Microsoft, 2010) [downloadable]
+vcrun2019                Visual C++ 2015-2019 libraries (mfc140.dll,msvcp140.dll) (Microsoft, 2019) [downloadable]
+";
+        let verbs = parse_verbs(dlls, "dlls");
+        assert_eq!(verbs.len(), 2);
+        assert_eq!(verbs[1].name, "vcrun2019");
+        assert_eq!(verbs[1].category, "dlls");
+        assert!(verbs[1].description.starts_with("Visual C++ 2015-2019"));
+    }
+}
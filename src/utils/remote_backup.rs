@@ -0,0 +1,317 @@
+//! Copy backups to and from another machine over SSH.
+//!
+//! Remote backups live in `<path>/<appid>/<timestamp>` on the host of the
+//! configured `user@host:/path` target, mirroring the local backup root, so
+//! a backup pulled back can be restored like any other. The copy is done
+//! by `rsync` or `sftp` as set in the `[remote]` section of the config, and
+//! both rely on the user's SSH setup for keys, ports and host aliases.
+
+use crate::error::{Error, Result};
+use crate::utils::app_config::{self, RemoteTransport};
+use crate::utils::{backup, logging, sandbox};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A `user@host:/path` destination of backups.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteTarget {
+    /// SSH destination, `host` or `user@host`.
+    pub host: String,
+    /// Directory on the host holding a directory per game.
+    pub path: String,
+}
+
+impl RemoteTarget {
+    pub fn parse(target: &str) -> Result<Self> {
+        let invalid = || {
+            Error::Parse(format!(
+                "invalid remote target '{}', expected user@host:/path",
+                target
+            ))
+        };
+        let (host, path) = target.trim().split_once(':').ok_or_else(invalid)?;
+        // ssh, sftp and rsync would take a host starting with `-` for an
+        // option such as `-oProxyCommand=...`.
+        if host.is_empty() || path.is_empty() || host.contains('/') || host.starts_with('-') {
+            return Err(invalid());
+        }
+        Ok(Self {
+            host: host.to_string(),
+            path: path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Directory holding the backups of `appid` on the host.
+    fn game_dir(&self, appid: u32) -> String {
+        format!("{}/{}", self.path, appid)
+    }
+}
+
+impl fmt::Display for RemoteTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.path)
+    }
+}
+
+/// The configured destination and transport of the backups of `appid`.
+pub fn configured(appid: u32) -> Result<(RemoteTarget, RemoteTransport)> {
+    let remote = app_config::load().remote;
+    let target = remote.target_for(appid).ok_or_else(|| {
+        Error::FileSystemError(format!(
+            "no remote backup target configured for {}, set [remote] target in the config file",
+            appid
+        ))
+    })?;
+    Ok((RemoteTarget::parse(target)?, remote.transport))
+}
+
+/// Quote `s` for the remote shell `ssh` hands its command to.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Quote `s` for an `sftp` batch file.
+fn sftp_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', r"\\").replace('"', "\\\""))
+}
+
+/// A program to run and the input it reads from stdin.
+struct Step {
+    command: Command,
+    input: Option<String>,
+}
+
+impl Step {
+    fn new(program: &str, args: &[&str]) -> Self {
+        let mut command = Command::new(program);
+        command.args(args);
+        Self {
+            command,
+            input: None,
+        }
+    }
+
+    /// `sftp` running the commands of `script` on `host`.
+    fn sftp(host: &str, script: String) -> Self {
+        let mut step = Self::new("sftp", &["-q", "-b", "-", host]);
+        step.input = Some(script);
+        step
+    }
+
+    fn run(mut self) -> Result<String> {
+        let program = self.command.get_program().to_string_lossy().into_owned();
+        #[cfg(not(test))]
+        {
+            use crate::utils::dependencies::{command_available, missing_message};
+            if !command_available(&program) {
+                return Err(Error::Network(missing_message(&program)));
+            }
+        }
+        let mut child = sandbox::host_command(self.command)
+            .stdin(if self.input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let (Some(input), Some(mut stdin)) = (self.input.take(), child.stdin.take()) {
+            stdin.write_all(input.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::Network(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn backup_name(backup: &Path) -> Result<String> {
+    backup
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| Error::FileSystemError(format!("not a backup: {}", backup.display())))
+}
+
+fn push_steps(
+    backup: &Path,
+    appid: u32,
+    target: &RemoteTarget,
+    transport: RemoteTransport,
+) -> Result<Vec<Step>> {
+    let dir = target.game_dir(appid);
+    let local = backup.to_string_lossy();
+    Ok(match transport {
+        RemoteTransport::Rsync => vec![
+            Step::new("ssh", &[&target.host, &format!("mkdir -p {}", shell_quote(&dir))]),
+            Step::new(
+                "rsync",
+                &["-a", "--partial", "--protect-args", &local, &format!("{}:{}/", target.host, dir)],
+            ),
+        ],
+        RemoteTransport::Sftp => {
+            let remote = format!("{}/{}", dir, backup_name(backup)?);
+            vec![Step::sftp(
+                &target.host,
+                format!(
+                    "-mkdir {}\n-mkdir {}\nput -r {} {}\n",
+                    sftp_quote(&target.path),
+                    sftp_quote(&dir),
+                    sftp_quote(&local),
+                    sftp_quote(&remote)
+                ),
+            )]
+        }
+    })
+}
+
+fn list_step(appid: u32, target: &RemoteTarget, transport: RemoteTransport) -> Step {
+    let dir = target.game_dir(appid);
+    match transport {
+        RemoteTransport::Rsync => Step::new(
+            "ssh",
+            &[&target.host, &format!("ls -1 {} 2>/dev/null || true", shell_quote(&dir))],
+        ),
+        RemoteTransport::Sftp => Step::sftp(&target.host, format!("-ls -1 {}\n", sftp_quote(&dir))),
+    }
+}
+
+fn pull_step(
+    appid: u32,
+    name: &str,
+    target: &RemoteTarget,
+    transport: RemoteTransport,
+    local_dir: &Path,
+) -> Step {
+    let remote = format!("{}/{}", target.game_dir(appid), name);
+    match transport {
+        RemoteTransport::Rsync => Step::new(
+            "rsync",
+            &[
+                "-a",
+                "--partial",
+                "--protect-args",
+                &format!("{}:{}", target.host, remote),
+                &format!("{}/", local_dir.display()),
+            ],
+        ),
+        RemoteTransport::Sftp => Step::sftp(
+            &target.host,
+            format!(
+                "get -r {} {}\n",
+                sftp_quote(&remote),
+                sftp_quote(&local_dir.join(name).to_string_lossy())
+            ),
+        ),
+    }
+}
+
+/// Backup names in the output of `ls`, which `sftp` prefixes with the
+/// directory.
+fn parse_listing(output: &str) -> Vec<String> {
+    let mut names: Vec<String> = output
+        .lines()
+        .filter_map(|line| line.trim().rsplit('/').next())
+        .filter(|name| chrono::NaiveDateTime::parse_from_str(name, "%Y%m%d%H%M%S").is_ok())
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Copy the local `backup` of `appid` to its remote target. Returns where
+/// it was copied to.
+pub fn push(backup: &Path, appid: u32) -> Result<String> {
+    logging::operation("remote_push", Some(appid), backup, || {
+        let (target, transport) = configured(appid)?;
+        for step in push_steps(backup, appid, &target, transport)? {
+            step.run()?;
+        }
+        Ok(format!("{}:{}/{}", target.host, target.game_dir(appid), backup_name(backup)?))
+    })
+}
+
+/// Names of the remote backups of `appid`, oldest first.
+pub fn list(appid: u32) -> Result<Vec<String>> {
+    let (target, transport) = configured(appid)?;
+    Ok(parse_listing(&list_step(appid, &target, transport).run()?))
+}
+
+/// Copy the remote backup `name` of `appid` into the local backup root so
+/// it can be restored. Returns the local backup.
+pub fn pull(appid: u32, name: &str) -> Result<PathBuf> {
+    if parse_listing(name).is_empty() {
+        return Err(Error::FileSystemError(format!("not a backup name: {}", name)));
+    }
+    let (target, transport) = configured(appid)?;
+    let local_dir = backup::backup_root().join(appid.to_string());
+    let local = local_dir.join(name);
+    logging::operation("remote_pull", Some(appid), &local, || {
+        fs::create_dir_all(&local_dir)?;
+        pull_step(appid, name, &target, transport, &local_dir).run()?;
+        Ok(local.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::app_config::RemoteConfig;
+
+    fn args(step: &Step) -> Vec<String> {
+        step.command
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_targets_and_commands() {
+        let target = RemoteTarget::parse("deck@nas:/srv/backups/").unwrap();
+        assert_eq!(target.host, "deck@nas");
+        assert_eq!(target.to_string(), "deck@nas:/srv/backups");
+        assert!(RemoteTarget::parse("/srv/backups").is_err());
+        assert!(RemoteTarget::parse("nas:").is_err());
+        assert!(RemoteTarget::parse("-oProxyCommand=touch /tmp/x:/x").is_err());
+        assert!(RemoteTarget::parse(" -nas:/x").is_err());
+
+        let remote: RemoteConfig = toml::from_str(
+            "target = \"deck@nas:/srv/backups\"\n[games]\n620 = \"deck@nas:/srv/portal\"\n",
+        )
+        .unwrap();
+        assert_eq!(remote.transport, RemoteTransport::Rsync);
+        assert_eq!(remote.target_for(620), Some("deck@nas:/srv/portal"));
+        assert_eq!(remote.target_for(440), Some("deck@nas:/srv/backups"));
+        assert_eq!(RemoteConfig::default().target_for(440), None);
+
+        let backup = Path::new("/home/deck/backups/620/20240101000000");
+        let steps = push_steps(backup, 620, &target, RemoteTransport::Rsync).unwrap();
+        assert_eq!(args(&steps[0]), ["deck@nas", "mkdir -p '/srv/backups/620'"]);
+        assert_eq!(
+            args(&steps[1])[3..],
+            [backup.to_str().unwrap(), "deck@nas:/srv/backups/620/"]
+        );
+        let steps = push_steps(backup, 620, &target, RemoteTransport::Sftp).unwrap();
+        assert_eq!(args(&steps[0]), ["-q", "-b", "-", "deck@nas"]);
+        assert!(steps[0]
+            .input
+            .as_deref()
+            .unwrap()
+            .ends_with("put -r \"/home/deck/backups/620/20240101000000\" \"/srv/backups/620/20240101000000\"\n"));
+
+        let step = pull_step(620, "20240101000000", &target, RemoteTransport::Rsync, Path::new("/b/620"));
+        assert_eq!(args(&step)[3..], ["deck@nas:/srv/backups/620/20240101000000", "/b/620/"]);
+
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(
+            parse_listing("sftp> ls -1 /srv/backups/620\n/srv/backups/620/20240102000000\n/srv/backups/620/20240101000000\nnotes.txt\n"),
+            ["20240101000000", "20240102000000"]
+        );
+    }
+}
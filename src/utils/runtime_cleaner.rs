@@ -1,6 +1,11 @@
 use crate::core::steam;
-use crate::utils::library::parse_appmanifest_installdir;
-use crate::utils::steam_paths;
+use crate::error::Result;
+use crate::utils::app_config;
+use crate::utils::backup;
+use crate::utils::json_model::{CleanerItem, CleanerReport, Versioned};
+use crate::utils::library::{self, parse_appmanifest, parse_appmanifest_installdir};
+use crate::utils::{manifest as manifest_utils, steam_paths, user_config};
+use keyvalues_parser::{Value, Vdf};
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -12,6 +17,8 @@ pub struct RuntimeItem {
     pub reason: String,
     pub selected: bool,
     pub verified: bool,
+    /// Bytes freed by deleting the item, for kinds where it is measured.
+    pub size: Option<u64>,
 }
 
 #[derive(Default)]
@@ -19,18 +26,115 @@ pub struct ScanResults {
     pub install_folders: Vec<RuntimeItem>,
     pub prefixes: Vec<RuntimeItem>,
     pub shader_caches: Vec<RuntimeItem>,
+    pub userdata: Vec<RuntimeItem>,
+    pub workshop: Vec<RuntimeItem>,
+    pub downloads: Vec<RuntimeItem>,
     pub tools: Vec<RuntimeItem>,
+    pub unused_tools: Vec<RuntimeItem>,
+    /// Items left out because they are on the ignore list of the
+    /// `[cleaner]` config section.
+    pub ignored: usize,
+}
+
+impl ScanResults {
+    /// Every kind of item with its title, in the order they are shown.
+    pub fn groups(&self) -> [(&'static str, &Vec<RuntimeItem>); 8] {
+        [
+            ("Orphaned Install Folders", &self.install_folders),
+            ("Orphaned Proton Prefixes", &self.prefixes),
+            ("Unused Shader Caches", &self.shader_caches),
+            ("Orphaned Workshop Content", &self.workshop),
+            ("Stale Downloads", &self.downloads),
+            ("Userdata of Uninstalled Games", &self.userdata),
+            ("Broken Custom Proton Versions", &self.tools),
+            ("Unused Custom Proton Versions", &self.unused_tools),
+        ]
+    }
+
+    /// Like [`ScanResults::groups`], for changing the items.
+    pub fn groups_mut(&mut self) -> [(&'static str, &mut Vec<RuntimeItem>); 8] {
+        [
+            ("Orphaned Install Folders", &mut self.install_folders),
+            ("Orphaned Proton Prefixes", &mut self.prefixes),
+            ("Unused Shader Caches", &mut self.shader_caches),
+            ("Orphaned Workshop Content", &mut self.workshop),
+            ("Stale Downloads", &mut self.downloads),
+            ("Userdata of Uninstalled Games", &mut self.userdata),
+            ("Broken Custom Proton Versions", &mut self.tools),
+            ("Unused Custom Proton Versions", &mut self.unused_tools),
+        ]
+    }
+}
+
+/// Directories of a library where Steam keeps unfinished downloads and
+/// updates, each holding an entry per AppID.
+const DOWNLOAD_DIRS: [&str; 3] = ["downloading", "temp", "staging"];
+
+/// AppID of an entry in a [`DOWNLOAD_DIRS`] directory: either the AppID
+/// itself or a `state_<appid>_<depot>.patch` file.
+fn download_appid(name: &str) -> Option<u32> {
+    name.parse().ok().or_else(|| {
+        name.strip_prefix("state_")?
+            .split('_')
+            .next()?
+            .parse()
+            .ok()
+    })
+}
+
+fn entry_size(path: &Path) -> Option<u64> {
+    if path.is_dir() {
+        backup::dir_size(path).ok()
+    } else {
+        path.metadata().ok().map(|m| m.len())
+    }
+}
+
+/// Directories in `userdata/<account>` that belong to Steam itself rather
+/// than to a game: client settings, screenshots and controller layouts.
+const STEAM_USERDATA_IDS: [u32; 3] = [7, 760, 241100];
+
+/// Whether a `userdata/<account>/<appid>` directory holds Steam Cloud files,
+/// which may be the only copy of the saves when cloud sync was off.
+fn has_cloud_files(dir: &Path) -> bool {
+    fs::read_dir(dir.join("remote"))
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false)
 }
 
 fn is_valid_tool(dir: &Path) -> bool {
     dir.join("proton").exists() || dir.join("proton.sh").exists()
 }
 
+/// Names games can refer to a custom tool by: its directory name and the
+/// internal names declared in its `compatibilitytool.vdf`.
+fn tool_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .into_iter()
+        .collect();
+    let contents = fs::read_to_string(dir.join("compatibilitytool.vdf")).unwrap_or_default();
+    if let Ok(vdf) = Vdf::parse(&contents) {
+        let tools = vdf
+            .value
+            .get_obj()
+            .and_then(|obj| obj.get("compat_tools")?.first())
+            .and_then(Value::get_obj);
+        if let Some(tools) = tools {
+            names.extend(tools.keys().map(|k| k.to_string()));
+        }
+    }
+    names
+}
+
 pub fn scan() -> ScanResults {
     let mut results = ScanResults::default();
+    let mut used_tools = HashSet::new();
     if let Ok(libraries) = steam::get_steam_libraries() {
         let mut appids = HashSet::new();
         let mut installdirs = HashSet::new();
+        let mut updating = HashSet::new();
         for lib in &libraries {
             let steamapps = lib.steamapps_path();
             if let Ok(entries) = fs::read_dir(&steamapps) {
@@ -41,6 +145,14 @@ pub fn scan() -> ScanResults {
                             appids.insert(appid);
                             installdirs.insert(dir);
                         }
+                        if let Some(manifest) = parse_appmanifest(&p).filter(|m| m.is_updating()) {
+                            updating.insert(manifest.appid);
+                        }
+                        if let Some(tool) = library::read_manifest_cached(&p)
+                            .and_then(|c| manifest_utils::get_value(&c, "CompatToolOverride"))
+                        {
+                            used_tools.insert(tool);
+                        }
                     }
                 }
             }
@@ -60,6 +172,7 @@ pub fn scan() -> ScanResults {
                                     reason: "No matching appmanifest".to_string(),
                                     selected: true,
                                     verified: true,
+                                    size: None,
                                 });
                             }
                         }
@@ -80,6 +193,7 @@ pub fn scan() -> ScanResults {
                                 reason: format!("No appmanifest found for AppID {}", app),
                                 selected: true,
                                 verified: true,
+                                size: None,
                             });
                         }
                     }
@@ -99,34 +213,324 @@ pub fn scan() -> ScanResults {
                                 reason: format!("No appmanifest found for AppID {}", app),
                                 selected: true,
                                 verified: true,
+                                size: None,
                             });
                         }
                     }
                 }
             }
         }
+        // Workshop content of uninstalled games, often the biggest leftover
+        for lib in &libraries {
+            let content = lib.steamapps_path().join("workshop/content");
+            for e in fs::read_dir(&content).into_iter().flatten().flatten() {
+                if let Ok(app) = e.file_name().to_string_lossy().parse::<u32>() {
+                    if !appids.contains(&app) {
+                        results.workshop.push(RuntimeItem {
+                            path: e.path(),
+                            app_id: Some(app),
+                            reason: format!("No appmanifest found for AppID {}", app),
+                            selected: true,
+                            verified: true,
+                            size: entry_size(&e.path()),
+                        });
+                    }
+                }
+            }
+        }
+        // Downloads and updates Steam is no longer working on
+        for lib in &libraries {
+            for sub in DOWNLOAD_DIRS {
+                let dir = lib.steamapps_path().join(sub);
+                for e in fs::read_dir(&dir).into_iter().flatten().flatten() {
+                    let Some(app) = download_appid(&e.file_name().to_string_lossy()) else {
+                        continue;
+                    };
+                    if updating.contains(&app) {
+                        continue;
+                    }
+                    results.downloads.push(RuntimeItem {
+                        path: e.path(),
+                        app_id: Some(app),
+                        reason: if appids.contains(&app) {
+                            format!("AppID {} has no install or update pending", app)
+                        } else {
+                            format!("No appmanifest found for AppID {}", app)
+                        },
+                        selected: true,
+                        verified: true,
+                        size: entry_size(&e.path()),
+                    });
+                }
+            }
+        }
+        // Userdata of uninstalled games
+        for userdata in steam_paths::userdata_dirs() {
+            for account in fs::read_dir(&userdata).into_iter().flatten().flatten() {
+                for e in fs::read_dir(account.path()).into_iter().flatten().flatten() {
+                    let Ok(app) = e.file_name().to_string_lossy().parse::<u32>() else {
+                        continue;
+                    };
+                    if appids.contains(&app) || STEAM_USERDATA_IDS.contains(&app) || !e.path().is_dir() {
+                        continue;
+                    }
+                    // Cloud files may be the last copy of the saves.
+                    let cloud = has_cloud_files(&e.path());
+                    results.userdata.push(RuntimeItem {
+                        path: e.path(),
+                        app_id: Some(app),
+                        reason: if cloud {
+                            format!("No appmanifest found for AppID {}; holds Steam Cloud files", app)
+                        } else {
+                            format!("No appmanifest found for AppID {}", app)
+                        },
+                        selected: !cloud,
+                        verified: !cloud,
+                        size: None,
+                    });
+                }
+            }
+        }
     }
 
-    // custom Proton tools
+    // custom Proton tools, broken or not set for any game
+    let mapped = user_config::used_compat_tools();
+    used_tools.extend(mapped.iter().flatten().cloned());
     for dir in steam_paths::compatibilitytools_dirs() {
         if let Ok(entries) = fs::read_dir(&dir) {
             for e in entries.flatten() {
-                if e.path().is_dir() && !is_valid_tool(&e.path()) {
+                let path = e.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                if !is_valid_tool(&path) {
                     results.tools.push(RuntimeItem {
-                        path: e.path(),
+                        path,
                         app_id: None,
                         reason: "Missing proton executable".to_string(),
                         selected: false,
                         verified: false,
+                        size: None,
+                    });
+                } else if !tool_names(&path).iter().any(|n| used_tools.contains(n)) {
+                    // Deleting a symlinked tool only removes the link.
+                    let link = path.symlink_metadata().is_ok_and(|m| m.is_symlink());
+                    results.unused_tools.push(RuntimeItem {
+                        reason: if mapped.is_some() {
+                            "Not set for any game or as the default".to_string()
+                        } else {
+                            "Steam's config.vdf with the tool of each game cannot be read".to_string()
+                        },
+                        selected: mapped.is_some(),
+                        verified: mapped.is_some(),
+                        size: if link { None } else { entry_size(&path) },
+                        path,
+                        app_id: None,
                     });
                 }
             }
         }
     }
 
+    let cleaner = app_config::load().cleaner;
+    let mut ignored = 0;
+    for (_, items) in results.groups_mut() {
+        let before = items.len();
+        items.retain(|item| !cleaner.is_ignored(&item.path));
+        ignored += before - items.len();
+    }
+    results.ignored = ignored;
+
     results
 }
 
 pub fn delete_item(item: &RuntimeItem) -> std::io::Result<()> {
-    fs::remove_dir_all(&item.path)
+    if item.path.is_dir() {
+        fs::remove_dir_all(&item.path)
+    } else {
+        fs::remove_file(&item.path)
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The scan results as CSV, one line per item.
+pub fn report_csv(results: &ScanResults) -> String {
+    let mut csv = String::from("group,path,appid,reason,size,verified\n");
+    for (title, items) in results.groups() {
+        for item in items.iter() {
+            let fields = [
+                csv_field(title),
+                csv_field(&item.path.to_string_lossy()),
+                item.app_id.map(|id| id.to_string()).unwrap_or_default(),
+                csv_field(&item.reason),
+                item.size.map(|s| s.to_string()).unwrap_or_default(),
+                item.verified.to_string(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+/// The scan results as a versioned JSON [`CleanerReport`].
+pub fn report_json(results: &ScanResults) -> String {
+    let items = results
+        .groups()
+        .iter()
+        .flat_map(|(title, items)| items.iter().map(|item| CleanerItem::new(title, item)))
+        .collect();
+    Versioned::new(CleanerReport {
+        items,
+        ignored: results.ignored,
+    })
+    .to_json()
+}
+
+/// Write the scan results to `path`, as CSV if its extension is `.csv`
+/// and as JSON otherwise.
+pub fn export_report(results: &ScanResults, path: &Path) -> Result<()> {
+    let csv = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    let report = if csv {
+        report_csv(results)
+    } else {
+        report_json(results)
+    };
+    fs::write(path, report)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{setup_steam_env, TEST_MUTEX};
+
+    #[test]
+    fn test_scan_leftovers() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        steam::clear_caches();
+        let (home, _, _) = setup_steam_env(4040, false);
+        let steamapps = home.path().join("library/steamapps");
+        fs::write(
+            steamapps.join("appmanifest_4040.acf"),
+            "\"AppState\" { \"appid\" \"4040\" \"name\" \"Game\" \"installdir\" \"Game\" \"CompatToolOverride\" \"ge7\" }",
+        )
+        .unwrap();
+        fs::write(
+            steamapps.join("appmanifest_4044.acf"),
+            "\"AppState\" { \"appid\" \"4044\" \"name\" \"Updating\" \"installdir\" \"Updating\" \"StateFlags\" \"1026\" }",
+        )
+        .unwrap();
+        for dir in ["downloading/4040", "downloading/4044", "temp/4045"] {
+            fs::create_dir_all(steamapps.join(dir)).unwrap();
+        }
+        fs::write(steamapps.join("downloading/4040/chunk"), "123").unwrap();
+        fs::write(steamapps.join("downloading/state_4044_4045.patch"), "").unwrap();
+        let account = home.path().join(".steam/steam/userdata/111111111");
+        for app in ["4040", "4041", "4042", "760", "config"] {
+            fs::create_dir_all(account.join(app)).unwrap();
+        }
+        fs::create_dir_all(account.join("4042/remote")).unwrap();
+        let workshop = steamapps.join("workshop/content");
+        for app in ["4040", "4043"] {
+            fs::create_dir_all(workshop.join(app).join("123456")).unwrap();
+            fs::write(workshop.join(app).join("123456/mod.pak"), "12345").unwrap();
+        }
+        fs::write(account.join("4042/remote/save.dat"), "level 3").unwrap();
+        let tools = home.path().join(".steam/steam/compatibilitytools.d");
+        for tool in ["GE-Proton7-0", "GE-Proton8-1", "GE-Proton9-7", "GE-Proton10-1"] {
+            fs::create_dir_all(tools.join(tool)).unwrap();
+            fs::write(tools.join(tool).join("proton"), "").unwrap();
+        }
+        fs::write(
+            tools.join("GE-Proton7-0/compatibilitytool.vdf"),
+            "\"compatibilitytools\" { \"compat_tools\" { \"ge7\" { \"display_name\" \"GE-Proton7-0\" } } }",
+        )
+        .unwrap();
+        fs::write(
+            home.path().join(".steam/steam/config/config.vdf"),
+            "\"InstallConfigStore\" { \"Software\" { \"Valve\" { \"Steam\" { \"CompatToolMapping\" { \"0\" { \"name\" \"GE-Proton9-7\" } } } } } }",
+        )
+        .unwrap();
+        // Set only as the override of one game in localconfig.vdf.
+        fs::write(
+            account.join("config/localconfig.vdf"),
+            "\"UserLocalConfigStore\" { \"Software\" { \"Valve\" { \"Steam\" { \"CompatToolOverrides\" { \"4041\" { \"name\" \"GE-Proton10-1\" } } } } } }",
+        )
+        .unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_config = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_CONFIG_HOME");
+        user_config::clear_localconfig_cache();
+
+        let results = scan();
+        assert_eq!(results.workshop.len(), 1);
+        assert_eq!(results.workshop[0].path, workshop.join("4043"));
+        assert_eq!(results.workshop[0].size, Some(5));
+        let mut downloads: Vec<(Option<u32>, Option<u64>)> =
+            results.downloads.iter().map(|i| (i.app_id, i.size)).collect();
+        downloads.sort();
+        assert_eq!(downloads, vec![(Some(4040), Some(3)), (Some(4045), Some(0))]);
+        // 4044 is being updated, so its download must be left alone.
+        let updating = steamapps.join("downloading/state_4044_4045.patch");
+        assert!(results.downloads.iter().all(|i| i.path != updating));
+        assert!(results.downloads.iter().all(|i| i.path != steamapps.join("downloading/4044")));
+
+        let mut userdata = results.userdata.clone();
+        userdata.sort_by_key(|item| item.app_id);
+        let found: Vec<(Option<u32>, bool)> = userdata.iter().map(|i| (i.app_id, i.verified)).collect();
+        assert_eq!(found, vec![(Some(4041), true), (Some(4042), false)]);
+        assert!(!userdata[1].selected);
+        assert!(userdata[1].reason.contains("Steam Cloud"));
+        assert_eq!(results.ignored, 0);
+        // GE-Proton10-1 is in use through localconfig.vdf alone.
+        let unused: Vec<&Path> = results.unused_tools.iter().map(|i| i.path.as_path()).collect();
+        assert_eq!(unused, vec![tools.join("GE-Proton8-1")]);
+        assert!(results.unused_tools[0].verified);
+
+        let report: serde_json::Value = serde_json::from_str(&report_json(&results)).unwrap();
+        let workshop_item = report["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|i| i["group"] == "Orphaned Workshop Content")
+            .unwrap();
+        assert_eq!(workshop_item["appid"], 4043);
+        assert_eq!(workshop_item["size"], 5);
+        assert_eq!(workshop_item["verified"], true);
+        let csv = report_csv(&results);
+        assert!(csv.starts_with("group,path,appid,reason,size,verified\n"));
+        assert!(csv.contains("AppID 4042; holds Steam Cloud files,,false\n"));
+        assert_eq!(csv_field("a, \"b\""), "\"a, \"\"b\"\"\"");
+
+        // Ignoring a directory hides everything below it too.
+        let mut config = app_config::load();
+        assert!(config.cleaner.set_ignored(&workshop, true));
+        assert!(!config.cleaner.set_ignored(&workshop, true));
+        config.cleaner.set_ignored(&account.join("4041"), true);
+        app_config::save(&config).unwrap();
+        let results = scan();
+        assert!(results.workshop.is_empty());
+        assert_eq!(results.userdata.len(), 1);
+        assert_eq!(results.userdata[0].app_id, Some(4042));
+        assert_eq!(results.ignored, 2);
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+        if let Some(c) = old_config {
+            std::env::set_var("XDG_CONFIG_HOME", c);
+        }
+    }
 }
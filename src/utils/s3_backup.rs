@@ -0,0 +1,350 @@
+//! Backups in S3-compatible object storage.
+//!
+//! A backup is uploaded as a gzip-compressed tarball under
+//! `<prefix>/<appid>/<timestamp>.tar.gz` in the bucket of the `[s3]` section
+//! of the config, which works with AWS S3, MinIO, Backblaze B2 and other
+//! services speaking the S3 API. Requests are made and signed with
+//! `curl --aws-sigv4`, and archives larger than [`PART_SIZE`] are sent as a
+//! multipart upload. A pulled backup is unpacked into the local backup root
+//! and restored like any other.
+
+use crate::error::{Error, Result};
+use crate::utils::app_config::{self, S3Config};
+use crate::utils::{archive, backup, logging};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Size of the parts of a multipart upload. S3 needs at least 5 MiB.
+const PART_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Percent-encode `s` for a URL, keeping `/` when `path` is set.
+fn encode(s: &str, path: bool) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if path => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Contents of every `<tag>` element of an S3 XML response.
+fn tag_values<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        values.push(&rest[..end]);
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+/// Body completing a multipart upload with the ETags of its parts.
+fn complete_body(etags: &[String]) -> String {
+    let parts: String = etags
+        .iter()
+        .enumerate()
+        .map(|(i, etag)| {
+            format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", i + 1, etag)
+        })
+        .collect();
+    format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts)
+}
+
+struct Bucket {
+    config: S3Config,
+}
+
+impl Bucket {
+    fn from_config(mut config: S3Config) -> Result<Self> {
+        if config.endpoint.trim().is_empty() || config.bucket.trim().is_empty() {
+            return Err(Error::FileSystemError(
+                "no S3 bucket configured, set [s3] endpoint and bucket in the config file"
+                    .to_string(),
+            ));
+        }
+        if config.access_key.is_empty() {
+            config.access_key = std::env::var("AWS_ACCESS_KEY_ID").unwrap_or_default();
+        }
+        if config.secret_key.is_empty() {
+            config.secret_key = std::env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default();
+        }
+        Ok(Self { config })
+    }
+
+    /// Key prefix of the backups of `appid`, ending in `/`.
+    fn game_prefix(&self, appid: u32) -> String {
+        match self.config.prefix.trim_matches('/') {
+            "" => format!("{}/", appid),
+            prefix => format!("{}/{}/", prefix, appid),
+        }
+    }
+
+    fn key(&self, appid: u32, name: &str) -> String {
+        format!("{}{}.tar.gz", self.game_prefix(appid), name)
+    }
+
+    fn url(&self, key: &str, query: &str) -> String {
+        let mut url = format!(
+            "{}/{}/{}",
+            self.config.endpoint.trim().trim_end_matches('/'),
+            self.config.bucket.trim(),
+            encode(key, true)
+        );
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(query);
+        }
+        url
+    }
+
+    /// Run a signed request with `curl`. The credentials are passed on
+    /// stdin so they do not show up in the process list.
+    fn request(&self, method: &str, url: &str, args: &[&str]) -> Result<Vec<u8>> {
+        log::debug!("S3 {} {}", method, url);
+        #[cfg(not(test))]
+        {
+            use crate::utils::dependencies::{command_available, missing_message};
+            if !command_available("curl") {
+                return Err(Error::Network(missing_message("curl")));
+            }
+        }
+        let sigv4 = format!("aws:amz:{}:s3", self.config.region.trim());
+        let mut child = Command::new("curl")
+            .args(["-fsS", "-X", method, "--aws-sigv4", &sigv4])
+            .args(["-H", "x-amz-content-sha256: UNSIGNED-PAYLOAD", "-K", "-"])
+            .args(args)
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let user = format!("{}:{}", self.config.access_key, self.config.secret_key);
+            writeln!(stdin, "user = \"{}\"", user.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::Network(format!(
+                "S3 {} {} failed: {}",
+                method,
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    fn request_text(&self, method: &str, url: &str, args: &[&str]) -> Result<String> {
+        Ok(String::from_utf8_lossy(&self.request(method, url, args)?).into_owned())
+    }
+
+    fn upload(&self, key: &str, file: &Path) -> Result<()> {
+        let path = file.to_string_lossy();
+        if fs::metadata(file)?.len() <= PART_SIZE {
+            self.request("PUT", &self.url(key, ""), &["-T", &path])?;
+            return Ok(());
+        }
+        let response = self.request_text("POST", &self.url(key, "uploads"), &[])?;
+        let upload_id = tag_values(&response, "UploadId")
+            .first()
+            .map(|id| encode(id, false))
+            .ok_or_else(|| Error::Parse("S3 did not return an upload ID".to_string()))?;
+        let query = format!("uploadId={}", upload_id);
+        let etags = match self.upload_parts(key, file, &upload_id) {
+            Ok(etags) => etags,
+            Err(e) => {
+                let _ = self.request("DELETE", &self.url(key, &query), &[]);
+                return Err(e);
+            }
+        };
+        let body = complete_body(&etags);
+        let response = self.request_text(
+            "POST",
+            &self.url(key, &query),
+            &["-H", "Content-Type: application/xml", "--data-binary", &body],
+        )?;
+        // Errors of the completion can come with status 200.
+        if let Some(message) = tag_values(&response, "Message").first() {
+            return Err(Error::Network(format!("S3 upload of {} failed: {}", key, message)));
+        }
+        Ok(())
+    }
+
+    /// Send `file` in parts of [`PART_SIZE`], returning their ETags.
+    fn upload_parts(&self, key: &str, file: &Path, upload_id: &str) -> Result<Vec<String>> {
+        let mut source = File::open(file)?;
+        let part = file.with_extension("part");
+        let mut etags = Vec::new();
+        let result = loop {
+            let written = File::create(&part)
+                .and_then(|mut out| io::copy(&mut (&mut source).take(PART_SIZE), &mut out));
+            match written {
+                Ok(0) => break Ok(etags),
+                Ok(_) => {}
+                Err(e) => break Err(e.into()),
+            }
+            let query = format!("partNumber={}&uploadId={}", etags.len() + 1, upload_id);
+            let etag = self.request_text(
+                "PUT",
+                &self.url(key, &query),
+                &["-T", &part.to_string_lossy(), "-o", "/dev/null", "-w", "%header{etag}"],
+            );
+            match etag {
+                Ok(etag) if !etag.trim().is_empty() => etags.push(etag.trim().to_string()),
+                Ok(_) => break Err(Error::Network(format!("S3 returned no ETag for {}", key))),
+                Err(e) => break Err(e),
+            }
+        };
+        let _ = fs::remove_file(&part);
+        result
+    }
+
+    /// Keys below `prefix`, following continuation tokens.
+    fn list_keys(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = format!("list-type=2&prefix={}", encode(prefix, false));
+            if let Some(token) = &token {
+                query.push_str(&format!("&continuation-token={}", encode(token, false)));
+            }
+            let response = self.request_text("GET", &self.url("", &query), &[])?;
+            keys.extend(tag_values(&response, "Key").into_iter().map(str::to_string));
+            match tag_values(&response, "NextContinuationToken").first() {
+                Some(next) if tag_values(&response, "IsTruncated").first() == Some(&"true") => {
+                    token = Some(next.to_string());
+                }
+                _ => return Ok(keys),
+            }
+        }
+    }
+}
+
+fn configured() -> Result<Bucket> {
+    Bucket::from_config(app_config::load().s3)
+}
+
+/// Backup names of the keys of a game, oldest first.
+fn backup_names(keys: &[String], game_prefix: &str) -> Vec<String> {
+    let mut names: Vec<String> = keys
+        .iter()
+        .filter_map(|k| k.strip_prefix(game_prefix)?.strip_suffix(".tar.gz"))
+        .filter(|name| chrono::NaiveDateTime::parse_from_str(name, "%Y%m%d%H%M%S").is_ok())
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
+/// A new private directory to keep the archive of a backup in while it is
+/// transferred. It is deleted with its contents when dropped.
+fn transfer_dir() -> Result<tempfile::TempDir> {
+    Ok(tempfile::Builder::new().prefix("proton-prefix-manager-").tempdir()?)
+}
+
+/// Compress the local `backup` of `appid` and upload it. Returns the
+/// `s3://` URL of the object.
+pub fn push(backup: &Path, appid: u32) -> Result<String> {
+    logging::operation("s3_push", Some(appid), backup, || {
+        let bucket = configured()?;
+        let name = backup
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| Error::FileSystemError(format!("not a backup: {}", backup.display())))?;
+        let key = bucket.key(appid, name);
+        let dir = transfer_dir()?;
+        let archive = dir.path().join(format!("{}.tar.gz", name));
+        archive::write_archive(backup, &archive)?;
+        bucket.upload(&key, &archive)?;
+        Ok(format!("s3://{}/{}", bucket.config.bucket.trim(), key))
+    })
+}
+
+/// Names of the backups of `appid` in the bucket, oldest first.
+pub fn list(appid: u32) -> Result<Vec<String>> {
+    let bucket = configured()?;
+    let prefix = bucket.game_prefix(appid);
+    Ok(backup_names(&bucket.list_keys(&prefix)?, &prefix))
+}
+
+/// Download the backup `name` of `appid` and unpack it into the local
+/// backup root so it can be restored. Returns the local backup.
+pub fn pull(appid: u32, name: &str) -> Result<PathBuf> {
+    if chrono::NaiveDateTime::parse_from_str(name, "%Y%m%d%H%M%S").is_err() {
+        return Err(Error::FileSystemError(format!("not a backup name: {}", name)));
+    }
+    let local = backup::backup_root().join(appid.to_string()).join(name);
+    logging::operation("s3_pull", Some(appid), &local, || {
+        if local.exists() {
+            return Err(Error::FileSystemError(format!("{} already exists", local.display())));
+        }
+        let bucket = configured()?;
+        let dir = transfer_dir()?;
+        let archive = dir.path().join(format!("{}.tar.gz", name));
+        let path = archive.to_string_lossy();
+        let result = bucket
+            .request("GET", &bucket.url(&bucket.key(appid, name), ""), &["-o", &path])
+            .and_then(|_| {
+                fs::create_dir_all(&local)?;
+                archive::unpack_archive(&archive, &local)
+            });
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&local);
+        }
+        result.map(|_| local.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_and_responses() {
+        let config: S3Config = toml::from_str(
+            "endpoint = \"https://minio.local:9000/\"\nbucket = \"games\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.region, "us-east-1");
+        let bucket = Bucket::from_config(config).unwrap();
+        assert_eq!(
+            bucket.key(620, "20240101000000"),
+            "proton-prefix-manager/620/20240101000000.tar.gz"
+        );
+        assert_eq!(
+            bucket.url("proton-prefix-manager/620/a b.tar.gz", "uploads"),
+            "https://minio.local:9000/games/proton-prefix-manager/620/a%20b.tar.gz?uploads"
+        );
+        assert!(Bucket::from_config(S3Config::default()).is_err());
+
+        let listing = "<ListBucketResult><IsTruncated>false</IsTruncated>\
+            <Contents><Key>proton-prefix-manager/620/20240102000000.tar.gz</Key></Contents>\
+            <Contents><Key>proton-prefix-manager/620/20240101000000.tar.gz</Key></Contents>\
+            <Contents><Key>proton-prefix-manager/620/notes.txt</Key></Contents></ListBucketResult>";
+        let keys: Vec<String> = tag_values(listing, "Key").into_iter().map(str::to_string).collect();
+        assert_eq!(
+            backup_names(&keys, &bucket.game_prefix(620)),
+            ["20240101000000", "20240102000000"]
+        );
+        assert_eq!(tag_values(listing, "IsTruncated"), ["false"]);
+
+        assert_eq!(
+            complete_body(&["\"a1\"".to_string(), "\"b2\"".to_string()]),
+            "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>\"a1\"</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"b2\"</ETag></Part></CompleteMultipartUpload>"
+        );
+        assert_eq!(encode("a+b/c=", false), "a%2Bb%2Fc%3D");
+    }
+}
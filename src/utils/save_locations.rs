@@ -0,0 +1,477 @@
+//! Save and config location detection using PCGamingWiki.
+//!
+//! Paths are looked up once per AppID and cached on disk. The cached entries
+//! keep the Windows paths as written on the wiki so they can be mapped into
+//! whichever prefix currently belongs to the game.
+
+use crate::error::{Error, Result};
+use crate::utils::http;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const API_URL: &str = "https://www.pcgamingwiki.com/w/api.php";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SaveKind {
+    Saves,
+    Config,
+}
+
+impl SaveKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SaveKind::Saves => "Save game",
+            SaveKind::Config => "Configuration",
+        }
+    }
+}
+
+/// A location as listed on PCGamingWiki.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WikiPath {
+    pub kind: SaveKind,
+    pub windows_path: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    page: String,
+    paths: Vec<WikiPath>,
+}
+
+/// A wiki location resolved against a game's prefix.
+#[derive(Clone, Debug)]
+pub struct SaveLocation {
+    pub kind: SaveKind,
+    pub windows_path: String,
+    /// Directory or file inside the prefix (or install dir) if the path
+    /// could be mapped.
+    pub path: Option<PathBuf>,
+}
+
+fn cache_file(appid: u32) -> PathBuf {
+    http::cache_root()
+        .join("pcgamingwiki")
+        .join(format!("{}.json", appid))
+}
+
+fn read_cache(appid: u32) -> Option<CacheEntry> {
+    let contents = fs::read_to_string(cache_file(appid)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_cache(appid: u32, entry: &CacheEntry) -> Result<()> {
+    let path = cache_file(appid);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(entry).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+fn find_page_name(appid: u32) -> Result<Option<String>> {
+    let condition = format!("Infobox_game.Steam_AppID HOLDS \"{}\"", appid);
+    let body = http::get(
+        API_URL,
+        &[
+            ("action", "cargoquery"),
+            ("tables", "Infobox_game"),
+            ("fields", "Infobox_game._pageName=Page"),
+            ("where", &condition),
+            ("format", "json"),
+        ],
+    )?;
+    let json: serde_json::Value =
+        serde_json::from_str(&body).map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(json["cargoquery"]
+        .get(0)
+        .and_then(|r| r["title"]["Page"].as_str())
+        .map(|s| s.to_string()))
+}
+
+fn fetch_wikitext(page: &str) -> Result<String> {
+    let body = http::get(
+        API_URL,
+        &[
+            ("action", "parse"),
+            ("page", page),
+            ("prop", "wikitext"),
+            ("format", "json"),
+        ],
+    )?;
+    let json: serde_json::Value =
+        serde_json::from_str(&body).map_err(|e| Error::Parse(e.to_string()))?;
+    json["parse"]["wikitext"]["*"]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| Error::Parse(format!("no wikitext returned for {}", page)))
+}
+
+/// Remove `<ref>` annotations and other inline markup from a wiki path.
+fn strip_markup(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("<ref") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        if let Some(end) = after.find("/>").filter(|e| !after[..*e].contains('>')) {
+            rest = &after[end + 2..];
+        } else if let Some(end) = after.find("</ref>") {
+            rest = &after[end + 6..];
+        } else {
+            rest = "";
+        }
+    }
+    out.push_str(rest);
+    out.replace("<code>", "")
+        .replace("</code>", "")
+        .trim()
+        .to_string()
+}
+
+/// Split template arguments on `|`, ignoring separators inside nested templates.
+fn split_args(inner: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                depth += 1;
+                current.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                depth -= 1;
+                current.push_str("}}");
+            }
+            '|' if depth == 0 => args.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    args.push(current);
+    args
+}
+
+/// Extract Windows save and config paths from a PCGamingWiki article.
+pub fn parse_wikitext(wikitext: &str) -> Vec<WikiPath> {
+    let mut paths = Vec::new();
+    for (marker, kind) in [
+        ("{{Game data/saves|", SaveKind::Saves),
+        ("{{Game data/config|", SaveKind::Config),
+    ] {
+        let mut rest = wikitext;
+        while let Some(start) = rest.find(marker) {
+            let body = &rest[start + marker.len()..];
+            let mut depth = 1;
+            let mut end = None;
+            let bytes = body.as_bytes();
+            let mut i = 0;
+            while i + 1 < bytes.len() {
+                if bytes[i] == b'{' && bytes[i + 1] == b'{' {
+                    depth += 1;
+                    i += 2;
+                } else if bytes[i] == b'}' && bytes[i + 1] == b'}' {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(i);
+                        break;
+                    }
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            let Some(end) = end else { break };
+            let args = split_args(&body[..end]);
+            if args.first().map(|p| p.trim()) == Some("Windows") {
+                for arg in &args[1..] {
+                    let cleaned = strip_markup(arg);
+                    if !cleaned.is_empty() {
+                        paths.push(WikiPath {
+                            kind,
+                            windows_path: cleaned,
+                        });
+                    }
+                }
+            }
+            rest = &body[end + 2..];
+        }
+    }
+    paths
+}
+
+/// Translate a `{{p|...}}` placeholder into a location relative to the prefix.
+fn placeholder_base(name: &str, prefix: &Path, install_dir: Option<&Path>) -> Option<PathBuf> {
+    let drive_c = prefix.join("pfx/drive_c");
+    let user = drive_c.join("users/steamuser");
+    match name.to_lowercase().replace('/', "\\").as_str() {
+        "userprofile" => Some(user),
+        "userprofile\\documents" => Some(user.join("Documents")),
+        "userprofile\\appdata\\locallow" => Some(user.join("AppData/LocalLow")),
+        "appdata" => Some(user.join("AppData/Roaming")),
+        "localappdata" => Some(user.join("AppData/Local")),
+        "programdata" => Some(drive_c.join("ProgramData")),
+        "public" => Some(drive_c.join("users/Public")),
+        "windir" => Some(drive_c.join("windows")),
+        "game" => install_dir.map(Path::to_path_buf),
+        _ => None,
+    }
+}
+
+/// Map a Windows path from PCGamingWiki into the game's prefix.
+///
+/// The result is truncated before the first component containing a
+/// wildcard or unresolved placeholder. It names a directory or, for
+/// locations like `{{p|localappdata}}\Game\config.ini`, a single file,
+/// and need not exist yet. Registry keys and unknown placeholders yield
+/// `None`.
+pub fn map_to_prefix(windows_path: &str, prefix: &Path, install_dir: Option<&Path>) -> Option<PathBuf> {
+    let trimmed = windows_path.trim();
+    let (mut base, remainder) = if let Some(rest) = trimmed.strip_prefix("{{p|") {
+        let end = rest.find("}}")?;
+        let base = placeholder_base(&rest[..end], prefix, install_dir)?;
+        (base, &rest[end + 2..])
+    } else if trimmed.len() > 2
+        && trimmed.as_bytes()[0].is_ascii_alphabetic()
+        && trimmed[1..].starts_with(":\\")
+    {
+        let drive = trimmed[..1].to_lowercase();
+        if drive != "c" {
+            return None;
+        }
+        (prefix.join("pfx/drive_c"), &trimmed[3..])
+    } else {
+        return None;
+    };
+
+    for component in remainder.split(['\\', '/']) {
+        let component = component.trim();
+        if component.is_empty() {
+            continue;
+        }
+        if component.contains(['*', '?', '<', '>', '{']) {
+            break;
+        }
+        base.push(component);
+    }
+    Some(base)
+}
+
+/// Return the cached wiki paths for `appid` without going online.
+pub fn cached_paths(appid: u32) -> Option<Vec<WikiPath>> {
+    read_cache(appid).map(|c| c.paths)
+}
+
+/// Look up save and config paths on PCGamingWiki, using the local cache
+/// unless `refresh` is set.
+pub fn lookup(appid: u32, refresh: bool) -> Result<Vec<WikiPath>> {
+    if !refresh {
+        if let Some(paths) = cached_paths(appid) {
+            return Ok(paths);
+        }
+    }
+    let page = find_page_name(appid)?.ok_or_else(|| {
+        Error::Network(format!("no PCGamingWiki article found for AppID {}", appid))
+    })?;
+    let paths = parse_wikitext(&fetch_wikitext(&page)?);
+    write_cache(
+        appid,
+        &CacheEntry {
+            page,
+            paths: paths.clone(),
+        },
+    )?;
+    Ok(paths)
+}
+
+/// Resolve wiki paths against the prefix and install directory of a game.
+pub fn resolve(paths: &[WikiPath], prefix: &Path, install_dir: Option<&Path>) -> Vec<SaveLocation> {
+    paths
+        .iter()
+        .map(|p| SaveLocation {
+            kind: p.kind,
+            windows_path: p.windows_path.clone(),
+            path: map_to_prefix(&p.windows_path, prefix, install_dir),
+        })
+        .collect()
+}
+
+/// Save and config locations of `appid` mapped into `prefix`, taken from
+/// the cached lookup only. Empty if the game was never looked up.
+pub fn cached_locations(appid: u32, prefix: &Path) -> Vec<PathBuf> {
+    let install_dir = crate::core::steam::find_install_dir(appid);
+    cached_paths(appid)
+        .map(|paths| {
+            resolve(&paths, prefix, install_dir.as_deref())
+                .into_iter()
+                .filter_map(|l| l.path)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Detect existing save/config directories and files inside a game's prefix.
+///
+/// Used by save-only backups; locations outside the prefix are ignored.
+pub fn detect_prefix_locations(appid: u32, prefix: &Path) -> Result<Vec<PathBuf>> {
+    let paths = lookup(appid, false)?;
+    let install_dir = crate::core::steam::find_install_dir(appid);
+    let mut found: Vec<PathBuf> = resolve(&paths, prefix, install_dir.as_deref())
+        .into_iter()
+        .filter_map(|l| l.path)
+        .filter(|p| p.starts_with(prefix) && p.exists())
+        .collect();
+    found.sort();
+    found.dedup();
+    // Drop locations already covered by a parent directory
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for path in found {
+        if !unique.iter().any(|u| path.starts_with(u)) {
+            unique.push(path);
+        }
+    }
+    Ok(unique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+    use tempfile::tempdir;
+
+    const WIKITEXT: &str = r#"
+===Game data===
+{{Game data|
+{{Game data/config|Windows|{{p|userprofile\Documents}}\My Games\Example\Config\<ref>Only after launch.</ref>}}
+{{Game data/config|OS X|{{p|osxhome}}/Library/Example/}}
+}}
+{{Game data|
+{{Game data/saves|Windows|{{p|appdata}}\Example\Saves\*.sav|{{p|hkcu}}\Software\Example}}
+{{Game data/saves|Steam|{{p|steam}}\userdata\{{p|uid}}\620\}}
+}}
+"#;
+
+    #[test]
+    fn test_parse_wikitext() {
+        let paths = parse_wikitext(WIKITEXT);
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[0].kind, SaveKind::Saves);
+        assert_eq!(paths[0].windows_path, r"{{p|appdata}}\Example\Saves\*.sav");
+        assert_eq!(paths[2].kind, SaveKind::Config);
+        assert_eq!(
+            paths[2].windows_path,
+            r"{{p|userprofile\Documents}}\My Games\Example\Config\"
+        );
+    }
+
+    #[test]
+    fn test_map_to_prefix() {
+        let prefix = Path::new("/lib/steamapps/compatdata/620");
+        let install = Path::new("/lib/steamapps/common/Example");
+        let user = prefix.join("pfx/drive_c/users/steamuser");
+
+        assert_eq!(
+            map_to_prefix(r"{{p|appdata}}\Example\Saves\*.sav", prefix, None),
+            Some(user.join("AppData/Roaming/Example/Saves"))
+        );
+        assert_eq!(
+            map_to_prefix(r"{{p|userprofile\Documents}}\My Games\Example\", prefix, None),
+            Some(user.join("Documents/My Games/Example"))
+        );
+        assert_eq!(
+            map_to_prefix(r"{{p|game}}\save", prefix, Some(install)),
+            Some(install.join("save"))
+        );
+        assert_eq!(
+            map_to_prefix(r"{{p|localappdata}}\Example\config.ini", prefix, None),
+            Some(user.join("AppData/Local/Example/config.ini"))
+        );
+        assert_eq!(map_to_prefix(r"{{p|hkcu}}\Software\Example", prefix, None), None);
+        assert_eq!(map_to_prefix(r"{{p|game}}\save", prefix, None), None);
+    }
+
+    #[test]
+    fn test_lookup_uses_cache() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let dir = tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", dir.path());
+
+        let appid = 424242;
+        let _ = fs::remove_file(cache_file(appid));
+        {
+            let mut responses = http::HTTP_RESPONSES.lock().unwrap();
+            responses.clear();
+            responses.push((
+                format!("{}?action=cargoquery", API_URL),
+                r#"{"cargoquery":[{"title":{"Page":"Example"}}]}"#.to_string(),
+            ));
+            responses.push((
+                format!("{}?action=parse", API_URL),
+                serde_json::json!({"parse": {"wikitext": {"*": WIKITEXT}}}).to_string(),
+            ));
+        }
+
+        let paths = lookup(appid, false).unwrap();
+        assert_eq!(paths.len(), 3);
+
+        http::HTTP_RESPONSES.lock().unwrap().clear();
+        let cached = lookup(appid, false).unwrap();
+        assert_eq!(cached, paths);
+
+        let _ = fs::remove_file(cache_file(appid));
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+
+    #[test]
+    fn test_detect_prefix_locations_keeps_files() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let dir = tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", dir.path());
+
+        let appid = 424243;
+        let windows_path = |kind, path: &str| WikiPath {
+            kind,
+            windows_path: path.to_string(),
+        };
+        write_cache(
+            appid,
+            &CacheEntry {
+                page: "Example".to_string(),
+                paths: vec![
+                    windows_path(SaveKind::Saves, r"{{p|appdata}}\Example\Saves\"),
+                    windows_path(SaveKind::Saves, r"{{p|appdata}}\Example\Saves\slot1.sav"),
+                    windows_path(SaveKind::Config, r"{{p|localappdata}}\Example\config.ini"),
+                    windows_path(SaveKind::Config, r"{{p|localappdata}}\Example\missing.ini"),
+                ],
+            },
+        )
+        .unwrap();
+        let prefix = dir.path().join("compatdata/620");
+        let user = prefix.join("pfx/drive_c/users/steamuser");
+        fs::create_dir_all(user.join("AppData/Roaming/Example/Saves")).unwrap();
+        fs::write(user.join("AppData/Roaming/Example/Saves/slot1.sav"), "save").unwrap();
+        fs::create_dir_all(user.join("AppData/Local/Example")).unwrap();
+        fs::write(user.join("AppData/Local/Example/config.ini"), "fov=90").unwrap();
+
+        assert_eq!(
+            detect_prefix_locations(appid, &prefix).unwrap(),
+            vec![
+                user.join("AppData/Local/Example/config.ini"),
+                user.join("AppData/Roaming/Example/Saves"),
+            ]
+        );
+
+        if let Some(h) = old_home {
+            std::env::set_var("HOME", h);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Inspecting and clearing the parts of a game's shader cache.
+//!
+//! Steam keeps per-game caches in `steamapps/shadercache/<appid>`. The
+//! subdirectories belong to different components and are rebuilt
+//! independently, so they can be cleared one at a time. Direct3D and the
+//! GPU drivers keep more caches inside the prefix in
+//! `compatdata/<appid>/pfx`, see [`backup::PREFIX_SHADER_CACHES`]. The
+//! Mesa and NVIDIA caches in the user's cache directory are listed too,
+//! but they are shared by every game and program, so they are only cleared
+//! when asked for explicitly.
+
+use crate::core::models::SteamLibrary;
+use crate::core::steam;
+use crate::error::{Error, Result};
+use crate::utils::{backup, logging};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CacheKind {
+    /// DXVK state cache in `DXVK_state_cache`.
+    Dxvk,
+    /// Fossilize pipeline caches in `fozpipelinesv*`.
+    Fossilize,
+    /// Mesa shader caches in `mesa_shader_cache*`.
+    Mesa,
+    /// Direct3D, NVIDIA and AMD caches inside the prefix.
+    Prefix,
+    /// Mesa and NVIDIA caches in `~/.cache`, shared by all games.
+    Driver,
+    /// Anything else, such as NVIDIA's GL cache.
+    Other,
+}
+
+impl CacheKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CacheKind::Dxvk => "DXVK state cache",
+            CacheKind::Fossilize => "Fossilize pipelines",
+            CacheKind::Mesa => "Mesa shader cache",
+            CacheKind::Prefix => "Caches in the prefix",
+            CacheKind::Driver => "Driver caches (all games)",
+            CacheKind::Other => "Other",
+        }
+    }
+
+    /// Kind of a subdirectory of `shadercache/<appid>`.
+    pub fn of(dir_name: &str) -> CacheKind {
+        if dir_name.starts_with("DXVK_state_cache") {
+            CacheKind::Dxvk
+        } else if dir_name.starts_with("fozpipelines") {
+            CacheKind::Fossilize
+        } else if dir_name.starts_with("mesa_shader_cache") {
+            CacheKind::Mesa
+        } else {
+            CacheKind::Other
+        }
+    }
+}
+
+/// Caches the GPU drivers keep for every program of the user, below the
+/// XDG cache directory unless moved with the drivers' own variables.
+fn driver_caches() -> Vec<PathBuf> {
+    let cache = dirs_next::cache_dir().unwrap_or_else(|| PathBuf::from(".cache"));
+    let mut dirs = vec![
+        std::env::var_os("MESA_SHADER_CACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| cache.join("mesa_shader_cache")),
+        cache.join("mesa_shader_cache_db"),
+        std::env::var_os("__GL_SHADER_DISK_CACHE_PATH")
+            .map(|p| PathBuf::from(p).join("GLCache"))
+            .unwrap_or_else(|| cache.join("nvidia/GLCache")),
+    ];
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// Entries of the game's shader cache and the caches in its prefix in
+/// every library, and the shared driver caches, with their kind.
+fn entries(appid: u32, libraries: &[SteamLibrary]) -> Vec<(CacheKind, PathBuf)> {
+    let mut found = Vec::new();
+    for lib in libraries {
+        let cache = lib
+            .steamapps_path()
+            .join("shadercache")
+            .join(appid.to_string());
+        if let Ok(dir) = fs::read_dir(&cache) {
+            for entry in dir.flatten() {
+                let kind = CacheKind::of(&entry.file_name().to_string_lossy());
+                found.push((kind, entry.path()));
+            }
+        }
+        let compatdata = lib
+            .steamapps_path()
+            .join("compatdata")
+            .join(appid.to_string());
+        for dir in backup::PREFIX_SHADER_CACHES {
+            let path = compatdata.join(dir);
+            if path.is_dir() {
+                found.push((CacheKind::Prefix, path));
+            }
+        }
+    }
+    found.extend(driver_caches().into_iter().map(|dir| (CacheKind::Driver, dir)));
+    found
+}
+
+/// Size in bytes of each kind of cache present for a game.
+pub fn sizes(appid: u32, libraries: &[SteamLibrary]) -> BTreeMap<CacheKind, u64> {
+    let mut sizes = BTreeMap::new();
+    for (kind, path) in entries(appid, libraries) {
+        let size = if path.is_dir() {
+            backup::dir_size(&path).unwrap_or(0)
+        } else {
+            path.metadata().map(|m| m.len()).unwrap_or(0)
+        };
+        *sizes.entry(kind).or_insert(0) += size;
+    }
+    sizes
+}
+
+/// Remove the given kinds of cache. Returns the number of bytes freed.
+pub fn clear(appid: u32, libraries: &[SteamLibrary], kinds: &[CacheKind]) -> Result<u64> {
+    let mut freed = 0;
+    for (kind, path) in entries(appid, libraries) {
+        if !kinds.contains(&kind) {
+            continue;
+        }
+        if path.is_dir() {
+            freed += backup::dir_size(&path).unwrap_or(0);
+            fs::remove_dir_all(&path)?;
+        } else {
+            freed += path.metadata().map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(freed)
+}
+
+/// The game's shader cache directory: an existing one in any library, or
+/// the one Steam would create next to the game's appmanifest.
+pub fn cache_dir(appid: u32, libraries: &[SteamLibrary]) -> Option<PathBuf> {
+    libraries
+        .iter()
+        .map(|lib| {
+            lib.steamapps_path()
+                .join("shadercache")
+                .join(appid.to_string())
+        })
+        .find(|dir| dir.is_dir())
+        .or_else(|| {
+            let steamapps = steam::find_manifest(appid)?.parent()?.to_path_buf();
+            Some(steamapps.join("shadercache").join(appid.to_string()))
+        })
+}
+
+/// Directory holding standalone shader cache backups of a game.
+fn backups_dir(appid: u32) -> PathBuf {
+    backup::backup_root()
+        .join("shadercache")
+        .join(appid.to_string())
+}
+
+/// Copy the game's shader cache into a timestamped backup directory.
+pub fn create_backup(appid: u32, libraries: &[SteamLibrary]) -> Result<PathBuf> {
+    logging::operation("backup_shadercache", Some(appid), &backups_dir(appid), || {
+        let cache = cache_dir(appid, libraries)
+            .filter(|dir| dir.is_dir())
+            .ok_or_else(|| {
+                Error::FileSystemError(format!("No shader cache found for {}", appid))
+            })?;
+        backup::create_backup_in(&cache, &backups_dir(appid))
+    })
+}
+
+/// Standalone shader cache backups of a game, oldest first.
+pub fn list_backups(appid: u32) -> Vec<PathBuf> {
+    backup::list_backups_in(&backups_dir(appid))
+}
+
+/// Replace the game's shader cache with a backup.
+pub fn restore_backup(appid: u32, libraries: &[SteamLibrary], backup_path: &Path) -> Result<PathBuf> {
+    logging::operation("restore_shadercache", Some(appid), backup_path, || {
+        let cache = cache_dir(appid, libraries).ok_or_else(|| {
+            Error::InvalidManifest(format!("appmanifest for {} not found", appid))
+        })?;
+        backup::restore_prefix(backup_path, &cache)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+
+    #[test]
+    fn test_sizes_and_clear() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_cache = std::env::var("XDG_CACHE_HOME").ok();
+        std::env::set_var("HOME", dir.path());
+        std::env::remove_var("XDG_CACHE_HOME");
+        std::env::remove_var("MESA_SHADER_CACHE_DIR");
+        std::env::remove_var("__GL_SHADER_DISK_CACHE_PATH");
+        let cache = dir.path().join("steamapps/shadercache/620");
+        for (sub, bytes) in [
+            ("DXVK_state_cache", 10),
+            ("fozpipelinesv6", 20),
+            ("mesa_shader_cache_sf", 30),
+            ("nvidiav1", 5),
+        ] {
+            fs::create_dir_all(cache.join(sub)).unwrap();
+            fs::write(cache.join(sub).join("data"), vec![0u8; bytes]).unwrap();
+        }
+        let local = dir
+            .path()
+            .join("steamapps/compatdata/620/pfx/drive_c/users/steamuser/AppData/Local");
+        fs::create_dir_all(local.join("NVIDIA/DXCache")).unwrap();
+        fs::write(local.join("NVIDIA/DXCache/cache.bin"), vec![0u8; 7]).unwrap();
+        fs::create_dir_all(local.join("Game/Saves")).unwrap();
+        let libs = vec![SteamLibrary::new(dir.path().to_path_buf()).unwrap()];
+
+        let found = sizes(620, &libs);
+        assert_eq!(found[&CacheKind::Dxvk], 10);
+        assert_eq!(found[&CacheKind::Fossilize], 20);
+        assert_eq!(found[&CacheKind::Mesa], 30);
+        assert_eq!(found[&CacheKind::Prefix], 7);
+        assert_eq!(found[&CacheKind::Other], 5);
+
+        let freed = clear(620, &libs, &[CacheKind::Dxvk, CacheKind::Mesa]).unwrap();
+        assert_eq!(freed, 40);
+        assert!(!cache.join("DXVK_state_cache").exists());
+        assert!(cache.join("fozpipelinesv6").exists());
+        assert_eq!(sizes(620, &libs).len(), 3);
+
+        assert_eq!(clear(620, &libs, &[CacheKind::Prefix]).unwrap(), 7);
+        assert!(!local.join("NVIDIA/DXCache").exists());
+        assert!(local.join("Game/Saves").exists());
+
+        fs::create_dir_all(local.join("D3DSCache")).unwrap();
+        let mesa = dir.path().join(".cache/mesa_shader_cache");
+        fs::create_dir_all(&mesa).unwrap();
+        fs::write(mesa.join("index"), vec![0u8; 3]).unwrap();
+        assert_eq!(sizes(620, &libs)[&CacheKind::Driver], 3);
+        backup::clear_shader_cache(620, &libs).unwrap();
+        assert!(!cache.exists());
+        assert!(!local.join("D3DSCache").exists());
+        assert!(local.join("Game/Saves").exists());
+        // The shared caches are only cleared when asked for.
+        assert!(mesa.exists());
+        assert_eq!(clear(620, &libs, &[CacheKind::Driver]).unwrap(), 3);
+        assert!(!mesa.exists());
+
+        match old_home {
+            Some(h) => std::env::set_var("HOME", h),
+            None => std::env::remove_var("HOME"),
+        }
+        if let Some(c) = old_cache {
+            std::env::set_var("XDG_CACHE_HOME", c);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Safety snapshots of a prefix taken before it is restored or reset.
+//!
+//! A snapshot is a backup in `<backup root>/snapshots/<appid>/pre-<operation>-<timestamp>`
+//! whose files are reflinks or hard links of the prefix's, so taking one is
+//! almost free. Since restores replace files instead of writing into them,
+//! a snapshot keeps the prefix as it was and can be restored like any other
+//! backup. Registry hives and the game's known save locations are copied
+//! instead of hard-linked, as Wine and games may write into those in
+//! place. Without reflinks, other files a game rewrites in place change in
+//! the snapshot too. Snapshots older than the retention window of the `[snapshots]`
+//! config section are deleted whenever a new one is taken.
+
+use crate::error::Result;
+use crate::utils::app_config::{self, SnapshotConfig};
+use crate::utils::backup;
+use crate::utils::backup_format::{self, BackupType, Descriptor};
+use crate::utils::prefix_info::prefix_appid;
+use crate::utils::{logging, save_locations};
+use chrono::{Duration, Local, NaiveDateTime};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S";
+
+/// Directory holding a directory of snapshots per prefix.
+pub fn snapshot_root() -> PathBuf {
+    backup::backup_root().join("snapshots")
+}
+
+/// When the snapshot was taken, from the timestamp ending its name and
+/// possibly followed by a `.<n>` suffix.
+fn taken_at(snapshot: &Path) -> Option<NaiveDateTime> {
+    let name = snapshot.file_name()?.to_str()?;
+    let timestamp = name.rsplit('-').next()?.split('.').next()?;
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()
+}
+
+/// Snapshots of the prefix named `name`, usually its AppID, oldest first.
+pub fn list(name: &str) -> Vec<PathBuf> {
+    let mut snapshots: Vec<PathBuf> = fs::read_dir(snapshot_root().join(name))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| taken_at(p).is_some())
+        .collect();
+    snapshots.sort_by_key(|p| taken_at(p));
+    snapshots
+}
+
+/// Snapshots and then backups of `prefix`, each newest first: where to
+/// look for a good copy of a damaged file.
+pub fn copies(prefix: &Path) -> Vec<PathBuf> {
+    let mut copies = prefix
+        .file_name()
+        .map(|name| list(&name.to_string_lossy()))
+        .unwrap_or_default();
+    copies.reverse();
+    if let Some(appid) = prefix_appid(prefix) {
+        copies.extend(backup::list_backups(appid).into_iter().rev());
+    }
+    copies
+}
+
+fn take_with(config: &SnapshotConfig, prefix: &Path, operation: &str) -> Result<Option<PathBuf>> {
+    if !config.enabled || !prefix.is_dir() {
+        return Ok(None);
+    }
+    let Some(name) = prefix.file_name() else {
+        return Ok(None);
+    };
+    let base = format!("pre-{}-{}", operation, Local::now().format(TIMESTAMP_FORMAT));
+    let dir = snapshot_root().join(name);
+    let mut dest = dir.join(&base);
+    // Operations within the same second each get their own snapshot.
+    let mut n = 1;
+    while dest.exists() {
+        n += 1;
+        dest = dir.join(format!("{}.{}", base, n));
+    }
+    let saves = prefix_appid(prefix)
+        .map(|appid| save_locations::cached_locations(appid, prefix))
+        .unwrap_or_default();
+    let written_in_place = |file: &Path| {
+        file.extension().is_some_and(|ext| ext == "reg") || saves.iter().any(|s| file.starts_with(s))
+    };
+    logging::operation("snapshot", prefix_appid(prefix), &dest, || {
+        backup::link_dir(prefix, &dest, &written_in_place)?;
+        backup_format::write(&dest, &Descriptor::new(BackupType::Plain, prefix))
+    })?;
+    log::info!("Snapshot of {} saved to {}", prefix.display(), dest.display());
+    if let Err(e) = cleanup(config.retention_days, Local::now().naive_local()) {
+        log::warn!("Failed to clean up old snapshots: {}", e);
+    }
+    Ok(Some(dest))
+}
+
+/// Snapshot `prefix` before `operation`, e.g. `restore`, unless snapshots
+/// are turned off or there is no prefix yet. Returns the snapshot.
+pub fn take(prefix: &Path, operation: &str) -> Result<Option<PathBuf>> {
+    take_with(&app_config::load().snapshots, prefix, operation)
+}
+
+/// Delete the snapshots taken more than `retention_days` before `now`.
+/// Returns how many were deleted.
+pub fn cleanup(retention_days: u32, now: NaiveDateTime) -> Result<usize> {
+    let cutoff = now - Duration::days(retention_days.into());
+    let mut removed = 0;
+    for dir in fs::read_dir(snapshot_root()).into_iter().flatten().flatten() {
+        let name = dir.file_name().to_string_lossy().into_owned();
+        for snapshot in list(&name) {
+            if taken_at(&snapshot).is_some_and(|t| t < cutoff) {
+                fs::remove_dir_all(&snapshot)?;
+                removed += 1;
+            }
+        }
+        // Only succeeds once the directory is empty.
+        let _ = fs::remove_dir(dir.path());
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::TEST_MUTEX;
+    use crate::utils::{prefix_info, prefix_repair};
+
+    #[test]
+    fn test_snapshot_survives_restore() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        let prefix = home.path().join("compatdata/620");
+        fs::create_dir_all(prefix.join("pfx/drive_c")).unwrap();
+        fs::write(prefix.join("pfx/drive_c/save.dat"), "level 3").unwrap();
+        let config = SnapshotConfig::default();
+        let snapshot = take_with(&config, &prefix, "restore").unwrap().unwrap();
+        assert!(snapshot.starts_with(snapshot_root().join("620")));
+        assert!(snapshot.file_name().unwrap().to_string_lossy().starts_with("pre-restore-"));
+
+        // A delta restore replaces the changed file rather than writing
+        // into the one shared with the snapshot.
+        let backup = home.path().join("backup");
+        fs::create_dir_all(backup.join("pfx/drive_c")).unwrap();
+        fs::write(backup.join("pfx/drive_c/save.dat"), "level 10").unwrap();
+        backup::restore_prefix_delta(&backup, &prefix).unwrap();
+        assert_eq!(fs::read_to_string(prefix.join("pfx/drive_c/save.dat")).unwrap(), "level 10");
+        assert_eq!(fs::read_to_string(snapshot.join("pfx/drive_c/save.dat")).unwrap(), "level 3");
+        let second = take_with(&config, &prefix, "restore").unwrap().unwrap();
+        assert_ne!(second, snapshot);
+        assert_eq!(list("620").len(), 2);
+        fs::remove_dir_all(&second).unwrap();
+        assert_eq!(list("620"), vec![snapshot.clone()]);
+
+        assert_eq!(cleanup(7, Local::now().naive_local()).unwrap(), 0);
+        let later = Local::now().naive_local() + Duration::days(8);
+        assert_eq!(cleanup(7, later).unwrap(), 1);
+        assert!(!snapshot.exists());
+        assert!(!snapshot_root().join("620").exists());
+
+        let disabled = SnapshotConfig {
+            enabled: false,
+            ..SnapshotConfig::default()
+        };
+        assert_eq!(take_with(&disabled, &prefix, "reset").unwrap(), None);
+
+        match old_home {
+            Some(h) => std::env::set_var("HOME", h),
+            None => std::env::remove_var("HOME"),
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+
+    #[test]
+    fn test_registry_restored_from_snapshot() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+
+        let prefix = home.path().join("compatdata/620");
+        let wine_prefix = prefix.join("pfx");
+        fs::create_dir_all(&wine_prefix).unwrap();
+        for reg in prefix_info::REGISTRY_FILES {
+            fs::write(wine_prefix.join(reg), "WINE REGISTRY Version 2\n;; snapshot\n").unwrap();
+        }
+        let backup = backup::backup_root().join("620/20240101000000/pfx");
+        fs::create_dir_all(&backup).unwrap();
+        fs::write(backup.join("system.reg"), "WINE REGISTRY Version 2\n;; backup\n").unwrap();
+        let config = SnapshotConfig::default();
+        let snapshot = take_with(&config, &prefix, "restore").unwrap().unwrap();
+        // Hives are copied into snapshots, so one truncated in place leaves
+        // the snapshot's alone.
+        fs::write(wine_prefix.join("system.reg"), "").unwrap();
+        assert_eq!(copies(&prefix), vec![snapshot.clone(), backup.parent().unwrap().to_path_buf()]);
+
+        let fixes = prefix_repair::fix_registry(&wine_prefix, &copies(&prefix)).unwrap();
+        assert_eq!(fixes.len(), 1);
+        let source = snapshot.join("pfx/system.reg");
+        assert!(fixes[0].starts_with(&format!("restored system.reg from {}", source.display())));
+        assert!(fixes[0].ends_with(", today)"));
+        assert_eq!(
+            fs::read_to_string(wine_prefix.join("system.reg")).unwrap(),
+            "WINE REGISTRY Version 2\n;; snapshot\n"
+        );
+        assert!(prefix_repair::damaged_hives(&wine_prefix).is_empty());
+
+        match old_home {
+            Some(h) => std::env::set_var("HOME", h),
+            None => std::env::remove_var("HOME"),
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+    }
+
+    #[test]
+    fn test_saves_are_copied_into_snapshots() {
+        let _guard = TEST_MUTEX.lock().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let old_home = std::env::var("HOME").ok();
+        let old_data = std::env::var("XDG_DATA_HOME").ok();
+        let old_cache = std::env::var("XDG_CACHE_HOME").ok();
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var("XDG_DATA_HOME");
+        std::env::remove_var("XDG_CACHE_HOME");
+
+        let cache = crate::utils::http::cache_root().join("pcgamingwiki/620.json");
+        fs::create_dir_all(cache.parent().unwrap()).unwrap();
+        let paths = serde_json::json!({
+            "page": "Portal",
+            "paths": [{"kind": "Saves", "windows_path": "{{p|userprofile}}\\Saved Games\\Portal"}],
+        });
+        fs::write(&cache, paths.to_string()).unwrap();
+        let prefix = home.path().join("compatdata/620");
+        let saves = prefix.join("pfx/drive_c/users/steamuser/Saved Games/Portal");
+        let game = prefix.join("pfx/drive_c/Program Files/Portal");
+        fs::create_dir_all(&saves).unwrap();
+        fs::create_dir_all(&game).unwrap();
+        fs::write(saves.join("slot1.sav"), "level 3").unwrap();
+        fs::write(game.join("game.exe"), "MZ").unwrap();
+        let snapshot = take_with(&SnapshotConfig::default(), &prefix, "reset").unwrap().unwrap();
+
+        // Games rewrite their saves in place.
+        fs::write(saves.join("slot1.sav"), "level 4").unwrap();
+        let saved = snapshot.join("pfx/drive_c/users/steamuser/Saved Games/Portal/slot1.sav");
+        assert_eq!(fs::read_to_string(saved).unwrap(), "level 3");
+        assert_eq!(
+            fs::read_to_string(snapshot.join("pfx/drive_c/Program Files/Portal/game.exe")).unwrap(),
+            "MZ"
+        );
+
+        match old_home {
+            Some(h) => std::env::set_var("HOME", h),
+            None => std::env::remove_var("HOME"),
+        }
+        if let Some(d) = old_data {
+            std::env::set_var("XDG_DATA_HOME", d);
+        }
+        if let Some(c) = old_cache {
+            std::env::set_var("XDG_CACHE_HOME", c);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Operating system, kernel and GPU details for troubleshooting.
+//!
+//! GPUs and their drivers come from `vulkaninfo --summary`, which reports
+//! exactly the devices DXVK and VKD3D-Proton will see.
+
+use std::collections::HashMap;
+use std::fs;
+
+/// A Vulkan device as reported by `vulkaninfo --summary`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Gpu {
+    pub name: String,
+    /// e.g. `discrete gpu`, `integrated gpu` or `cpu` for llvmpipe.
+    pub device_type: String,
+    /// Highest Vulkan version the driver supports.
+    pub api_version: String,
+    pub driver_name: String,
+    /// Driver version, e.g. `Mesa 24.0.5` or `550.78`.
+    pub driver_info: String,
+}
+
+impl Gpu {
+    /// Software renderers like llvmpipe work but are far too slow for games.
+    pub fn is_software(&self) -> bool {
+        self.device_type == "cpu"
+    }
+}
+
+/// Parse the `Devices:` part of `vulkaninfo --summary`.
+pub fn parse_vulkan_summary(text: &str) -> Vec<Gpu> {
+    let mut gpus: Vec<Gpu> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("GPU") && line.ends_with(':') {
+            gpus.push(Gpu::default());
+            continue;
+        }
+        let (Some(gpu), Some((key, value))) = (gpus.last_mut(), line.split_once('=')) else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "deviceName" => gpu.name = value,
+            "deviceType" => {
+                gpu.device_type = value
+                    .trim_start_matches("PHYSICAL_DEVICE_TYPE_")
+                    .replace('_', " ")
+                    .to_lowercase()
+            }
+            "apiVersion" => gpu.api_version = value,
+            "driverName" => gpu.driver_name = value,
+            "driverInfo" => gpu.driver_info = value,
+            _ => {}
+        }
+    }
+    gpus
+}
+
+#[cfg(not(test))]
+fn vulkaninfo_summary() -> Option<String> {
+    let mut cmd = std::process::Command::new("vulkaninfo");
+    cmd.arg("--summary");
+    let output = crate::utils::sandbox::host_command(cmd).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+fn vulkaninfo_summary() -> Option<String> {
+    Some("Devices:\n========\nGPU0:\n\tapiVersion         = 1.3.278\n\tdeviceType         = PHYSICAL_DEVICE_TYPE_DISCRETE_GPU\n\tdeviceName         = AMD Radeon RX 6800 (RADV NAVI21)\n\tdriverName         = radv\n\tdriverInfo         = Mesa 24.0.5\n".into())
+}
+
+/// Vulkan devices of this machine, or `None` when `vulkaninfo` is missing
+/// or fails, which usually means no working Vulkan driver.
+pub fn vulkan_devices() -> Option<Vec<Gpu>> {
+    vulkaninfo_summary().map(|text| parse_vulkan_summary(&text))
+}
+
+/// Key-value pairs of `/etc/os-release`.
+pub fn os_release() -> HashMap<String, String> {
+    fs::read_to_string("/etc/os-release")
+        .or_else(|_| fs::read_to_string("/usr/lib/os-release"))
+        .map(|text| parse_os_release(&text))
+        .unwrap_or_default()
+}
+
+fn parse_os_release(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+        .collect()
+}
+
+/// Name of the distribution, e.g. `SteamOS` or `Fedora Linux 40`.
+pub fn os_name() -> Option<String> {
+    let release = os_release();
+    release
+        .get("PRETTY_NAME")
+        .or_else(|| release.get("NAME"))
+        .cloned()
+}
+
+/// Version of the running kernel.
+pub fn kernel_version() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|v| v.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_summary_and_os_release() {
+        let summary = "\
+==========
+VULKANINFO
+==========
+
+Devices:
+========
+GPU0:
+\tapiVersion         = 1.3.277
+\tdriverVersion      = 550.78.0.0
+\tdeviceType         = PHYSICAL_DEVICE_TYPE_DISCRETE_GPU
+\tdeviceName         = NVIDIA GeForce RTX 3070
+\tdriverName         = NVIDIA
+\tdriverInfo         = 550.78
+GPU1:
+\tapiVersion         = 1.3.278
+\tdeviceType         = PHYSICAL_DEVICE_TYPE_CPU
+\tdeviceName         = llvmpipe (LLVM 17.0.6, 256 bits)
+\tdriverName         = llvmpipe
+\tdriverInfo         = Mesa 24.0.5 (LLVM 17.0.6)
+";
+        let gpus = parse_vulkan_summary(summary);
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "NVIDIA GeForce RTX 3070");
+        assert_eq!(gpus[0].device_type, "discrete gpu");
+        assert_eq!(gpus[0].driver_info, "550.78");
+        assert!(!gpus[0].is_software());
+        assert!(gpus[1].is_software());
+        assert_eq!(vulkan_devices().unwrap()[0].driver_name, "radv");
+
+        let release = parse_os_release("NAME=\"SteamOS\"\nID=steamos\nID_LIKE=arch\n");
+        assert_eq!(release["NAME"], "SteamOS");
+        assert_eq!(release["ID_LIKE"], "arch");
+    }
+}
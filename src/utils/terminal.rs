@@ -2,6 +2,7 @@ use std::path::Path;
 use std::process::Command;
 
 use super::dependencies::command_available;
+use super::sandbox;
 
 /// Find a usable terminal emulator command.
 ///
@@ -33,9 +34,7 @@ pub fn open_terminal(path: &Path) -> std::io::Result<()> {
     let term = find_terminal()
         .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No terminal emulator found"))?;
 
-    Command::new(term)
-        .env("WINEPREFIX", path)
-        .current_dir(path)
-        .spawn()
-        .map(|_| ())
+    let mut cmd = Command::new(term);
+    cmd.env("WINEPREFIX", path).current_dir(path);
+    sandbox::host_command(cmd).spawn().map(|_| ())
 }
@@ -0,0 +1,99 @@
+//! Per-game vkBasalt configuration.
+//!
+//! vkBasalt is a Vulkan layer rather than a command, so it is detected
+//! through its layer manifest. Its config file is kept in the game's
+//! compatdata directory and removed together with the prefix.
+
+use crate::core::steam;
+use crate::error::Result;
+use crate::utils::app_config::VkBasaltSettings;
+use std::fs;
+use std::path::PathBuf;
+
+/// Whether a vkBasalt Vulkan layer manifest is installed.
+pub fn installed() -> bool {
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/vulkan/implicit_layer.d"),
+        PathBuf::from("/usr/local/share/vulkan/implicit_layer.d"),
+        PathBuf::from("/etc/vulkan/implicit_layer.d"),
+    ];
+    if let Some(data) = dirs_next::data_dir() {
+        dirs.push(data.join("vulkan/implicit_layer.d"));
+    }
+    dirs.iter().any(|dir| {
+        fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|e| e.file_name().to_string_lossy().starts_with("vkBasalt"))
+        })
+    })
+}
+
+/// Config file vkBasalt is pointed at through `VKBASALT_CONFIG_FILE`.
+pub fn config_file(appid: u32) -> Result<PathBuf> {
+    Ok(steam::compatdata_dir(appid)?.join("vkBasalt.conf"))
+}
+
+/// Render a vkBasalt config.
+pub fn render(settings: &VkBasaltSettings) -> String {
+    let effects: Vec<&str> = [
+        ("cas", settings.cas),
+        ("dls", settings.dls),
+        ("fxaa", settings.fxaa),
+        ("smaa", settings.smaa),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect();
+    let mut out = String::from("# Generated by proton-prefix-manager\n");
+    out.push_str(&format!("effects = {}\n", effects.join(":")));
+    if settings.cas {
+        out.push_str(&format!("casSharpness = {:.2}\n", settings.cas_sharpness));
+    }
+    if settings.dls {
+        out.push_str(&format!("dlsSharpness = {:.2}\n", settings.dls_sharpness));
+    }
+    if !settings.toggle_key.trim().is_empty() {
+        out.push_str(&format!("toggleKey = {}\n", settings.toggle_key.trim()));
+    }
+    out
+}
+
+/// Write or remove the config file of a game.
+pub fn write_config(appid: u32, settings: Option<&VkBasaltSettings>) -> Result<()> {
+    match settings {
+        Some(settings) => {
+            let path = config_file(appid)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, render(settings))?;
+        }
+        None => {
+            if let Ok(path) = config_file(appid) {
+                if path.exists() {
+                    fs::remove_file(&path)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let settings = VkBasaltSettings {
+            smaa: true,
+            ..Default::default()
+        };
+        let conf = render(&settings);
+        assert!(conf.contains("effects = cas:smaa\n"));
+        assert!(conf.contains("casSharpness = 0.40\n"));
+        assert!(!conf.contains("dlsSharpness"));
+        assert!(conf.contains("toggleKey = Home\n"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Windows version and architecture a Wine prefix is configured for.
+
+use crate::error::{Error, Result};
+use crate::utils::{proton_runtime, wine_reg};
+use std::fs;
+use std::path::Path;
+
+/// Windows versions `winecfg -v` accepts, newest first, with their names.
+pub const VERSIONS: [(&str, &str); 8] = [
+    ("win11", "Windows 11"),
+    ("win10", "Windows 10"),
+    ("win81", "Windows 8.1"),
+    ("win8", "Windows 8"),
+    ("win7", "Windows 7"),
+    ("vista", "Windows Vista"),
+    ("winxp64", "Windows XP (64-bit)"),
+    ("winxp", "Windows XP"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowsInfo {
+    /// Version as named by winecfg, such as `win10`.
+    pub version: String,
+    pub is_64bit: bool,
+}
+
+impl WindowsInfo {
+    pub fn label(&self) -> String {
+        label(&self.version)
+    }
+
+    pub fn arch(&self) -> &'static str {
+        if self.is_64bit {
+            "64-bit"
+        } else {
+            "32-bit"
+        }
+    }
+}
+
+/// Readable name of a Windows version id.
+pub fn label(version: &str) -> String {
+    let version = if version == "winvista" { "vista" } else { version };
+    VERSIONS
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(version))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| version.to_string())
+}
+
+/// Map the version information in `system.reg` to a Windows version.
+pub fn detect_windows_version(system_reg: &str) -> &'static str {
+    let values = wine_reg::read_values(system_reg, r"Software\Microsoft\Windows NT\CurrentVersion");
+    let build = values
+        .get("CurrentBuildNumber")
+        .or_else(|| values.get("CurrentBuild"))
+        .and_then(|b| b.parse::<u32>().ok())
+        .unwrap_or(0);
+    if build >= 22000 {
+        return "win11";
+    }
+    if build >= 10240 {
+        return "win10";
+    }
+    match values.get("CurrentVersion").map(String::as_str) {
+        Some("6.3") => "win81",
+        Some("6.2") => "win8",
+        Some("6.1") => "win7",
+        Some("6.0") => "winvista",
+        Some("5.2") => "win2003",
+        Some("5.1") => "winxp",
+        _ => "win10",
+    }
+}
+
+/// Whether `system.reg` belongs to a 64-bit prefix. Wine records the
+/// architecture in the `#arch=` line of the header.
+pub fn is_64bit(system_reg: &str) -> bool {
+    system_reg
+        .lines()
+        .take_while(|l| !l.starts_with('['))
+        .find_map(|l| l.strip_prefix("#arch="))
+        .is_none_or(|arch| arch.trim() == "win64")
+}
+
+/// Read the Windows version and architecture of a Wine prefix (the `pfx`
+/// directory). A version set with winecfg in `user.reg` takes precedence
+/// over the one in `system.reg`, as it does for Wine.
+pub fn detect(pfx: &Path) -> Option<WindowsInfo> {
+    let system_reg = fs::read_to_string(pfx.join("system.reg")).ok()?;
+    let user_reg = fs::read_to_string(pfx.join("user.reg")).unwrap_or_default();
+    let version = wine_reg::read_values(&user_reg, r"Software\Wine")
+        .remove("Version")
+        .unwrap_or_else(|| detect_windows_version(&system_reg).to_string());
+    Some(WindowsInfo {
+        version: version.to_lowercase(),
+        is_64bit: is_64bit(&system_reg),
+    })
+}
+
+/// Change the Windows version of a game's prefix with `winecfg -v`, run by
+/// the Wine of the game's Proton build so the registry is written the way
+/// that Wine expects.
+pub fn set_version(appid: u32, version: &str) -> Result<()> {
+    if !VERSIONS.iter().any(|(id, _)| *id == version) {
+        return Err(Error::FileSystemError(format!(
+            "Unknown Windows version: {}",
+            version
+        )));
+    }
+    let output = proton_runtime::wine_tool(appid, "winecfg")?
+        .args(["-v", version])
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::FileSystemError(format!(
+            "winecfg exited with status {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_windows_version() {
+        let reg = |build: &str, version: &str| {
+            format!(
+                "[Software\\\\Microsoft\\\\Windows NT\\\\CurrentVersion] 1700000000\n\"CurrentBuildNumber\"=\"{}\"\n\"CurrentVersion\"=\"{}\"\n",
+                build, version
+            )
+        };
+        assert_eq!(detect_windows_version(&reg("19043", "6.3")), "win10");
+        assert_eq!(detect_windows_version(&reg("22000", "6.3")), "win11");
+        assert_eq!(detect_windows_version(&reg("7601", "6.1")), "win7");
+        assert_eq!(detect_windows_version(""), "win10");
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("system.reg"),
+            format!("WINE REGISTRY Version 2\n;; All keys relative to \\\\Machine\n\n#arch=win32\n\n{}", reg("7601", "6.1")),
+        )
+        .unwrap();
+        let info = detect(dir.path()).unwrap();
+        assert_eq!(info.version, "win7");
+        assert!(!info.is_64bit);
+        assert_eq!(info.label(), "Windows 7");
+
+        fs::write(
+            dir.path().join("user.reg"),
+            "WINE REGISTRY Version 2\n\n[Software\\\\Wine] 1700000000\n#time=1da\n\"Version\"=\"winxp\"\n",
+        )
+        .unwrap();
+        assert_eq!(detect(dir.path()).unwrap().version, "winxp");
+    }
+}
@@ -0,0 +1,112 @@
+//! Minimal reader and writer for Wine registry files (`user.reg`,
+//! `system.reg`).
+//!
+//! Only plain string values (`"name"="value"`) are handled, which covers
+//! DLL overrides and version information.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Section header for a registry key such as `Software\Wine\DllOverrides`.
+/// Wine escapes the backslashes in key names.
+fn section_header(key: &str) -> String {
+    format!("[{}]", key.replace('\\', "\\\\"))
+}
+
+fn is_section(line: &str, header: &str) -> bool {
+    line.to_lowercase().starts_with(&header.to_lowercase())
+}
+
+fn parse_entry(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((key.to_string(), value.to_string()))
+}
+
+/// Read the string values stored under `key`.
+pub fn read_values(contents: &str, key: &str) -> BTreeMap<String, String> {
+    let header = section_header(key);
+    contents
+        .lines()
+        .skip_while(|l| !is_section(l, &header))
+        .skip(1)
+        .take_while(|l| !l.is_empty() && !l.starts_with('['))
+        .filter_map(parse_entry)
+        .collect()
+}
+
+/// Read the string values of every direct subkey of `key`, by subkey name.
+pub fn read_subkeys(contents: &str, key: &str) -> BTreeMap<String, BTreeMap<String, String>> {
+    let parent = format!("{}\\", key.to_ascii_lowercase());
+    let mut subkeys = BTreeMap::new();
+    let mut current: Option<(String, BTreeMap<String, String>)> = None;
+    for line in contents.lines() {
+        if line.starts_with('[') {
+            if let Some((name, values)) = current.take() {
+                subkeys.insert(name, values);
+            }
+            let Some(end) = line.rfind(']') else {
+                continue;
+            };
+            let name = line[1..end].replace("\\\\", "\\");
+            current = name
+                .to_ascii_lowercase()
+                .strip_prefix(&parent)
+                .filter(|rest| !rest.is_empty() && !rest.contains('\\'))
+                .map(|_| (name[parent.len()..].to_string(), BTreeMap::new()));
+        } else if let Some((_, values)) = &mut current {
+            if let Some((k, v)) = parse_entry(line) {
+                values.insert(k, v);
+            }
+        }
+    }
+    if let Some((name, values)) = current {
+        subkeys.insert(name, values);
+    }
+    subkeys
+}
+
+/// Undo the escaping of backslashes and quotes in a string value.
+pub fn unescape(value: &str) -> String {
+    value.replace("\\\\", "\\").replace("\\\"", "\"")
+}
+
+/// Add or replace string values under `key`, creating the key if needed.
+pub fn write_values(contents: &str, key: &str, values: &BTreeMap<String, String>) -> String {
+    let header = section_header(key);
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let start = match lines.iter().position(|l| is_section(l, &header)) {
+        Some(idx) => idx,
+        None => {
+            if lines.last().is_some_and(|l| !l.is_empty()) {
+                lines.push(String::new());
+            }
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            lines.push(format!("{} {}", header, now));
+            lines.len() - 1
+        }
+    };
+
+    for (name, value) in values {
+        let entry = format!("\"{}\"=\"{}\"", name, value);
+        let mut end = start + 1;
+        while end < lines.len() && !lines[end].is_empty() && !lines[end].starts_with('[') {
+            end += 1;
+        }
+        let existing = (start + 1..end).find(|&i| {
+            parse_entry(&lines[i]).is_some_and(|(k, _)| k.eq_ignore_ascii_case(name))
+        });
+        match existing {
+            Some(i) => lines[i] = entry,
+            None => lines.insert(end, entry),
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}